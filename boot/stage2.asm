@@ -1,6 +1,18 @@
-; boot/stage2.asm  -  PORTIX Stage-2  v9.3
+; boot/stage2.asm  -  PORTIX Stage-2  v9.5
 ; nasm -f bin -DKERNEL_SECTORS=N [-DKERNEL_LBA=N] stage2.asm -o stage2.bin
 ;
+; CAMBIOS v9.5:
+;
+;   [E820-MAX]       Las entradas E820 ocupan 24 bytes: el bucle se corta en
+;                    E820_MAX para no pisar el bloque EDID de BINFO_EDID.
+;
+; CAMBIOS v9.4:
+;
+;   [EDID]           Tras el modo VESA se lee el bloque EDID base (128 bytes)
+;                    con VBE/DDC (INT 10h AX=4F15h) en BINFO_EDID y se pone
+;                    el bit 2 de BINFO_FLAGS si la BIOS respondió 004Fh. La
+;                    cabecera y el checksum los valida el kernel.
+;
 ; CORRECCIONES vs v9.2 — EL BUG REAL DEL 0x01 EN ISO:
 ;
 ;   [FIX-IDE-ORDER]  El bloque "Deshabilitar IRQ IDE" (OUT 0x3F6/0x376) se
@@ -52,6 +64,8 @@ BINFO_HEIGHT  equ BINFO_BASE + 0x0A
 BINFO_PITCH   equ BINFO_BASE + 0x0C
 BINFO_BPP     equ BINFO_BASE + 0x0E
 BINFO_E820    equ 0x9100
+BINFO_EDID    equ 0x9C00          ; tras la tabla E820 (0x9100 + 117*24 < 0x9C00)
+E820_MAX      equ (BINFO_EDID - BINFO_E820) / 24
 
 PML4_ADDR     equ 0x1000
 PDPT_ADDR     equ 0x2000
@@ -155,6 +169,8 @@ start2:
     jz   .e820_next
     add  di, 24
     inc  bp
+    cmp  bp, E820_MAX
+    jae  .e820_done
 .e820_next:
     test ebx, ebx
     jnz  .e820_loop
@@ -296,6 +312,33 @@ start2:
     and  word [BINFO_FLAGS], ~0x0001
 .vesa_done:
 
+    ; ── 5b. EDID (VBE/DDC) ────────────────────────────────────────────────
+    ; BL=0: ¿hay DDC?  BL=1: leer el bloque 0 del monitor en ES:DI.
+    xor  ax, ax
+    mov  es, ax
+    mov  ax, 0x4F15
+    xor  bx, bx
+    xor  cx, cx
+    xor  di, di
+    int  0x10
+    cmp  ax, 0x004F
+    jne  .edid_done
+    xor  ax, ax
+    mov  es, ax
+    mov  ax, 0x4F15
+    mov  bx, 0x0001
+    xor  cx, cx
+    xor  dx, dx
+    mov  di, BINFO_EDID
+    int  0x10
+    cmp  ax, 0x004F
+    jne  .edid_done
+    or   word [BINFO_FLAGS], 0x0004
+.edid_done:
+    xor  ax, ax
+    mov  ds, ax
+    mov  es, ax
+
     ; ── 6. Paginación ─────────────────────────────────────────────────────
     mov  edi, PML4_ADDR
     xor  eax, eax
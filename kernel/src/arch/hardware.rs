@@ -1,5 +1,6 @@
 // kernel/src/hardware.rs - PORTIX Hardware Detection Layer
 // Detección universal: CPU (CPUID), Discos (ATA IDENTIFY), RAM (E820), Display
+// e hipervisor (CPUID 1 ECX.31 + firmas en las hojas 0x4000_0000+)
 #![allow(dead_code)]
 
 // ── Port I/O helpers ──────────────────────────────────────────────────────────
@@ -54,6 +55,8 @@ pub struct CpuInfo {
     pub physical_cores: u8,
     pub base_mhz:      u32,       // MHz
     pub max_mhz:       u32,       // MHz
+    pub tsc_mhz:       u32,       // MHz medidos por time::tsc::calibrate (0 = sin TSC)
+    pub tsc_invariant: bool,
     pub has_sse2:      bool,
     pub has_sse4:      bool,
     pub has_avx:       bool,
@@ -61,6 +64,7 @@ pub struct CpuInfo {
     pub has_aes:       bool,
     pub max_leaf:      u32,
     pub max_ext_leaf:  u32,
+    pub hypervisor:    HypervisorInfo,
 }
 
 impl CpuInfo {
@@ -72,6 +76,8 @@ impl CpuInfo {
             physical_cores: 1,
             base_mhz:  0,
             max_mhz:   0,
+            tsc_mhz:   crate::time::tsc::mhz(),
+            tsc_invariant: crate::time::tsc::invariant(),
             has_sse2:  false,
             has_sse4:  false,
             has_avx:   false,
@@ -79,6 +85,7 @@ impl CpuInfo {
             has_aes:   false,
             max_leaf:      0,
             max_ext_leaf:  0,
+            hypervisor:    HypervisorInfo::NONE,
         };
 
         unsafe {
@@ -176,6 +183,7 @@ impl CpuInfo {
             }
         }
 
+        info.hypervisor = HypervisorInfo::detect(info.max_leaf, &info.brand);
         info
     }
 
@@ -200,6 +208,127 @@ impl CpuInfo {
     }
 }
 
+// ── Hipervisor ───────────────────────────────────────────────────────────────
+// CPUID 1 ECX bit 31 está reservado (a 0) en hardware real y los
+// hipervisores lo ponen a 1. Su firma son 12 bytes EBX:ECX:EDX de la hoja
+// 0x4000_0000; quien además emula la interfaz de Hyper-V (KVM con
+// "enlightenments", VirtualBox) pone "Microsoft Hv" ahí y su propia firma en
+// una base superior (0x4000_0100, ...), así que se recorren varias bases.
+//
+// Un hipervisor puede ocultarse (KVM `-cpu host,kvm=off`, VMware
+// `hypervisor.cpuid.v0 = FALSE`): sin bit ni firma conocida no hay forma
+// fiable de distinguirlo del hardware real y se informa "bare metal".
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Hypervisor {
+    BareMetal,
+    Kvm,
+    Qemu,
+    VMware,
+    VirtualBox,
+    HyperV,
+    Xen,
+    Parallels,
+    Bhyve,
+    /// Bit de hipervisor activo pero firma desconocida o vacía.
+    Unknown,
+}
+
+const HV_SIGNATURES: [(&[u8; 12], Hypervisor); 8] = [
+    (b"KVMKVMKVM\0\0\0", Hypervisor::Kvm),
+    (b"TCGTCGTCGTCG",    Hypervisor::Qemu),
+    (b"VMwareVMware",    Hypervisor::VMware),
+    (b"VBoxVBoxVBox",    Hypervisor::VirtualBox),
+    (b"Microsoft Hv",    Hypervisor::HyperV),
+    (b"XenVMMXenVMM",    Hypervisor::Xen),
+    (b" lrpepyh  vr",    Hypervisor::Parallels),
+    (b"bhyve bhyve ",    Hypervisor::Bhyve),
+];
+
+/// Bases de hoja exploradas: 0x4000_0000, 0x4000_0100, ... (16 en total).
+const HV_LEAF_BASES: u32 = 16;
+
+#[derive(Clone, Copy)]
+pub struct HypervisorInfo {
+    pub kind:        Hypervisor,
+    /// CPUID 1 ECX bit 31.
+    pub present_bit: bool,
+    /// Firma tal cual (EBX:ECX:EDX) de la hoja en `leaf`.
+    pub signature:   [u8; 12],
+    pub leaf:        u32,
+    /// Hoja máxima del hipervisor (EAX de `leaf`).
+    pub max_leaf:    u32,
+}
+
+impl HypervisorInfo {
+    pub const NONE: HypervisorInfo = HypervisorInfo {
+        kind: Hypervisor::BareMetal, present_bit: false, signature: [0; 12], leaf: 0, max_leaf: 0,
+    };
+
+    fn detect(max_std_leaf: u32, brand: &[u8; 49]) -> Self {
+        let mut hv = Self::NONE;
+        hv.present_bit = max_std_leaf >= 1 && unsafe { cpuid(1, 0) }.ecx & (1 << 31) != 0;
+
+        // Sin el bit, en hardware real las hojas 0x4000_0000+ devuelven lo de
+        // la hoja básica más alta: solo cuenta una firma conocida
+        for i in 0..HV_LEAF_BASES {
+            let leaf = 0x4000_0000 + i * 0x100;
+            let r = unsafe { cpuid(leaf, 0) };
+            let mut sig = [0u8; 12];
+            sig[0..4].copy_from_slice(&r.ebx.to_le_bytes());
+            sig[4..8].copy_from_slice(&r.ecx.to_le_bytes());
+            sig[8..12].copy_from_slice(&r.edx.to_le_bytes());
+            let Some(&(_, kind)) = HV_SIGNATURES.iter().find(|(s, _)| **s == sig) else {
+                if i == 0 && hv.present_bit { hv.signature = sig; hv.leaf = leaf; hv.max_leaf = r.eax; }
+                continue;
+            };
+            // "Microsoft Hv" puede ser solo la interfaz emulada: seguir buscando
+            if hv.kind == Hypervisor::BareMetal || hv.kind == Hypervisor::HyperV {
+                hv.kind = kind; hv.signature = sig; hv.leaf = leaf; hv.max_leaf = r.eax;
+            }
+            if kind != Hypervisor::HyperV { break; }
+        }
+
+        if hv.present_bit && hv.kind == Hypervisor::BareMetal {
+            // VirtualBox "legacy" y algunos QEMU no publican firma
+            let b = &brand[..];
+            hv.kind = if b.windows(4).any(|w| w == b"QEMU") { Hypervisor::Qemu } else { Hypervisor::Unknown };
+        }
+        hv
+    }
+
+    pub fn is_virtual(&self) -> bool { self.kind != Hypervisor::BareMetal }
+
+    /// Hipervisor identificado por su firma aunque el bit de CPUID esté a 0.
+    pub fn is_hidden(&self) -> bool { self.is_virtual() && !self.present_bit }
+
+    pub fn name(&self) -> &'static str {
+        match self.kind {
+            Hypervisor::BareMetal  => "bare metal",
+            Hypervisor::Kvm        => "KVM",
+            Hypervisor::Qemu       => "QEMU",
+            Hypervisor::VMware     => "VMware",
+            Hypervisor::VirtualBox => "VirtualBox",
+            Hypervisor::HyperV     => "Hyper-V",
+            Hypervisor::Xen        => "Xen",
+            Hypervisor::Parallels  => "Parallels",
+            Hypervisor::Bhyve      => "bhyve",
+            Hypervisor::Unknown    => "hipervisor desconocido",
+        }
+    }
+
+    /// Firma imprimible (sin NUL ni espacios finales; vacía si no hay).
+    pub fn signature_str(&self) -> &str {
+        let end = self.signature.iter().rposition(|&b| b != 0 && b != b' ').map_or(0, |i| i + 1);
+        let s = &self.signature[..end];
+        if s.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+            core::str::from_utf8(s).unwrap_or("")
+        } else {
+            ""
+        }
+    }
+}
+
 /// Parse "3.70GHz" or "4.30GHz" or "3600MHz" from brand string → MHz
 fn parse_freq_from_brand(brand: &[u8; 49]) -> u32 {
     // Find "GHz" or "MHz" in brand string
@@ -256,8 +385,9 @@ fn parse_u32_str(s: &str) -> u32 {
 }
 
 // ── Disk Info (ATA IDENTIFY) ──────────────────────────────────────────────────
-// Soporta hasta 4 unidades: Primary Master/Slave, Secondary Master/Slave
-pub const MAX_DISKS: usize = 4;
+// Hasta 4 unidades ATA legacy (Primary/Secondary, Master/Slave) más las que
+// encuentre el driver AHCI (drivers::storage::ahci) en el espacio restante.
+pub const MAX_DISKS: usize = 8;
 
 #[derive(Clone, Copy)]
 pub struct DiskInfo {
@@ -265,10 +395,12 @@ pub struct DiskInfo {
     pub is_atapi: bool,        // CD-ROM / optical
     pub model:    [u8; 41],    // 40 char model + \0
     pub serial:   [u8; 21],    // 20 char serial + \0
+    pub sectors:  u64,         // sectores de 512 B (IDENTIFY)
     pub size_mb:  u64,         // MiB
     pub lba48:    bool,
-    pub bus:      u8,          // 0=Primary, 1=Secondary
-    pub drive:    u8,          // 0=Master, 1=Slave
+    pub bus:      u8,          // 0=Primary, 1=Secondary (AHCI: nº de puerto)
+    pub drive:    u8,          // 0=Master, 1=Slave (AHCI: 0)
+    pub ahci:     bool,        // detectado por el controlador SATA AHCI
 }
 
 impl DiskInfo {
@@ -278,12 +410,89 @@ impl DiskInfo {
             is_atapi: false,
             model:    [0u8; 41],
             serial:   [0u8; 21],
+            sectors:  0,
             size_mb:  0,
             lba48:    false,
             bus:      0,
             drive:    0,
+            ahci:     false,
         }
     }
+
+    /// Rellena modelo, serie y capacidad desde los 256 words de IDENTIFY
+    /// (o IDENTIFY PACKET si `is_atapi`). Lo comparten ATA PIO y AHCI.
+    pub fn from_identify(buf: &[u16; 256], is_atapi: bool) -> Self {
+        let mut d = DiskInfo::empty();
+        d.present  = true;
+        d.is_atapi = is_atapi;
+
+        // Model: words 27-46, big-endian byte pairs
+        for i in 0..20usize {
+            let w = buf[27 + i];
+            d.model[i*2]     = (w >> 8) as u8;
+            d.model[i*2 + 1] = (w & 0xFF) as u8;
+        }
+        d.model[40] = 0;
+        // Trim trailing spaces
+        let mut end = 40usize;
+        while end > 0 && (d.model[end-1] == b' ' || d.model[end-1] == 0) { end -= 1; }
+        d.model[end] = 0;
+
+        // Serial: words 10-19
+        for i in 0..10usize {
+            let w = buf[10 + i];
+            d.serial[i*2]     = (w >> 8) as u8;
+            d.serial[i*2 + 1] = (w & 0xFF) as u8;
+        }
+        d.serial[20] = 0;
+        let mut end = 20usize;
+        while end > 0 && (d.serial[end-1] == b' ' || d.serial[end-1] == 0) { end -= 1; }
+        d.serial[end] = 0;
+
+        // Tamaño: directamente del IDENTIFY (LBA48 words 100-103 si el disco lo
+        // reporta, si no LBA28 words 60-61) × 512 B/sector. En u64: un disco de
+        // >2 TB no cabe en un u32 de sectores ni, a partir de 4 PiB, de MiB.
+        if !is_atapi {
+            let (sectors, lba48) = crate::drivers::storage::ata::identify_capacity(buf);
+            d.sectors = sectors;
+            d.lba48   = lba48;
+            d.size_mb = sectors * 512 / (1024 * 1024);
+        }
+        d
+    }
+
+    /// Handle de E/S PIO para esta unidad, sin re-escanear el bus (un
+    /// segundo reset del canal lo deja inservible en QEMU/VirtualBox).
+    /// None para ATAPI y para discos AHCI, que aún no tienen E/S propia.
+    pub fn ata_drive(&self) -> Option<crate::drivers::storage::ata::AtaDrive> {
+        use crate::drivers::storage::ata::{AtaDrive, DriveId, DriveInfo, DriveType};
+        if !self.present || self.is_atapi || self.ahci { return None; }
+        let id = match (self.bus, self.drive) {
+            (0, 0) => DriveId::Primary0,
+            (0, _) => DriveId::Primary1,
+            (_, 0) => DriveId::Secondary0,
+            _      => DriveId::Secondary1,
+        };
+        let mut info = DriveInfo {
+            id, kind: DriveType::Ata,
+            total_sectors: self.sectors, capacity_mib: self.size_mb, lba48: self.lba48,
+            model: [b' '; 40], firmware: [b' '; 8], serial: [b' '; 20],
+        };
+        info.model.copy_from_slice(&self.model[..40]);
+        info.serial.copy_from_slice(&self.serial[..20]);
+        Some(AtaDrive::from_info(info))
+    }
+
+    /// Etiqueta del bus: "ATA0"/"ATA1" o "SATA<puerto>" para AHCI.
+    pub fn bus_label<'a>(&self, out: &'a mut [u8; 8]) -> &'a str {
+        let prefix: &[u8] = if self.ahci { b"SATA" } else { b"ATA" };
+        let mut n = prefix.len();
+        out[..n].copy_from_slice(prefix);
+        if self.bus >= 10 { out[n] = b'0' + self.bus / 10; n += 1; }
+        out[n] = b'0' + self.bus % 10; n += 1;
+        core::str::from_utf8(&out[..n]).unwrap_or("?")
+    }
+
     pub fn model_str(&self) -> &str {
         let end = self.model.iter().position(|&b| b == 0).unwrap_or(40);
         core::str::from_utf8(&self.model[..end]).unwrap_or("Unknown")
@@ -388,48 +597,7 @@ unsafe fn ata_identify(base: u16, ctrl: u16, drive: u8) -> Option<DiskInfo> {
         buf[i] = inw(base + ATA_REG_DATA);
     }
 
-    let mut d = DiskInfo::empty();
-    d.present  = true;
-    d.is_atapi = is_atapi;
-
-    // Model: words 27-46, big-endian byte pairs
-    for i in 0..20usize {
-        let w = buf[27 + i];
-        d.model[i*2]     = (w >> 8) as u8;
-        d.model[i*2 + 1] = (w & 0xFF) as u8;
-    }
-    d.model[40] = 0;
-    // Trim trailing spaces
-    let mut end = 40usize;
-    while end > 0 && (d.model[end-1] == b' ' || d.model[end-1] == 0) { end -= 1; }
-    d.model[end] = 0;
-
-    // Serial: words 10-19
-    for i in 0..10usize {
-        let w = buf[10 + i];
-        d.serial[i*2]     = (w >> 8) as u8;
-        d.serial[i*2 + 1] = (w & 0xFF) as u8;
-    }
-    d.serial[20] = 0;
-    let mut end = 20usize;
-    while end > 0 && (d.serial[end-1] == b' ' || d.serial[end-1] == 0) { end -= 1; }
-    d.serial[end] = 0;
-
-    // Size: LBA48 (words 100-103) if supported, else LBA28 (words 60-61)
-    let support_lba48 = (buf[83] >> 10) & 1 != 0;
-    if support_lba48 && !is_atapi {
-        d.lba48 = true;
-        let sectors = (buf[100] as u64)
-            | ((buf[101] as u64) << 16)
-            | ((buf[102] as u64) << 32)
-            | ((buf[103] as u64) << 48);
-        d.size_mb = sectors / 2048; // 512-byte sectors → MiB
-    } else if !is_atapi {
-        let sectors = (buf[60] as u64) | ((buf[61] as u64) << 16);
-        d.size_mb = sectors / 2048;
-    }
-
-    Some(d)
+    Some(DiskInfo::from_identify(&buf, is_atapi))
 }
 
 pub struct Disks {
@@ -439,6 +607,12 @@ pub struct Disks {
 
 impl Disks {
     pub fn detect() -> Self {
+        Self::detect_with(|_, _| {})
+    }
+
+    /// Como `detect`, avisando con `on_probe(bus, drive)` antes de cada
+    /// IDENTIFY (cada uno puede agotar su timeout si no hay unidad).
+    pub fn detect_with<F: FnMut(u8, u8)>(mut on_probe: F) -> Self {
         let mut disks = Disks {
             drives: [DiskInfo::empty(); MAX_DISKS],
             count: 0,
@@ -453,6 +627,7 @@ impl Disks {
         ];
 
         for (i, &(base, ctrl, bus, drv)) in controllers.iter().enumerate() {
+            on_probe(bus, drv);
             if let Some(mut d) = unsafe { ata_identify(base, ctrl, drv) } {
                 d.bus   = bus;
                 d.drive = drv;
@@ -464,6 +639,38 @@ impl Disks {
 
         disks
     }
+
+    /// Sustituye las unidades ATA PIO del canal `bus` por las encontradas tras
+    /// un reset (`atareset`): `ident` son los words de IDENTIFY de master y
+    /// slave. Se conserva el orden ATA0, ATA1 y después AHCI.
+    pub fn replace_ata_channel(&mut self, bus: u8, ident: &[Option<[u16; 256]>; 2]) {
+        let old = core::mem::replace(self, Disks { drives: [DiskInfo::empty(); MAX_DISKS], count: 0 });
+        let mut fresh = ident.iter().enumerate().filter_map(|(drv, w)| {
+            let w = w.as_ref()?;
+            let mut d = DiskInfo::from_identify(w, w[0] & 0x8000 != 0);
+            d.bus   = bus;
+            d.drive = drv as u8;
+            Some(d)
+        });
+        let mut inserted = false;
+        for d in &old.drives[..old.count] {
+            if !d.ahci && d.bus == bus { continue; }
+            if !inserted && (d.ahci || d.bus > bus) {
+                for n in fresh.by_ref() { self.push(n); }
+                inserted = true;
+            }
+            self.push(*d);
+        }
+        for n in fresh { self.push(n); }
+    }
+
+    /// Añade una unidad encontrada por otro driver (AHCI). false si no cabe.
+    pub fn push(&mut self, d: DiskInfo) -> bool {
+        if self.count >= MAX_DISKS { return false; }
+        self.drives[self.count] = d;
+        self.count += 1;
+        true
+    }
 }
 
 // ── RAM Info (from E820 table written by stage2) ──────────────────────────────
@@ -476,21 +683,14 @@ pub struct RamInfo {
 impl RamInfo {
     pub fn detect() -> Self {
         let mut info = RamInfo { usable_mb: 0, total_mb: 0, entry_count: 0 };
-        unsafe {
-            let count = core::ptr::read_volatile(0x9100 as *const u16);
-            info.entry_count = count;
-            let cap = count.min(128) as usize;
-            for i in 0..cap {
-                let p = (0x9102usize + i * 20) as *const u8;
-                let len  = core::ptr::read_unaligned(p.add(8) as *const u64);
-                let kind = core::ptr::read_unaligned(p.add(16) as *const u32);
-                let mb = len / (1024 * 1024);
-                // Only count actual physical RAM; skip MMIO/reserved (fixes 12GB false total)
-                match kind {
-                    1 => { info.usable_mb += mb; info.total_mb += mb; }
-                    3 => { info.total_mb += mb; }  // ACPI reclaimable
-                    _ => {}
-                }
+        info.entry_count = crate::mem::e820_count();
+        for (_, len, kind) in crate::mem::e820_entries() {
+            let mb = len / (1024 * 1024);
+            // Only count actual physical RAM; skip MMIO/reserved (fixes 12GB false total)
+            match kind {
+                1 => { info.usable_mb += mb; info.total_mb += mb; }
+                3 => { info.total_mb += mb; }  // ACPI reclaimable
+                _ => {}
             }
         }
         info
@@ -508,6 +708,7 @@ pub struct DisplayInfo {
     pub height:   u16,
     pub pitch:    u16,
     pub bpp:      u8,
+    pub edid:     Result<Edid, EdidError>,
 }
 
 impl DisplayInfo {
@@ -519,6 +720,7 @@ impl DisplayInfo {
                 height:   core::ptr::read_volatile(0x900A as *const u16),
                 pitch:    core::ptr::read_volatile(0x900C as *const u16),
                 bpp:      core::ptr::read_volatile(0x900E as *const u8),
+                edid:     Edid::from_boot(),
             }
         }
     }
@@ -528,6 +730,151 @@ impl DisplayInfo {
     }
 }
 
+// ── EDID del monitor ─────────────────────────────────────────────────────────
+// Stage2 lee el bloque base (128 bytes) con VBE/DDC en 0x9C00 y pone el bit 2
+// de las flags de arranque (0x9002) si la BIOS respondió. Aquí se comprueban
+// la cabecera fija y el checksum (suma de los 128 bytes ≡ 0 mod 256) antes de
+// fiarse de ningún campo.
+
+const EDID_ADDR:   usize = 0x9C00;
+const EDID_FLAG:   u16   = 0x0004;
+const EDID_HEADER: [u8; 8] = [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00];
+/// Modos anunciados que se guardan (descriptores de timing + estándar).
+pub const EDID_MAX_MODES: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EdidError {
+    /// La BIOS no tiene DDC o no contestó (sin monitor, VM sin EDID...).
+    Absent,
+    BadHeader,
+    BadChecksum,
+}
+
+impl EdidError {
+    pub fn reason(self) -> &'static str {
+        match self {
+            EdidError::Absent      => "la BIOS no expone EDID",
+            EdidError::BadHeader   => "cabecera EDID invalida",
+            EdidError::BadChecksum => "checksum EDID invalido",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct EdidMode {
+    pub width:  u16,
+    pub height: u16,
+    /// Hz (0 si no se puede calcular).
+    pub refresh: u8,
+}
+
+#[derive(Clone, Copy)]
+pub struct Edid {
+    /// Código PNP de 3 letras ("DEL", "SAM", "RHT"...).
+    pub manufacturer: [u8; 3],
+    pub product:      u16,
+    pub year:         u16,
+    pub version:      u8,
+    pub revision:     u8,
+    /// Descriptor 0xFC; vacío si el monitor no lo incluye.
+    pub name:         [u8; 13],
+    pub name_len:     usize,
+    /// Primer descriptor de timing = resolución preferida (nativa).
+    pub native:       Option<EdidMode>,
+    pub modes:        [EdidMode; EDID_MAX_MODES],
+    pub mode_count:   usize,
+}
+
+impl Edid {
+    fn from_boot() -> Result<Self, EdidError> {
+        let flags = unsafe { core::ptr::read_volatile(0x9002 as *const u16) };
+        if flags & EDID_FLAG == 0 { return Err(EdidError::Absent); }
+        let mut raw = [0u8; 128];
+        for (i, b) in raw.iter_mut().enumerate() {
+            *b = unsafe { core::ptr::read_volatile((EDID_ADDR + i) as *const u8) };
+        }
+        Self::parse(&raw)
+    }
+
+    pub fn parse(raw: &[u8; 128]) -> Result<Self, EdidError> {
+        if raw[..8] != EDID_HEADER { return Err(EdidError::BadHeader); }
+        if raw.iter().fold(0u8, |s, &b| s.wrapping_add(b)) != 0 {
+            return Err(EdidError::BadChecksum);
+        }
+
+        // Fabricante: 3 letras de 5 bits (1 = 'A') en big-endian
+        let id = u16::from_be_bytes([raw[8], raw[9]]);
+        let letter = |v: u16| match (v & 0x1F) as u8 { n @ 1..=26 => b'A' + n - 1, _ => b'?' };
+        let mut e = Edid {
+            manufacturer: [letter(id >> 10), letter(id >> 5), letter(id)],
+            product:      u16::from_le_bytes([raw[10], raw[11]]),
+            year:         if raw[17] == 0 { 0 } else { 1990 + raw[17] as u16 },
+            version:      raw[18],
+            revision:     raw[19],
+            name:         [0; 13],
+            name_len:     0,
+            native:       None,
+            modes:        [EdidMode { width: 0, height: 0, refresh: 0 }; EDID_MAX_MODES],
+            mode_count:   0,
+        };
+
+        // Cuatro descriptores de 18 bytes: timing si el reloj ≠ 0
+        for d in raw[54..126].as_chunks::<18>().0 {
+            let clock = u16::from_le_bytes([d[0], d[1]]) as u64;
+            if clock != 0 {
+                let ha = d[2] as u64 | ((d[4] as u64 & 0xF0) << 4);
+                let hb = d[3] as u64 | ((d[4] as u64 & 0x0F) << 8);
+                let va = d[5] as u64 | ((d[7] as u64 & 0xF0) << 4);
+                let vb = d[6] as u64 | ((d[7] as u64 & 0x0F) << 8);
+                let total = (ha + hb) * (va + vb);
+                // Reloj en unidades de 10 kHz
+                let hz = (clock * 10_000 + total / 2).checked_div(total).unwrap_or(0);
+                let m = EdidMode { width: ha as u16, height: va as u16, refresh: hz.min(255) as u8 };
+                if e.native.is_none() { e.native = Some(m); }
+                e.push_mode(m);
+            } else if d[3] == 0xFC {
+                let text = &d[5..18];
+                let end = text.iter().position(|&b| b == 0x0A).unwrap_or(text.len());
+                let text = &text[..end];
+                let end = text.iter().rposition(|&b| b != b' ').map_or(0, |i| i + 1);
+                for (i, &b) in text[..end].iter().enumerate() {
+                    e.name[i] = if b.is_ascii_graphic() || b == b' ' { b } else { b'?' };
+                }
+                e.name_len = end;
+            }
+        }
+
+        // Timings estándar: 8 entradas de 2 bytes, 0x0101 = sin usar
+        for s in raw[38..54].as_chunks::<2>().0 {
+            if s[0] <= 1 { continue; }
+            let w = (s[0] as u32 + 31) * 8;
+            let h = match s[1] >> 6 {
+                // 16:10 desde EDID 1.3; antes 1:1
+                0 => if e.version > 1 || e.revision >= 3 { w * 10 / 16 } else { w },
+                1 => w * 3 / 4,
+                2 => w * 4 / 5,
+                _ => w * 9 / 16,
+            };
+            e.push_mode(EdidMode { width: w as u16, height: h as u16, refresh: (s[1] & 0x3F) + 60 });
+        }
+        Ok(e)
+    }
+
+    fn push_mode(&mut self, m: EdidMode) {
+        if self.mode_count == EDID_MAX_MODES || self.modes[..self.mode_count].contains(&m) { return; }
+        self.modes[self.mode_count] = m;
+        self.mode_count += 1;
+    }
+
+    pub fn manufacturer_str(&self) -> &str {
+        core::str::from_utf8(&self.manufacturer).unwrap_or("???")
+    }
+
+    pub fn name_str(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("")
+    }
+}
+
 // ── Full hardware snapshot ────────────────────────────────────────────────────
 pub struct HardwareInfo {
     pub cpu:     CpuInfo,
@@ -538,11 +885,135 @@ pub struct HardwareInfo {
 
 impl HardwareInfo {
     pub fn detect_all() -> Self {
+        Self::detect_all_with(|_, _| {})
+    }
+
+    /// `detect_all` con progreso del escaneo de discos (ver Disks::detect_with).
+    pub fn detect_all_with<F: FnMut(u8, u8)>(on_disk_probe: F) -> Self {
         HardwareInfo {
             cpu:     CpuInfo::detect(),
             ram:     RamInfo::detect(),
-            disks:   Disks::detect(),
+            disks:   Disks::detect_with(on_disk_probe),
             display: DisplayInfo::detect(),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// EDID 1.4 de "DEL" con 1920x1080@60 como timing preferido y nombre
+    /// "DELL U2412M" en un descriptor 0xFC.
+    fn sample_edid() -> [u8; 128] {
+        let mut e = [0u8; 128];
+        e[..8].copy_from_slice(&EDID_HEADER);
+        e[8..10].copy_from_slice(&0x10ACu16.to_be_bytes());   // D=4 E=5 L=12
+        e[10..12].copy_from_slice(&0xA0C1u16.to_le_bytes());
+        e[17] = 21;                                            // 2011
+        e[18] = 1; e[19] = 4;
+        e[38..54].fill(0x01);                                  // sin timings estándar
+        // Descriptor 0: 148.5 MHz, 1920+280 x 1080+45
+        e[54..62].copy_from_slice(&[0x02, 0x3A, 0x80, 0x18, 0x71, 0x38, 0x2D, 0x40]);
+        // Descriptor 1: nombre del monitor
+        e[72..77].copy_from_slice(&[0, 0, 0, 0xFC, 0]);
+        e[77..90].copy_from_slice(b"DELL U2412M\n ");
+        fix_checksum(&mut e);
+        e
+    }
+
+    fn fix_checksum(e: &mut [u8; 128]) {
+        let sum = e[..127].iter().fold(0u8, |s, &b| s.wrapping_add(b));
+        e[127] = 0u8.wrapping_sub(sum);
+    }
+
+    #[test]
+    fn edid_parses_vendor_mode_and_name() {
+        let Ok(e) = Edid::parse(&sample_edid()) else { panic!("EDID valida rechazada") };
+        assert_eq!(e.manufacturer_str(), "DEL");
+        assert_eq!(e.product, 0xA0C1);
+        assert_eq!(e.year, 2011);
+        assert_eq!(e.name_str(), "DELL U2412M");
+        let native = e.native.unwrap();
+        assert_eq!((native.width, native.height, native.refresh), (1920, 1080, 60));
+        assert_eq!(e.mode_count, 1);
+    }
+
+    #[test]
+    fn edid_rejects_bad_header() {
+        let mut e = sample_edid();
+        e[0] = 0xFF;
+        fix_checksum(&mut e);
+        assert!(matches!(Edid::parse(&e), Err(EdidError::BadHeader)));
+    }
+
+    #[test]
+    fn edid_rejects_bad_checksum() {
+        let mut e = sample_edid();
+        e[127] = e[127].wrapping_add(1);
+        assert!(matches!(Edid::parse(&e), Err(EdidError::BadChecksum)));
+        // Un byte cambiado en el cuerpo también invalida la suma
+        let mut e = sample_edid();
+        e[20] ^= 0x10;
+        assert!(matches!(Edid::parse(&e), Err(EdidError::BadChecksum)));
+    }
+
+    /// IDENTIFY con `model` y `serial` en pares de bytes big-endian.
+    fn identify_words(model: &[u8], serial: &[u8]) -> [u16; 256] {
+        let mut w = [0u16; 256];
+        let mut put = |first: usize, len: usize, text: &[u8]| {
+            for i in 0..len {
+                let b = |k: usize| *text.get(k).unwrap_or(&b' ') as u16;
+                w[first + i] = (b(2 * i) << 8) | b(2 * i + 1);
+            }
+        };
+        put(27, 20, model);
+        put(10, 10, serial);
+        w
+    }
+
+    #[test]
+    fn identify_strings_are_byte_swapped_and_trimmed() {
+        let mut w = identify_words(b"QEMU HARDDISK", b"QM00001");
+        w[60] = 0x0000; w[61] = 0x0010;   // 0x100000 sectores LBA28
+        let d = DiskInfo::from_identify(&w, false);
+        assert!(d.present && !d.is_atapi && !d.ahci);
+        assert_eq!(d.model_str(), "QEMU HARDDISK");
+        assert_eq!(d.serial_str(), "QM00001");
+        assert_eq!(d.sectors, 0x10_0000);
+        assert_eq!(d.size_mb, 512);
+        assert!(!d.lba48);
+    }
+
+    #[test]
+    fn identify_full_width_strings() {
+        let model = [b'M'; 40];
+        let w = identify_words(&model, b"01234567890123456789");
+        let d = DiskInfo::from_identify(&w, false);
+        assert_eq!(d.model_str().len(), 40);
+        assert_eq!(d.serial_str(), "01234567890123456789");
+    }
+
+    #[test]
+    fn identify_packet_has_no_capacity() {
+        let mut w = identify_words(b"QEMU DVD-ROM", b"");
+        w[60] = 0xFFFF;
+        let d = DiskInfo::from_identify(&w, true);
+        assert!(d.is_atapi);
+        assert_eq!(d.model_str(), "QEMU DVD-ROM");
+        assert_eq!(d.serial_str(), "");
+        assert_eq!((d.sectors, d.size_mb), (0, 0));
+    }
+
+    #[test]
+    fn identify_lba48_capacity() {
+        let mut w = identify_words(b"BIG", b"X");
+        w[60] = 0xFFFF; w[61] = 0x0FFF;
+        w[83] = 0x4000 | (1 << 10);
+        w[100] = 0; w[101] = 0; w[102] = 1;   // 2^32 sectores = 2 TiB
+        let d = DiskInfo::from_identify(&w, false);
+        assert!(d.lba48);
+        assert_eq!(d.sectors, 1 << 32);
+        assert_eq!(d.size_mb, 2 * 1024 * 1024);
+    }
+}
+
@@ -2,7 +2,8 @@
 ;
 ; NUEVO: Antes de llamar a cada handler Rust, llenamos la estructura
 ;        crash_frame (definida en isr_handlers.rs como #[no_mangle] static mut)
-;        con RIP, RSP, RFLAGS, CR3, RAX, RBX, RCX, RDX, RSI, RDI, R8-R15.
+;        con RIP, RSP, RFLAGS, CR3, RAX, RBX, RCX, RDX, RSI, RDI, R8-R15, RBP,
+;        el vector y el código de error (si la excepción lo empuja).
 ;
 ; Layout del stack al entrar a una excepción SIN error code (el CPU empuja):
 ;   [RSP+0]  = RIP del faulting code
@@ -50,7 +51,10 @@ extern isr_generic_handler
 ;   +120 r13
 ;   +128 r14
 ;   +136 r15
-;   +144 valid (u8)
+;   +144 rbp
+;   +152 valid (u8)
+;   +160 vector
+;   +168 error      (código de error del CPU, 0 si la excepción no lo empuja)
 extern crash_frame
 
 global irq0_handler
@@ -61,7 +65,8 @@ global reload_segments
 ; ─── Macro: llenar crash_frame ANTES de tocar los registros ─────────────────
 ; Se llama al inicio del stub, cuando el stack aún tiene el frame original.
 ; rip_offset = offset al RIP en el frame del CPU (0 sin EC, 8 con EC).
-%macro CAPTURE_FRAME 1      ; arg: offset_to_rip_on_stack
+; Con EC, el código de error está en [RSP+0] y se guarda en crash_frame.error.
+%macro CAPTURE_FRAME 2      ; args: offset_to_rip_on_stack, vector
     ; Usar scratch: rax, rcx, rdx — luego los restauramos del frame
     push    rax
     push    rcx
@@ -104,9 +109,19 @@ global reload_segments
     mov     [crash_frame + 120], r13
     mov     [crash_frame + 128], r14
     mov     [crash_frame + 136], r15
+    mov     [crash_frame + 144], rbp
+
+    ; Vector y código de error
+    mov     qword [crash_frame + 160], %2
+%if %1 == 8
+    mov     rax, [rsp + 24]         ; EC justo encima de los 3 push
+%else
+    xor     eax, eax
+%endif
+    mov     [crash_frame + 168], rax
 
     ; Marcar como válido
-    mov     byte [crash_frame + 144], 1
+    mov     byte [crash_frame + 152], 1
 
     pop     rdx
     pop     rcx
@@ -142,6 +157,7 @@ global reload_segments
 irq0_handler:
     PUSH_REGS
     push    rax                 ; dummy align
+    mov     rdi, [rsp + 80]     ; RIP interrumpido (10 push) → profile
     call    pit_tick
     pop     rax
     mov     al, 0x20
@@ -182,7 +198,7 @@ global isr_16, isr_17, isr_18, isr_19
 ; Al entrar: [RSP+0]=RIP, [RSP+8]=CS, [RSP+16]=RFLAGS, [RSP+24]=RSP, [RSP+32]=SS
 %macro ISR_NOERR 2
 isr_%1:
-    CAPTURE_FRAME 0         ; RIP en [RSP+0] (antes de cualquier push)
+    CAPTURE_FRAME 0, %1     ; RIP en [RSP+0] (antes de cualquier push)
     PUSH_REGS
     push rax                ; align
     call %2
@@ -195,7 +211,7 @@ isr_%1:
 ; Al entrar: [RSP+0]=EC, [RSP+8]=RIP, [RSP+16]=CS, [RSP+24]=RFLAGS, [RSP+32]=RSP
 %macro ISR_ERR 2
 isr_%1:
-    CAPTURE_FRAME 8         ; RIP en [RSP+8] (EC está en [RSP+0])
+    CAPTURE_FRAME 8, %1     ; RIP en [RSP+8] (EC está en [RSP+0])
     pop     rdi             ; error code → RDI (primer arg Rust)
     PUSH_REGS
     push    rax             ; align
@@ -213,7 +229,7 @@ ISR_NOERR  4, isr_generic_handler
 ISR_NOERR  5, isr_bound_range
 ISR_NOERR  6, isr_ud_handler
 ISR_NOERR  7, isr_generic_handler
-ISR_NOERR  8, isr_double_fault      ; #DF error code siempre 0, tratado como NOERR
+ISR_ERR    8, isr_double_fault      ; #DF: el CPU empuja EC (siempre 0)
 ISR_ERR   10, isr_generic_handler   ; #TS
 ISR_ERR   11, isr_generic_handler   ; #NP
 ISR_ERR   12, isr_generic_handler   ; #SS
@@ -1,4 +1,4 @@
-// arch/isr_handlers.rs — PORTIX OS v3.1
+// arch/isr_handlers.rs — PORTIX OS
 //
 // [FIX-REG-EXHAUSTION]  inline_capture_frame() ya no usa outputs separados por
 //                       registro. En su lugar escribe directamente a crash_frame
@@ -17,19 +17,99 @@
 //   • Mensaje del panic en write_at_tall (grande, legible)
 //   • Hints dinámicos según mensaje de panic
 //   • RIP real en esquina superior derecha
+//
+// v3.2: verbosidad de la pantalla de pánico (`config set panic.verbosity`):
+//   compacta (mensaje, RIP, ubicación y causas) o completa (paneles + GPR).
+//
+// v3.3: backtrace por cadena de RBP ([rbp] = rbp previo, [rbp+8] = retorno).
+//   Requiere frame pointers ("frame-pointer": "always" en el target JSON).
+//   El recorrido se corta ante un rbp desalineado, fuera de la pila del
+//   kernel o que no crece, y ante un retorno fuera de .text.
+//
+// v3.4: direcciones como `nombre+offset` con la tabla .ksyms (arch/symbols.rs);
+//   `??` si la dirección cae fuera del kernel o la tabla no está incrustada.
+//
+// v3.5: mini-hexdump de los primeros 64 bytes desde RSP en la vista completa
+//   (mismo formato de fila que `hexdump`). Solo se lee si RSP cae dentro de
+//   la pila del kernel, y se recorta a las filas que quepan sobre las causas.
+//
+// v3.6: #PF decodifica el código de error (decode_pf_error): cada bit se
+//   rotula según su estado (lectura/escritura, usuario/supervisor...), la
+//   causa probable sale de la combinación y hay un resumen del acceso.
+//
+// v3.7: los stubs de isr.asm capturan RBP, vector y código de error además de
+//   los GPR (valid pasa a +152, como pedía FIX-RBP-OFFSET). #DF usa el stub
+//   con EC. Todas las pantallas de excepción muestran el mismo bloque de
+//   registros (draw_cpu_context) en vez de subconjuntos distintos.
+//
+// v3.8: capture_live() reutiliza la captura del panic sobre un CrashFrame
+//   local (comando `reg`), sin tocar crash_frame.
 
 #![allow(dead_code)]
 
 use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicU8, Ordering};
 use crate::graphics::driver::framebuffer::{Color, Console};
 use crate::arch::halt::halt_loop;
 use crate::util::fmt::{fmt_u32, fmt_hex};
+use crate::arch::symbols::fmt_symbol;
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "3.8";
+
+// ═══════════════════════════════════════════════════════════════════════════════
+//  VERBOSIDAD DEL PANIC
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Un atómico simple: el panic_handler lo lee sin tomar locks ni tocar el heap,
+// así que es seguro aunque el pánico venga de mitad de un `config set`.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PanicVerbosity {
+    /// Compacta si la pantalla no da para la completa (< PANIC_FULL_MIN_H).
+    Auto,
+    Compact,
+    Full,
+}
+
+impl PanicVerbosity {
+    pub fn name(self) -> &'static str {
+        match self { Self::Auto => "auto", Self::Compact => "compact", Self::Full => "full" }
+    }
+
+    pub fn parse(s: &[u8]) -> Option<Self> {
+        match s {
+            b"auto"               => Some(Self::Auto),
+            b"compact" | b"compacta" => Some(Self::Compact),
+            b"full" | b"completa" => Some(Self::Full),
+            _ => None,
+        }
+    }
+}
+
+/// Alto mínimo (px) con el que `Auto` dibuja la pantalla completa.
+const PANIC_FULL_MIN_H: usize = 560;
+
+static PANIC_VERBOSITY: AtomicU8 = AtomicU8::new(PanicVerbosity::Auto as u8);
+
+pub fn panic_verbosity() -> PanicVerbosity {
+    match PANIC_VERBOSITY.load(Ordering::Relaxed) {
+        1 => PanicVerbosity::Compact,
+        2 => PanicVerbosity::Full,
+        _ => PanicVerbosity::Auto,
+    }
+}
+
+pub fn set_panic_verbosity(v: PanicVerbosity) {
+    PANIC_VERBOSITY.store(v as u8, Ordering::Relaxed);
+}
 
 // ═══════════════════════════════════════════════════════════════════════════════
 //  CRASH FRAME
 // ═══════════════════════════════════════════════════════════════════════════════
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct CrashFrame {
     pub rip:    u64,  // +0
     pub rsp:    u64,  // +8
@@ -51,18 +131,49 @@ pub struct CrashFrame {
     pub r15:    u64,  // +136
     pub rbp:    u64,  // +144
     pub valid:  u8,   // +152
+    pub vector: u64,  // +160  (VECTOR_NONE si viene del panic handler)
+    pub error:  u64,  // +168  (0 si la excepción no empuja código de error)
 }
 
-#[no_mangle]
-pub static mut crash_frame: CrashFrame = CrashFrame {
-    rip: 0, rsp: 0, rflags: 0, cr3: 0,
-    rax: 0, rbx: 0, rcx: 0, rdx: 0,
-    rsi: 0, rdi: 0,
-    r8: 0, r9: 0, r10: 0, r11: 0,
-    r12: 0, r13: 0, r14: 0, r15: 0,
-    rbp: 0, valid: 0,
+/// `vector` de un CrashFrame capturado por el panic handler, no por una ISR.
+pub const VECTOR_NONE: u64 = u64::MAX;
+
+// isr.asm escribe en crash_frame con offsets literales: cualquier cambio de
+// layout tiene que romper el build aquí y no en tiempo de ejecución.
+const _: () = {
+    use core::mem::offset_of;
+    assert!(offset_of!(CrashFrame, rip)    == 0);
+    assert!(offset_of!(CrashFrame, rsp)    == 8);
+    assert!(offset_of!(CrashFrame, rflags) == 16);
+    assert!(offset_of!(CrashFrame, cr3)    == 24);
+    assert!(offset_of!(CrashFrame, rax)    == 32);
+    assert!(offset_of!(CrashFrame, rbx)    == 40);
+    assert!(offset_of!(CrashFrame, rcx)    == 48);
+    assert!(offset_of!(CrashFrame, rdx)    == 56);
+    assert!(offset_of!(CrashFrame, rsi)    == 64);
+    assert!(offset_of!(CrashFrame, rdi)    == 72);
+    assert!(offset_of!(CrashFrame, r8)     == 80);
+    assert!(offset_of!(CrashFrame, r15)    == 136);
+    assert!(offset_of!(CrashFrame, rbp)    == 144);
+    assert!(offset_of!(CrashFrame, valid)  == 152);
+    assert!(offset_of!(CrashFrame, vector) == 160);
+    assert!(offset_of!(CrashFrame, error)  == 168);
 };
 
+impl CrashFrame {
+    pub const EMPTY: CrashFrame = CrashFrame {
+        rip: 0, rsp: 0, rflags: 0, cr3: 0,
+        rax: 0, rbx: 0, rcx: 0, rdx: 0,
+        rsi: 0, rdi: 0,
+        r8: 0, r9: 0, r10: 0, r11: 0,
+        r12: 0, r13: 0, r14: 0, r15: 0,
+        rbp: 0, valid: 0, vector: VECTOR_NONE, error: 0,
+    };
+}
+
+#[no_mangle]
+pub static mut crash_frame: CrashFrame = CrashFrame::EMPTY;
+
 fn frame() -> &'static CrashFrame { unsafe { &crash_frame } }
 
 /// [FIX-REG-EXHAUSTION] Captura registros usando un único puntero de entrada.
@@ -70,7 +181,22 @@ fn frame() -> &'static CrashFrame { unsafe { &crash_frame } }
 /// El compilador solo necesita asignar UN registro para `ptr`.
 #[inline(never)]
 unsafe fn inline_capture_frame() {
-    let ptr = core::ptr::addr_of_mut!(crash_frame) as u64;
+    capture_into(core::ptr::addr_of_mut!(crash_frame));
+}
+
+/// Estado de registros en este punto, en un frame local (no toca
+/// crash_frame). RIP/RSP son los de la propia captura y el registro que
+/// lleva el puntero de destino muestra ese puntero; el resto conserva el
+/// valor que tenía al llamar, porque RAX se guarda antes de usarse.
+pub fn capture_live() -> CrashFrame {
+    let mut f = CrashFrame::EMPTY;
+    unsafe { capture_into(&mut f); }
+    f
+}
+
+#[inline(never)]
+unsafe fn capture_into(dst: *mut CrashFrame) {
+    let ptr = dst as u64;
     core::arch::asm!(
         "mov [{p} + 32],  rax",
         "mov [{p} + 40],  rbx",
@@ -100,6 +226,9 @@ unsafe fn inline_capture_frame() {
         // RIP via LEA relativa
         "lea rax, [rip]",
         "mov [{p} + 0], rax",
+        // No viene de una excepción
+        "mov qword ptr [{p} + 160], -1",
+        "mov qword ptr [{p} + 168], 0",
         // valid = 1
         "mov byte ptr [{p} + 152], 1",
         p = in(reg) ptr,
@@ -108,6 +237,64 @@ unsafe fn inline_capture_frame() {
     );
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+//  BACKTRACE (CADENA DE RBP)
+// ═══════════════════════════════════════════════════════════════════════════════
+
+extern "C" {
+    static __stack_bottom: u8;
+    static __stack_top: u8;
+}
+
+/// Direcciones de retorno que se muestran como mucho en el panic.
+pub const BACKTRACE_MAX: usize = 8;
+
+/// Rango [bottom, top) de la pila del kernel (linker.ld).
+pub fn stack_range() -> (u64, u64) {
+    (core::ptr::addr_of!(__stack_bottom) as u64,
+     core::ptr::addr_of!(__stack_top) as u64)
+}
+
+/// Sigue la cadena de frames desde `rbp`. `read` lee un u64 de la pila; se
+/// pasa como closure para poder recorrer una pila simulada en un array.
+/// Se detiene sin leer fuera de `stack` ante el primer frame sospechoso:
+/// rbp desalineado o fuera de rango, retorno fuera de `text`, o un rbp
+/// guardado que no es mayor que el actual (la pila crece hacia abajo, así
+/// que un frame corrupto o un ciclo no puede avanzar).
+pub fn walk_frames<R: Fn(u64) -> u64>(
+    mut rbp: u64, stack: (u64, u64), text: (u64, u64), read: R,
+) -> ([u64; BACKTRACE_MAX], usize) {
+    let mut out = [0u64; BACKTRACE_MAX];
+    let mut n = 0;
+    while n < BACKTRACE_MAX {
+        if !rbp.is_multiple_of(8) || rbp < stack.0 || rbp.saturating_add(16) > stack.1 { break; }
+        let ret = read(rbp + 8);
+        if ret < text.0 || ret >= text.1 { break; }
+        out[n] = ret;
+        n += 1;
+        let next = read(rbp);
+        if next <= rbp { break; }
+        rbp = next;
+    }
+    (out, n)
+}
+
+/// Bytes del volcado de pila en el panic.
+pub const STACK_DUMP_BYTES: usize = 64;
+
+/// Cuántos bytes se pueden volcar desde `rsp` sin salir de `stack`
+/// (0 si `rsp` no apunta dentro de la pila: no se lee nada).
+pub fn stack_dump_len(rsp: u64, stack: (u64, u64)) -> usize {
+    if rsp < stack.0 || rsp >= stack.1 { return 0; }
+    ((stack.1 - rsp) as usize).min(STACK_DUMP_BYTES)
+}
+
+/// Backtrace real sobre la pila del kernel.
+pub fn backtrace(rbp: u64) -> ([u64; BACKTRACE_MAX], usize) {
+    walk_frames(rbp, stack_range(), crate::arch::paging::kernel_text(),
+                |a| unsafe { core::ptr::read_volatile(a as *const u64) })
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 //  PALETAS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -283,6 +470,40 @@ fn reg_grid_ncol(c: &mut Console, regs: &[(&str, u64)],
     }
 }
 
+/// Bloque de registros común a todas las pantallas de excepción: RIP, RSP,
+/// RFLAGS y CR3 seguidos de los 15 GPR, en 3 columnas si caben en `w` y si
+/// no en 2. Sin frame válido deja una línea de aviso. Devuelve el alto usado.
+fn draw_cpu_context(c: &mut Console, f: &CrashFrame, title: &str,
+                    x: usize, y: usize, w: usize, accent: Color) -> usize {
+    const MIN_COL_W: usize = 200;
+    section_title(c, title, x, y, pal::MID);
+    if f.valid == 0 {
+        c.write_at("(sin captura de registros)", x, y + 16, pal::MID);
+        return 30;
+    }
+    let regs: &[(&str, u64)] = &[
+        ("RIP ", f.rip), ("RSP ", f.rsp), ("RFLG", f.rflags), ("CR3 ", f.cr3),
+        ("RAX ", f.rax), ("RBX ", f.rbx), ("RCX ", f.rcx), ("RDX ", f.rdx),
+        ("RSI ", f.rsi), ("RDI ", f.rdi), ("RBP ", f.rbp), ("R8  ", f.r8),
+        ("R9  ", f.r9),  ("R10 ", f.r10), ("R11 ", f.r11), ("R12 ", f.r12),
+        ("R13 ", f.r13), ("R14 ", f.r14), ("R15 ", f.r15),
+    ];
+    let cols = if w >= 3 * MIN_COL_W + 8 { 3 } else { 2 };
+    let col_w = (w.saturating_sub((cols - 1) * 4)) / cols;
+    reg_grid_ncol(c, regs, x, y + 16, cols, col_w, 16, accent);
+    16 + regs.len().div_ceil(cols) * 16 + 6
+}
+
+/// Mnemónico de un vector de excepción (ISR genérico y #DF).
+pub fn vector_name(v: u64) -> &'static str {
+    match v {
+        0 => "#DE", 1 => "#DB", 2 => "NMI", 3 => "#BP", 4 => "#OF", 5 => "#BR",
+        6 => "#UD", 7 => "#NM", 8 => "#DF", 10 => "#TS", 11 => "#NP", 12 => "#SS",
+        13 => "#GP", 14 => "#PF", 16 => "#MF", 17 => "#AC", 18 => "#MC", 19 => "#XM",
+        _ => "??",
+    }
+}
+
 fn draw_top_bar(c: &mut Console, l: Color, r: Color) {
     let w = c.width();
     grad_h_bar(c, 0, 0, w, 6, l, r);
@@ -299,7 +520,8 @@ fn draw_bottom_bar(c: &mut Console, l: Color, r: Color, label: &str) {
     c.fill_rect(20, by + 6, 7, 7, r.dim(160));
     c.fill_rect(32, by + 6, 7, 7, pal::MID);
     c.write_at(label, 48, by + 6, pal::MID);
-    c.write_at("PORTIX-OS  v0.7.4", w.saturating_sub(155), by + 6, pal::MID.dim(90));
+    c.write_at("PORTIX-OS  v", w.saturating_sub(155), by + 6, pal::MID.dim(90));
+    c.write_at(crate::VERSION, w.saturating_sub(155) + 12 * 8, by + 6, pal::MID.dim(90));
 }
 
 fn draw_corner_rip(c: &mut Console, rip: u64, valid: u8) {
@@ -314,6 +536,7 @@ fn draw_corner_rip(c: &mut Console, rip: u64, valid: u8) {
 //  KERNEL PANIC
 // ═══════════════════════════════════════════════════════════════════════════════
 
+#[cfg(not(test))]
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
     unsafe { inline_capture_frame(); }
@@ -377,16 +600,102 @@ fn panic(info: &PanicInfo) -> ! {
     c.write_at_tall(line1, 54, mp_y + 2, pal::WHITE);
     if !line2.is_empty() { c.write_at(line2, 54, mp_y + 24, pal::LIGHT); }
 
+    let compact = match panic_verbosity() {
+        PanicVerbosity::Compact => true,
+        PanicVerbosity::Full    => false,
+        PanicVerbosity::Auto    => h < PANIC_FULL_MIN_H,
+    };
+    let hint_y = if compact {
+        draw_panic_compact(&mut c, f, info, mp_y + mp_h + 12)
+    } else {
+        draw_panic_full(&mut c, f, info, mp_y + mp_h + 12)
+    };
+
+    // ── Hints dinámicos ───────────────────────────────────────────────────────
+    if hint_y + 38 < h.saturating_sub(26) {
+        section_title(&mut c, "POSIBLES CAUSAS", 44, hint_y, pal::PANIC_HINT.dim(150));
+        let (h1, h2) = panic_hints(msg_str);
+        c.write_at(h1, 44, hint_y + 16, pal::PANIC_HINT.dim(120));
+        c.write_at(h2, 44, hint_y + 28, pal::PANIC_HINT.dim(100));
+    }
+
+    if !compact { energy_bars(&mut c, 44, h.saturating_sub(56), pal::PANIC_RED); }
+    draw_bottom_bar(&mut c, pal::PANIC_CRIMSON, pal::PANIC_RED,
+                   "KERNEL PANIC  |  INTERRUPTS DISABLED  |  SISTEMA DETENIDO");
+    c.present();
+    halt_loop()
+}
+
+/// Pantalla compacta: RIP y ubicación en un único panel bajo el mensaje.
+/// Devuelve la Y donde van las causas posibles.
+fn draw_panic_compact(c: &mut Console, f: &CrashFrame, info: &PanicInfo, y: usize) -> usize {
+    let w = c.width();
+    let ph = 62usize;
+    panel(c, 44, y, w.saturating_sub(90), ph, pal::PANIC_PANEL, pal::PANIC_RED.dim(70));
+    accent_bar(c, 44, y, ph, pal::PANIC_ORANGE);
+
+    let mut rb = [0u8; 18];
+    c.write_at("RIP:", 54, y + 8, pal::MID);
+    c.write_at(fmt_hex(f.rip, &mut rb), 100, y + 8,
+               if f.valid != 0 { pal::PANIC_CRIMSON } else { pal::MID });
+    let mut sb = [0u8; 64];
+    c.write_at(fmt_symbol(f.rip, &mut sb), 100 + 20 * 8, y + 8, pal::PANIC_ORANGE);
+
+    c.write_at("EN:", 54, y + 26, pal::MID);
+    if let Some(loc) = info.location() {
+        // "archivo:linea:columna", recortando el archivo por la izquierda
+        let max = (w.saturating_sub(100 + 56) / 8).max(16);
+        let mut lb = [0u8; 16]; let mut cb = [0u8; 16];
+        let line = fmt_u32(loc.line(), &mut lb);
+        let col  = fmt_u32(loc.column(), &mut cb);
+        let file = loc.file();
+        let keep = max.saturating_sub(line.len() + col.len() + 2).min(file.len());
+        let fd   = &file[file.len() - keep..];
+        let mut x = 100;
+        c.write_at(fd, x, y + 26, pal::PANIC_ORANGE);       x += fd.len() * 8;
+        c.write_at(":", x, y + 26, pal::MID);               x += 8;
+        c.write_at(line, x, y + 26, pal::PANIC_CRIMSON);    x += line.len() * 8;
+        c.write_at(":", x, y + 26, pal::MID);               x += 8;
+        c.write_at(col, x, y + 26, pal::PANIC_CRIMSON);
+    } else {
+        c.write_at("(ubicacion no disponible)", 100, y + 26, pal::MID);
+    }
+
+    // Backtrace en una sola línea: símbolo (o hex si no resuelve) de tantos
+    // frames como quepan, del más interno al más externo.
+    c.write_at("BT:", 54, y + 44, pal::MID);
+    let (bt, n) = backtrace(f.rbp);
+    if n == 0 {
+        c.write_at("(sin frames validos)", 100, y + 44, pal::MID);
+    }
+    let mut x = 100;
+    for (i, ret) in bt[..n].iter().enumerate() {
+        let mut sb = [0u8; 64]; let mut hb = [0u8; 18];
+        let sym = fmt_symbol(*ret, &mut sb);
+        let s = if sym == "??" { fmt_hex(*ret, &mut hb) } else { sym };
+        let sep = if i == 0 { 0 } else { 3 };
+        if x + (sep + s.len()) * 8 > w.saturating_sub(50) { break; }
+        if sep != 0 { c.write_at(" < ", x, y + 44, pal::MID); x += sep * 8; }
+        c.write_at(s, x, y + 44, pal::PANIC_ORANGE);
+        x += s.len() * 8;
+    }
+    y + ph + 12
+}
+
+/// Pantalla completa: paneles de ubicación y CPU + grid de los 15 GPR.
+/// Devuelve la Y donde van las causas posibles.
+fn draw_panic_full(c: &mut Console, f: &CrashFrame, info: &PanicInfo, panels_y: usize) -> usize {
+    let w = c.width();
+
     // ── Paneles: ubicación + CPU ───────────────────────────────────────────────
-    let panels_y = mp_y + mp_h + 12;
     let panel_h  = 92usize;
     let lw = (w / 2).saturating_sub(54);
     let rx = 44 + lw + 14;
     let rw = w.saturating_sub(rx + 20);
 
-    panel(&mut c, 44, panels_y, lw, panel_h, pal::PANIC_PANEL, pal::PANIC_RED.dim(70));
-    accent_bar(&mut c, 44, panels_y, panel_h, pal::PANIC_ORANGE);
-    section_title(&mut c, "UBICACION DEL PANIC", 54, panels_y + 8, pal::PANIC_ORANGE.dim(200));
+    panel(c, 44, panels_y, lw, panel_h, pal::PANIC_PANEL, pal::PANIC_RED.dim(70));
+    accent_bar(c, 44, panels_y, panel_h, pal::PANIC_ORANGE);
+    section_title(c, "UBICACION DEL PANIC", 54, panels_y + 8, pal::PANIC_ORANGE.dim(200));
 
     if let Some(loc) = info.location() {
         let file = loc.file();
@@ -402,9 +711,9 @@ fn panic(info: &PanicInfo) -> ! {
         c.write_at("(ubicacion no disponible)", 54, panels_y + 38, pal::MID);
     }
 
-    panel(&mut c, rx, panels_y, rw, panel_h, pal::PANIC_PANEL, pal::PANIC_RED.dim(70));
-    accent_bar(&mut c, rx, panels_y, panel_h, pal::PANIC_CRIMSON);
-    section_title(&mut c, "CPU AL MOMENTO DEL PANIC", rx + 10, panels_y + 8, pal::PANIC_ORANGE.dim(200));
+    panel(c, rx, panels_y, rw, panel_h, pal::PANIC_PANEL, pal::PANIC_RED.dim(70));
+    accent_bar(c, rx, panels_y, panel_h, pal::PANIC_CRIMSON);
+    section_title(c, "CPU AL MOMENTO DEL PANIC", rx + 10, panels_y + 8, pal::PANIC_ORANGE.dim(200));
 
     let cw2 = (rw.saturating_sub(30)) / 2;
     let crit: &[(&str, u64)] = &[
@@ -412,14 +721,14 @@ fn panic(info: &PanicInfo) -> ! {
         ("RFLG", f.rflags),("CR3 ", f.cr3),
     ];
     for (i, (name, val)) in crit.iter().enumerate() {
-        reg_row_w(&mut c, name, *val,
+        reg_row_w(c, name, *val,
                   rx + 10 + (i % 2) * (cw2 + 4), panels_y + 26 + (i / 2) * 18,
                   cw2, 15, pal::PANIC_RED, pal::MID, pal::WHITE);
     }
 
     // ── GPR 3 columnas ────────────────────────────────────────────────────────
     let gpr_y = panels_y + panel_h + 14;
-    section_title(&mut c, "REGISTROS DE PROPOSITO GENERAL", 44, gpr_y, pal::MID.dim(180));
+    section_title(c, "REGISTROS DE PROPOSITO GENERAL", 44, gpr_y, pal::MID.dim(180));
 
     let all_regs: &[(&str, u64)] = &[
         ("RAX", f.rax), ("RBX", f.rbx), ("RCX", f.rcx),
@@ -429,44 +738,150 @@ fn panic(info: &PanicInfo) -> ! {
         ("R13", f.r13), ("R14", f.r14), ("R15", f.r15),
     ];
     let col_w3 = (w.saturating_sub(88 + 16)) / 3;
-    reg_grid_ncol(&mut c, all_regs, 44, gpr_y + 16, 3, col_w3, 16, pal::PANIC_RED.dim(120));
+    reg_grid_ncol(c, all_regs, 44, gpr_y + 16, 3, col_w3, 16, pal::PANIC_RED.dim(120));
 
-    // ── Hints dinámicos ───────────────────────────────────────────────────────
-    let rows_3 = (all_regs.len() + 2) / 3;
-    let hint_y = gpr_y + 16 + rows_3 * 16 + 10;
-    if hint_y + 38 < h.saturating_sub(26) {
-        section_title(&mut c, "POSIBLES CAUSAS", 44, hint_y, pal::PANIC_HINT.dim(150));
-        let (h1, h2) = if msg_str.contains("zero") {
-            ("►  Division por cero: el divisor era 0 en DIV/IDIV o en Rust /",
-             "►  Verificar denominadores antes de dividir (if divisor != 0)")
-        } else if msg_str.contains("index") || msg_str.contains("out of bounds") {
-            ("►  Indice fuera de rango: acceso a slice/array mas alla de su longitud",
-             "►  Verificar .len() antes de indexar, o usar .get() con Option")
-        } else if msg_str.contains("unwrap") || msg_str.contains("None") {
-            ("►  unwrap() sobre None: la Option estaba vacia inesperadamente",
-             "►  Usar if let / match en lugar de unwrap(); revisar flujo de datos")
-        } else if msg_str.contains("overflow") || msg_str.contains("arithmetic") {
-            ("►  Desbordamiento aritmetico (debug): operacion supero el tipo",
-             "►  Usar wrapping_add/sub/mul o checked_* para evitar panics")
-        } else {
-            ("►  unwrap()/expect() sobre None/Err    ►  assert!() fallido",
-             "►  Desbordamiento aritmetico (debug)   ►  Indice fuera de rango")
-        };
-        c.write_at(h1, 44, hint_y + 16, pal::PANIC_HINT.dim(120));
-        c.write_at(h2, 44, hint_y + 28, pal::PANIC_HINT.dim(100));
+    let rows_3 = all_regs.len().div_ceil(3);
+    let bt_y = gpr_y + 16 + rows_3 * 16 + 10;
+    let st_y = bt_y + draw_backtrace(c, f.rbp, bt_y) + 10;
+    st_y + draw_stack_dump(c, f.rsp, f.valid != 0, st_y)
+}
+
+/// Mini-hexdump desde RSP, recortado para dejar sitio a POSIBLES CAUSAS y
+/// a la barra inferior. Devuelve el alto usado (0 si no cabe ni una fila).
+fn draw_stack_dump(c: &mut Console, rsp: u64, valid: bool, y: usize) -> usize {
+    const ROW_H: usize = 13;
+    let h = c.height();
+    let room = h.saturating_sub(56 + 38 + 10).saturating_sub(y + 16);
+    let max_rows = room / ROW_H;
+    if max_rows == 0 { return 0; }
+
+    section_title(c, "PILA DESDE RSP", 44, y, pal::MID.dim(180));
+    let len = if valid { stack_dump_len(rsp, stack_range()) } else { 0 };
+    if len == 0 {
+        c.write_at("(RSP fuera de la pila del kernel: no se lee)", 44, y + 16, pal::MID);
+        return 16 + ROW_H + 10;
+    }
+    let mut bytes = [0u8; STACK_DUMP_BYTES];
+    for (i, b) in bytes[..len].iter_mut().enumerate() {
+        *b = unsafe { core::ptr::read_volatile((rsp + i as u64) as *const u8) };
     }
+    let rows = len.div_ceil(16).min(max_rows);
+    for r in 0..rows {
+        let chunk = &bytes[r * 16..len.min(r * 16 + 16)];
+        let mut line = [0u8; 96]; let mut lp = 0;
+        crate::console::terminal::fmt::append_hexdump_row(
+            &mut line, &mut lp, rsp + (r * 16) as u64, chunk);
+        let txt = core::str::from_utf8(&line[..lp]).unwrap_or("?");
+        c.write_at(txt, 54, y + 16 + r * ROW_H, if r == 0 { pal::WHITE } else { pal::LIGHT });
+    }
+    16 + rows * ROW_H + 10
+}
 
-    energy_bars(&mut c, 44, h.saturating_sub(56), pal::PANIC_RED);
-    draw_bottom_bar(&mut c, pal::PANIC_CRIMSON, pal::PANIC_RED,
-                   "KERNEL PANIC  |  INTERRUPTS DISABLED  |  SISTEMA DETENIDO");
-    c.present();
-    halt_loop()
+/// Sección BACKTRACE de la vista completa: 2 columnas de `#i dirección
+/// nombre+offset`. Devuelve su alto.
+fn draw_backtrace(c: &mut Console, rbp: u64, y: usize) -> usize {
+    const NAMES: [&str; BACKTRACE_MAX] = ["#0 ", "#1 ", "#2 ", "#3 ", "#4 ", "#5 ", "#6 ", "#7 "];
+    let w = c.width();
+    section_title(c, "BACKTRACE (CADENA RBP)", 44, y, pal::MID.dim(180));
+    let (bt, n) = backtrace(rbp);
+    if n == 0 {
+        c.write_at("(sin frames validos: rbp fuera de la pila o corrupto)", 44, y + 16, pal::MID);
+        return 28;
+    }
+    let col_w = (w.saturating_sub(88 + 4)) / 2;
+    let sym_x = 46 + 18 * 8 + 8;
+    let max_chars = col_w.saturating_sub(sym_x + 8) / 8;
+    for (i, ret) in bt[..n].iter().enumerate() {
+        let x = 44 + (i % 2) * (col_w + 4);
+        let ry = y + 16 + (i / 2) * 16;
+        reg_row_w(c, NAMES[i], *ret, x, ry, col_w, 14,
+                  pal::PANIC_ORANGE.dim(120), pal::MID, pal::WHITE);
+        let mut sb = [0u8; 64];
+        let sym = fmt_symbol(*ret, &mut sb);
+        let mut cut = sym.len().saturating_sub(max_chars);
+        while !sym.is_char_boundary(cut) { cut += 1; }
+        let sym = &sym[cut..];
+        c.write_at(sym, x + sym_x, ry + 2,
+                   if sym == "??" { pal::MID } else { pal::PANIC_ORANGE });
+    }
+    16 + n.div_ceil(2) * 16
+}
+
+/// Dos líneas de causas probables según el texto del mensaje.
+fn panic_hints(msg_str: &str) -> (&'static str, &'static str) {
+    if msg_str.contains("zero") {
+        ("►  Division por cero: el divisor era 0 en DIV/IDIV o en Rust /",
+         "►  Verificar denominadores antes de dividir (if divisor != 0)")
+    } else if msg_str.contains("index") || msg_str.contains("out of bounds") {
+        ("►  Indice fuera de rango: acceso a slice/array mas alla de su longitud",
+         "►  Verificar .len() antes de indexar, o usar .get() con Option")
+    } else if msg_str.contains("unwrap") || msg_str.contains("None") {
+        ("►  unwrap() sobre None: la Option estaba vacia inesperadamente",
+         "►  Usar if let / match en lugar de unwrap(); revisar flujo de datos")
+    } else if msg_str.contains("overflow") || msg_str.contains("arithmetic") {
+        ("►  Desbordamiento aritmetico (debug): operacion supero el tipo",
+         "►  Usar wrapping_add/sub/mul o checked_* para evitar panics")
+    } else {
+        ("►  unwrap()/expect() sobre None/Err    ►  assert!() fallido",
+         "►  Desbordamiento aritmetico (debug)   ►  Indice fuera de rango")
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
 //  PAGE FAULT  #PF
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Bits del código de error de #PF (Intel SDM vol. 3, 4.7).
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PfFlags {
+    pub present:  bool, // bit 0: violación de permisos (1) / página no presente (0)
+    pub write:    bool, // bit 1
+    pub user:     bool, // bit 2: CPL=3
+    pub reserved: bool, // bit 3: bit reservado a 1 en alguna entrada de tabla
+    pub fetch:    bool, // bit 4: fetch de instrucción (NX)
+    pub pkey:     bool, // bit 5
+    pub shadow:   bool, // bit 6
+    pub sgx:      bool, // bit 15
+}
+
+pub fn decode_pf_error(ec: u64) -> PfFlags {
+    let b = |n: u32| ec & (1 << n) != 0;
+    PfFlags {
+        present: b(0), write: b(1), user: b(2), reserved: b(3),
+        fetch: b(4), pkey: b(5), shadow: b(6), sgx: b(15),
+    }
+}
+
+impl PfFlags {
+    pub fn access(&self) -> &'static str {
+        if self.fetch { "fetch" } else if self.write { "escritura" } else { "lectura" }
+    }
+    pub fn mode(&self) -> &'static str {
+        if self.user { "usuario" } else { "supervisor" }
+    }
+    pub fn page(&self) -> &'static str {
+        if self.present { "presente" } else { "no presente" }
+    }
+
+    /// Causa probable según la combinación de bits. Un bit reservado manda
+    /// sobre el resto: la CPU deja de evaluar permisos al encontrarlo.
+    pub fn cause(&self) -> &'static str {
+        if self.reserved { return "Bit reservado en tabla de paginas (PTE corrupta)"; }
+        if self.pkey     { return "Proteccion por clave (PKU) denegada"; }
+        if self.shadow   { return "Acceso invalido a shadow stack (CET)"; }
+        if self.sgx      { return "Violacion de control de acceso SGX"; }
+        match (self.present, self.fetch, self.write, self.user) {
+            (false, true, _, _)      => "Salto a direccion no mapeada",
+            (false, _, true, _)      => "Escritura a pagina no mapeada",
+            (false, _, _, _)         => "Lectura de pagina no mapeada",
+            (true, true, _, _)       => "Ejecucion en pagina no ejecutable (NX)",
+            (true, _, _, true)       => "Usuario accediendo a pagina de supervisor",
+            (true, _, true, false)   => "Escritura a pagina de solo lectura",
+            (true, _, false, false)  => "Lectura denegada en pagina presente",
+        }
+    }
+}
+
 #[no_mangle]
 extern "C" fn isr_page_fault(ec: u64) {
     let cr2: u64;
@@ -503,45 +918,56 @@ extern "C" fn isr_page_fault(ec: u64) {
     c.write_at("DIRECCION FAULTING (CR2)", lp+8, 111, pal::MID);
     { let mut buf=[0u8;18]; write_glow(&mut c, fmt_hex(cr2,&mut buf), lp+8, 124, pal::PF_GOLD, pal::PF_GOLD_DIM.dim(55)); }
 
-    let cause = if cr2 < 0x1000 { "Null pointer / acceso a pagina 0" }
-                else if cr2 > 0xFFFF_8000_0000_0000 { "Acceso a espacio de kernel desde usuario" }
-                else if cr2 == f.rip { "Instruccion no ejecutable (NX bit activo)" }
-                else { "Pagina no presente o sin permisos de acceso" };
+    let pf = decode_pf_error(ec);
+    let cause = if cr2 < 0x1000 && !pf.reserved { "Null pointer / acceso a pagina 0" }
+                else { pf.cause() };
     c.write_at("CAUSA PROBABLE", lp, 158, pal::MID);
     c.write_at(cause, lp, 172, pal::PF_BLUE);
-    c.write_at("VECTOR   0x0E  (#PF)", lp, 192, pal::MID);
-    { let mut buf=[0u8;18]; c.write_at("RIP", lp, 208, pal::MID); c.write_at(fmt_hex(f.rip,&mut buf), lp+36, 208, pal::PF_BLUE); }
+
+    // Resumen: "escritura / supervisor / no presente"
+    c.write_at("ACCESO", lp, 192, pal::MID);
+    let mut ax = lp + 64;
+    for (i, part) in [pf.access(), pf.mode(), pf.page()].iter().enumerate() {
+        if i > 0 { c.write_at("/", ax, 192, pal::MID); ax += 16; }
+        c.write_at(part, ax, 192, pal::PF_GOLD);
+        ax += part.len() * 8 + 8;
+    }
+    c.write_at("VECTOR   0x0E  (#PF)", lp, 208, pal::MID);
+    { let mut buf=[0u8;18]; c.write_at("RIP", lp, 224, pal::MID); c.write_at(fmt_hex(f.rip,&mut buf), lp+36, 224, pal::PF_BLUE); }
+    { let mut sb=[0u8;64]; c.write_at(fmt_symbol(f.rip,&mut sb), lp+36, 238, pal::PF_BLUE.dim(160)); }
 
     let rx = split + 18;
     let rw = w.saturating_sub(rx + 14);
     section_title(&mut c, "ERROR CODE", rx, 28, pal::PF_GOLD.dim(200));
     { let mut buf=[0u8;18]; c.write_at(fmt_hex(ec,&mut buf), rx+110, 28, pal::PF_GOLD); }
 
-    let bits: &[(&str, &str, u64)] = &[
-        ("P",  "Presente en tabla",    1<<0), ("W",   "Operacion escritura",   1<<1),
-        ("U",  "Modo usuario (CPL=3)", 1<<2), ("R",   "Reserved write",        1<<3),
-        ("I",  "Inst. fetch (NX/XD)", 1<<4),  ("PK",  "Prot. key (MPK)",       1<<5),
-        ("SS", "Shadow stack (CET)",  1<<6),  ("SGX", "SGX access control",   1<<15),
+    // (bit, activo, rótulo si está a 1, rótulo si está a 0)
+    let bits: &[(&str, bool, &str, &str)] = &[
+        ("P",   pf.present,  "Pagina presente (permisos)",  "Pagina no presente"),
+        ("W",   pf.write,    "Escritura",                   "Lectura"),
+        ("U",   pf.user,     "Modo usuario (CPL=3)",        "Modo supervisor (CPL<3)"),
+        ("R",   pf.reserved, "Bit reservado en PTE",        "Sin bits reservados"),
+        ("I",   pf.fetch,    "Fetch de instruccion (NX)",   "Acceso a datos"),
+        ("PK",  pf.pkey,     "Prot. key (MPK)",             "Prot. key (MPK)"),
+        ("SS",  pf.shadow,   "Shadow stack (CET)",          "Shadow stack (CET)"),
+        ("SGX", pf.sgx,      "SGX access control",          "SGX access control"),
     ];
-    for (i, (name, desc, mask)) in bits.iter().enumerate() {
+    for (i, (name, set, on, off)) in bits.iter().enumerate() {
         let by = 46 + i * 20;
-        let set = (ec & mask) != 0;
+        let set = *set;
         let ic = if set { pal::PF_GREEN } else { pal::PF_GRAY };
         c.fill_rect(rx, by+1, 12, 12, ic.dim(if set{200}else{50}));
         if set { c.fill_rect(rx+3, by+4, 6, 6, ic); }
         c.write_at(name, rx+16, by+2, ic);
-        c.write_at(desc, rx+44, by+2, if set{pal::WHITE}else{pal::MID});
+        // P/W/U/I son informativos en ambos estados; el resto solo si está a 1
+        let informative = i < 5 && i != 3;
+        c.write_at(if set { on } else { off }, rx+44, by+2,
+                   if set { pal::WHITE } else if informative { pal::LIGHT } else { pal::MID });
     }
 
     let sep_y = 46 + bits.len() * 20 + 8;
     c.fill_rect(rx, sep_y, rw, 1, pal::PF_GOLD.dim(35));
-    section_title(&mut c, "CONTEXTO DE CPU", rx, sep_y+10, pal::PF_GOLD.dim(200));
-    let regs: &[(&str,u64)] = &[
-        ("CR2 ",cr2),("EC  ",ec),("RIP ",f.rip),("RSP ",f.rsp),
-        ("RFLG",f.rflags),("CR3 ",f.cr3),("RAX ",f.rax),("RBX ",f.rbx),
-        ("RCX ",f.rcx),("RDX ",f.rdx),
-    ];
-    reg_grid_ncol(&mut c, regs, rx, sep_y+24, 2, (rw.saturating_sub(12))/2, 16, pal::PF_GOLD.dim(110));
+    draw_cpu_context(&mut c, f, "CONTEXTO DE CPU", rx, sep_y+10, rw, pal::PF_GOLD.dim(110));
 
     draw_bottom_bar(&mut c, pal::PF_GOLD, pal::PF_BLUE, "#PF PAGE FAULT  |  SISTEMA DETENIDO");
     c.present(); halt_loop()
@@ -611,14 +1037,7 @@ extern "C" fn isr_gp_handler(ec: u64) {
         c.write_at(cause,tx,idx_y+34,pal::LIGHT);
     }
 
-    section_title(&mut c,"CONTEXTO DE CPU",col2_x,col_y,pal::GP_VIOLET.dim(180));
-    let cpu_regs: &[(&str,u64)] = &[
-        ("RIP ",f.rip),("RSP ",f.rsp),("RFLG",f.rflags),("CR3 ",f.cr3),
-        ("RAX ",f.rax),("RBX ",f.rbx),("RCX ",f.rcx),("RDX ",f.rdx),
-        ("RSI ",f.rsi),("RDI ",f.rdi),
-    ];
-    let cw2 = (w.saturating_sub(col2_x+20))/2;
-    reg_grid_ncol(&mut c,cpu_regs,col2_x,col_y+16,2,cw2.saturating_sub(4),16,pal::GP_MAGENTA.dim(110));
+    draw_cpu_context(&mut c,f,"CONTEXTO DE CPU",col2_x,col_y,w.saturating_sub(col2_x+20),pal::GP_MAGENTA.dim(110));
 
     draw_bottom_bar(&mut c,pal::GP_VIOLET,pal::GP_MAGENTA,"#GP GENERAL PROTECTION FAULT  |  SISTEMA DETENIDO");
     c.present(); halt_loop()
@@ -629,7 +1048,7 @@ extern "C" fn isr_gp_handler(ec: u64) {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 #[no_mangle]
-extern "C" fn isr_double_fault() {
+extern "C" fn isr_double_fault(_ec: u64) {
     unsafe {
         let v = 0xB8000usize as *mut u16;
         for i in 0..160usize { core::ptr::write_volatile(v.add(i), 0x4F20); }
@@ -671,7 +1090,18 @@ extern "C" fn isr_double_fault() {
     for (i,(t,col)) in lines.iter().enumerate() {
         if t.is_empty(){continue;} c.write_at(t,crt_cx(crt_x,crt_w,cw,t),wy(3+i),*col);
     }
-    c.fill_rect(crt_x+10,wy(3+lines.len())+ch-3,cw,2,pal::WHITE);
+    // Contexto mínimo: el stub ya capturó el frame antes de saltar aquí
+    let f = frame();
+    if f.valid != 0 {
+        let mut rb=[0u8;18]; let mut sb=[0u8;18];
+        let row = wy(3+lines.len());
+        let x0 = crt_cx(crt_x,crt_w,cw,"RIP 0x0000000000000000   RSP 0x0000000000000000");
+        c.write_at("RIP",x0,row,Color::new(0xFF,0xDD,0xDD));
+        c.write_at(fmt_hex(f.rip,&mut rb),x0+4*cw,row,pal::WHITE);
+        c.write_at("RSP",x0+25*cw,row,Color::new(0xFF,0xDD,0xDD));
+        c.write_at(fmt_hex(f.rsp,&mut sb),x0+29*cw,row,pal::WHITE);
+    }
+    c.fill_rect(crt_x+10,wy(4+lines.len())+ch-3,cw,2,pal::WHITE);
     let mut sy=crt_y; while sy<crt_y+crt_h{c.fill_rect(crt_x,sy,crt_w,1,Color::new(0x44,0,0));sy+=2;}
     for i in 0..16usize {
         let d=(16-i) as u8*14;
@@ -716,15 +1146,7 @@ extern "C" fn isr_divide_by_zero() {
     c.write_at("►  Divisor (RCX/RBX/otro) vale 0 en el momento del fallo",tx,d_y+16,pal::AMBER.dim(180));
     c.write_at("►  IDIV con resultado fuera de rango del registro destino",tx,d_y+28,pal::AMBER.dim(180));
 
-    if f.valid != 0 {
-        let reg_y=d_y+48;
-        section_title(&mut c,"REGISTROS AL MOMENTO DEL FALLO",tx,reg_y,pal::MID);
-        let regs: &[(&str,u64)] = &[
-            ("RIP ",f.rip),("RAX ",f.rax),("RDX ",f.rdx),
-            ("RCX ",f.rcx),("RBX ",f.rbx),("RSP ",f.rsp),
-        ];
-        reg_grid_ncol(&mut c,regs,tx,reg_y+16,3,(w.saturating_sub(tx+44))/3,16,pal::PANIC_ORANGE.dim(130));
-    }
+    draw_cpu_context(&mut c,f,"REGISTROS AL MOMENTO DEL FALLO",tx,d_y+48,w.saturating_sub(tx+44),pal::PANIC_ORANGE.dim(130));
     draw_bottom_bar(&mut c,pal::PANIC_ORANGE,pal::PANIC_RED,"#DE DIVIDE BY ZERO  |  SISTEMA DETENIDO");
     c.present(); halt_loop()
 }
@@ -750,12 +1172,7 @@ extern "C" fn isr_bound_range() {
     c.write_at("Indice fuera del rango definido por la instruccion BOUND.",tx,ty+38,pal::LIGHT);
     c.write_at("VECTOR  0x05  (#BR)",tx,ty+52,pal::MID);
 
-    if f.valid != 0 {
-        let d_y=ty+70;
-        section_title(&mut c,"REGISTROS",tx,d_y,pal::MID);
-        let regs: &[(&str,u64)] = &[("RIP ",f.rip),("RAX ",f.rax),("RCX ",f.rcx),("RSP ",f.rsp)];
-        reg_grid_ncol(&mut c,regs,tx,d_y+16,2,200,16,pal::PF_BLUE.dim(130));
-    }
+    draw_cpu_context(&mut c,f,"REGISTROS",tx,ty+70,w.saturating_sub(tx+44),pal::PF_BLUE.dim(130));
     draw_bottom_bar(&mut c,pal::PF_BLUE,Color::new(0,0x44,0x88),"#BR BOUND RANGE  |  SISTEMA DETENIDO");
     c.present(); halt_loop()
 }
@@ -787,12 +1204,7 @@ extern "C" fn isr_ud_handler() {
     c.write_at("►  Binario para ISA superior (SSE4/AVX en CPU sin soporte)",tx,d_y+28,pal::GP_PINK.dim(180));
     c.write_at("►  Puntero de funcion invalido / salto a datos corrompidos",tx,d_y+40,pal::GP_PINK.dim(180));
 
-    if f.valid != 0 {
-        let reg_y=d_y+58;
-        section_title(&mut c,"CONTEXTO AL MOMENTO DEL FALLO",tx,reg_y,pal::MID);
-        let regs: &[(&str,u64)] = &[("RIP ",f.rip),("RSP ",f.rsp),("RAX ",f.rax),("RBX ",f.rbx)];
-        reg_grid_ncol(&mut c,regs,tx,reg_y+16,2,200,16,pal::GP_VIOLET.dim(130));
-    }
+    draw_cpu_context(&mut c,f,"CONTEXTO AL MOMENTO DEL FALLO",tx,d_y+58,w.saturating_sub(tx+44),pal::GP_VIOLET.dim(130));
     draw_bottom_bar(&mut c,pal::GP_VIOLET,pal::GP_MAGENTA,"#UD INVALID OPCODE  |  SISTEMA DETENIDO");
     c.present(); halt_loop()
 }
@@ -817,18 +1229,19 @@ extern "C" fn isr_generic_handler() {
     c.fill_rect(tx,ty+30,w-tx-44,1,pal::AMBER.dim(45));
     c.write_at("Excepcion de CPU no manejada especificamente por este kernel.",tx,ty+38,pal::LIGHT);
 
-    if f.valid != 0 {
-        let reg_y=ty+56;
-        section_title(&mut c,"CONTEXTO COMPLETO DE CPU",tx,reg_y,pal::MID);
-        let regs: &[(&str,u64)] = &[
-            ("RIP ",f.rip),("RSP ",f.rsp),("RFLG",f.rflags),("CR3 ",f.cr3),
-            ("RAX ",f.rax),("RBX ",f.rbx),("RCX ",f.rcx),("RDX ",f.rdx),
-            ("RSI ",f.rsi),("RDI ",f.rdi),("R8  ",f.r8),("R9  ",f.r9),
-            ("R10 ",f.r10),("R11 ",f.r11),("R12 ",f.r12),("R13 ",f.r13),
-            ("R14 ",f.r14),("R15 ",f.r15),
-        ];
-        reg_grid_ncol(&mut c,regs,tx,reg_y+16,3,(w.saturating_sub(tx+44))/3,16,pal::AMBER.dim(130));
+    // "VECTOR  0x0B  (#NP)   EC  0x..."
+    if f.valid != 0 && f.vector != VECTOR_NONE {
+        let mut vb=[0u8;18]; let mut eb=[0u8;18];
+        let v = fmt_hex(f.vector,&mut vb);
+        c.write_at("VECTOR",tx,ty+52,pal::MID);
+        c.write_at("0x",tx+64,ty+52,pal::AMBER);
+        c.write_at(&v[v.len()-2..],tx+80,ty+52,pal::AMBER);
+        c.write_at(vector_name(f.vector),tx+104,ty+52,pal::AMBER);
+        c.write_at("EC",tx+144,ty+52,pal::MID);
+        c.write_at(fmt_hex(f.error,&mut eb),tx+168,ty+52,pal::AMBER);
     }
+
+    draw_cpu_context(&mut c,f,"CONTEXTO COMPLETO DE CPU",tx,ty+70,w.saturating_sub(tx+44),pal::AMBER.dim(130));
     draw_bottom_bar(&mut c,pal::AMBER,pal::MID,"CPU EXCEPTION  |  SISTEMA DETENIDO");
     c.present(); halt_loop()
 }
@@ -837,20 +1250,381 @@ extern "C" fn isr_generic_handler() {
 //  INTRÍNSECOS DE MEMORIA
 // ═══════════════════════════════════════════════════════════════════════════════
 
-#[no_mangle]
+// memcpy/memset usan SSE2 en bloques de 16 B cuando hay suficiente longitud:
+// los bytes hasta alinear el destino y la cola van por el camino escalar.
+// Con el origen también alineado la carga es `movdqa`; si no, `movdqu`. Las
+// copias de más de SSE_NT_MIN bytes usan `movntdq` (no contaminan la caché)
+// seguido de `sfence`.
+//
+// SSE2 se comprueba una vez en tiempo de ejecución (CPUID.1:EDX[26] y
+// CR4.OSFXSR, sin el cual las instrucciones SSE lanzan #UD) porque estas
+// funciones pueden llamarse antes de `HardwareInfo::detect_all`. Estado SSE:
+// los bucles están en asm! con los xmm como clobber, así que el compilador
+// preserva lo que el llamador tuviera vivo; la única IRQ (PIT) no toca xmm.
+//
+// Los bucles escalares usan accesos volátiles para que LLVM no los convierta
+// en una llamada a memcpy/memset (recursión infinita).
+
+/// Por debajo de esto no compensa preparar los bloques SSE.
+const SSE_MIN:    usize = 64;
+/// A partir de aquí, stores no temporales.
+const SSE_NT_MIN: usize = 32 * 1024;
+
+/// 0 = sin comprobar, 1 = sin SSE2, 2 = SSE2 utilizable.
+static SSE2_STATE: AtomicU8 = AtomicU8::new(0);
+
+fn sse2_usable() -> bool {
+    match SSE2_STATE.load(Ordering::Relaxed) {
+        0 => {
+            let edx: u32;
+            let cr4: u64;
+            unsafe {
+                core::arch::asm!(
+                    "push rbx", "cpuid", "pop rbx",
+                    inout("eax") 1u32 => _, inout("ecx") 0u32 => _, out("edx") edx,
+                    options(nomem),
+                );
+                core::arch::asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack));
+            }
+            let ok = edx & (1 << 26) != 0 && cr4 & (1 << 9) != 0;
+            SSE2_STATE.store(if ok { 2 } else { 1 }, Ordering::Relaxed);
+            ok
+        }
+        st => st == 2,
+    }
+}
+
+/// Bytes hasta que `p` quede alineado a 16 (como mucho `n`).
+#[inline(always)]
+fn head_len(p: usize, n: usize) -> usize { ((16 - (p & 15)) & 15).min(n) }
+
+unsafe fn copy_bytes(d: *mut u8, s: *const u8, n: usize) {
+    for i in 0..n { core::ptr::write_volatile(d.add(i), core::ptr::read_volatile(s.add(i))); }
+}
+
+unsafe fn fill_bytes(d: *mut u8, v: u8, n: usize) {
+    for i in 0..n { core::ptr::write_volatile(d.add(i), v); }
+}
+
+/// `blocks` × 16 B de `s` a `d` (`d` alineado a 16).
+unsafe fn copy_blocks(d: *mut u8, s: *const u8, blocks: usize, nt: bool) {
+    macro_rules! copy_loop {
+        ($load:literal, $store:literal) => {
+            core::arch::asm!(
+                "2:",
+                concat!($load, " {x}, [{s}]"),
+                concat!($store, " [{d}], {x}"),
+                "add {s}, 16",
+                "add {d}, 16",
+                "dec {c}",
+                "jnz 2b",
+                s = inout(reg) s => _, d = inout(reg) d => _, c = inout(reg) blocks => _,
+                x = out(xmm_reg) _,
+                options(nostack),
+            )
+        };
+    }
+    match ((s as usize) & 15 == 0, nt) {
+        (true,  false) => copy_loop!("movdqa", "movdqa"),
+        (true,  true)  => copy_loop!("movdqa", "movntdq"),
+        (false, false) => copy_loop!("movdqu", "movdqa"),
+        (false, true)  => copy_loop!("movdqu", "movntdq"),
+    }
+    if nt { core::arch::asm!("sfence", options(nostack)); }
+}
+
+/// `blocks` × 16 B del byte `v` en `d` (`d` alineado a 16).
+unsafe fn fill_blocks(d: *mut u8, v: u8, blocks: usize, nt: bool) {
+    let pat = 0x0101_0101_0101_0101u64 * v as u64;
+    macro_rules! fill_loop {
+        ($store:literal) => {
+            core::arch::asm!(
+                "movq {x}, {p}",
+                "punpcklqdq {x}, {x}",
+                "2:",
+                concat!($store, " [{d}], {x}"),
+                "add {d}, 16",
+                "dec {c}",
+                "jnz 2b",
+                p = in(reg) pat, d = inout(reg) d => _, c = inout(reg) blocks => _,
+                x = out(xmm_reg) _,
+                options(nostack),
+            )
+        };
+    }
+    if nt { fill_loop!("movntdq"); core::arch::asm!("sfence", options(nostack)); }
+    else  { fill_loop!("movdqa"); }
+}
+
+#[cfg_attr(not(test), no_mangle)]
 pub unsafe extern "C" fn memset(s: *mut u8, cv: i32, n: usize) -> *mut u8 {
-    for i in 0..n { core::ptr::write_volatile(s.add(i), cv as u8); } s
+    let v = cv as u8;
+    if n < SSE_MIN || !sse2_usable() { fill_bytes(s, v, n); return s; }
+    let head   = head_len(s as usize, n);
+    let blocks = (n - head) / 16;
+    fill_bytes(s, v, head);
+    fill_blocks(s.add(head), v, blocks, n >= SSE_NT_MIN);
+    let done = head + blocks * 16;
+    fill_bytes(s.add(done), v, n - done);
+    s
 }
-#[no_mangle]
+#[cfg_attr(not(test), no_mangle)]
 pub unsafe extern "C" fn memcpy(d: *mut u8, s: *const u8, n: usize) -> *mut u8 {
-    for i in 0..n { core::ptr::write_volatile(d.add(i), core::ptr::read_volatile(s.add(i))); } d
+    if n < SSE_MIN || !sse2_usable() { copy_bytes(d, s, n); return d; }
+    let head   = head_len(d as usize, n);
+    let blocks = (n - head) / 16;
+    copy_bytes(d, s, head);
+    copy_blocks(d.add(head), s.add(head), blocks, n >= SSE_NT_MIN);
+    let done = head + blocks * 16;
+    copy_bytes(d.add(done), s.add(done), n - done);
+    d
 }
-#[no_mangle]
+#[cfg_attr(not(test), no_mangle)]
 pub unsafe extern "C" fn memmove(d: *mut u8, s: *const u8, n: usize) -> *mut u8 {
     if (d as usize) <= (s as usize) { memcpy(d, s, n) }
     else { let mut i=n; while i>0 { i-=1; core::ptr::write_volatile(d.add(i), core::ptr::read_volatile(s.add(i))); } d }
 }
-#[no_mangle]
+#[cfg_attr(not(test), no_mangle)]
 pub unsafe extern "C" fn memcmp(a: *const u8, b: *const u8, n: usize) -> i32 {
     for i in 0..n { let d=*a.add(i) as i32 - *b.add(i) as i32; if d!=0{return d;} } 0
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pila simulada de 64 palabras en [STACK, STACK + 512).
+    const STACK: u64 = 0x10_0000;
+    const TEXT:  (u64, u64) = (0x20_0000, 0x30_0000);
+
+    fn range() -> (u64, u64) { (STACK, STACK + 64 * 8) }
+
+    /// Lector que falla el test si se sale de la pila simulada.
+    fn reader(mem: &[u64; 64]) -> impl Fn(u64) -> u64 + '_ {
+        move |a| {
+            assert!(a >= STACK && a + 8 <= STACK + 64 * 8 && a % 8 == 0, "lectura fuera de la pila: {a:#x}");
+            mem[((a - STACK) / 8) as usize]
+        }
+    }
+
+    /// Frame en la palabra `w`: rbp guardado y dirección de retorno.
+    fn frame(mem: &mut [u64; 64], w: usize, saved_rbp: u64, ret: u64) {
+        mem[w] = saved_rbp;
+        mem[w + 1] = ret;
+    }
+
+    fn at(w: usize) -> u64 { STACK + w as u64 * 8 }
+
+    #[test]
+    fn pf_error_bits_decode_one_by_one() {
+        assert_eq!(decode_pf_error(0), PfFlags::default());
+        let bits = [(0, "present"), (1, "write"), (2, "user"), (3, "reserved"),
+                    (4, "fetch"), (5, "pkey"), (6, "shadow"), (15, "sgx")];
+        for (bit, name) in bits {
+            let f = decode_pf_error(1 << bit);
+            let set = [f.present, f.write, f.user, f.reserved, f.fetch, f.pkey, f.shadow, f.sgx];
+            assert_eq!(set.iter().filter(|&&b| b).count(), 1, "{name}");
+        }
+        // Bits sin significado se ignoran
+        assert_eq!(decode_pf_error(1 << 7 | 1 << 14 | 1 << 32), PfFlags::default());
+    }
+
+    #[test]
+    fn pf_cause_for_each_combination() {
+        // P=bit0 W=bit1 U=bit2 R=bit3 I=bit4 PK=bit5 SS=bit6 SGX=bit15
+        let cases: &[(u64, &str)] = &[
+            (0b00000, "Lectura de pagina no mapeada"),
+            (0b00010, "Escritura a pagina no mapeada"),
+            (0b00100, "Lectura de pagina no mapeada"),
+            (0b00110, "Escritura a pagina no mapeada"),
+            (0b10000, "Salto a direccion no mapeada"),
+            (0b10100, "Salto a direccion no mapeada"),
+            (0b00001, "Lectura denegada en pagina presente"),
+            (0b00011, "Escritura a pagina de solo lectura"),
+            (0b00101, "Usuario accediendo a pagina de supervisor"),
+            (0b00111, "Usuario accediendo a pagina de supervisor"),
+            (0b10001, "Ejecucion en pagina no ejecutable (NX)"),
+            (0b10101, "Ejecucion en pagina no ejecutable (NX)"),
+            (0b01000, "Bit reservado en tabla de paginas (PTE corrupta)"),
+            (0b01011, "Bit reservado en tabla de paginas (PTE corrupta)"),
+            (1 << 5 | 1,      "Proteccion por clave (PKU) denegada"),
+            (1 << 6 | 0b11,   "Acceso invalido a shadow stack (CET)"),
+            (1 << 15 | 1,     "Violacion de control de acceso SGX"),
+            (1 << 5 | 1 << 3, "Bit reservado en tabla de paginas (PTE corrupta)"),
+        ];
+        for &(ec, cause) in cases {
+            assert_eq!(decode_pf_error(ec).cause(), cause, "ec={ec:#x}");
+        }
+    }
+
+    #[test]
+    fn pf_access_mode_and_page_labels() {
+        let f = decode_pf_error(0b10111);
+        assert_eq!((f.access(), f.mode(), f.page()), ("fetch", "usuario", "presente"));
+        let f = decode_pf_error(0b00010);
+        assert_eq!((f.access(), f.mode(), f.page()), ("escritura", "supervisor", "no presente"));
+        assert_eq!(decode_pf_error(0).access(), "lectura");
+    }
+
+    #[test]
+    fn walk_follows_a_valid_chain() {
+        let mut mem = [0u64; 64];
+        frame(&mut mem, 2, at(10), TEXT.0 + 0x10);
+        frame(&mut mem, 10, at(20), TEXT.0 + 0x20);
+        frame(&mut mem, 20, 0, TEXT.0 + 0x30);   // rbp = 0: fin de la cadena
+        let (out, n) = walk_frames(at(2), range(), TEXT, reader(&mem));
+        assert_eq!(&out[..n], &[TEXT.0 + 0x10, TEXT.0 + 0x20, TEXT.0 + 0x30]);
+    }
+
+    #[test]
+    fn walk_stops_on_a_cycle() {
+        let mut mem = [0u64; 64];
+        frame(&mut mem, 4, at(8), TEXT.0 + 1);
+        frame(&mut mem, 8, at(4), TEXT.0 + 2);   // vuelve hacia abajo
+        let (out, n) = walk_frames(at(4), range(), TEXT, reader(&mem));
+        assert_eq!(&out[..n], &[TEXT.0 + 1, TEXT.0 + 2]);
+        // Un frame que se apunta a sí mismo tampoco avanza
+        frame(&mut mem, 4, at(4), TEXT.0 + 1);
+        assert_eq!(walk_frames(at(4), range(), TEXT, reader(&mem)).1, 1);
+    }
+
+    #[test]
+    fn walk_rejects_misaligned_or_out_of_range_rbp() {
+        let mut mem = [0u64; 64];
+        frame(&mut mem, 2, at(10) + 4, TEXT.0 + 0x10);  // siguiente rbp desalineado
+        frame(&mut mem, 10, 0, TEXT.0 + 0x20);
+        assert_eq!(walk_frames(at(2), range(), TEXT, reader(&mem)).1, 1);
+        assert_eq!(walk_frames(at(2) + 3, range(), TEXT, reader(&mem)).1, 0);
+        assert_eq!(walk_frames(STACK - 8, range(), TEXT, reader(&mem)).1, 0);
+        // El frame tiene que caber entero (rbp + retorno) bajo el tope
+        assert_eq!(walk_frames(at(63), range(), TEXT, reader(&mem)).1, 0);
+        assert_eq!(walk_frames(0, range(), TEXT, reader(&mem)).1, 0);
+    }
+
+    #[test]
+    fn walk_stops_at_return_outside_text() {
+        let mut mem = [0u64; 64];
+        frame(&mut mem, 2, at(10), TEXT.0 + 0x10);
+        frame(&mut mem, 10, at(20), TEXT.1);      // fin exclusivo
+        frame(&mut mem, 20, 0, TEXT.0 + 0x30);
+        assert_eq!(walk_frames(at(2), range(), TEXT, reader(&mem)).1, 1);
+    }
+
+    #[test]
+    fn walk_caps_depth_at_backtrace_max() {
+        let mut mem = [0u64; 64];
+        for k in 0..20 {
+            frame(&mut mem, k * 2, at(k * 2 + 2), TEXT.0 + k as u64);
+        }
+        let (out, n) = walk_frames(at(0), range(), TEXT, reader(&mem));
+        assert_eq!(n, BACKTRACE_MAX);
+        assert_eq!(out[BACKTRACE_MAX - 1], TEXT.0 + BACKTRACE_MAX as u64 - 1);
+    }
+
+    #[test]
+    fn stack_dump_stays_inside_the_stack() {
+        let (lo, hi) = range();
+        assert_eq!(stack_dump_len(lo, (lo, hi)), STACK_DUMP_BYTES);
+        assert_eq!(stack_dump_len(hi - 8, (lo, hi)), 8);
+        assert_eq!(stack_dump_len(hi - 1, (lo, hi)), 1);
+        assert_eq!(stack_dump_len(hi - STACK_DUMP_BYTES as u64, (lo, hi)), STACK_DUMP_BYTES);
+    }
+
+    #[test]
+    fn stack_dump_outside_the_stack_reads_nothing() {
+        let (lo, hi) = range();
+        assert_eq!(stack_dump_len(lo - 8, (lo, hi)), 0);
+        assert_eq!(stack_dump_len(hi, (lo, hi)), 0);
+        assert_eq!(stack_dump_len(0, (lo, hi)), 0);
+        assert_eq!(stack_dump_len(u64::MAX, (lo, hi)), 0);
+        // Pila vacía o sin detectar
+        assert_eq!(stack_dump_len(lo, (lo, lo)), 0);
+    }
+
+    /// Offsets de todos los campos del CrashFrame, en orden de declaración.
+    fn crash_frame_fields() -> [(&'static str, usize); 22] {
+        use core::mem::offset_of;
+        [
+            ("rip",    offset_of!(CrashFrame, rip)),
+            ("rsp",    offset_of!(CrashFrame, rsp)),
+            ("rflags", offset_of!(CrashFrame, rflags)),
+            ("cr3",    offset_of!(CrashFrame, cr3)),
+            ("rax",    offset_of!(CrashFrame, rax)),
+            ("rbx",    offset_of!(CrashFrame, rbx)),
+            ("rcx",    offset_of!(CrashFrame, rcx)),
+            ("rdx",    offset_of!(CrashFrame, rdx)),
+            ("rsi",    offset_of!(CrashFrame, rsi)),
+            ("rdi",    offset_of!(CrashFrame, rdi)),
+            ("r8",     offset_of!(CrashFrame, r8)),
+            ("r9",     offset_of!(CrashFrame, r9)),
+            ("r10",    offset_of!(CrashFrame, r10)),
+            ("r11",    offset_of!(CrashFrame, r11)),
+            ("r12",    offset_of!(CrashFrame, r12)),
+            ("r13",    offset_of!(CrashFrame, r13)),
+            ("r14",    offset_of!(CrashFrame, r14)),
+            ("r15",    offset_of!(CrashFrame, r15)),
+            ("rbp",    offset_of!(CrashFrame, rbp)),
+            ("valid",  offset_of!(CrashFrame, valid)),
+            ("vector", offset_of!(CrashFrame, vector)),
+            ("error",  offset_of!(CrashFrame, error)),
+        ]
+    }
+
+    #[test]
+    fn crash_frame_layout_is_pinned() {
+        let want = [
+            ("rip", 0), ("rsp", 8), ("rflags", 16), ("cr3", 24),
+            ("rax", 32), ("rbx", 40), ("rcx", 48), ("rdx", 56), ("rsi", 64), ("rdi", 72),
+            ("r8", 80), ("r9", 88), ("r10", 96), ("r11", 104),
+            ("r12", 112), ("r13", 120), ("r14", 128), ("r15", 136),
+            ("rbp", 144), ("valid", 152), ("vector", 160), ("error", 168),
+        ];
+        assert_eq!(crash_frame_fields(), want);
+        assert_eq!(core::mem::size_of::<CrashFrame>(), 176);
+    }
+
+    /// Campo que CAPTURE_FRAME guarda al copiar `rax` cargado desde `src`
+    /// (con `%1` = offset del RIP en el frame del CPU).
+    fn field_from_rax_source(src: &str) -> &'static str {
+        match src {
+            "[rsp + 24 + %1]"      => "rip",
+            "[rsp + 24 + %1 + 24]" => "rsp",
+            "[rsp + 24 + %1 + 16]" => "rflags",
+            "cr3"                  => "cr3",
+            "[rsp + 16]"           => "rax",   // push rax (el primero de 3)
+            "[rsp + 8]"            => "rcx",
+            "[rsp + 0]"            => "rdx",
+            "[rsp + 24]"           => "error", // EC, justo encima de los push
+            _ => panic!("origen desconocido en CAPTURE_FRAME: {}", src),
+        }
+    }
+
+    #[test]
+    fn isr_stub_stores_match_crash_frame_offsets() {
+        // Recorre CAPTURE_FRAME en isr.asm y reconstruye campo → offset
+        let asm = include_str!("isr.asm");
+        let start = asm.find("%macro CAPTURE_FRAME").unwrap();
+        let end   = start + asm[start..].find("%endmacro").unwrap();
+        let mut stored: alloc::vec::Vec<(&str, usize)> = alloc::vec::Vec::new();
+        let mut rax_src = "";
+        for line in asm[start..end].lines() {
+            let code = line.split(';').next().unwrap().trim();
+            let Some(ops) = code.strip_prefix("mov") else { continue };
+            let (dst, src) = ops.split_once(',').unwrap();
+            let (dst, src) = (dst.trim(), src.trim());
+            if dst == "rax" { rax_src = src; continue; }
+            let Some(off) = dst.split("[crash_frame + ").nth(1) else { continue };
+            let off: usize = off.trim_end_matches(']').parse().unwrap();
+            let field = match (dst, src) {
+                (_, "rax")                                      => field_from_rax_source(rax_src),
+                (d, "%2") if d.starts_with("qword")             => "vector",
+                (d, "1")  if d.starts_with("byte")              => "valid",
+                (_, reg)                                        => reg,
+            };
+            stored.push((field, off));
+        }
+        // Cada campo se escribe exactamente una vez y en su offset
+        stored.sort_by_key(|&(_, off)| off);
+        assert_eq!(stored, crash_frame_fields());
+    }
+}
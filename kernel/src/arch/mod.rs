@@ -1,4 +1,6 @@
 pub mod halt;
 pub mod idt;
 pub mod hardware;
-pub mod isr_handlers;
\ No newline at end of file
+pub mod isr_handlers;
+pub mod paging;
+pub mod symbols;
@@ -0,0 +1,467 @@
+// kernel/src/arch/paging.rs — PORTIX paginación del kernel
+//
+// Sustituye las tablas provisionales de stage2 (0x1000..0x5000) por unas
+// propias y lleva la cuenta de cada región mapeada (comando `vmmap`):
+//
+//   0 .. IDENTITY_LIMIT   RAM en identidad. Solo las páginas de .text son
+//                         ejecutables; como .text no empieza ni acaba en
+//                         frontera de 2 MiB, esos bordes van en páginas de
+//                         4 KiB y el resto en páginas de 2 MiB.
+//   LFB                   Framebuffer en identidad (si está por encima del
+//                         primer bloque), NX.
+//   MMIO_BASE ..          Ventana de `map_mmio` para BARs y demás registros
+//                         de dispositivo: sin caché (PCD|PWT) y NX.
+//
+// Las tablas salen de un pool estático en .bss, por debajo de
+// IDENTITY_LIMIT, así que su dirección virtual coincide con la física y
+// `translate` recorre igual las tablas de stage2 que las propias.
+//
+// El bit NX solo se escribe si la CPU lo anuncia (CPUID 8000_0001h EDX.20)
+// y tras activar EFER.NXE: sin eso es un bit reservado y cualquier acceso a
+// la página daría #PF.
+
+use core::ptr::{addr_of, addr_of_mut};
+
+use crate::mem::IDENTITY_LIMIT;
+
+pub const PAGE_4K: u64 = 0x1000;
+pub const PAGE_2M: u64 = 0x20_0000;
+
+const P_PRESENT: u64 = 1 << 0;
+const P_WRITE:   u64 = 1 << 1;
+const P_PWT:     u64 = 1 << 3;
+const P_PCD:     u64 = 1 << 4;
+const P_HUGE:    u64 = 1 << 7;
+const P_NX:      u64 = 1 << 63;
+const ADDR_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+const RAM_FLAGS:  u64 = P_PRESENT | P_WRITE;
+const FB_FLAGS:   u64 = P_PRESENT | P_WRITE | P_NX;
+const MMIO_FLAGS: u64 = P_PRESENT | P_WRITE | P_PCD | P_PWT | P_NX;
+
+/// Inicio de la ventana MMIO: entrada 511 del PML4, los últimos 512 GiB
+/// del espacio canónico.
+pub const MMIO_BASE: u64 = 0xFFFF_FF80_0000_0000;
+const MMIO_SIZE:     u64 = 1 << 39;
+/// Direcciones físicas de 52 bits como máximo.
+const PHYS_LIMIT:    u64 = 1 << 52;
+
+/// Tablas de 4 KiB disponibles (PML4 + PDPT + PD + PT de todas las regiones).
+pub const POOL_TABLES: usize = 64;
+pub const MAX_REGIONS: usize = 32;
+
+const EFER:     u32 = 0xC000_0080;
+const EFER_NXE: u64 = 1 << 11;
+
+extern "C" {
+    static __text_start: u8;
+    static __text_end:   u8;
+}
+
+#[repr(C, align(4096))]
+#[derive(Clone, Copy)]
+struct Table([u64; 512]);
+
+static mut POOL: [Table; POOL_TABLES] = [Table([0; 512]); POOL_TABLES];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RegionKind { Identity, Framebuffer, Mmio }
+
+impl RegionKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            RegionKind::Identity    => "RAM identidad",
+            RegionKind::Framebuffer => "Framebuffer",
+            RegionKind::Mmio        => "MMIO",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Region {
+    pub virt:     u64,
+    pub phys:     u64,
+    pub size:     u64,
+    pub kind:     RegionKind,
+    /// Flags de las entradas hoja (sin P_HUGE), ya sin NX si no hay soporte.
+    pub flags:    u64,
+    pub pages_2m: u32,
+    pub pages_4k: u32,
+}
+
+impl Region {
+    const EMPTY: Region = Region {
+        virt: 0, phys: 0, size: 0, kind: RegionKind::Identity, flags: 0, pages_2m: 0, pages_4k: 0,
+    };
+    pub fn writable(&self)   -> bool { self.flags & P_WRITE != 0 }
+    pub fn executable(&self) -> bool { self.flags & P_NX == 0 }
+    pub fn uncached(&self)   -> bool { self.flags & P_PCD != 0 }
+}
+
+struct Paging {
+    /// Dirección física del PML4 propio (0 hasta `init`).
+    pml4:      u64,
+    used:      usize,
+    regions:   [Region; MAX_REGIONS],
+    count:     usize,
+    /// Siguiente desplazamiento libre dentro de la ventana MMIO.
+    mmio_next: u64,
+    nx:        bool,
+    active:    bool,
+}
+
+static mut PAGING: Paging = Paging {
+    pml4: 0, used: 0, regions: [Region::EMPTY; MAX_REGIONS], count: 0,
+    mmio_next: 0, nx: false, active: false,
+};
+
+/// Índices (PML4, PDPT, PD, PT) de `virt`: 9 bits por nivel a partir del
+/// bit 39, 12 y 21 son los desplazamientos dentro de páginas de 4 KiB y 2 MiB.
+pub const fn table_indices(virt: u64) -> [usize; 4] {
+    [
+        ((virt >> 39) & 0x1FF) as usize,
+        ((virt >> 30) & 0x1FF) as usize,
+        ((virt >> 21) & 0x1FF) as usize,
+        ((virt >> 12) & 0x1FF) as usize,
+    ]
+}
+
+/// Los bits 63..47 deben ser copia del bit 47.
+pub const fn is_canonical(virt: u64) -> bool {
+    let top = virt >> 47;
+    top == 0 || top == 0x1_FFFF
+}
+
+const fn align_up(v: u64, a: u64) -> u64 { (v + a - 1) & !(a - 1) }
+
+fn nx_supported() -> bool {
+    let (max, edx): (u32, u32);
+    unsafe {
+        core::arch::asm!(
+            "push rbx", "cpuid", "pop rbx",
+            inout("eax") 0x8000_0000u32 => max,
+            out("ecx") _, out("edx") _,
+            options(nostack, nomem)
+        );
+        if max < 0x8000_0001 { return false; }
+        core::arch::asm!(
+            "push rbx", "cpuid", "pop rbx",
+            inout("eax") 0x8000_0001u32 => _,
+            out("ecx") _, out("edx") edx,
+            options(nostack, nomem)
+        );
+    }
+    edx & (1 << 20) != 0
+}
+
+unsafe fn rdmsr(msr: u32) -> u64 {
+    let (lo, hi): (u32, u32);
+    core::arch::asm!("rdmsr", in("ecx") msr, out("eax") lo, out("edx") hi, options(nostack, nomem));
+    (hi as u64) << 32 | lo as u64
+}
+
+unsafe fn wrmsr(msr: u32, v: u64) {
+    core::arch::asm!("wrmsr", in("ecx") msr, in("eax") v as u32, in("edx") (v >> 32) as u32,
+                     options(nostack, nomem));
+}
+
+pub fn cr3() -> u64 {
+    let v: u64;
+    unsafe { core::arch::asm!("mov {}, cr3", out(reg) v, options(nostack, nomem, preserves_flags)); }
+    v
+}
+
+/// Rango [inicio, fin) de la sección .text del kernel (símbolos del linker).
+pub fn kernel_text() -> (u64, u64) {
+    (addr_of!(__text_start) as u64, addr_of!(__text_end) as u64)
+}
+
+impl Paging {
+    fn alloc_table(&mut self) -> Result<u64, &'static str> {
+        if self.used == POOL_TABLES { return Err("sin tablas de paginas libres"); }
+        let t = unsafe { &mut *addr_of_mut!(POOL[self.used]) };
+        t.0 = [0; 512];
+        self.used += 1;
+        Ok(t as *mut Table as u64)
+    }
+
+    /// Tabla que contiene la entrada de `virt` tras bajar `levels` niveles
+    /// desde el PML4 (2 = PD, 3 = PT), creando las intermedias que falten.
+    fn table_for(&mut self, virt: u64, levels: usize) -> Result<*mut u64, &'static str> {
+        let idx = table_indices(virt);
+        let mut table = self.pml4 as *mut u64;
+        for &i in &idx[..levels] {
+            let e = unsafe { &mut *table.add(i) };
+            if *e & P_PRESENT == 0 {
+                *e = self.alloc_table()? | P_PRESENT | P_WRITE;
+            } else if *e & P_HUGE != 0 {
+                return Err("la region solapa una pagina grande ya mapeada");
+            }
+            table = (*e & ADDR_MASK) as *mut u64;
+        }
+        Ok(table)
+    }
+
+    /// Mapea `size` bytes (múltiplo de 4 KiB) de `phys` en `virt`. Usa una
+    /// página de 2 MiB donde ambas direcciones están alineadas y queda sitio,
+    /// y páginas de 4 KiB en los bordes. Devuelve (páginas 2 MiB, 4 KiB).
+    fn map_range(&mut self, virt: u64, phys: u64, size: u64, flags: u64) -> Result<(u32, u32), &'static str> {
+        let (mut v, mut p, end) = (virt, phys, virt + size);
+        let (mut n2, mut n4) = (0u32, 0u32);
+        while v < end {
+            let huge = v.is_multiple_of(PAGE_2M) && p.is_multiple_of(PAGE_2M) && end - v >= PAGE_2M;
+            let (table, i, entry, step) = if huge {
+                (self.table_for(v, 2)?, table_indices(v)[2], p | flags | P_HUGE, PAGE_2M)
+            } else {
+                (self.table_for(v, 3)?, table_indices(v)[3], p | flags, PAGE_4K)
+            };
+            let e = unsafe { &mut *table.add(i) };
+            if *e & P_PRESENT != 0 { return Err("direccion virtual ya mapeada"); }
+            *e = entry;
+            if self.active {
+                unsafe { core::arch::asm!("invlpg [{}]", in(reg) v, options(nostack, preserves_flags)); }
+            }
+            if huge { n2 += 1 } else { n4 += 1 }
+            v += step;
+            p += step;
+        }
+        Ok((n2, n4))
+    }
+
+    fn map_region(&mut self, virt: u64, phys: u64, size: u64, flags: u64, kind: RegionKind) -> Result<(), &'static str> {
+        if size == 0 { return Ok(()); }
+        if self.count == MAX_REGIONS { return Err("tabla de regiones llena"); }
+        let flags = if self.nx { flags } else { flags & !P_NX };
+        let (pages_2m, pages_4k) = self.map_range(virt, phys, size, flags)?;
+        self.regions[self.count] = Region { virt, phys, size, kind, flags, pages_2m, pages_4k };
+        self.count += 1;
+        Ok(())
+    }
+}
+
+/// Construye las tablas propias y carga CR3. Si algo falla se siguen usando
+/// las de stage2 y `map_mmio` no estará disponible.
+pub fn init() -> Result<(), &'static str> {
+    let pg = unsafe { &mut *addr_of_mut!(PAGING) };
+    if pg.active { return Ok(()); }
+
+    pg.nx = nx_supported();
+    if pg.nx {
+        unsafe { wrmsr(EFER, rdmsr(EFER) | EFER_NXE); }
+    }
+    pg.pml4 = pg.alloc_table()?;
+
+    let limit   = IDENTITY_LIMIT as u64;
+    let text_lo = (addr_of!(__text_start) as u64 & !(PAGE_4K - 1)).min(limit);
+    let text_hi = align_up(addr_of!(__text_end) as u64, PAGE_4K).min(limit);
+    pg.map_region(0,       0,       text_lo,           RAM_FLAGS | P_NX, RegionKind::Identity)?;
+    pg.map_region(text_lo, text_lo, text_hi - text_lo, RAM_FLAGS,        RegionKind::Identity)?;
+    pg.map_region(text_hi, text_hi, limit - text_hi,   RAM_FLAGS | P_NX, RegionKind::Identity)?;
+
+    let (lfb, fb_len) = crate::graphics::driver::framebuffer::boot_lfb();
+    if lfb >= limit && fb_len > 0 {
+        let lo = lfb & !(PAGE_2M - 1);
+        let hi = align_up(lfb + fb_len as u64, PAGE_2M);
+        pg.map_region(lo, lo, hi - lo, FB_FLAGS, RegionKind::Framebuffer)?;
+    }
+
+    // Sin `nomem`: las escrituras en las tablas deben estar hechas antes
+    unsafe { core::arch::asm!("mov cr3, {}", in(reg) pg.pml4, options(nostack, preserves_flags)); }
+    pg.active = true;
+    Ok(())
+}
+
+/// Mapea `size` bytes de registros de dispositivo desde la dirección física
+/// `phys` y devuelve su dirección virtual. Un rango ya cubierto por otro
+/// `map_mmio` reutiliza ese mapeo. Las regiones de 2 MiB o más se colocan
+/// con la misma alineación que la física para poder usar páginas grandes.
+pub fn map_mmio(phys: u64, size: u64) -> Result<u64, &'static str> {
+    let pg = unsafe { &mut *addr_of_mut!(PAGING) };
+    if !pg.active { return Err("paginacion propia no inicializada"); }
+    if size == 0 { return Err("tamano cero"); }
+    let end = match phys.checked_add(size) {
+        Some(e) if e <= PHYS_LIMIT => e,
+        _ => return Err("direccion fisica fuera de rango"),
+    };
+    if let Some(v) = mmio_lookup(phys, size) { return Ok(v); }
+
+    let base = phys & !(PAGE_4K - 1);
+    let len  = align_up(end, PAGE_4K) - base;
+    let off  = if len >= PAGE_2M {
+        align_up(pg.mmio_next, PAGE_2M) + (base & (PAGE_2M - 1))
+    } else {
+        pg.mmio_next
+    };
+    if off + len > MMIO_SIZE { return Err("ventana MMIO agotada"); }
+    // Se avanza aunque falle: las páginas ya escritas no se reutilizan
+    pg.mmio_next = off + len;
+    pg.map_region(MMIO_BASE + off, base, len, MMIO_FLAGS, RegionKind::Mmio)?;
+    Ok(MMIO_BASE + off + (phys - base))
+}
+
+/// Dirección virtual de `phys .. phys + size` si ya la cubre un `map_mmio`.
+pub fn mmio_lookup(phys: u64, size: u64) -> Option<u64> {
+    let pg  = unsafe { &*addr_of!(PAGING) };
+    let end = phys.checked_add(size)?;
+    pg.regions[..pg.count].iter()
+        .find(|r| r.kind == RegionKind::Mmio && phys >= r.phys && end <= r.phys + r.size)
+        .map(|r| r.virt + (phys - r.phys))
+}
+
+/// Deshace el `map_mmio` cuya región contiene `virt`: borra sus entradas,
+/// invalida el TLB y la quita de `vmmap`. Las tablas intermedias y el hueco
+/// de la ventana MMIO no se recuperan (son un pool/bump sin liberación).
+pub fn unmap_mmio(virt: u64) -> Result<(), &'static str> {
+    let pg = unsafe { &mut *addr_of_mut!(PAGING) };
+    let Some(ri) = pg.regions[..pg.count].iter()
+        .position(|r| r.kind == RegionKind::Mmio && virt >= r.virt && virt < r.virt + r.size)
+    else { return Err("no es una region MMIO mapeada"); };
+
+    let r = pg.regions[ri];
+    let mut v = r.virt;
+    while v < r.virt + r.size {
+        let pd = pg.table_for(v, 2)?;
+        let pde = unsafe { &mut *pd.add(table_indices(v)[2]) };
+        let step = if *pde & P_HUGE != 0 {
+            *pde = 0;
+            PAGE_2M
+        } else {
+            let pt = (*pde & ADDR_MASK) as *mut u64;
+            unsafe { *pt.add(table_indices(v)[3]) = 0; }
+            PAGE_4K
+        };
+        unsafe { core::arch::asm!("invlpg [{}]", in(reg) v, options(nostack, preserves_flags)); }
+        v += step;
+    }
+    pg.regions.copy_within(ri + 1..pg.count, ri);
+    pg.count -= 1;
+    Ok(())
+}
+
+/// Dirección física de `virt` según las tablas que haya en CR3 (las de
+/// stage2 antes de `init`), o None si no está mapeada.
+pub fn translate(virt: u64) -> Option<u64> {
+    if !is_canonical(virt) { return None; }
+    let idx = table_indices(virt);
+    let mut table = cr3() & ADDR_MASK;
+    for (level, &i) in idx.iter().enumerate() {
+        // Solo se siguen tablas alcanzables en identidad
+        if table >= IDENTITY_LIMIT as u64 { return None; }
+        let e = unsafe { core::ptr::read_volatile((table as *const u64).add(i)) };
+        if e & P_PRESENT == 0 { return None; }
+        if level == 3 || (level > 0 && e & P_HUGE != 0) {
+            let span = 1u64 << (39 - 9 * level);
+            return Some((e & ADDR_MASK & !(span - 1)) | (virt & (span - 1)));
+        }
+        table = e & ADDR_MASK;
+    }
+    None
+}
+
+/// ¿Están mapeados todos los bytes de `virt .. virt + len`?
+pub fn is_mapped(virt: u64, len: u64) -> bool {
+    let Some(last) = virt.checked_add(len.max(1) - 1) else { return false };
+    let mut page = virt & !(PAGE_4K - 1);
+    loop {
+        if translate(page).is_none() { return false; }
+        match page.checked_add(PAGE_4K) {
+            Some(next) if next <= last => page = next,
+            _ => return true,
+        }
+    }
+}
+
+/// Resumen para `vmmap`.
+pub struct PagingInfo {
+    pub active:  bool,
+    pub nx:      bool,
+    pub cr3:     u64,
+    pub tables:  usize,
+    pub regions: [Region; MAX_REGIONS],
+    pub count:   usize,
+}
+
+pub fn info() -> PagingInfo {
+    let pg = unsafe { &*addr_of!(PAGING) };
+    PagingInfo {
+        active: pg.active, nx: pg.nx, cr3: cr3(), tables: pg.used,
+        regions: pg.regions, count: pg.count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_per_level() {
+        assert_eq!(table_indices(0), [0, 0, 0, 0]);
+        assert_eq!(table_indices(PAGE_2M + PAGE_4K), [0, 0, 1, 1]);
+        assert_eq!(table_indices(1 << 30), [0, 1, 0, 0]);
+        assert_eq!(table_indices(MMIO_BASE), [511, 0, 0, 0]);
+        assert_eq!(table_indices(0xFFFF_FFFF_FFFF_F000), [511, 511, 511, 511]);
+    }
+
+    #[test]
+    fn canonical_addresses() {
+        assert!(is_canonical(0));
+        assert!(is_canonical(0x0000_7FFF_FFFF_FFFF));
+        assert!(!is_canonical(0x0000_8000_0000_0000));
+        assert!(!is_canonical(0xFFFF_7FFF_FFFF_FFFF));
+        assert!(is_canonical(0xFFFF_8000_0000_0000));
+        assert!(is_canonical(MMIO_BASE));
+        assert!(is_canonical(MMIO_BASE + (MMIO_SIZE - 1)));
+    }
+
+    /// Entrada hoja de `virt` en las tablas de `pg` (PD si es de 2 MiB).
+    fn leaf(pg: &Paging, virt: u64) -> Option<u64> {
+        let mut table = pg.pml4 as *const u64;
+        for (level, &i) in table_indices(virt).iter().enumerate() {
+            let e = unsafe { *table.add(i) };
+            if e & P_PRESENT == 0 { return None; }
+            if level == 3 || (level == 2 && e & P_HUGE != 0) { return Some(e); }
+            table = (e & ADDR_MASK) as *const u64;
+        }
+        None
+    }
+
+    /// Todo el test usa el POOL estático: no puede repartirse en varios
+    /// #[test] que corran en paralelo.
+    #[test]
+    fn map_range_uses_huge_pages_where_aligned() {
+        let mut pg = Paging {
+            pml4: 0, used: 0, regions: [Region::EMPTY; MAX_REGIONS], count: 0,
+            mmio_next: 0, nx: true, active: false,
+        };
+        pg.pml4 = pg.alloc_table().unwrap();
+
+        // 0x1000 .. 0x401000: 511 páginas de 4 KiB hasta 2 MiB, una de 2 MiB
+        // y la última de 4 KiB
+        assert_eq!(pg.map_range(PAGE_4K, PAGE_4K, 2 * PAGE_2M, RAM_FLAGS), Ok((1, 512)));
+        assert_eq!(leaf(&pg, PAGE_4K), Some(PAGE_4K | RAM_FLAGS));
+        assert_eq!(leaf(&pg, PAGE_2M + 0x1234), Some(PAGE_2M | RAM_FLAGS | P_HUGE));
+        assert_eq!(leaf(&pg, 2 * PAGE_2M), Some(2 * PAGE_2M | RAM_FLAGS));
+        assert_eq!(leaf(&pg, 0), None);
+        assert_eq!(leaf(&pg, 2 * PAGE_2M + PAGE_4K), None);
+
+        // Virtual y física con distinta alineación: todo en páginas de 4 KiB
+        let v = MMIO_BASE + PAGE_4K;
+        assert_eq!(pg.map_range(v, 0xFEB0_0000, PAGE_2M, MMIO_FLAGS), Ok((0, 512)));
+        assert_eq!(leaf(&pg, v), Some(0xFEB0_0000 | MMIO_FLAGS));
+
+        // Dos veces la misma página, o una de 4 KiB dentro de una grande
+        assert!(pg.map_range(PAGE_4K, 0, PAGE_4K, RAM_FLAGS).is_err());
+        assert!(pg.map_range(PAGE_2M + PAGE_4K, 0, PAGE_4K, RAM_FLAGS).is_err());
+
+        // Sin NX la región se registra sin el bit
+        pg.nx = false;
+        pg.map_region(8 * PAGE_2M, 8 * PAGE_2M, PAGE_4K, FB_FLAGS, RegionKind::Framebuffer).unwrap();
+        let r = pg.regions[pg.count - 1];
+        assert!(r.executable() && r.writable() && !r.uncached());
+        assert_eq!(leaf(&pg, 8 * PAGE_2M), Some(8 * PAGE_2M | (FB_FLAGS & !P_NX)));
+
+        // Pool agotado: error, no pánico
+        while pg.used < POOL_TABLES { pg.alloc_table().unwrap(); }
+        assert!(pg.map_range(1 << 40, 0, PAGE_4K, RAM_FLAGS).is_err());
+    }
+}
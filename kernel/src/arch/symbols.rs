@@ -0,0 +1,217 @@
+// arch/symbols.rs — PORTIX OS
+//
+// Tabla de símbolos mínima del kernel para traducir direcciones a
+// `nombre+offset` en la pantalla de panic y en el backtrace.
+//
+// linker.ld reserva la sección .ksyms (KSYMS_SIZE bytes, llena de ceros) y
+// scripts/build.py la rellena tras el enlace con `objcopy --update-section`
+// a partir de `nm` sobre el ELF. Así no hace falta enlazar dos veces y las
+// direcciones no se mueven al incrustar la tabla.
+//
+// Formato (little-endian):
+//   cabecera   "KSYM"  count:u32  strtab:u32  reservado:u32       (16 bytes)
+//   entradas   addr:u64  size:u32  name_off:u16  name_len:u8  pad  (16 bytes)
+//   strtab     nombres sin terminador, ya demangled y sin hash
+//
+// Las entradas van ordenadas por dirección. `size` = 0 significa tamaño
+// desconocido; si no, una dirección más allá del final del símbolo se trata
+// como desconocida (build.py puede descartar símbolos si la tabla no cabe).
+
+#![allow(dead_code)]
+
+pub const MAGIC: &[u8; 4] = b"KSYM";
+pub const HEADER_LEN: usize = 16;
+pub const ENTRY_LEN: usize = 16;
+
+extern "C" {
+    static __ksyms_start: u8;
+    static __ksyms_end: u8;
+}
+
+/// Vista sobre una tabla ya validada.
+#[derive(Clone, Copy)]
+pub struct SymTable<'a> {
+    data:   &'a [u8],
+    count:  usize,
+    strtab: usize,
+}
+
+fn rd_u16(d: &[u8], o: usize) -> u16 { u16::from_le_bytes([d[o], d[o + 1]]) }
+fn rd_u32(d: &[u8], o: usize) -> u32 {
+    u32::from_le_bytes([d[o], d[o + 1], d[o + 2], d[o + 3]])
+}
+fn rd_u64(d: &[u8], o: usize) -> u64 {
+    let mut b = [0u8; 8];
+    b.copy_from_slice(&d[o..o + 8]);
+    u64::from_le_bytes(b)
+}
+
+impl<'a> SymTable<'a> {
+    /// Valida cabecera y límites. `None` si la sección está vacía (build sin
+    /// tabla) o si algún campo apunta fuera de `data`.
+    pub fn parse(data: &'a [u8]) -> Option<SymTable<'a>> {
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC { return None; }
+        let count  = rd_u32(data, 4) as usize;
+        let strtab = rd_u32(data, 8) as usize;
+        let entries_end = HEADER_LEN.checked_add(count.checked_mul(ENTRY_LEN)?)?;
+        if entries_end > strtab || strtab > data.len() { return None; }
+        Some(SymTable { data, count, strtab })
+    }
+
+    pub fn len(&self) -> usize { self.count }
+    pub fn is_empty(&self) -> bool { self.count == 0 }
+
+    /// (dirección, tamaño, nombre) de la entrada `i`. Un nombre fuera de la
+    /// strtab o no UTF-8 se devuelve como "??" en vez de fallar.
+    pub fn entry(&self, i: usize) -> (u64, u32, &'a str) {
+        let o = HEADER_LEN + i * ENTRY_LEN;
+        let addr = rd_u64(self.data, o);
+        let size = rd_u32(self.data, o + 8);
+        let off  = self.strtab + rd_u16(self.data, o + 12) as usize;
+        let len  = self.data[o + 14] as usize;
+        let name = self.data.get(off..off + len)
+            .and_then(|b| core::str::from_utf8(b).ok())
+            .unwrap_or("??");
+        (addr, size, name)
+    }
+
+    /// Símbolo con la mayor dirección ≤ `addr` y el offset dentro de él.
+    /// `None` si `addr` queda antes del primer símbolo o pasado el final de
+    /// un símbolo de tamaño conocido.
+    pub fn lookup(&self, addr: u64) -> Option<(&'a str, u64)> {
+        let (mut lo, mut hi) = (0usize, self.count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.entry(mid).0 <= addr { lo = mid + 1; } else { hi = mid; }
+        }
+        if lo == 0 { return None; }
+        let (start, size, name) = self.entry(lo - 1);
+        let off = addr - start;
+        if size != 0 && off >= size as u64 { return None; }
+        Some((name, off))
+    }
+}
+
+/// Tabla incrustada en el kernel, si build.py la rellenó.
+pub fn kernel() -> Option<SymTable<'static>> {
+    let start = core::ptr::addr_of!(__ksyms_start);
+    let end   = core::ptr::addr_of!(__ksyms_end) as usize;
+    let len   = end.saturating_sub(start as usize);
+    SymTable::parse(unsafe { core::slice::from_raw_parts(start, len) })
+}
+
+/// Resuelve una dirección del kernel. Direcciones fuera de .text → `None`.
+pub fn resolve(addr: u64) -> Option<(&'static str, u64)> {
+    let (lo, hi) = crate::arch::paging::kernel_text();
+    if addr < lo || addr >= hi { return None; }
+    kernel()?.lookup(addr)
+}
+
+/// "nombre+0x1A" (recortando el nombre por la izquierda si no cabe) o "??".
+pub fn fmt_symbol(addr: u64, buf: &mut [u8; 64]) -> &str {
+    let Some((name, off)) = resolve(addr) else { return "??"; };
+    const H: &[u8] = b"0123456789ABCDEF";
+    let mut hex = [0u8; 16];
+    let mut nh = 0;
+    let mut v = off;
+    loop {
+        hex[nh] = H[(v & 0xF) as usize];
+        nh += 1; v >>= 4;
+        if v == 0 { break; }
+    }
+    let suffix = 3 + nh;
+    let nb = name.as_bytes();
+    let keep = nb.len().min(buf.len() - suffix);
+    let mut pos = 0;
+    for b in &nb[nb.len() - keep..] { buf[pos] = *b; pos += 1; }
+    for b in b"+0x" { buf[pos] = *b; pos += 1; }
+    for i in (0..nh).rev() { buf[pos] = hex[i]; pos += 1; }
+    core::str::from_utf8(&buf[..pos]).unwrap_or("??")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Tabla en el formato de build.py con las entradas ya ordenadas.
+    fn build(syms: &[(u64, u32, &str)]) -> Vec<u8> {
+        let strtab = HEADER_LEN + syms.len() * ENTRY_LEN;
+        let mut d = Vec::new();
+        d.extend_from_slice(MAGIC);
+        d.extend_from_slice(&(syms.len() as u32).to_le_bytes());
+        d.extend_from_slice(&(strtab as u32).to_le_bytes());
+        d.extend_from_slice(&[0; 4]);
+        let mut off = 0u16;
+        for &(addr, size, name) in syms {
+            d.extend_from_slice(&addr.to_le_bytes());
+            d.extend_from_slice(&size.to_le_bytes());
+            d.extend_from_slice(&off.to_le_bytes());
+            d.push(name.len() as u8);
+            d.push(0);
+            off += name.len() as u16;
+        }
+        for &(_, _, name) in syms { d.extend_from_slice(name.as_bytes()); }
+        d
+    }
+
+    fn sample() -> Vec<u8> {
+        build(&[(0x1000, 0x100, "rust_main"), (0x1100, 0, "idle"), (0x2000, 0x10, "pit_tick")])
+    }
+
+    #[test]
+    fn parse_reads_header_and_entries() {
+        let data = sample();
+        let t = SymTable::parse(&data).unwrap();
+        assert_eq!(t.len(), 3);
+        assert_eq!(t.entry(0), (0x1000, 0x100, "rust_main"));
+        assert_eq!(t.entry(2), (0x2000, 0x10, "pit_tick"));
+        let empty = build(&[]);
+        assert!(SymTable::parse(&empty).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_missing_or_truncated_tables() {
+        // Sección sin rellenar (build sin tabla): todo ceros
+        assert!(SymTable::parse(&[0u8; 64]).is_none());
+        assert!(SymTable::parse(b"KSYM").is_none());
+        let mut bad = sample();
+        bad[0] = b'X';
+        assert!(SymTable::parse(&bad).is_none());
+        // strtab más allá del final
+        let data = sample();
+        assert!(SymTable::parse(&data[..HEADER_LEN + 3 * ENTRY_LEN - 1]).is_none());
+        // count que no cabe antes de la strtab
+        let mut big = sample();
+        big[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(SymTable::parse(&big).is_none());
+    }
+
+    #[test]
+    fn lookup_boundaries() {
+        let data = sample();
+        let t = SymTable::parse(&data).unwrap();
+        assert_eq!(t.lookup(0), None);
+        assert_eq!(t.lookup(0xFFF), None);              // antes del primer símbolo
+        assert_eq!(t.lookup(0x1000), Some(("rust_main", 0)));
+        assert_eq!(t.lookup(0x10FF), Some(("rust_main", 0xFF)));
+        assert_eq!(t.lookup(0x1100), Some(("idle", 0))); // justo en el límite
+        // Tamaño desconocido: llega hasta el siguiente símbolo
+        assert_eq!(t.lookup(0x1FFF), Some(("idle", 0xEFF)));
+        assert_eq!(t.lookup(0x200F), Some(("pit_tick", 0xF)));
+        assert_eq!(t.lookup(0x2010), None);              // pasado el final
+        assert_eq!(t.lookup(u64::MAX), None);
+    }
+
+    #[test]
+    fn bad_name_reads_as_unknown() {
+        let mut data = sample();
+        let strtab = HEADER_LEN + 3 * ENTRY_LEN;
+        data[HEADER_LEN + 12..HEADER_LEN + 14].copy_from_slice(&0xFFFFu16.to_le_bytes());
+        let t = SymTable::parse(&data).unwrap();
+        assert_eq!(t.entry(0).2, "??");
+        data[strtab] = 0xFF;   // byte no UTF-8 al principio de "rust_main"
+        data[HEADER_LEN + 12..HEADER_LEN + 14].copy_from_slice(&0u16.to_le_bytes());
+        assert_eq!(SymTable::parse(&data).unwrap().lookup(0x1000), Some(("??", 0)));
+    }
+}
@@ -0,0 +1,206 @@
+// console/terminal/alias.rs — PORTIX Kernel
+// Alias de comandos (`alias ll=ls -l`). Tabla fija dentro de `Terminal`:
+// `exec_line` sustituye la primera palabra por su alias antes de despachar,
+// así ningún comando tiene que saber que existen. Si la línea expandida no
+// cabe en INPUT_MAX se rechaza con error, nunca se ejecuta recortada. La expansión es de un
+// solo nivel: el comando del alias se busca ya en la tabla estática, de
+// modo que `alias ls=ls -l` funciona y un alias no puede entrar en bucle.
+
+use super::INPUT_MAX;
+
+pub const ALIAS_MAX:      usize = 16;
+pub const ALIAS_NAME_MAX: usize = 16;
+
+#[derive(Clone, Copy)]
+pub struct Alias {
+    name:     [u8; ALIAS_NAME_MAX],
+    name_len: usize,
+    cmd:      [u8; INPUT_MAX],
+    cmd_len:  usize,
+}
+
+impl Alias {
+    const EMPTY: Self = Alias { name: [0; ALIAS_NAME_MAX], name_len: 0, cmd: [0; INPUT_MAX], cmd_len: 0 };
+
+    pub fn name(&self) -> &[u8] { &self.name[..self.name_len] }
+    pub fn cmd(&self)  -> &[u8] { &self.cmd[..self.cmd_len] }
+}
+
+pub struct AliasTable {
+    entries: [Alias; ALIAS_MAX],
+    count:   usize,
+}
+
+/// Comandos que un alias nunca puede tapar: sin ellos no se podría deshacer.
+pub fn is_reserved(name: &[u8]) -> bool {
+    matches!(name, b"alias" | b"unalias")
+}
+
+/// Comandos críticos: taparlos exige `alias -f`.
+pub fn is_critical(name: &[u8]) -> bool {
+    matches!(name,
+        b"reboot" | b"restart" | b"reiniciar"
+        | b"poweroff" | b"shutdown" | b"apagar"
+        | b"sync" | b"diskwrite" | b"rm" | b"del" | b"eliminar")
+}
+
+fn valid_name(name: &[u8]) -> bool {
+    !name.is_empty() && name.len() <= ALIAS_NAME_MAX
+        && name.iter().all(|&b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_' || b == b'.')
+}
+
+impl AliasTable {
+    pub const EMPTY: Self = AliasTable { entries: [Alias::EMPTY; ALIAS_MAX], count: 0 };
+
+    pub fn len(&self) -> usize { self.count }
+    pub fn is_empty(&self) -> bool { self.count == 0 }
+    pub fn entries(&self) -> &[Alias] { &self.entries[..self.count] }
+
+    pub fn get(&self, name: &[u8]) -> Option<&[u8]> {
+        self.entries().iter().find(|a| a.name() == name).map(|a| a.cmd())
+    }
+
+    /// Registra (o redefine) `name`. Ok(true) si ya existía.
+    pub fn define(&mut self, name: &[u8], cmd: &[u8]) -> Result<bool, &'static str> {
+        if !valid_name(name) { return Err("nombre no valido (letras, digitos, - _ . y max 16)"); }
+        if is_reserved(name) { return Err("ese nombre no se puede redefinir"); }
+        if cmd.is_empty()    { return Err("falta el comando"); }
+        if cmd.len() > INPUT_MAX { return Err("comando demasiado largo (max 80)"); }
+
+        let found = self.entries().iter().position(|a| a.name() == name);
+        let idx = match found {
+            Some(i) => i,
+            None if self.count < ALIAS_MAX => { self.count += 1; self.count - 1 }
+            None => return Err("tabla de alias llena (max 16)"),
+        };
+        let a = &mut self.entries[idx];
+        *a = Alias::EMPTY;
+        a.name[..name.len()].copy_from_slice(name);
+        a.name_len = name.len();
+        a.cmd[..cmd.len()].copy_from_slice(cmd);
+        a.cmd_len = cmd.len();
+        Ok(found.is_some())
+    }
+
+    /// Quita `name`. false si no existía.
+    pub fn remove(&mut self, name: &[u8]) -> bool {
+        let Some(i) = self.entries().iter().position(|a| a.name() == name) else { return false };
+        self.entries.copy_within(i + 1..self.count, i);
+        self.count -= 1;
+        self.entries[self.count] = Alias::EMPTY;
+        true
+    }
+
+    /// Si la primera palabra de `line` es un alias, escribe en `out` la línea
+    /// expandida (comando del alias + resto de argumentos) y devuelve su
+    /// longitud. None si no hay alias que aplicar. La longitud es la de la
+    /// línea completa: si supera `out.len()` solo se copió el principio y el
+    /// llamador debe rechazarla en vez de ejecutarla recortada.
+    pub fn expand(&self, line: &[u8], out: &mut [u8]) -> Option<usize> {
+        let start = line.iter().position(|&b| b != b' ')?;
+        let line  = &line[start..];
+        let sp    = line.iter().position(|&b| b == b' ').unwrap_or(line.len());
+        let cmd   = self.get(&line[..sp])?;
+        let rest  = &line[sp..];
+        let n = cmd.len() + rest.len();
+        if n <= out.len() {
+            out[..cmd.len()].copy_from_slice(cmd);
+            out[cmd.len()..n].copy_from_slice(rest);
+        }
+        Some(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand<'a>(t: &AliasTable, line: &[u8], out: &'a mut [u8; INPUT_MAX]) -> Option<&'a [u8]> {
+        let n = t.expand(line, out)?;
+        assert!(n <= INPUT_MAX);
+        Some(&out[..n])
+    }
+
+    #[test]
+    fn define_and_redefine() {
+        let mut t = AliasTable::EMPTY;
+        assert_eq!(t.define(b"ll", b"ls -l"), Ok(false));
+        assert_eq!(t.get(b"ll"), Some(&b"ls -l"[..]));
+        // Redefinir reemplaza en el sitio y no ocupa otra entrada
+        assert_eq!(t.define(b"ll", b"ls"), Ok(true));
+        assert_eq!(t.get(b"ll"), Some(&b"ls"[..]));
+        assert_eq!(t.len(), 1);
+        assert!(t.remove(b"ll"));
+        assert!(!t.remove(b"ll"));
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn table_full() {
+        let mut t = AliasTable::EMPTY;
+        for i in 0..ALIAS_MAX {
+            let name = [b'a', b'0' + (i / 10) as u8, b'0' + (i % 10) as u8];
+            assert_eq!(t.define(&name, b"ls"), Ok(false));
+        }
+        assert!(t.define(b"extra", b"ls").is_err());
+        // Redefinir uno existente sigue funcionando con la tabla llena
+        assert_eq!(t.define(b"a00", b"help"), Ok(true));
+        // Al quitar uno el hueco se compacta y vuelve a haber sitio
+        assert!(t.remove(b"a05"));
+        assert_eq!(t.entries()[5].name(), b"a06");
+        assert_eq!(t.define(b"extra", b"ls"), Ok(false));
+    }
+
+    #[test]
+    fn rejected_names_and_commands() {
+        let mut t = AliasTable::EMPTY;
+        assert!(t.define(b"alias", b"ls").is_err());
+        assert!(t.define(b"unalias", b"ls").is_err());
+        assert!(t.define(b"", b"ls").is_err());
+        assert!(t.define(b"a b", b"ls").is_err());
+        assert!(t.define(b"a=b", b"ls").is_err());
+        assert!(t.define(&[b'x'; ALIAS_NAME_MAX + 1], b"ls").is_err());
+        assert!(t.define(b"x", b"").is_err());
+        assert!(t.define(b"x", &[b'a'; INPUT_MAX + 1]).is_err());
+        assert!(t.define(b"x", &[b'a'; INPUT_MAX]).is_ok());
+        assert_eq!(t.len(), 1);
+    }
+
+    #[test]
+    fn reserved_and_critical() {
+        assert!(is_reserved(b"alias") && is_reserved(b"unalias"));
+        assert!(!is_reserved(b"ls"));
+        for name in [&b"reboot"[..], b"poweroff", b"sync", b"rm", b"eliminar"] {
+            assert!(is_critical(name));
+        }
+        assert!(!is_critical(b"ls") && !is_critical(b"rmdir"));
+        // La tabla no decide sobre críticos: eso es `alias -f` en cmd_alias
+        let mut t = AliasTable::EMPTY;
+        assert_eq!(t.define(b"rm", b"ls"), Ok(false));
+    }
+
+    #[test]
+    fn expand_passes_arguments_through() {
+        let mut t = AliasTable::EMPTY;
+        t.define(b"ll", b"ls -l").unwrap();
+        let mut out = [0u8; INPUT_MAX];
+        assert_eq!(expand(&t, b"ll", &mut out), Some(&b"ls -l"[..]));
+        assert_eq!(expand(&t, b"  ll /home x", &mut out), Some(&b"ls -l /home x"[..]));
+        // Solo la primera palabra, y entera
+        assert_eq!(expand(&t, b"echo ll", &mut out), None);
+        assert_eq!(expand(&t, b"lll", &mut out), None);
+        assert_eq!(expand(&t, b"   ", &mut out), None);
+        // Un solo nivel: el resultado no se vuelve a expandir
+        t.define(b"ls", b"ll").unwrap();
+        assert_eq!(expand(&t, b"ls a", &mut out), Some(&b"ll a"[..]));
+    }
+
+    #[test]
+    fn expand_reports_overflow() {
+        let mut t = AliasTable::EMPTY;
+        t.define(b"x", &[b'c'; INPUT_MAX - 2]).unwrap();
+        let mut out = [0u8; INPUT_MAX];
+        assert_eq!(t.expand(b"x a", &mut out), Some(INPUT_MAX));
+        assert_eq!(t.expand(b"x ab", &mut out), Some(INPUT_MAX + 1));
+    }
+}
@@ -0,0 +1,384 @@
+// console/terminal/commands/config.rs — PORTIX Kernel
+// Configuración en tiempo de ejecución persistida en un único archivo
+// clave=valor (/portix/config, el mismo directorio que los marcadores del
+// Explorador; FAT32 sin LFN no admite nombres que empiecen por '.').
+//
+// ┌─ Uso ──────────────────────────────────────────────────────────────────────┐
+// │  config [show]            Valor actual y por defecto de cada clave        │
+// │  config set <clave> <v>   Cambiar una clave (sin guardar)                 │
+// │  config save              Escribir todas las claves en /portix/config     │
+// │  config load              Leer el archivo y aplicar cada clave            │
+// │  printenv | env           Volcado por categoria de todo el estado         │
+// └───────────────────────────────────────────────────────────────────────────┘
+//
+// Formato: una `clave=valor` por línea; '#' inicia un comentario. Al cargar,
+// las líneas mal formadas, las claves desconocidas y los valores no válidos
+// se avisan y se ignoran; toda clave ausente o inválida vuelve a su valor
+// por defecto. Para añadir una opción basta con una entrada en SETTINGS.
+
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
+use crate::console::terminal::fmt::*;
+use crate::console::terminal::commands::{disk, system};
+use crate::arch::isr_handlers::{self, PanicVerbosity};
+use crate::drivers::input::mouse;
+use crate::drivers::serial;
+use crate::drivers::storage::fat32;
+
+type Hw  = crate::arch::hardware::HardwareInfo;
+type Pci = crate::drivers::bus::pci::PciBus;
+
+pub const CONFIG_PATH: &[u8] = b"/portix/config";
+const CONFIG_DIR:      &str  = "portix";
+const CONFIG_MAX:      usize = 2048;
+
+struct Setting {
+    key:     &'static str,
+    default: &'static str,
+    desc:    &'static str,
+    /// Escribe el valor actual en `out` y devuelve su longitud.
+    get:     fn(&Terminal, &mut [u8]) -> usize,
+    /// Aplica `v`; false si el valor no es válido.
+    set:     fn(&mut Terminal, &[u8], &Hw, &Pci) -> bool,
+}
+
+const SETTINGS: [Setting; 8] = [
+    Setting {
+        key: "fat32.mode", default: "rw", desc: "Montaje del volumen (ro|rw)",
+        get: |_, out| put(out, if fat32::default_read_only() { b"ro" } else { b"rw" }),
+        set: |_, v, _, _| match v {
+            b"ro" => { fat32::set_default_read_only(true);  true }
+            b"rw" => { fat32::set_default_read_only(false); true }
+            _     => false,
+        },
+    },
+    Setting {
+        key: "mouse.sensitivity", default: "2", desc: "Multiplicador del raton (1-8)",
+        get: |_, out| { let mut p = 0; append_u32(out, &mut p, mouse::sensitivity() as u32); p },
+        set: |_, v, _, _| match parse_u64(v) {
+            Some(n) if (mouse::SENSITIVITY_MIN as u64..=mouse::SENSITIVITY_MAX as u64).contains(&n) => {
+                mouse::set_sensitivity(n as i32);
+                true
+            }
+            _ => false,
+        },
+    },
+    Setting {
+        key: "mouse.accel", default: "off", desc: "Aceleracion del raton (on|off)",
+        get: |_, out| put(out, if mouse::accel() { b"on" } else { b"off" }),
+        set: |_, v, _, _| match v {
+            b"on"  => { mouse::set_accel(true);  true }
+            b"off" => { mouse::set_accel(false); true }
+            _      => false,
+        },
+    },
+    Setting {
+        key: "panic.verbosity", default: "auto", desc: "Pantalla de panic (auto|compact|full)",
+        get: |_, out| put(out, isr_handlers::panic_verbosity().name().as_bytes()),
+        set: |_, v, _, _| match PanicVerbosity::parse(v) {
+            Some(pv) => { isr_handlers::set_panic_verbosity(pv); true }
+            None     => false,
+        },
+    },
+    Setting {
+        key: "serial.color", default: "off", desc: "Color ANSI en los logs de COM1 (on|off)",
+        get: |_, out| put(out, if serial::color() { b"on" } else { b"off" }),
+        set: |_, v, _, _| match v {
+            b"on"  => { serial::set_color(true);  true }
+            b"off" => { serial::set_color(false); true }
+            _      => false,
+        },
+    },
+    Setting {
+        key: "serial.baud", default: "38400", desc: "Baud rate de COM1 (115200 / n)",
+        get: |_, out| { let mut p = 0; append_u32(out, &mut p, serial::baud()); p },
+        set: |_, v, _, _| match parse_u64(v) {
+            Some(b) if b <= u32::MAX as u64 => serial::baud() as u64 == b || serial::set_baud(b as u32).is_ok(),
+            _ => false,
+        },
+    },
+    Setting {
+        key: "terminal.remote", default: "off", desc: "Sesion remota por COM1 (on|off)",
+        get: |t, out| put(out, if t.remote_active() { b"on" } else { b"off" }),
+        set: |t, v, _, _| match v {
+            b"on"  => t.remote_active() || t.set_remote(true),
+            b"off" => t.set_remote(false),
+            _      => false,
+        },
+    },
+    Setting {
+        key: "terminal.banner", default: "", desc: "Comando del banner fijo (vacio = ninguno)",
+        get: |t, out| put(out, &t.banner_cmd[..t.banner_cmd_len]),
+        set: |t, v, hw, pci| {
+            if v.is_empty() { t.clear_banner(); return true; }
+            if t.banner_count > 0 && &t.banner_cmd[..t.banner_cmd_len] == v { return true; }
+            system::cmd_banner_set(t, v, hw, pci);
+            &t.banner_cmd[..t.banner_cmd_len] == v
+        },
+    },
+];
+
+fn put(out: &mut [u8], s: &[u8]) -> usize {
+    let mut p = 0;
+    append_str(out, &mut p, s);
+    p
+}
+
+/// Recorta espacios, tabuladores y '\r' (archivos editados en el host).
+fn trim_ws(s: &[u8]) -> &[u8] {
+    let ws = |b: &u8| matches!(*b, b' ' | b'\t' | b'\r');
+    let a = s.iter().position(|b| !ws(b)).unwrap_or(s.len());
+    let b = s.iter().rposition(|b| !ws(b)).map_or(a, |i| i + 1);
+    &s[a..b]
+}
+
+fn find(key: &[u8]) -> Option<usize> {
+    SETTINGS.iter().position(|s| s.key.as_bytes() == key)
+}
+
+fn warn_line(t: &mut Terminal, line_no: usize, msg: &[u8], what: &[u8]) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  config: linea ");
+    append_u32(&mut buf, &mut pos, line_no as u32);
+    append_str(&mut buf, &mut pos, b": ");
+    append_str(&mut buf, &mut pos, msg);
+    append_str(&mut buf, &mut pos, &what[..what.len().min(40)]);
+    t.write_bytes(&buf[..pos], LineColor::Warning);
+}
+
+pub fn cmd_config(t: &mut Terminal, args: &[u8], hw: &Hw, pci: &Pci) {
+    let args = trim(args);
+    let (sub, rest) = match args.iter().position(|&b| b == b' ') {
+        Some(i) => (&args[..i], trim(&args[i + 1..])),
+        None    => (args, &[][..]),
+    };
+    match sub {
+        b"" | b"show" => show(t),
+        b"save"       => { save(t); }
+        b"load"       => { load(t, hw, pci); }
+        b"set"        => set(t, rest, hw, pci),
+        _ => t.write_line("  Uso: config [show | set <clave> <valor> | save | load]", LineColor::Warning),
+    }
+}
+
+fn show(t: &mut Terminal) {
+    t.write_line("  Clave               Valor         Defecto   Descripcion", LineColor::Header);
+    for s in SETTINGS.iter() {
+        let mut val = [0u8; 48];
+        let vl = (s.get)(t, &mut val);
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_str(&mut buf, &mut pos, s.key.as_bytes());
+        while pos < 22 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, if vl == 0 { b"-" } else { &val[..vl.min(12)] });
+        while pos < 36 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, if s.default.is_empty() { "-" } else { s.default }.as_bytes());
+        while pos < 46 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, s.desc.as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Archivo: ");
+    append_str(&mut buf, &mut pos, CONFIG_PATH);
+    t.write_bytes(&buf[..pos], LineColor::Info);
+}
+
+fn set(t: &mut Terminal, args: &[u8], hw: &Hw, pci: &Pci) {
+    let (key, val) = match args.iter().position(|&b| b == b' ' || b == b'=') {
+        Some(i) => (&args[..i], trim(&args[i + 1..])),
+        None    => (args, &[][..]),
+    };
+    let Some(i) = find(key) else {
+        t.write_line("  config set: clave desconocida (ver 'config show')", LineColor::Error);
+        return;
+    };
+    if !(SETTINGS[i].set)(t, val, hw, pci) {
+        t.write_line("  config set: valor no valido para esa clave", LineColor::Error);
+        return;
+    }
+    t.write_line("  Aplicado. Usa 'config save' para conservarlo.", LineColor::Success);
+}
+
+/// Serializa todas las claves y las escribe en CONFIG_PATH.
+pub fn save(t: &mut Terminal) -> bool {
+    let mut data = [0u8; CONFIG_MAX]; let mut n = 0;
+    append_str(&mut data, &mut n, b"# PORTIX - configuracion (config save)\n");
+    for s in SETTINGS.iter() {
+        let mut val = [0u8; 128];
+        let vl = (s.get)(t, &mut val);
+        append_str(&mut data, &mut n, s.key.as_bytes());
+        append_str(&mut data, &mut n, b"=");
+        append_str(&mut data, &mut n, &val[..vl]);
+        append_str(&mut data, &mut n, b"\n");
+    }
+    if !disk::ensure_root_dir(t, b"config", CONFIG_DIR)
+        || !disk::write_text_file(t, b"config", CONFIG_PATH, &data[..n], false)
+    {
+        return false;
+    }
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  [OK] ");
+    append_u32(&mut buf, &mut pos, SETTINGS.len() as u32);
+    append_str(&mut buf, &mut pos, b" claves guardadas en ");
+    append_str(&mut buf, &mut pos, CONFIG_PATH);
+    t.write_bytes(&buf[..pos], LineColor::Success);
+    true
+}
+
+/// Lee CONFIG_PATH y aplica cada clave; las ausentes o inválidas vuelven a
+/// su valor por defecto.
+pub fn load(t: &mut Terminal, hw: &Hw, pci: &Pci) -> bool {
+    let mut data = [0u8; CONFIG_MAX];
+    let Some(n) = disk::read_text_file(t, b"config", CONFIG_PATH, &mut data) else { return false };
+
+    let mut seen    = [false; SETTINGS.len()];
+    let mut applied = 0u32;
+    let mut ignored = 0u32;
+
+    for (idx, raw) in data[..n].split(|&b| b == b'\n').enumerate() {
+        let line = trim_ws(raw);
+        if line.is_empty() || line[0] == b'#' { continue; }
+        let Some(eq) = line.iter().position(|&b| b == b'=') else {
+            warn_line(t, idx + 1, b"sin '=', ignorada: ", line);
+            ignored += 1;
+            continue;
+        };
+        let (key, val) = (trim_ws(&line[..eq]), trim_ws(&line[eq + 1..]));
+        let Some(i) = find(key) else {
+            warn_line(t, idx + 1, b"clave desconocida ", key);
+            ignored += 1;
+            continue;
+        };
+        seen[i] = true;
+        if (SETTINGS[i].set)(t, val, hw, pci) {
+            applied += 1;
+        } else {
+            warn_line(t, idx + 1, b"valor no valido, se usa el defecto: ", key);
+            ignored += 1;
+            seen[i] = false;
+        }
+    }
+
+    let mut defaults = 0u32;
+    for (i, s) in SETTINGS.iter().enumerate() {
+        if !seen[i] && (s.set)(t, s.default.as_bytes(), hw, pci) { defaults += 1; }
+    }
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Configuracion cargada: ");
+    append_u32(&mut buf, &mut pos, applied);
+    append_str(&mut buf, &mut pos, b" claves, ");
+    append_u32(&mut buf, &mut pos, defaults);
+    append_str(&mut buf, &mut pos, b" por defecto");
+    if ignored > 0 {
+        append_str(&mut buf, &mut pos, b", ");
+        append_u32(&mut buf, &mut pos, ignored);
+        append_str(&mut buf, &mut pos, b" lineas ignoradas");
+    }
+    t.write_bytes(&buf[..pos], LineColor::Success);
+    true
+}
+
+/// Al arrancar: aplica /portix/config si existe (sin avisos si no hay disco
+/// o archivo).
+pub fn load_at_boot(t: &mut Terminal, hw: &Hw, pci: &Pci) {
+    use crate::drivers::registry::{self, State, Subsystem};
+    if registry::state(Subsystem::Fat32) != State::Active { return; }
+    if disk::path_kind(t, CONFIG_PATH) == Some(false) {
+        load(t, hw, pci);
+    }
+}
+
+// ── printenv / env ───────────────────────────────────────────────────────────
+//
+// Contraparte legible de `config save`: las claves de SETTINGS agrupadas por
+// categoría (el prefijo antes del '.'), seguidas del estado de sesión que no
+// se guarda y de los alias definidos. Cada valor distinto de su defecto se
+// marca con '*'.
+
+fn env_section(t: &mut Terminal, title: &[u8]) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  -- ");
+    append_str(&mut buf, &mut pos, title);
+    append_str(&mut buf, &mut pos, b" ");
+    while pos < 60 { buf[pos] = b'-'; pos += 1; }
+    t.write_bytes(&buf[..pos], LineColor::Header);
+}
+
+/// Una línea `clave = valor`; con `default` se indica si difiere de él.
+fn env_line(t: &mut Terminal, key: &[u8], val: &[u8], default: Option<&[u8]>) {
+    let modified = default.is_some_and(|d| d != val);
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, if modified { b"  * " } else { b"    " });
+    append_str(&mut buf, &mut pos, key);
+    while pos < 26 { buf[pos] = b' '; pos += 1; }
+    append_str(&mut buf, &mut pos, b"= ");
+    append_str(&mut buf, &mut pos, if val.is_empty() { b"-" } else { &val[..val.len().min(32)] });
+    match default {
+        Some(d) if modified => {
+            while pos < 62 { buf[pos] = b' '; pos += 1; }
+            append_str(&mut buf, &mut pos, b"(defecto: ");
+            append_str(&mut buf, &mut pos, if d.is_empty() { b"-" } else { d });
+            append_str(&mut buf, &mut pos, b")");
+        }
+        Some(_) => {
+            while pos < 62 { buf[pos] = b' '; pos += 1; }
+            append_str(&mut buf, &mut pos, b"(defecto)");
+        }
+        None => {}
+    }
+    t.write_bytes(&buf[..pos], if modified { LineColor::Info } else { LineColor::Normal });
+}
+
+fn on_off(on: bool) -> &'static [u8] { if on { b"on" } else { b"off" } }
+
+pub fn cmd_printenv(t: &mut Terminal) {
+    use crate::drivers::input::keyboard;
+    use crate::drivers::registry::{self, State, Subsystem};
+
+    // Claves persistentes, en el orden de SETTINGS y agrupadas por prefijo
+    let mut last_cat: &[u8] = b"";
+    for s in SETTINGS.iter() {
+        let key = s.key.as_bytes();
+        let cat = &key[..key.iter().position(|&b| b == b'.').unwrap_or(key.len())];
+        if cat != last_cat {
+            env_section(t, cat);
+            last_cat = cat;
+        }
+        let mut val = [0u8; 128];
+        let vl = (s.get)(t, &mut val);
+        env_line(t, key, &val[..vl], Some(s.default.as_bytes()));
+    }
+
+    // Estado de la sesión: cambia con comandos pero `config save` no lo guarda
+    env_section(t, b"sesion (no se guarda)");
+    let km = keyboard::keymap();
+    env_line(t, b"keymap", km.name(), Some(b"us"));
+    let cwd = t.cwd;
+    env_line(t, b"cwd", &cwd[..t.cwd_len], Some(b"/"));
+    env_line(t, b"present-mode", on_off(t.present), Some(b"off"));
+    env_line(t, b"scroll-sync", on_off(t.log_follow), Some(b"on"));
+    env_line(t, b"split", on_off(t.split_active()), Some(b"off"));
+    let mut n = [0u8; 12]; let mut np = 0;
+    append_u32(&mut n, &mut np, t.sticky_count as u32);
+    env_line(t, b"sticky", &n[..np], Some(b"0"));
+
+    // Alias de la sesión (`alias`): tampoco se guardan
+    env_section(t, b"alias");
+    if t.aliases.is_empty() {
+        t.write_line("    (ninguno)", LineColor::Normal);
+    }
+    for i in 0..t.aliases.len() {
+        let a = t.aliases.entries()[i];
+        env_line(t, a.name(), a.cmd(), None);
+    }
+
+    // Estado del sistema (solo lectura)
+    env_section(t, b"sistema");
+    let vol: &[u8] = match registry::state(Subsystem::Fat32) {
+        State::Active => if fat32::effective_read_only() { b"montado (ro)" } else { b"montado (rw)" },
+        _             => b"sin montar",
+    };
+    env_line(t, b"volumen", vol, None);
+    env_line(t, b"config", CONFIG_PATH, None);
+    t.write_line("  '*' = distinto del valor por defecto. Guardar: config save", LineColor::Info);
+}
@@ -1,24 +1,29 @@
 // console/terminal/commands/convert.rs
-// Comandos: calc, hex, dec, bin, rgb
+// Comandos: calc, hex, dec, bin, rgb, asciitable
 
-use crate::console::terminal::{Terminal, LineColor};
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
 use crate::console::terminal::fmt::*;
 
 pub fn cmd_calc(t: &mut Terminal, args: &[u8]) {
     if args.is_empty() {
-        t.write_line("  Uso: calc 2+3*4   o   = 100/7", LineColor::Warning); return;
+        t.write_line("  Uso: calc 2*(3+4)   o   = (0xF0 | 1) << 4", LineColor::Warning); return;
     }
     match simple_eval(args) {
         Some(r) => {
             let mut buf = [0u8; 80]; let mut pos = 0;
             append_str(&mut buf, &mut pos, b"  = ");
-            if r < 0 { buf[pos] = b'-'; pos += 1; append_u32(&mut buf, &mut pos, (-r) as u32); }
-            else { append_u32(&mut buf, &mut pos, r as u32); }
+            if r < 0 { buf[pos] = b'-'; pos += 1; }
+            append_u64(&mut buf, &mut pos, r.unsigned_abs());
             append_str(&mut buf, &mut pos, b"  (0x"); append_hex64_short(&mut buf, &mut pos, r as u64);
             append_str(&mut buf, &mut pos, b")");
             t.write_bytes(&buf[..pos], LineColor::Success);
+            // Binario aparte: un negativo son 64 bits (79 caracteres con `_`)
+            let mut bb = [0u8; 96]; let mut bp = 0;
+            append_str(&mut bb, &mut bp, b"    0b");
+            append_bin_grouped(&mut bb, &mut bp, r as u64);
+            t.write_bytes(&bb[..bp], LineColor::Info);
         }
-        None => t.write_line("  Error: expresion invalida", LineColor::Error),
+        None => t.write_line("  Error: expresion invalida, division por cero o desbordamiento", LineColor::Error),
     }
 }
 
@@ -55,11 +60,7 @@ pub fn cmd_bin(t: &mut Terminal, args: &[u8]) {
             let v = n & 0xFFFF_FFFF;
             let mut buf = [0u8; 80]; let mut pos = 0;
             append_u32(&mut buf, &mut pos, v as u32); append_str(&mut buf, &mut pos, b" = 0b");
-            let bits = if v == 0 { 1 } else { (64 - v.leading_zeros() as usize + 3) / 4 * 4 };
-            for i in (0..bits).rev() {
-                buf[pos] = if (v >> i) & 1 != 0 { b'1' } else { b'0' }; pos += 1;
-                if i > 0 && i % 4 == 0 { buf[pos] = b'_'; pos += 1; }
-            }
+            append_bin_grouped(&mut buf, &mut pos, v);
             t.write_bytes(&buf[..pos], LineColor::Success);
         }
         None => t.write_line("  Error: decimal invalido", LineColor::Error),
@@ -105,3 +106,116 @@ pub fn cmd_rgb(t: &mut Terminal, args: &[u8]) {
         t.write_bytes(&buf[..pos], LineColor::Success);
     }
 }
+
+// ── asciitable ────────────────────────────────────────────────────────────────
+//
+// Los 95 imprimibles en columnas, ordenados de arriba abajo como `man ascii`.
+// Cada celda es "DEC HX C  " (el espacio se muestra como SP) y caben tantas
+// columnas como permita TERM_COLS con la sangría de dos espacios.
+
+const ASCII_FIRST: u8 = 0x20;
+const ASCII_LAST:  u8 = 0x7E;
+const ASCII_CELL:  usize = 11;
+
+/// Columnas que caben en una línea del terminal.
+fn ascii_cols() -> usize { ((TERM_COLS - 2) / ASCII_CELL).max(1) }
+
+/// Celda de un carácter: decimal a 3, hex a 2 y el glifo en 3 de ancho.
+fn append_ascii_cell(buf: &mut [u8], pos: &mut usize, ch: u8) {
+    const H: &[u8] = b"0123456789ABCDEF";
+    if ch < 100 { append_str(buf, pos, b" "); }
+    if ch < 10  { append_str(buf, pos, b" "); }
+    append_u32(buf, pos, ch as u32);
+    append_str(buf, pos, &[b' ', H[(ch >> 4) as usize], H[(ch & 0xF) as usize], b' ']);
+    if ch == b' ' { append_str(buf, pos, b"SP "); } else { append_str(buf, pos, &[ch, b' ', b' ']); }
+}
+
+/// Fila `row` de la tabla de `lo..=hi` en `cols` columnas (orden por columnas).
+fn append_ascii_row(buf: &mut [u8], pos: &mut usize, lo: u8, hi: u8, row: usize, cols: usize) {
+    let n = (hi - lo) as usize + 1;
+    let rows = n.div_ceil(cols);
+    append_str(buf, pos, b"  ");
+    for col in 0..cols {
+        let idx = col * rows + row;
+        if idx >= n { break; }
+        if col > 0 { append_str(buf, pos, b" "); }
+        append_ascii_cell(buf, pos, lo + idx as u8);
+    }
+}
+
+pub fn cmd_asciitable(t: &mut Terminal) {
+    let cols = ascii_cols();
+    let n = (ASCII_LAST - ASCII_FIRST) as usize + 1;
+    t.separador("TABLA ASCII (0x20-0x7E)");
+    let mut hdr = [0u8; TERM_COLS]; let mut hp = 0;
+    append_str(&mut hdr, &mut hp, b"  ");
+    for col in 0..cols.min(n) {
+        if col > 0 { append_str(&mut hdr, &mut hp, b" "); }
+        append_str(&mut hdr, &mut hp, b"Dec Hx C  ");
+    }
+    t.write_bytes(&hdr[..hp], LineColor::Header);
+    for row in 0..n.div_ceil(cols) {
+        let mut line = [0u8; TERM_COLS]; let mut lp = 0;
+        append_ascii_row(&mut line, &mut lp, ASCII_FIRST, ASCII_LAST, row, cols);
+        t.write_bytes(&line[..lp], LineColor::Normal);
+    }
+    t.write_empty();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ascii_row(row: usize, cols: usize) -> String {
+        let mut buf = [0u8; 256]; let mut pos = 0;
+        append_ascii_row(&mut buf, &mut pos, ASCII_FIRST, ASCII_LAST, row, cols);
+        String::from_utf8(buf[..pos].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn ascii_cells() {
+        let cell = |ch: u8| {
+            let mut buf = [0u8; 16]; let mut pos = 0;
+            append_ascii_cell(&mut buf, &mut pos, ch);
+            String::from_utf8(buf[..pos].to_vec()).unwrap()
+        };
+        assert_eq!(cell(b' '), " 32 20 SP ");
+        assert_eq!(cell(b'A'), " 65 41 A  ");
+        assert_eq!(cell(b'~'), "126 7E ~  ");
+        assert_eq!(cell(b'A').len() + 1, ASCII_CELL);
+    }
+
+    #[test]
+    fn ascii_table_is_column_major() {
+        let cols = ascii_cols();
+        let rows = 95usize.div_ceil(cols);
+        // Fila 0: 0x20, 0x20 + rows, 0x20 + 2·rows...
+        let r0 = ascii_row(0, cols);
+        assert!(r0.starts_with("   32 20 SP "));
+        let second = format!("{:>3} {:02X}", 0x20 + rows, 0x20 + rows);
+        assert_eq!(&r0[2 + ASCII_CELL..2 + ASCII_CELL + 6], second);
+    }
+
+    #[test]
+    fn ascii_table_lists_each_char_once_and_fits() {
+        let cols = ascii_cols();
+        let rows = 95usize.div_ceil(cols);
+        let mut seen = Vec::new();
+        for row in 0..rows {
+            let line = ascii_row(row, cols);
+            assert!(line.len() <= TERM_COLS);
+            for cell in line[2..].as_bytes().chunks(ASCII_CELL) {
+                let dec = core::str::from_utf8(&cell[..3]).unwrap().trim();
+                seen.push(dec.parse::<u8>().unwrap());
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, (ASCII_FIRST..=ASCII_LAST).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ascii_table_single_column() {
+        assert_eq!(ascii_row(0, 1).trim_end(), "   32 20 SP");
+        assert_eq!(ascii_row(94, 1).trim_end(), "  126 7E ~");
+    }
+}
@@ -1,9 +1,28 @@
 // console/terminal/commands/debug.rs
-// Comandos: hexdump, peek, poke, cpuid, pic, gdt, memtest, inb, outb
+// Comandos: hexdump, readsec, peek, poke, vmmap, cpuid, pic, gdt, memtest,
+//           inb, outb, regwatch, reg, profile, bench
+//
+// hexdump/peek/poke trabajan con direcciones virtuales: la RAM baja está en
+// identidad y los dispositivos en la ventana MMIO (ver `vmmap`). Antes de
+// tocar memoria se comprueba que esté mapeada para no provocar un #PF.
 
+use crate::arch::paging;
+use crate::time::profile;
 use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
 use crate::console::terminal::fmt::*;
 
+/// Avisa y devuelve false si algún byte de `addr .. addr + len` no está
+/// mapeado.
+fn check_mapped(t: &mut Terminal, addr: u64, len: u64) -> bool {
+    if paging::is_mapped(addr, len) { return true; }
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Error: 0x");
+    append_hex64_short(&mut buf, &mut pos, addr);
+    append_str(&mut buf, &mut pos, b" no esta mapeada (ver 'vmmap')");
+    t.write_bytes(&buf[..pos], LineColor::Error);
+    false
+}
+
 // ── hexdump ───────────────────────────────────────────────────────────────────
 
 pub fn cmd_hexdump(t: &mut Terminal, args: &[u8]) {
@@ -23,6 +42,7 @@ pub fn cmd_hexdump(t: &mut Terminal, args: &[u8]) {
     };
     let count = if count_part.is_empty() { 64 }
                 else { match parse_u64(count_part) { Some(n) => n.min(256) as usize, None => 64 } };
+    if !check_mapped(t, addr, count as u64) { return; }
     {
         let mut hdr = [0u8; 80]; let mut hp = 0;
         append_str(&mut hdr, &mut hp, b"  Volcado 0x");
@@ -35,31 +55,99 @@ pub fn cmd_hexdump(t: &mut Terminal, args: &[u8]) {
     t.write_line("  Offset    00 01 02 03 04 05 06 07  08 09 0A 0B 0C 0D 0E 0F  ASCII", LineColor::Header);
     for row in 0..((count + 15) / 16) {
         let base = addr + (row * 16) as u64;
+        let n = 16.min(count - row * 16);
+        let mut bytes = [0u8; 16];
+        for (col, b) in bytes[..n].iter_mut().enumerate() {
+            *b = unsafe { core::ptr::read_volatile((base + col as u64) as *const u8) };
+        }
         let mut line = [0u8; TERM_COLS]; let mut lp = 0;
         append_str(&mut line, &mut lp, b"  ");
-        append_hex64_short(&mut line, &mut lp, base);
-        append_str(&mut line, &mut lp, b"  ");
-        let mut ascii_buf = [b'.'; 16];
-        for col in 0..16usize {
-            let idx = row * 16 + col;
-            if col == 8 { append_str(&mut line, &mut lp, b" "); }
-            if idx < count {
-                let byte = unsafe { core::ptr::read_volatile((base + col as u64) as *const u8) };
-                const H: &[u8] = b"0123456789ABCDEF";
-                if lp < TERM_COLS - 1 { line[lp] = H[(byte >> 4) as usize]; lp += 1; }
-                if lp < TERM_COLS - 1 { line[lp] = H[(byte & 0xF) as usize]; lp += 1; }
-                if lp < TERM_COLS - 1 { line[lp] = b' '; lp += 1; }
-                ascii_buf[col] = if byte >= 32 && byte < 127 { byte } else { b'.' };
-            } else {
-                append_str(&mut line, &mut lp, b"   ");
-            }
+        append_hexdump_row(&mut line, &mut lp, base, &bytes[..n]);
+        t.write_bytes(&line[..lp], LineColor::Normal);
+    }
+}
+
+// ── readsec ───────────────────────────────────────────────────────────────────
+//
+// Sectores crudos del disco primario (ATA0 maestro) con la misma lectura PIO
+// que usa FAT32, sin pasar por el volumen. Cada sector son 32 filas, así que
+// `count` se limita para que el volcado quepa en el historial del terminal.
+
+const READSEC_MAX: u64 = 3;
+
+/// Una fila del volcado de sector: offset dentro del sector, 16 bytes en hex
+/// (con separación a la mitad) y su columna ASCII. Devuelve la longitud.
+fn sector_row(line: &mut [u8; TERM_COLS], off: u16, bytes: &[u8]) -> usize {
+    const H: &[u8] = b"0123456789ABCDEF";
+    let mut lp = 0;
+    append_str(line, &mut lp, b"  ");
+    append_hex16(line, &mut lp, off);
+    append_str(line, &mut lp, b"   ");
+    for (col, &b) in bytes.iter().enumerate() {
+        if col == 8 { append_str(line, &mut lp, b" "); }
+        append_str(line, &mut lp, &[H[(b >> 4) as usize], H[(b & 0xF) as usize], b' ']);
+    }
+    append_str(line, &mut lp, b" ");
+    for &b in bytes {
+        append_str(line, &mut lp, &[if (0x20..0x7F).contains(&b) { b } else { b'.' }]);
+    }
+    lp
+}
+
+pub fn cmd_readsec(t: &mut Terminal, args: &[u8]) {
+    use crate::drivers::storage::ata::{AtaDrive, get_cached_drive_info};
+    use super::disk::ata_err_msg;
+
+    let args = trim(args);
+    let (lba_part, count_part) = match args.iter().position(|&b| b == b' ') {
+        Some(sp) => (&args[..sp], trim(&args[sp + 1..])),
+        None     => (args, &b""[..]),
+    };
+    let num = |s: &[u8]| if s.starts_with(b"0x") || s.starts_with(b"0X") { parse_hex(s) } else { parse_u64(s) };
+    let Some(lba) = num(lba_part) else {
+        t.write_line("  Uso: readsec <lba> [count]  (count: 1-3, predeterminado 1)", LineColor::Warning);
+        return;
+    };
+    let requested = if count_part.is_empty() { 1 } else {
+        match num(count_part) {
+            Some(n) if n >= 1 => n,
+            _ => { t.write_line("  Error: count invalido (1-3)", LineColor::Error); return; }
         }
-        append_str(&mut line, &mut lp, b" ");
-        let acnt = 16.min(count.saturating_sub(row * 16));
-        for &ac in &ascii_buf[..acnt] {
-            if lp < TERM_COLS - 1 { line[lp] = ac; lp += 1; }
+    };
+    let count = requested.min(READSEC_MAX);
+    let Some(info) = get_cached_drive_info() else {
+        t.write_line("  Error: no hay disco primario (ATA0 maestro).", LineColor::Error);
+        return;
+    };
+    let drive = AtaDrive::from_info(info);
+
+    let mut sector = [0u8; 512];
+    for s in lba..lba.saturating_add(count) {
+        if let Err(e) = drive.read_sectors(s, 1, &mut sector) {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  Error leyendo LBA ");
+            append_u64(&mut buf, &mut pos, s);
+            append_str(&mut buf, &mut pos, b": ");
+            append_str(&mut buf, &mut pos, ata_err_msg(e));
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            return;
+        }
+        {
+            let mut hdr = [0u8; TERM_COLS]; let mut hp = 0;
+            append_str(&mut hdr, &mut hp, b"  LBA ");
+            append_u64(&mut hdr, &mut hp, s);
+            append_str(&mut hdr, &mut hp, b" (512 bytes):");
+            t.write_bytes(&hdr[..hp], LineColor::Info);
+        }
+        t.write_line("  Offset   00 01 02 03 04 05 06 07  08 09 0A 0B 0C 0D 0E 0F  ASCII", LineColor::Header);
+        for (row, bytes) in sector.as_chunks::<16>().0.iter().enumerate() {
+            let mut line = [0u8; TERM_COLS];
+            let n = sector_row(&mut line, (row * 16) as u16, bytes);
+            t.write_bytes(&line[..n], LineColor::Normal);
         }
-        t.write_bytes(&line[..lp], LineColor::Normal);
+    }
+    if requested > READSEC_MAX {
+        t.write_line("  (count limitado a 3 sectores)", LineColor::Warning);
     }
 }
 
@@ -72,6 +160,7 @@ pub fn cmd_peek(t: &mut Terminal, args: &[u8]) {
         Some(a) => a,
         None => { t.write_line("  Error: direccion invalida", LineColor::Error); return; }
     };
+    if !check_mapped(t, addr, 8) { return; }
     let val = unsafe { core::ptr::read_volatile(addr as *const u64) };
     let mut buf = [0u8; 80]; let mut pos = 0;
     append_str(&mut buf, &mut pos, b"  [0x"); append_hex64_short(&mut buf, &mut pos, addr);
@@ -95,6 +184,7 @@ pub fn cmd_poke(t: &mut Terminal, args: &[u8]) {
         Some(v) => v as u8,
         None => { t.write_line("  Error: valor invalido", LineColor::Error); return; }
     };
+    if !check_mapped(t, addr, 1) { return; }
     unsafe { core::ptr::write_volatile(addr as *mut u8, val); }
     let mut buf = [0u8; 80]; let mut pos = 0;
     append_str(&mut buf, &mut pos, b"  Escrito 0x");
@@ -105,6 +195,67 @@ pub fn cmd_poke(t: &mut Terminal, args: &[u8]) {
     t.write_bytes(&buf[..pos], LineColor::Success);
 }
 
+// ── vmmap ─────────────────────────────────────────────────────────────────────
+
+fn append_region_size(buf: &mut [u8], pos: &mut usize, bytes: u64) {
+    let (v, unit): (u64, &[u8]) = if bytes >= 1 << 30 && bytes.is_multiple_of(1 << 30) { (bytes >> 30, b" GiB") }
+                                  else if bytes >= 1 << 20 && bytes.is_multiple_of(1 << 20) { (bytes >> 20, b" MiB") }
+                                  else { (bytes >> 10, b" KiB") };
+    append_u32(buf, pos, v as u32);
+    append_str(buf, pos, unit);
+}
+
+pub fn cmd_vmmap(t: &mut Terminal) {
+    let info = paging::info();
+    t.separador("MAPA DE MEMORIA VIRTUAL");
+    if !info.active {
+        t.write_line("  Paginacion propia no activa: se usan las tablas de stage2", LineColor::Warning);
+        t.write_line("  (primer GiB y framebuffer en identidad, sin MMIO)", LineColor::Warning);
+        return;
+    }
+    t.write_line("  Virtual           Fisica            Tamano    Paginas          Flags    Tipo", LineColor::Header);
+    for r in &info.regions[..info.count] {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_hex64_full(&mut buf, &mut pos, r.virt);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_hex64_full(&mut buf, &mut pos, r.phys);
+        append_str(&mut buf, &mut pos, b"  ");
+        let col = pos;
+        append_region_size(&mut buf, &mut pos, r.size);
+        while pos < col + 10 { buf[pos] = b' '; pos += 1; }
+        let col = pos;
+        if r.pages_2m > 0 {
+            append_u32(&mut buf, &mut pos, r.pages_2m);
+            append_str(&mut buf, &mut pos, b"x2M ");
+        }
+        if r.pages_4k > 0 {
+            append_u32(&mut buf, &mut pos, r.pages_4k);
+            append_str(&mut buf, &mut pos, b"x4K");
+        }
+        while pos < col + 17 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, if r.writable()   { b"RW " } else { b"RO " });
+        append_str(&mut buf, &mut pos, if r.executable() { b"X  " } else { b"NX " });
+        append_str(&mut buf, &mut pos, if r.uncached()   { b"UC  " } else { b"WB  " });
+        append_str(&mut buf, &mut pos, r.kind.label().as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  CR3 0x");
+    append_hex64_short(&mut buf, &mut pos, info.cr3);
+    append_str(&mut buf, &mut pos, b"  tablas ");
+    append_u32(&mut buf, &mut pos, info.tables as u32);
+    append_str(&mut buf, &mut pos, b"/");
+    append_u32(&mut buf, &mut pos, paging::POOL_TABLES as u32);
+    append_str(&mut buf, &mut pos, b"  regiones ");
+    append_u32(&mut buf, &mut pos, info.count as u32);
+    append_str(&mut buf, &mut pos, b"/");
+    append_u32(&mut buf, &mut pos, paging::MAX_REGIONS as u32);
+    append_str(&mut buf, &mut pos, if info.nx { b"  NX activo" } else { b"  NX no soportado" });
+    t.write_bytes(&buf[..pos], LineColor::Info);
+}
+
 // ── cpuid ─────────────────────────────────────────────────────────────────────
 
 pub fn cmd_cpuid(t: &mut Terminal, args: &[u8]) {
@@ -277,6 +428,287 @@ pub fn cmd_memtest(t: &mut Terminal, args: &[u8]) {
     t.write_empty();
 }
 
+// ── regwatch ──────────────────────────────────────────────────────────────────
+//
+//   regwatch <0xFISICA> [ms] [8|16|32|64]
+//
+// Muestrea un registro de dispositivo cada `ms` (10..10000, por defecto 100)
+// y escribe una línea por cada cambio de valor. El terminal queda a la
+// espera como con `waitkey`: el loop principal llama a `regwatch_poll` en
+// cada vuelta y cualquier tecla (local o por COM1) llama a `regwatch_stop`.
+//
+// El registro se lee con read_volatile a través de la ventana MMIO (PCD|PWT,
+// sin caché). Si la página no estaba ya mapeada por un driver, el mapeo es
+// de `regwatch` y se deshace al detenerlo.
+
+const REGWATCH_MS: u64 = 100;
+
+pub struct RegWatch {
+    phys:     u64,
+    virt:     u64,
+    /// Ancho del registro en bytes (1, 2, 4 u 8).
+    width:    u8,
+    interval: u64,
+    next:     u64,
+    last:     u64,
+    samples:  u32,
+    changes:  u32,
+    /// El mapeo lo creó `regwatch`: desmapear al salir.
+    owned:    bool,
+}
+
+impl RegWatch {
+    fn read(&self) -> u64 {
+        // SAFETY: `virt` apunta a una página MMIO mapeada mientras dure el
+        // watch y está alineada al ancho del registro.
+        unsafe {
+            match self.width {
+                1 => core::ptr::read_volatile(self.virt as *const u8)  as u64,
+                2 => core::ptr::read_volatile(self.virt as *const u16) as u64,
+                4 => core::ptr::read_volatile(self.virt as *const u32) as u64,
+                _ => core::ptr::read_volatile(self.virt as *const u64),
+            }
+        }
+    }
+}
+
+fn append_reg(buf: &mut [u8], pos: &mut usize, v: u64, width: u8) {
+    const H: &[u8] = b"0123456789ABCDEF";
+    append_str(buf, pos, b"0x");
+    for i in (0..width as usize * 2).rev() {
+        append_str(buf, pos, &[H[((v >> (i * 4)) & 0xF) as usize]]);
+    }
+}
+
+fn append_secs(buf: &mut [u8], pos: &mut usize, ticks: u64) {
+    use crate::time::pit::PIT_HZ;
+    let hz = PIT_HZ as u64;
+    append_u32(buf, pos, (ticks / hz) as u32);
+    let cs = (ticks % hz) * 100 / hz;
+    append_str(buf, pos, &[b'.', b'0' + (cs / 10) as u8, b'0' + (cs % 10) as u8]);
+}
+
+pub fn cmd_regwatch(t: &mut Terminal, args: &[u8]) {
+    let mut it = trim(args).split(|&b| b == b' ').filter(|s| !s.is_empty());
+    let (Some(addr_tok), ms_tok, w_tok, None) = (it.next(), it.next(), it.next(), it.next()) else {
+        t.write_line("  Uso: regwatch <0xFISICA> [ms] [8|16|32|64]  (cualquier tecla detiene)", LineColor::Warning);
+        return;
+    };
+    let Some(phys) = parse_hex(addr_tok) else {
+        t.write_line("  Error: direccion invalida (usa prefijo 0x)", LineColor::Error); return;
+    };
+    let ms = match ms_tok.map(parse_u64) {
+        None                                    => REGWATCH_MS,
+        Some(Some(ms)) if (10..=10_000).contains(&ms) => ms,
+        Some(_) => { t.write_line("  Error: intervalo invalido (10-10000 ms)", LineColor::Error); return; }
+    };
+    let width: u8 = match w_tok {
+        None | Some(b"32") => 4,
+        Some(b"8")  => 1,
+        Some(b"16") => 2,
+        Some(b"64") => 8,
+        Some(_) => { t.write_line("  Error: ancho invalido (8, 16, 32 o 64)", LineColor::Error); return; }
+    };
+    if phys % width as u64 != 0 {
+        t.write_line("  Error: direccion no alineada al ancho del registro", LineColor::Error);
+        return;
+    }
+    if t.redirecting {
+        t.write_line("  regwatch no admite redireccion (es interactivo)", LineColor::Error);
+        return;
+    }
+
+    let (virt, owned) = match paging::mmio_lookup(phys, width as u64) {
+        Some(v) => (v, false),
+        None => match paging::map_mmio(phys, width as u64) {
+            Ok(v)  => (v, true),
+            Err(e) => {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  Error: no se pudo mapear: ");
+                append_str(&mut buf, &mut pos, e.as_bytes());
+                t.write_bytes(&buf[..pos], LineColor::Error);
+                return;
+            }
+        },
+    };
+    let now = crate::time::pit::ticks();
+    let mut w = RegWatch {
+        phys, virt, width, interval: (ms / 10).max(1), next: 0, last: 0,
+        samples: 1, changes: 0, owned,
+    };
+    w.last = w.read();
+    w.next = now + w.interval;
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  regwatch 0x");
+    append_hex64_short(&mut buf, &mut pos, phys);
+    append_str(&mut buf, &mut pos, b" (");
+    append_u32(&mut buf, &mut pos, width as u32 * 8);
+    append_str(&mut buf, &mut pos, b" bits) cada ");
+    append_u32(&mut buf, &mut pos, ms as u32);
+    append_str(&mut buf, &mut pos, b" ms  virt 0x");
+    append_hex64_short(&mut buf, &mut pos, virt);
+    append_str(&mut buf, &mut pos, if owned { b"" } else { b" (mapeo del driver)" });
+    t.write_bytes(&buf[..pos], LineColor::Info);
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  [");
+    append_secs(&mut buf, &mut pos, now);
+    append_str(&mut buf, &mut pos, b"] inicial ");
+    append_reg(&mut buf, &mut pos, w.last, width);
+    append_str(&mut buf, &mut pos, b"   (cualquier tecla detiene)");
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+    t.regwatch = Some(w);
+}
+
+/// Muestreo pendiente del `regwatch` activo. Devuelve true si escribió algo.
+pub fn regwatch_poll(t: &mut Terminal, now: u64) -> bool {
+    let Some(w) = t.regwatch.as_mut() else { return false };
+    if now < w.next { return false; }
+    // Sin recuperar muestras perdidas si el loop se retrasó
+    w.next = now + w.interval;
+    w.samples += 1;
+    let v = w.read();
+    if v == w.last { return false; }
+    let (old, width) = (w.last, w.width);
+    w.last = v;
+    w.changes += 1;
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  [");
+    append_secs(&mut buf, &mut pos, now);
+    append_str(&mut buf, &mut pos, b"] ");
+    append_reg(&mut buf, &mut pos, old, width);
+    append_str(&mut buf, &mut pos, b" -> ");
+    append_reg(&mut buf, &mut pos, v, width);
+    append_str(&mut buf, &mut pos, b"  bits ");
+    append_reg(&mut buf, &mut pos, old ^ v, width);
+    t.write_bytes(&buf[..pos], LineColor::Success);
+    true
+}
+
+/// Detiene el `regwatch` activo, deshace su mapeo y escribe el resumen.
+pub fn regwatch_stop(t: &mut Terminal) {
+    let Some(w) = t.regwatch.take() else { return };
+    let unmapped = w.owned && paging::unmap_mmio(w.virt).is_ok();
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  regwatch 0x");
+    append_hex64_short(&mut buf, &mut pos, w.phys);
+    append_str(&mut buf, &mut pos, b" detenido: ");
+    append_u32(&mut buf, &mut pos, w.samples);
+    append_str(&mut buf, &mut pos, b" muestras, ");
+    append_u32(&mut buf, &mut pos, w.changes);
+    append_str(&mut buf, &mut pos, if w.changes == 1 { b" cambio" } else { b" cambios" });
+    if unmapped { append_str(&mut buf, &mut pos, b" (MMIO desmapeado)"); }
+    t.write_bytes(&buf[..pos], LineColor::Info);
+    t.remote_prompt();
+}
+
+// ── reg ───────────────────────────────────────────────────────────────────────
+//
+// Captura en vivo con la misma rutina que el panic (isr_handlers::capture_live)
+// sobre un frame local. Los valores son los del propio comando en ese punto:
+// útil para ver el estado de control (RFLAGS, CR0/CR4) y el aspecto de la pila.
+
+/// Todo lo que lista `reg`, en orden de salida. El tamaño fijo obliga a que
+/// cualquier registro nuevo aparezca aquí.
+fn reg_rows(f: &crate::arch::isr_handlers::CrashFrame, ctl: [u64; 3]) -> [(&'static str, u64); 22] {
+    [
+        ("RAX", f.rax), ("RBX", f.rbx), ("RCX", f.rcx),
+        ("RDX", f.rdx), ("RSI", f.rsi), ("RDI", f.rdi),
+        ("RBP", f.rbp), ("RSP", f.rsp), ("R8 ", f.r8),
+        ("R9 ", f.r9),  ("R10", f.r10), ("R11", f.r11),
+        ("R12", f.r12), ("R13", f.r13), ("R14", f.r14),
+        ("R15", f.r15), ("RIP", f.rip), ("RFL", f.rflags),
+        ("CR0", ctl[0]), ("CR2", ctl[1]), ("CR3", f.cr3),
+        ("CR4", ctl[2]),
+    ]
+}
+
+/// Bits con nombre de RFLAGS / CR0 / CR4 que `reg` decodifica.
+const RFLAGS_BITS: &[(u32, &[u8])] = &[
+    (0, b"CF"), (2, b"PF"), (4, b"AF"), (6, b"ZF"), (7, b"SF"),
+    (8, b"TF"), (9, b"IF"), (10, b"DF"), (11, b"OF"), (21, b"ID"),
+];
+const CR0_BITS: &[(u32, &[u8])] = &[
+    (0, b"PE"), (1, b"MP"), (2, b"EM"), (3, b"TS"), (5, b"NE"),
+    (16, b"WP"), (18, b"AM"), (29, b"NW"), (30, b"CD"), (31, b"PG"),
+];
+const CR4_BITS: &[(u32, &[u8])] = &[
+    (4, b"PSE"), (5, b"PAE"), (7, b"PGE"), (9, b"OSFXSR"),
+    (10, b"OSXMMEXCPT"), (13, b"VMXE"), (18, b"OSXSAVE"), (20, b"SMEP"), (21, b"SMAP"),
+];
+
+fn append_bits(buf: &mut [u8], pos: &mut usize, v: u64, bits: &[(u32, &[u8])]) {
+    append_str(buf, pos, b"[");
+    let mut first = true;
+    for (bit, name) in bits {
+        if v & (1 << bit) == 0 { continue; }
+        if !first { append_str(buf, pos, b" "); }
+        append_str(buf, pos, name);
+        first = false;
+    }
+    append_str(buf, pos, b"]");
+}
+
+pub fn cmd_reg(t: &mut Terminal) {
+    // Primero la captura: cualquier trabajo previo cambiaría los GPR
+    let f = crate::arch::isr_handlers::capture_live();
+    let (cr0, cr2, cr4): (u64, u64, u64);
+    unsafe {
+        core::arch::asm!("mov {}, cr0", out(reg) cr0, options(nomem, nostack, preserves_flags));
+        core::arch::asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack, preserves_flags));
+        core::arch::asm!("mov {}, cr4", out(reg) cr4, options(nomem, nostack, preserves_flags));
+    }
+    let rows = reg_rows(&f, [cr0, cr2, cr4]);
+
+    t.separador("REGISTROS (CAPTURA EN VIVO)");
+    for chunk in rows[..17].chunks(3) {
+        let mut buf = [0u8; 96]; let mut pos = 0;
+        for (name, v) in chunk {
+            append_str(&mut buf, &mut pos, b"  ");
+            append_str(&mut buf, &mut pos, name.as_bytes());
+            append_str(&mut buf, &mut pos, b" ");
+            append_reg(&mut buf, &mut pos, *v, 8);
+        }
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    t.write_line("  Control:", LineColor::Header);
+    for (name, v) in &rows[17..] {
+        let mut buf = [0u8; 96]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_str(&mut buf, &mut pos, name.as_bytes());
+        append_str(&mut buf, &mut pos, b" ");
+        append_reg(&mut buf, &mut pos, *v, 8);
+        append_str(&mut buf, &mut pos, b"  ");
+        match *name {
+            "RFL" => {
+                append_bits(&mut buf, &mut pos, *v, RFLAGS_BITS);
+                append_str(&mut buf, &mut pos, b" IOPL=");
+                append_u32(&mut buf, &mut pos, ((*v >> 12) & 3) as u32);
+            }
+            "CR0" => append_bits(&mut buf, &mut pos, *v, CR0_BITS),
+            "CR2" => append_str(&mut buf, &mut pos, b"(ultima direccion de #PF)"),
+            "CR3" => {
+                append_str(&mut buf, &mut pos, b"PML4 en 0x");
+                append_hex64_short(&mut buf, &mut pos, *v & !0xFFF);
+            }
+            "CR4" => append_bits(&mut buf, &mut pos, *v, CR4_BITS),
+            _ => {}
+        }
+        t.write_bytes(&buf[..pos], LineColor::Info);
+    }
+    {
+        let mut sb = [0u8; 64];
+        let sym = crate::arch::symbols::fmt_symbol(f.rip, &mut sb);
+        let mut buf = [0u8; 96]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  RIP/RSP son los de la propia captura: ");
+        append_str(&mut buf, &mut pos, sym.as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    t.write_empty();
+}
+
 // ── inb / outb ────────────────────────────────────────────────────────────────
 
 pub fn cmd_inb(t: &mut Terminal, args: &[u8]) {
@@ -323,3 +755,359 @@ pub fn cmd_outb(t: &mut Terminal, args: &[u8]) {
     append_str(&mut buf, &mut pos, b") completado");
     t.write_bytes(&buf[..pos], LineColor::Success);
 }
+
+// ── profile ───────────────────────────────────────────────────────────────────
+//
+// `profile on` arma el muestreo en IRQ0 (time::profile) y vuelve al prompt:
+// las muestras reflejan el loop principal tal y como corre normalmente.
+// `profile show` agrupa los RIP y los atribuye a la función conocida más
+// cercana por debajo. Sin tabla de símbolos la atribución es aproximada: solo
+// se conocen las funciones de `PROFILE_FUNCS` y el código inlineado cuenta
+// para quien lo llama.
+
+const PROFILE_SECS:  u64   = 5;
+const PROFILE_TOP:   usize = 10;
+/// Distancia máxima a la función conocida para atribuirle una muestra.
+const PROFILE_REACH: u64   = 16 * 1024;
+const PROFILE_NFUNCS: usize = 14;
+
+/// Funciones conocidas (inicio de su código) para atribuir los RIP.
+fn profile_funcs() -> [(&'static str, u64); PROFILE_NFUNCS] {
+    use crate::graphics::driver::framebuffer::{Console, Framebuffer};
+    [
+        ("rust_main",          crate::rust_main as *const () as u64),
+        ("pit_tick",           crate::time::pit::pit_tick as *const () as u64),
+        ("draw_chrome",        crate::ui::chrome::draw_chrome as *const () as u64),
+        ("draw_terminal_tab",  crate::ui::tabs::terminal::draw_terminal_tab as *const () as u64),
+        ("draw_ide_tab",       crate::ui::tabs::ide::draw_ide_tab as *const () as u64),
+        ("draw_explorer_tab",  crate::ui::tabs::explorer::draw_explorer_tab as *const () as u64),
+        ("draw_system_tab",    crate::ui::tabs::system::draw_system_tab as *const () as u64),
+        ("draw_devices_tab",   crate::ui::tabs::devices::draw_devices_tab as *const () as u64),
+        ("Framebuffer::present",   Framebuffer::present as *const () as u64),
+        ("Framebuffer::fill_rect", Framebuffer::fill_rect as *const () as u64),
+        ("Console::fill_rect", Console::fill_rect as *const () as u64),
+        ("Console::write_at",  Console::write_at as *const () as u64),
+        ("cmd_profile",        cmd_profile as *const () as u64),
+        ("dispatch",           super::dispatch as *const () as u64),
+    ]
+}
+
+/// Función conocida que contiene `rip` (la más cercana por debajo).
+fn profile_owner(funcs: &[(&'static str, u64)], rip: u64) -> Option<(&'static str, u64)> {
+    let (lo, hi) = paging::kernel_text();
+    if rip < lo || rip >= hi { return None; }
+    funcs.iter()
+        .filter(|&&(_, a)| a <= rip && rip - a < PROFILE_REACH)
+        .max_by_key(|&&(_, a)| a)
+        .map(|&(name, a)| (name, rip - a))
+}
+
+fn append_pct(buf: &mut [u8], pos: &mut usize, n: usize, total: usize) {
+    let pm = (n * 1000 / total.max(1)) as u32;
+    if pm < 1000 { append_str(buf, pos, b" "); }
+    if pm < 100  { append_str(buf, pos, b" "); }
+    append_u32(buf, pos, pm / 10);
+    append_str(buf, pos, b".");
+    append_u32(buf, pos, pm % 10);
+    append_str(buf, pos, b"%");
+}
+
+fn append_count(buf: &mut [u8], pos: &mut usize, n: usize) {
+    let mut w = 1; let mut v = n;
+    while v >= 10 { v /= 10; w += 1; }
+    for _ in w..5 { append_str(buf, pos, b" "); }
+    append_u32(buf, pos, n as u32);
+}
+
+pub fn cmd_profile(t: &mut Terminal, args: &[u8]) {
+    let mut it = trim(args).split(|&b| b == b' ').filter(|s| !s.is_empty());
+    match (it.next(), it.next(), it.next()) {
+        (Some(b"on"), secs, None) => {
+            let secs = match secs.map(parse_u64) {
+                None => PROFILE_SECS,
+                Some(Some(s)) if (1..=10).contains(&s) => s,
+                Some(_) => { t.write_line("  Error: duracion invalida (1-10 s)", LineColor::Error); return; }
+            };
+            let n = (secs * crate::time::pit::PIT_HZ as u64) as usize;
+            profile::start(n);
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  Muestreando RIP durante ");
+            append_u32(&mut buf, &mut pos, secs as u32);
+            append_str(&mut buf, &mut pos, b" s (");
+            append_u32(&mut buf, &mut pos, n.min(profile::MAX_SAMPLES) as u32);
+            append_str(&mut buf, &mut pos, b" muestras). Luego: profile show");
+            t.write_bytes(&buf[..pos], LineColor::Info);
+        }
+        (Some(b"off"), None, _) => {
+            profile::stop();
+            let (n, _) = profile::progress();
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  Muestreo detenido con ");
+            append_u32(&mut buf, &mut pos, n as u32);
+            append_str(&mut buf, &mut pos, b" muestras.");
+            t.write_bytes(&buf[..pos], LineColor::Success);
+        }
+        (Some(b"show"), None, _) => profile_show(t),
+        _ => t.write_line("  Uso: profile on [1-10 s] | off | show", LineColor::Warning),
+    }
+}
+
+fn profile_show(t: &mut Terminal) {
+    let mut samples = [0u64; profile::MAX_SAMPLES];
+    let total = profile::copy_samples(&mut samples);
+    if total == 0 {
+        t.write_line("  Sin muestras. Usa 'profile on' primero.", LineColor::Warning);
+        return;
+    }
+    let samples = &mut samples[..total];
+    samples.sort_unstable();
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Perfil: ");
+    append_u32(&mut buf, &mut pos, total as u32);
+    append_str(&mut buf, &mut pos, b" muestras a ");
+    append_u32(&mut buf, &mut pos, crate::time::pit::PIT_HZ);
+    append_str(&mut buf, &mut pos, b" Hz");
+    if profile::active() {
+        let (_, goal) = profile::progress();
+        append_str(&mut buf, &mut pos, b" (en curso, objetivo ");
+        append_u32(&mut buf, &mut pos, goal as u32);
+        append_str(&mut buf, &mut pos, b")");
+    }
+    t.write_bytes(&buf[..pos], LineColor::Info);
+
+    // Top de direcciones: grupos de RIP iguales en el array ordenado
+    let mut top = [(0u64, 0usize); PROFILE_TOP];
+    let mut i = 0;
+    while i < total {
+        let rip = samples[i];
+        let mut j = i;
+        while j < total && samples[j] == rip { j += 1; }
+        let n = j - i;
+        if let Some(k) = top.iter().position(|&(_, c)| n > c) {
+            top.copy_within(k..PROFILE_TOP - 1, k + 1);
+            top[k] = (rip, n);
+        }
+        i = j;
+    }
+    let funcs = profile_funcs();
+    t.write_line("  RIP                  muestras      %  funcion", LineColor::Header);
+    for &(rip, n) in top.iter().take_while(|&&(_, c)| c > 0) {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  0x");
+        append_hex64_full(&mut buf, &mut pos, rip);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_count(&mut buf, &mut pos, n);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_pct(&mut buf, &mut pos, n, total);
+        append_str(&mut buf, &mut pos, b"  ");
+        match profile_owner(&funcs, rip) {
+            Some((name, off)) => {
+                append_str(&mut buf, &mut pos, name.as_bytes());
+                append_str(&mut buf, &mut pos, b"+0x");
+                append_hex64_short(&mut buf, &mut pos, off);
+            }
+            None => append_str(&mut buf, &mut pos, b"?"),
+        }
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+
+    // Agregado por función conocida
+    let mut per_fn = [0usize; PROFILE_NFUNCS];
+    let mut unknown = 0;
+    for &rip in samples.iter() {
+        match profile_owner(&funcs, rip).and_then(|(name, _)| funcs.iter().position(|&(f, _)| f == name)) {
+            Some(k) => per_fn[k] += 1,
+            None    => unknown += 1,
+        }
+    }
+    t.write_line("  Por funcion (aproximado):", LineColor::Header);
+    let mut order = [0usize; PROFILE_NFUNCS];
+    for (k, slot) in order.iter_mut().enumerate() { *slot = k; }
+    order.sort_unstable_by(|&a, &b| per_fn[b].cmp(&per_fn[a]));
+    for &k in order.iter().filter(|&&k| per_fn[k] > 0) {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"    ");
+        append_count(&mut buf, &mut pos, per_fn[k]);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_pct(&mut buf, &mut pos, per_fn[k], total);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_str(&mut buf, &mut pos, funcs[k].0.as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    if unknown > 0 {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"    ");
+        append_count(&mut buf, &mut pos, unknown);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_pct(&mut buf, &mut pos, unknown, total);
+        append_str(&mut buf, &mut pos, b"  ? (sin funcion conocida cerca)");
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+}
+
+// ── bench ─────────────────────────────────────────────────────────────────────
+//
+// `bench <comando>` ejecuta la línea como si se hubiera tecleado y mide los
+// ciclos del TSC que tarda. El tiempo sale de la frecuencia calibrada en el
+// arranque (time::tsc); sin TSC invariante es solo orientativo.
+
+/// `us` microsegundos como milisegundos con tres decimales ("12.034").
+fn append_ms(buf: &mut [u8], pos: &mut usize, us: u64) {
+    append_u64(buf, pos, us / 1000);
+    append_str(buf, pos, b".");
+    let frac = us % 1000;
+    if frac < 100 { append_str(buf, pos, b"0"); }
+    if frac < 10  { append_str(buf, pos, b"0"); }
+    append_u64(buf, pos, frac);
+}
+
+pub fn cmd_bench(
+    t:    &mut Terminal,
+    args: &[u8],
+    hw:   &crate::arch::hardware::HardwareInfo,
+    pci:  &crate::drivers::bus::pci::PciBus,
+) {
+    use crate::time::tsc;
+    let line = trim(args);
+    let cmd  = line.split(|&b| b == b' ').next().unwrap_or(b"");
+    if cmd.is_empty() {
+        t.write_line("  Uso: bench <comando> [args]", LineColor::Warning);
+        return;
+    }
+    if super::is_interactive(cmd) || cmd == b"bench" || line.contains(&b'>') {
+        t.write_line("  bench: no se puede medir un comando interactivo, anidado o redirigido", LineColor::Error);
+        t.fail();
+        return;
+    }
+    if !tsc::present() {
+        t.write_line("  bench: la CPU no tiene TSC", LineColor::Error);
+        t.fail();
+        return;
+    }
+
+    let c0 = tsc::read();
+    let ok = t.run_command(line, hw, pci);
+    let cycles = tsc::read().wrapping_sub(c0);
+
+    let hz = tsc::hz();
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  bench: ");
+    append_u64(&mut buf, &mut pos, cycles);
+    append_str(&mut buf, &mut pos, b" ciclos");
+    if hz > 0 {
+        let us = tsc::cycles_to_us(cycles, hz);
+        append_str(&mut buf, &mut pos, b" = ");
+        append_ms(&mut buf, &mut pos, us);
+        append_str(&mut buf, &mut pos, b" ms (TSC @ ");
+        append_u32(&mut buf, &mut pos, tsc::mhz());
+        append_str(&mut buf, &mut pos, b" MHz)");
+    } else {
+        append_str(&mut buf, &mut pos, b" (TSC sin calibrar)");
+    }
+    if !ok { append_str(&mut buf, &mut pos, b"  [el comando fallo]"); }
+    t.write_bytes(&buf[..pos], LineColor::Info);
+    if hz > 0 && !tsc::invariant() {
+        t.write_line("  Aviso: TSC no invariante, el tiempo es aproximado", LineColor::Warning);
+    }
+    // El resultado de `bench` es el del comando medido
+    if !ok { t.fail(); }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(off: u16, bytes: &[u8]) -> String {
+        let mut line = [0u8; TERM_COLS];
+        let n = sector_row(&mut line, off, bytes);
+        String::from_utf8(line[..n].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn sector_row_layout() {
+        let bytes: [u8; 16] = core::array::from_fn(|i| b'A' + i as u8);
+        assert_eq!(row(0x0010, &bytes),
+            "  0010   41 42 43 44 45 46 47 48  49 4A 4B 4C 4D 4E 4F 50  ABCDEFGHIJKLMNOP");
+    }
+
+    #[test]
+    fn sector_row_masks_non_printable() {
+        let mut bytes = [0u8; 16];
+        bytes[..4].copy_from_slice(&[0x1F, 0x20, 0x7E, 0x7F]);
+        bytes[15] = 0xFF;
+        let r = row(0x01F0, &bytes);
+        assert!(r.starts_with("  01F0   1F 20 7E 7F 00 00 00 00  00 00 00 00 00 00 00 FF "));
+        assert!(r.ends_with("FF  . ~............."));
+    }
+
+    #[test]
+    fn sector_rows_fit_the_terminal() {
+        // 512 bytes = 32 filas alineadas: la ASCII siempre en la misma columna
+        let sector = [0xAAu8; 512];
+        for (i, bytes) in sector.chunks_exact(16).enumerate() {
+            let r = row((i * 16) as u16, bytes);
+            assert_eq!(r.len(), 2 + 4 + 3 + 16 * 3 + 1 + 1 + 16);
+            assert!(r.len() <= TERM_COLS);
+        }
+    }
+
+    #[test]
+    fn bench_time_in_ms() {
+        let ms = |us: u64| {
+            let mut buf = [0u8; 32]; let mut pos = 0;
+            append_ms(&mut buf, &mut pos, us);
+            String::from_utf8(buf[..pos].to_vec()).unwrap()
+        };
+        assert_eq!(ms(0), "0.000");
+        assert_eq!(ms(7), "0.007");
+        assert_eq!(ms(45), "0.045");
+        assert_eq!(ms(1_000), "1.000");
+        assert_eq!(ms(12_034), "12.034");
+        assert_eq!(ms(u64::MAX), "18446744073709551.615");
+    }
+
+    #[test]
+    fn reg_rows_map_every_field() {
+        use crate::arch::isr_handlers::CrashFrame;
+        let mut f = CrashFrame::EMPTY;
+        (f.rax, f.rbx, f.rcx, f.rdx, f.rsi, f.rdi) = (1, 2, 3, 4, 5, 6);
+        (f.rbp, f.rsp, f.r8, f.r9, f.r10, f.r11) = (7, 8, 9, 10, 11, 12);
+        (f.r12, f.r13, f.r14, f.r15, f.rip, f.rflags) = (13, 14, 15, 16, 17, 18);
+        f.cr3 = 21;
+        let rows = reg_rows(&f, [19, 20, 22]);
+        // Valores en orden de salida: cada registro con su campo
+        for (i, (_, v)) in rows.iter().enumerate() {
+            assert_eq!(*v, i as u64 + 1, "{}", rows[i].0);
+        }
+        assert!(rows.iter().all(|(n, _)| n.len() == 3));
+        let mut names: Vec<_> = rows.iter().map(|(n, _)| *n).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), rows.len());
+    }
+
+    #[test]
+    fn reg_control_rows_start_at_17() {
+        // `reg` imprime rows[..17] como GPR y decodifica rows[17..]
+        let rows = reg_rows(&crate::arch::isr_handlers::CrashFrame::EMPTY, [0; 3]);
+        let ctl: Vec<_> = rows[17..].iter().map(|(n, _)| *n).collect();
+        assert_eq!(ctl, ["RFL", "CR0", "CR2", "CR3", "CR4"]);
+        assert_eq!(rows[16].0, "RIP");
+    }
+
+    #[test]
+    fn named_bits() {
+        let bits = |v: u64, table| {
+            let mut buf = [0u8; 96]; let mut pos = 0;
+            append_bits(&mut buf, &mut pos, v, table);
+            String::from_utf8(buf[..pos].to_vec()).unwrap()
+        };
+        assert_eq!(bits(0, RFLAGS_BITS), "[]");
+        assert_eq!(bits(0x202, RFLAGS_BITS), "[IF]");
+        assert_eq!(bits(0x8000_0011, CR0_BITS), "[PE PG]");
+        // Los bits sin nombre no aparecen
+        assert_eq!(bits(1 << 1 | 1 << 5, CR4_BITS), "[PAE]");
+    }
+}
+
@@ -36,19 +36,25 @@
 // Herramientas de disco y sistema de archivos para PORTIX.
 //
 // ┌─ Navegación ──────────────────────────────────────────────────────────────┐
-// │  ls   [ruta]          Listar directorio (actual si se omite)              │
+// │  ls [-l] [ruta]       Listar directorio (actual si se omite)              │
 // │  cd   <ruta>          Cambiar directorio                                  │
 // │  pwd                  Mostrar ruta actual                                 │
 // │  tree [ruta]          Árbol de directorios recursivo                      │
+// │  find <patron>        Buscar archivos por nombre (ver find.rs)            │
 // └───────────────────────────────────────────────────────────────────────────┘
 // ┌─ Archivos ─────────────────────────────────────────────────────────────────┐
 // │  cat  <archivo>       Ver contenido de un archivo de texto                │
+// │  wc   <archivo>       Contar líneas, palabras y bytes                     │
 // │  touch <archivo>      Crear archivo vacío                                 │
 // │  write <arch> <texto> Crear/sobreescribir archivo con texto               │
 // │  rm   <ruta>          Eliminar archivo o directorio vacío                 │
 // │  mv   <src> <dst>     Renombrar archivo o directorio                      │
 // │  stat <ruta>          Información detallada de entrada                    │
 // │  edit <archivo>       Abrir editor de texto tipo nano                     │
+// │  view <archivo>       Visor hexadecimal del archivo completo (scroll)     │
+// │  screenshot [a.bmp]   Captura de pantalla a BMP de 24 bpp                 │
+// │  img info <a.bmp>     Dimensiones, bpp y tamaño de un BMP                 │
+// │  img convert <s> <d>  Pre-escalar un BMP al tamaño de pantalla            │
 // └───────────────────────────────────────────────────────────────────────────┘
 // ┌─ Directorios ──────────────────────────────────────────────────────────────┐
 // │  mkdir <ruta>         Crear directorio                                    │
@@ -59,19 +65,25 @@
 // │  diskedit [lba] [drv] Editor hexadecimal de sector raw                    │
 // │  diskwrite <lba> <0x> Rellenar sector con patrón (solo debug)             │
 // │  diskpart             Panel tipo diskpart con layout del disco             │
+// │  remount [ro|rw]      Modo de montaje del volumen FAT32                   │
+// │  atareset [0|1]       Reset del canal ATA + nuevo IDENTIFY                │
+// │  sync                 FSInfo + flush de la caché del disco                │
 // └───────────────────────────────────────────────────────────────────────────┘
 
 #![allow(dead_code)]
 
-use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS, links};
 use crate::console::terminal::fmt::*;
 use crate::console::terminal::editor::EditorState;
+use crate::console::terminal::viewer::ViewerState;
 use crate::drivers::storage::ata::{
     AtaBus, AtaError, AtaDrive, DriveId, DriveType,
     get_cached_drive_info,  // v0.8.0: caché global — no re-escanea el bus
 };
-use crate::drivers::storage::fat32::{Fat32Volume, FatError};
+use crate::drivers::storage::fat32::{self, Fat32Volume, FatError};
 use crate::drivers::storage::vfs::{VfsMount, path_split, path_join, basename, parent_copy};
+use crate::graphics::driver::framebuffer::BackBufferView;
+use crate::graphics::render::bmp;
 
 // ── Helpers privados ──────────────────────────────────────────────────────────
 
@@ -97,7 +109,7 @@ fn parse_lba_drive(args: &[u8]) -> (u64, usize) {
     (lba, drv.min(3))
 }
 
-fn ata_err_msg(e: AtaError) -> &'static [u8] {
+pub(super) fn ata_err_msg(e: AtaError) -> &'static [u8] {
     match e {
         AtaError::Timeout         => "timeout del dispositivo".as_bytes(),
         AtaError::DriveFault      => "fallo de hardware del drive".as_bytes(),
@@ -108,7 +120,7 @@ fn ata_err_msg(e: AtaError) -> &'static [u8] {
     }
 }
 
-fn fat_err_msg(e: FatError) -> &'static [u8] {
+pub(super) fn fat_err_msg(e: FatError) -> &'static [u8] {
     match e {
         FatError::NotFound    => b"ruta no encontrada",
         FatError::NoSpace     => b"sin espacio en disco",
@@ -118,6 +130,7 @@ fn fat_err_msg(e: FatError) -> &'static [u8] {
         FatError::InvalidPath => b"ruta no valida",
         FatError::Corrupt     => b"sistema de archivos corrupto",
         FatError::NotFat32    => b"volumen no FAT32 o no montado",
+        FatError::ReadOnly    => b"volumen montado en solo lectura (usa 'remount rw')",
         FatError::Ata(e)      => ata_err_msg(e),
     }
 }
@@ -134,7 +147,7 @@ fn fat_err_msg(e: FatError) -> &'static [u8] {
 //   Esto significa que necesitamos crear un AtaDrive nuevo en cada llamada,
 //   pero usando from_info() (que NO toca el hardware), no scan() (que sí).
 
-fn mount_vol(t: &mut Terminal) -> Option<(Fat32Volume, VfsMount)> {
+pub(super) fn mount_vol(t: &mut Terminal) -> Option<(Fat32Volume, VfsMount)> {
     // v0.8.0: usar caché en lugar de scan()
     let info = match get_cached_drive_info() {
         Some(i) => i,
@@ -289,77 +302,288 @@ fn fmt_size(buf: &mut [u8], pos: &mut usize, bytes: u32) {
     }
 }
 
+/// Lee un archivo completo en `out` y devuelve el error sin escribirlo (el
+/// fallo al montar sí queda en el terminal y se devuelve como NotFat32).
+pub fn read_file_raw(t: &mut Terminal, path: &[u8], out: &mut [u8]) -> fat32::FatResult<usize> {
+    let path = trim(path);
+    let Some((vol, mnt)) = mount_vol(t) else { return Err(FatError::NotFat32) };
+
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+    let mut par = [0u8; 256];
+    let par_len = parent_copy(abs_s, &mut par);
+
+    resolve_path(&vol, &mnt, &par, par_len, b".")
+        .and_then(|dir| vol.find_entry(dir, basename(abs_s)))
+        .and_then(|e| if e.is_dir { Err(FatError::IsDir) } else { Ok(e) })
+        .and_then(|e| vol.read_file(&e, out))
+}
+
+/// Lee un archivo de texto completo en `out` (lo usan `source` y otros
+/// comandos que consumen archivos). Los errores se escriben con prefijo `who`.
+pub fn read_text_file(t: &mut Terminal, who: &[u8], path: &[u8], out: &mut [u8]) -> Option<usize> {
+    match read_file_raw(t, path, out) {
+        Ok(n) => Some(n),
+        // mount_vol ya escribió el motivo
+        Err(FatError::NotFat32) => None,
+        Err(e) => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  ");
+            append_str(&mut buf, &mut pos, who);
+            append_str(&mut buf, &mut pos, b": ");
+            append_str(&mut buf, &mut pos, fat_err_msg(e));
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            None
+        }
+    }
+}
+
+/// Tamaño máximo de un archivo al anexar con `>>` (existente + nuevo).
+const APPEND_MAX: usize = 64 * 1024;
+static mut APPEND_BUF: [u8; APPEND_MAX] = [0u8; APPEND_MAX];
+
+/// Escribe `data` en `path` creando o truncando el archivo; con `append`
+/// se añade al final del contenido existente. Errores con prefijo `who`.
+pub fn write_text_file(t: &mut Terminal, who: &[u8], path: &[u8], data: &[u8], append: bool) -> bool {
+    let path = trim(path);
+    let Some((vol, mnt)) = mount_vol(t) else { return false };
+
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+    let mut par = [0u8; 256];
+    let par_len = parent_copy(abs_s, &mut par);
+    let name    = basename(abs_s);
+
+    let mut too_big = false;
+    let res = resolve_path(&vol, &mnt, &par, par_len, b".").and_then(|dir| {
+        let existing = match vol.find_entry(dir, name) {
+            Ok(e) if e.is_dir => return Err(FatError::IsDir),
+            Ok(e)             => Some(e),
+            Err(FatError::NotFound) => None,
+            Err(e)            => return Err(e),
+        };
+        match existing {
+            Some(e) if append && e.size > 0 => {
+                let old = e.size as usize;
+                if old + data.len() > APPEND_MAX { too_big = true; return Err(FatError::NoSpace); }
+                // SAFETY: kernel monohilo; el buffer solo se usa aquí.
+                let buf = unsafe { &mut *core::ptr::addr_of_mut!(APPEND_BUF) };
+                let n = vol.read_file(&e, &mut buf[..old])?;
+                buf[n..n + data.len()].copy_from_slice(data);
+                vol.write_file_in(dir, name, &buf[..n + data.len()])
+            }
+            _ => vol.write_file_in(dir, name, data),
+        }
+    });
+
+    match res {
+        Ok(()) => true,
+        Err(e) => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  ");
+            append_str(&mut buf, &mut pos, who);
+            append_str(&mut buf, &mut pos, b": ");
+            if too_big {
+                append_str(&mut buf, &mut pos, b"el archivo superaria 64 KiB al anexar");
+            } else {
+                append_str(&mut buf, &mut pos, fat_err_msg(e));
+            }
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            false
+        }
+    }
+}
+
+/// true si `path` existe (archivo o directorio). No escribe nada si no existe.
+pub fn path_exists(t: &mut Terminal, path: &[u8]) -> bool {
+    let path = trim(path);
+    let Some((vol, mnt)) = mount_vol(t) else { return false };
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    resolve_path(&vol, &mnt, &abs, abs_len, b".").is_ok()
+}
+
+/// Crea el directorio `name` en la raíz si no existe (p. ej. /portix para
+/// los archivos de configuración). Los errores se escriben con prefijo `who`.
+pub fn ensure_root_dir(t: &mut Terminal, who: &[u8], name: &str) -> bool {
+    let Some((vol, _)) = mount_vol(t) else { return false };
+    match vol.find_or_create_dir(vol.root_cluster(), name) {
+        Ok(_) => true,
+        Err(e) => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  ");
+            append_str(&mut buf, &mut pos, who);
+            append_str(&mut buf, &mut pos, b": ");
+            append_str(&mut buf, &mut pos, fat_err_msg(e));
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            false
+        }
+    }
+}
+
+/// Tipo de una ruta existente: Some(true) si es directorio, Some(false) si es
+/// archivo. None si no existe (sin mensaje) o si no hay volumen montable.
+pub fn path_kind(t: &mut Terminal, path: &[u8]) -> Option<bool> {
+    let path = trim(path);
+    let (vol, mnt) = mount_vol(t)?;
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+    if abs_s == "/" { return Some(true); }
+    let mut par = [0u8; 256];
+    let par_len = parent_copy(abs_s, &mut par);
+    resolve_path(&vol, &mnt, &par, par_len, b".")
+        .and_then(|dir| vol.find_entry(dir, basename(abs_s)))
+        .ok()
+        .map(|e| e.is_dir)
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // COMANDOS DE NAVEGACIÓN — idénticos a v0.7.5 excepto que usan nuevo mount_vol
 // ═══════════════════════════════════════════════════════════════════════════════
 
-pub fn cmd_ls(t: &mut Terminal, args: &[u8]) {
-    let (vol, mnt) = match mount_vol(t) { Some(x) => x, None => return };
-    let cwd     = t.cwd;
-    let cwd_len = t.cwd_len;
+// ── ls ────────────────────────────────────────────────────────────────────────
+//
+//   ls [-l] [ruta]    Sin ruta lista el cwd. Una ruta a un archivo muestra
+//                     solo esa entrada. -l añade tamaño exacto, fecha de
+//                     modificación y primer clúster.
 
-    let cluster = if trim(args).is_empty() {
-        mnt.resolve(core::str::from_utf8(&cwd[..cwd_len]).unwrap_or("/"))
-           .unwrap_or(mnt.root_cluster())
-    } else {
-        match resolve_path(&vol, &mnt, &cwd, cwd_len, trim(args)) {
-            Ok(c) => c,
-            Err(e) => {
-                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
-                append_str(&mut buf, &mut pos, b"  ls: ");
-                let em = fat_err_msg(e);
-                buf[pos..pos + em.len()].copy_from_slice(em); pos += em.len();
-                t.write_bytes(&buf[..pos], LineColor::Error);
+/// Columna del nombre en las filas de `ls -l`.
+const LS_LONG_NAME_COL: usize = 49;
+
+/// "AAAA-MM-DD HH:MM" a partir de `DirEntryInfo::mtime` (fecha y hora FAT).
+fn append_fat_mtime(buf: &mut [u8], pos: &mut usize, mtime: u32) {
+    if mtime == 0 {
+        append_str(buf, pos, b"----------  -----");
+        return;
+    }
+    let (date, time) = (mtime >> 16, mtime & 0xFFFF);
+    let two = |buf: &mut [u8], pos: &mut usize, v: u32| {
+        if v < 10 { append_str(buf, pos, b"0"); }
+        append_u32(buf, pos, v);
+    };
+    append_u32(buf, pos, 1980 + (date >> 9));
+    append_str(buf, pos, b"-");
+    two(buf, pos, (date >> 5) & 0x0F);
+    append_str(buf, pos, b"-");
+    two(buf, pos, date & 0x1F);
+    append_str(buf, pos, b"  ");
+    two(buf, pos, time >> 11);
+    append_str(buf, pos, b":");
+    two(buf, pos, (time >> 5) & 0x3F);
+}
+
+/// Escribe la fila de `ls` (corta o `-l`) de una entrada. El nombre queda
+/// en LS_NAME_COL / LS_LONG_NAME_COL y es un enlace clickable.
+fn ls_row(t: &mut Terminal, e: &fat32::DirEntryInfo, long: bool) {
+    let mut buf = [0u8; TERM_COLS];
+    let (pos, col, nl) = format_ls_row(&mut buf, e, long);
+    t.write_bytes(&buf[..pos], if e.is_dir { LineColor::Info } else { LineColor::Normal });
+    if long { t.set_last_link(col, nl); }
+}
+
+/// Texto de la fila de `ls_row`. Devuelve (longitud, columna del nombre,
+/// bytes del nombre mostrados).
+fn format_ls_row(buf: &mut [u8; TERM_COLS], e: &fat32::DirEntryInfo, long: bool) -> (usize, usize, usize) {
+    let mut pos = 0;
+    append_str(buf, &mut pos, if e.is_dir { b"  [DIR]  " } else { b"  [ARC]  " });
+    if long {
+        let mut num = [0u8; 12]; let mut nl = 0;
+        if e.is_dir { append_str(&mut num, &mut nl, b"-"); } else { append_u32(&mut num, &mut nl, e.size); }
+        for _ in nl..10 { buf[pos] = b' '; pos += 1; }
+        append_str(buf, &mut pos, &num[..nl]);
+        append_str(buf, &mut pos, b"  ");
+        append_fat_mtime(buf, &mut pos, e.mtime);
+        append_str(buf, &mut pos, b"  ");
+        append_u32(buf, &mut pos, e.cluster);
+    } else if !e.is_dir {
+        fmt_size(buf, &mut pos, e.size);
+    }
+    // Con tamaño y clúster de 10 cifras la fila pasa de la columna: el
+    // nombre se corre para no pegarse al clúster
+    let col = (if long { LS_LONG_NAME_COL } else { links::LS_NAME_COL }).max(pos + 1);
+    while pos < col { buf[pos] = b' '; pos += 1; }
+
+    let name = &e.name[..e.name_len];
+    let nl = name.len().min(TERM_COLS - pos);
+    append_str(buf, &mut pos, &name[..nl]);
+    (pos, col, nl)
+}
+
+pub fn cmd_ls(t: &mut Terminal, args: &[u8]) {
+    let mut long = false;
+    let mut path: &[u8] = b"";
+    for tok in trim(args).split(|&b| b == b' ').filter(|s| !s.is_empty()) {
+        match tok {
+            b"-l" => long = true,
+            _ if tok[0] == b'-' || !path.is_empty() => {
+                t.write_line("  Uso: ls [-l] [ruta]", LineColor::Warning);
                 return;
             }
+            _ => path = tok,
         }
+    }
+
+    let (vol, _mnt) = match mount_vol(t) { Some(x) => x, None => return };
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+
+    let Some(entry) = vol.resolve_path(abs_s) else {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ls: no existe: ");
+        append_str(&mut buf, &mut pos, &abs[..abs_len.min(TERM_COLS - 20)]);
+        t.write_bytes(&buf[..pos], LineColor::Error);
+        return;
     };
 
+    // Ruta absoluta del directorio: los enlaces clickables de los nombres se
+    // resuelven contra esta cabecera (ver console/terminal/links.rs). Para un
+    // archivo, la cabecera es su directorio padre.
     {
+        let mut par = [0u8; 256];
+        let dir: &[u8] = if entry.is_dir {
+            &abs[..abs_len]
+        } else {
+            let n = parent_copy(abs_s, &mut par);
+            &par[..n]
+        };
         let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
         append_str(&mut buf, &mut pos, b"  Directorio: ");
-        if trim(args).is_empty() {
-            let l = cwd_len.min(TERM_COLS - pos);
-            buf[pos..pos + l].copy_from_slice(&cwd[..l]); pos += l;
-        } else {
-            let a = trim(args);
-            let l = a.len().min(TERM_COLS - pos);
-            buf[pos..pos + l].copy_from_slice(&a[..l]); pos += l;
-        }
+        let l = dir.len().min(TERM_COLS - pos);
+        append_str(&mut buf, &mut pos, &dir[..l]);
         t.write_bytes(&buf[..pos], LineColor::Info);
     }
-    t.write_line("  Tipo  Tamaño      Nombre", LineColor::Header);
-    t.write_line("  ----  ----------  ------", LineColor::Normal);
+    if long {
+        t.write_line("  Tipo        Bytes  Modificado         Cluster  Nombre", LineColor::Header);
+        t.write_line("  -----  ----------  -----------------  -------- ------", LineColor::Normal);
+    } else {
+        t.write_line("  Tipo  Tamaño      Nombre", LineColor::Header);
+        t.write_line("  ----  ----------  ------", LineColor::Normal);
+    }
+
+    if !entry.is_dir {
+        ls_row(t, &entry, long);
+        t.write_empty();
+        return;
+    }
 
     let mut count_files = 0u32;
     let mut count_dirs  = 0u32;
     let mut total_bytes = 0u64;
 
-    let result = vol.list_dir(cluster, |e| {
+    let result = vol.list_dir(entry.cluster, |e| {
         let name = e.name_str();
         if name == "." || name == ".." { return; }
-
-        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
-        append_str(&mut buf, &mut pos, b"  ");
-
         if e.is_dir {
-            append_str(&mut buf, &mut pos, b"[DIR]  ");
-            append_str(&mut buf, &mut pos, b"           ");
             count_dirs += 1;
         } else {
-            append_str(&mut buf, &mut pos, b"[ARC]  ");
-            fmt_size(&mut buf, &mut pos, e.size);
-            while pos < 20 { buf[pos] = b' '; pos += 1; }
             count_files += 1;
             total_bytes += e.size as u64;
         }
-
-        let nb = name.as_bytes();
-        let nl = nb.len().min(TERM_COLS - pos);
-        buf[pos..pos + nl].copy_from_slice(&nb[..nl]); pos += nl;
-
-        let color = if e.is_dir { LineColor::Info } else { LineColor::Normal };
-        t.write_bytes(&buf[..pos], color);
+        ls_row(t, e, long);
     });
 
     if let Err(e) = result {
@@ -371,7 +595,8 @@ pub fn cmd_ls(t: &mut Terminal, args: &[u8]) {
         return;
     }
 
-    t.write_line("  ----  ----------  ------", LineColor::Normal);
+    t.write_line(if long { "  -----  ----------  -----------------  -------- ------" }
+                 else    { "  ----  ----------  ------" }, LineColor::Normal);
     {
         let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
         append_str(&mut buf, &mut pos, b"  ");
@@ -491,6 +716,55 @@ fn draw_tree(t: &mut Terminal, vol: &Fat32Volume, cluster: u32, depth: usize, ma
 // COMANDOS DE ARCHIVOS — idénticos a v0.7.5
 // ═══════════════════════════════════════════════════════════════════════════════
 
+// ── cat ───────────────────────────────────────────────────────────────────────
+//
+// Lee el archivo por bloques (read_file_at) y lo imprime numerado. Las líneas
+// más anchas que el terminal continúan en filas sangradas; los bytes no
+// imprimibles salen como '.'. Pasadas CAT_MAX_LINES líneas se corta con aviso.
+
+const CAT_MAX_LINES: u32   = 500;
+const CAT_CHUNK:     usize = 4096;
+/// Ancho de "  NNNN  " delante de cada línea.
+const CAT_GUTTER:    usize = 8;
+
+/// Fila de salida de `cat` en construcción.
+struct CatRow {
+    buf:    [u8; TERM_COLS],
+    pos:    usize,
+    line_n: u32,
+}
+
+impl CatRow {
+    fn start(&mut self, cont: bool) {
+        self.pos = 0;
+        append_str(&mut self.buf, &mut self.pos, b"  ");
+        if !cont {
+            let mut num = [0u8; 12]; let mut nl = 0;
+            append_u32(&mut num, &mut nl, self.line_n);
+            for _ in nl..4 { self.buf[self.pos] = b' '; self.pos += 1; }
+            append_str(&mut self.buf, &mut self.pos, &num[..nl]);
+        }
+        while self.pos < CAT_GUTTER { self.buf[self.pos] = b' '; self.pos += 1; }
+    }
+
+    fn flush(&mut self, t: &mut Terminal) {
+        t.write_bytes(&self.buf[..self.pos], LineColor::Normal);
+    }
+
+    fn push(&mut self, t: &mut Terminal, b: u8) {
+        if self.pos >= TERM_COLS {
+            self.flush(t);
+            self.start(true);
+        }
+        self.buf[self.pos] = match b {
+            b'\t'              => b' ',
+            0x20..=0x7E        => b,
+            _                  => b'.',
+        };
+        self.pos += 1;
+    }
+}
+
 pub fn cmd_cat(t: &mut Terminal, args: &[u8]) {
     let args = trim(args);
     if args.is_empty() {
@@ -498,96 +772,465 @@ pub fn cmd_cat(t: &mut Terminal, args: &[u8]) {
         return;
     }
 
-    let (vol, mnt) = match mount_vol(t) { Some(x) => x, None => return };
+    let (vol, _mnt) = match mount_vol(t) { Some(x) => x, None => return };
 
-    let name = basename(core::str::from_utf8(args).unwrap_or(""));
-    let mut par = [0u8; 256]; let par_len;
     let mut abs = [0u8; 256];
     let abs_len = make_abs_path(&t.cwd, t.cwd_len, args, &mut abs);
-    {
-        par_len = parent_copy(core::str::from_utf8(&abs[..abs_len]).unwrap_or("/"), &mut par);
+    let Some(entry) = vol.resolve_path(core::str::from_utf8(&abs[..abs_len]).unwrap_or("")) else {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  cat: ");
+        append_str(&mut buf, &mut pos, fat_err_msg(FatError::NotFound));
+        t.write_bytes(&buf[..pos], LineColor::Error);
+        return;
+    };
+
+    if entry.is_dir {
+        t.write_line("  cat: es un directorio. Usa 'ls' para listar su contenido.", LineColor::Warning);
+        return;
     }
 
-    let dir_cluster = match resolve_path(&vol, &mnt, &par, par_len, b".") {
-        Ok(c) => c,
-        Err(_) => {
-            mnt.resolve(core::str::from_utf8(&t.cwd[..t.cwd_len]).unwrap_or("/"))
-               .unwrap_or(mnt.root_cluster())
+    let size = entry.size as usize;
+    let mut chunk = [0u8; CAT_CHUNK];
+    let mut off   = 0usize;
+    let mut row   = CatRow { buf: [0u8; TERM_COLS], pos: 0, line_n: 1 };
+    let mut prev_cr   = false;
+    let mut truncated = false;
+
+    'read: while off < size {
+        let n = match vol.read_file_at(&entry, off, &mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) => {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  cat: error leyendo: ");
+                append_str(&mut buf, &mut pos, fat_err_msg(e));
+                t.write_bytes(&buf[..pos], LineColor::Error);
+                return;
+            }
+        };
+
+        if off == 0 {
+            // Cabecera, con aviso si el primer bloque parece binario
+            let binary = chunk[..n].iter()
+                .filter(|&&b| b < 0x20 && !matches!(b, b'\n' | b'\r' | b'\t'))
+                .count() * 10 > n;
+            let mut hdr = [0u8; TERM_COLS]; let mut hp = 0;
+            append_str(&mut hdr, &mut hp, b"  --- ");
+            append_str(&mut hdr, &mut hp, &abs[..abs_len.min(50)]);
+            append_str(&mut hdr, &mut hp, b" (");
+            append_u32(&mut hdr, &mut hp, size as u32);
+            append_str(&mut hdr, &mut hp, if binary { b" bytes, binario) ---" } else { b" bytes) ---" });
+            t.write_bytes(&hdr[..hp], LineColor::Header);
+            row.start(false);
+        }
+
+        for &b in &chunk[..n] {
+            // "\r\n" cuenta como un solo salto, aunque caiga entre bloques
+            if b == b'\n' && prev_cr { prev_cr = false; continue; }
+            prev_cr = b == b'\r';
+            if b == b'\n' || b == b'\r' {
+                row.flush(t);
+                row.line_n += 1;
+                if row.line_n > CAT_MAX_LINES { truncated = true; break 'read; }
+                row.start(false);
+            } else {
+                row.push(t, b);
+            }
+        }
+        off += n;
+    }
+
+    if size == 0 {
+        t.write_line("  (archivo vacio)", LineColor::Info);
+    } else if !truncated && row.pos > CAT_GUTTER {
+        row.flush(t);
+    }
+
+    if truncated {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ... mostradas ");
+        append_u32(&mut buf, &mut pos, CAT_MAX_LINES);
+        append_str(&mut buf, &mut pos, b" lineas; usa 'view' o 'edit' para el resto");
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    }
+    t.write_empty();
+}
+
+// ── wc ────────────────────────────────────────────────────────────────────────
+//
+// Lee el archivo por bloques de CAT_CHUNK, así que no hay límite de tamaño.
+// Una palabra es una racha de bytes que no son espacio, tab, CR ni LF. Las
+// líneas son los LF más una última línea sin salto final, si la hay (a
+// diferencia de `wc` de Unix, "a" cuenta como 1 línea). CR suelto no
+// cuenta: los archivos CRLF dan el mismo resultado que los LF.
+
+/// Contadores de `wc`; `feed` admite el archivo troceado en cualquier punto.
+#[derive(Clone, Copy)]
+pub(crate) struct WcCount {
+    pub lines: u64,
+    pub words: u64,
+    pub bytes: u64,
+    in_word:   bool,
+    /// El último byte visto fue LF (o aún no se ha visto ninguno).
+    at_bol:    bool,
+}
+
+impl WcCount {
+    pub const fn new() -> Self {
+        WcCount { lines: 0, words: 0, bytes: 0, in_word: false, at_bol: true }
+    }
+
+    pub fn feed(&mut self, data: &[u8]) {
+        for &b in data {
+            let space = matches!(b, b' ' | b'\t' | b'\r' | b'\n');
+            if !space && !self.in_word { self.words += 1; }
+            self.in_word = !space;
+            if b == b'\n' { self.lines += 1; }
+            self.at_bol = b == b'\n';
         }
+        self.bytes += data.len() as u64;
+    }
+
+    /// Cierra el recuento: la última línea sin LF también cuenta.
+    pub fn finish(mut self) -> Self {
+        if !self.at_bol { self.lines += 1; self.at_bol = true; }
+        self
+    }
+}
+
+pub fn cmd_wc(t: &mut Terminal, args: &[u8]) {
+    let args = trim(args);
+    if args.is_empty() {
+        t.write_line("  Uso: wc <archivo>", LineColor::Warning);
+        return;
+    }
+    let (vol, _mnt) = match mount_vol(t) { Some(x) => x, None => return };
+
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, args, &mut abs);
+    let Some(entry) = vol.resolve_path(core::str::from_utf8(&abs[..abs_len]).unwrap_or("")) else {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  wc: ");
+        append_str(&mut buf, &mut pos, fat_err_msg(FatError::NotFound));
+        t.write_bytes(&buf[..pos], LineColor::Error);
+        return;
     };
+    if entry.is_dir {
+        t.write_line("  wc: es un directorio.", LineColor::Warning);
+        return;
+    }
 
-    let entry = match vol.find_entry(dir_cluster, name) {
+    let size = entry.size as usize;
+    let mut chunk = [0u8; CAT_CHUNK];
+    let mut off   = 0usize;
+    let mut wc    = WcCount::new();
+    while off < size {
+        let n = match vol.read_file_at(&entry, off, &mut chunk) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) => {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  wc: error leyendo: ");
+                append_str(&mut buf, &mut pos, fat_err_msg(e));
+                t.write_bytes(&buf[..pos], LineColor::Error);
+                return;
+            }
+        };
+        wc.feed(&chunk[..n]);
+        off += n;
+    }
+    let wc = wc.finish();
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  ");
+    append_u64(&mut buf, &mut pos, wc.lines);
+    append_str(&mut buf, &mut pos, if wc.lines == 1 { b" linea  " } else { b" lineas  " });
+    append_u64(&mut buf, &mut pos, wc.words);
+    append_str(&mut buf, &mut pos, if wc.words == 1 { b" palabra  " } else { b" palabras  " });
+    append_u64(&mut buf, &mut pos, wc.bytes);
+    append_str(&mut buf, &mut pos, b" bytes  ");
+    append_str(&mut buf, &mut pos, &abs[..abs_len.min(40)]);
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+}
+
+pub fn cmd_view(t: &mut Terminal, args: &[u8]) {
+    let args = trim(args);
+    if args.is_empty() {
+        t.write_line("  Uso: view <archivo>", LineColor::Warning);
+        return;
+    }
+    let (vol, mnt) = match mount_vol(t) { Some(x) => x, None => return };
+
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, args, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+    let mut par = [0u8; 256];
+    let par_len = parent_copy(abs_s, &mut par);
+
+    let entry = match resolve_path(&vol, &mnt, &par, par_len, b".")
+        .and_then(|dir| vol.find_entry(dir, basename(abs_s)))
+    {
+        Ok(e) if e.is_dir => {
+            t.write_line("  view: es un directorio. Usa 'ls' para listar su contenido.", LineColor::Warning);
+            return;
+        }
         Ok(e) => e,
         Err(e) => {
             let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
-            append_str(&mut buf, &mut pos, b"  cat: ");
-            let em = fat_err_msg(e);
-            buf[pos..pos + em.len()].copy_from_slice(em); pos += em.len();
+            append_str(&mut buf, &mut pos, b"  view: ");
+            append_str(&mut buf, &mut pos, fat_err_msg(e));
             t.write_bytes(&buf[..pos], LineColor::Error);
             return;
         }
     };
 
-    if entry.is_dir {
-        t.write_line("  cat: es un directorio. Usa 'ls' para listar su contenido.", LineColor::Warning);
-        return;
-    }
+    t.viewer = Some(ViewerState::new(&vol, entry, &abs[..abs_len]));
+}
 
-    const MAX_READ: usize = 8192;
-    let mut content = [0u8; MAX_READ];
-    let bytes = match vol.read_file(&entry, &mut content) {
-        Ok(n) => n,
+/// `screenshot [archivo.bmp]` — vuelca el último frame dibujado a un BMP de
+/// 24 bpp. Sin nombre usa SCRnnnnn.BMP (ticks del PIT) en el cwd.
+pub fn cmd_screenshot(t: &mut Terminal, args: &[u8]) {
+    let args = trim(args);
+    let mut def = [0u8; 12];
+    let path: &[u8] = if args.is_empty() {
+        let n = (crate::time::pit::ticks() % 100_000) as u32;
+        def[..3].copy_from_slice(b"SCR");
+        for (i, d) in [10_000, 1_000, 100, 10, 1].iter().enumerate() {
+            def[3 + i] = b'0' + ((n / d) % 10) as u8;
+        }
+        def[8..].copy_from_slice(b".BMP");
+        &def
+    } else { args };
+
+    let (vol, mnt) = match mount_vol(t) { Some(x) => x, None => return };
+    let view = BackBufferView::current();
+    let (w, h) = (view.width, view.height);
+    let size   = bmp::file_size(w, h);
+
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+    let mut par = [0u8; 256];
+    let par_len = parent_copy(abs_s, &mut par);
+
+    let res = resolve_path(&vol, &mnt, &par, par_len, b".").and_then(|dir| {
+        vol.write_file_in_with(dir, basename(abs_s), size, |off, out| {
+            bmp::encode_range(w, h, off, out, |x, y| view.pixel(x, y));
+        })
+    });
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    match res {
+        Ok(()) => {
+            append_str(&mut buf, &mut pos, b"  [OK] Captura guardada: ");
+            append_str(&mut buf, &mut pos, &abs[..abs_len]);
+            append_str(&mut buf, &mut pos, b" (");
+            append_u32(&mut buf, &mut pos, w as u32);
+            append_str(&mut buf, &mut pos, b"x");
+            append_u32(&mut buf, &mut pos, h as u32);
+            append_str(&mut buf, &mut pos, b", ");
+            fmt_size(&mut buf, &mut pos, size as u32);
+            while pos > 0 && buf[pos - 1] == b' ' { pos -= 1; }
+            append_str(&mut buf, &mut pos, b")");
+            t.write_bytes(&buf[..pos], LineColor::Success);
+        }
         Err(e) => {
-            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
-            append_str(&mut buf, &mut pos, b"  cat: error leyendo: ");
-            let em = fat_err_msg(e);
-            buf[pos..pos + em.len()].copy_from_slice(em); pos += em.len();
+            append_str(&mut buf, &mut pos, b"  screenshot: ");
+            append_str(&mut buf, &mut pos, fat_err_msg(e));
             t.write_bytes(&buf[..pos], LineColor::Error);
-            return;
         }
+    }
+}
+
+/// `img info <archivo.bmp>` / `img convert <origen.bmp> <destino.bmp>
+/// [fit|fill|stretch]`. `info` solo lee las cabeceras; `convert` carga la
+/// imagen en el heap y la guarda reescalada al tamaño de pantalla como BMP de
+/// 24 bpp, para no reescalar en cada uso.
+pub fn cmd_img(t: &mut Terminal, args: &[u8]) {
+    let args = trim(args);
+    let (sub, rest) = match args.iter().position(|&b| b == b' ') {
+        Some(i) => (&args[..i], trim(&args[i + 1..])),
+        None    => (args, &b""[..]),
+    };
+    match sub {
+        b"info"    => img_info(t, rest),
+        b"convert" => cmd_img_convert(t, rest),
+        _ => {
+            t.write_line("  Uso: img info <archivo.bmp>", LineColor::Warning);
+            t.write_line("       img convert <origen.bmp> <destino.bmp> [fit|fill|stretch]", LineColor::Warning);
+            t.write_line("  fit = conserva el aspecto con franjas (por defecto), fill = recorta,", LineColor::Normal);
+            t.write_line("  stretch = deforma hasta ocupar toda la pantalla.", LineColor::Normal);
+        }
+    }
+}
+
+fn img_err(t: &mut Terminal, msg: &[u8]) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  img: ");
+    append_str(&mut buf, &mut pos, msg);
+    t.write_bytes(&buf[..pos], LineColor::Error);
+}
+
+/// Resuelve `path` y lee sus cabeceras BMP. Informa del error y devuelve
+/// None si no existe, es un directorio o no es un BMP reconocible.
+fn img_open(t: &mut Terminal, vol: &Fat32Volume, path: &[u8])
+    -> Option<(fat32::DirEntryInfo, bmp::BmpInfo)>
+{
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, path, &mut abs);
+    let Some(entry) = vol.resolve_path(core::str::from_utf8(&abs[..abs_len]).unwrap_or("")) else {
+        img_err(t, fat_err_msg(FatError::NotFound));
+        return None;
     };
+    if entry.is_dir { img_err(t, fat_err_msg(FatError::IsDir)); return None; }
 
-    {
-        let mut hdr = [0u8; TERM_COLS]; let mut hp = 0;
-        append_str(&mut hdr, &mut hp, b"  --- ");
-        let nb = args.len().min(50);
-        hdr[hp..hp + nb].copy_from_slice(&args[..nb]); hp += nb;
-        append_str(&mut hdr, &mut hp, b" (");
-        append_u32(&mut hdr, &mut hp, bytes as u32);
-        append_str(&mut hdr, &mut hp, b" bytes) ---");
-        t.write_bytes(&hdr[..hp], LineColor::Header);
-    }
-
-    let mut start = 0usize;
-    let mut line_n = 1u32;
-    for i in 0..bytes {
-        let is_nl = content[i] == b'\n' || content[i] == b'\r';
-        let is_end = i + 1 == bytes;
-        if is_nl || is_end {
-            let end = if is_end && !is_nl { i + 1 } else { i };
-            if end > start {
-                let chunk = &content[start..end];
-                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
-                append_str(&mut buf, &mut pos, b"  ");
-                append_u32(&mut buf, &mut pos, line_n);
-                append_str(&mut buf, &mut pos, b"  ");
-                if pos < 6 { buf[pos] = b' '; pos += 1; }
-                for &b in chunk {
-                    if pos >= TERM_COLS - 1 { break; }
-                    buf[pos] = if b >= 0x20 && b < 0x7F { b } else if b == b'\t' { b' ' } else { b'.' };
-                    pos += 1;
+    let mut hd = [0u8; bmp::HEADER_LEN];
+    let n = match vol.read_file_at(&entry, 0, &mut hd) {
+        Ok(n) => n,
+        Err(e) => { img_err(t, fat_err_msg(e)); return None; }
+    };
+    match bmp::parse_header(&hd[..n]) {
+        Ok(info) => Some((entry, info)),
+        Err(e)   => { img_err(t, e.msg()); None }
+    }
+}
+
+fn img_info(t: &mut Terminal, args: &[u8]) {
+    if args.is_empty() {
+        t.write_line("  Uso: img info <archivo.bmp>", LineColor::Warning);
+        return;
+    }
+    let (vol, _mnt) = match mount_vol(t) { Some(x) => x, None => return };
+    let Some((entry, info)) = img_open(t, &vol, args) else { return };
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  --- ");
+    append_str(&mut buf, &mut pos, &args[..args.len().min(50)]);
+    append_str(&mut buf, &mut pos, b" ---");
+    t.write_bytes(&buf[..pos], LineColor::Header);
+
+    pos = 0;
+    append_str(&mut buf, &mut pos, b"  Dimensiones: ");
+    append_u32(&mut buf, &mut pos, info.width as u32);
+    append_str(&mut buf, &mut pos, b" x ");
+    append_u32(&mut buf, &mut pos, info.height as u32);
+    append_str(&mut buf, &mut pos, if info.top_down { b"  (filas de arriba abajo)" } else { b"  (filas de abajo arriba)" });
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+
+    pos = 0;
+    append_str(&mut buf, &mut pos, b"  Color:       ");
+    append_u32(&mut buf, &mut pos, info.bpp as u32);
+    append_str(&mut buf, &mut pos, b" bpp, compresion ");
+    append_str(&mut buf, &mut pos, info.compression_name().as_bytes());
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+
+    pos = 0;
+    append_str(&mut buf, &mut pos, b"  Tamano:      ");
+    fmt_size(&mut buf, &mut pos, entry.size);
+    while pos > 0 && buf[pos - 1] == b' ' { pos -= 1; }
+    append_str(&mut buf, &mut pos, b" (");
+    append_u32(&mut buf, &mut pos, entry.size);
+    append_str(&mut buf, &mut pos, b" bytes)");
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+
+    let view = BackBufferView::current();
+    pos = 0;
+    append_str(&mut buf, &mut pos, b"  Pantalla:    ");
+    append_u32(&mut buf, &mut pos, view.width as u32);
+    append_str(&mut buf, &mut pos, b" x ");
+    append_u32(&mut buf, &mut pos, view.height as u32);
+    append_str(&mut buf, &mut pos, if info.width == view.width && info.height == view.height {
+        b"  (ya tiene el tamano de pantalla)"
+    } else {
+        b"  ('img convert' la pre-escala)"
+    });
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+
+    if let Err(e) = info.check_decodable(entry.size as usize) {
+        pos = 0;
+        append_str(&mut buf, &mut pos, b"  Aviso: ");
+        append_str(&mut buf, &mut pos, e.msg());
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    }
+}
+
+/// `img convert` (alias `bmp2raw`): ver `cmd_img`.
+pub fn cmd_img_convert(t: &mut Terminal, args: &[u8]) {
+    let mut it = args.split(|&b| b == b' ').filter(|w| !w.is_empty());
+    let (Some(src), Some(dst)) = (it.next(), it.next()) else {
+        t.write_line("  Uso: img convert <origen.bmp> <destino.bmp> [fit|fill|stretch]", LineColor::Warning);
+        return;
+    };
+    let mode = match it.next() {
+        None => bmp::ScaleMode::Fit,
+        Some(m) => match bmp::ScaleMode::parse(m) {
+            Some(m) => m,
+            None => { img_err(t, b"modo desconocido (fit, fill o stretch)"); return; }
+        },
+    };
+
+    let (vol, mnt) = match mount_vol(t) { Some(x) => x, None => return };
+    let Some((entry, info)) = img_open(t, &vol, src) else { return };
+    if let Err(e) = info.check_decodable(entry.size as usize) { img_err(t, e.msg()); return; }
+
+    // Imagen completa en el heap: el reescalado accede a filas arbitrarias
+    let size = entry.size as usize;
+    let mut data = alloc::vec::Vec::new();
+    if data.try_reserve_exact(size).is_err() {
+        img_err(t, b"memoria insuficiente para cargar la imagen");
+        return;
+    }
+    data.resize(size, 0u8);
+    match vol.read_file(&entry, &mut data) {
+        Ok(n) if n == size => {}
+        Ok(_)  => { img_err(t, bmp::BmpError::Short.msg()); return; }
+        Err(e) => { img_err(t, fat_err_msg(e)); return; }
+    }
+
+    let view = BackBufferView::current();
+    let (dw, dh) = (view.width, view.height);
+    let out_size = bmp::file_size(dw, dh);
+
+    let mut abs = [0u8; 256];
+    let abs_len = make_abs_path(&t.cwd, t.cwd_len, dst, &mut abs);
+    let abs_s   = core::str::from_utf8(&abs[..abs_len]).unwrap_or("/");
+    let mut par = [0u8; 256];
+    let par_len = parent_copy(abs_s, &mut par);
+
+    let res = resolve_path(&vol, &mnt, &par, par_len, b".").and_then(|dir| {
+        vol.write_file_in_with(dir, basename(abs_s), out_size, |off, out| {
+            bmp::encode_range(dw, dh, off, out, |x, y| {
+                match bmp::scale_src(mode, info.width, info.height, dw, dh, x, y) {
+                    Some((sx, sy)) => bmp::pixel_at(&info, &data, sx, sy),
+                    None           => 0,
                 }
-                t.write_bytes(&buf[..pos], LineColor::Normal);
-                line_n += 1;
-            }
-            if content[i] == b'\r' && i + 1 < bytes && content[i + 1] == b'\n' {
-                start = i + 2;
-            } else {
-                start = i + 1;
-            }
+            });
+        })
+    });
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    match res {
+        Ok(()) => {
+            append_str(&mut buf, &mut pos, b"  [OK] ");
+            append_str(&mut buf, &mut pos, &abs[..abs_len.min(40)]);
+            append_str(&mut buf, &mut pos, b" (");
+            append_u32(&mut buf, &mut pos, info.width as u32);
+            append_str(&mut buf, &mut pos, b"x");
+            append_u32(&mut buf, &mut pos, info.height as u32);
+            append_str(&mut buf, &mut pos, b" -> ");
+            append_u32(&mut buf, &mut pos, dw as u32);
+            append_str(&mut buf, &mut pos, b"x");
+            append_u32(&mut buf, &mut pos, dh as u32);
+            append_str(&mut buf, &mut pos, b", ");
+            append_str(&mut buf, &mut pos, mode.name().as_bytes());
+            append_str(&mut buf, &mut pos, b", ");
+            fmt_size(&mut buf, &mut pos, out_size as u32);
+            while pos > 0 && buf[pos - 1] == b' ' { pos -= 1; }
+            append_str(&mut buf, &mut pos, b")");
+            t.write_bytes(&buf[..pos], LineColor::Success);
         }
+        Err(e) => img_err(t, fat_err_msg(e)),
     }
-    t.write_empty();
 }
 
 pub fn cmd_touch(t: &mut Terminal, args: &[u8]) {
@@ -1175,6 +1818,140 @@ pub fn cmd_diskinfo(t: &mut Terminal) {
 // COMANDOS ATA RAW — v0.8.0: Primary0 usa caché, otros drives scan puntual
 // ═══════════════════════════════════════════════════════════════════════════════
 
+pub fn cmd_remount(t: &mut Terminal, args: &[u8]) {
+    match trim(args) {
+        b"ro" => fat32::set_default_read_only(true),
+        b"rw" => {
+            if let Some(info) = get_cached_drive_info() {
+                if info.kind == DriveType::Atapi {
+                    t.write_line("  remount: la unidad es ATAPI (CD-ROM/ISO), siempre solo lectura.", LineColor::Error);
+                    return;
+                }
+            }
+            fat32::set_default_read_only(false);
+        }
+        b"" => {}
+        _ => { t.write_line("  Uso: remount [ro|rw]", LineColor::Warning); return; }
+    }
+    if fat32::default_read_only() {
+        t.write_line("  Volumen FAT32: solo lectura (ro)", LineColor::Warning);
+    } else {
+        t.write_line("  Volumen FAT32: lectura-escritura (rw)", LineColor::Success);
+    }
+}
+
+// ── atareset ──────────────────────────────────────────────────────────────────
+//
+// Software reset de un canal ATA (bit SRST) para recuperarlo tras un timeout
+// o un error, y nueva detección de sus unidades. `hw` es de solo lectura para
+// los comandos, así que `cmd_atareset` solo deja la petición en `ata_reset` y
+// el loop principal llama a `run_atareset` con `hw` mutable en la vuelta
+// siguiente (antes de procesar más entrada).
+
+pub fn cmd_atareset(t: &mut Terminal, args: &[u8]) {
+    let bus = match trim(args) {
+        b"" | b"0" => 0,
+        b"1"       => 1,
+        _ => return t.write_line("  Uso: atareset [0|1]  (0 = canal primario, 1 = secundario)", LineColor::Warning),
+    };
+    t.ata_reset = Some(bus);
+    t.write_line(if bus == 0 { "  Reseteando el canal ATA primario (0x1F0)..." }
+                 else        { "  Reseteando el canal ATA secundario (0x170)..." },
+                 LineColor::Info);
+}
+
+pub fn run_atareset(t: &mut Terminal, hw: &mut crate::arch::hardware::HardwareInfo, bus: u8) {
+    use crate::drivers::registry::{self, Subsystem};
+    use crate::drivers::storage::ata::reset_channel;
+
+    let ident = match reset_channel(bus) {
+        Ok(ident) => ident,
+        Err(e) => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  atareset: ");
+            append_str(&mut buf, &mut pos, ata_err_msg(e));
+            append_str(&mut buf, &mut pos, b" (unidades del canal retiradas)");
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            hw.disks.replace_ata_channel(bus, &[None, None]);
+            registry::set_ok(Subsystem::Ata, get_cached_drive_info().is_some());
+            t.remote_prompt();
+            return;
+        }
+    };
+    hw.disks.replace_ata_channel(bus, &ident);
+    registry::set_ok(Subsystem::Ata, get_cached_drive_info().is_some());
+
+    let n = ident.iter().filter(|w| w.is_some()).count();
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Canal ATA");
+    append_u32(&mut buf, &mut pos, bus as u32);
+    append_str(&mut buf, &mut pos, b" reseteado: ");
+    append_u32(&mut buf, &mut pos, n as u32);
+    append_str(&mut buf, &mut pos, if n == 1 { b" unidad" } else { b" unidades" });
+    t.write_bytes(&buf[..pos], LineColor::Success);
+
+    for d in hw.disks.drives[..hw.disks.count].iter().filter(|d| !d.ahci && d.bus == bus) {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, if d.drive == 0 { b"    master  " } else { b"    slave   " });
+        append_str(&mut buf, &mut pos, if d.is_atapi { b"ATAPI  " } else { b"ATA    " });
+        if !d.is_atapi {
+            append_u32(&mut buf, &mut pos, d.size_mb as u32);
+            append_str(&mut buf, &mut pos, b" MiB  ");
+        }
+        append_str(&mut buf, &mut pos, d.model_str().as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    t.remote_prompt();
+}
+
+// ── sync_disks — antes de apagar/reiniciar ────────────────────────────────────
+//
+// No hay caché de bloques propia (cada escritura va directa al disco), así
+// que basta con actualizar el FSInfo del volumen y vaciar la caché interna
+// del drive (FLUSH CACHE). Los marcadores del explorador ya se guardan en
+// disco al modificarse. Un fallo solo se avisa: el apagado continúa.
+// Devuelve true si todo quedó sincronizado.
+
+pub fn sync_disks(t: &mut Terminal) -> bool {
+    let Some(info) = get_cached_drive_info() else {
+        t.write_line("  sync: no hay disco, nada que sincronizar", LineColor::Normal);
+        return true;
+    };
+    if info.kind == DriveType::Atapi {
+        return true;
+    }
+    let warn = |t: &mut Terminal, what: &[u8], e: FatError| {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  AVISO: ");
+        append_str(&mut buf, &mut pos, what);
+        append_str(&mut buf, &mut pos, b": ");
+        append_str(&mut buf, &mut pos, fat_err_msg(e));
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    };
+
+    let mut ok = true;
+    match Fat32Volume::mount(AtaDrive::from_info(info)) {
+        Ok(vol) if vol.is_read_only() => {}
+        Ok(vol) => match vol.sync_fsinfo() {
+            Ok(free) => {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  FSInfo actualizado: ");
+                append_u32(&mut buf, &mut pos, free);
+                append_str(&mut buf, &mut pos, b" clusters libres");
+                t.write_bytes(&buf[..pos], LineColor::Success);
+            }
+            Err(e) => { warn(t, b"no se pudo actualizar el FSInfo", e); ok = false; }
+        },
+        Err(FatError::NotFat32) => {}
+        Err(e) => { warn(t, b"no se pudo montar el volumen", e); ok = false; }
+    }
+    if let Err(e) = AtaDrive::from_info(info).flush() {
+        warn(t, b"fallo el flush de la cache del disco", FatError::Ata(e));
+        ok = false;
+    }
+    ok
+}
+
 pub fn cmd_diskread(t: &mut Terminal, args: &[u8]) {
     let (lba, drv_idx) = parse_lba_drive(args);
     let id             = drive_id(drv_idx);
@@ -1353,4 +2130,107 @@ pub fn cmd_diskwrite(t: &mut Terminal, args: &[u8]) {
         }
         Err(_) => { t.write_line("  Error: fallo al escribir sector.", LineColor::Error); }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wc(chunks: &[&[u8]]) -> (u64, u64, u64) {
+        let mut c = WcCount::new();
+        for ch in chunks { c.feed(ch); }
+        let c = c.finish();
+        (c.lines, c.words, c.bytes)
+    }
+
+    #[test]
+    fn wc_counts_known_buffers() {
+        assert_eq!(wc(&[b"hola mundo\nadios\n"]), (2, 3, 17));
+        // Última línea sin LF
+        assert_eq!(wc(&[b"uno\ndos"]), (2, 2, 7));
+        assert_eq!(wc(&[b"sin salto"]), (1, 2, 9));
+    }
+
+    #[test]
+    fn wc_edge_cases() {
+        assert_eq!(wc(&[]), (0, 0, 0));
+        assert_eq!(wc(&[b""]), (0, 0, 0));
+        assert_eq!(wc(&[b"\n\n\n"]), (3, 0, 3));
+        // Rachas de espacios y tabuladores no crean palabras vacías
+        assert_eq!(wc(&[b"   a  \t  b    c   \n"]), (1, 3, 19));
+        // CRLF: el CR es espacio y cada LF cierra una línea
+        assert_eq!(wc(&[b"uno dos\r\ntres\r\n"]), (2, 3, 15));
+    }
+
+    #[test]
+    fn wc_word_split_across_chunks() {
+        assert_eq!(wc(&[b"pala", b"bra otra\n"]), (1, 2, 13));
+        assert_eq!(wc(&[b"a ", b" b", b"\n"]), (1, 2, 5));
+        // LF al final de un trozo y texto en el siguiente
+        assert_eq!(wc(&[b"uno\n", b"dos"]), (2, 2, 7));
+        assert_eq!(wc(&[b"uno", b"\n"]), (1, 1, 4));
+    }
+
+    /// Fecha y hora FAT empaquetadas como `DirEntryInfo::mtime`.
+    fn fat_mtime(y: u32, mo: u32, d: u32, h: u32, mi: u32) -> u32 {
+        ((y - 1980) << 9 | mo << 5 | d) << 16 | (h << 11 | mi << 5)
+    }
+
+    fn mtime_str(mtime: u32) -> alloc::string::String {
+        let mut buf = [0u8; 32]; let mut pos = 0;
+        append_fat_mtime(&mut buf, &mut pos, mtime);
+        alloc::string::String::from_utf8(buf[..pos].to_vec()).unwrap()
+    }
+
+    fn row(e: &fat32::DirEntryInfo, long: bool) -> (alloc::string::String, usize, usize) {
+        let mut buf = [0u8; TERM_COLS];
+        let (n, col, nl) = format_ls_row(&mut buf, e, long);
+        (alloc::string::String::from_utf8(buf[..n].to_vec()).unwrap(), col, nl)
+    }
+
+    #[test]
+    fn fat_mtime_format() {
+        assert_eq!(mtime_str(fat_mtime(2024, 3, 5, 9, 7)), "2024-03-05  09:07");
+        assert_eq!(mtime_str(fat_mtime(1980, 1, 1, 0, 0)), "1980-01-01  00:00");
+        assert_eq!(mtime_str(fat_mtime(2107, 12, 31, 23, 59)), "2107-12-31  23:59");
+        // Sin fecha: misma anchura para no descuadrar la columna del nombre
+        assert_eq!(mtime_str(0), "----------  -----");
+        assert_eq!(mtime_str(0).len(), mtime_str(fat_mtime(2024, 3, 5, 9, 7)).len());
+    }
+
+    #[test]
+    fn ls_long_rows() {
+        let f = fat32::DirEntryInfo::for_test("notas.txt", false, 12345, 1234, fat_mtime(2024, 3, 5, 9, 7));
+        let (line, col, nl) = row(&f, true);
+        assert_eq!(line, "  [ARC]       12345  2024-03-05  09:07  1234     notas.txt");
+        assert_eq!((col, nl), (LS_LONG_NAME_COL, 9));
+        assert_eq!(&line[col..], "notas.txt");
+
+        let d = fat32::DirEntryInfo::for_test("docs", true, 0, 9, 0);
+        let (line, col, _) = row(&d, true);
+        assert_eq!(line, "  [DIR]           -  ----------  -----  9        docs");
+        assert_eq!(&line[col..], "docs");
+
+        // Con tamaño y clúster máximos el nombre se corre pero no se pega
+        let big = fat32::DirEntryInfo::for_test("x", false, u32::MAX, u32::MAX, fat_mtime(2024, 1, 1, 0, 0));
+        let (line, col, _) = row(&big, true);
+        assert!(line.ends_with("4294967295 x"));
+        assert_eq!(&line[col..], "x");
+    }
+
+    #[test]
+    fn ls_short_rows() {
+        let (line, col, _) = row(&fat32::DirEntryInfo::for_test("a.bin", false, 3 * 1024, 5, 0), false);
+        assert_eq!(col, links::LS_NAME_COL);
+        assert_eq!(line, "  [ARC]  3 KiB      a.bin");
+        let (line, _, _) = row(&fat32::DirEntryInfo::for_test("home", true, 0, 5, 0), false);
+        assert_eq!(line, "  [DIR]             home");
+    }
+
+    #[test]
+    fn ls_row_cuts_long_names_at_the_line_end() {
+        let name = "n".repeat(200);
+        let (line, col, nl) = row(&fat32::DirEntryInfo::for_test(&name, false, 1, 5, 0), true);
+        assert_eq!(line.len(), TERM_COLS);
+        assert_eq!(nl, TERM_COLS - col);
+    }
+}
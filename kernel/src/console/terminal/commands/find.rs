@@ -0,0 +1,188 @@
+// console/terminal/commands/find.rs — PORTIX Kernel
+// Búsqueda recursiva de archivos por nombre en todo el volumen FAT32.
+//
+// ┌─ Uso ──────────────────────────────────────────────────────────────────────┐
+// │  find <patron>     Rutas de los archivos cuyo nombre contiene <patron>    │
+// │                    (sin distinguir mayúsculas, desde la raíz)             │
+// └───────────────────────────────────────────────────────────────────────────┘
+//
+// El recorrido es iterativo y NO anida llamadas a list_dir: cada una reserva
+// un ClusterBuf de 64 KiB en la pila, así que recursar desde el callback
+// desbordaría la pila del kernel en pocos niveles. En su lugar se guarda una
+// pila explícita de MAX_PATH_DEPTH marcos (clúster + cuántos subdirectorios
+// ya se visitaron) y cada directorio se relista para localizar el siguiente
+// hijo. Límites de seguridad: profundidad, directorios visitados en total
+// (una cadena FAT cíclica no debería existir, pero no se confía en ello),
+// y un clúster que ya está en la rama actual no se vuelve a visitar.
+
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
+use crate::console::terminal::fmt::*;
+use crate::console::terminal::commands::disk;
+
+/// Niveles de directorio como máximo por debajo de la raíz.
+const MAX_PATH_DEPTH: usize = 16;
+/// Directorios recorridos como máximo en una búsqueda.
+const MAX_DIRS:       u32   = 2048;
+/// Coincidencias impresas como máximo.
+const MAX_RESULTS:    u32   = 200;
+
+#[derive(Clone, Copy)]
+struct Frame {
+    cluster:  u32,
+    /// Subdirectorios de este nivel ya recorridos.
+    done:     u32,
+    /// Longitud de `path` hasta este directorio (sin '/' final).
+    path_len: usize,
+}
+
+/// ¿Contiene `name` a `pat`? Sin distinguir mayúsculas ASCII; un patrón
+/// vacío coincide con todo.
+pub fn name_matches(name: &[u8], pat: &[u8]) -> bool {
+    pat.is_empty() || name.windows(pat.len()).any(|w| w.eq_ignore_ascii_case(pat))
+}
+
+fn write_path(t: &mut Terminal, path: &[u8], name: &[u8]) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  ");
+    append_str(&mut buf, &mut pos, path);
+    append_str(&mut buf, &mut pos, b"/");
+    append_str(&mut buf, &mut pos, name);
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+}
+
+pub fn cmd_find(t: &mut Terminal, args: &[u8]) {
+    let pat = trim(args);
+    if pat.is_empty() {
+        t.write_line("  Uso: find <patron>", LineColor::Warning);
+        return;
+    }
+    let Some((vol, _mnt)) = disk::mount_vol(t) else { return };
+
+    let mut path  = [0u8; 256];
+    let mut stack = [Frame { cluster: 0, done: 0, path_len: 0 }; MAX_PATH_DEPTH + 1];
+    stack[0].cluster = vol.root_cluster();
+    let mut depth    = 0usize;
+
+    let mut dirs      = 0u32;
+    let mut found     = 0u32;
+    let mut too_deep  = false;
+    let mut truncated = false;
+
+    'walk: loop {
+        let cur = stack[depth];
+        let first_visit = cur.done == 0;
+        if first_visit {
+            dirs += 1;
+            if dirs > MAX_DIRS { truncated = true; break; }
+        }
+
+        // Una pasada: imprimir coincidencias (solo la primera vez) y
+        // localizar el subdirectorio número `done`.
+        let mut next: Option<(u32, [u8; 256], usize)> = None;
+        let mut seen_dirs = 0u32;
+        let res = vol.list_dir(cur.cluster, |e| {
+            let name = &e.name[..e.name_len];
+            if name == b"." || name == b".." { return; }
+            if e.is_dir {
+                if seen_dirs == cur.done && next.is_none() {
+                    let mut n = [0u8; 256];
+                    n[..e.name_len].copy_from_slice(name);
+                    next = Some((e.cluster, n, e.name_len));
+                }
+                seen_dirs += 1;
+            } else if first_visit && name_matches(name, pat) {
+                found += 1;
+                if found <= MAX_RESULTS { write_path(t, &path[..cur.path_len], name); }
+            }
+        });
+        if let Err(e) = res {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  find: ");
+            append_str(&mut buf, &mut pos, disk::fat_err_msg(e));
+            append_str(&mut buf, &mut pos, b" en ");
+            append_str(&mut buf, &mut pos, if cur.path_len == 0 { b"/" } else { &path[..cur.path_len] });
+            t.write_bytes(&buf[..pos], LineColor::Warning);
+            next = None;
+        }
+
+        match next {
+            Some((clus, name, nl)) => {
+                stack[depth].done += 1;
+                let on_branch = stack[..=depth].iter().any(|f| f.cluster == clus);
+                let fits = cur.path_len + 1 + nl <= path.len();
+                if depth == MAX_PATH_DEPTH || clus < 2 || on_branch || !fits {
+                    too_deep |= depth == MAX_PATH_DEPTH;
+                    continue 'walk;
+                }
+                path[cur.path_len] = b'/';
+                path[cur.path_len + 1..cur.path_len + 1 + nl].copy_from_slice(&name[..nl]);
+                depth += 1;
+                stack[depth] = Frame { cluster: clus, done: 0, path_len: cur.path_len + 1 + nl };
+            }
+            None => {
+                if depth == 0 { break; }
+                depth -= 1;
+            }
+        }
+    }
+
+    if found > MAX_RESULTS {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ... y ");
+        append_u32(&mut buf, &mut pos, found - MAX_RESULTS);
+        append_str(&mut buf, &mut pos, b" mas sin mostrar");
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    }
+    if too_deep {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Aviso: no se baja de ");
+        append_u32(&mut buf, &mut pos, MAX_PATH_DEPTH as u32);
+        append_str(&mut buf, &mut pos, b" niveles de directorio");
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    }
+    if truncated {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Aviso: busqueda cortada tras ");
+        append_u32(&mut buf, &mut pos, MAX_DIRS);
+        append_str(&mut buf, &mut pos, b" directorios");
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    }
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  ");
+    append_u32(&mut buf, &mut pos, found);
+    append_str(&mut buf, &mut pos, if found == 1 { b" archivo en " } else { b" archivos en " });
+    append_u32(&mut buf, &mut pos, dirs.min(MAX_DIRS));
+    append_str(&mut buf, &mut pos, b" directorios");
+    t.write_bytes(&buf[..pos], if found == 0 { LineColor::Warning } else { LineColor::Success });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substring_anywhere_ignoring_case() {
+        assert!(name_matches(b"KERNEL.BIN", b"kernel"));
+        assert!(name_matches(b"kernel.bin", b"L.B"));
+        assert!(name_matches(b"readme.txt", b"txt"));
+        assert!(name_matches(b"a", b"A"));
+        assert!(name_matches(b"notas.txt", b"notas.txt"));
+    }
+
+    #[test]
+    fn no_match() {
+        assert!(!name_matches(b"kernel.bin", b"kernels"));
+        assert!(!name_matches(b"abc", b"abcd"));
+        assert!(!name_matches(b"abc", b"ac"));
+        assert!(!name_matches(b"", b"a"));
+        // Solo ASCII sin mayúsculas: los bytes no ASCII comparan tal cual
+        assert!(!name_matches("Ñu".as_bytes(), "ñu".as_bytes()));
+    }
+
+    #[test]
+    fn empty_pattern_matches_everything() {
+        assert!(name_matches(b"x", b""));
+        assert!(name_matches(b"", b""));
+    }
+}
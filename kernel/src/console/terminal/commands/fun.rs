@@ -8,27 +8,13 @@ pub fn cmd_beep(t: &mut Terminal, args: &[u8]) {
     let freq = if args.is_empty() { 440u32 } else {
         match parse_u64(trim(args)) { Some(f) => (f as u32).max(20).min(20000), None => 440 }
     };
-    let div = 1_193_182u32 / freq;
-    unsafe {
-        core::arch::asm!("out 0x43, al", in("al") 0xB6u8, options(nostack, nomem));
-        core::arch::asm!("out 0x42, al", in("al") (div & 0xFF) as u8, options(nostack, nomem));
-        core::arch::asm!("out 0x42, al", in("al") ((div >> 8) & 0xFF) as u8, options(nostack, nomem));
-        let mut p: u8;
-        core::arch::asm!("in al, 0x61", out("al") p, options(nostack, nomem));
-        p |= 0x03;
-        core::arch::asm!("out 0x61, al", in("al") p, options(nostack, nomem));
-    }
+    crate::time::pit::speaker_on(freq);
     // ~200 ms de duración
     let start = crate::time::pit::ticks();
     while crate::time::pit::ticks().wrapping_sub(start) < 20 {
         unsafe { core::arch::asm!("pause", options(nostack, nomem)); }
     }
-    unsafe {
-        let mut p: u8;
-        core::arch::asm!("in al, 0x61", out("al") p, options(nostack, nomem));
-        p &= !0x03;
-        core::arch::asm!("out 0x61, al", in("al") p, options(nostack, nomem));
-    }
+    crate::time::pit::speaker_off();
     let mut buf = [0u8; 80]; let mut pos = 0;
     append_str(&mut buf, &mut pos, b"  Pitido a ");
     append_u32(&mut buf, &mut pos, freq);
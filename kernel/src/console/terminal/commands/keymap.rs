@@ -0,0 +1,92 @@
+// console/terminal/commands/keymap.rs — PORTIX Kernel
+// Mapa de teclado en tiempo de ejecución.
+//
+// ┌─ Uso ──────────────────────────────────────────────────────────────────────┐
+// │  loadkeys              Mapa activo                                        │
+// │  loadkeys <archivo>    Cargar un mapa scancode→carácter desde FAT32       │
+// │  loadkeys us | es      Layout embebido US o ES (también `keymap us|es`)   │
+// └───────────────────────────────────────────────────────────────────────────┘
+//
+// El formato está descrito en drivers/input/keyboard.rs. Un archivo con
+// cualquier línea inválida, o sin ninguna tecla, no se aplica a medias: se
+// listan los errores y se vuelve al layout por defecto.
+
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
+use crate::console::terminal::fmt::*;
+use crate::console::terminal::commands::disk;
+use crate::drivers::input::keyboard;
+
+/// Tamaño máximo del archivo de mapa.
+const KEYMAP_FILE_MAX: usize = 4096;
+/// Errores listados como máximo (el resto solo se cuenta).
+const MAX_ERRORS_SHOWN: u32 = 8;
+
+fn show_current(t: &mut Terminal) {
+    let km = keyboard::keymap();
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Mapa de teclado activo: ");
+    append_str(&mut buf, &mut pos, km.name());
+    if km.is_default() { append_str(&mut buf, &mut pos, b" (embebido)"); }
+    t.write_bytes(&buf[..pos], LineColor::Info);
+    t.write_line("  Uso: loadkeys <archivo> | keymap us|es", LineColor::Normal);
+}
+
+pub fn cmd_loadkeys(t: &mut Terminal, args: &[u8]) {
+    let path = trim(args);
+    match path {
+        b"" => { show_current(t); return; }
+        b"us" | b"default" => {
+            keyboard::reset_keymap();
+            t.write_line("  Layout US embebido restaurado", LineColor::Success);
+            return;
+        }
+        b"es" => {
+            keyboard::set_keymap(&keyboard::Keymap::es());
+            t.write_line("  Layout ES embebido activo (AltGr: | @ # ~ [ ] { } \\)", LineColor::Success);
+            t.write_line("  Sin glifos latinos: enie -> n, c cedilla -> c; las tildes son teclas muertas", LineColor::Info);
+            return;
+        }
+        _ => {}
+    }
+
+    let mut data = [0u8; KEYMAP_FILE_MAX];
+    let Some(n) = disk::read_text_file(t, b"loadkeys", path, &mut data) else { return };
+
+    let mut shown = 0u32;
+    let parsed = keyboard::parse_keymap(&data[..n], |line, msg| {
+        shown += 1;
+        if shown > MAX_ERRORS_SHOWN { return; }
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  loadkeys: linea ");
+        append_u32(&mut buf, &mut pos, line as u32);
+        append_str(&mut buf, &mut pos, b": ");
+        append_str(&mut buf, &mut pos, msg.as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    });
+
+    if parsed.errors > 0 || parsed.entries == 0 {
+        keyboard::reset_keymap();
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Mapa no valido (");
+        if parsed.entries == 0 && parsed.errors == 0 {
+            append_str(&mut buf, &mut pos, b"sin teclas");
+        } else {
+            append_u32(&mut buf, &mut pos, parsed.errors);
+            append_str(&mut buf, &mut pos, if parsed.errors == 1 { b" error" } else { b" errores" });
+        }
+        append_str(&mut buf, &mut pos, b"): se usa el layout US por defecto");
+        t.write_bytes(&buf[..pos], LineColor::Error);
+        return;
+    }
+
+    let mut km = parsed.map;
+    km.set_name(path);
+    keyboard::set_keymap(&km);
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  [OK] Mapa '");
+    append_str(&mut buf, &mut pos, km.name());
+    append_str(&mut buf, &mut pos, b"' cargado: ");
+    append_u32(&mut buf, &mut pos, parsed.entries);
+    append_str(&mut buf, &mut pos, if parsed.entries == 1 { b" tecla" } else { b" teclas" });
+    t.write_bytes(&buf[..pos], LineColor::Success);
+}
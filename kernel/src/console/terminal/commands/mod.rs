@@ -6,9 +6,30 @@ pub mod debug;
 pub mod convert;
 pub mod fun;
 pub mod disk;
+pub mod script;
+pub mod config;
+pub mod find;
+pub mod keymap;
+pub mod watch;
 
 use crate::console::terminal::{Terminal, LineColor, INPUT_MAX};
 
+/// Comandos que toman el control de la pantalla o del sistema: su salida no
+/// se puede redirigir a un archivo (`cmd > archivo`).
+pub fn is_interactive(cmd: &[u8]) -> bool {
+    matches!(cmd,
+        b"edit" | b"nano" | b"editor" | b"view" | b"hexview"
+        | b"diskedit" | b"hexedit" | b"clear" | b"cls" | b"limpiar"
+        | b"poweroff" | b"shutdown" | b"apagar"
+        | b"reboot" | b"restart" | b"reiniciar")
+}
+
+/// Comandos cuyos argumentos son una expresión de `calc`: ahí `>>` es un
+/// desplazamiento, no una redirección, así que la línea se pasa entera.
+pub fn is_expression(cmd: &[u8]) -> bool {
+    matches!(cmd, b"calc" | b"math" | b"=")
+}
+
 /// Enruta `cmd` al handler correspondiente.
 pub fn dispatch(
     t:   &mut Terminal,
@@ -29,10 +50,20 @@ pub fn dispatch(
             => system::cmd_cpu(t, hw),
         b"mem" | b"memory" | b"lsmem"
             => system::cmd_mem(t, hw),
+        b"heap"
+            => system::cmd_heap(t),
+        b"free" | b"libre"
+            => system::cmd_free(t, hw),
+        b"versions" | b"versiones"
+            => system::cmd_versions(t),
         b"disks" | b"storage" | b"lsblk"
-            => system::cmd_disks(t, hw),
+            => system::cmd_disks(t, args, hw),
         b"pci" | b"lspci"
-            => system::cmd_pci(t, pci),
+            => system::cmd_pci(t, args, pci),
+        b"lsmod" | b"drivers" | b"modulos"
+            => system::cmd_lsmod(t),
+        b"acpi"
+            => system::cmd_acpi(t),
         b"neofetch" | b"fetch"
             => system::cmd_neofetch(t, hw, pci),
         b"uname"
@@ -44,21 +75,64 @@ pub fn dispatch(
         b"motd"
             => system::cmd_motd(t),
         b"ver" | b"version"
-            => system::cmd_ver(t),
+            => system::cmd_ver(t, hw),
         b"uptime" | b"time"
             => system::cmd_uptime(t),
         b"date" | b"fecha"
-            => system::cmd_fecha(t),
+            => system::cmd_fecha(t, args),
+        b"timer" | b"temporizador"
+            => system::cmd_timer(t, args),
         b"ticks"
             => system::cmd_ticks(t),
 
         // ── Terminal ─────────────────────────────────────────────────────────
         b"clear" | b"cls" | b"limpiar"
-            => t.clear_history(),
+            => system::cmd_clear(t, args),
         b"echo" | b"print"
-            => t.write_bytes(args, LineColor::Normal),
+            => system::cmd_echo(t, args),
         b"history" | b"historial"
             => system::cmd_history(t),
+        b"alias"   => system::cmd_alias(t, args),
+        b"unalias" => system::cmd_unalias(t, args),
+        b"sticky" | b"fijar"
+            => system::cmd_sticky(t, args),
+        b"split" | b"dividir"
+            => system::cmd_split(t, args),
+        b"watch" | b"vigilar"
+            => watch::cmd_watch(t, args),
+        b"dmesg" | b"log"
+            => system::cmd_dmesg(t, args),
+        b"scroll-sync"
+            => system::cmd_scroll_sync(t, args),
+        b"present-mode" | b"presentacion"
+            => system::cmd_present(t, args),
+        b"banner-set"
+            => system::cmd_banner_set(t, args, hw, pci),
+        b"banner-clear"
+            => system::cmd_banner_clear(t),
+        b"remote" | b"remoto"
+            => system::cmd_remote(t, args),
+        b"serial" | b"serie"
+            => system::cmd_serial(t, args),
+        b"config" | b"envset"
+            => config::cmd_config(t, args, hw, pci),
+        b"printenv" | b"env"
+            => config::cmd_printenv(t),
+        b"loadkeys" | b"keymap"
+            => keymap::cmd_loadkeys(t, args),
+        b"mouse" | b"raton"
+            => system::cmd_mouse(t, args),
+
+        // ── Scripts ──────────────────────────────────────────────────────────
+        b"source" | b"." | b"run"
+            => script::cmd_source(t, cmd, args, hw, pci),
+        b"test"  => script::cmd_test(t, args),
+        b"waitkey" | b"pausa"
+            => script::cmd_waitkey(t),
+        b"true"  => {}
+        b"false" => t.fail(),
+        b"if" | b"then" | b"else" | b"fi"
+            => t.write_line("  if/then/else/fi solo estan disponibles dentro de scripts (source).", LineColor::Error),
 
         // ── Cálculo y conversión ─────────────────────────────────────────────
         b"calc" | b"math" | b"="
@@ -67,12 +141,16 @@ pub fn dispatch(
         b"dec"  => convert::cmd_dec(t, args),
         b"bin"  => convert::cmd_bin(t, args),
         b"rgb"  => convert::cmd_rgb(t, args),
+        b"asciitable" | b"tablaascii"
+            => convert::cmd_asciitable(t),
 
         // ── Hardware / depuración ────────────────────────────────────────────
         b"hexdump" | b"dump" | b"hd"
             => debug::cmd_hexdump(t, args),
+        b"readsec" => debug::cmd_readsec(t, args),
         b"peek"    => debug::cmd_peek(t, args),
         b"poke"    => debug::cmd_poke(t, args),
+        b"vmmap"   => debug::cmd_vmmap(t),
         b"cpuid"   => debug::cmd_cpuid(t, args),
         b"pic" | b"lsirq"
             => debug::cmd_pic(t),
@@ -80,6 +158,12 @@ pub fn dispatch(
         b"memtest" => debug::cmd_memtest(t, args),
         b"inb"     => debug::cmd_inb(t, args),
         b"outb"    => debug::cmd_outb(t, args),
+        b"regwatch" => debug::cmd_regwatch(t, args),
+        b"reg" | b"regs" | b"registros"
+            => debug::cmd_reg(t),
+        b"profile" | b"perfil"
+            => debug::cmd_profile(t, args),
+        b"bench"   => debug::cmd_bench(t, args, hw, pci),
 
         // ── Navegación del sistema de archivos (estilo Unix) ─────────────────
         b"ls" | b"dir" | b"listar"
@@ -90,11 +174,15 @@ pub fn dispatch(
             => disk::cmd_pwd(t),
         b"tree" | b"arbol"
             => disk::cmd_tree(t, args),
+        b"find" | b"buscar"
+            => find::cmd_find(t, args),
 
         // ── Archivos ─────────────────────────────────────────────────────────
 
 b"cat" | b"type"
     => disk::cmd_cat(t, args),
+        b"wc" | b"contar"
+            => disk::cmd_wc(t, args),
         b"touch" | b"nuevo"
             => disk::cmd_touch(t, args),
         b"write" | b"escribir"
@@ -109,16 +197,30 @@ b"cat" | b"type"
             => disk::cmd_stat(t, args),
         b"edit" | b"nano" | b"editor"
             => disk::cmd_edit(t, args),
+        b"view" | b"hexview"
+            => disk::cmd_view(t, args),
+        b"screenshot" | b"captura"
+            => disk::cmd_screenshot(t, args),
+        b"img" | b"imagen"
+            => disk::cmd_img(t, args),
+        b"bmp2raw"
+            => disk::cmd_img_convert(t, args),
 
         // ── Disco ATA / DiskPart ─────────────────────────────────────────────
         b"diskpart" | b"diskinfo" | b"drives"
             => disk::cmd_diskpart(t),
+        b"parts" | b"partprint" | b"particiones"
+            => system::cmd_parts(t, args),
         b"diskread" | b"sectors"
             => disk::cmd_diskread(t, args),
         b"diskedit" | b"hexedit"
             => disk::cmd_diskedit(t, args),
         b"diskwrite"
             => disk::cmd_diskwrite(t, args),
+        b"remount" | b"montar"
+            => disk::cmd_remount(t, args),
+        b"atareset"
+            => disk::cmd_atareset(t, args),
 
         // ── Entretenimiento ──────────────────────────────────────────────────
         b"beep"    => fun::cmd_beep(t, args),
@@ -133,13 +235,16 @@ b"cat" | b"type"
             => fun::cmd_scrolltest(t),
 
         // ── Energía ──────────────────────────────────────────────────────────
-        b"poweroff" | b"shutdown" | b"apagar" => {
-            t.write_line("  Apagando el sistema...", LineColor::Warning);
-            crate::drivers::bus::acpi::poweroff();
-        }
-        b"reboot" | b"restart" | b"reiniciar" => {
-            t.write_line("  Reiniciando...", LineColor::Warning);
-            crate::drivers::bus::acpi::reboot();
+        b"poweroff" | b"shutdown" | b"apagar"
+            => system::cmd_poweroff(t, args),
+        b"reboot" | b"restart" | b"reiniciar"
+            => system::reboot_now(t),
+        b"sync" => {
+            if disk::sync_disks(t) {
+                t.write_line("  Discos sincronizados.", LineColor::Success);
+            } else {
+                t.fail();
+            }
         }
 
         // ── Comando desconocido ──────────────────────────────────────────────
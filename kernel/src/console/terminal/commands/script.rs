@@ -0,0 +1,467 @@
+// console/terminal/commands/script.rs — PORTIX Kernel v0.8.2
+// Ejecutor de scripts de comandos + condicionales mínimos.
+//
+// ┌─ Sintaxis ─────────────────────────────────────────────────────────────────┐
+// │  source <archivo>             Ejecutar cada línea como un comando         │
+// │  run <archivo>                Igual que source                            │
+// │  if <cmd>; then <cmd>; fi     La condición es el éxito del comando        │
+// │  if <cmd>; then ...; else ...; fi                                         │
+// │  test <expr>                  -n/-z <s>, -e <ruta>, a = b, a != b,        │
+// │                               a -eq/-ne/-lt/-gt b                         │
+// │  true / false                 Éxito / fallo sin salida                    │
+// │  waitkey                      Pausar hasta pulsar una tecla (Esc aborta)  │
+// └───────────────────────────────────────────────────────────────────────────┘
+//
+// Cada línea se trocea por ';' y cada sentencia alimenta una pequeña máquina
+// de estados (IfFrame por nivel). Un bloque mal formado aborta el script con
+// "error de sintaxis" indicando la línea. Anidamiento máximo: MAX_IF_DEPTH.
+//
+// `waitkey` no bloquea el kernel: marca el terminal como en espera y cada
+// nivel de `source` en curso guarda lo que le queda por ejecutar (texto
+// restante + estado de los `if`) en PAUSED, del más anidado al más externo.
+// El loop principal entrega la siguiente tecla a `resume_after_key`, que
+// reanuda los niveles en ese orden; Esc los descarta todos.
+//
+// Líneas vacías y las que empiezan por '#' se saltan. Una sentencia más
+// larga que la línea de comando (INPUT_MAX) aborta el script en vez de
+// ejecutarse truncada. Cada nivel de `source`/`run` reserva SCRIPT_MAX
+// bytes de pila, así que el anidamiento se corta en MAX_SOURCE_DEPTH (un
+// script que se llama a sí mismo falla en vez de agotar la pila).
+
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS, INPUT_MAX};
+use crate::console::terminal::fmt::*;
+use crate::console::terminal::commands::disk;
+
+pub const MAX_IF_DEPTH: usize = 4;
+const SCRIPT_MAX:       usize = 8192;
+/// Niveles de `source` anidados que pueden quedar en pausa a la vez.
+const PAUSE_MAX:        usize = 4;
+/// Niveles de `source`/`run` anidados como máximo.
+pub const MAX_SOURCE_DEPTH: usize = 8;
+
+/// Niveles de `source`/`run` en ejecución ahora mismo.
+static mut SOURCE_DEPTH: usize = 0;
+
+type Hw  = crate::arch::hardware::HardwareInfo;
+type Pci = crate::drivers::bus::pci::PciBus;
+
+// ── Máquina de estados if/then/else/fi ────────────────────────────────────────
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum IfStage { Cond, Then, Else }
+
+#[derive(Clone, Copy)]
+struct IfFrame {
+    stage:     IfStage,
+    parent_on: bool,   // el bloque que contiene este `if` se está ejecutando
+    cond:      bool,   // resultado del comando de condición
+}
+
+impl IfFrame {
+    const fn empty() -> Self { IfFrame { stage: IfStage::Cond, parent_on: false, cond: false } }
+}
+
+#[derive(Clone, Copy)]
+struct ScriptRunner {
+    stack: [IfFrame; MAX_IF_DEPTH],
+    depth: usize,
+}
+
+impl ScriptRunner {
+    const fn new() -> Self {
+        ScriptRunner { stack: [IfFrame::empty(); MAX_IF_DEPTH], depth: 0 }
+    }
+
+    /// true si las sentencias en la posición actual deben ejecutarse.
+    fn active(&self) -> bool {
+        if self.depth == 0 { return true; }
+        let f = self.stack[self.depth - 1];
+        match f.stage {
+            IfStage::Cond => false,
+            IfStage::Then => f.parent_on && f.cond,
+            IfStage::Else => f.parent_on && !f.cond,
+        }
+    }
+
+    fn top_stage(&self) -> Option<IfStage> {
+        if self.depth == 0 { None } else { Some(self.stack[self.depth - 1].stage) }
+    }
+
+    fn feed(&mut self, t: &mut Terminal, stmt: &[u8], hw: &Hw, pci: &Pci) -> Result<(), &'static str> {
+        let stmt = trim(stmt);
+        if stmt.is_empty() { return Ok(()); }
+        let sp = stmt.iter().position(|&b| b == b' ').unwrap_or(stmt.len());
+        let kw   = &stmt[..sp];
+        let rest = trim(&stmt[sp..]);
+
+        match kw {
+            b"if" => {
+                if self.top_stage() == Some(IfStage::Cond) { return Err("se esperaba 'then'"); }
+                if self.depth >= MAX_IF_DEPTH { return Err("demasiados 'if' anidados (max 4)"); }
+                if rest.is_empty() { return Err("'if' sin condicion"); }
+                let parent_on = self.active();
+                let cond = parent_on && t.run_command(rest, hw, pci);
+                self.stack[self.depth] = IfFrame { stage: IfStage::Cond, parent_on, cond };
+                self.depth += 1;
+                Ok(())
+            }
+            b"then" => {
+                if self.top_stage() != Some(IfStage::Cond) { return Err("'then' sin 'if'"); }
+                self.stack[self.depth - 1].stage = IfStage::Then;
+                self.feed(t, rest, hw, pci)
+            }
+            b"else" => {
+                if self.top_stage() != Some(IfStage::Then) { return Err("'else' fuera de un bloque if/then"); }
+                self.stack[self.depth - 1].stage = IfStage::Else;
+                self.feed(t, rest, hw, pci)
+            }
+            b"fi" => {
+                match self.top_stage() {
+                    None                => return Err("'fi' sin 'if'"),
+                    Some(IfStage::Cond) => return Err("'fi' sin 'then'"),
+                    _ => {}
+                }
+                if !rest.is_empty() { return Err("texto inesperado tras 'fi'"); }
+                self.depth -= 1;
+                Ok(())
+            }
+            _ => {
+                if self.top_stage() == Some(IfStage::Cond) { return Err("se esperaba 'then'"); }
+                if self.active() { t.run_command(stmt, hw, pci); }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(&self) -> Result<(), &'static str> {
+        if self.depth != 0 { Err("falta 'fi'") } else { Ok(()) }
+    }
+}
+
+// ── Pausa (`waitkey`) ─────────────────────────────────────────────────────────
+
+/// Resto de un script suspendido por `waitkey`.
+#[derive(Clone, Copy)]
+struct Paused {
+    text:   [u8; SCRIPT_MAX],
+    len:    usize,
+    /// Líneas ya consumidas antes de `text` (para los mensajes de error)
+    line_n: u32,
+    runner: ScriptRunner,
+}
+
+impl Paused {
+    const EMPTY: Self = Paused { text: [0u8; SCRIPT_MAX], len: 0, line_n: 0, runner: ScriptRunner::new() };
+}
+
+/// [0] = nivel más anidado, que se reanuda primero.
+static mut PAUSED:       [Paused; PAUSE_MAX] = [Paused::EMPTY; PAUSE_MAX];
+static mut PAUSED_COUNT: usize = 0;
+/// Dónde inserta el siguiente nivel que se suspende: los niveles de una
+/// misma pausa se guardan delante de los que ya esperaban, en orden.
+static mut PAUSE_INSERT: usize = 0;
+
+/// Guarda `rest` como nivel suspendido. false si no queda hueco.
+fn save_paused(rest: &[u8], line_n: u32, runner: &ScriptRunner) -> bool {
+    // SAFETY: kernel monohilo; PAUSED solo se toca desde el terminal.
+    unsafe {
+        let paused = &mut *core::ptr::addr_of_mut!(PAUSED);
+        let count  = *core::ptr::addr_of!(PAUSED_COUNT);
+        let at     = (*core::ptr::addr_of!(PAUSE_INSERT)).min(count);
+        if count >= PAUSE_MAX || rest.len() > SCRIPT_MAX { return false; }
+        paused.copy_within(at..count, at + 1);
+        let p = &mut paused[at];
+        p.text[..rest.len()].copy_from_slice(rest);
+        p.len    = rest.len();
+        p.line_n = line_n;
+        p.runner = *runner;
+        *core::ptr::addr_of_mut!(PAUSED_COUNT) = count + 1;
+        *core::ptr::addr_of_mut!(PAUSE_INSERT) = at + 1;
+    }
+    true
+}
+
+fn take_paused() -> Option<Paused> {
+    // SAFETY: ver save_paused.
+    unsafe {
+        let paused = &mut *core::ptr::addr_of_mut!(PAUSED);
+        let count  = *core::ptr::addr_of!(PAUSED_COUNT);
+        if count == 0 { return None; }
+        let p = paused[0];
+        paused.copy_within(1..count, 0);
+        *core::ptr::addr_of_mut!(PAUSED_COUNT) = count - 1;
+        Some(p)
+    }
+}
+
+fn clear_paused() {
+    // SAFETY: ver save_paused.
+    unsafe { *core::ptr::addr_of_mut!(PAUSED_COUNT) = 0; }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Flow { Done, SyntaxError, Paused }
+
+/// Ejecuta `text` sentencia a sentencia. `line_n` son las líneas ya
+/// consumidas antes de `text` (al reanudar a mitad de script).
+fn run_text(
+    t: &mut Terminal, text: &[u8], mut line_n: u32, runner: &mut ScriptRunner,
+    hw: &Hw, pci: &Pci,
+) -> Flow {
+    let mut pos = 0usize;
+    while pos < text.len() {
+        let (raw, eol) = next_line(text, pos);
+        line_n += 1;
+        if is_code_line(raw) {
+            let mut s = 0usize;
+            for stmt in raw.split(|&b| b == b';') {
+                s += stmt.len() + 1;
+                if trim(stmt).len() > INPUT_MAX {
+                    long_statement(t, line_n);
+                    return Flow::SyntaxError;
+                }
+                if let Err(msg) = runner.feed(t, stmt, hw, pci) {
+                    syntax_error(t, line_n, msg.as_bytes());
+                    return Flow::SyntaxError;
+                }
+                if t.key_wait {
+                    // Si quedan sentencias en esta línea, el resto empieza a
+                    // mitad de ella y no cuenta como consumida
+                    let (from, done) = if pos + s < pos + raw.len() {
+                        (pos + s, line_n - 1)
+                    } else {
+                        ((eol + 1).min(text.len()), line_n)
+                    };
+                    if !save_paused(&text[from..], done, runner) {
+                        t.write_line("  waitkey: demasiados scripts en pausa; se descarta el resto",
+                                     LineColor::Warning);
+                    }
+                    return Flow::Paused;
+                }
+            }
+        }
+        pos = eol + 1;
+    }
+    if let Err(msg) = runner.finish() {
+        syntax_error(t, line_n, msg.as_bytes());
+        return Flow::SyntaxError;
+    }
+    Flow::Done
+}
+
+/// Línea de `text` que empieza en `pos`, sin el '\n' ni un '\r' final, y la
+/// posición de su '\n' (o `text.len()` si es la última).
+fn next_line(text: &[u8], pos: usize) -> (&[u8], usize) {
+    let eol = text[pos..].iter().position(|&b| b == b'\n').map_or(text.len(), |i| pos + i);
+    let raw = &text[pos..eol];
+    (raw.strip_suffix(b"\r").unwrap_or(raw), eol)
+}
+
+/// false para las líneas que se saltan: vacías o comentarios ('#').
+fn is_code_line(raw: &[u8]) -> bool {
+    let line = trim(raw);
+    !line.is_empty() && line[0] != b'#'
+}
+
+/// Ejecuta un script ya cargado en memoria. Devuelve false si se abortó
+/// por error de sintaxis (el mensaje ya se ha escrito en el terminal). Si
+/// el script queda en pausa por `waitkey` devuelve true y el resto lo
+/// ejecuta `resume_after_key`.
+pub fn run_script(t: &mut Terminal, text: &[u8], hw: &Hw, pci: &Pci) -> bool {
+    let mut runner = ScriptRunner::new();
+    run_text(t, text, 0, &mut runner, hw, pci) != Flow::SyntaxError
+}
+
+/// Tecla recibida mientras el terminal espera por `waitkey`: reanuda los
+/// scripts en pausa o, con `abort` (Esc), los descarta.
+pub fn resume_after_key(t: &mut Terminal, abort: bool, hw: &Hw, pci: &Pci) {
+    t.key_wait = false;
+    if abort {
+        // SAFETY: ver save_paused.
+        let pending = unsafe { *core::ptr::addr_of!(PAUSED_COUNT) };
+        clear_paused();
+        if pending > 0 {
+            t.write_line("  Script abortado (Esc).", LineColor::Warning);
+            t.last_ok = Some(false);
+        }
+        t.remote_prompt();
+        return;
+    }
+
+    let mut ok = true;
+    while let Some(mut p) = take_paused() {
+        let flow = run_text(t, &p.text[..p.len], p.line_n, &mut p.runner, hw, pci);
+        ok &= flow != Flow::SyntaxError;
+        if flow == Flow::Paused { return; }
+    }
+    if !ok { t.last_ok = Some(false); }
+    t.remote_prompt();
+}
+
+fn syntax_error(t: &mut Terminal, line_n: u32, msg: &[u8]) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  script: error de sintaxis en linea ");
+    append_u32(&mut buf, &mut pos, line_n);
+    append_str(&mut buf, &mut pos, b": ");
+    append_str(&mut buf, &mut pos, msg);
+    t.write_bytes(&buf[..pos], LineColor::Error);
+    t.write_line("  Script abortado.", LineColor::Warning);
+}
+
+/// Sentencia que no cabría en la línea de comando: el límite sale de INPUT_MAX.
+fn long_statement(t: &mut Terminal, line_n: u32) {
+    let mut buf = [0u8; 48]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"sentencia demasiado larga (max ");
+    append_u32(&mut buf, &mut pos, INPUT_MAX as u32);
+    append_str(&mut buf, &mut pos, b" caracteres)");
+    syntax_error(t, line_n, &buf[..pos]);
+}
+
+// ── Comandos ──────────────────────────────────────────────────────────────────
+
+/// `source`/`run`: `name` es el comando tecleado, para los mensajes.
+pub fn cmd_source(t: &mut Terminal, name: &[u8], args: &[u8], hw: &Hw, pci: &Pci) {
+    let args = trim(args);
+    if args.is_empty() {
+        let mut buf = [0u8; 40]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Uso: ");
+        append_str(&mut buf, &mut pos, name);
+        append_str(&mut buf, &mut pos, b" <archivo>");
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+        return;
+    }
+    // SAFETY: kernel monohilo; SOURCE_DEPTH solo se toca desde aquí.
+    let depth = unsafe { *core::ptr::addr_of!(SOURCE_DEPTH) };
+    if depth >= MAX_SOURCE_DEPTH {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_str(&mut buf, &mut pos, name);
+        append_str(&mut buf, &mut pos, b": demasiados scripts anidados (max ");
+        append_u32(&mut buf, &mut pos, MAX_SOURCE_DEPTH as u32);
+        append_str(&mut buf, &mut pos, b")");
+        t.write_bytes(&buf[..pos], LineColor::Error);
+        return;
+    }
+    let mut text = [0u8; SCRIPT_MAX];
+    let Some(n) = disk::read_text_file(t, name, args, &mut text) else { return };
+    // SAFETY: ver arriba.
+    unsafe { *core::ptr::addr_of_mut!(SOURCE_DEPTH) = depth + 1; }
+    let ok = run_script(t, &text[..n], hw, pci);
+    unsafe { *core::ptr::addr_of_mut!(SOURCE_DEPTH) = depth; }
+    if !ok { t.fail(); }
+}
+
+/// `waitkey`: detiene el terminal (y el script en curso) hasta la próxima
+/// tecla. Dentro de una redirección no tiene sentido esperar y no hace nada.
+pub fn cmd_waitkey(t: &mut Terminal) {
+    if t.redirecting { return; }
+    t.write_line("  Presiona una tecla para continuar... (Esc aborta el script)", LineColor::Info);
+    t.key_wait = true;
+    // SAFETY: ver save_paused.
+    unsafe { *core::ptr::addr_of_mut!(PAUSE_INSERT) = 0; }
+}
+
+/// `test` no escribe nada: solo fija el código de retorno.
+pub fn cmd_test(t: &mut Terminal, args: &[u8]) {
+    let a = trim(args);
+    let mut toks: [&[u8]; 3] = [b""; 3];
+    let mut n = 0usize;
+    for tok in a.split(|&b| b == b' ').filter(|s| !s.is_empty()) {
+        if n == 3 { n += 1; break; }
+        toks[n] = tok; n += 1;
+    }
+
+    let ok = match n {
+        0 => false,
+        1 => true,
+        2 => match toks[0] {
+            b"-n" => !toks[1].is_empty(),
+            b"-z" => toks[1].is_empty(),
+            b"-e" => disk::path_exists(t, toks[1]),
+            _     => { test_usage(t); return; }
+        },
+        3 => match toks[1] {
+            b"="   | b"==" => toks[0] == toks[2],
+            b"!="          => toks[0] != toks[2],
+            op => {
+                let (Some(x), Some(y)) = (parse_u64(toks[0]), parse_u64(toks[2])) else {
+                    test_usage(t); return;
+                };
+                match op {
+                    b"-eq" => x == y,
+                    b"-ne" => x != y,
+                    b"-lt" => x <  y,
+                    b"-gt" => x >  y,
+                    _      => { test_usage(t); return; }
+                }
+            }
+        },
+        _ => { test_usage(t); return; }
+    };
+    if !ok { t.fail(); }
+}
+
+fn test_usage(t: &mut Terminal) {
+    t.write_line("  Uso: test -n|-z <texto> | -e <ruta> | a = b | a != b | a -eq|-ne|-lt|-gt b", LineColor::Error);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Líneas de `text` tal como las recorre run_text: (nº de línea, contenido).
+    fn code_lines(text: &[u8]) -> alloc::vec::Vec<(u32, &[u8])> {
+        let mut out = alloc::vec::Vec::new();
+        let (mut pos, mut n) = (0usize, 0u32);
+        while pos < text.len() {
+            let (raw, eol) = next_line(text, pos);
+            n += 1;
+            if is_code_line(raw) { out.push((n, raw)); }
+            pos = eol + 1;
+        }
+        out
+    }
+
+    #[test]
+    fn next_line_strips_newline_and_carriage_return() {
+        let text = b"uno\r\ndos\ntres";
+        assert_eq!(next_line(text, 0), (&b"uno"[..], 4));
+        assert_eq!(next_line(text, 5), (&b"dos"[..], 8));
+        // Última línea sin '\n': termina en text.len()
+        assert_eq!(next_line(text, 9), (&b"tres"[..], text.len()));
+        assert_eq!(next_line(b"\n", 0), (&b""[..], 0));
+    }
+
+    #[test]
+    fn blank_and_comment_lines_are_skipped() {
+        assert!(is_code_line(b"echo hola"));
+        assert!(is_code_line(b"  echo # no es comentario"));
+        assert!(!is_code_line(b""));
+        assert!(!is_code_line(b"    "));
+        assert!(!is_code_line(b"# comentario"));
+        assert!(!is_code_line(b"   # sangrado"));
+    }
+
+    #[test]
+    fn line_numbers_count_skipped_lines() {
+        let text = b"# cabecera\r\n\necho a; echo b\r\n  # otro\nver";
+        let lines = code_lines(text);
+        assert_eq!(lines, [(3, &b"echo a; echo b"[..]), (5, &b"ver"[..])]);
+        // Las sentencias de una línea se separan por ';'
+        let stmts: alloc::vec::Vec<&[u8]> = lines[0].1.split(|&b| b == b';').map(trim).collect();
+        assert_eq!(stmts, [&b"echo a"[..], &b"echo b"[..]]);
+    }
+
+    #[test]
+    fn long_statement_message_uses_input_max() {
+        let mut t = alloc::boxed::Box::new(Terminal::new());
+        long_statement(&mut t, 7);
+        let l = *t.line_at(t.line_count - 2);
+        let mut want = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut want, &mut pos, b"  script: error de sintaxis en linea 7: sentencia demasiado larga (max ");
+        append_u32(&mut want, &mut pos, INPUT_MAX as u32);
+        append_str(&mut want, &mut pos, b" caracteres)");
+        assert_eq!(&l.buf[..l.len], &want[..pos]);
+        assert_eq!(l.color, LineColor::Error);
+    }
+}
@@ -1,9 +1,12 @@
 // console/terminal/commands/system.rs
 // Comandos: help, ver, motd, ascii, uname, whoami, hostname,
-//           info, cpu, mem, disks, pci, neofetch, uptime, date/fecha
+//           info, cpu, mem, disks, pci, neofetch, uptime, date/fecha, timer, sticky,
+//           split, banner-set, banner-clear, parts, remote, serial, lsmod, acpi,
+//           mouse, clear, echo, alias, unalias, free
 
-use crate::console::terminal::{Terminal, LineColor, TERM_COLS};
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS, INPUT_MAX, STICKY_MAX, BANNER_MAX};
 use crate::console::terminal::fmt::*;
+use crate::drivers::storage::fsprobe::FsKind;
 
 // ── help ──────────────────────────────────────────────────────────────────────
 
@@ -18,25 +21,45 @@ pub fn cmd_help(t: &mut Terminal) {
     t.write_line("    neofetch      Vista general del sistema con logo ASCII",    LineColor::Normal);
     t.write_line("    cpu           Detalles del procesador y extensiones ISA",   LineColor::Normal);
     t.write_line("    mem           Mapa de memoria RAM (E820)",                  LineColor::Normal);
-    t.write_line("    disks         Dispositivos de almacenamiento ATA",          LineColor::Normal);
+    t.write_line("    heap          Uso y fragmentacion del heap del kernel",     LineColor::Normal);
+    t.write_line("    free          RAM total, usada por el heap y libre",        LineColor::Normal);
+    t.write_line("    disks         Dispositivos de almacenamiento ATA/AHCI",     LineColor::Normal);
+    t.write_line("    lsblk -f      Sistema de archivos de cada disco/particion", LineColor::Normal);
     t.write_line("    pci           Enumeracion del bus PCI",                     LineColor::Normal);
+    t.write_line("    pci -v B:D.F  Config space y BARs de un dispositivo",      LineColor::Normal);
+    t.write_line("    lsmod         Drivers/subsistemas del kernel y su estado",  LineColor::Normal);
+    t.write_line("    acpi          Tablas ACPI (RSDP/RSDT/FADT) y datos de apagado", LineColor::Normal);
     t.write_line("    uname / ver   Version del sistema operativo",              LineColor::Normal);
+    t.write_line("    versions      Version de cada subsistema compilado",       LineColor::Normal);
     t.write_line("    uptime        Tiempo en linea y ticks del PIT",             LineColor::Normal);
-    t.write_line("    date          Fecha/hora desde el arranque",                LineColor::Normal);
+    t.write_line("    date [-s ..]  Fecha/hora del RTC (-s YYYY-MM-DD HH:MM:SS)", LineColor::Normal);
+    t.write_line("    timer <seg>   Cuenta atras con alarma (list, cancel [id])", LineColor::Normal);
     t.write_empty();
 
     t.write_line("  DISCO ATA:", LineColor::Info);
+    t.write_line("    parts [drive]             Tabla de particiones del MBR",  LineColor::Normal);
+    t.write_line("    find <patron>             Buscar archivos por nombre",      LineColor::Normal);
+    t.write_line("    wc <archivo>              Contar lineas, palabras y bytes", LineColor::Normal);
     t.write_line("    diskinfo                  Listar drives ATA detectados",    LineColor::Normal);
     t.write_line("    diskread [lba] [drive]    Hexdump de sector (sin editar)",  LineColor::Normal);
     t.write_line("    diskedit [lba] [drive]    Editor hexadecimal interactivo",  LineColor::Normal);
     t.write_line("    diskwrite <lba> <0xPAT>   Rellenar sector (QEMU/debug)",    LineColor::Normal);
+    t.write_line("    remount [ro|rw]           Solo lectura / lectura-escritura",  LineColor::Normal);
+    t.write_line("    sync                      Actualizar FSInfo y vaciar cache",  LineColor::Normal);
+    t.write_line("    atareset [0|1]            Reset del canal ATA y re-detectar", LineColor::Normal);
     t.write_line("    drive: 0=ATA0-M 1=ATA0-S 2=ATA1-M 3=ATA1-S",              LineColor::Normal);
     t.write_empty();
 
     t.write_line("  HARDWARE Y DEPURACION:", LineColor::Info);
     t.write_line("    hexdump <dir> [bytes]  Volcado hexadecimal de memoria",     LineColor::Normal);
-    t.write_line("    peek <dir>             Leer 8 bytes en direccion fisica",   LineColor::Normal);
-    t.write_line("    poke <dir> <val>       Escribir byte en direccion fisica",  LineColor::Normal);
+    t.write_line("    readsec <lba> [n]      Sectores crudos del disco primario", LineColor::Normal);
+    t.write_line("    regwatch <fis> [ms]    Vigilar cambios de un registro MMIO", LineColor::Normal);
+    t.write_line("    reg                    Registros de CPU en vivo (GPR, RFLAGS, CR0-CR4)", LineColor::Normal);
+    t.write_line("    profile on|off|show    Muestrear el RIP en IRQ0 (histograma)", LineColor::Normal);
+    t.write_line("    bench <cmd>            Medir ciclos/tiempo de un comando (TSC)", LineColor::Normal);
+    t.write_line("    peek <dir>             Leer 8 bytes en direccion virtual",  LineColor::Normal);
+    t.write_line("    poke <dir> <val>       Escribir byte en direccion virtual", LineColor::Normal);
+    t.write_line("    vmmap                  Regiones mapeadas por la paginacion", LineColor::Normal);
     t.write_line("    cpuid [hoja]           Ejecutar instruccion CPUID",         LineColor::Normal);
     t.write_line("    pic                    Estado de mascaras del PIC/IRQ",     LineColor::Normal);
     t.write_line("    gdt                    Volcado de la tabla GDT",            LineColor::Normal);
@@ -46,22 +69,51 @@ pub fn cmd_help(t: &mut Terminal) {
     t.write_empty();
 
     t.write_line("  CALCULO Y CONVERSION:", LineColor::Info);
-    t.write_line("    calc / = <expr>   Aritmetica: + - * /",                    LineColor::Normal);
+    t.write_line("    calc / = <expr>   + - * / % ** & | ^ ~ << >> ( ), 0x 0b", LineColor::Normal);
     t.write_line("    hex <decimal>     Decimal a hexadecimal",                  LineColor::Normal);
     t.write_line("    dec <0xHEX>       Hexadecimal a decimal",                  LineColor::Normal);
     t.write_line("    bin <decimal>     Decimal a binario",                      LineColor::Normal);
     t.write_line("    rgb <r> <g> <b>   Componentes RGB a 0xRRGGBB",             LineColor::Normal);
+    t.write_line("    asciitable        Tabla de los 95 caracteres imprimibles",  LineColor::Normal);
     t.write_empty();
 
     t.write_line("  TERMINAL:", LineColor::Info);
-    t.write_line("    echo <texto>   Imprimir texto en pantalla",                 LineColor::Normal);
+    t.write_line("    echo [-c color] <texto>  Imprimir texto (success/warning/error/info/header)", LineColor::Normal);
     t.write_line("    history        Historial de comandos (ultimos 16)",         LineColor::Normal);
-    t.write_line("    clear          Limpiar la pantalla del terminal",           LineColor::Normal);
+    t.write_line("    alias [-f] n=cmd | unalias n  Alias de comandos (alias: listar)", LineColor::Normal);
+    t.write_line("    watch [-n s] <cmd>  Repetir un comando cada s segundos (Esc/q)", LineColor::Normal);
+    t.write_line("    clear [--keep] Limpiar la pantalla (--keep: conservar scroll)", LineColor::Normal);
+    t.write_line("    sticky         Fijar/desfijar la ultima linea en el tope",  LineColor::Normal);
+    t.write_line("    split [on|off] Dividir: historial congelado arriba, input abajo", LineColor::Normal);
+    t.write_line("    present-mode on|off  Fuente x2 para proyector",             LineColor::Normal);
+    t.write_line("    dmesg [-w|-l n] Log del kernel (-w: seguir, -l: nivel minimo)", LineColor::Normal);
+    t.write_line("    scroll-sync on|off  Auto-scroll inicial del visor del log", LineColor::Normal);
+    t.write_line("    banner-set <cmd>  Usar la salida de <cmd> como cabecera fija", LineColor::Normal);
+    t.write_line("    banner-clear   Quitar la cabecera fija",                    LineColor::Normal);
+    t.write_line("    cmd > f / >> f Redirigir la salida a un archivo (crear/anexar)", LineColor::Normal);
+    t.write_line("    remote on|off  Controlar el terminal desde COM1 (serie)",   LineColor::Normal);
+    t.write_line("    serial baud <n> Cambiar el baud rate de COM1 (115200 / n)", LineColor::Normal);
+    t.write_line("    screenshot [f] Captura de pantalla a BMP (SCRnnnnn.BMP)",   LineColor::Normal);
+    t.write_line("    img info|convert  Datos de un BMP / pre-escalarlo a pantalla", LineColor::Normal);
+    t.write_line("    config show|set|save|load  Opciones en /portix/config",     LineColor::Normal);
+    t.write_line("    printenv | env         Toda la configuracion efectiva",     LineColor::Normal);
+    t.write_line("    loadkeys <f>|us  Cargar mapa de teclado (scancode normal shift)", LineColor::Normal);
+    t.write_line("    keymap us|es     Layout embebido US o ES (AltGr, teclas muertas)", LineColor::Normal);
+    t.write_line("    mouse speed <n> | accel on|off  Velocidad y aceleracion del raton", LineColor::Normal);
     t.write_line("    scrolltest     Generar 50 lineas para probar scroll",       LineColor::Normal);
     t.write_empty();
 
+    t.write_line("  SCRIPTS:", LineColor::Info);
+    t.write_line("    source | run <archivo>    Ejecutar un script de comandos",  LineColor::Normal);
+    t.write_line("    if <cmd>; then ..; else ..; fi   Condicional (max 4 niveles)", LineColor::Normal);
+    t.write_line("    test <expr>               -n/-z/-e, =, !=, -eq/-lt/-gt",    LineColor::Normal);
+    t.write_line("    true / false              Exito / fallo sin salida",        LineColor::Normal);
+    t.write_line("    waitkey                   Pausar hasta una tecla (Esc aborta)", LineColor::Normal);
+    t.write_empty();
+
     t.write_line("  NAVEGACION:", LineColor::Info);
     t.write_line("    RePag / AvPag  Desplazarse 10 lineas arriba/abajo",        LineColor::Normal);
+    t.write_line("    Ctrl+Flechas   Desplazarse linea a linea",                 LineColor::Normal);
     t.write_line("    Inicio / Fin   Saltar al principio / final",               LineColor::Normal);
     t.write_line("    Mouse          Arrastrar la barra lateral para navegar",   LineColor::Normal);
     t.write_empty();
@@ -75,19 +127,85 @@ pub fn cmd_help(t: &mut Terminal) {
     t.write_empty();
 
     t.write_line("  ENERGIA:", LineColor::Warning);
-    t.write_line("    reboot        Reiniciar el sistema",                        LineColor::Normal);
-    t.write_line("    poweroff      Apagar el sistema (ACPI S5)",                 LineColor::Normal);
+    t.write_line("    reboot        Sincronizar discos y reiniciar",              LineColor::Normal);
+    t.write_line("    poweroff      Sincronizar discos y apagar (ACPI S5)",       LineColor::Normal);
+    t.write_line("    poweroff <s>  Apagado programado (poweroff cancel / Esc)",  LineColor::Normal);
     t.write_empty();
 }
 
 // ── ver / uname / whoami / hostname ──────────────────────────────────────────
 
-pub fn cmd_ver(t: &mut Terminal) {
+/// "KVM (firma KVMKVMKVM en 0x40000000)", "bare metal", ...
+fn append_hypervisor(buf: &mut [u8], pos: &mut usize, hv: &crate::arch::hardware::HypervisorInfo) {
+    append_str(buf, pos, hv.name().as_bytes());
+    if !hv.is_virtual() { return; }
+    let sig = hv.signature_str();
+    if !sig.is_empty() {
+        append_str(buf, pos, b" (firma \"");
+        append_str(buf, pos, sig.as_bytes());
+        append_str(buf, pos, b"\" en 0x");
+        append_hex64_short(buf, pos, hv.leaf as u64);
+        append_str(buf, pos, b")");
+    }
+    if hv.is_hidden() { append_str(buf, pos, b" [oculto]"); }
+}
+
+pub fn cmd_ver(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
     t.separador("VERSION DEL SISTEMA");
-    t.write_line("  PORTIX Kernel v0.7  -  x86_64 bare-metal",                   LineColor::Success);
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  PORTIX Kernel v");
+    append_str(&mut buf, &mut pos, crate::VERSION.as_bytes());
+    append_str(&mut buf, &mut pos, b"  -  x86_64 bare-metal");
+    t.write_bytes(&buf[..pos], LineColor::Success);
+    let hv = &hw.cpu.hypervisor;
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Plataforma: ");
+    append_hypervisor(&mut buf, &mut pos, hv);
+    t.write_bytes(&buf[..pos], if hv.is_virtual() { LineColor::Info } else { LineColor::Normal });
+    if hv.is_hidden() {
+        t.write_line("  (el bit de hipervisor de CPUID esta a 0, pero su firma responde)", LineColor::Normal);
+    }
     t.write_line("  Compilacion: 2026 / Rust nightly (no_std) + NASM",           LineColor::Normal);
     t.write_line("  Subsistemas: PIT  Teclado PS/2  Raton PS/2  ATA  VESA  DblBuf", LineColor::Info);
     t.write_line("               PCI  ACPI  Serial COM1  E820  IDT",             LineColor::Info);
+    t.write_line("  Version de cada subsistema: 'versions'",                     LineColor::Normal);
+    t.write_empty();
+}
+
+// ── versions ──────────────────────────────────────────────────────────────────
+//
+// Cada módulo declara su propia `VERSION`; esta tabla solo las reúne, así
+// que no puede divergir de lo compilado.
+
+const VERSIONS: &[(&str, &str, &str)] = &[
+    ("Kernel",      crate::VERSION,                                "main.rs"),
+    ("UI / chrome", crate::ui::VERSION,                            "ui/"),
+    ("IDE",         crate::ui::tabs::ide::VERSION,                 "ui/tabs/ide.rs"),
+    ("Explorador",  crate::ui::tabs::explorer::VERSION,            "ui/tabs/explorer.rs"),
+    ("Terminal",    crate::console::terminal::VERSION,             "console/terminal/"),
+    ("ISR / panic", crate::arch::isr_handlers::VERSION,            "arch/isr_handlers.rs"),
+    ("Framebuffer", crate::graphics::driver::framebuffer::VERSION, "graphics/driver/framebuffer.rs"),
+    ("ATA",         crate::drivers::storage::ata::VERSION,         "drivers/storage/ata.rs"),
+    ("FAT32",       crate::drivers::storage::fat32::VERSION,       "drivers/storage/fat32.rs"),
+    ("VFS",         crate::drivers::storage::vfs::VERSION,         "drivers/storage/vfs.rs"),
+    ("Teclado PS/2",crate::drivers::input::keyboard::VERSION,      "drivers/input/keyboard.rs"),
+    ("Raton PS/2",  crate::drivers::input::mouse::VERSION,         "drivers/input/mouse.rs"),
+];
+
+pub fn cmd_versions(t: &mut Terminal) {
+    t.separador("VERSIONES POR SUBSISTEMA");
+    t.write_line("  Subsistema     Version   Modulo", LineColor::Header);
+    for &(name, ver, module) in VERSIONS {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_str(&mut buf, &mut pos, name.as_bytes());
+        while pos < 17 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, b"v");
+        append_str(&mut buf, &mut pos, ver.as_bytes());
+        while pos < 27 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, module.as_bytes());
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
     t.write_empty();
 }
 
@@ -121,7 +239,82 @@ pub fn cmd_uptime(t: &mut Terminal) {
     t.write_bytes(&buf[..pos], LineColor::Success);
 }
 
-pub fn cmd_fecha(t: &mut Terminal) {
+// ── date — RTC ───────────────────────────────────────────────────────────────
+//
+//   date                            Fecha/hora del RTC y tiempo desde arranque
+//   date -s YYYY-MM-DD HH:MM:SS     Ajustar el RTC (p. ej. con la pila agotada)
+
+fn append_2d(buf: &mut [u8], pos: &mut usize, v: u8) {
+    if v < 10 { append_str(buf, pos, b"0"); }
+    append_u32(buf, pos, v as u32);
+}
+
+fn append_datetime(buf: &mut [u8], pos: &mut usize, dt: &crate::time::rtc::DateTime) {
+    append_u32(buf, pos, dt.year as u32); append_str(buf, pos, b"-");
+    append_2d(buf, pos, dt.month);        append_str(buf, pos, b"-");
+    append_2d(buf, pos, dt.day);          append_str(buf, pos, b" ");
+    append_2d(buf, pos, dt.hour);         append_str(buf, pos, b":");
+    append_2d(buf, pos, dt.minute);       append_str(buf, pos, b":");
+    append_2d(buf, pos, dt.second);
+}
+
+/// Número decimal de exactamente `n` dígitos.
+fn parse_fixed(s: &[u8], n: usize) -> Option<u16> {
+    if s.len() != n || !s.iter().all(u8::is_ascii_digit) { return None; }
+    Some(s.iter().fold(0u16, |acc, &b| acc * 10 + (b - b'0') as u16))
+}
+
+/// "YYYY-MM-DD HH:MM:SS" (separados por uno o más espacios).
+fn parse_datetime(s: &[u8]) -> Option<crate::time::rtc::DateTime> {
+    let s  = trim(s);
+    let sp = s.iter().position(|&b| b == b' ')?;
+    let (d, h) = (&s[..sp], trim(&s[sp + 1..]));
+    let mut dp = d.split(|&b| b == b'-');
+    let mut hp = h.split(|&b| b == b':');
+    let dt = crate::time::rtc::DateTime {
+        year:   parse_fixed(dp.next()?, 4)?,
+        month:  parse_fixed(dp.next()?, 2)? as u8,
+        day:    parse_fixed(dp.next()?, 2)? as u8,
+        hour:   parse_fixed(hp.next()?, 2)? as u8,
+        minute: parse_fixed(hp.next()?, 2)? as u8,
+        second: parse_fixed(hp.next()?, 2)? as u8,
+    };
+    if dp.next().is_some() || hp.next().is_some() { return None; }
+    Some(dt)
+}
+
+pub fn cmd_fecha(t: &mut Terminal, args: &[u8]) {
+    use crate::time::rtc;
+    let args = trim(args);
+    if let Some(rest) = args.strip_prefix(b"-s") {
+        let Some(dt) = parse_datetime(rest) else {
+            t.write_line("  Uso: date -s YYYY-MM-DD HH:MM:SS", LineColor::Error);
+            return;
+        };
+        if let Err(e) = rtc::write(&dt) {
+            let mut buf = [0u8; 80]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  date: ");
+            append_str(&mut buf, &mut pos, e.as_bytes());
+            append_str(&mut buf, &mut pos, b" (1900-2099, dia segun el mes)");
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            return;
+        }
+        let mut buf = [0u8; 80]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  RTC ajustado: ");
+        append_datetime(&mut buf, &mut pos, &rtc::read());
+        t.write_bytes(&buf[..pos], LineColor::Success);
+        return;
+    }
+    if !args.is_empty() {
+        t.write_line("  Uso: date [-s YYYY-MM-DD HH:MM:SS]", LineColor::Warning);
+        return;
+    }
+
+    let mut buf = [0u8; 80]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Fecha/hora (RTC): ");
+    append_datetime(&mut buf, &mut pos, &rtc::read());
+    t.write_bytes(&buf[..pos], LineColor::Success);
+
     let (h, m, s) = crate::time::pit::uptime_hms();
     let tick = crate::time::pit::ticks();
     let mut buf = [0u8; 80]; let mut pos = 0;
@@ -135,7 +328,6 @@ pub fn cmd_fecha(t: &mut Terminal) {
     append_u32(&mut buf, &mut pos, (tick & 0xFFFF_FFFF) as u32);
     append_str(&mut buf, &mut pos, b")");
     t.write_bytes(&buf[..pos], LineColor::Info);
-    t.write_line("  Nota: sin driver RTC -- tiempo mostrado es desde el arranque.", LineColor::Normal);
 }
 
 pub fn cmd_ticks(t: &mut Terminal) {
@@ -149,6 +341,163 @@ pub fn cmd_ticks(t: &mut Terminal) {
     t.write_bytes(&buf[..pos], LineColor::Info);
 }
 
+// ── clear ─────────────────────────────────────────────────────────────────────
+//
+//   clear             Vacía el historial (el banner y las sticky se quedan)
+//   clear --keep      Limpia la vista pero conserva el historial para el scroll
+
+pub fn cmd_clear(t: &mut Terminal, args: &[u8]) {
+    match trim(args) {
+        b""                => t.clear_history(),
+        b"--keep" | b"-k"  => t.clear_keep(),
+        _ => t.write_line("  Uso: clear [--keep]", LineColor::Warning),
+    }
+}
+
+// ── echo ──────────────────────────────────────────────────────────────────────
+//
+//   echo <texto>              Imprime el texto tal cual (espacios incluidos)
+//   echo -c <color> <texto>   Con color: success, warning, error, info, header
+
+/// `LineColor` de un nombre de `echo -c`. None si no es uno de los válidos.
+pub(crate) fn echo_color(name: &[u8]) -> Option<LineColor> {
+    match name {
+        b"success" => Some(LineColor::Success),
+        b"warning" => Some(LineColor::Warning),
+        b"error"   => Some(LineColor::Error),
+        b"info"    => Some(LineColor::Info),
+        b"header"  => Some(LineColor::Header),
+        b"normal"  => Some(LineColor::Normal),
+        _ => None,
+    }
+}
+
+/// Separa `-c <color>` del texto. Solo se consume un espacio tras el color,
+/// así el texto conserva sus espacios. Sin `-c` → (None, args).
+pub(crate) fn split_echo_args(args: &[u8]) -> (Option<&[u8]>, &[u8]) {
+    let Some(rest) = args.strip_prefix(b"-c") else { return (None, args); };
+    if !rest.is_empty() && rest[0] != b' ' { return (None, args); }
+    let rest  = &rest[rest.iter().position(|&b| b != b' ').unwrap_or(rest.len())..];
+    let end   = rest.iter().position(|&b| b == b' ').unwrap_or(rest.len());
+    let text  = rest.get(end + 1..).unwrap_or(b"");
+    (Some(&rest[..end]), text)
+}
+
+pub fn cmd_echo(t: &mut Terminal, args: &[u8]) {
+    let (name, text) = split_echo_args(args);
+    let color = match name {
+        None => LineColor::Normal,
+        Some(b"") => {
+            t.write_line("  Uso: echo [-c success|warning|error|info|header] <texto>", LineColor::Warning);
+            return;
+        }
+        Some(n) => echo_color(n).unwrap_or_else(|| {
+            let mut buf = [0u8; 80]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  echo: color desconocido '");
+            append_str(&mut buf, &mut pos, &n[..n.len().min(24)]);
+            append_str(&mut buf, &mut pos, b"', se usa normal");
+            t.write_bytes(&buf[..pos], LineColor::Warning);
+            LineColor::Normal
+        }),
+    };
+    // `-c error` es presentación: no debe marcar el comando como fallido
+    let failed = t.cmd_failed;
+    t.write_bytes(text, color);
+    t.cmd_failed = failed;
+}
+
+// ── alias / unalias ───────────────────────────────────────────────────────────
+//
+//   alias                     Lista los alias definidos
+//   alias <nombre>            Muestra un alias
+//   alias [-f] <nombre>=<cmd> Define o redefine (-f para tapar un comando
+//                             crítico como reboot); admite 'cmd' o "cmd"
+//   unalias <nombre>          Quita un alias
+
+fn write_alias(t: &mut Terminal, name: &[u8], cmd: &[u8]) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  ");
+    append_str(&mut buf, &mut pos, name);
+    append_str(&mut buf, &mut pos, b"='");
+    append_str(&mut buf, &mut pos, &cmd[..cmd.len().min(TERM_COLS - 24)]);
+    append_str(&mut buf, &mut pos, b"'");
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+}
+
+pub fn cmd_alias(t: &mut Terminal, args: &[u8]) {
+    let mut args = trim(args);
+    if args.is_empty() {
+        if t.aliases.is_empty() {
+            t.write_line("  (sin alias)  Uso: alias <nombre>=<comando>", LineColor::Normal);
+            return;
+        }
+        t.separador("ALIAS");
+        for i in 0..t.aliases.len() {
+            let a = t.aliases.entries()[i];
+            write_alias(t, a.name(), a.cmd());
+        }
+        return;
+    }
+
+    let force = args.starts_with(b"-f ");
+    if force { args = trim(&args[3..]); }
+    let Some(eq) = args.iter().position(|&b| b == b'=') else {
+        match t.aliases.get(args) {
+            Some(cmd) => {
+                let mut c = [0u8; INPUT_MAX]; let n = cmd.len();
+                c[..n].copy_from_slice(cmd);
+                write_alias(t, args, &c[..n]);
+            }
+            None => t.write_line("  alias: no existe ese alias", LineColor::Error),
+        }
+        return;
+    };
+
+    let name = trim(&args[..eq]);
+    let mut cmd = trim(&args[eq + 1..]);
+    if cmd.len() >= 2 && (cmd[0] == b'\'' || cmd[0] == b'"') && cmd[cmd.len() - 1] == cmd[0] {
+        cmd = trim(&cmd[1..cmd.len() - 1]);
+    }
+    if crate::console::terminal::alias::is_critical(name) && !force {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  alias: '");
+        append_str(&mut buf, &mut pos, &name[..name.len().min(16)]);
+        append_str(&mut buf, &mut pos, b"' es un comando critico; usa 'alias -f' para taparlo");
+        t.write_bytes(&buf[..pos], LineColor::Error);
+        return;
+    }
+    match t.aliases.define(name, cmd) {
+        Ok(replaced) => {
+            if force && crate::console::terminal::alias::is_critical(name) {
+                t.write_line("  Aviso: el alias tapa un comando critico (unalias para recuperarlo).",
+                             LineColor::Warning);
+            }
+            let msg: &[u8] = if replaced { b"  Alias redefinido: " } else { b"  Alias definido: " };
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, msg);
+            append_str(&mut buf, &mut pos, name);
+            t.write_bytes(&buf[..pos], LineColor::Success);
+        }
+        Err(e) => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  alias: ");
+            append_str(&mut buf, &mut pos, e.as_bytes());
+            t.write_bytes(&buf[..pos], LineColor::Error);
+        }
+    }
+}
+
+pub fn cmd_unalias(t: &mut Terminal, args: &[u8]) {
+    let name = trim(args);
+    if name.is_empty() {
+        t.write_line("  Uso: unalias <nombre>", LineColor::Warning);
+    } else if t.aliases.remove(name) {
+        t.write_line("  Alias eliminado.", LineColor::Success);
+    } else {
+        t.write_line("  unalias: no existe ese alias", LineColor::Error);
+    }
+}
+
 // ── Historial ─────────────────────────────────────────────────────────────────
 
 pub fn cmd_history(t: &mut Terminal) {
@@ -172,6 +521,652 @@ pub fn cmd_history(t: &mut Terminal) {
     t.write_empty();
 }
 
+// ── timer ─────────────────────────────────────────────────────────────────────
+//
+//   timer <seg>          Cuenta atrás en segundo plano; pitido al llegar a 0
+//   timer [list]         Temporizadores activos
+//   timer cancel [id]    Cancelar uno (o todos sin id)
+//
+// El estado vive en time::timer; el loop principal lo avanza y la barra de
+// estado muestra el más próximo.
+
+fn append_mmss(buf: &mut [u8], pos: &mut usize, secs: u32) {
+    if secs >= 3600 {
+        append_u32(buf, pos, secs / 3600);
+        append_str(buf, pos, b":");
+        append_2d(buf, pos, ((secs % 3600) / 60) as u8);
+    } else {
+        append_2d(buf, pos, (secs / 60) as u8);
+    }
+    append_str(buf, pos, b":");
+    append_2d(buf, pos, (secs % 60) as u8);
+}
+
+pub fn cmd_timer(t: &mut Terminal, args: &[u8]) {
+    use crate::time::timer;
+    let args = trim(args);
+    let (sub, rest) = match args.iter().position(|&b| b == b' ') {
+        Some(sp) => (&args[..sp], trim(&args[sp + 1..])),
+        None     => (args, &b""[..]),
+    };
+    match sub {
+        b"" | b"list" => {
+            let (list, n) = timer::active();
+            if n == 0 {
+                t.write_line("  (sin temporizadores activos)", LineColor::Normal);
+                return;
+            }
+            let now = crate::time::pit::ticks();
+            for tm in &list[..n] {
+                let mut buf = [0u8; 80]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  #");
+                append_u32(&mut buf, &mut pos, tm.id);
+                append_str(&mut buf, &mut pos, b"  quedan ");
+                append_mmss(&mut buf, &mut pos, tm.remaining_secs(now));
+                append_str(&mut buf, &mut pos, b"  de ");
+                append_mmss(&mut buf, &mut pos, tm.secs);
+                t.write_bytes(&buf[..pos], LineColor::Normal);
+            }
+        }
+        b"cancel" | b"cancelar" => {
+            if rest.is_empty() {
+                let n = timer::cancel_all();
+                let mut buf = [0u8; 80]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  ");
+                append_u32(&mut buf, &mut pos, n as u32);
+                append_str(&mut buf, &mut pos, b" temporizador(es) cancelado(s)");
+                t.write_bytes(&buf[..pos], LineColor::Success);
+            } else if parse_u64(rest.strip_prefix(b"#").unwrap_or(rest))
+                .is_some_and(|id| timer::cancel(id as u32))
+            {
+                t.write_line("  Temporizador cancelado.", LineColor::Success);
+            } else {
+                t.write_line("  timer: no existe ese temporizador (ver 'timer list')", LineColor::Error);
+            }
+        }
+        secs if rest.is_empty() => {
+            let Some(n) = parse_u64(secs).filter(|&n| n > 0 && n <= timer::TIMER_MAX_SECS as u64) else {
+                t.write_line("  Uso: timer <seg> | timer [list] | timer cancel [id]", LineColor::Warning);
+                return;
+            };
+            let Some(tm) = timer::start(n as u32) else {
+                let mut buf = [0u8; 80]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  timer: maximo ");
+                append_u32(&mut buf, &mut pos, timer::TIMER_MAX as u32);
+                append_str(&mut buf, &mut pos, b" temporizadores a la vez");
+                t.write_bytes(&buf[..pos], LineColor::Error);
+                return;
+            };
+            let mut buf = [0u8; 80]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  Temporizador #");
+            append_u32(&mut buf, &mut pos, tm.id);
+            append_str(&mut buf, &mut pos, b": ");
+            append_mmss(&mut buf, &mut pos, tm.secs);
+            append_str(&mut buf, &mut pos, b" (cancelar: timer cancel ");
+            append_u32(&mut buf, &mut pos, tm.id);
+            append_str(&mut buf, &mut pos, b")");
+            t.write_bytes(&buf[..pos], LineColor::Success);
+        }
+        _ => t.write_line("  Uso: timer <seg> | timer [list] | timer cancel [id]", LineColor::Warning),
+    }
+}
+
+// ── poweroff ──────────────────────────────────────────────────────────────────
+//
+//   poweroff                  Apaga ya (tras sincronizar los discos)
+//   poweroff [--timer] <seg>  Programa el apagado; cuenta atrás en la barra
+//   poweroff cancel           Cancela el apagado programado (o Esc)
+
+pub fn cmd_poweroff(t: &mut Terminal, args: &[u8]) {
+    use crate::time::timer;
+    let args = trim(args);
+    let secs_arg = match args.strip_prefix(b"--timer") {
+        Some(rest) => trim(rest),
+        None       => args,
+    };
+    match args {
+        b"" => shutdown_now(t),
+        b"cancel" => {
+            if timer::cancel_shutdown() {
+                t.write_line("  Apagado programado cancelado.", LineColor::Success);
+            } else {
+                t.write_line("  No hay ningun apagado programado.", LineColor::Warning);
+            }
+        }
+        _ => match parse_u64(secs_arg) {
+            Some(n) if (1..=timer::TIMER_MAX_SECS as u64).contains(&n) => {
+                let again = timer::schedule_shutdown(n as u32);
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, if again { b"  Apagado reprogramado: en " } else { b"  Apagado programado en " });
+                append_mmss(&mut buf, &mut pos, n as u32);
+                append_str(&mut buf, &mut pos, b" (cuenta atras en la barra de estado)");
+                t.write_bytes(&buf[..pos], LineColor::Warning);
+                t.write_line("  Cancelar: Esc o 'poweroff cancel'", LineColor::Info);
+            }
+            _ => t.write_line("  Uso: poweroff [--timer <seg 1-86400> | cancel]", LineColor::Warning),
+        },
+    }
+}
+
+/// Sincroniza los discos y apaga por ACPI.
+pub fn shutdown_now(t: &mut Terminal) -> ! {
+    t.write_line("  Sincronizando discos...", LineColor::Info);
+    if !super::disk::sync_disks(t) {
+        t.write_line("  Se apaga igualmente.", LineColor::Warning);
+    }
+    t.write_line("  Apagando el sistema...", LineColor::Warning);
+    crate::drivers::bus::acpi::poweroff();
+}
+
+/// Sincroniza los discos y reinicia.
+pub fn reboot_now(t: &mut Terminal) -> ! {
+    t.write_line("  Sincronizando discos...", LineColor::Info);
+    if !super::disk::sync_disks(t) {
+        t.write_line("  Se reinicia igualmente.", LineColor::Warning);
+    }
+    t.write_line("  Reiniciando...", LineColor::Warning);
+    crate::drivers::bus::acpi::reboot();
+}
+
+/// Aviso en el terminal cuando vence un temporizador (lo llama main).
+pub fn timer_expired(t: &mut Terminal, tm: &crate::time::timer::Timer) {
+    let mut buf = [0u8; 80]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  [timer #");
+    append_u32(&mut buf, &mut pos, tm.id);
+    append_str(&mut buf, &mut pos, b"] Tiempo cumplido (");
+    append_mmss(&mut buf, &mut pos, tm.secs);
+    append_str(&mut buf, &mut pos, b")");
+    t.write_bytes(&buf[..pos], LineColor::Warning);
+}
+
+// ── sticky ────────────────────────────────────────────────────────────────────
+//
+//   sticky            Fija/desfija la última línea de salida
+//   sticky list       Lista las líneas fijadas
+//   sticky -d <n>     Desfija la línea n (1 = la de arriba)
+//   sticky clear      Desfija todas
+
+pub fn cmd_sticky(t: &mut Terminal, args: &[u8]) {
+    let args = trim(args);
+    let (sub, rest) = match args.iter().position(|&b| b == b' ') {
+        Some(sp) => (&args[..sp], trim(&args[sp + 1..])),
+        None     => (args, &b""[..]),
+    };
+    match sub {
+        b"" => {
+            let Some(line) = t.last_output_line() else {
+                t.write_line("  sticky: no hay ninguna linea que fijar", LineColor::Warning);
+                return;
+            };
+            match t.toggle_sticky(&line) {
+                Some(true)  => t.write_line("  Linea fijada en el tope.", LineColor::Success),
+                Some(false) => t.write_line("  Linea desfijada.", LineColor::Success),
+                None => {
+                    let mut buf = [0u8; 80]; let mut pos = 0;
+                    append_str(&mut buf, &mut pos, b"  sticky: maximo ");
+                    append_u32(&mut buf, &mut pos, STICKY_MAX as u32);
+                    append_str(&mut buf, &mut pos, b" lineas fijadas (usa 'sticky -d <n>')");
+                    t.write_bytes(&buf[..pos], LineColor::Error);
+                }
+            }
+        }
+        b"list" | b"ls" => {
+            if t.sticky_count == 0 {
+                t.write_line("  (sin lineas fijadas)", LineColor::Normal); return;
+            }
+            for i in 0..t.sticky_count {
+                let line = t.sticky[i];
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  ");
+                append_u32(&mut buf, &mut pos, (i + 1) as u32);
+                append_str(&mut buf, &mut pos, b"  ");
+                append_str(&mut buf, &mut pos, &line.buf[..line.len]);
+                t.write_bytes(&buf[..pos], LineColor::Normal);
+            }
+        }
+        b"-d" => {
+            let n = parse_u64(rest).unwrap_or(0) as usize;
+            if n == 0 || !t.unpin(n - 1) {
+                t.write_line("  sticky: indice fuera de rango (ver 'sticky list')", LineColor::Error);
+            } else {
+                t.write_line("  Linea desfijada.", LineColor::Success);
+            }
+        }
+        b"clear" => {
+            t.clear_sticky();
+            t.write_line("  Lineas fijadas eliminadas.", LineColor::Success);
+        }
+        _ => t.write_line("  Uso: sticky [list | -d <n> | clear]", LineColor::Warning),
+    }
+}
+
+// ── split ─────────────────────────────────────────────────────────────────────
+//
+//   split             Divide/une la vista (congela arriba la salida actual)
+//   split on | off    Forzar un estado
+
+pub fn cmd_split(t: &mut Terminal, args: &[u8]) {
+    let open = match trim(args) {
+        b""    => !t.split_active(),
+        b"on"  => true,
+        b"off" => false,
+        _ => {
+            t.write_line("  Uso: split [on | off]", LineColor::Warning);
+            return;
+        }
+    };
+    if !open {
+        t.close_split();
+        t.write_line("  Vista unica.", LineColor::Success);
+        return;
+    }
+    // Congelar todo lo anterior al eco de este comando
+    if !t.split_active() { t.open_split(t.line_count.saturating_sub(1)); }
+    t.write_line("  Vista dividida: arriba historial congelado (rueda o Ctrl+RePag/AvPag).",
+                 LineColor::Success);
+    t.write_line("  'split off' vuelve a la vista unica.", LineColor::Info);
+}
+
+// ── present-mode ──────────────────────────────────────────────────────────────
+//
+//   present-mode              Estado actual
+//   present-mode on | off     Fuente x2 en la pestaña Terminal (proyector)
+//
+// Las columnas efectivas bajan a la mitad; las líneas ya escritas no se
+// recortan, se parten en varias filas al dibujarlas.
+
+/// Columnas de historial que caben en pantalla con o sin modo presentación.
+fn present_cols(on: bool) -> usize {
+    use crate::graphics::driver::framebuffer::{boot_mode, Layout};
+    use crate::ui::tabs::terminal::{hist_max_chars, PRESENT_SCALE};
+    let (w, h, _, _) = boot_mode();
+    let lay = Layout::new(w, h);
+    hist_max_chars(&if on { lay.scaled(PRESENT_SCALE) } else { lay })
+}
+
+pub fn cmd_present(t: &mut Terminal, args: &[u8]) {
+    let on = match trim(args) {
+        b""    => t.present,
+        b"on"  => true,
+        b"off" => false,
+        _ => {
+            t.write_line("  Uso: present-mode [on | off]", LineColor::Warning);
+            return;
+        }
+    };
+    t.present = on;
+    t.scroll_to_bottom();
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, if on { b"  Modo presentacion: ON (fuente x2, " }
+                                   else  { b"  Modo presentacion: OFF (" });
+    append_u32(&mut buf, &mut pos, present_cols(on) as u32);
+    append_str(&mut buf, &mut pos, b" columnas)");
+    t.write_bytes(&buf[..pos], LineColor::Success);
+    if on {
+        t.write_line("  Las lineas mas anchas se parten en varias filas.", LineColor::Info);
+    }
+}
+
+// ── dmesg / scroll-sync ───────────────────────────────────────────────────────
+//
+//   dmesg                    Volcar el ring de log del kernel al terminal
+//   dmesg -w                 Abrir el visor que sigue las entradas nuevas
+//   dmesg -l <nivel>         Solo entradas de ese nivel o más graves
+//                            (debug, info, ok, warn, error)
+//   scroll-sync [on | off]   Auto-scroll con el que se abre ese visor (F lo
+//                            cambia dentro del visor)
+
+fn logview_rows() -> usize {
+    use crate::graphics::driver::framebuffer::{boot_mode, Layout};
+    let (w, h, _, _) = boot_mode();
+    crate::console::terminal::logview::logview_visible_rows(&Layout::new(w, h))
+}
+
+pub fn cmd_dmesg(t: &mut Terminal, args: &[u8]) {
+    use crate::console::terminal::logview::{format_entry, LogViewState};
+    use crate::drivers::serial::{self, Level};
+
+    let args = trim(args);
+    let min = match args {
+        b"" => Level::Debug,
+        b"-w" => {
+            t.logview = Some(LogViewState::new(t.log_follow, logview_rows()));
+            return;
+        }
+        _ => match args.strip_prefix(b"-l").map(trim).and_then(Level::parse) {
+            Some(l) => l,
+            None => return t.write_line("  Uso: dmesg [-w | -l debug|info|ok|warn|error]", LineColor::Warning),
+        },
+    };
+    let (oldest, end) = (serial::log_oldest(), serial::log_seq());
+    if oldest == end {
+        t.write_line("  (log vacio)", LineColor::Info);
+        return;
+    }
+    if oldest > 0 {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  (");
+        append_u32(&mut buf, &mut pos, oldest as u32);
+        append_str(&mut buf, &mut pos, b" entradas antiguas ya descartadas del ring)");
+        t.write_bytes(&buf[..pos], LineColor::Info);
+    }
+    for seq in oldest..end {
+        let Some(e) = serial::log_entry(seq) else { continue };
+        if e.level < min { continue; }
+        let mut buf = [0u8; TERM_COLS];
+        buf[..2].copy_from_slice(b"  ");
+        let n = 2 + format_entry(&e, &mut buf[2..]);
+        // Los errores del log no hacen fallar a `dmesg`: van en amarillo
+        let color = match e.level {
+            Level::Ok                  => LineColor::Success,
+            Level::Warn | Level::Error => LineColor::Warning,
+            _                          => LineColor::Normal,
+        };
+        t.write_bytes(&buf[..n], color);
+    }
+}
+
+pub fn cmd_scroll_sync(t: &mut Terminal, args: &[u8]) {
+    match trim(args) {
+        b""    => {}
+        b"on"  => t.log_follow = true,
+        b"off" => t.log_follow = false,
+        _ => return t.write_line("  Uso: scroll-sync [on | off]", LineColor::Warning),
+    }
+    t.write_line(if t.log_follow { "  Auto-scroll del visor de log (dmesg -w): ON" }
+                 else            { "  Auto-scroll del visor de log (dmesg -w): OFF" },
+                 LineColor::Success);
+}
+
+// ── mouse ─────────────────────────────────────────────────────────────────────
+//
+// `mouse speed <n>` y `mouse accel on|off` cambian lo mismo que
+// `config set mouse.sensitivity|mouse.accel` (sin guardarlo en el archivo).
+
+pub fn cmd_mouse(t: &mut Terminal, args: &[u8]) {
+    use crate::drivers::input::mouse;
+    let args = trim(args);
+    let (sub, val) = match args.iter().position(|&b| b == b' ') {
+        Some(i) => (&args[..i], trim(&args[i + 1..])),
+        None    => (args, &b""[..]),
+    };
+    match (sub, val) {
+        (b"", _) => {}
+        (b"speed" | b"velocidad", v) => match parse_u64(v) {
+            Some(n) if (mouse::SENSITIVITY_MIN as u64..=mouse::SENSITIVITY_MAX as u64).contains(&n) =>
+                mouse::set_sensitivity(n as i32),
+            _ => return t.write_line("  Error: velocidad invalida (1-8)", LineColor::Error),
+        },
+        (b"accel", b"on")  => mouse::set_accel(true),
+        (b"accel", b"off") => mouse::set_accel(false),
+        _ => return t.write_line("  Uso: mouse [speed <1-8> | accel on|off]", LineColor::Warning),
+    }
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Raton: velocidad ");
+    append_u32(&mut buf, &mut pos, mouse::sensitivity() as u32);
+    append_str(&mut buf, &mut pos, b"  aceleracion ");
+    append_str(&mut buf, &mut pos, if mouse::accel() { b"ON (x1 lento .. x3 rapido)" } else { b"OFF (1:1)" });
+    t.write_bytes(&buf[..pos], if args.is_empty() { LineColor::Info } else { LineColor::Success });
+}
+
+// ── banner-set / banner-clear ─────────────────────────────────────────────────
+//
+// `banner-set <comando>` ejecuta el comando y convierte su salida (máx.
+// BANNER_MAX líneas) en la cabecera fija del terminal, p. ej. `banner-set motd`
+// o `banner-set banner PORTIX`. La salida no queda en el historial.
+
+pub fn cmd_banner_set(
+    t:    &mut Terminal,
+    args: &[u8],
+    hw:   &crate::arch::hardware::HardwareInfo,
+    pci:  &crate::drivers::bus::pci::PciBus,
+) {
+    let args = trim(args);
+    if args.is_empty() {
+        t.write_line("  Uso: banner-set <comando>   (p. ej. banner-set motd)", LineColor::Warning);
+        if t.banner_count > 0 {
+            let mut buf = [0u8; 64]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  Banner actual: ");
+            append_u32(&mut buf, &mut pos, t.banner_count as u32);
+            append_str(&mut buf, &mut pos, b" lineas");
+            t.write_bytes(&buf[..pos], LineColor::Normal);
+        }
+        return;
+    }
+
+    let from = t.line_count;
+    if !t.run_command(args, hw, pci) {
+        // Dejar visible el error del comando y no tocar el banner actual
+        t.write_line("  banner-set: el comando fallo; banner sin cambios", LineColor::Error);
+        return;
+    }
+    let total = t.line_count - from;
+    let n = t.capture_banner(from);
+    if n == 0 {
+        t.write_line("  banner-set: el comando no produjo salida", LineColor::Warning);
+        return;
+    }
+    let l = args.len().min(t.banner_cmd.len());
+    t.banner_cmd[..l].copy_from_slice(&args[..l]);
+    t.banner_cmd_len = l;
+    let mut buf = [0u8; 80]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Banner fijado (");
+    append_u32(&mut buf, &mut pos, n as u32);
+    append_str(&mut buf, &mut pos, b" lineas)");
+    if total > BANNER_MAX {
+        append_str(&mut buf, &mut pos, b" - salida recortada a ");
+        append_u32(&mut buf, &mut pos, BANNER_MAX as u32);
+    }
+    t.write_bytes(&buf[..pos], LineColor::Success);
+}
+
+pub fn cmd_banner_clear(t: &mut Terminal) {
+    if t.banner_count == 0 {
+        t.write_line("  (no hay banner definido)", LineColor::Normal); return;
+    }
+    t.clear_banner();
+    t.write_line("  Banner eliminado.", LineColor::Success);
+}
+
+// ── remote — sesión remota por COM1 ──────────────────────────────────────────
+//
+//   remote [status]   Muestra si hay una sesión remota activa
+//   remote on         Acepta entrada por COM1 y espeja allí la salida
+//   remote off        Vuelve a entrada solo local
+
+pub fn cmd_remote(t: &mut Terminal, args: &[u8]) {
+    match trim(args) {
+        b"" | b"status" => {
+            if t.remote_active() {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                append_str(&mut buf, &mut pos, b"  Sesion remota ACTIVA en COM1 (");
+                append_u32(&mut buf, &mut pos, crate::drivers::serial::baud());
+                append_str(&mut buf, &mut pos, b" 8N1)");
+                t.write_bytes(&buf[..pos], LineColor::Success);
+            } else {
+                t.write_line("  Sesion remota inactiva (usa 'remote on')", LineColor::Normal);
+            }
+        }
+        b"on" => {
+            if t.remote_active() {
+                t.write_line("  remote: la sesion ya esta activa", LineColor::Warning);
+            } else if !t.set_remote(true) {
+                t.write_line("  remote: COM1 no disponible (fallo el loopback test)", LineColor::Error);
+            } else {
+                crate::drivers::serial::write_str("\n*** PORTIX: sesion remota iniciada ***\n");
+                t.write_line("  Sesion remota iniciada en COM1: entrada y salida espejadas.", LineColor::Success);
+            }
+        }
+        b"off" => {
+            if !t.remote_active() {
+                t.write_line("  remote: no hay ninguna sesion activa", LineColor::Warning);
+                return;
+            }
+            t.write_line("  Sesion remota finalizada.", LineColor::Success);
+            t.set_remote(false);
+        }
+        _ => t.write_line("  Uso: remote [on | off | status]", LineColor::Warning),
+    }
+}
+
+// ── serial — parámetros de COM1 ──────────────────────────────────────────────
+//
+//   serial [status]    Estado del puerto y baud rate actual
+//   serial baud <n>    Reprogramar el divisor del UART (115200 / n) en caliente
+//
+// El cambio no se guarda; para conservarlo: config set serial.baud <n>.
+
+pub fn cmd_serial(t: &mut Terminal, args: &[u8]) {
+    use crate::drivers::serial;
+    let args = trim(args);
+    let (sub, val) = match args.iter().position(|&b| b == b' ') {
+        Some(i) => (&args[..i], trim(&args[i + 1..])),
+        None    => (args, &b""[..]),
+    };
+    match (sub, val) {
+        (b"" | b"status", b"") => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  COM1: ");
+            append_str(&mut buf, &mut pos, if serial::is_ready() { b"listo" } else { b"no disponible" });
+            append_str(&mut buf, &mut pos, b"  ");
+            append_u32(&mut buf, &mut pos, serial::baud());
+            append_str(&mut buf, &mut pos, b" baud 8N1");
+            t.write_bytes(&buf[..pos], LineColor::Info);
+        }
+        (b"baud", v) if !v.is_empty() => {
+            let Some(baud) = parse_u64(v).filter(|&b| b <= u32::MAX as u64) else {
+                return t.write_line("  serial: baud invalido", LineColor::Error);
+            };
+            match serial::set_baud(baud as u32) {
+                Ok(real) => {
+                    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                    append_str(&mut buf, &mut pos, b"  COM1 reconfigurado a ");
+                    append_u32(&mut buf, &mut pos, real);
+                    append_str(&mut buf, &mut pos, b" baud 8N1");
+                    if real as u64 != baud {
+                        append_str(&mut buf, &mut pos, b" (el mas cercano alcanzable)");
+                    }
+                    t.write_bytes(&buf[..pos], LineColor::Success);
+                }
+                Err(e) => {
+                    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                    append_str(&mut buf, &mut pos, b"  serial: ");
+                    append_str(&mut buf, &mut pos, e.as_bytes());
+                    t.write_bytes(&buf[..pos], LineColor::Error);
+                }
+            }
+        }
+        _ => t.write_line("  Uso: serial [status | baud <n>]  (p. ej. 9600, 38400, 115200)", LineColor::Warning),
+    }
+}
+
+// ── parts — tabla de particiones MBR ──────────────────────────────────────────
+//
+//   parts [drive]    drive 0-3 (0 = Primary Master, por defecto)
+//
+// Lee el LBA 0 y muestra las 4 entradas primarias (16 B cada una desde 0x1BE):
+//   +0 estado (0x80 = arranque) · +4 tipo · +8 LBA inicio · +12 nº sectores
+
+fn part_type_name(ptype: u8) -> &'static [u8] {
+    match ptype {
+        0x01               => b"FAT12",
+        0x04 | 0x06 | 0x0E => b"FAT16",
+        0x05 | 0x0F        => b"Extendida",
+        0x07               => b"NTFS/exFAT",
+        0x0B | 0x0C        => b"FAT32",
+        0x82               => b"Linux swap",
+        0x83               => b"Linux",
+        0xEE               => b"GPT protect",
+        0xEF               => b"EFI",
+        _                  => b"Desconocido",
+    }
+}
+
+pub fn cmd_parts(t: &mut Terminal, args: &[u8]) {
+    use crate::drivers::storage::ata::{AtaBus, AtaDrive, DriveId, get_cached_drive_info};
+
+    let a = trim(args);
+    let drv = if a.is_empty() { Some(0) } else { parse_u64(a).filter(|&d| d <= 3) };
+    let Some(drv) = drv else {
+        t.write_line("  Uso: parts [drive]   (drive 0-3)", LineColor::Warning);
+        return;
+    };
+    let id = match drv {
+        1 => DriveId::Primary1,
+        2 => DriveId::Secondary0,
+        3 => DriveId::Secondary1,
+        _ => DriveId::Primary0,
+    };
+    // Primary0 usa el DriveInfo cacheado en boot; el resto, scan puntual
+    let info = if drv == 0 { get_cached_drive_info() } else { AtaBus::scan().info(id).copied() };
+    let Some(info) = info else {
+        t.write_line("  parts: drive no detectado", LineColor::Error);
+        return;
+    };
+
+    let mut mbr = [0u8; 512];
+    if AtaDrive::from_info(info).read_sectors(0, 1, &mut mbr).is_err() {
+        t.write_line("  parts: error leyendo el MBR (LBA 0)", LineColor::Error);
+        return;
+    }
+
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"TABLA DE PARTICIONES - DRIVE ");
+        append_u32(&mut buf, &mut pos, drv as u32);
+        t.separador(core::str::from_utf8(&buf[..pos]).unwrap_or(""));
+    }
+    let Some(entries) = crate::drivers::storage::fsprobe::parse_mbr(&mbr) else {
+        t.write_line("  Sin firma MBR 0x55AA: el disco no tiene tabla de particiones.", LineColor::Warning);
+        t.write_empty();
+        return;
+    };
+
+    t.write_line("  #  Boot  Tipo                LBA inicio    Sectores      Tamano", LineColor::Header);
+    let mut used = 0;
+    for (i, e) in entries.iter().enumerate() {
+        let (boot, ptype, lba, size) = (e.boot, e.ptype, e.lba, e.sectors);
+
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_u32(&mut buf, &mut pos, (i + 1) as u32);
+        if ptype == 0x00 {
+            append_str(&mut buf, &mut pos, b"  -     (vacia)");
+            t.write_bytes(&buf[..pos], LineColor::Normal);
+            continue;
+        }
+        used += 1;
+
+        append_str(&mut buf, &mut pos, match boot {
+            0x80 => b"  *     ",
+            0x00 => b"        ",
+            _    => b"  ?     ",   // valor no válido: ni 0x00 ni 0x80
+        });
+        let col0 = pos;
+        append_str(&mut buf, &mut pos, part_type_name(ptype));
+        append_str(&mut buf, &mut pos, b" (0x");
+        append_hex8_byte(&mut buf, &mut pos, ptype);
+        append_str(&mut buf, &mut pos, b")");
+        while pos < col0 + 20 { buf[pos] = b' '; pos += 1; }
+        let col1 = pos;
+        append_u32(&mut buf, &mut pos, lba);
+        while pos < col1 + 14 { buf[pos] = b' '; pos += 1; }
+        let col2 = pos;
+        append_u32(&mut buf, &mut pos, size);
+        while pos < col2 + 14 { buf[pos] = b' '; pos += 1; }
+        append_mib(&mut buf, &mut pos, size as u64 / 2048);
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+
+        if e.is_extended() {
+            t.write_line("           -> contiene particiones logicas (EBR en su primer sector)", LineColor::Info);
+        }
+    }
+    if used == 0 {
+        t.write_line("  MBR valido pero sin particiones definidas.", LineColor::Warning);
+    }
+    t.write_empty();
+}
+
 // ── Hardware: cpu, mem, disks, pci ───────────────────────────────────────────
 
 pub fn cmd_info(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
@@ -205,6 +1200,11 @@ pub fn cmd_cpu(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
             append_str(&mut buf, &mut pos, b"  base ");
             append_mhz(&mut buf, &mut pos, hw.cpu.base_mhz);
         }
+        if hw.cpu.tsc_mhz > 0 {
+            append_str(&mut buf, &mut pos, b"  TSC ");
+            append_mhz(&mut buf, &mut pos, hw.cpu.tsc_mhz);
+            if !hw.cpu.tsc_invariant { append_str(&mut buf, &mut pos, b" (no invariante)"); }
+        }
         t.write_bytes(&buf[..pos], LineColor::Normal);
     }
     {
@@ -259,16 +1259,168 @@ pub fn cmd_mem(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
     t.write_empty();
 }
 
-pub fn cmd_disks(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
-    t.separador("ALMACENAMIENTO (ATA)");
+/// Tamaño en KiB, o en MiB a partir de 1 MiB.
+fn append_size(buf: &mut [u8], pos: &mut usize, bytes: usize) {
+    if bytes >= 1024 * 1024 && bytes.is_multiple_of(1024 * 1024) {
+        append_u32(buf, pos, (bytes >> 20) as u32);
+        append_str(buf, pos, b" MiB");
+    } else {
+        append_u32(buf, pos, (bytes >> 10) as u32);
+        append_str(buf, pos, b" KiB");
+    }
+}
+
+// ── free ──────────────────────────────────────────────────────────────────────
+//
+// RAM total (E820) frente al heap: lo usado, lo libre y lo que queda fuera
+// del heap (kernel y RAM que el buddy no gestiona). Detalle en `heap`.
+
+const FREE_BAR_W: usize = 40;
+
+pub fn cmd_free(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
+    let m = crate::mem::mem_summary(hw.ram.usable_or_default() * 1024 * 1024);
+    t.separador("MEMORIA: USADA / LIBRE");
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Total detectado : ");
+        append_mib(&mut buf, &mut pos, m.total >> 20);
+        append_str(&mut buf, &mut pos, b"  (E820 utilizable)");
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    if !m.heap_ready() {
+        t.write_line("  Heap no inicializado: no hay uso ni memoria libre que medir.", LineColor::Warning);
+        t.write_empty();
+        return;
+    }
+    let pct = m.heap_used_pct();
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Usado por heap  : ");
+        append_size(&mut buf, &mut pos, m.heap_used as usize);
+        append_str(&mut buf, &mut pos, b" de ");
+        append_size(&mut buf, &mut pos, m.heap_size as usize);
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Libre           : ");
+        append_size(&mut buf, &mut pos, m.heap_free as usize);
+        t.write_bytes(&buf[..pos], LineColor::Success);
+    }
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Fuera del heap  : ");
+        append_size(&mut buf, &mut pos, m.unmanaged as usize);
+        append_str(&mut buf, &mut pos, b"  (kernel + RAM no gestionada)");
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Heap ");
+        append_bar(&mut buf, &mut pos, pct, FREE_BAR_W);
+        append_str(&mut buf, &mut pos, b" ");
+        append_u32(&mut buf, &mut pos, pct);
+        append_str(&mut buf, &mut pos, b"% en uso");
+        t.write_bytes(&buf[..pos], if pct >= 90 { LineColor::Warning } else { LineColor::Info });
+    }
+    t.write_empty();
+}
+
+pub fn cmd_heap(t: &mut Terminal) {
+    use crate::mem::{self, allocator::ALLOC_STATS, MIN_ORDER};
+    use core::sync::atomic::Ordering;
+
+    t.separador("HEAP DEL KERNEL (BUDDY)");
+    let u = mem::heap_usage();
+    if u.size == 0 {
+        t.write_line("  Heap no inicializado: no hay RAM utilizable en el mapa E820", LineColor::Error);
+        t.write_line("  por encima del kernel; toda asignacion devuelve null.", LineColor::Error);
+        t.write_empty();
+        return;
+    }
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Region       : 0x");
+        append_hex64_full(&mut buf, &mut pos, u.start as u64);
+        append_str(&mut buf, &mut pos, b" - 0x");
+        append_hex64_full(&mut buf, &mut pos, (u.start + u.size) as u64);
+        append_str(&mut buf, &mut pos, b"  (");
+        append_size(&mut buf, &mut pos, u.size);
+        append_str(&mut buf, &mut pos, b")");
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    {
+        let pct = (u.used() as u64 * 100 / u.size as u64) as u32;
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  En uso       : ");
+        append_size(&mut buf, &mut pos, u.used());
+        append_str(&mut buf, &mut pos, b" (");
+        append_u32(&mut buf, &mut pos, pct);
+        append_str(&mut buf, &mut pos, b"%)   Libre: ");
+        append_size(&mut buf, &mut pos, u.free);
+        t.write_bytes(&buf[..pos], if pct >= 90 { LineColor::Warning } else { LineColor::Normal });
+    }
+    {
+        let frag = u.fragmentation_pct();
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Mayor libre  : ");
+        append_size(&mut buf, &mut pos, u.largest_free);
+        append_str(&mut buf, &mut pos, b"   Fragmentacion: ");
+        append_u32(&mut buf, &mut pos, frag);
+        append_str(&mut buf, &mut pos, b"%");
+        t.write_bytes(&buf[..pos], if frag >= 50 { LineColor::Warning } else { LineColor::Normal });
+    }
+    {
+        let failed = ALLOC_STATS.failed_allocs.load(Ordering::Relaxed) as u32;
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  Asignaciones : ");
+        append_u32(&mut buf, &mut pos, ALLOC_STATS.total_allocs.load(Ordering::Relaxed) as u32);
+        append_str(&mut buf, &mut pos, b"   Liberaciones: ");
+        append_u32(&mut buf, &mut pos, ALLOC_STATS.total_frees.load(Ordering::Relaxed) as u32);
+        append_str(&mut buf, &mut pos, b"   Fallidas: ");
+        append_u32(&mut buf, &mut pos, failed);
+        t.write_bytes(&buf[..pos], if failed > 0 { LineColor::Warning } else { LineColor::Normal });
+    }
+    t.write_empty();
+    t.write_line("  Bloques libres por tamano:", LineColor::Info);
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    for (i, &n) in u.free_blocks.iter().enumerate() {
+        if n == 0 { continue; }
+        if pos > TERM_COLS - 20 {
+            t.write_bytes(&buf[..pos], LineColor::Normal);
+            pos = 0;
+        }
+        append_str(&mut buf, &mut pos, b"    ");
+        let sz = 1usize << (MIN_ORDER + i);
+        if sz < 1024 {
+            append_u32(&mut buf, &mut pos, sz as u32);
+            append_str(&mut buf, &mut pos, b" B");
+        } else {
+            append_size(&mut buf, &mut pos, sz);
+        }
+        append_str(&mut buf, &mut pos, b" x");
+        append_u32(&mut buf, &mut pos, n as u32);
+    }
+    if pos > 0 { t.write_bytes(&buf[..pos], LineColor::Normal); }
+    t.write_empty();
+}
+
+pub fn cmd_disks(t: &mut Terminal, args: &[u8], hw: &crate::arch::hardware::HardwareInfo) {
+    match trim(args) {
+        b"" => {}
+        b"-f" => return cmd_disks_fs(t, hw),
+        _ => return t.write_line("  Uso: lsblk [-f]", LineColor::Warning),
+    }
+    t.separador("ALMACENAMIENTO (ATA / AHCI)");
     if hw.disks.count == 0 {
-        t.write_line("  No se detectaron unidades ATA.", LineColor::Warning);
+        t.write_line("  No se detectaron unidades ATA ni AHCI.", LineColor::Warning);
         t.write_empty(); return;
     }
     for i in 0..hw.disks.count {
         let d   = &hw.disks.drives[i];
-        let bus = if d.bus   == 0 { b"ATA0" as &[u8] } else { b"ATA1" };
-        let drv = if d.drive == 0 { b"Maestro" as &[u8] } else { b"Esclavo" };
+        let mut lb = [0u8; 8];
+        let bus = d.bus_label(&mut lb).as_bytes();
+        let drv = if d.ahci { b"AHCI" as &[u8] } else if d.drive == 0 { b"Maestro" } else { b"Esclavo" };
         let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
         append_str(&mut buf, &mut pos, b"  [");
         buf[pos..pos + bus.len()].copy_from_slice(bus); pos += bus.len();
@@ -287,7 +1439,402 @@ pub fn cmd_disks(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
     t.write_empty();
 }
 
-pub fn cmd_pci(t: &mut Terminal, pci: &crate::drivers::bus::pci::PciBus) {
+// ── lsblk -f — sistema de archivos de cada disco y partición ─────────────────
+//
+// Solo se leen las unidades ATA (PIO); las ópticas y las de AHCI se listan
+// sin FS. Si el sector 0 ya es un boot sector el disco es un superfloppy
+// (sin tabla); si no, se prueba cada partición primaria del MBR.
+
+/// Columnas de `lsblk -f`: dispositivo, tipo, FS, tamaño.
+const FS_COLS: [usize; 3] = [15, 35, 49];
+
+fn fs_row(t: &mut Terminal, dev: &[u8], kind: &[u8], fs: &str, mib: Option<u64>) {
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, dev);
+    while pos < FS_COLS[0] { buf[pos] = b' '; pos += 1; }
+    append_str(&mut buf, &mut pos, kind);
+    while pos < FS_COLS[1] { buf[pos] = b' '; pos += 1; }
+    append_str(&mut buf, &mut pos, fs.as_bytes());
+    if let Some(mib) = mib {
+        while pos < FS_COLS[2] { buf[pos] = b' '; pos += 1; }
+        append_mib(&mut buf, &mut pos, mib);
+    }
+    let color = if fs == FsKind::Unknown.name() { LineColor::Warning } else { LineColor::Normal };
+    t.write_bytes(&buf[..pos], color);
+}
+
+fn cmd_disks_fs(t: &mut Terminal, hw: &crate::arch::hardware::HardwareInfo) {
+    use crate::drivers::storage::fsprobe::{self, MbrEntry};
+
+    t.separador("SISTEMAS DE ARCHIVOS");
+    if hw.disks.count == 0 {
+        t.write_line("  No se detectaron unidades ATA ni AHCI.", LineColor::Warning);
+        t.write_empty(); return;
+    }
+    t.write_line("  Dispositivo  Tipo                FS            Tamano", LineColor::Header);
+    for d in &hw.disks.drives[..hw.disks.count] {
+        let mut dev = [0u8; 16]; let mut dp = 0;
+        let mut lb = [0u8; 8];
+        append_str(&mut dev, &mut dp, b"  ");
+        append_str(&mut dev, &mut dp, d.bus_label(&mut lb).as_bytes());
+        if !d.ahci { append_str(&mut dev, &mut dp, if d.drive == 0 { b"-M" } else { b"-E" }); }
+
+        let Some(drive) = d.ata_drive() else {
+            let (kind, fs): (&[u8], _) = if d.is_atapi { (b"optico", "-") } else { (b"disco", "(AHCI: sin lectura)") };
+            fs_row(t, &dev[..dp], kind, fs, None);
+            continue;
+        };
+        let mut sec0 = [0u8; 512];
+        if drive.read_sectors(0, 1, &mut sec0).is_err() {
+            fs_row(t, &dev[..dp], b"disco", "(error leyendo LBA 0)", Some(d.size_mb));
+            continue;
+        }
+
+        let direct = fsprobe::detect_boot_sector(&sec0);
+        let table = if direct == FsKind::Unknown { fsprobe::parse_mbr(&sec0) } else { None };
+        let Some(entries) = table else {
+            // Superfloppy: FS en el sector 0 (o ext, con superbloque en el 2)
+            let fs = if direct != FsKind::Unknown { direct } else { fsprobe::probe_volume(&drive, 0) };
+            fs_row(t, &dev[..dp], b"disco (sin MBR)", fs.name(), Some(d.size_mb));
+            continue;
+        };
+        fs_row(t, &dev[..dp], b"disco (MBR)", "-", Some(d.size_mb));
+
+        let used: [Option<&MbrEntry>; 4] = core::array::from_fn(|n| Some(&entries[n]).filter(|e| !e.is_empty()));
+        let last = used.iter().rposition(|e| e.is_some());
+        for (n, e) in used.iter().enumerate() {
+            let Some(e) = e else { continue };
+            let mut pd = [0u8; 16]; let mut pp = 0;
+            append_str(&mut pd, &mut pp, if Some(n) == last { b"   `-part" } else { b"   |-part" });
+            append_u32(&mut pd, &mut pp, n as u32 + 1);
+            let mut kb = [0u8; 24]; let mut kp = 0;
+            append_str(&mut kb, &mut kp, part_type_name(e.ptype));
+            append_str(&mut kb, &mut kp, b" (");
+            append_hex8_byte(&mut kb, &mut kp, e.ptype);
+            append_str(&mut kb, &mut kp, b")");
+            let fs = if e.is_extended() || e.ptype == 0xEE {
+                "-"
+            } else {
+                fsprobe::probe_volume(&drive, e.lba as u64).name()
+            };
+            fs_row(t, &pd[..pp], &kb[..kp], fs, Some(e.sectors as u64 / 2048));
+        }
+        if last.is_none() {
+            t.write_line("     (MBR sin particiones definidas)", LineColor::Warning);
+        }
+    }
+    t.write_empty();
+}
+
+// ── acpi — tablas encontradas al arrancar ────────────────────────────────────
+
+pub fn cmd_acpi(t: &mut Terminal) {
+    use crate::drivers::bus::acpi;
+    let Some(info) = acpi::info() else {
+        t.write_line("  ACPI: no se encontro una RSDP valida (apagado por puertos fijos)", LineColor::Warning);
+        acpi_chains(t, None);
+        return;
+    };
+    t.separador("ACPI");
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  RSDP 0x");
+    append_hex64_short(&mut buf, &mut pos, info.rsdp);
+    append_str(&mut buf, &mut pos, b"  rev ");
+    append_u32(&mut buf, &mut pos, info.revision as u32);
+    append_str(&mut buf, &mut pos, b"  OEM '");
+    append_str(&mut buf, &mut pos, &info.oem);
+    append_str(&mut buf, &mut pos, b"'");
+    t.write_bytes(&buf[..pos], LineColor::Info);
+
+    t.write_line("  Tabla  Direccion         Longitud  Rev  Checksum", LineColor::Header);
+    for tb in &info.tables[..info.count] {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        for &b in &tb.sig { buf[pos] = if (32..127).contains(&b) { b } else { b'?' }; pos += 1; }
+        append_str(&mut buf, &mut pos, b"   0x");
+        append_hex64_short(&mut buf, &mut pos, tb.phys);
+        while pos < 27 { buf[pos] = b' '; pos += 1; }
+        append_u32(&mut buf, &mut pos, tb.len);
+        while pos < 37 { buf[pos] = b' '; pos += 1; }
+        append_u32(&mut buf, &mut pos, tb.rev as u32);
+        while pos < 42 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, if tb.checksum_ok { b"OK" } else { b"INVALIDO" });
+        t.write_bytes(&buf[..pos], if tb.checksum_ok { LineColor::Normal } else { LineColor::Warning });
+    }
+    if info.dropped > 0 {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  (+");
+        append_u32(&mut buf, &mut pos, info.dropped as u32);
+        append_str(&mut buf, &mut pos, b" tablas mas sin listar)");
+        t.write_bytes(&buf[..pos], LineColor::Info);
+    }
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  PM1a_CNT 0x");
+    append_hex64_short(&mut buf, &mut pos, info.pm1a_cnt as u64);
+    append_str(&mut buf, &mut pos, b"  PM1b_CNT ");
+    if info.pm1b_cnt != 0 {
+        append_str(&mut buf, &mut pos, b"0x");
+        append_hex64_short(&mut buf, &mut pos, info.pm1b_cnt as u64);
+    } else {
+        append_str(&mut buf, &mut pos, b"-");
+    }
+    append_str(&mut buf, &mut pos, b"  S5 ");
+    match info.s5 {
+        Some((a, b)) => {
+            append_str(&mut buf, &mut pos, b"SLP_TYPa=");
+            append_u32(&mut buf, &mut pos, a as u32);
+            append_str(&mut buf, &mut pos, b" SLP_TYPb=");
+            append_u32(&mut buf, &mut pos, b as u32);
+        }
+        None => append_str(&mut buf, &mut pos, b"no encontrado"),
+    }
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+    acpi_chains(t, Some(&info));
+}
+
+/// Métodos de apagado y reinicio en el orden en que se intentarán.
+fn acpi_chains(t: &mut Terminal, info: Option<&crate::drivers::bus::acpi::AcpiInfo>) {
+    use crate::drivers::bus::acpi;
+    let (off, n_off) = acpi::poweroff_chain(info);
+    let (rst, n_rst) = acpi::reset_chain(info);
+    let lines: [(&[u8], &mut dyn Iterator<Item = &str>); 2] = [
+        (b"  Apagado:   ", &mut off[..n_off].iter().map(|m| m.name())),
+        (b"  Reinicio:  ", &mut rst[..n_rst].iter().map(|m| m.name())),
+    ];
+    for (label, names) in lines {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, label);
+        for (i, name) in names.enumerate() {
+            if i > 0 { append_str(&mut buf, &mut pos, b" -> "); }
+            append_str(&mut buf, &mut pos, name.as_bytes());
+        }
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+}
+
+// ── lsmod — subsistemas del kernel ────────────────────────────────────────────
+//
+// Lee el registro central (drivers::registry). El estado de FAT32 se vuelve a
+// comprobar montando el volumen, para reflejar la situación actual y no solo
+// la del arranque.
+
+pub fn cmd_lsmod(t: &mut Terminal) {
+    use crate::drivers::registry::{self, State, Subsystem};
+    use crate::drivers::storage::{ata, fat32};
+
+    let drive = ata::get_cached_drive_info();
+    let vol = drive.and_then(|info| fat32::Fat32Volume::mount(ata::AtaDrive::from_info(info)).ok());
+    let mounted = vol.is_some();
+    registry::set_ok(Subsystem::Fat32, mounted);
+
+    t.separador("SUBSISTEMAS DEL KERNEL");
+    t.write_line("  Modulo     Version  Estado          Descripcion", LineColor::Info);
+    t.write_line("  ---------  -------  --------------  ----------------------------------", LineColor::Normal);
+    let mut active = 0u32;
+    for sub in registry::ALL {
+        let st = registry::state(sub);
+        if st == State::Active { active += 1; }
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_str(&mut buf, &mut pos, sub.name().as_bytes());
+        pad_to(&mut buf, &mut pos, 13);
+        append_str(&mut buf, &mut pos, sub.version().as_bytes());
+        pad_to(&mut buf, &mut pos, 22);
+        append_str(&mut buf, &mut pos, st.label().as_bytes());
+        pad_to(&mut buf, &mut pos, 38);
+        append_str(&mut buf, &mut pos, sub.desc().as_bytes());
+        let col = match st {
+            State::Active   => LineColor::Success,
+            State::Inactive => LineColor::Normal,
+            _               => LineColor::Warning,
+        };
+        t.write_bytes(&buf[..pos], col);
+    }
+    t.write_empty();
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Driver de disco: ");
+    match drive {
+        Some(info) => {
+            // La caché de arranque solo guarda Primary Master
+            append_str(&mut buf, &mut pos, b"ATA PIO (Primary Master, ");
+            append_str(&mut buf, &mut pos, info.model_str().as_bytes());
+            append_str(&mut buf, &mut pos, b")");
+        }
+        None => append_str(&mut buf, &mut pos, b"ninguno"),
+    }
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  FAT32: ");
+    if let Some(vol) = &vol {
+        append_str(&mut buf, &mut pos, b"montado");
+        append_str(&mut buf, &mut pos, if vol.is_read_only() { b" (ro)" } else { b" (rw)" });
+    } else {
+        append_str(&mut buf, &mut pos, b"no disponible");
+    }
+    t.write_bytes(&buf[..pos], LineColor::Normal);
+
+    let mut buf = [0u8; 64]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  ");
+    append_u32(&mut buf, &mut pos, active);
+    append_str(&mut buf, &mut pos, b" de ");
+    append_u32(&mut buf, &mut pos, registry::ALL.len() as u32);
+    append_str(&mut buf, &mut pos, b" subsistemas activos");
+    t.write_bytes(&buf[..pos], LineColor::Info);
+    t.write_empty();
+}
+
+/// Rellena con espacios hasta la columna `col` (al menos un espacio).
+fn pad_to(buf: &mut [u8], pos: &mut usize, col: usize) {
+    append_str(buf, pos, b" ");
+    while *pos < col && *pos < buf.len() { buf[*pos] = b' '; *pos += 1; }
+}
+
+/// `B:D.F` tal como lo muestra el listado: bus decimal, dispositivo en
+/// hexadecimal y función decimal.
+fn parse_bdf(s: &[u8]) -> Option<(u8, u8, u8)> {
+    let s = trim(s);
+    let s = s.strip_prefix(b"[").unwrap_or(s);
+    let s = s.strip_suffix(b"]").unwrap_or(s);
+    let colon = s.iter().position(|&b| b == b':')?;
+    let dot = s.iter().rposition(|&b| b == b'.')?;
+    if dot < colon { return None; }
+    let bus = parse_u64(&s[..colon])?;
+    let dev = parse_hex_raw(&s[colon + 1..dot])?;
+    let func = parse_u64(&s[dot + 1..])?;
+    if bus > 255 || dev > 31 || func > 7 { return None; }
+    Some((bus as u8, dev as u8, func as u8))
+}
+
+fn append_bar_size(buf: &mut [u8], pos: &mut usize, bytes: u64) {
+    let (v, unit): (u64, &[u8]) = if bytes >= 1 << 30 { (bytes >> 30, b" GiB") }
+                                  else if bytes >= 1 << 20 { (bytes >> 20, b" MiB") }
+                                  else if bytes >= 1 << 10 { (bytes >> 10, b" KiB") }
+                                  else { (bytes, b" B") };
+    append_u32(buf, pos, v as u32);
+    append_str(buf, pos, unit);
+}
+
+/// Fabricante, o su ID en hex si no está en la tabla.
+fn append_pci_vendor(buf: &mut [u8], pos: &mut usize, d: &crate::drivers::bus::pci::PciDevice) {
+    match d.vendor_name() {
+        Some(n) => append_str(buf, pos, n.as_bytes()),
+        None => {
+            append_str(buf, pos, b"Vendor ");
+            append_hex16(buf, pos, d.vendor_id);
+        }
+    }
+}
+
+/// Nombre de la clase, o `Clase CC:SS.PP` en hex si no está en la tabla.
+fn append_pci_class(buf: &mut [u8], pos: &mut usize, d: &crate::drivers::bus::pci::PciDevice) {
+    match d.class_name() {
+        Some(n) => append_str(buf, pos, n.as_bytes()),
+        None => {
+            append_str(buf, pos, b"Clase ");
+            append_hex8_byte(buf, pos, d.class_code);
+            append_str(buf, pos, b":");
+            append_hex8_byte(buf, pos, d.subclass);
+            append_str(buf, pos, b".");
+            append_hex8_byte(buf, pos, d.prog_if);
+        }
+    }
+}
+
+/// `pci -v <B:D.F>`: volcado del espacio de configuración y BARs decodificados.
+fn cmd_pci_verbose(t: &mut Terminal, args: &[u8], pci: &crate::drivers::bus::pci::PciBus) {
+    use crate::drivers::bus::pci::{read_config, BarKind, CONFIG_SPACE};
+
+    let Some((b, d, f)) = parse_bdf(args) else {
+        t.write_line("  Uso: pci -v <bus:dev.fn>   (ej: pci -v 0:1F.2)", LineColor::Warning);
+        return;
+    };
+    let Some(dev) = pci.find(b, d, f) else {
+        t.write_line("  pci: no hay ningun dispositivo en esa direccion.", LineColor::Error);
+        return;
+    };
+
+    t.separador("ESPACIO DE CONFIGURACION PCI");
+    {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  [");
+        append_u32(&mut buf, &mut pos, b as u32);
+        append_str(&mut buf, &mut pos, b":");
+        append_hex8_byte(&mut buf, &mut pos, d);
+        append_str(&mut buf, &mut pos, b".");
+        append_u32(&mut buf, &mut pos, f as u32);
+        append_str(&mut buf, &mut pos, b"]  ");
+        append_hex16(&mut buf, &mut pos, dev.vendor_id);
+        append_str(&mut buf, &mut pos, b":");
+        append_hex16(&mut buf, &mut pos, dev.device_id);
+        append_str(&mut buf, &mut pos, b"  ");
+        append_pci_vendor(&mut buf, &mut pos, dev);
+        append_str(&mut buf, &mut pos, b" / ");
+        append_pci_class(&mut buf, &mut pos, dev);
+        t.write_bytes(&buf[..pos], LineColor::Info);
+    }
+    t.write_empty();
+
+    let mut cfg = [0u8; CONFIG_SPACE];
+    read_config(b, d, f, &mut cfg);
+    for (row, chunk) in cfg.chunks(16).enumerate() {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_hex8_byte(&mut buf, &mut pos, (row * 16) as u8);
+        append_str(&mut buf, &mut pos, b": ");
+        for (i, &v) in chunk.iter().enumerate() {
+            if i == 8 { append_str(&mut buf, &mut pos, b" "); }
+            append_hex8_byte(&mut buf, &mut pos, v);
+            append_str(&mut buf, &mut pos, b" ");
+        }
+        append_str(&mut buf, &mut pos, b" ");
+        for &v in chunk {
+            append_str(&mut buf, &mut pos, &[if (0x20..0x7F).contains(&v) { v } else { b'.' }]);
+        }
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    t.write_empty();
+
+    if dev.bar_count() == 0 {
+        t.write_line("  Cabecera sin BARs.", LineColor::Normal);
+        t.write_empty(); return;
+    }
+    t.write_line("  BAR  Tipo    Base                 Tamano     Flags", LineColor::Info);
+    t.write_line("  ---  ------  -------------------  ---------  -----", LineColor::Normal);
+    for (i, bar) in dev.bars[..dev.bar_count()].iter().enumerate() {
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ");
+        append_u32(&mut buf, &mut pos, i as u32);
+        append_str(&mut buf, &mut pos, b"    ");
+        append_str(&mut buf, &mut pos, bar.label().as_bytes());
+        if !bar.is_used() {
+            t.write_bytes(&buf[..pos], LineColor::Normal);
+            continue;
+        }
+        while pos < 13 { buf[pos] = b' '; pos += 1; }
+        append_str(&mut buf, &mut pos, b"0x");
+        if bar.kind == BarKind::Mem64 {
+            append_hex64_full(&mut buf, &mut pos, bar.base);
+        } else {
+            append_hex64_short(&mut buf, &mut pos, bar.base);
+        }
+        while pos < 34 { buf[pos] = b' '; pos += 1; }
+        append_bar_size(&mut buf, &mut pos, bar.size);
+        while pos < 45 { buf[pos] = b' '; pos += 1; }
+        if bar.prefetch { append_str(&mut buf, &mut pos, b"prefetch"); }
+        t.write_bytes(&buf[..pos], LineColor::Normal);
+    }
+    t.write_empty();
+}
+
+pub fn cmd_pci(t: &mut Terminal, args: &[u8], pci: &crate::drivers::bus::pci::PciBus) {
+    let args = trim(args);
+    if let Some(rest) = args.strip_prefix(b"-v") {
+        cmd_pci_verbose(t, rest, pci);
+        return;
+    }
     t.separador("BUS PCI");
     if pci.count == 0 {
         t.write_line("  No se encontraron dispositivos PCI.", LineColor::Warning);
@@ -301,8 +1848,8 @@ pub fn cmd_pci(t: &mut Terminal, pci: &crate::drivers::bus::pci::PciBus) {
         t.write_bytes(&buf[..pos], LineColor::Normal);
     }
     t.write_empty();
-    t.write_line("  [B:D.F]  VendorID:DevID  Fabricante           Clase", LineColor::Info);
-    t.write_line("  -------  --------------  -------------------  ---------", LineColor::Normal);
+    t.write_line("  [B:D.F]   Vend:Disp  Fabricante          Clase", LineColor::Info);
+    t.write_line("  --------  ---------  ------------------  --------------------", LineColor::Normal);
     for i in 0..pci.count.min(32) {
         let d = &pci.devices[i];
         let mut lb = [0u8; TERM_COLS]; let mut lp = 0;
@@ -316,12 +1863,11 @@ pub fn cmd_pci(t: &mut Terminal, pci: &crate::drivers::bus::pci::PciBus) {
         append_hex16(&mut lb, &mut lp, d.vendor_id);
         append_str(&mut lb, &mut lp, b":");
         append_hex16(&mut lb, &mut lp, d.device_id);
-        append_str(&mut lb, &mut lp, b"  ");
-        let vn = d.vendor_name().as_bytes();
-        lb[lp..lp + vn.len()].copy_from_slice(vn); lp += vn.len();
-        while lp < 56 { lb[lp] = b' '; lp += 1; }
-        let cn = d.class_name().as_bytes(); let cl = cn.len().min(20);
-        lb[lp..lp + cl].copy_from_slice(&cn[..cl]); lp += cl;
+        while lp < 23 { lb[lp] = b' '; lp += 1; }
+        append_pci_vendor(&mut lb, &mut lp, d);
+        lp = lp.min(41);
+        while lp < 43 { lb[lp] = b' '; lp += 1; }
+        append_pci_class(&mut lb, &mut lp, d);
         t.write_bytes(&lb[..lp], LineColor::Info);
     }
     t.write_empty();
@@ -364,8 +1910,19 @@ pub fn cmd_neofetch(
         }}
     }
 
-    iline!(b"SO       ", "PORTIX v0.7 bare-metal");
+    {
+        let mut v = [0u8; 32]; let mut vl = 0;
+        append_str(&mut v, &mut vl, b"PORTIX v");
+        append_str(&mut v, &mut vl, crate::VERSION.as_bytes());
+        append_str(&mut v, &mut vl, b" bare-metal");
+        iline!(b"SO       ", core::str::from_utf8(&v[..vl]).unwrap_or("PORTIX"));
+    }
     iline!(b"Arq      ", "x86_64");
+    { let mut buf = [0u8; 80]; let mut pos = 0;
+      append_str(&mut buf, &mut pos, b"  Host    : ");
+      append_str(&mut buf, &mut pos, hw.cpu.hypervisor.name().as_bytes());
+      if hw.cpu.hypervisor.is_hidden() { append_str(&mut buf, &mut pos, b" [oculto]"); }
+      il[n] = buf; ils[n] = pos; n += 1; }
     iline!(b"Kernel   ", "Rust nightly (no_std) + NASM");
     iline!(b"Video    ", "VESA LFB (doble buffer @ 0x600000)");
 
@@ -431,4 +1988,50 @@ pub fn cmd_neofetch(
         if cp > 0 { t.write_bytes(&combined[..cp], col); }
     }
     t.write_empty();
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn echo_args_without_flag_are_untouched() {
+        assert_eq!(split_echo_args(b"hola  mundo"), (None, &b"hola  mundo"[..]));
+        assert_eq!(split_echo_args(b""), (None, &b""[..]));
+        // `-cx` no es la opción: se imprime tal cual
+        assert_eq!(split_echo_args(b"-cerror hola"), (None, &b"-cerror hola"[..]));
+    }
+
+    #[test]
+    fn echo_args_split_color_and_keep_text_spaces() {
+        assert_eq!(split_echo_args(b"-c error fallo"), (Some(&b"error"[..]), &b"fallo"[..]));
+        // Espacios extra antes del color se saltan; tras él solo se consume uno
+        assert_eq!(split_echo_args(b"-c   info  a  b"), (Some(&b"info"[..]), &b" a  b"[..]));
+        assert_eq!(split_echo_args(b"-c warning"), (Some(&b"warning"[..]), &b""[..]));
+        // `-c` sin color → nombre vacío (cmd_echo muestra el uso)
+        assert_eq!(split_echo_args(b"-c"), (Some(&b""[..]), &b""[..]));
+        assert_eq!(split_echo_args(b"-c "), (Some(&b""[..]), &b""[..]));
+    }
+
+    #[test]
+    fn echo_color_names() {
+        assert_eq!(echo_color(b"success"), Some(LineColor::Success));
+        assert_eq!(echo_color(b"warning"), Some(LineColor::Warning));
+        assert_eq!(echo_color(b"error"),   Some(LineColor::Error));
+        assert_eq!(echo_color(b"info"),    Some(LineColor::Info));
+        assert_eq!(echo_color(b"header"),  Some(LineColor::Header));
+        assert_eq!(echo_color(b"normal"),  Some(LineColor::Normal));
+        assert_eq!(echo_color(b"prompt"),  None);
+        assert_eq!(echo_color(b"ERROR"),   None);
+        assert_eq!(echo_color(b""),        None);
+    }
+
+    #[test]
+    fn echo_error_color_does_not_fail_the_command() {
+        let mut t = alloc::boxed::Box::new(Terminal::new());
+        cmd_echo(&mut t, b"-c error  hola");
+        let l = *t.line_at(t.line_count - 1);
+        assert_eq!(&l.buf[..l.len], b" hola");
+        assert_eq!(l.color, LineColor::Error);
+        assert!(!t.cmd_failed);
+    }
+}
@@ -0,0 +1,188 @@
+// console/terminal/commands/watch.rs — PORTIX Kernel
+// `watch [-n <seg>] <comando>`: re-ejecuta un comando cada pocos segundos,
+// como `watch` de Unix. Útil con `uptime`, `mem`, `heap`...
+//
+// No bloquea el kernel: `cmd_watch` solo deja el estado en `Terminal::watch`
+// y el loop principal llama a `watch_poll` en cada vuelta. Cada refresco
+// descarta la salida anterior (`truncate_lines` hasta `base`) y la vista
+// empieza en la cabecera, como tras `clear --keep`, así que el historial
+// de antes sigue ahí al subir con el scroll y no se llena el ring buffer.
+// Esc o `q` (o cualquier byte por COM1) lo detienen.
+//
+// La salida de cada vuelta se recorta a WATCH_MAX_LINES: un comando largo
+// no puede empujar fuera del ring buffer el historial anterior al `watch`.
+
+use crate::console::terminal::{Terminal, LineColor, TERM_COLS, INPUT_MAX};
+use crate::console::terminal::fmt::*;
+use crate::time::pit::PIT_HZ;
+
+type Hw  = crate::arch::hardware::HardwareInfo;
+type Pci = crate::drivers::bus::pci::PciBus;
+
+const WATCH_SECS:      u64   = 1;
+const WATCH_MAX_SECS:  u64   = 3600;
+const WATCH_MAX_LINES: usize = 48;
+
+pub struct Watch {
+    cmd:      [u8; INPUT_MAX],
+    cmd_len:  usize,
+    secs:     u64,
+    next:     u64,
+    /// Primera línea lógica de la salida del `watch` (su cabecera).
+    base:     usize,
+    runs:     u32,
+}
+
+/// Separa `-n <seg>` del comando. Err con el mensaje si el intervalo no
+/// es válido o falta el comando.
+pub(crate) fn parse_watch_args(args: &[u8]) -> Result<(u64, &[u8]), &'static str> {
+    let args = trim(args);
+    if args == b"-n" { return Err("falta el intervalo"); }
+    let Some(rest) = args.strip_prefix(b"-n ") else {
+        return if args.is_empty() { Err("falta el comando") } else { Ok((WATCH_SECS, args)) };
+    };
+    let rest = trim(rest);
+    let sp   = rest.iter().position(|&b| b == b' ').unwrap_or(rest.len());
+    let secs = match parse_u64(&rest[..sp]) {
+        Some(s) if (1..=WATCH_MAX_SECS).contains(&s) => s,
+        _ => return Err("intervalo invalido (1-3600 segundos)"),
+    };
+    let cmd = trim(&rest[sp..]);
+    if cmd.is_empty() { return Err("falta el comando"); }
+    Ok((secs, cmd))
+}
+
+/// Comandos que no se pueden vigilar: toman la pantalla, esperan teclas o
+/// ya se repiten solos.
+fn unwatchable(cmd: &[u8]) -> bool {
+    super::is_interactive(cmd) || matches!(cmd,
+        b"watch" | b"vigilar" | b"regwatch" | b"waitkey" | b"pausa"
+        | b"source" | b"." | b"run" | b"dmesg" | b"log")
+}
+
+pub fn cmd_watch(t: &mut Terminal, args: &[u8]) {
+    let (secs, cmd) = match parse_watch_args(args) {
+        Ok(v)  => v,
+        Err(e) => {
+            let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+            append_str(&mut buf, &mut pos, b"  watch: ");
+            append_str(&mut buf, &mut pos, e.as_bytes());
+            t.write_bytes(&buf[..pos], LineColor::Error);
+            t.write_line("  Uso: watch [-n <seg>] <comando>   (Esc o q detiene)", LineColor::Warning);
+            return;
+        }
+    };
+    let first = cmd.split(|&b| b == b' ').next().unwrap_or(b"");
+    let first = t.aliases.get(first).and_then(|c| c.split(|&b| b == b' ').next()).unwrap_or(first);
+    if unwatchable(first) {
+        t.write_line("  watch: ese comando no se puede repetir (es interactivo)", LineColor::Error);
+        return;
+    }
+    if t.redirecting {
+        t.write_line("  watch no admite redireccion (es interactivo)", LineColor::Error);
+        return;
+    }
+    let mut w = Watch {
+        cmd: [0u8; INPUT_MAX], cmd_len: cmd.len(), secs, next: 0,
+        base: t.line_count, runs: 0,
+    };
+    w.cmd[..cmd.len()].copy_from_slice(cmd);
+    t.watch = Some(w);
+}
+
+/// Refresco pendiente del `watch` activo. Devuelve true si redibujó.
+pub fn watch_poll(t: &mut Terminal, now: u64, hw: &Hw, pci: &Pci) -> bool {
+    let line_count = t.line_count;
+    let Some(w) = t.watch.as_mut() else { return false };
+    if now < w.next { return false; }
+    // `base` no puede quedar más allá del final (p. ej. tras capturar un banner)
+    w.base = w.base.min(line_count);
+    // Sin recuperar vueltas perdidas si el loop se retrasó
+    w.next = now + w.secs * PIT_HZ as u64;
+    w.runs += 1;
+    let (base, secs) = (w.base, w.secs);
+    let mut cmd = [0u8; INPUT_MAX];
+    let n = w.cmd_len;
+    cmd[..n].copy_from_slice(&w.cmd[..n]);
+
+    t.truncate_lines(base);
+    t.clear_mark = base;
+    t.scroll_to_bottom();
+
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  Cada ");
+    append_u32(&mut buf, &mut pos, secs as u32);
+    append_str(&mut buf, &mut pos, b"s: ");
+    append_str(&mut buf, &mut pos, &cmd[..n.min(40)]);
+    append_str(&mut buf, &mut pos, b"   [");
+    append_u32(&mut buf, &mut pos, (now / PIT_HZ as u64) as u32);
+    append_str(&mut buf, &mut pos, b" s]   Esc/q = salir");
+    t.write_bytes(&buf[..pos], LineColor::Header);
+    t.write_empty();
+
+    let out = t.line_count;
+    t.run_command(&cmd[..n], hw, pci);
+    let lines = t.line_count.saturating_sub(out);
+    if lines > WATCH_MAX_LINES {
+        t.truncate_lines(out + WATCH_MAX_LINES - 1);
+        let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+        append_str(&mut buf, &mut pos, b"  ... (");
+        append_u32(&mut buf, &mut pos, (lines - WATCH_MAX_LINES + 1) as u32);
+        append_str(&mut buf, &mut pos, b" lineas mas)");
+        t.write_bytes(&buf[..pos], LineColor::Warning);
+    }
+    // Si el comando abrió algo interactivo (p. ej. vía alias), se para aquí
+    if t.editor.is_some() || t.viewer.is_some() || t.logview.is_some()
+        || t.regwatch.is_some() || t.key_wait
+    {
+        watch_stop(t);
+    }
+    true
+}
+
+/// Detiene el `watch` activo. La última salida se queda en pantalla.
+pub fn watch_stop(t: &mut Terminal) {
+    let Some(w) = t.watch.take() else { return };
+    let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+    append_str(&mut buf, &mut pos, b"  watch detenido tras ");
+    append_u32(&mut buf, &mut pos, w.runs);
+    append_str(&mut buf, &mut pos, if w.runs == 1 { b" ejecucion" } else { b" ejecuciones" });
+    t.write_bytes(&buf[..pos], LineColor::Info);
+    t.remote_prompt();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_and_command() {
+        assert_eq!(parse_watch_args(b"free"), Ok((WATCH_SECS, &b"free"[..])));
+        assert_eq!(parse_watch_args(b"  -n 5   ls -l /  "), Ok((5, &b"ls -l /"[..])));
+        assert_eq!(parse_watch_args(b"-n 1 uptime"), Ok((1, &b"uptime"[..])));
+        assert_eq!(parse_watch_args(b"-n 3600 uptime"), Ok((3600, &b"uptime"[..])));
+    }
+
+    #[test]
+    fn bad_interval() {
+        assert_eq!(parse_watch_args(b"-n"), Err("falta el intervalo"));
+        assert_eq!(parse_watch_args(b"-n   "), Err("falta el intervalo"));
+        for args in [&b"-n 0 free"[..], b"-n 3601 free", b"-n x free", b"-n -1 free"] {
+            assert_eq!(parse_watch_args(args), Err("intervalo invalido (1-3600 segundos)"));
+        }
+    }
+
+    #[test]
+    fn missing_command() {
+        assert_eq!(parse_watch_args(b""), Err("falta el comando"));
+        assert_eq!(parse_watch_args(b"   "), Err("falta el comando"));
+        assert_eq!(parse_watch_args(b"-n 5"), Err("falta el comando"));
+        assert_eq!(parse_watch_args(b"-n 5   "), Err("falta el comando"));
+    }
+
+    #[test]
+    fn interactive_commands_are_refused() {
+        assert!(unwatchable(b"watch") && unwatchable(b"waitkey") && unwatchable(b"dmesg"));
+        assert!(!unwatchable(b"free") && !unwatchable(b"ls"));
+    }
+}
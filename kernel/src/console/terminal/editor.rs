@@ -560,6 +560,9 @@ fn handle_key_text(&mut self, key: Key, ctrl: bool) -> bool {
                     m[p..p + nl].copy_from_slice(&self.file_path[..nl]);
                     self.set_msg(&m, MsgKind::Ok);
                 }
+                Err(crate::drivers::storage::fat32::FatError::ReadOnly) => {
+                    self.set_msg(b"[ERROR] Volumen de solo lectura (remount rw).", MsgKind::Error);
+                }
                 Err(_) => {
                     self.set_msg(b"[ERROR] No se pudo escribir el archivo.", MsgKind::Error);
                 }
@@ -20,6 +20,15 @@ pub(crate) fn append_u32(buf: &mut [u8], pos: &mut usize, mut n: u32) {
     append_str(buf, pos, &tmp[..i]);
 }
 
+pub(crate) fn append_u64(buf: &mut [u8], pos: &mut usize, mut n: u64) {
+    if n == 0 { append_str(buf, pos, b"0"); return; }
+    let mut tmp = [0u8; 20];
+    let mut i = 0;
+    while n > 0 { tmp[i] = b'0' + (n % 10) as u8; n /= 10; i += 1; }
+    tmp[..i].reverse();
+    append_str(buf, pos, &tmp[..i]);
+}
+
 pub(crate) fn append_hex8_byte(buf: &mut [u8], pos: &mut usize, v: u8) {
     const H: &[u8] = b"0123456789ABCDEF";
     append_str(buf, pos, &[H[(v >> 4) as usize], H[(v & 0xF) as usize]]);
@@ -48,6 +57,48 @@ pub(crate) fn append_hex64_short(buf: &mut [u8], pos: &mut usize, mut v: u64) {
     append_str(buf, pos, &tmp[start..]);
 }
 
+/// `v` en binario agrupado de 4 en 4 con `_`, sin ceros a la izquierda más
+/// allá del primer grupo (`0b` lo pone quien llama).
+pub(crate) fn append_bin_grouped(buf: &mut [u8], pos: &mut usize, v: u64) {
+    let bits = if v == 0 { 1 } else { (64 - v.leading_zeros() as usize).div_ceil(4) * 4 };
+    for i in (0..bits).rev() {
+        append_str(buf, pos, if (v >> i) & 1 != 0 { b"1" } else { b"0" });
+        if i > 0 && i % 4 == 0 { append_str(buf, pos, b"_"); }
+    }
+}
+
+/// Una fila de hexdump: `DIRECCION  00 01 .. 07  08 .. 0F  ASCII` para hasta
+/// 16 bytes de `bytes` (los que falten se rellenan con espacios). La usan
+/// `hexdump` y el volcado de pila de la pantalla de panic.
+pub(crate) fn append_hexdump_row(buf: &mut [u8], pos: &mut usize, base: u64, bytes: &[u8]) {
+    const H: &[u8] = b"0123456789ABCDEF";
+    let n = bytes.len().min(16);
+    append_hex64_short(buf, pos, base);
+    append_str(buf, pos, b"  ");
+    for col in 0..16usize {
+        if col == 8 { append_str(buf, pos, b" "); }
+        if let Some(&b) = bytes[..n].get(col) {
+            append_str(buf, pos, &[H[(b >> 4) as usize], H[(b & 0xF) as usize], b' ']);
+        } else {
+            append_str(buf, pos, b"   ");
+        }
+    }
+    append_str(buf, pos, b" ");
+    for &b in &bytes[..n] {
+        append_str(buf, pos, &[if (32..127).contains(&b) { b } else { b'.' }]);
+    }
+}
+
+/// Barra de texto `[#####.....]` de `width` celdas con `pct` (0..100) llenas.
+pub(crate) fn append_bar(buf: &mut [u8], pos: &mut usize, pct: u32, width: usize) {
+    let filled = (pct.min(100) as usize * width).div_ceil(100);
+    append_str(buf, pos, b"[");
+    for i in 0..width {
+        append_str(buf, pos, if i < filled { b"#" } else { b"." });
+    }
+    append_str(buf, pos, b"]");
+}
+
 pub(crate) fn append_mhz(buf: &mut [u8], pos: &mut usize, mhz: u32) {
     if mhz >= 1000 {
         let gi = mhz / 1000; let gf = (mhz % 1000) / 10;
@@ -61,7 +112,10 @@ pub(crate) fn append_mhz(buf: &mut [u8], pos: &mut usize, mhz: u32) {
 
 pub(crate) fn append_mib(buf: &mut [u8], pos: &mut usize, mb: u64) {
     if mb == 0 { append_str(buf, pos, b"0 MB"); return; }
-    if mb >= 1024 {
+    if mb >= 1024 * 1024 {
+        append_u32(buf, pos, (mb / (1024 * 1024)) as u32); append_str(buf, pos, b".");
+        append_u32(buf, pos, ((mb % (1024 * 1024)) * 10 / (1024 * 1024)) as u32); append_str(buf, pos, b" TB");
+    } else if mb >= 1024 {
         append_u32(buf, pos, (mb / 1024) as u32); append_str(buf, pos, b".");
         append_u32(buf, pos, ((mb % 1024) * 10 / 1024) as u32); append_str(buf, pos, b" GB");
     } else {
@@ -107,41 +161,360 @@ pub(crate) fn trim(s: &[u8]) -> &[u8] {
     match s.iter().rposition(|&b| b != b' ') { Some(i) => &s[..=i], None => s }
 }
 
-// ══ Evaluador aritmético simple (+, -, *, /) ══════════════════════════════════
+// ══ Evaluador aritmético (calc) ═══════════════════════════════════════════════
+//
+// Shunting-yard con dos pilas fijas (valores y operadores) que evalúa sobre la
+// marcha: sin heap y sin recursión, así que el anidamiento solo está limitado
+// por EVAL_STACK (más profundo → None, nunca desborda la pila del kernel).
+//
+// Precedencia, de menor a mayor (la de Rust, más `**`):
+//   |          OR bit a bit
+//   ^          XOR
+//   &          AND
+//   << >>      desplazamientos (>> aritmético; cantidad fuera de 0..63 es error)
+//   + -        binarios, izquierda
+//   * / %      izquierda (división y módulo truncan hacia cero, como Rust)
+//   - + ~      unarios (~ es NOT bit a bit: ~0 = -1)
+//   **         potencia, derecha  (-2**2 = -4, 2**-1 es error)
+//
+// Así `1 + 2 << 3` = 24 y `0xF0 | 0x0F & 3` = 0xF3, igual que en Rust.
+//
+// Toda la aritmética es checked: desbordamiento, división o módulo por cero y
+// exponentes negativos devuelven None igual que una expresión mal formada.
+//
+// Literales: decimal, `0x..` y `0b..` (se admite `_` como separador). Las
+// bases se pueden mezclar. Un literal hex/bin es un patrón de 64 bits, así
+// que 0xFFFFFFFFFFFFFFFF vale -1 (como la salida hex de `calc`); más de 64
+// bits significativos es error. El signo se escribe aparte: -0x10 = -16.
 
+const EVAL_STACK: usize = 64;
+
+/// Operador unario menos en la pila de operadores.
+const OP_NEG: u8 = b'n';
+/// Potencia (`**`).
+const OP_POW: u8 = b'P';
+/// NOT bit a bit unario (`~`).
+const OP_NOT: u8 = b'~';
+/// Desplazamientos (`<<`, `>>`).
+const OP_SHL: u8 = b'L';
+const OP_SHR: u8 = b'R';
+
+/// Literal en `expr[i..]` (ya se sabe que empieza por dígito). Devuelve el
+/// valor y el índice siguiente.
+fn eval_literal(expr: &[u8], mut i: usize) -> Option<(i64, usize)> {
+    let radix = match (expr[i], expr.get(i + 1).map(|b| b.to_ascii_lowercase())) {
+        (b'0', Some(b'x')) => 16,
+        (b'0', Some(b'b')) => 2,
+        _ => 10,
+    };
+    if radix == 10 {
+        let mut n: i64 = 0;
+        while i < expr.len() && (expr[i].is_ascii_digit() || expr[i] == b'_') {
+            if expr[i] != b'_' { n = n.checked_mul(10)?.checked_add((expr[i] - b'0') as i64)?; }
+            i += 1;
+        }
+        return Some((n, i));
+    }
+    i += 2;
+    let mut digits = [0u8; 64]; let mut nd = 0usize;
+    let mut seen = false;
+    while i < expr.len() && (expr[i].is_ascii_hexdigit() || expr[i] == b'_') {
+        if expr[i] != b'_' {
+            seen = true;
+            // Ceros a la izquierda no cuentan para el límite de 64 bits
+            if nd == 0 && expr[i] == b'0' { i += 1; continue; }
+            if nd == digits.len() { return None; }
+            digits[nd] = expr[i]; nd += 1;
+        }
+        i += 1;
+    }
+    if !seen { return None; }
+    let v = if radix == 16 {
+        if nd > 16 { return None; }
+        if nd == 0 { 0 } else { parse_hex_raw(&digits[..nd])? }
+    } else {
+        let mut v = 0u64;
+        for &d in &digits[..nd] {
+            if d > b'1' { return None; }
+            v = (v << 1) | (d - b'0') as u64;
+        }
+        v
+    };
+    Some((v as i64, i))
+}
+
+/// (precedencia, asociativo por la derecha).
+fn eval_prec(op: u8) -> (u8, bool) {
+    match op {
+        b'|'               => (1, false),
+        b'^'               => (2, false),
+        b'&'               => (3, false),
+        OP_SHL | OP_SHR    => (4, false),
+        b'+' | b'-'        => (5, false),
+        b'*' | b'/' | b'%' => (6, false),
+        OP_NEG | OP_NOT    => (7, true),
+        OP_POW             => (8, true),
+        _                  => (0, false),
+    }
+}
+
+fn eval_apply(op: u8, vals: &mut [i64; EVAL_STACK], vn: &mut usize) -> Option<()> {
+    if op == OP_NEG || op == OP_NOT {
+        let v = vals.get_mut(vn.checked_sub(1)?)?;
+        *v = if op == OP_NEG { v.checked_neg()? } else { !*v };
+        return Some(());
+    }
+    if *vn < 2 { return None; }
+    let (a, b) = (vals[*vn - 2], vals[*vn - 1]);
+    let r = match op {
+        b'+'   => a.checked_add(b)?,
+        b'-'   => a.checked_sub(b)?,
+        b'*'   => a.checked_mul(b)?,
+        b'/'   => a.checked_div(b)?,
+        b'%'   => a.checked_rem(b)?,
+        OP_POW => a.checked_pow(u32::try_from(b).ok()?)?,
+        b'&'   => a & b,
+        b'|'   => a | b,
+        b'^'   => a ^ b,
+        OP_SHL if (0..64).contains(&b) => a << b,
+        OP_SHR if (0..64).contains(&b) => a >> b,
+        _      => return None,
+    };
+    *vn -= 1;
+    vals[*vn - 1] = r;
+    Some(())
+}
+
+/// Evalúa `expr`; None si está mal formada o si algún paso no es
+/// representable en i64.
 pub(crate) fn simple_eval(expr: &[u8]) -> Option<i64> {
-    let mut tokens = [(0i64, b'+'); 32]; let mut tcount = 0usize;
-    let mut i = 0usize; let mut first = true;
+    let mut vals = [0i64; EVAL_STACK]; let mut vn = 0usize;
+    let mut ops  = [0u8; EVAL_STACK];  let mut on = 0usize;
+    let mut expect_operand = true;
+    let mut i = 0usize;
+
     while i < expr.len() {
-        while i < expr.len() && expr[i] == b' ' { i += 1; }
-        if i >= expr.len() { break; }
-        let neg = if expr[i] == b'-' && first { i += 1; true } else { false };
-        let mut n: i64 = 0; let mut digits = 0;
-        while i < expr.len() && expr[i].is_ascii_digit() {
-            n = n * 10 + (expr[i] - b'0') as i64; i += 1; digits += 1;
+        let c = expr[i];
+        if c == b' ' || c == b'\t' { i += 1; continue; }
+
+        if c.is_ascii_digit() {
+            if !expect_operand { return None; }
+            let (n, next) = eval_literal(expr, i)?;
+            i = next;
+            if vn == EVAL_STACK { return None; }
+            vals[vn] = n; vn += 1;
+            expect_operand = false;
+            continue;
+        }
+
+        match c {
+            b'(' => {
+                if !expect_operand || on == EVAL_STACK { return None; }
+                ops[on] = b'('; on += 1;
+            }
+            b')' => {
+                if expect_operand { return None; }
+                loop {
+                    if on == 0 { return None; }
+                    on -= 1;
+                    if ops[on] == b'(' { break; }
+                    eval_apply(ops[on], &mut vals, &mut vn)?;
+                }
+            }
+            b'-' | b'+' | b'~' if expect_operand => {
+                if c != b'+' {
+                    if on == EVAL_STACK { return None; }
+                    ops[on] = if c == b'-' { OP_NEG } else { OP_NOT }; on += 1;
+                }
+            }
+            b'+' | b'-' | b'*' | b'/' | b'%' | b'&' | b'|' | b'^' | b'<' | b'>' => {
+                if expect_operand { return None; }
+                let next = expr.get(i + 1).copied();
+                let op = match (c, next) {
+                    (b'*', Some(b'*')) => { i += 1; OP_POW }
+                    (b'<', Some(b'<')) => { i += 1; OP_SHL }
+                    (b'>', Some(b'>')) => { i += 1; OP_SHR }
+                    (b'<', _) | (b'>', _) => return None,
+                    _ => c,
+                };
+                let (p, right) = eval_prec(op);
+                while on > 0 && ops[on - 1] != b'(' {
+                    let (tp, _) = eval_prec(ops[on - 1]);
+                    if tp > p || (tp == p && !right) {
+                        on -= 1;
+                        eval_apply(ops[on], &mut vals, &mut vn)?;
+                    } else {
+                        break;
+                    }
+                }
+                if on == EVAL_STACK { return None; }
+                ops[on] = op; on += 1;
+                expect_operand = true;
+            }
+            _ => return None,
+        }
+        i += 1;
+    }
+
+    if expect_operand { return None; }
+    while on > 0 {
+        on -= 1;
+        if ops[on] == b'(' { return None; }
+        eval_apply(ops[on], &mut vals, &mut vn)?;
+    }
+    if vn == 1 { Some(vals[0]) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(s: &str) -> Option<i64> { simple_eval(s.as_bytes()) }
+
+    #[test]
+    fn eval_precedence_and_associativity() {
+        assert_eq!(eval("2+3*4"), Some(14));
+        assert_eq!(eval("2*(3+4)"), Some(14));
+        assert_eq!(eval("10-4-3"), Some(3));
+        assert_eq!(eval("100/10/5"), Some(2));
+        assert_eq!(eval("2**3**2"), Some(512));
+        assert_eq!(eval("-2**2"), Some(-4));
+        assert_eq!(eval("(-2)**2"), Some(4));
+        assert_eq!(eval("- -3"), Some(3));
+        assert_eq!(eval(" 1 +\t2 "), Some(3));
+    }
+
+    #[test]
+    fn eval_modulo_and_division_truncate_like_rust() {
+        assert_eq!(eval("10%3"), Some(1));
+        assert_eq!(eval("-7%3"), Some(-1));
+        assert_eq!(eval("7%-3"), Some(1));
+        assert_eq!(eval("7/2"), Some(3));
+        assert_eq!(eval("-7/2"), Some(-3));
+    }
+
+    #[test]
+    fn eval_nesting() {
+        assert_eq!(eval("((((1+2))*3))"), Some(9));
+        assert_eq!(eval("(1+(2*(3+(4*(5+6)))))"), Some(95));
+        let mut deep = [0u8; 2 * EVAL_STACK + 3];
+        deep[..EVAL_STACK + 1].fill(b'(');
+        deep[EVAL_STACK + 1] = b'1';
+        deep[EVAL_STACK + 2..].fill(b')');
+        // Hasta EVAL_STACK niveles; uno más es error, nunca desborda
+        assert_eq!(simple_eval(&deep), None);
+        assert_eq!(simple_eval(&deep[1..deep.len() - 1]), Some(1));
+    }
+
+    #[test]
+    fn eval_division_by_zero_and_overflow() {
+        assert_eq!(eval("1/0"), None);
+        assert_eq!(eval("5%0"), None);
+        assert_eq!(eval("1/(2-2)"), None);
+        assert_eq!(eval("9223372036854775807+1"), None);
+        assert_eq!(eval("9223372036854775808"), None);
+        assert_eq!(eval("-9223372036854775807-1"), Some(i64::MIN));
+        assert_eq!(eval("-(-9223372036854775807-1)"), None);
+        assert_eq!(eval("(-9223372036854775807-1)/-1"), None);
+        assert_eq!(eval("2**63"), None);
+        assert_eq!(eval("2**62"), Some(1 << 62));
+        assert_eq!(eval("2**-1"), None);
+    }
+
+    #[test]
+    fn eval_hex_and_binary_literals() {
+        assert_eq!(eval("0xFF+1"), Some(256));
+        assert_eq!(eval("0b1010*2"), Some(20));
+        assert_eq!(eval("0XfF"), Some(255));
+        assert_eq!(eval("0x10 + 0b11 + 7"), Some(26));
+        assert_eq!(eval("1_000 + 0xFF_FF"), Some(66535));
+        assert_eq!(eval("-0x10"), Some(-16));
+        assert_eq!(eval("0x0000000000000000001"), Some(1));
+        // Patrón de 64 bits: todo a uno es -1
+        assert_eq!(eval("0xFFFFFFFFFFFFFFFF"), Some(-1));
+        assert_eq!(eval("0x1FFFFFFFFFFFFFFFF"), None);
+        assert_eq!(eval("0x"), None);
+        assert_eq!(eval("0b"), None);
+        assert_eq!(eval("0b102"), None);
+        assert_eq!(eval("0xG"), None);
+    }
+
+    #[test]
+    fn eval_bitwise_and_shifts() {
+        assert_eq!(eval("0xF0|0x0F"), Some(0xFF));
+        assert_eq!(eval("0xF0&0x3C"), Some(0x30));
+        assert_eq!(eval("0xFF^0x0F"), Some(0xF0));
+        assert_eq!(eval("1<<8"), Some(256));
+        assert_eq!(eval("256>>4"), Some(16));
+        assert_eq!(eval("-16>>2"), Some(-4));
+        assert_eq!(eval("~0"), Some(-1));
+        assert_eq!(eval("~0xFF & 0xFFF"), Some(0xF00));
+        // Precedencia de Rust
+        assert_eq!(eval("1 + 2 << 3"), Some(24));
+        assert_eq!(eval("0xF0 | 0x0F & 3"), Some(0xF3));
+        assert_eq!(eval("6 & 3 ^ 1 | 8"), Some(11));
+    }
+
+    #[test]
+    fn eval_shift_count_out_of_range() {
+        assert_eq!(eval("1<<63"), Some(i64::MIN));
+        assert_eq!(eval("1<<64"), None);
+        assert_eq!(eval("1>>64"), None);
+        assert_eq!(eval("1<<100"), None);
+        assert_eq!(eval("1<<-1"), None);
+        assert_eq!(eval("1<2"), None);
+        assert_eq!(eval("1<<<2"), None);
+    }
+
+    #[test]
+    fn eval_rejects_malformed_input() {
+        for bad in ["", " ", "1+", "*2", "(1", "1)", "()", "1 2", "(1)(2)", "2(3)", "abc", "1+x", "3***2", "1//2"] {
+            assert_eq!(eval(bad), None, "{bad:?}");
         }
-        if digits == 0 && !neg { return None; }
-        if neg { n = -n; }
-        while i < expr.len() && expr[i] == b' ' { i += 1; }
-        let op = if i < expr.len() { let o = expr[i]; i += 1; o } else { b'+' };
-        if tcount < 32 { tokens[tcount] = (n, op); tcount += 1; }
-        first = false;
-    }
-    if tcount == 0 { return None; }
-    let mut vals = [0i64; 32]; let mut ops = [b'+'; 32]; let mut vn = 0usize;
-    let (mut acc, mut cur_op) = (tokens[0].0, tokens[0].1);
-    for t in 1..tcount {
-        let (num, next_op) = tokens[t];
-        if cur_op == b'*'      { acc *= num; }
-        else if cur_op == b'/' { if num == 0 { return None; } acc /= num; }
-        else { vals[vn] = acc; ops[vn] = cur_op; vn += 1; acc = num; }
-        cur_op = next_op;
-    }
-    vals[vn] = acc; vn += 1;
-    let mut result = vals[0];
-    for k in 1..vn {
-        if ops[k - 1] == b'+' { result += vals[k]; }
-        else if ops[k - 1] == b'-' { result -= vals[k]; }
-    }
-    Some(result)
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn append_u64_full_range() {
+        let fmt = |n: u64| {
+            let mut buf = [0u8; 24]; let mut pos = 0;
+            append_u64(&mut buf, &mut pos, n);
+            String::from_utf8(buf[..pos].to_vec()).unwrap()
+        };
+        assert_eq!(fmt(0), "0");
+        assert_eq!(fmt(10), "10");
+        assert_eq!(fmt(u32::MAX as u64 + 1), "4294967296");
+        assert_eq!(fmt(u64::MAX), "18446744073709551615");
+    }
+
+    fn hexdump_row(base: u64, bytes: &[u8]) -> String {
+        let mut buf = [0u8; 128]; let mut pos = 0;
+        append_hexdump_row(&mut buf, &mut pos, base, bytes);
+        String::from_utf8(buf[..pos].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn hexdump_full_row() {
+        let bytes: [u8; 16] = core::array::from_fn(|i| b'a' + i as u8);
+        assert_eq!(hexdump_row(0x1000, &bytes),
+            "000001000  61 62 63 64 65 66 67 68  69 6A 6B 6C 6D 6E 6F 70  abcdefghijklmnop");
+    }
+
+    #[test]
+    fn hexdump_short_row_keeps_ascii_column() {
+        // Con menos de 16 bytes la columna ASCII no se desplaza
+        let full  = hexdump_row(0x1000, &[0u8; 16]);
+        let short = hexdump_row(0x1000, b"\x00A\x7F");
+        assert!(short.starts_with("000001000  00 41 7F    "));
+        assert_eq!(short.len(), full.len() - 13);
+        assert_eq!(short.find(".A."), full.rfind("  ").map(|i| i + 2));
+        assert_eq!(hexdump_row(0xFFFF_8000_0000_0000, &[]).trim_end(), "FFFF800000000000");
+    }
+
+    #[test]
+    fn hexdump_ignores_bytes_past_16() {
+        let bytes = [b'x'; 20];
+        assert!(hexdump_row(0, &bytes).ends_with(&"x".repeat(16)));
+        assert!(!hexdump_row(0, &bytes).ends_with(&"x".repeat(17)));
+    }
+}
+
@@ -0,0 +1,218 @@
+// console/terminal/links.rs — PORTIX Kernel
+// Rutas clickables en la salida del terminal. Al escribir cada línea se
+// marcan las subcadenas que parecen rutas (TermLine::links); la pestaña del
+// terminal las subraya bajo el ratón y un clic abre el archivo en el IDE o
+// la carpeta en el Explorador.
+//
+// Heurística: un token es ruta si empieza por "/", "./" o "../", o si
+// termina en una extensión conocida. En las filas de `ls` ("[DIR]"/"[ARC]")
+// la columna de nombre entera es un enlace, aunque no tenga extensión.
+// Las rutas relativas se resuelven contra la cabecera "Directorio: " más
+// cercana de la misma salida (la de `ls`) o, si no hay, contra el cwd.
+
+use super::{Terminal, TermLine, LineColor, TERM_COLS};
+use super::commands::disk;
+use crate::drivers::storage::fat32::FatResult;
+
+/// Enlaces como máximo por línea.
+pub const LINK_MAX: usize = 4;
+
+/// Columna donde empieza el nombre en las filas de `ls` (`ls -l` marca su
+/// enlace explícitamente con `set_last_link`).
+pub const LS_NAME_COL: usize = 20;
+const LS_HEADER: &[u8] = b"  Directorio: ";
+
+const EXTS: [&[u8]; 14] = [
+    b"rs", b"c", b"h", b"asm", b"s", b"txt", b"md", b"toml",
+    b"bmp", b"raw", b"bin", b"elf", b"cfg", b"sh",
+];
+
+/// Tamaño máximo de un archivo abierto desde un enlace.
+const OPEN_MAX: usize = 64 * 1024;
+static mut OPEN_BUF: [u8; OPEN_MAX] = [0u8; OPEN_MAX];
+
+/// Destino resuelto de un enlace: ruta absoluta normalizada.
+pub struct LinkTarget {
+    pub path:   [u8; 256],
+    pub len:    usize,
+    pub is_dir: bool,
+}
+
+impl LinkTarget {
+    pub fn path(&self) -> &[u8] { &self.path[..self.len] }
+
+    /// Nombre base, para la pestaña del IDE.
+    pub fn name(&self) -> &str {
+        let p = self.path();
+        let from = p.iter().rposition(|&b| b == b'/').map_or(0, |i| i + 1);
+        core::str::from_utf8(&p[from..]).unwrap_or("archivo")
+    }
+}
+
+fn is_delim(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'"' | b'\'' | b'(' | b')' | b',' | b';'
+        | b':' | b'[' | b']' | b'<' | b'>' | b'=')
+}
+
+fn is_name_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-' | b'/' | b'~')
+}
+
+fn looks_like_path(tok: &[u8]) -> bool {
+    if tok.is_empty() || !tok.iter().all(|&b| is_name_byte(b)) { return false; }
+    if tok.windows(2).any(|w| w == b"//") { return false; }
+    if tok.len() > 1 && tok[0] == b'/' { return true; }
+    if tok.starts_with(b"./") || tok.starts_with(b"../") { return true; }
+    let name = &tok[tok.iter().rposition(|&b| b == b'/').map_or(0, |i| i + 1)..];
+    match name.iter().rposition(|&b| b == b'.') {
+        Some(dot) if dot > 0 => {
+            let ext = &name[dot + 1..];
+            EXTS.iter().any(|e| ext.eq_ignore_ascii_case(e))
+        }
+        _ => false,
+    }
+}
+
+impl TermLine {
+    /// Recalcula las regiones clickables de la línea.
+    pub(crate) fn scan_links(&mut self) {
+        self.link_count = 0;
+        let len = self.len;
+        let b   = &self.buf[..len];
+
+        if b.starts_with(b"  [DIR]  ") || b.starts_with(b"  [ARC]  ") {
+            let end = b.iter().rposition(|&c| c != b' ').map_or(0, |i| i + 1);
+            if end > LS_NAME_COL {
+                self.links[0] = (LS_NAME_COL as u8, (end - LS_NAME_COL) as u8);
+                self.link_count = 1;
+            }
+            return;
+        }
+
+        let mut i = 0;
+        while i < len && (self.link_count as usize) < LINK_MAX {
+            if is_delim(b[i]) { i += 1; continue; }
+            let start = i;
+            while i < len && !is_delim(b[i]) { i += 1; }
+            // Punto final de frase: "Guardado en /a/b.txt."
+            let mut end = i;
+            if end - start > 1 && b[end - 1] == b'.' && b[end - 2] != b'.' { end -= 1; }
+            if looks_like_path(&b[start..end]) {
+                self.links[self.link_count as usize] = (start as u8, (end - start) as u8);
+                self.link_count += 1;
+            }
+        }
+    }
+
+    /// Enlace que contiene la columna `col`, como (inicio, longitud).
+    pub fn link_at(&self, col: usize) -> Option<(usize, usize)> {
+        self.links[..self.link_count as usize].iter()
+            .map(|&(s, l)| (s as usize, l as usize))
+            .find(|&(s, l)| col >= s && col < s + l)
+    }
+}
+
+/// Anexa a `out[..len]` los componentes de `rel`, resolviendo "." y "..".
+fn push_components(out: &mut [u8; 256], len: &mut usize, rel: &[u8]) {
+    for comp in rel.split(|&c| c == b'/') {
+        match comp {
+            b"" | b"." => {}
+            b".." => {
+                *len = out[..*len].iter().rposition(|&c| c == b'/').unwrap_or(0);
+            }
+            _ => {
+                if *len + 1 + comp.len() > out.len() { return; }
+                out[*len] = b'/';
+                out[*len + 1..*len + 1 + comp.len()].copy_from_slice(comp);
+                *len += 1 + comp.len();
+            }
+        }
+    }
+}
+
+impl Terminal {
+    /// Base de las rutas relativas de la línea lógica `li`: la cabecera
+    /// "Directorio: " más cercana por encima sin cruzar el eco del comando.
+    fn link_base(&self, li: usize) -> Option<([u8; TERM_COLS], usize)> {
+        let oldest = self.line_count.saturating_sub(super::TERM_ROWS);
+        let mut i = li;
+        loop {
+            let line = self.line_at(i);
+            if line.color == LineColor::Prompt { return None; }
+            let b = &line.buf[..line.len];
+            if let Some(rest) = b.strip_prefix(LS_HEADER) {
+                let mut out = [0u8; TERM_COLS];
+                out[..rest.len()].copy_from_slice(rest);
+                return Some((out, rest.len()));
+            }
+            if i == oldest { return None; }
+            i -= 1;
+        }
+    }
+
+    /// Resuelve el enlace (`start`, `len`) de la línea lógica `li` y
+    /// comprueba que exista. Escribe un aviso si no se encuentra.
+    pub fn link_target(&mut self, li: usize, start: usize, len: usize) -> Option<LinkTarget> {
+        let mut text = [0u8; TERM_COLS];
+        let line = self.line_at(li);
+        let end  = (start + len).min(line.len);
+        let tl   = end.saturating_sub(start);
+        text[..tl].copy_from_slice(&line.buf[start..end]);
+        if tl == 0 { return None; }
+        let text = &text[..tl];
+
+        let mut t = LinkTarget { path: [0u8; 256], len: 0, is_dir: false };
+        if text[0] != b'/' {
+            match self.link_base(li) {
+                Some((base, bl)) => push_components(&mut t.path, &mut t.len, &base[..bl]),
+                None => {
+                    let cwd = self.cwd;
+                    push_components(&mut t.path, &mut t.len, &cwd[..self.cwd_len]);
+                }
+            }
+        }
+        push_components(&mut t.path, &mut t.len, text);
+        if t.len == 0 { t.path[0] = b'/'; t.len = 1; }
+
+        match disk::path_kind(self, t.path()) {
+            Some(d) => { t.is_dir = d; Some(t) }
+            None => {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                super::fmt::append_str(&mut buf, &mut pos, b"  No se encuentra: ");
+                let n = t.len.min(TERM_COLS - pos);
+                super::fmt::append_str(&mut buf, &mut pos, &t.path[..n]);
+                self.write_bytes(&buf[..pos], LineColor::Warning);
+                None
+            }
+        }
+    }
+
+    /// Sustituye los enlaces de la última línea escrita por uno solo en
+    /// (`start`, `len`), para salidas cuyo formato no detecta `scan_links`.
+    pub fn set_last_link(&mut self, start: usize, len: usize) {
+        if self.redirecting || self.line_count == 0 || len == 0 { return; }
+        let line = &mut self.lines[(self.line_count - 1) % super::TERM_ROWS];
+        if start + len > line.len { return; }
+        line.links[0]   = (start as u8, len as u8);
+        line.link_count = 1;
+    }
+
+    /// Lee el archivo de un enlace para abrirlo en el IDE. Los errores se
+    /// escriben en el terminal.
+    pub fn read_link_file(&mut self, target: &LinkTarget) -> Option<&'static [u8]> {
+        // SAFETY: kernel monohilo; OPEN_BUF solo se usa aquí y el IDE copia
+        // el contenido antes del siguiente clic.
+        let buf = unsafe { &mut *core::ptr::addr_of_mut!(OPEN_BUF) };
+        let n = disk::read_text_file(self, b"abrir", target.path(), buf)?;
+        Some(unsafe { &(&*core::ptr::addr_of!(OPEN_BUF))[..n] })
+    }
+
+    /// Relee `path` (absoluta) para Recargar del IDE. Como read_link_file,
+    /// pero el error vuelve al llamador para mostrarlo en la status bar.
+    pub fn reread_file(&mut self, path: &[u8]) -> FatResult<&'static [u8]> {
+        // SAFETY: ver read_link_file
+        let buf = unsafe { &mut *core::ptr::addr_of_mut!(OPEN_BUF) };
+        let n = disk::read_file_raw(self, path, buf)?;
+        Ok(unsafe { &(&*core::ptr::addr_of!(OPEN_BUF))[..n] })
+    }
+}
@@ -0,0 +1,214 @@
+// console/terminal/logview.rs — PORTIX Kernel
+//
+// Visor del log del kernel (ring de `serial`) — comando `dmesg -w`.
+//
+// ┌─ Teclas ───────────────────────────────────────────────────────────────────┐
+// │  Flechas / RePág / AvPág / rueda  → scroll                                │
+// │  Inicio / Fin                      → entrada más antigua / más reciente   │
+// │  F                                 → activar / desactivar auto-scroll     │
+// │  Esc / Q                           → salir                                │
+// └────────────────────────────────────────────────────────────────────────────┘
+//
+// Auto-scroll: con `follow` activo la vista sigue las entradas nuevas solo
+// mientras está pegada al fondo (`pinned`). Subir a mano la despega y pausa
+// el seguimiento; volver al fondo (Fin, AvPág, rueda) lo reanuda. La posición
+// se guarda como nº de secuencia del log, así que las entradas nuevas no la
+// mueven aunque el ring dé la vuelta (solo se recorta si la fila superior
+// ya se ha sobrescrito).
+
+#![allow(dead_code)]
+
+use crate::drivers::input::keyboard::Key;
+use crate::drivers::serial::{self, Level, LogEntry};
+use crate::graphics::driver::framebuffer::{Color, Console, Layout};
+use crate::console::terminal::editor::EdPalette;
+use crate::time::pit::PIT_HZ;
+
+pub struct LogViewState {
+    /// Secuencia de la primera fila visible.
+    pub top:    usize,
+    /// Auto-scroll activado (tecla F, valor inicial de `scroll-sync`).
+    pub follow: bool,
+    /// La vista muestra la última entrada: solo así la arrastra el auto-scroll.
+    pub pinned: bool,
+    /// `serial::log_seq()` en el último refresco.
+    pub seen:   usize,
+    pub exit:   bool,
+}
+
+impl LogViewState {
+    /// Abre el visor pegado al fondo.
+    pub fn new(follow: bool, vis: usize) -> Self {
+        LogViewState { top: max_top(vis), follow, pinned: true, seen: serial::log_seq(), exit: false }
+    }
+
+    /// Entradas nuevas por debajo de la vista (mientras el seguimiento está
+    /// pausado o desactivado).
+    pub fn unseen(&self, vis: usize) -> usize {
+        serial::log_seq().saturating_sub(self.top + vis)
+    }
+
+    /// Incorpora las entradas llegadas desde el último refresco. Devuelve
+    /// true si hay que redibujar.
+    pub fn refresh(&mut self, vis: usize) -> bool {
+        let seq = serial::log_seq();
+        if seq == self.seen { return false; }
+        self.seen = seq;
+        if self.follow && self.pinned {
+            self.top = max_top(vis);
+        } else {
+            self.top = self.top.max(serial::log_oldest());
+        }
+        true
+    }
+
+    /// Desplaza `n` filas (hacia arriba si `up`) y recalcula `pinned`.
+    pub fn scroll(&mut self, up: bool, n: usize, vis: usize) {
+        let max = max_top(vis);
+        self.top = if up { self.top.saturating_sub(n) } else { self.top + n };
+        self.top = self.top.clamp(serial::log_oldest(), max);
+        self.pinned = self.top == max;
+    }
+
+    pub fn handle_key(&mut self, key: Key, vis: usize) {
+        match key {
+            Key::Escape | Key::Char(b'q') | Key::Char(b'Q') => self.exit = true,
+            Key::Up       => self.scroll(true,  1, vis),
+            Key::Down     => self.scroll(false, 1, vis),
+            Key::PageUp   => self.scroll(true,  vis, vis),
+            Key::PageDown => self.scroll(false, vis, vis),
+            Key::Home     => self.scroll(true,  usize::MAX, vis),
+            Key::End      => self.scroll(false, usize::MAX / 2, vis),
+            Key::Char(b'f') | Key::Char(b'F') => {
+                self.follow = !self.follow;
+                // Activarlo salta al fondo: si no, no seguiría nada
+                if self.follow { self.scroll(false, usize::MAX / 2, vis); }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Primera fila con la que la última entrada queda en la última fila visible.
+fn max_top(vis: usize) -> usize {
+    serial::log_seq().saturating_sub(vis).max(serial::log_oldest())
+}
+
+/// Filas de log que caben en pantalla (lo usa `main` para el scroll).
+pub fn logview_visible_rows(lay: &Layout) -> usize {
+    let row_h = lay.font_h + 2;
+    let body  = lay.bottom_y.saturating_sub(lay.content_y + 2 * (lay.font_h + 6));
+    (body / row_h).max(1)
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Debug => EdPalette::OFFSET_FG,
+        Level::Info  => EdPalette::WHITE,
+        Level::Ok    => EdPalette::MSG_OK,
+        Level::Warn  => EdPalette::MSG_WARN,
+        Level::Error => EdPalette::MSG_ERR,
+    }
+}
+
+fn push(buf: &mut [u8], pos: &mut usize, s: &[u8]) {
+    for &b in s { if *pos < buf.len() { buf[*pos] = b; *pos += 1; } }
+}
+
+fn push_num(buf: &mut [u8], pos: &mut usize, n: usize) {
+    let mut tmp = [0u8; 20];
+    push(buf, pos, crate::util::fmt::fmt_u64(n as u64, &mut tmp).as_bytes());
+}
+
+/// "[   12.34] [  OK ] TAG  mensaje" — formato común del visor y de `dmesg`.
+pub fn format_entry(e: &LogEntry, out: &mut [u8]) -> usize {
+    let mut lp = 0;
+    let secs  = (e.tick / PIT_HZ as u64) as usize;
+    let cents = (e.tick % PIT_HZ as u64) as usize * 100 / PIT_HZ as usize;
+    let mut sbuf = [0u8; 20]; let mut sp = 0;
+    push_num(&mut sbuf, &mut sp, secs);
+    push(out, &mut lp, b"[");
+    for _ in sp..5 { push(out, &mut lp, b" "); }
+    push(out, &mut lp, &sbuf[..sp]);
+    push(out, &mut lp, &[b'.', b'0' + (cents / 10) as u8, b'0' + (cents % 10) as u8]);
+    push(out, &mut lp, b"] ");
+    push(out, &mut lp, e.level.prefix().as_bytes());
+    push(out, &mut lp, b" ");
+    push(out, &mut lp, e.text());
+    lp
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RENDERIZADO
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub fn draw_logview_tab(c: &mut Console, lay: &Layout, v: &LogViewState) {
+    let fw    = lay.fw;
+    let ch    = lay.font_h;
+    let x0    = 8usize;
+    let y0    = lay.content_y;
+    let row_h = ch + 2;
+    let vis   = logview_visible_rows(lay);
+
+    c.fill_rect(0, y0, fw, lay.bottom_y.saturating_sub(y0), EdPalette::ROW_ODD);
+
+    // Cabecera
+    c.fill_rect(x0, y0, fw - x0, ch + 4, EdPalette::HEADER);
+    {
+        let mut hbuf = [0u8; 96]; let mut hp = 0;
+        push(&mut hbuf, &mut hp, b" LOG DEL KERNEL (dmesg)  ");
+        push_num(&mut hbuf, &mut hp, serial::log_seq());
+        push(&mut hbuf, &mut hp, b" entradas desde el arranque");
+        c.write_at_bg(core::str::from_utf8(&hbuf[..hp]).unwrap_or(""), x0 + 4, y0 + 2,
+                      EdPalette::HEADER_TXT, EdPalette::HEADER);
+    }
+
+    let y_rows = y0 + ch + 6;
+    for i in 0..vis {
+        let seq    = v.top + i;
+        let y_row  = y_rows + i * row_h;
+        let row_bg = if seq.is_multiple_of(2) { EdPalette::ROW_EVEN } else { EdPalette::ROW_ODD };
+        c.fill_rect(x0, y_row, fw - x0, row_h, row_bg);
+        let Some(e) = serial::log_entry(seq) else { continue };
+
+        let mut line = [0u8; 128];
+        let lp  = format_entry(&e, &mut line);
+        let max = fw.saturating_sub(x0 + 16) / lay.font_w;
+        c.write_at_bg(core::str::from_utf8(&line[..lp.min(max)]).unwrap_or(""),
+                      x0 + 4, y_row + 1, level_color(e.level), row_bg);
+    }
+
+    // Scrollbar (sobre las entradas que siguen en el ring)
+    let oldest  = serial::log_oldest();
+    let total   = serial::log_seq() - oldest;
+    let total_h = vis * row_h;
+    let sb_x    = fw - 10;
+    c.fill_rect(sb_x, y_rows, 8, total_h, EdPalette::BORDER);
+    if total > vis {
+        let thumb_h = (total_h * vis / total).max(8);
+        // `top` pudo quedar atrás si el ring pisó entradas desde el último clamp
+        let thumb_y = y_rows + (v.top.saturating_sub(oldest) * total_h) / total;
+        let th      = thumb_h.min((y_rows + total_h).saturating_sub(thumb_y));
+        c.fill_rect(sb_x + 1, thumb_y, 6, th, EdPalette::HEADER);
+    } else {
+        c.fill_rect(sb_x + 1, y_rows, 6, total_h, EdPalette::HEADER);
+    }
+
+    // Barra de estado: modo de seguimiento + entradas pendientes
+    let y_st = y_rows + total_h + 4;
+    c.fill_rect(x0, y_st, fw - x0, ch + 4, EdPalette::BORDER);
+    let (mode, mcol): (&str, Color) = if !v.follow { (" SEGUIR: OFF ", EdPalette::SHORTCUT) }
+                                      else if v.pinned { (" SEGUIR: ON ", EdPalette::MSG_OK) }
+                                      else { (" SEGUIR: PAUSADO ", EdPalette::MSG_WARN) };
+    c.write_at_bg(mode, x0 + 4, y_st + 2, mcol, EdPalette::BORDER);
+    let mut sbuf = [0u8; 120]; let mut sp = 0;
+    let pending = v.unseen(vis);
+    if pending > 0 {
+        push(&mut sbuf, &mut sp, b" +");
+        push_num(&mut sbuf, &mut sp, pending);
+        push(&mut sbuf, &mut sp, b" nuevas  ");
+    }
+    push(&mut sbuf, &mut sp, b"  Flechas/RePag/AvPag/Inicio/Fin = Scroll   F = Seguir   Esc/Q = Salir");
+    c.write_at_bg(core::str::from_utf8(&sbuf[..sp]).unwrap_or(""),
+                  x0 + 4 + mode.len() * lay.font_w, y_st + 2, EdPalette::SHORTCUT, EdPalette::BORDER);
+}
@@ -1,24 +1,81 @@
-// console/terminal/mod.rs — PORTIX Kernel v0.7.5
+// console/terminal/mod.rs — PORTIX Kernel
 // Struct Terminal + toda la lógica core.
 // CAMBIOS v0.7.5:
 //   - Añadido campo `cwd` / `cwd_len` para directorio de trabajo actual.
 //   - El CWD persiste entre comandos dentro de la sesión.
 //   - El CWD inicial es "/home/user" (coincide con mkfs).
+// CAMBIOS v0.8.1:
+//   - Códigos de retorno: `last_ok` guarda el éxito/fallo del último comando.
+//     Un comando falla si escribe una línea LineColor::Error o llama a `fail()`.
+//   - `run_command()` separa parseo + dispatch de `enter()` para que el
+//     ejecutor de scripts (`source`) reutilice exactamente el mismo camino.
+//   - Campo `viewer`: visor hexadecimal de archivos (comando `view`).
+//   - Líneas sticky: copias fijadas en el tope del historial (máx. STICKY_MAX).
+//     Sobreviven a `clear` y no cuentan para el scroll: `max_scroll` y
+//     `visible_range` descuentan sus filas del área visible.
+//   - Banner persistente (`banner-set`/`banner-clear`): cabecera fija sobre
+//     las líneas sticky; tampoco cuenta para el scroll ni la borra `clear`.
+//   - Redirección `cmd > archivo` / `cmd >> archivo`: durante el dispatch
+//     `write_bytes` desvía la salida a REDIR_BUF (las líneas de error siguen
+//     yendo a pantalla, como stderr) y al terminar se escribe en FAT32.
+//   - El prompt y el eco del comando se colorean con el resultado (`last_ok`,
+//     `TermLine::ok`). `clear` vuelve al color neutro.
+//   - Autoscroll solo si la vista estaba al fondo; leyendo más arriba, la
+//     salida nueva no arrastra la vista (`unseen` cuenta lo que llegó).
+//   - Sesión remota por COM1 (`remote on`, ver remote.rs): entrada desde el
+//     puerto serie y salida espejada allí.
+//   - Rutas clickables en la salida (ver links.rs): cada TermLine guarda sus
+//     regiones de enlace; un clic abre el archivo en el IDE o la carpeta en
+//     el Explorador.
+//   - `waitkey` (ver commands/script.rs): `key_wait` pide al loop principal
+//     la siguiente tecla para reanudar o abortar el script en pausa.
+//   - Vista dividida (`split`, ver split.rs): panel superior congelado con
+//     historial antiguo y scroll propio; el input sigue en el inferior.
+//   - Modo presentación (`present-mode on`): la pestaña dibuja historial e
+//     input con la fuente x2 (ui/tabs/terminal.rs); el ring buffer no cambia.
+//   - Campo `logview`: visor del log del kernel con auto-scroll (`dmesg -w`,
+//     ver logview.rs); `log_follow` es su modo inicial (`scroll-sync`).
+//   - `regwatch` (ver commands/debug.rs): como `waitkey`, el loop principal
+//     muestrea el registro en cada vuelta y la siguiente tecla lo detiene.
+//   - `clear --keep`: `clear_mark` oculta lo anterior mientras la vista está
+//     al fondo, sin tocar `line_count`; al subir se ve con un separador.
+//   - Alias (`alias nombre=comando`, ver alias.rs): `exec_line` expande la
+//     primera palabra antes del dispatch; un solo nivel, sin recursión. Si
+//     la expansión no cabe en INPUT_MAX el comando falla con error.
+//   - `watch` (ver commands/watch.rs): `watch_active` lo consulta el loop
+//     principal, que re-ejecuta el comando y redibuja su salida en el sitio.
+//   - `atareset` (ver commands/disk.rs): `ata_reset` pide al loop principal
+//     el reset del canal, que es quien puede actualizar `hw.disks`.
 
 #![allow(dead_code)]
 
 pub mod fmt;
 pub mod commands;
 pub mod editor;
+pub mod viewer;
+pub mod logview;
+pub mod remote;
+pub mod links;
+pub mod split;
+pub mod alias;
 
 // ── Constantes públicas ───────────────────────────────────────────────────────
 
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.7.5";
+
 pub const TERM_COLS:   usize = 92;
 pub const TERM_ROWS:   usize = 128;
 pub const INPUT_MAX:   usize = 80;
 pub const PROMPT:      &[u8] = b"PORTIX> ";
 pub const SCROLL_STEP: usize = 3;
 pub const CWD_MAX:     usize = 256;
+pub const STICKY_MAX:  usize = 4;
+pub const BANNER_MAX:  usize = 8;
+pub const REDIRECT_MAX: usize = 16 * 1024;
+
+/// Salida capturada por una redirección en curso (una sola a la vez).
+static mut REDIR_BUF: [u8; REDIRECT_MAX] = [0u8; REDIRECT_MAX];
 
 // ── Tipos públicos ────────────────────────────────────────────────────────────
 
@@ -30,10 +87,18 @@ pub struct TermLine {
     pub buf:   [u8; TERM_COLS],
     pub len:   usize,
     pub color: LineColor,
+    /// Solo en ecos del prompt: resultado del comando (None = sin ejecutar)
+    pub ok:    Option<bool>,
+    /// Rutas clickables (inicio, longitud) detectadas al escribir (links.rs)
+    pub links: [(u8, u8); links::LINK_MAX],
+    pub link_count: u8,
 }
 impl TermLine {
     pub const fn empty() -> Self {
-        TermLine { buf: [0; TERM_COLS], len: 0, color: LineColor::Normal }
+        TermLine {
+            buf: [0; TERM_COLS], len: 0, color: LineColor::Normal, ok: None,
+            links: [(0, 0); links::LINK_MAX], link_count: 0,
+        }
     }
 }
 
@@ -47,17 +112,64 @@ pub struct Terminal {
     pub input:         [u8; INPUT_MAX],
     pub input_len:     usize,
     pub cursor_vis:    bool,
-    // Scroll
+    // Scroll: 0 = pegado al fondo (la salida nueva lo arrastra)
     pub scroll_offset: usize,
+    // Filas visibles del último scroll manual (para acotar el offset)
+    pub(crate) view_rows: usize,
+    // Líneas llegadas mientras se leía más arriba
+    pub unseen: usize,
+    // Primera línea tras el último `clear --keep` (0 = ninguno): abajo del
+    // todo la vista empieza aquí; al subir se ve lo anterior con separador
+    pub clear_mark: usize,
     // Historial de comandos (ring buffer de 16)
     pub(crate) hist_cmds:  [[u8; INPUT_MAX]; 16],
     pub(crate) hist_lens:  [usize; 16],
     pub(crate) hist_count: usize,
     // Editor (hex o texto) — Some = editor activo, None = terminal normal
     pub editor: Option<editor::EditorState>,
+    // Visor hex de archivos (`view`) — Some = visor activo
+    pub viewer: Option<viewer::ViewerState>,
+    // Visor del log del kernel (`dmesg -w`) — Some = visor activo
+    pub logview: Option<logview::LogViewState>,
+    // Auto-scroll con el que se abre el visor del log (`scroll-sync`)
+    pub log_follow: bool,
     // Directorio de trabajo actual (CWD), persiste entre comandos
     pub cwd:     [u8; CWD_MAX],
     pub cwd_len: usize,
+    // Resultado del último comando: None = aún no se ha ejecutado ninguno
+    pub last_ok:    Option<bool>,
+    pub(crate) cmd_failed: bool,
+    // Líneas fijadas en el tope (no forman parte del ring buffer)
+    pub sticky:       [TermLine; STICKY_MAX],
+    pub sticky_count: usize,
+    // Banner de cabecera persistente
+    pub banner:       [TermLine; BANNER_MAX],
+    pub banner_count: usize,
+    // Comando que generó el banner (para `config save`); 0 = sin banner
+    pub banner_cmd:     [u8; INPUT_MAX],
+    pub banner_cmd_len: usize,
+    // Redirección de salida en curso (`cmd > archivo`)
+    pub(crate) redirecting:    bool,
+    pub(crate) redir_len:      usize,
+    pub(crate) redir_overflow: bool,
+    // `clear` durante el comando en curso: no fijar `last_ok`
+    pub(crate) status_reset:   bool,
+    // Sesión remota por puerto serie
+    pub(crate) remote: remote::RemoteSession,
+    // Modo presentación: fuente x2 en la pestaña Terminal
+    pub present: bool,
+    // Vista dividida: fin (lógico, exclusivo) del panel superior congelado
+    pub split_end: Option<usize>,
+    // `waitkey`: la próxima tecla reanuda (o aborta con Esc) el script
+    pub(crate) key_wait: bool,
+    // `regwatch` en curso: registro MMIO muestreado desde el loop principal
+    pub(crate) regwatch: Option<commands::debug::RegWatch>,
+    // `atareset` pendiente: canal (0/1) que resetea el loop principal
+    pub(crate) ata_reset: Option<u8>,
+    // Alias de comandos (`alias nombre=comando`)
+    pub(crate) aliases: alias::AliasTable,
+    // `watch` en curso: comando que el loop principal re-ejecuta
+    pub(crate) watch: Option<commands::watch::Watch>,
 }
 
 impl Terminal {
@@ -74,12 +186,38 @@ impl Terminal {
             input_len:     0,
             cursor_vis:    true,
             scroll_offset: 0,
+            view_rows:     0,
+            unseen:        0,
+            clear_mark:    0,
             hist_cmds:     [[0u8; INPUT_MAX]; 16],
             hist_lens:     [0usize; 16],
             hist_count:    0,
             editor:        None,
+            viewer:        None,
+            logview:       None,
+            log_follow:    true,
             cwd,
             cwd_len:       10, // len("/home/user")
+            last_ok:       None,
+            cmd_failed:    false,
+            sticky:        [TermLine::empty(); STICKY_MAX],
+            sticky_count:  0,
+            banner:        [TermLine::empty(); BANNER_MAX],
+            banner_count:  0,
+            banner_cmd:     [0u8; INPUT_MAX],
+            banner_cmd_len: 0,
+            redirecting:    false,
+            redir_len:      0,
+            redir_overflow: false,
+            status_reset:   false,
+            remote:         remote::RemoteSession::IDLE,
+            present:        false,
+            split_end:      None,
+            key_wait:       false,
+            regwatch:       None,
+            ata_reset:      None,
+            aliases:        alias::AliasTable::EMPTY,
+            watch:          None,
         }
     }
 
@@ -90,6 +228,10 @@ impl Terminal {
     }
 
     pub fn write_bytes(&mut self, s: &[u8], color: LineColor) {
+        if color == LineColor::Error { self.cmd_failed = true; }
+        else if self.redirecting { self.redirect_bytes(s); return; }
+        self.remote_mirror(s, color);
+        let before    = self.line_count;
         let mut start = 0;
         loop {
             let end   = (start + TERM_COLS).min(s.len());
@@ -100,11 +242,41 @@ impl Terminal {
             for b in &mut self.lines[row].buf[len..] { *b = 0; }
             self.lines[row].len   = len;
             self.lines[row].color = color;
+            self.lines[row].ok    = None;
+            self.lines[row].scan_links();
             self.line_count += 1;
             start = end;
             if start >= s.len() { break; }
         }
-        self.scroll_offset = 0;
+        self.follow_output(self.line_count - before);
+    }
+
+    /// Al fondo, la salida nueva mantiene la vista al fondo. Leyendo más
+    /// arriba, el offset crece con las líneas nuevas para seguir mostrando
+    /// la misma línea lógica; si el ring buffer ya la descartó, la vista
+    /// queda en la línea más antigua disponible.
+    fn follow_output(&mut self, added: usize) {
+        if self.scroll_offset == 0 { return; }
+        self.unseen += added;
+        let max = if self.view_rows > 0 {
+            self.max_scroll(self.view_rows)
+        } else {
+            self.line_count - self.oldest_logical()
+        };
+        self.scroll_offset = (self.scroll_offset + added).min(max);
+    }
+
+    /// Anexa una línea (más '\n') al buffer de redirección. Lo que no cabe
+    /// se descarta y se avisa al terminar.
+    fn redirect_bytes(&mut self, s: &[u8]) {
+        // SAFETY: kernel monohilo; REDIR_BUF solo se usa con `redirecting`.
+        let buf = unsafe { &mut *core::ptr::addr_of_mut!(REDIR_BUF) };
+        let free = REDIRECT_MAX - self.redir_len;
+        if s.len() + 1 > free { self.redir_overflow = true; return; }
+        buf[self.redir_len..self.redir_len + s.len()].copy_from_slice(s);
+        self.redir_len += s.len();
+        buf[self.redir_len] = b'\n';
+        self.redir_len += 1;
     }
 
     pub fn write_empty(&mut self) { self.write_bytes(b"", LineColor::Normal); }
@@ -126,6 +298,18 @@ impl Terminal {
         self.write_bytes(&buf[..pos], LineColor::Header);
     }
 
+    /// true mientras `waitkey` espera una tecla.
+    pub fn waiting_key(&self) -> bool { self.key_wait }
+
+    /// Hay un `regwatch` activo (la próxima tecla lo detiene).
+    pub fn watching(&self) -> bool { self.regwatch.is_some() }
+
+    /// Hay un `watch` activo (Esc o q lo detienen).
+    pub fn watch_active(&self) -> bool { self.watch.is_some() }
+
+    /// Marca el comando en curso como fallido sin escribir nada (p. ej. `test`).
+    pub fn fail(&mut self) { self.cmd_failed = true; }
+
     // ══ CWD ══════════════════════════════════════════════════════════════════
 
     /// Retorna el CWD como &str (best-effort, '/' si UTF-8 falla).
@@ -141,11 +325,21 @@ impl Terminal {
     }
 
     #[inline]
-    fn oldest_logical(&self) -> usize {
+    pub(crate) fn oldest_logical(&self) -> usize {
         if self.line_count <= TERM_ROWS { 0 } else { self.line_count - TERM_ROWS }
     }
 
+    /// Filas de cabecera fija: banner + líneas sticky.
+    pub fn fixed_rows(&self) -> usize { self.banner_count + self.sticky_count }
+
+    /// Filas del área de historial que quedan para el scroll normal una vez
+    /// reservadas la cabecera fija y el panel superior de `split`.
+    pub fn scroll_rows(&self, max_visible: usize) -> usize {
+        max_visible.saturating_sub(self.fixed_rows() + self.split_rows(max_visible))
+    }
+
     pub fn max_scroll(&self, max_visible: usize) -> usize {
+        let max_visible = self.scroll_rows(max_visible);
         let available = self.line_count.saturating_sub(self.oldest_logical());
         available.saturating_sub(max_visible)
     }
@@ -153,26 +347,132 @@ impl Terminal {
     // ══ Scroll ════════════════════════════════════════════════════════════════
 
     pub fn scroll_up(&mut self, lines: usize, max_visible: usize) {
-        let max = self.max_scroll(max_visible);
-        self.scroll_offset = (self.scroll_offset + lines).min(max);
+        self.set_scroll(self.scroll_offset.saturating_add(lines), max_visible);
     }
     pub fn scroll_down(&mut self, lines: usize) {
         self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+        if self.scroll_offset == 0 { self.unseen = 0; }
     }
-    pub fn scroll_to_bottom(&mut self) { self.scroll_offset = 0; }
+    /// Fija el offset (acotado a `max_scroll`), p. ej. al arrastrar la barra.
+    pub fn set_scroll(&mut self, offset: usize, max_visible: usize) {
+        self.view_rows     = max_visible;
+        self.scroll_offset = offset.min(self.max_scroll(max_visible));
+        if self.scroll_offset == 0 { self.unseen = 0; }
+    }
+    pub fn scroll_to_bottom(&mut self) { self.scroll_offset = 0; self.unseen = 0; }
     pub fn at_bottom(&self)  -> bool   { self.scroll_offset == 0 }
 
     pub fn visible_range(&self, max_visible: usize) -> (usize, usize) {
         if self.line_count == 0 { return (0, 0); }
+        let max_visible     = self.scroll_rows(max_visible);
         let oldest          = self.oldest_logical();
         let total_available = self.line_count - oldest;
         let count           = total_available.min(max_visible);
         let bottom_start    = self.line_count.saturating_sub(count);
-        let start           = bottom_start.saturating_sub(self.scroll_offset).max(oldest);
+        let mut start       = bottom_start.saturating_sub(self.scroll_offset).max(oldest);
+        // Tras `clear --keep`, abajo del todo no se ve lo anterior a la marca
+        if self.scroll_offset == 0 && self.clear_mark > start {
+            start = self.clear_mark.min(self.line_count);
+        }
         let end             = (start + count).min(self.line_count);
         (start, end.saturating_sub(start))
     }
 
+    // ══ Líneas sticky ═════════════════════════════════════════════════════════
+
+    /// Última línea de salida del historial, saltando los ecos del prompt.
+    pub fn last_output_line(&self) -> Option<TermLine> {
+        let oldest = self.oldest_logical();
+        (oldest..self.line_count).rev()
+            .map(|li| *self.line_at(li))
+            .find(|l| l.color != LineColor::Prompt)
+    }
+
+    fn sticky_index(&self, line: &TermLine) -> Option<usize> {
+        self.sticky[..self.sticky_count].iter().position(|s| {
+            s.len == line.len && s.color == line.color && s.buf[..s.len] == line.buf[..line.len]
+        })
+    }
+
+    /// Fija una copia de `line` en el tope. false si ya no caben más.
+    pub fn pin_line(&mut self, line: &TermLine) -> bool {
+        if self.sticky_count >= STICKY_MAX { return false; }
+        self.sticky[self.sticky_count] = *line;
+        self.sticky_count += 1;
+        true
+    }
+
+    /// Quita la línea sticky `idx` (0 = la de arriba).
+    pub fn unpin(&mut self, idx: usize) -> bool {
+        if idx >= self.sticky_count { return false; }
+        self.sticky.copy_within(idx + 1..self.sticky_count, idx);
+        self.sticky_count -= 1;
+        self.sticky[self.sticky_count] = TermLine::empty();
+        true
+    }
+
+    /// Fija `line` o, si ya estaba fijada, la desfija.
+    /// Some(true) = fijada, Some(false) = desfijada, None = sin hueco libre.
+    pub fn toggle_sticky(&mut self, line: &TermLine) -> Option<bool> {
+        if let Some(i) = self.sticky_index(line) {
+            self.unpin(i);
+            Some(false)
+        } else if self.pin_line(line) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    pub fn clear_sticky(&mut self) {
+        self.sticky = [TermLine::empty(); STICKY_MAX];
+        self.sticky_count = 0;
+    }
+
+    /// Mueve al banner las líneas escritas desde `from` (número lógico) y las
+    /// retira del historial. Se descartan las líneas vacías de los extremos.
+    /// Devuelve cuántas líneas quedaron en el banner.
+    pub fn capture_banner(&mut self, from: usize) -> usize {
+        let from  = from.max(self.oldest_logical());
+        let mut a = from;
+        let mut b = self.line_count;
+        while a < b && self.line_at(a).len == 0     { a += 1; }
+        while b > a && self.line_at(b - 1).len == 0 { b -= 1; }
+
+        self.banner = [TermLine::empty(); BANNER_MAX];
+        self.banner_count = (b - a).min(BANNER_MAX);
+        for i in 0..self.banner_count {
+            self.banner[i] = *self.line_at(a + i);
+        }
+        self.truncate_lines(from);
+        self.scroll_to_bottom();
+        self.banner_count
+    }
+
+    /// Descarta las líneas desde `from` (número lógico) hasta el final, como
+    /// si no se hubieran escrito. Lo usan el banner y el refresco de `watch`.
+    pub(crate) fn truncate_lines(&mut self, from: usize) {
+        if from >= self.line_count { return; }
+        for li in from.max(self.oldest_logical())..self.line_count {
+            self.lines[li % TERM_ROWS] = TermLine::empty();
+        }
+        self.line_count = from;
+        self.clear_mark = self.clear_mark.min(from);
+    }
+
+    pub fn clear_banner(&mut self) {
+        self.banner = [TermLine::empty(); BANNER_MAX];
+        self.banner_count = 0;
+        self.banner_cmd_len = 0;
+    }
+
+    /// Escribe una línea normal y además la fija en el tope (p. ej. banners).
+    pub fn write_sticky(&mut self, s: &[u8], color: LineColor) {
+        self.write_bytes(s, color);
+        let line = *self.line_at(self.line_count - 1);
+        self.pin_line(&line);
+    }
+
     // ══ Input ═════════════════════════════════════════════════════════════════
 
     pub fn type_char(&mut self, c: u8) {
@@ -188,10 +488,25 @@ impl Terminal {
         self.input_len = 0;
         for b in &mut self.input { *b = 0; }
     }
+    /// Vacía el historial. El banner y las líneas sticky se conservan.
+    /// El prompt vuelve al color neutro (sin resultado previo).
     pub fn clear_history(&mut self) {
-        for l in &mut self.lines { l.len = 0; l.buf[0] = 0; }
+        for l in &mut self.lines { l.len = 0; l.buf[0] = 0; l.link_count = 0; }
         self.line_count    = 0;
-        self.scroll_offset = 0;
+        self.clear_mark    = 0;
+        self.scroll_to_bottom();
+        self.last_ok       = None;
+        self.status_reset  = true;
+    }
+
+    /// `clear --keep`: deja la pantalla limpia sin borrar el ring buffer.
+    /// `line_count` no cambia; la vista vuelve al fondo y empieza en la marca,
+    /// y al subir con el scroll reaparece el historial anterior.
+    pub fn clear_keep(&mut self) {
+        self.clear_mark    = self.line_count;
+        self.scroll_to_bottom();
+        self.last_ok       = None;
+        self.status_reset  = true;
     }
 
     // ══ Enter ════════════════════════════════════════════════════════════════
@@ -201,6 +516,25 @@ impl Terminal {
         hw:  &crate::arch::hardware::HardwareInfo,
         pci: &crate::drivers::bus::pci::PciBus,
     ) {
+        let mut line = [0u8; INPUT_MAX];
+        let il = self.input_len.min(INPUT_MAX);
+        line[..il].copy_from_slice(&self.input[..il]);
+        self.submit(&line[..il], hw, pci);
+        self.clear_input();
+        self.remote_prompt();
+    }
+
+    /// Ejecuta `line` como si se hubiera tecleado en el prompt (eco,
+    /// historial y resultado en el eco), sin tocar la línea de entrada.
+    /// Lo usan `enter` y el minibuffer del IDE.
+    pub fn submit(
+        &mut self,
+        line: &[u8],
+        hw:   &crate::arch::hardware::HardwareInfo,
+        pci:  &crate::drivers::bus::pci::PciBus,
+    ) -> bool {
+        // Ejecutar un comando siempre vuelve al fondo para ver su salida
+        self.scroll_to_bottom();
         // Echo con CWD en el prompt: "user@portix:/home/user> comando"
         let mut echo = [0u8; INPUT_MAX + 60];
         let mut ep = 0;
@@ -209,24 +543,124 @@ impl Terminal {
         let cl = self.cwd_len.min(40);
         echo[ep..ep + cl].copy_from_slice(&self.cwd[..cl]); ep += cl;
         for b in b"$ " { echo[ep] = *b; ep += 1; }
-        let il = self.input_len.min(INPUT_MAX);
-        echo[ep..ep + il].copy_from_slice(&self.input[..il]); ep += il;
+        let il = line.len().min(INPUT_MAX);
+        echo[ep..ep + il].copy_from_slice(&line[..il]); ep += il;
         self.write_bytes(&echo[..ep], LineColor::Prompt);
+        let echo_li = self.line_count - 1;
 
         // Historial
-        if self.input_len > 0 {
+        if il > 0 {
             let slot = self.hist_count % 16;
-            self.hist_cmds[slot][..self.input_len].copy_from_slice(&self.input[..self.input_len]);
-            self.hist_lens[slot] = self.input_len;
+            self.hist_cmds[slot][..il].copy_from_slice(&line[..il]);
+            self.hist_lens[slot] = il;
             self.hist_count += 1;
         }
 
-        // Parsear cmd y args
+        let ok = self.run_command(&line[..il], hw, pci);
+        // Colorear el eco (si sigue en el historial: `clear` lo habrá borrado)
+        if il > 0 && echo_li < self.line_count && self.line_at(echo_li).color == LineColor::Prompt {
+            self.lines[echo_li % TERM_ROWS].ok = Some(ok);
+        }
+        ok
+    }
+
+    /// Cambia el cwd a `path` (absoluta) y lista su contenido. Lo usa
+    /// "Abrir en terminal" del Explorador.
+    pub fn open_dir(
+        &mut self,
+        path: &[u8],
+        hw:   &crate::arch::hardware::HardwareInfo,
+        pci:  &crate::drivers::bus::pci::PciBus,
+    ) {
+        let l = path.len().min(CWD_MAX);
+        self.cwd[..l].copy_from_slice(&path[..l]);
+        self.cwd_len = l;
+        self.scroll_to_bottom();
+        self.run_command(b"ls", hw, pci);
+        self.remote_prompt();
+    }
+
+    /// Ejecuta una línea de comando, con redirección opcional `> archivo` o
+    /// `>> archivo` al final (salvo en `calc`, donde `>>` es un operador).
+    /// Devuelve true si el comando terminó con éxito (sin líneas de error).
+    /// Una línea vacía no cambia `last_ok` y cuenta como éxito.
+    pub fn run_command(
+        &mut self,
+        line: &[u8],
+        hw:   &crate::arch::hardware::HardwareInfo,
+        pci:  &crate::drivers::bus::pci::PciBus,
+    ) -> bool {
+        let first = fmt::trim(line).split(|&b| b == b' ').next().unwrap_or(b"");
+        if commands::is_expression(first) {
+            return self.exec_line(line, hw, pci);
+        }
+        let Some(gt) = line.iter().position(|&b| b == b'>') else {
+            return self.exec_line(line, hw, pci);
+        };
+
+        let cmd_part = fmt::trim(&line[..gt]);
+        let append   = line.get(gt + 1) == Some(&b'>');
+        let target   = fmt::trim(&line[gt + if append { 2 } else { 1 }..]);
+        let cmd_tok  = cmd_part.split(|&b| b == b' ').next().unwrap_or(b"");
+
+        let err = if cmd_part.is_empty() {
+            Some("  redireccion: falta el comando")
+        } else if target.is_empty() || target.contains(&b'>') || target.contains(&b' ') {
+            Some("  redireccion: uso 'comando > archivo' o 'comando >> archivo'")
+        } else if self.redirecting {
+            Some("  redireccion: no se admiten redirecciones anidadas")
+        } else if commands::is_interactive(cmd_tok) {
+            Some("  redireccion: no se puede redirigir un comando interactivo")
+        } else { None };
+        if let Some(msg) = err {
+            self.write_line(msg, LineColor::Error);
+            self.last_ok = Some(false);
+            return false;
+        }
+
+        self.redirecting    = true;
+        self.redir_len      = 0;
+        self.redir_overflow = false;
+        let mut ok = self.exec_line(cmd_part, hw, pci);
+        self.redirecting = false;
+
+        // SAFETY: ver redirect_bytes; la redirección ya terminó.
+        let data = unsafe { &(&*core::ptr::addr_of!(REDIR_BUF))[..self.redir_len] };
+        if !commands::disk::write_text_file(self, b"redireccion", target, data, append) {
+            ok = false;
+        } else if self.redir_overflow {
+            self.write_line("  redireccion: salida truncada a 16 KiB", LineColor::Warning);
+        }
+        self.last_ok = Some(ok);
+        ok
+    }
+
+    /// Parsea `line` en comando + argumentos y lo despacha.
+    fn exec_line(
+        &mut self,
+        line: &[u8],
+        hw:   &crate::arch::hardware::HardwareInfo,
+        pci:  &crate::drivers::bus::pci::PciBus,
+    ) -> bool {
         let mut cmd_buf  = [0u8; INPUT_MAX];
         let mut args_buf = [0u8; INPUT_MAX];
+        let mut expanded = [0u8; INPUT_MAX];
+        let line = match self.aliases.expand(line, &mut expanded) {
+            Some(n) if n > INPUT_MAX => {
+                let mut buf = [0u8; TERM_COLS]; let mut pos = 0;
+                fmt::append_str(&mut buf, &mut pos, b"  alias: la linea expandida supera ");
+                fmt::append_u32(&mut buf, &mut pos, INPUT_MAX as u32);
+                fmt::append_str(&mut buf, &mut pos, b" caracteres");
+                self.write_bytes(&buf[..pos], LineColor::Error);
+                self.last_ok = Some(false);
+                return false;
+            }
+            Some(n) => &expanded[..n],
+            None    => line,
+        };
         let cmd_len; let args_len;
         {
-            let raw     = &self.input[..self.input_len];
+            let raw     = line;
             let start   = raw.iter().position(|&b| b != b' ').unwrap_or(0);
             let trimmed = &raw[start..];
             let end     = trimmed.iter().rposition(|&b| b != b' ').map(|i| i + 1).unwrap_or(0);
@@ -243,9 +677,109 @@ impl Terminal {
             args_buf[..args_len].copy_from_slice(&args[..args_len]);
         }
 
-        if cmd_len == 0 { self.clear_input(); return; }
+        if cmd_len == 0 { return true; }
 
+        // Guardar el estado del llamador: un `source` anidado no debe
+        // perder un fallo ya registrado por el script que lo invoca.
+        let outer_failed = self.cmd_failed;
+        self.cmd_failed   = false;
+        self.status_reset = false;
         commands::dispatch(self, &cmd_buf[..cmd_len], &args_buf[..args_len], hw, pci);
-        self.clear_input();
+        let ok = !self.cmd_failed;
+        self.cmd_failed = outer_failed;
+        if !self.status_reset { self.last_ok = Some(ok); }
+        ok
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Terminal con `n` líneas de historial (en el heap: ocupa decenas de KiB).
+    fn term_with_lines(n: usize) -> alloc::boxed::Box<Terminal> {
+        let mut t = alloc::boxed::Box::new(Terminal::new());
+        for _ in 0..n { t.write_line("x", LineColor::Normal); }
+        t
+    }
+
+    #[test]
+    fn line_scroll_stops_at_oldest_line() {
+        // Ctrl+↑ repetido: de una en una hasta la línea más antigua
+        let mut t = term_with_lines(50);
+        t.scroll_up(1, 20);
+        assert_eq!(t.scroll_offset, 1);
+        for _ in 0..100 { t.scroll_up(1, 20); }
+        assert_eq!(t.scroll_offset, 30);
+        assert_eq!(t.scroll_offset, t.max_scroll(20));
+        // Ctrl+↓ vuelve al final y no pasa de él
+        t.scroll_down(1);
+        assert_eq!(t.scroll_offset, 29);
+        for _ in 0..100 { t.scroll_down(1); }
+        assert!(t.at_bottom());
+    }
+
+    #[test]
+    fn line_scroll_with_short_history_does_nothing() {
+        let mut t = term_with_lines(10);
+        t.scroll_up(1, 20);
+        assert!(t.at_bottom());
+        t.scroll_down(1);
+        assert!(t.at_bottom());
+    }
+
+    #[test]
+    fn line_scroll_limit_discounts_pinned_rows() {
+        let mut t = term_with_lines(50);
+        let pinned = t.lines[0];
+        assert!(t.pin_line(&pinned));
+        for _ in 0..100 { t.scroll_up(1, 20); }
+        // Una fila menos para el historial: una línea más de recorrido
+        assert_eq!(t.scroll_offset, 31);
+    }
+
+    #[test]
+    fn line_scroll_limit_after_ring_wraps() {
+        let mut t = term_with_lines(TERM_ROWS + 40);
+        for _ in 0..1000 { t.scroll_up(1, 20); }
+        assert_eq!(t.scroll_offset, TERM_ROWS - 20);
+    }
+
+    #[test]
+    fn clear_keep_preserves_history_below_the_mark() {
+        let mut t = term_with_lines(50);
+        t.scroll_up(5, 20);
+        t.clear_keep();
+        // El ring buffer no se toca: sólo se mueve la marca y se vuelve al fondo
+        assert_eq!(t.line_count, 50);
+        assert_eq!(t.clear_mark, 50);
+        assert!(t.at_bottom());
+        assert_eq!(t.visible_range(20), (50, 0));
+        // Lo nuevo aparece a partir de la marca
+        for _ in 0..3 { t.write_line("y", LineColor::Normal); }
+        assert_eq!(t.line_count, 53);
+        assert_eq!(t.visible_range(20), (50, 3));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn clear_keep_history_reappears_when_scrolling_up() {
+        let mut t = term_with_lines(50);
+        t.clear_keep();
+        for _ in 0..3 { t.write_line("y", LineColor::Normal); }
+        t.scroll_up(1, 20);
+        assert_eq!(t.visible_range(20), (32, 20));
+        // Al volver al fondo se respeta otra vez la marca
+        t.scroll_down(1);
+        assert_eq!(t.visible_range(20), (50, 3));
+    }
+
+    #[test]
+    fn clear_history_resets_the_keep_mark() {
+        let mut t = term_with_lines(50);
+        t.clear_keep();
+        t.clear_history();
+        assert_eq!(t.line_count, 0);
+        assert_eq!(t.clear_mark, 0);
+        t.write_line("z", LineColor::Normal);
+        assert_eq!(t.visible_range(20), (0, 1));
+    }
+}
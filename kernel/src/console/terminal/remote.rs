@@ -0,0 +1,352 @@
+// console/terminal/remote.rs — PORTIX Kernel
+// Sesión remota por COM1 (`remote on`): la entrada del terminal se acepta
+// también desde el puerto serie y toda la salida se espeja allí, de modo que
+// un operador en el host controla el sistema mientras se ve en pantalla.
+//
+// Eco: cada carácter se envía al puerto serie UNA vez, venga del teclado
+// local o del host (el terminal del host no hace eco local). Por eso las
+// líneas de eco del prompt (LineColor::Prompt) no se espejan: el host ya vio
+// el comando carácter a carácter; en su lugar se envía un prompt propio al
+// terminar cada comando.
+//
+// Los bytes se traducen a `Key` igual que el teclado (`serial_key`, y las
+// secuencias CSI del host con `csi_key`). En el prompt se procesan aquí
+// mismo; con el editor o un visor abiertos se encolan y el bucle principal
+// los entrega por el mismo camino que las teclas locales.
+
+use crate::drivers::input::keyboard::Key;
+use crate::drivers::serial;
+use super::{Terminal, LineColor, INPUT_MAX};
+
+/// Bytes de entrada serie procesados como máximo por frame.
+const POLL_MAX: usize = 64;
+/// Teclas encoladas como máximo para el editor/visores por frame.
+pub const REMOTE_KEYS: usize = 16;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Esc { None, Start, Csi }
+
+#[derive(Clone, Copy)]
+pub struct RemoteSession {
+    pub active: bool,
+    esc:        Esc,
+    /// Primer parámetro numérico de la secuencia CSI en curso (`ESC [ 5 ~`).
+    csi_param:  u8,
+    last_cr:    bool,
+    keys:       [(Key, bool); REMOTE_KEYS],
+    nkeys:      usize,
+}
+
+impl RemoteSession {
+    /// Sesión inactiva, sin secuencia de escape a medias.
+    pub const IDLE: Self = RemoteSession {
+        active: false, esc: Esc::None, csi_param: 0, last_cr: false,
+        keys: [(Key::Escape, false); REMOTE_KEYS], nkeys: 0,
+    };
+
+    fn push_key(&mut self, key: Key, ctrl: bool) {
+        if self.nkeys < REMOTE_KEYS {
+            self.keys[self.nkeys] = (key, ctrl);
+            self.nkeys += 1;
+        }
+    }
+
+    /// Decodifica un byte para el editor/visores y encola la tecla. Una
+    /// secuencia CSI puede llegar repartida entre varias ráfagas.
+    fn key_byte(&mut self, b: u8) {
+        match self.esc {
+            Esc::Start if b == b'[' || b == b'O' => {
+                self.esc = Esc::Csi;
+                self.csi_param = 0;
+                return;
+            }
+            // ESC seguido de otra cosa: Escape y el byte se procesa aparte
+            Esc::Start => {
+                self.esc = Esc::None;
+                self.push_key(Key::Escape, false);
+            }
+            Esc::Csi => {
+                if b.is_ascii_digit() {
+                    self.csi_param = self.csi_param.saturating_mul(10).saturating_add(b - b'0');
+                } else if (0x40..=0x7E).contains(&b) {
+                    self.esc = Esc::None;
+                    if let Some(key) = csi_key(self.csi_param, b) {
+                        self.push_key(key, false);
+                    }
+                }
+                return;
+            }
+            Esc::None => {}
+        }
+
+        let after_cr = self.last_cr;
+        self.last_cr = b == b'\r';
+        if b == 0x1B { self.esc = Esc::Start; return; }
+        if b == b'\n' && after_cr { return; }
+        if let Some((key, ctrl)) = serial_key(b) {
+            self.push_key(key, ctrl);
+        }
+    }
+
+    /// Fin de ráfaga: un ESC suelto al final es la tecla Escape, porque las
+    /// secuencias del host empiezan siempre dentro del mismo frame.
+    fn end_burst(&mut self) {
+        if self.esc == Esc::Start {
+            self.esc = Esc::None;
+            self.push_key(Key::Escape, false);
+        }
+    }
+}
+
+/// Traduce un byte recibido por el puerto serie a la tecla equivalente y si
+/// lleva Ctrl. CR y LF son Enter (el par CRLF lo filtra el llamador), BS y
+/// DEL son Backspace, y 0x01-0x1A son Ctrl+letra.
+pub fn serial_key(b: u8) -> Option<(Key, bool)> {
+    match b {
+        b'\r' | b'\n'   => Some((Key::Enter, false)),
+        0x08 | 0x7F     => Some((Key::Backspace, false)),
+        b'\t'           => Some((Key::Tab, false)),
+        0x1B            => Some((Key::Escape, false)),
+        0x01..=0x1A     => Some((Key::Char(b'a' + b - 1), true)),
+        32..=126        => Some((Key::Char(b), false)),
+        _               => None,
+    }
+}
+
+/// Tecla de una secuencia CSI (`ESC [ <param> <final>`) de un terminal
+/// VT100/xterm: flechas, Inicio/Fin y el bloque de edición.
+pub fn csi_key(param: u8, fin: u8) -> Option<Key> {
+    match (fin, param) {
+        (b'A', _) => Some(Key::Up),
+        (b'B', _) => Some(Key::Down),
+        (b'C', _) => Some(Key::Right),
+        (b'D', _) => Some(Key::Left),
+        (b'H', _) | (b'~', 1) | (b'~', 7) => Some(Key::Home),
+        (b'F', _) | (b'~', 4) | (b'~', 8) => Some(Key::End),
+        (b'~', 2) => Some(Key::Insert),
+        (b'~', 3) => Some(Key::Delete),
+        (b'~', 5) => Some(Key::PageUp),
+        (b'~', 6) => Some(Key::PageDown),
+        _ => None,
+    }
+}
+
+impl Terminal {
+    pub fn remote_active(&self) -> bool { self.remote.active }
+
+    /// Activa/desactiva la sesión. false si COM1 no está disponible.
+    pub fn set_remote(&mut self, on: bool) -> bool {
+        if on && !serial::is_ready() { return false; }
+        self.remote = RemoteSession { active: on, ..RemoteSession::IDLE };
+        true
+    }
+
+    /// Copia al puerto serie una línea de salida (CRLF al final).
+    pub(crate) fn remote_mirror(&self, s: &[u8], color: LineColor) {
+        if !self.remote.active || color == LineColor::Prompt { return; }
+        for &b in s {
+            serial::write_byte(if b == b'\t' || (32..127).contains(&b) { b } else { b'.' });
+        }
+        serial::write_str("\n");
+    }
+
+    /// Envía el prompt "root@portix:<cwd>$ " seguido del input en curso.
+    pub fn remote_prompt(&self) {
+        if !self.remote.active { return; }
+        serial::write_str("root@portix:");
+        serial::write_bytes_raw(&self.cwd[..self.cwd_len.min(40)]);
+        serial::write_str("$ ");
+        serial::write_bytes_raw(&self.input[..self.input_len]);
+    }
+
+    /// Procesa la entrada pendiente del puerto serie como si se hubiera
+    /// tecleado localmente. Devuelve true si algo cambió (hay que redibujar).
+    pub fn poll_remote(
+        &mut self,
+        hw:  &crate::arch::hardware::HardwareInfo,
+        pci: &crate::drivers::bus::pci::PciBus,
+    ) -> bool {
+        if !self.remote.active { return false; }
+        let mut changed = false;
+        for _ in 0..POLL_MAX {
+            let Some(b) = serial::read_byte() else { break };
+            if self.fullscreen() {
+                self.remote.key_byte(b);
+            } else {
+                changed |= self.remote_byte(b, hw, pci);
+            }
+            if !self.remote.active { break; }
+        }
+        if self.fullscreen() { self.remote.end_burst(); }
+        changed || self.remote.nkeys > 0
+    }
+
+    /// Editor o visor abiertos: consumen teclas en vez del prompt.
+    fn fullscreen(&self) -> bool {
+        self.editor.is_some() || self.viewer.is_some() || self.logview.is_some()
+    }
+
+    /// Teclas recibidas para el editor/visores desde el último frame, en
+    /// orden, con su estado de Ctrl.
+    pub fn take_remote_keys(&mut self) -> ([(Key, bool); REMOTE_KEYS], usize) {
+        let n = self.remote.nkeys;
+        self.remote.nkeys = 0;
+        (self.remote.keys, n)
+    }
+
+    fn remote_byte(
+        &mut self,
+        b:   u8,
+        hw:  &crate::arch::hardware::HardwareInfo,
+        pci: &crate::drivers::bus::pci::PciBus,
+    ) -> bool {
+        // En el prompt las secuencias de escape (flechas, F-keys...) se
+        // descartan enteras
+        match self.remote.esc {
+            Esc::Start => {
+                self.remote.esc = if b == b'[' || b == b'O' { Esc::Csi } else { Esc::None };
+                return false;
+            }
+            Esc::Csi => {
+                if (0x40..=0x7E).contains(&b) { self.remote.esc = Esc::None; }
+                return false;
+            }
+            Esc::None => {}
+        }
+        // `regwatch`: cualquier byte lo detiene
+        if self.regwatch.is_some() {
+            if b == 0x1B { self.remote.esc = Esc::Start; }
+            super::commands::debug::regwatch_stop(self);
+            return true;
+        }
+        // `watch`: cualquier byte lo detiene (las flechas también llegan
+        // como ESC, así que no se distingue Esc de otras teclas)
+        if self.watch.is_some() {
+            if b == 0x1B { self.remote.esc = Esc::Start; }
+            super::commands::watch::watch_stop(self);
+            return true;
+        }
+        // `waitkey`: cualquier byte continúa; ESC aborta el script
+        if self.key_wait {
+            if b == 0x1B { self.remote.esc = Esc::Start; }
+            super::commands::script::resume_after_key(self, b == 0x1B, hw, pci);
+            return true;
+        }
+
+        let after_cr = self.remote.last_cr;
+        self.remote.last_cr = b == b'\r';
+
+        // CR, LF o CRLF = un solo Enter
+        if b == b'\n' && after_cr { return false; }
+        match serial_key(b) {
+            Some((Key::Escape, _)) => { self.remote.esc = Esc::Start; false }
+            Some((Key::Enter, _)) => {
+                serial::write_str("\n");
+                self.enter(hw, pci);
+                true
+            }
+            Some((Key::Backspace, _)) => {
+                if self.input_len == 0 { return false; }
+                self.backspace();
+                serial::write_str("\x08 \x08");
+                true
+            }
+            // Ctrl+C: descartar la línea en curso
+            Some((Key::Char(b'c'), true)) => {
+                serial::write_str("^C\n");
+                self.clear_input();
+                self.remote_prompt();
+                true
+            }
+            // Ctrl+L: igual que ESC en el teclado local
+            Some((Key::Char(b'l'), true)) => {
+                self.clear_history();
+                self.clear_input();
+                serial::write_str("\x1B[2J\x1B[H");
+                self.remote_prompt();
+                true
+            }
+            Some((Key::Char(ch), false)) => {
+                if self.input_len >= INPUT_MAX - 1 { return false; }
+                self.type_char(ch);
+                serial::write_byte(ch);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    /// Entrega cada ráfaga como un poll y devuelve las teclas encoladas.
+    fn feed(bursts: &[&[u8]]) -> Vec<(Key, bool)> {
+        let mut r = RemoteSession::IDLE;
+        for burst in bursts {
+            for &b in *burst { r.key_byte(b); }
+            r.end_burst();
+        }
+        r.keys[..r.nkeys].to_vec()
+    }
+
+    #[test]
+    fn serial_bytes_to_keys() {
+        assert_eq!(serial_key(b'a'), Some((Key::Char(b'a'), false)));
+        assert_eq!(serial_key(b'~'), Some((Key::Char(b'~'), false)));
+        assert_eq!(serial_key(b'\r'), Some((Key::Enter, false)));
+        assert_eq!(serial_key(b'\n'), Some((Key::Enter, false)));
+        assert_eq!(serial_key(0x7F), Some((Key::Backspace, false)));
+        assert_eq!(serial_key(0x08), Some((Key::Backspace, false)));
+        assert_eq!(serial_key(b'\t'), Some((Key::Tab, false)));
+        assert_eq!(serial_key(0x1B), Some((Key::Escape, false)));
+        assert_eq!(serial_key(0x03), Some((Key::Char(b'c'), true)));
+        assert_eq!(serial_key(0x1A), Some((Key::Char(b'z'), true)));
+        assert_eq!(serial_key(0x00), None);
+        assert_eq!(serial_key(0x1C), None);
+        assert_eq!(serial_key(0x80), None);
+    }
+
+    #[test]
+    fn csi_sequences() {
+        assert_eq!(csi_key(0, b'A'), Some(Key::Up));
+        assert_eq!(csi_key(0, b'D'), Some(Key::Left));
+        assert_eq!(csi_key(0, b'H'), Some(Key::Home));
+        assert_eq!(csi_key(1, b'~'), Some(Key::Home));
+        assert_eq!(csi_key(8, b'~'), Some(Key::End));
+        assert_eq!(csi_key(3, b'~'), Some(Key::Delete));
+        assert_eq!(csi_key(6, b'~'), Some(Key::PageDown));
+        assert_eq!(csi_key(15, b'~'), None);
+        assert_eq!(csi_key(0, b'Z'), None);
+    }
+
+    #[test]
+    fn csi_split_across_reads() {
+        assert_eq!(feed(&[b"\x1b[", b"5~"]), [(Key::PageUp, false)]);
+        assert_eq!(feed(&[b"\x1b[1", b"5", b"~x"]), [(Key::Char(b'x'), false)]);
+        assert_eq!(feed(&[b"\x1bO", b"B"]), [(Key::Down, false)]);
+    }
+
+    #[test]
+    fn lone_escape() {
+        assert_eq!(feed(&[b"\x1b"]), [(Key::Escape, false)]);
+        // ESC seguido de otro byte: Escape y luego ese byte
+        assert_eq!(feed(&[b"\x1bq"]), [(Key::Escape, false), (Key::Char(b'q'), false)]);
+        assert_eq!(feed(&[b"a\x1b", b"b"]),
+                   [(Key::Char(b'a'), false), (Key::Escape, false), (Key::Char(b'b'), false)]);
+    }
+
+    #[test]
+    fn crlf_is_one_enter() {
+        assert_eq!(feed(&[b"x\r\n"]), [(Key::Char(b'x'), false), (Key::Enter, false)]);
+        assert_eq!(feed(&[b"\r", b"\n"]), [(Key::Enter, false)]);
+        assert_eq!(feed(&[b"\n\n"]), [(Key::Enter, false), (Key::Enter, false)]);
+    }
+
+    #[test]
+    fn queue_is_bounded() {
+        assert_eq!(feed(&[&[b'a'; REMOTE_KEYS + 5]]).len(), REMOTE_KEYS);
+    }
+}
@@ -0,0 +1,62 @@
+// console/terminal/split.rs — PORTIX Kernel
+// Vista dividida del terminal (`split`): un panel superior congelado con
+// historial antiguo y el panel normal debajo, con el input. El superior se
+// ancla a un número de línea lógica, así que la salida nueva no lo mueve;
+// tiene su propio scroll (rueda sobre él o Ctrl+RePag/AvPag).
+//
+// Sus filas se descuentan del área de scroll igual que la cabecera fija
+// (ver `scroll_rows`), de modo que el panel inferior se comporta como la
+// vista única pero más bajo.
+
+use super::Terminal;
+
+/// Filas de historial mínimas para poder dividir (ambos paneles + separador).
+const SPLIT_MIN_ROWS: usize = 5;
+
+impl Terminal {
+    pub fn split_active(&self) -> bool { self.split_end.is_some() }
+
+    /// Congela en el panel superior las líneas anteriores a `end` (número
+    /// lógico, exclusivo).
+    pub fn open_split(&mut self, end: usize) {
+        self.split_end = Some(end.min(self.line_count));
+        self.scroll_to_bottom();
+    }
+
+    pub fn close_split(&mut self) {
+        self.split_end = None;
+    }
+
+    /// Filas que ocupa el panel superior, separador incluido (0 = sin
+    /// dividir o sin sitio).
+    pub fn split_rows(&self, max_visible: usize) -> usize {
+        let avail = max_visible.saturating_sub(self.fixed_rows());
+        if self.split_end.is_none() || avail < SPLIT_MIN_ROWS { return 0; }
+        avail / 2
+    }
+
+    /// Rango (inicio, cantidad) de líneas lógicas del panel superior para
+    /// `rows` filas. Si el ring buffer ya descartó el ancla, el panel se
+    /// queda en las líneas más antiguas disponibles.
+    pub fn split_range(&self, rows: usize) -> (usize, usize) {
+        let Some(anchor) = self.split_end else { return (0, 0) };
+        let oldest = self.oldest_logical();
+        let end    = anchor.min(self.line_count).max((oldest + rows).min(self.line_count));
+        let start  = end.saturating_sub(rows).max(oldest);
+        (start, end - start)
+    }
+
+    pub fn split_scroll_up(&mut self, lines: usize, max_visible: usize) {
+        let rows = self.split_rows(max_visible).saturating_sub(1);
+        if let Some(end) = self.split_end {
+            let floor = (self.oldest_logical() + rows).min(self.line_count);
+            self.split_end = Some(end.saturating_sub(lines).max(floor));
+        }
+    }
+
+    pub fn split_scroll_down(&mut self, lines: usize) {
+        if let Some(end) = self.split_end {
+            self.split_end = Some(end.saturating_add(lines).min(self.line_count));
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// console/terminal/viewer.rs — PORTIX Kernel v0.8.1
+//
+// Visor hexadecimal de archivos FAT32 (solo lectura) — comando `view`.
+//
+// ┌─ Teclas ───────────────────────────────────────────────────────────────────┐
+// │  Flechas Arriba/Abajo  → 1 fila (16 bytes)                                │
+// │  RePág / AvPág         → 1 pantalla                                       │
+// │  Inicio / Fin          → principio / final del archivo                    │
+// │  Esc / Q               → salir                                            │
+// └────────────────────────────────────────────────────────────────────────────┘
+//
+// A diferencia del editor hex (un sector de 512 B), el visor recorre el archivo
+// completo. Solo mantiene en RAM una ventana de VIEW_WINDOW bytes: al salir de
+// ella con el scroll se remonta el volumen (DriveInfo cacheado) y se lee la
+// nueva ventana con `read_file_at`, que salta clusters por la cadena FAT.
+
+#![allow(dead_code)]
+
+use crate::drivers::input::keyboard::Key;
+use crate::drivers::storage::ata::{AtaDrive, get_cached_drive_info};
+use crate::drivers::storage::fat32::{Fat32Volume, DirEntryInfo};
+use crate::graphics::driver::framebuffer::{Console, Layout};
+use crate::console::terminal::editor::EdPalette;
+use crate::util::fmt as kfmt;
+
+/// Bytes del archivo mantenidos en memoria a la vez.
+pub const VIEW_WINDOW: usize = 4096;
+const ROW_BYTES:       usize = 16;
+
+pub struct ViewerState {
+    pub entry:     DirEntryInfo,
+    pub size:      usize,
+    pub path:      [u8; 256],
+    pub path_len:  usize,
+    /// Primera fila visible (fila = 16 bytes).
+    pub scroll:    usize,
+    /// Offset del archivo donde empieza `window`.
+    pub win_off:   usize,
+    pub win_len:   usize,
+    pub window:    [u8; VIEW_WINDOW],
+    pub io_error:  bool,
+    pub exit:      bool,
+}
+
+impl ViewerState {
+    /// Crea el visor y carga la primera ventana con el volumen ya montado.
+    pub fn new(vol: &Fat32Volume, entry: DirEntryInfo, path: &[u8]) -> Self {
+        let mut v = ViewerState {
+            size:     entry.size as usize,
+            entry,
+            path:     [0u8; 256],
+            path_len: path.len().min(256),
+            scroll:   0,
+            win_off:  0,
+            win_len:  0,
+            window:   [0u8; VIEW_WINDOW],
+            io_error: false,
+            exit:     false,
+        };
+        v.path[..v.path_len].copy_from_slice(&path[..v.path_len]);
+        v.load_window(vol, 0);
+        v
+    }
+
+    pub fn total_rows(&self) -> usize { self.size.div_ceil(ROW_BYTES).max(1) }
+
+    fn load_window(&mut self, vol: &Fat32Volume, off: usize) {
+        match vol.read_file_at(&self.entry, off, &mut self.window) {
+            Ok(n)  => { self.win_off = off; self.win_len = n; self.io_error = false; }
+            Err(_) => { self.win_len = 0; self.io_error = true; }
+        }
+    }
+
+    /// Garantiza que las filas visibles estén dentro de la ventana cargada.
+    fn ensure_loaded(&mut self, vis: usize) {
+        let start = self.scroll * ROW_BYTES;
+        let end   = ((self.scroll + vis) * ROW_BYTES).min(self.size);
+        if start >= self.win_off && end <= self.win_off + self.win_len { return; }
+
+        // Centrar la ventana alrededor de la zona visible (alineada a filas)
+        let span = (end - start).min(VIEW_WINDOW);
+        let lead = (VIEW_WINDOW - span) / 2 / ROW_BYTES * ROW_BYTES;
+        let off  = start.saturating_sub(lead);
+
+        let Some(info) = get_cached_drive_info() else { self.io_error = true; return };
+        match Fat32Volume::mount(AtaDrive::from_info(info)) {
+            Ok(vol) => self.load_window(&vol, off),
+            Err(_)  => self.io_error = true,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: Key, vis: usize) {
+        let max = self.total_rows().saturating_sub(vis);
+        match key {
+            Key::Escape | Key::Char(b'q') | Key::Char(b'Q') => { self.exit = true; return; }
+            Key::Up       => self.scroll = self.scroll.saturating_sub(1),
+            Key::Down     => self.scroll = (self.scroll + 1).min(max),
+            Key::PageUp   => self.scroll = self.scroll.saturating_sub(vis),
+            Key::PageDown => self.scroll = (self.scroll + vis).min(max),
+            Key::Home     => self.scroll = 0,
+            Key::End      => self.scroll = max,
+            _ => return,
+        }
+        self.ensure_loaded(vis);
+    }
+
+    fn byte_at(&self, off: usize) -> Option<u8> {
+        if off >= self.win_off && off < self.win_off + self.win_len {
+            Some(self.window[off - self.win_off])
+        } else { None }
+    }
+}
+
+/// Filas de datos que caben en pantalla (lo usa `main` para el scroll).
+pub fn viewer_visible_rows(lay: &Layout) -> usize {
+    let row_h = lay.font_h + 2;
+    let body  = lay.bottom_y.saturating_sub(lay.content_y + 2 * (lay.font_h + 6) + lay.font_h + 6);
+    (body / row_h).max(1)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// RENDERIZADO
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub fn draw_viewer_tab(c: &mut Console, lay: &Layout, v: &ViewerState) {
+    const HEX: &[u8] = b"0123456789ABCDEF";
+    let fw    = lay.fw;
+    let ch    = lay.font_h;
+    let x0    = 8usize;
+    let y0    = lay.content_y;
+    let row_h = ch + 2;
+    let vis   = viewer_visible_rows(lay);
+
+    c.fill_rect(0, y0, fw, lay.bottom_y.saturating_sub(y0), EdPalette::ROW_ODD);
+
+    // Cabecera
+    c.fill_rect(x0, y0, fw - x0, ch + 4, EdPalette::HEADER);
+    {
+        let mut hbuf = [0u8; 120]; let mut hp = 0;
+        let mut push = |s: &[u8]| for &b in s { if hp < 120 { hbuf[hp] = b; hp += 1; } };
+        push(b" VISOR HEX  ");
+        push(&v.path[..v.path_len.min(60)]);
+        push(b"  (");
+        let mut tmp = [0u8; 20];
+        push(kfmt::fmt_u64(v.size as u64, &mut tmp).as_bytes());
+        push(b" bytes)  [solo lectura]");
+        c.write_at_bg(
+            core::str::from_utf8(&hbuf[..hp]).unwrap_or(""),
+            x0 + 4, y0 + 2,
+            EdPalette::HEADER_TXT, EdPalette::HEADER,
+        );
+    }
+
+    let y_col = y0 + ch + 6;
+    c.fill_rect(x0, y_col, fw - x0, ch + 2, EdPalette::BORDER);
+    c.write_at_bg(
+        " Offset     00 01 02 03 04 05 06 07  08 09 0A 0B 0C 0D 0E 0F   ASCII",
+        x0 + 4, y_col + 1,
+        EdPalette::OFFSET_FG, EdPalette::BORDER,
+    );
+
+    let y_rows = y_col + ch + 4;
+    for i in 0..vis {
+        let row    = v.scroll + i;
+        let byte0  = row * ROW_BYTES;
+        let y_row  = y_rows + i * row_h;
+        let row_bg = if row.is_multiple_of(2) { EdPalette::ROW_EVEN } else { EdPalette::ROW_ODD };
+        c.fill_rect(x0, y_row, fw - x0, row_h, row_bg);
+        if byte0 >= v.size { continue; }
+
+        let mut line = [0u8; 128]; let mut lp = 0;
+        line[lp] = b' '; lp += 1;
+        for sh in (0..8).rev() {
+            line[lp] = HEX[(byte0 >> (sh * 4)) & 0xF]; lp += 1;
+        }
+        line[lp] = b' '; lp += 1; line[lp] = b' '; lp += 1; line[lp] = b' '; lp += 1;
+
+        for col in 0..ROW_BYTES {
+            if col == 8 { line[lp] = b' '; lp += 1; }
+            let off = byte0 + col;
+            if off >= v.size {
+                line[lp] = b' '; line[lp + 1] = b' ';
+            } else if let Some(b) = v.byte_at(off) {
+                line[lp] = HEX[(b >> 4) as usize]; line[lp + 1] = HEX[(b & 0xF) as usize];
+            } else {
+                line[lp] = b'?'; line[lp + 1] = b'?';
+            }
+            lp += 2;
+            line[lp] = b' '; lp += 1;
+        }
+
+        line[lp] = b' '; lp += 1; line[lp] = b' '; lp += 1;
+        for col in 0..ROW_BYTES {
+            let off = byte0 + col;
+            if off >= v.size { break; }
+            line[lp] = match v.byte_at(off) {
+                Some(b) if (0x20..0x7F).contains(&b) => b,
+                _ => b'.',
+            };
+            lp += 1;
+        }
+
+        c.write_at_bg(
+            core::str::from_utf8(&line[..lp]).unwrap_or(""),
+            x0 + 2, y_row + 1,
+            EdPalette::WHITE, row_bg,
+        );
+    }
+
+    // Scrollbar
+    let total   = v.total_rows();
+    let total_h = vis * row_h;
+    let sb_x    = fw - 10;
+    c.fill_rect(sb_x, y_rows, 8, total_h, EdPalette::BORDER);
+    if total > vis {
+        let thumb_h = (total_h * vis / total).max(8);
+        let thumb_y = y_rows + (v.scroll * total_h) / total;
+        let th      = thumb_h.min((y_rows + total_h).saturating_sub(thumb_y));
+        c.fill_rect(sb_x + 1, thumb_y, 6, th, EdPalette::HEADER);
+    } else {
+        c.fill_rect(sb_x + 1, y_rows, 6, total_h, EdPalette::HEADER);
+    }
+
+    // Barra de estado
+    let y_st = y_rows + total_h + 4;
+    c.fill_rect(x0, y_st, fw - x0, ch + 4, EdPalette::BORDER);
+    let mut sbuf = [0u8; 120]; let mut sp = 0;
+    {
+        let mut push = |s: &[u8]| for &b in s { if sp < 120 { sbuf[sp] = b; sp += 1; } };
+        let mut tmp = [0u8; 20];
+        push(b" Fila ");
+        push(kfmt::fmt_u64((v.scroll + 1) as u64, &mut tmp).as_bytes());
+        push(b"/");
+        push(kfmt::fmt_u64(total as u64, &mut tmp).as_bytes());
+        push(b"   Flechas/RePag/AvPag/Inicio/Fin = Scroll   Esc/Q = Salir");
+    }
+    let (msg, col) = if v.io_error {
+        ("  [ERROR] lectura de disco", EdPalette::MSG_ERR)
+    } else { ("", EdPalette::SHORTCUT) };
+    c.write_at_bg(core::str::from_utf8(&sbuf[..sp]).unwrap_or(""), x0 + 4, y_st + 2, EdPalette::SHORTCUT, EdPalette::BORDER);
+    if !msg.is_empty() {
+        c.write_at_bg(msg, x0 + 4 + sp * lay.font_w, y_st + 2, col, EdPalette::BORDER);
+    }
+}
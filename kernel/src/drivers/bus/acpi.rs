@@ -1,11 +1,39 @@
-// kernel/src/acpi.rs — PORTIX ACPI Basic (poweroff / reboot)
+// kernel/src/acpi.rs — PORTIX ACPI (RSDP/RSDT/FADT, poweroff / reboot)
+//
+// Al arrancar (`init`) se busca la RSDP en la EBDA y en 0xE0000..0xFFFFF, se
+// valida su checksum y se recorre la XSDT (ACPI 2.0+) o la RSDT. De la FADT
+// salen los bloques PM1a/PM1b_CNT y la dirección del DSDT, donde se busca el
+// paquete `_S5_` con los SLP_TYP del apagado. `poweroff` usa esos datos si
+// están completos; sin ACPI, con firmas o checksums inválidos o sin `_S5_`
+// recurre a los puertos fijos de QEMU/Bochs/VirtualBox de siempre.
+//
+// Apagado y reinicio son cadenas de métodos (`poweroff_chain`,
+// `reset_chain`): cada uno se intenta, se espera un momento (`settle`) y si
+// la máquina sigue viva se pasa al siguiente. La selección depende solo de
+// AcpiInfo, así que el orden se puede comprobar sin tocar hardware.
+//
+// Las tablas por debajo de IDENTITY_LIMIT se leen en identidad; las de más
+// arriba (lo normal: al final de la RAM) a través de `map_mmio`, por lo que
+// `init` debe llamarse después de `paging::init`.
 #![allow(dead_code)]
 
+use core::ptr::{addr_of, addr_of_mut};
+
+use crate::arch::paging;
+use crate::drivers::serial::{self, Level};
+use crate::mem::IDENTITY_LIMIT;
+
 #[inline(always)]
 unsafe fn outw(p: u16, v: u16) {
     core::arch::asm!("out dx, ax", in("dx") p, in("ax") v, options(nostack, nomem));
 }
 #[inline(always)]
+unsafe fn inw(p: u16) -> u16 {
+    let v: u16;
+    core::arch::asm!("in ax, dx", out("ax") v, in("dx") p, options(nostack, nomem));
+    v
+}
+#[inline(always)]
 unsafe fn outb(p: u16, v: u8) {
     core::arch::asm!("out dx, al", in("dx") p, in("al") v, options(nostack, nomem));
 }
@@ -16,36 +44,581 @@ unsafe fn inb(p: u16) -> u8 {
     v
 }
 
-/// Power off the machine.
-/// Tries QEMU → Bochs → VirtualBox in sequence.
+// ── Tablas ────────────────────────────────────────────────────────────────────
+
+/// Tablas recordadas para el comando `acpi` (las de más se cuentan aparte).
+pub const MAX_TABLES: usize = 24;
+/// Cabecera común de toda SDT.
+const SDT_HDR: usize = 36;
+/// Tamaño máximo que se acepta para una tabla (protege de longitudes basura).
+const SDT_MAX: u32 = 1 << 20;
+/// Tamaño mínimo de cada ventana `map_mmio`: las tablas suelen estar juntas
+/// al final de la RAM y así comparten una sola región de `vmmap`.
+const MAP_WINDOW: usize = 64 * 1024;
+
+/// PM1_CNT: SCI_EN (bit 0), SLP_TYP (bits 10-12) y SLP_EN (bit 13).
+const SCI_EN:       u16 = 1 << 0;
+const SLP_TYP_MASK: u16 = 7 << 10;
+const SLP_EN:       u16 = 1 << 13;
+/// FADT.Flags bit 10: RESET_REG válido.
+const RESET_REG_SUP: u32 = 1 << 10;
+/// Vueltas de espera tras cada intento antes de pasar al siguiente método.
+const SETTLE_SPINS: u32 = 20_000_000;
+
+#[derive(Clone, Copy)]
+pub struct TableInfo {
+    pub sig:         [u8; 4],
+    pub phys:        u64,
+    pub len:         u32,
+    pub rev:         u8,
+    pub checksum_ok: bool,
+}
+
+impl TableInfo {
+    const EMPTY: Self = TableInfo { sig: [0; 4], phys: 0, len: 0, rev: 0, checksum_ok: false };
+}
+
+/// RESET_REG de la FADT (Generic Address Structure), solo espacios de
+/// memoria (0) y E/S (1).
+#[derive(Clone, Copy)]
+pub struct ResetReg {
+    pub space: u8,
+    pub addr:  u64,
+    pub value: u8,
+}
+
+#[derive(Clone, Copy)]
+pub struct AcpiInfo {
+    pub rsdp:        u64,
+    pub revision:    u8,
+    pub oem:         [u8; 6],
+    /// Dirección de la RSDT/XSDT usada y si es la XSDT.
+    pub root:        u64,
+    pub xsdt:        bool,
+    pub tables:      [TableInfo; MAX_TABLES],
+    pub count:       usize,
+    /// Entradas de la raíz que no cupieron en `tables`.
+    pub dropped:     usize,
+    pub pm1a_cnt:    u16,
+    pub pm1b_cnt:    u16,
+    pub smi_cmd:     u32,
+    pub acpi_enable: u8,
+    pub dsdt:        u64,
+    /// SLP_TYPa / SLP_TYPb del estado S5, leídos del `_S5_` del DSDT.
+    pub s5:          Option<(u8, u8)>,
+    /// Registro de reinicio si la FADT lo anuncia (RESET_REG_SUP).
+    pub reset:       Option<ResetReg>,
+}
+
+impl AcpiInfo {
+    /// true si `poweroff` puede apagar por ACPI sin adivinar valores.
+    pub fn can_poweroff(&self) -> bool { self.pm1a_cnt != 0 && self.s5.is_some() }
+
+    fn push(&mut self, t: TableInfo) {
+        if self.count < MAX_TABLES {
+            self.tables[self.count] = t;
+            self.count += 1;
+        } else {
+            self.dropped += 1;
+        }
+    }
+}
+
+static mut INFO: Option<AcpiInfo> = None;
+
+/// Resultado de `init` (None = no se encontró una RSDP válida).
+pub fn info() -> Option<AcpiInfo> { unsafe { *addr_of!(INFO) } }
+
+/// Suma de todos los bytes módulo 256: una tabla ACPI es válida si da 0.
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))
+}
+
+fn rd16(b: &[u8], off: usize) -> u16 { u16::from_le_bytes([b[off], b[off + 1]]) }
+fn rd32(b: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes([b[off], b[off + 1], b[off + 2], b[off + 3]])
+}
+fn rd64(b: &[u8], off: usize) -> u64 { rd32(b, off) as u64 | (rd32(b, off + 4) as u64) << 32 }
+
+/// Bytes físicos `phys .. phys + len`: en identidad por debajo de
+/// IDENTITY_LIMIT, si no a través de una ventana `map_mmio`.
+fn phys_bytes(phys: u64, len: usize) -> Option<&'static [u8]> {
+    if phys == 0 || len == 0 { return None; }
+    let end = phys.checked_add(len as u64)?;
+    let virt = if end <= IDENTITY_LIMIT as u64 {
+        phys
+    } else {
+        paging::map_mmio(phys, len.max(MAP_WINDOW) as u64)
+            .or_else(|_| paging::map_mmio(phys, len as u64))
+            .ok()?
+    };
+    Some(unsafe { core::slice::from_raw_parts(virt as *const u8, len) })
+}
+
+/// Lee la SDT de `phys` completa y su resumen; None si ni la cabecera es legible.
+fn read_sdt(phys: u64) -> Option<(&'static [u8], TableInfo)> {
+    let hdr = phys_bytes(phys, SDT_HDR)?;
+    let len = rd32(hdr, 4);
+    if !(SDT_HDR as u32..=SDT_MAX).contains(&len) { return None; }
+    let data = phys_bytes(phys, len as usize)?;
+    let info = TableInfo {
+        sig: [data[0], data[1], data[2], data[3]],
+        phys, len, rev: data[8],
+        checksum_ok: checksum(data) == 0,
+    };
+    Some((data, info))
+}
+
+/// Busca "RSD PTR " alineada a 16 bytes con checksum válido (el de 20 bytes
+/// de ACPI 1.0 y, en revisión 2+, el extendido de toda la estructura).
+fn find_rsdp() -> Option<u64> {
+    // Segmento de la EBDA en el BDA (0x40E); solo si cae donde debe
+    let ebda = phys_bytes(0x40E, 2).map_or(0, |b| (rd16(b, 0) as u64) << 4);
+    let ebda_ok = (0x8_0000..0xA_0000).contains(&ebda);
+    let areas = [(ebda, if ebda_ok { 1024 } else { 0 }), (0xE_0000, 0x2_0000)];
+    for &(start, len) in areas.iter().filter(|a| a.1 > 0) {
+        let area = phys_bytes(start, len)?;
+        for off in (0..len.saturating_sub(36)).step_by(16) {
+            let r = &area[off..];
+            if &r[..8] != b"RSD PTR " || checksum(&r[..20]) != 0 { continue; }
+            if r[15] >= 2 {
+                let ext = rd32(r, 20) as usize;
+                if !(36..=r.len().min(64)).contains(&ext) || checksum(&r[..ext]) != 0 { continue; }
+            }
+            return Some(start + off as u64);
+        }
+    }
+    None
+}
+
+/// Valores SLP_TYPa/b del paquete `_S5_` del AML:
+/// `NameOp "_S5_" PackageOp PkgLength NumElements <a> <b> ...`, con cada
+/// valor como BytePrefix+byte, ZeroOp u OneOp.
+fn parse_s5(aml: &[u8]) -> Option<(u8, u8)> {
+    let mut from = 0;
+    while let Some(rel) = aml[from..].windows(4).position(|w| w == b"_S5_") {
+        let i = from + rel;
+        from = i + 4;
+        let named = (i >= 1 && aml[i - 1] == 0x08)
+            || (i >= 2 && aml[i - 2] == 0x08 && aml[i - 1] == b'\\');
+        if !named || aml.get(i + 4) != Some(&0x12) { continue; }
+        // PkgLength: los 2 bits altos del primer byte = bytes adicionales
+        let &lead = aml.get(i + 5)?;
+        let mut p = i + 5 + 1 + (lead >> 6) as usize + 1;
+        let mut vals = [0u8; 2];
+        let mut ok = true;
+        for v in vals.iter_mut() {
+            match aml.get(p) {
+                Some(0x0A) => { *v = *aml.get(p + 1)?; p += 2; }
+                Some(0x00) => { *v = 0; p += 1; }
+                Some(0x01) => { *v = 1; p += 1; }
+                _ => { ok = false; break; }
+            }
+        }
+        if ok { return Some((vals[0] & 7, vals[1] & 7)); }
+    }
+    None
+}
+
+/// Datos de apagado de la FADT y su DSDT.
+fn parse_fadt(acpi: &mut AcpiInfo, fadt: &[u8]) {
+    let len = fadt.len();
+    if len < 76 { return; }
+    acpi.smi_cmd     = rd32(fadt, 48);
+    acpi.acpi_enable = fadt[52];
+    acpi.pm1a_cnt    = rd32(fadt, 64) as u16;
+    acpi.pm1b_cnt    = rd32(fadt, 68) as u16;
+    // X_PM1a_CNT_BLK (GAS en 172) si el bloque de 32 bits viene a 0 y es E/S
+    if acpi.pm1a_cnt == 0 && len >= 184 && fadt[172] == 1 {
+        acpi.pm1a_cnt = rd64(fadt, 176) as u16;
+    }
+    // RESET_REG (GAS en 116) y RESET_VALUE (128), si FLAGS.RESET_REG_SUP
+    if len >= 129 && rd32(fadt, 112) & RESET_REG_SUP != 0 {
+        let (space, addr) = (fadt[116], rd64(fadt, 120));
+        if space <= 1 && addr != 0 {
+            acpi.reset = Some(ResetReg { space, addr, value: fadt[128] });
+        }
+    }
+    let x_dsdt = if len >= 148 { rd64(fadt, 140) } else { 0 };
+    acpi.dsdt = if x_dsdt != 0 { x_dsdt } else { rd32(fadt, 40) as u64 };
+
+    if let Some((aml, t)) = read_sdt(acpi.dsdt) {
+        acpi.push(t);
+        if &t.sig == b"DSDT" && t.checksum_ok {
+            acpi.s5 = parse_s5(&aml[SDT_HDR..]);
+        }
+    }
+}
+
+/// Localiza y valida las tablas. true si el apagado S5 por ACPI es posible.
+pub fn init() -> bool {
+    let Some(rsdp_phys) = find_rsdp() else {
+        serial::log_level(Level::Warn, "ACPI", "sin RSDP valida: apagado por puertos fijos");
+        return false;
+    };
+    let Some(rsdp) = phys_bytes(rsdp_phys, 36) else { return false };
+    let revision = rsdp[15];
+    let mut acpi = AcpiInfo {
+        rsdp: rsdp_phys, revision,
+        oem: [rsdp[9], rsdp[10], rsdp[11], rsdp[12], rsdp[13], rsdp[14]],
+        root: 0, xsdt: false,
+        tables: [TableInfo::EMPTY; MAX_TABLES], count: 0, dropped: 0,
+        pm1a_cnt: 0, pm1b_cnt: 0, smi_cmd: 0, acpi_enable: 0, dsdt: 0, s5: None,
+        reset: None,
+    };
+
+    // XSDT si la hay y es válida; si no, la RSDT de siempre
+    let xsdt = if revision >= 2 { rd64(rsdp, 24) } else { 0 };
+    let root = [(xsdt, b"XSDT", 8usize), (rd32(rsdp, 16) as u64, b"RSDT", 4)]
+        .into_iter()
+        .filter_map(|(phys, sig, w)| read_sdt(phys).map(|(d, t)| (d, t, sig, w)))
+        .find(|(_, t, sig, _)| &t.sig == *sig && t.checksum_ok);
+    let Some((root_data, root_info, _, width)) = root else {
+        serial::log_level(Level::Warn, "ACPI", "RSDT/XSDT ausente o invalida: apagado por puertos fijos");
+        unsafe { *addr_of_mut!(INFO) = Some(acpi); }
+        return false;
+    };
+    acpi.root = root_info.phys;
+    acpi.xsdt = width == 8;
+    acpi.push(root_info);
+
+    let entries = &root_data[SDT_HDR..];
+    for e in entries.chunks_exact(width) {
+        let phys = if width == 8 { rd64(e, 0) } else { rd32(e, 0) as u64 };
+        let Some((data, t)) = read_sdt(phys) else { continue };
+        acpi.push(t);
+        if &t.sig == b"FACP" && t.checksum_ok && acpi.dsdt == 0 {
+            parse_fadt(&mut acpi, data);
+        }
+    }
+
+    let ok = acpi.can_poweroff();
+    unsafe { *addr_of_mut!(INFO) = Some(acpi); }
+    if ok {
+        serial::log_level(Level::Ok, "ACPI", "tablas validas, apagado S5 por PM1_CNT");
+    } else if acpi.pm1a_cnt == 0 {
+        serial::log_level(Level::Warn, "ACPI", "FADT ausente o invalida: apagado por puertos fijos");
+    } else {
+        serial::log_level(Level::Warn, "ACPI", "_S5_ no encontrado en el DSDT: apagado por puertos fijos");
+    }
+    ok
+}
+
+/// Pasa el chipset a modo ACPI (SCI_EN) si el firmware lo dejó en modo legacy.
+unsafe fn enable_acpi(acpi: &AcpiInfo) {
+    if inw(acpi.pm1a_cnt) & SCI_EN != 0 || acpi.smi_cmd == 0 || acpi.acpi_enable == 0 { return; }
+    outb(acpi.smi_cmd as u16, acpi.acpi_enable);
+    for _ in 0..1_000_000 {
+        if inw(acpi.pm1a_cnt) & SCI_EN != 0 { break; }
+        core::hint::spin_loop();
+    }
+}
+
+// ── Cadenas de apagado y reinicio ─────────────────────────────────────────────
+//
+// APM (int 15h AX=5307h) no figura: necesita modo real o protegido de 16
+// bits y no es alcanzable desde long mode.
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OffMethod {
+    /// SLP_TYP de `_S5_` + SLP_EN en PM1a/PM1b_CNT.
+    AcpiS5,
+    /// PM1a fijo de QEMU ≥ 2.x (0x604) y de Bochs / QEMU antiguo (0xB004).
+    QemuBochs,
+    /// PM1a fijo de VirtualBox (0x4004).
+    VirtualBox,
+}
+
+impl OffMethod {
+    pub fn name(self) -> &'static str {
+        match self {
+            OffMethod::AcpiS5     => "ACPI S5",
+            OffMethod::QemuBochs  => "QEMU/Bochs",
+            OffMethod::VirtualBox => "VirtualBox",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ResetMethod {
+    /// RESET_VALUE en el RESET_REG de la FADT.
+    AcpiReset,
+    /// Pulso de la línea de reset del 8042 (0xFE en 0x64).
+    Kbc,
+    /// "Fast reset" del puerto 0x92 (System Control Port A).
+    Port92,
+    /// IDT de límite 0 + int3: la CPU no puede entregar la excepción.
+    TripleFault,
+}
+
+impl ResetMethod {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResetMethod::AcpiReset   => "ACPI reset",
+            ResetMethod::Kbc         => "8042",
+            ResetMethod::Port92      => "puerto 0x92",
+            ResetMethod::TripleFault => "triple fault",
+        }
+    }
+}
+
+/// Métodos de apagado en el orden en que se intentan.
+pub fn poweroff_chain(acpi: Option<&AcpiInfo>) -> ([OffMethod; 3], usize) {
+    let mut out = [OffMethod::QemuBochs; 3];
+    let mut n = 0;
+    if acpi.is_some_and(AcpiInfo::can_poweroff) { out[n] = OffMethod::AcpiS5; n += 1; }
+    for m in [OffMethod::QemuBochs, OffMethod::VirtualBox] { out[n] = m; n += 1; }
+    (out, n)
+}
+
+/// Métodos de reinicio en el orden en que se intentan. El triple fault va
+/// siempre el último: no vuelve.
+pub fn reset_chain(acpi: Option<&AcpiInfo>) -> ([ResetMethod; 4], usize) {
+    let mut out = [ResetMethod::TripleFault; 4];
+    let mut n = 0;
+    if acpi.is_some_and(|a| a.reset.is_some()) { out[n] = ResetMethod::AcpiReset; n += 1; }
+    for m in [ResetMethod::Kbc, ResetMethod::Port92, ResetMethod::TripleFault] { out[n] = m; n += 1; }
+    (out, n)
+}
+
+/// Breve espera tras un intento, sin depender de interrupciones.
+fn settle() {
+    for _ in 0..SETTLE_SPINS { core::hint::spin_loop(); }
+}
+
+unsafe fn try_poweroff(m: OffMethod, acpi: Option<&AcpiInfo>) {
+    match m {
+        OffMethod::AcpiS5 => {
+            let Some(acpi) = acpi else { return };
+            let (typ_a, typ_b) = acpi.s5.unwrap_or((0, 0));
+            enable_acpi(acpi);
+            let cnt = inw(acpi.pm1a_cnt) & !SLP_TYP_MASK;
+            outw(acpi.pm1a_cnt, cnt | (typ_a as u16) << 10 | SLP_EN);
+            if acpi.pm1b_cnt != 0 {
+                let cnt = inw(acpi.pm1b_cnt) & !SLP_TYP_MASK;
+                outw(acpi.pm1b_cnt, cnt | (typ_b as u16) << 10 | SLP_EN);
+            }
+        }
+        OffMethod::QemuBochs => {
+            outw(0x604,  0x2000); // QEMU ≥ 2.x  ACPI PM1a
+            outw(0xB004, 0x2000); // Bochs / old QEMU
+        }
+        OffMethod::VirtualBox => outw(0x4004, 0x3400),
+    }
+}
+
+unsafe fn try_reset(m: ResetMethod, acpi: Option<&AcpiInfo>) {
+    match m {
+        ResetMethod::AcpiReset => {
+            let Some(r) = acpi.and_then(|a| a.reset) else { return };
+            if r.space == 1 {
+                outb(r.addr as u16, r.value);
+            } else if let Ok(v) = paging::map_mmio(r.addr, 1) {
+                core::ptr::write_volatile(v as *mut u8, r.value);
+            }
+        }
+        ResetMethod::Kbc => {
+            // Drain the KBC input buffer
+            let mut limit = 100_000u32;
+            while inb(0x64) & 0x02 != 0 && limit > 0 { limit -= 1; }
+            outb(0x64, 0xFE); // Pulse CPU reset line
+        }
+        ResetMethod::Port92 => {
+            // Bit 0 debe pasar de 0 a 1; el resto (A20) se conserva
+            let v = inb(0x92) & !0x01;
+            outb(0x92, v);
+            outb(0x92, v | 0x01);
+        }
+        ResetMethod::TripleFault => {
+            core::arch::asm!(
+                "cli",
+                "lidt [rip + 2f]",
+                "int 3",
+                "2:",
+                ".word 0",         // IDT limit = 0
+                ".quad 0",         // IDT base  = 0
+                options(nostack, nomem)
+            );
+        }
+    }
+}
+
+fn halt() -> ! {
+    loop { unsafe { core::arch::asm!("cli", "hlt", options(nostack, nomem)); } }
+}
+
+/// Power off the machine: each method of `poweroff_chain` in turn. If the
+/// machine survives all of them the CPU is halted (safe to cut power).
 pub fn poweroff() -> ! {
-    unsafe {
-        outw(0x604,  0x2000); // QEMU ≥ 2.x  ACPI PM1a
-        outw(0xB004, 0x2000); // Bochs / old QEMU
-        outw(0x4004, 0x3400); // VirtualBox
-        // Last resort: triple-fault via null IDT
-        core::arch::asm!(
-            "cli",
-            "lidt [rip + 2f]",
-            "int 3",
-            "2:",
-            ".word 0",         // IDT limit = 0
-            ".quad 0",         // IDT base  = 0
-            options(nostack, nomem)
-        );
-        loop { core::arch::asm!("hlt", options(nostack, nomem)); }
-    }
-}
-
-/// Reboot via keyboard controller pulse.
+    let acpi = info();
+    let (chain, n) = poweroff_chain(acpi.as_ref());
+    for &m in &chain[..n] {
+        serial::log_level(Level::Info, "ACPI", m.name());
+        unsafe { try_poweroff(m, acpi.as_ref()); }
+        settle();
+    }
+    serial::log_level(Level::Error, "ACPI", "apagado no soportado: CPU detenida");
+    halt()
+}
+
+/// Reboot: each method of `reset_chain` in turn, ending in a triple fault.
 pub fn reboot() -> ! {
-    unsafe {
-        // Drain the KBC input buffer
-        let mut limit = 100_000u32;
-        while inb(0x64) & 0x02 != 0 && limit > 0 { limit -= 1; }
-        outb(0x64, 0xFE); // Pulse CPU reset line
-        // Fallback: QEMU ISA reset
-        outb(0x92, 0x01);
-        loop { core::arch::asm!("hlt", options(nostack, nomem)); }
-    }
-}
\ No newline at end of file
+    let acpi = info();
+    let (chain, n) = reset_chain(acpi.as_ref());
+    for &m in &chain[..n] {
+        serial::log_level(Level::Info, "ACPI", m.name());
+        unsafe { try_reset(m, acpi.as_ref()); }
+        settle();
+    }
+    halt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_info() -> AcpiInfo {
+        AcpiInfo {
+            rsdp: 0, revision: 0, oem: [0; 6], root: 0, xsdt: false,
+            tables: [TableInfo::EMPTY; MAX_TABLES], count: 0, dropped: 0,
+            pm1a_cnt: 0, pm1b_cnt: 0, smi_cmd: 0, acpi_enable: 0, dsdt: 0, s5: None,
+            reset: None,
+        }
+    }
+
+    #[test]
+    fn checksum_wraps_to_zero() {
+        assert_eq!(checksum(&[]), 0);
+        assert_eq!(checksum(&[0x10, 0x20, 0xD0]), 0);
+        assert_eq!(checksum(&[0xFF, 0x02]), 1);
+        let mut table = *b"RSD PTR \0BOCHS \0\0\0\0\0";
+        table[8] = 0u8.wrapping_sub(checksum(&table));
+        assert_eq!(checksum(&table), 0);
+    }
+
+    #[test]
+    fn s5_with_byte_prefix_zero_and_one() {
+        // Name(_S5_, Package(4) {0x05, 0x05, Zero, Zero})
+        let aml = [0x10, 0x08, b'_', b'S', b'5', b'_', 0x12, 0x0A, 0x04,
+                   0x0A, 0x05, 0x0A, 0x05, 0x00, 0x00];
+        assert_eq!(parse_s5(&aml), Some((5, 5)));
+        // Zero / One como opcodes, con la raíz '\' delante del nombre
+        let aml = [0x08, b'\\', b'_', b'S', b'5', b'_', 0x12, 0x06, 0x04, 0x00, 0x01, 0x00, 0x00];
+        assert_eq!(parse_s5(&aml), Some((0, 1)));
+    }
+
+    #[test]
+    fn s5_skips_references_and_long_pkglength() {
+        // Primero una referencia a _S5_ (sin NameOp), luego la definición con
+        // PkgLength de 2 bytes (bits 6-7 del primer byte = 1)
+        let aml = [0x70, b'_', b'S', b'5', b'_', 0x60,
+                   0x08, b'_', b'S', b'5', b'_', 0x12, 0x40, 0x01, 0x04, 0x0A, 0x07, 0x0A, 0x0F];
+        assert_eq!(parse_s5(&aml), Some((7, 7)));   // solo 3 bits de SLP_TYP
+    }
+
+    #[test]
+    fn s5_missing_or_truncated() {
+        assert_eq!(parse_s5(b""), None);
+        assert_eq!(parse_s5(b"\x08_S4_\x12\x06\x04\x00\x00"), None);
+        assert_eq!(parse_s5(&[0x08, b'_', b'S', b'5', b'_', 0x12]), None);
+        assert_eq!(parse_s5(&[0x08, b'_', b'S', b'5', b'_', 0x12, 0x06, 0x04, 0x0A]), None);
+        // Un elemento que no es entero
+        assert_eq!(parse_s5(&[0x08, b'_', b'S', b'5', b'_', 0x12, 0x06, 0x04, 0x0D, 0x00]), None);
+    }
+
+    #[test]
+    fn fadt_pm1_and_reset_register() {
+        let mut fadt = [0u8; 244];
+        fadt[48..52].copy_from_slice(&0xB2u32.to_le_bytes());
+        fadt[52] = 0xF1;
+        fadt[64..68].copy_from_slice(&0x604u32.to_le_bytes());
+        fadt[112..116].copy_from_slice(&RESET_REG_SUP.to_le_bytes());
+        fadt[116] = 1;
+        fadt[120..128].copy_from_slice(&0xCF9u64.to_le_bytes());
+        fadt[128] = 0x06;
+        let mut acpi = empty_info();
+        // DSDT a 0: no se intenta leer memoria física
+        parse_fadt(&mut acpi, &fadt);
+        assert_eq!((acpi.smi_cmd, acpi.acpi_enable), (0xB2, 0xF1));
+        assert_eq!((acpi.pm1a_cnt, acpi.pm1b_cnt), (0x604, 0));
+        let r = acpi.reset.unwrap();
+        assert_eq!((r.space, r.addr, r.value), (1, 0xCF9, 6));
+        assert_eq!(acpi.dsdt, 0);
+        assert!(!acpi.can_poweroff());
+        acpi.s5 = Some((5, 0));
+        assert!(acpi.can_poweroff());
+    }
+
+    #[test]
+    fn fadt_extended_pm1a_and_ignored_reset() {
+        let mut fadt = [0u8; 244];
+        // PM1a solo en X_PM1a_CNT_BLK (GAS de E/S)
+        fadt[172] = 1;
+        fadt[176..184].copy_from_slice(&0xB004u64.to_le_bytes());
+        // RESET_REG en espacio PCI (2): no soportado
+        fadt[112..116].copy_from_slice(&RESET_REG_SUP.to_le_bytes());
+        fadt[116] = 2;
+        fadt[120..128].copy_from_slice(&0xCF9u64.to_le_bytes());
+        let mut acpi = empty_info();
+        parse_fadt(&mut acpi, &fadt);
+        assert_eq!(acpi.pm1a_cnt, 0xB004);
+        assert!(acpi.reset.is_none());
+
+        // FADT demasiado corta: no se toca nada
+        let mut acpi = empty_info();
+        parse_fadt(&mut acpi, &fadt[..75]);
+        assert_eq!(acpi.pm1a_cnt, 0);
+    }
+
+    fn off(acpi: Option<&AcpiInfo>) -> alloc::vec::Vec<OffMethod> {
+        let (m, n) = poweroff_chain(acpi);
+        m[..n].to_vec()
+    }
+
+    fn reset(acpi: Option<&AcpiInfo>) -> alloc::vec::Vec<ResetMethod> {
+        let (m, n) = reset_chain(acpi);
+        m[..n].to_vec()
+    }
+
+    #[test]
+    fn poweroff_without_acpi_uses_fixed_ports() {
+        assert!(off(None) == [OffMethod::QemuBochs, OffMethod::VirtualBox]);
+        // ACPI sin `_S5_` o sin PM1a tampoco sirve
+        let mut acpi = empty_info();
+        acpi.s5 = Some((5, 5));
+        assert!(off(Some(&acpi)) == [OffMethod::QemuBochs, OffMethod::VirtualBox]);
+        acpi.s5 = None;
+        acpi.pm1a_cnt = 0x604;
+        assert!(off(Some(&acpi)) == [OffMethod::QemuBochs, OffMethod::VirtualBox]);
+    }
+
+    #[test]
+    fn poweroff_tries_acpi_s5_first() {
+        let mut acpi = empty_info();
+        acpi.s5 = Some((5, 5));
+        acpi.pm1a_cnt = 0x604;
+        assert!(off(Some(&acpi)) == [OffMethod::AcpiS5, OffMethod::QemuBochs, OffMethod::VirtualBox]);
+    }
+
+    #[test]
+    fn reset_tries_acpi_reset_reg_first() {
+        assert!(reset(None) == [ResetMethod::Kbc, ResetMethod::Port92, ResetMethod::TripleFault]);
+        let mut acpi = empty_info();
+        assert!(reset(Some(&acpi)) == [ResetMethod::Kbc, ResetMethod::Port92, ResetMethod::TripleFault]);
+        acpi.reset = Some(ResetReg { space: 1, addr: 0xCF9, value: 0x06 });
+        assert!(reset(Some(&acpi))
+            == [ResetMethod::AcpiReset, ResetMethod::Kbc, ResetMethod::Port92, ResetMethod::TripleFault]);
+    }
+
+    #[test]
+    fn triple_fault_is_always_last() {
+        let mut acpi = empty_info();
+        for with_reset in [false, true] {
+            acpi.reset = with_reset.then_some(ResetReg { space: 1, addr: 0xCF9, value: 0x06 });
+            for chain in [reset(None), reset(Some(&acpi))] {
+                assert!(chain.last() == Some(&ResetMethod::TripleFault));
+                assert_eq!(chain.iter().filter(|&&m| m == ResetMethod::TripleFault).count(), 1);
+            }
+        }
+    }
+}
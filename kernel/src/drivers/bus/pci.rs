@@ -1,10 +1,82 @@
 // kernel/src/pci.rs — PORTIX PCI Bus Enumeration
+//
+// BARs: en el escaneo se decodifican los Base Address Registers (6 en
+// cabeceras tipo 0, 2 en puentes PCI-PCI). El tamaño se obtiene escribiendo
+// 0xFFFFFFFF, leyendo la máscara y restaurando el valor original, con la
+// decodificación de memoria/IO desactivada mientras dura la prueba. Un BAR de
+// 64 bits ocupa dos entradas: la segunda queda marcada como `BarKind::Upper`.
 #![allow(dead_code)]
 
 const PCI_ADDR: u16 = 0xCF8;
 const PCI_DATA: u16 = 0xCFC;
 
 pub const MAX_PCI_DEVICES: usize = 64;
+pub const MAX_BARS: usize = 6;
+/// Tamaño del espacio de configuración accesible por los puertos 0xCF8/0xCFC.
+pub const CONFIG_SPACE: usize = 256;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BarKind {
+    Unused,
+    Io,
+    Mem32,
+    Mem64,
+    /// Mitad alta de un BAR de 64 bits (la entrada anterior).
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct Bar {
+    pub kind:     BarKind,
+    pub base:     u64,
+    pub size:     u64,
+    pub prefetch: bool,
+}
+
+impl Bar {
+    pub const NONE: Bar = Bar { kind: BarKind::Unused, base: 0, size: 0, prefetch: false };
+
+    /// Decodifica el valor de un BAR. `hi` es el registro siguiente y solo se
+    /// usa si `lo` indica memoria de 64 bits. El tamaño queda a 0.
+    pub fn decode(lo: u32, hi: u32) -> Bar {
+        if lo & 1 != 0 {
+            return Bar { kind: BarKind::Io, base: (lo & 0xFFFF_FFFC) as u64, size: 0, prefetch: false };
+        }
+        let prefetch = lo & 0x8 != 0;
+        let base = (lo & 0xFFFF_FFF0) as u64;
+        match (lo >> 1) & 0x3 {
+            0x2 => Bar { kind: BarKind::Mem64, base: base | ((hi as u64) << 32), size: 0, prefetch },
+            _   => Bar { kind: BarKind::Mem32, base, size: 0, prefetch },
+        }
+    }
+
+    /// Tamaño a partir de lo leído tras escribir todo unos (`hi_mask` solo
+    /// cuenta en BARs de 64 bits). Es el bit más bajo que el dispositivo deja
+    /// a 1; 0 si el BAR no está implementado.
+    pub fn size_from_mask(kind: BarKind, lo_mask: u32, hi_mask: u32) -> u64 {
+        let m = match kind {
+            BarKind::Io    => (lo_mask & 0xFFFF_FFFC) as u64,
+            BarKind::Mem32 => (lo_mask & 0xFFFF_FFF0) as u64,
+            BarKind::Mem64 => (lo_mask & 0xFFFF_FFF0) as u64 | ((hi_mask as u64) << 32),
+            _ => 0,
+        };
+        m.isolate_lowest_one()
+    }
+
+    pub fn is_used(&self) -> bool {
+        matches!(self.kind, BarKind::Io | BarKind::Mem32 | BarKind::Mem64)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self.kind {
+            BarKind::Unused => "--",
+            BarKind::Io     => "I/O",
+            BarKind::Mem32  => "Mem32",
+            BarKind::Mem64  => "Mem64",
+            BarKind::Upper  => "(alta)",
+        }
+    }
+}
 
 #[derive(Clone, Copy)]
 pub struct PciDevice {
@@ -18,6 +90,7 @@ pub struct PciDevice {
     pub prog_if:    u8,
     pub header_type: u8,
     pub irq_line:   u8,
+    pub bars:       [Bar; MAX_BARS],
 }
 
 impl PciDevice {
@@ -25,65 +98,174 @@ impl PciDevice {
         PciDevice { bus:0, device:0, function:0,
             vendor_id: 0xFFFF, device_id: 0xFFFF,
             class_code:0, subclass:0, prog_if:0,
-            header_type:0, irq_line:0xFF }
-    }
-
-    pub fn class_name(&self) -> &'static str {
-        match self.class_code {
-            0x00 => "Unclassified",
-            0x01 => match self.subclass {
-                0x01 => "IDE Controller",
-                0x06 => "SATA (AHCI)",
-                0x08 => "NVM Express",
-                _ => "Mass Storage",
-            },
-            0x02 => "Network Controller",
-            0x03 => match self.subclass {
-                0x00 => "VGA Controller",
-                0x01 => "XGA Controller",
-                0x02 => "3D Controller",
-                _ => "Display Controller",
-            },
-            0x04 => "Multimedia Controller",
-            0x05 => "Memory Controller",
-            0x06 => match self.subclass {
-                0x00 => "Host Bridge",
-                0x01 => "ISA Bridge",
-                0x04 => "PCI-PCI Bridge",
-                _ => "Bridge Device",
-            },
-            0x07 => "Communication Controller",
-            0x08 => "System Peripheral",
-            0x09 => "Input Device",
-            0x0C => match self.subclass {
-                0x03 => "USB Controller",
-                0x05 => "SMBus",
-                _ => "Serial Bus Controller",
-            },
-            0x0D => "Wireless Controller",
-            0x10 => "Encryption Controller",
-            0x11 => "Signal Processing",
-            _ => "Unknown Device",
+            header_type:0, irq_line:0xFF, bars: [Bar::NONE; MAX_BARS] }
+    }
+
+    /// BARs que define la cabecera (tipo 0: 6, puente PCI-PCI: 2, CardBus: 1).
+    pub fn bar_count(&self) -> usize {
+        match self.header_type & 0x7F {
+            0x00 => 6,
+            0x01 => 2,
+            0x02 => 1,
+            _    => 0,
         }
     }
 
-    pub fn vendor_name(&self) -> &'static str {
-        match self.vendor_id {
-            0x8086 => "Intel",
-            0x1022 => "AMD",
-            0x10DE => "NVIDIA",
-            0x1002 => "AMD/ATI",
-            0x14E4 => "Broadcom",
-            0x1AF4 => "VirtIO",
-            0x1234 => "QEMU/Bochs",
-            0x106B => "Apple",
-            0x15AD => "VMware",
-            0x80EE => "VirtualBox",
-            _ => "Unknown",
+    /// Nombre legible de la clase; `None` si no está en `CLASS_NAMES`.
+    pub fn class_name(&self) -> Option<&'static str> {
+        class_name(self.class_code, self.subclass, self.prog_if)
+    }
+
+    /// Nombre del fabricante; `None` si el ID no está en `VENDOR_NAMES`.
+    pub fn vendor_name(&self) -> Option<&'static str> {
+        vendor_name(self.vendor_id)
+    }
+
+    /// Activa la decodificación de memoria y el bus master (bits 1 y 2 del
+    /// registro de comando), necesarios para MMIO y DMA del dispositivo.
+    pub fn enable_mem_and_dma(&self) {
+        unsafe {
+            let cmd = pci_read32(self.bus, self.device, self.function, 0x04) & 0xFFFF;
+            pci_write32(self.bus, self.device, self.function, 0x04, cmd | 0x6);
         }
     }
 }
 
+// ── Tablas de nombres ─────────────────────────────────────────────────────────
+//
+// Subconjunto de pci.ids con lo que suele aparecer en PCs y máquinas
+// virtuales. Quien muestre un ID que no está aquí debe imprimir el hex.
+
+/// Vendor IDs ordenados para búsqueda binaria.
+static VENDOR_NAMES: &[(u16, &str)] = &[
+    (0x1000, "LSI Logic"),
+    (0x1002, "AMD/ATI"),
+    (0x1013, "Cirrus Logic"),
+    (0x1022, "AMD"),
+    (0x1028, "Dell"),
+    (0x102B, "Matrox"),
+    (0x1033, "NEC"),
+    (0x1039, "SiS"),
+    (0x103C, "HP"),
+    (0x104C, "Texas Instruments"),
+    (0x106B, "Apple"),
+    (0x10B5, "PLX Technology"),
+    (0x10DE, "NVIDIA"),
+    (0x10EC, "Realtek"),
+    (0x1106, "VIA"),
+    (0x1179, "Toshiba"),
+    (0x1217, "O2 Micro"),
+    (0x1234, "QEMU/Bochs"),
+    (0x126F, "Silicon Motion"),
+    (0x1414, "Microsoft"),
+    (0x144D, "Samsung"),
+    (0x14E4, "Broadcom"),
+    (0x15AD, "VMware"),
+    (0x15B3, "Mellanox"),
+    (0x15B7, "SanDisk"),
+    (0x168C, "Qualcomm Atheros"),
+    (0x17AA, "Lenovo"),
+    (0x1912, "Renesas"),
+    (0x1969, "Qualcomm Atheros"),
+    (0x1987, "Phison"),
+    (0x1AB8, "Parallels"),
+    (0x1AF4, "Red Hat VirtIO"),
+    (0x1B21, "ASMedia"),
+    (0x1B36, "Red Hat QEMU"),
+    (0x1B4B, "Marvell"),
+    (0x1C5C, "SK hynix"),
+    (0x2646, "Kingston"),
+    (0x5853, "XenSource"),
+    (0x8086, "Intel"),
+    (0x8087, "Intel"),
+    (0x80EE, "VirtualBox"),
+    (0x9004, "Adaptec"),
+    (0x9005, "Adaptec"),
+];
+
+struct ClassName {
+    class:    u8,
+    /// `None` = cualquier subclase / prog-if.
+    subclass: Option<u8>,
+    prog_if:  Option<u8>,
+    name:     &'static str,
+}
+
+/// Búsqueda lineal: dentro de cada clase las entradas más específicas van
+/// primero, así la primera coincidencia es la mejor.
+static CLASS_NAMES: &[ClassName] = &[
+    ClassName { class: 0x00, subclass: Some(0x01), prog_if: None, name: "VGA (pre-2.0)" },
+    ClassName { class: 0x00, subclass: None, prog_if: None, name: "Unclassified" },
+    ClassName { class: 0x01, subclass: Some(0x00), prog_if: None, name: "SCSI Controller" },
+    ClassName { class: 0x01, subclass: Some(0x01), prog_if: None, name: "IDE Controller" },
+    ClassName { class: 0x01, subclass: Some(0x02), prog_if: None, name: "Floppy Controller" },
+    ClassName { class: 0x01, subclass: Some(0x04), prog_if: None, name: "RAID Controller" },
+    ClassName { class: 0x01, subclass: Some(0x05), prog_if: None, name: "ATA Controller" },
+    ClassName { class: 0x01, subclass: Some(0x06), prog_if: Some(0x01), name: "SATA (AHCI)" },
+    ClassName { class: 0x01, subclass: Some(0x06), prog_if: None, name: "SATA Controller" },
+    ClassName { class: 0x01, subclass: Some(0x07), prog_if: None, name: "SAS Controller" },
+    ClassName { class: 0x01, subclass: Some(0x08), prog_if: Some(0x02), name: "NVMe Controller" },
+    ClassName { class: 0x01, subclass: Some(0x08), prog_if: None, name: "NVM Controller" },
+    ClassName { class: 0x01, subclass: None, prog_if: None, name: "Mass Storage" },
+    ClassName { class: 0x02, subclass: Some(0x00), prog_if: None, name: "Ethernet Controller" },
+    ClassName { class: 0x02, subclass: None, prog_if: None, name: "Network Controller" },
+    ClassName { class: 0x03, subclass: Some(0x00), prog_if: None, name: "VGA Controller" },
+    ClassName { class: 0x03, subclass: Some(0x01), prog_if: None, name: "XGA Controller" },
+    ClassName { class: 0x03, subclass: Some(0x02), prog_if: None, name: "3D Controller" },
+    ClassName { class: 0x03, subclass: None, prog_if: None, name: "Display Controller" },
+    ClassName { class: 0x04, subclass: Some(0x00), prog_if: None, name: "Video Device" },
+    ClassName { class: 0x04, subclass: Some(0x01), prog_if: None, name: "Audio Device" },
+    ClassName { class: 0x04, subclass: Some(0x03), prog_if: None, name: "HD Audio" },
+    ClassName { class: 0x04, subclass: None, prog_if: None, name: "Multimedia Controller" },
+    ClassName { class: 0x05, subclass: Some(0x00), prog_if: None, name: "RAM Controller" },
+    ClassName { class: 0x05, subclass: None, prog_if: None, name: "Memory Controller" },
+    ClassName { class: 0x06, subclass: Some(0x00), prog_if: None, name: "Host Bridge" },
+    ClassName { class: 0x06, subclass: Some(0x01), prog_if: None, name: "ISA Bridge" },
+    ClassName { class: 0x06, subclass: Some(0x04), prog_if: None, name: "PCI-PCI Bridge" },
+    ClassName { class: 0x06, subclass: Some(0x07), prog_if: None, name: "CardBus Bridge" },
+    ClassName { class: 0x06, subclass: None, prog_if: None, name: "Bridge Device" },
+    ClassName { class: 0x07, subclass: Some(0x00), prog_if: None, name: "Serial Controller" },
+    ClassName { class: 0x07, subclass: Some(0x01), prog_if: None, name: "Parallel Controller" },
+    ClassName { class: 0x07, subclass: None, prog_if: None, name: "Communication Controller" },
+    ClassName { class: 0x08, subclass: Some(0x00), prog_if: None, name: "PIC" },
+    ClassName { class: 0x08, subclass: Some(0x01), prog_if: None, name: "DMA Controller" },
+    ClassName { class: 0x08, subclass: Some(0x02), prog_if: None, name: "Timer" },
+    ClassName { class: 0x08, subclass: Some(0x03), prog_if: None, name: "RTC Controller" },
+    ClassName { class: 0x08, subclass: Some(0x05), prog_if: None, name: "SD Host Controller" },
+    ClassName { class: 0x08, subclass: Some(0x06), prog_if: None, name: "IOMMU" },
+    ClassName { class: 0x08, subclass: None, prog_if: None, name: "System Peripheral" },
+    ClassName { class: 0x09, subclass: Some(0x00), prog_if: None, name: "Keyboard Controller" },
+    ClassName { class: 0x09, subclass: Some(0x02), prog_if: None, name: "Mouse Controller" },
+    ClassName { class: 0x09, subclass: None, prog_if: None, name: "Input Device" },
+    ClassName { class: 0x0B, subclass: None, prog_if: None, name: "Processor" },
+    ClassName { class: 0x0C, subclass: Some(0x00), prog_if: None, name: "FireWire Controller" },
+    ClassName { class: 0x0C, subclass: Some(0x03), prog_if: Some(0x00), name: "USB UHCI" },
+    ClassName { class: 0x0C, subclass: Some(0x03), prog_if: Some(0x10), name: "USB OHCI" },
+    ClassName { class: 0x0C, subclass: Some(0x03), prog_if: Some(0x20), name: "USB EHCI" },
+    ClassName { class: 0x0C, subclass: Some(0x03), prog_if: Some(0x30), name: "USB xHCI" },
+    ClassName { class: 0x0C, subclass: Some(0x03), prog_if: None, name: "USB Controller" },
+    ClassName { class: 0x0C, subclass: Some(0x05), prog_if: None, name: "SMBus" },
+    ClassName { class: 0x0C, subclass: None, prog_if: None, name: "Serial Bus Controller" },
+    ClassName { class: 0x0D, subclass: None, prog_if: None, name: "Wireless Controller" },
+    ClassName { class: 0x10, subclass: None, prog_if: None, name: "Encryption Controller" },
+    ClassName { class: 0x11, subclass: None, prog_if: None, name: "Signal Processing" },
+    ClassName { class: 0x12, subclass: None, prog_if: None, name: "Processing Accelerator" },
+];
+
+pub fn vendor_name(vendor_id: u16) -> Option<&'static str> {
+    VENDOR_NAMES.binary_search_by_key(&vendor_id, |&(id, _)| id)
+        .ok()
+        .map(|i| VENDOR_NAMES[i].1)
+}
+
+pub fn class_name(class: u8, subclass: u8, prog_if: u8) -> Option<&'static str> {
+    CLASS_NAMES.iter()
+        .find(|e| e.class == class
+            && e.subclass.is_none_or(|s| s == subclass)
+            && e.prog_if.is_none_or(|p| p == prog_if))
+        .map(|e| e.name)
+}
+
 #[inline(always)]
 unsafe fn outl(p: u16, v: u32) {
     core::arch::asm!("out dx, eax", in("dx") p, in("eax") v, options(nostack, nomem));
@@ -108,6 +290,66 @@ pub unsafe fn pci_read32(bus: u8, dev: u8, func: u8, reg: u8) -> u32 {
     inl(PCI_DATA)
 }
 
+unsafe fn pci_write32(bus: u8, dev: u8, func: u8, reg: u8, v: u32) {
+    outl(PCI_ADDR, make_addr(bus, dev, func, reg));
+    outl(PCI_DATA, v);
+}
+
+/// Copia los 256 bytes del espacio de configuración de una función.
+pub fn read_config(bus: u8, dev: u8, func: u8, out: &mut [u8; CONFIG_SPACE]) {
+    for reg in (0..CONFIG_SPACE).step_by(4) {
+        let v = unsafe { pci_read32(bus, dev, func, reg as u8) };
+        out[reg..reg + 4].copy_from_slice(&v.to_le_bytes());
+    }
+}
+
+/// Escribe todo unos en `reg`, lee la máscara y restaura `orig`.
+unsafe fn probe_bar(bus: u8, dev: u8, func: u8, reg: u8, orig: u32) -> u32 {
+    pci_write32(bus, dev, func, reg, 0xFFFF_FFFF);
+    let mask = pci_read32(bus, dev, func, reg);
+    pci_write32(bus, dev, func, reg, orig);
+    mask
+}
+
+/// Decodifica y mide los `count` primeros BARs de una función.
+unsafe fn read_bars(bus: u8, dev: u8, func: u8, count: usize) -> [Bar; MAX_BARS] {
+    let mut bars = [Bar::NONE; MAX_BARS];
+    if count == 0 { return bars; }
+
+    // Sin decodificación de I/O ni memoria mientras el BAR vale 0xFFFFFFFF.
+    // Solo se escribe el registro de comando: los bits de estado son RW1C y
+    // escribir 0 no los altera.
+    let cmd = pci_read32(bus, dev, func, 0x04) & 0xFFFF;
+    pci_write32(bus, dev, func, 0x04, cmd & !0x3);
+
+    let mut i = 0;
+    while i < count {
+        let reg = 0x10 + (i as u8) * 4;
+        let lo = pci_read32(bus, dev, func, reg);
+        let lo_mask = probe_bar(bus, dev, func, reg, lo);
+        let is64 = lo & 1 == 0 && (lo >> 1) & 0x3 == 0x2 && i + 1 < count;
+        let (hi, hi_mask) = if is64 {
+            let hi = pci_read32(bus, dev, func, reg + 4);
+            (hi, probe_bar(bus, dev, func, reg + 4, hi))
+        } else {
+            (0, 0)
+        };
+
+        let mut bar = Bar::decode(lo, hi);
+        bar.size = Bar::size_from_mask(bar.kind, lo_mask, hi_mask);
+        if bar.size != 0 { bars[i] = bar; }
+        if is64 {
+            if bar.size != 0 { bars[i + 1].kind = BarKind::Upper; }
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+
+    pci_write32(bus, dev, func, 0x04, cmd);
+    bars
+}
+
 pub unsafe fn pci_read8(bus: u8, dev: u8, func: u8, reg: u8) -> u8 {
     let v = pci_read32(bus, dev, func, reg & !3);
     (v >> ((reg & 3) * 8)) as u8
@@ -120,6 +362,12 @@ pub struct PciBus {
 
 impl PciBus {
     pub fn scan() -> Self {
+        Self::scan_with(|_, _| {})
+    }
+
+    /// Como `scan`, pero llama a `on_probe(bus, dispositivo)` antes de probar
+    /// cada ranura (progreso en pantalla durante el arranque).
+    pub fn scan_with<F: FnMut(u8, u8)>(mut on_probe: F) -> Self {
         let mut bus = PciBus {
             devices: [PciDevice::empty(); MAX_PCI_DEVICES],
             count: 0,
@@ -127,6 +375,7 @@ impl PciBus {
         unsafe {
             'outer: for b in 0u8..=255u8 {
                 for d in 0u8..32u8 {
+                    on_probe(b, d);
                     let id = pci_read32(b, d, 0, 0);
                     let vendor = (id & 0xFFFF) as u16;
                     if vendor == 0xFFFF { continue; }
@@ -142,7 +391,7 @@ impl PciBus {
                         let cls   = pci_read32(b, d, f, 0x08);
                         let irqr  = pci_read32(b, d, f, 0x3C);
                         if bus.count >= MAX_PCI_DEVICES { break 'outer; }
-                        bus.devices[bus.count] = PciDevice {
+                        let mut dev = PciDevice {
                             bus: b, device: d, function: f,
                             vendor_id:  fvendor,
                             device_id:  (fid >> 16) as u16,
@@ -151,7 +400,10 @@ impl PciBus {
                             prog_if:    (cls >>  8) as u8,
                             header_type: pci_read8(b, d, f, 0x0E),
                             irq_line:   (irqr & 0xFF) as u8,
+                            bars:       [Bar::NONE; MAX_BARS],
                         };
+                        dev.bars = read_bars(b, d, f, dev.bar_count());
+                        bus.devices[bus.count] = dev;
                         bus.count += 1;
                     }
                 }
@@ -159,4 +411,102 @@ impl PciBus {
         }
         bus
     }
-}
\ No newline at end of file
+
+    pub fn find(&self, bus: u8, device: u8, function: u8) -> Option<&PciDevice> {
+        self.devices[..self.count].iter()
+            .find(|d| d.bus == bus && d.device == device && d.function == function)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_io_and_mem32() {
+        let io = Bar::decode(0xC041, 0xDEAD_BEEF);
+        assert!(io.kind == BarKind::Io && io.base == 0xC040 && !io.prefetch);
+
+        let mem = Bar::decode(0xFEBF_1000, 0xDEAD_BEEF);
+        assert!(mem.kind == BarKind::Mem32 && !mem.prefetch);
+        assert_eq!(mem.base, 0xFEBF_1000);
+
+        let pf = Bar::decode(0xE000_0008, 0);
+        assert!(pf.kind == BarKind::Mem32 && pf.prefetch);
+        assert_eq!(pf.base, 0xE000_0000);
+    }
+
+    #[test]
+    fn decode_mem64_uses_high_dword() {
+        let b = Bar::decode(0xC000_000C, 0x0000_0080);
+        assert!(b.kind == BarKind::Mem64 && b.prefetch);
+        assert_eq!(b.base, 0x80_C000_0000);
+        assert_eq!(b.size, 0);
+    }
+
+    #[test]
+    fn size_is_lowest_writable_bit() {
+        assert_eq!(Bar::size_from_mask(BarKind::Mem32, 0xFFFF_F000, 0), 0x1000);
+        // Los bits de tipo no cuentan
+        assert_eq!(Bar::size_from_mask(BarKind::Mem32, 0xFFFF_FFF8, 0), 0x10);
+        assert_eq!(Bar::size_from_mask(BarKind::Io, 0xFFFF_FFE1, 0), 0x20);
+        assert_eq!(Bar::size_from_mask(BarKind::Mem64, 0x0000_000C, 0xFFFF_FFF0), 0x10_0000_0000);
+        assert_eq!(Bar::size_from_mask(BarKind::Mem64, 0xF000_000C, 0xFFFF_FFFF), 0x1000_0000);
+    }
+
+    #[test]
+    fn unimplemented_bar_has_no_size() {
+        assert_eq!(Bar::size_from_mask(BarKind::Mem32, 0, 0), 0);
+        assert_eq!(Bar::size_from_mask(BarKind::Io, 0x1, 0), 0);
+        assert_eq!(Bar::size_from_mask(BarKind::Upper, 0xFFFF_FFFF, 0xFFFF_FFFF), 0);
+        assert_eq!(Bar::size_from_mask(BarKind::Unused, 0xFFFF_F000, 0), 0);
+    }
+
+    #[test]
+    fn bar_count_per_header_type() {
+        let mut d = PciDevice::empty();
+        assert_eq!(d.bar_count(), 6);
+        d.header_type = 0x81;   // bit multifunción
+        assert_eq!(d.bar_count(), 2);
+        d.header_type = 0x02;
+        assert_eq!(d.bar_count(), 1);
+        d.header_type = 0x7F;
+        assert_eq!(d.bar_count(), 0);
+    }
+
+    #[test]
+    fn vendor_table_is_sorted_for_binary_search() {
+        assert!(VENDOR_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(vendor_name(0x8086), Some("Intel"));
+        assert_eq!(vendor_name(0x80EE), Some("VirtualBox"));
+        assert_eq!(vendor_name(0x9005), Some("Adaptec"));
+        assert_eq!(vendor_name(0x0000), None);
+        assert_eq!(vendor_name(0xFFFF), None);
+    }
+
+    #[test]
+    fn class_name_prefers_specific_entries() {
+        assert_eq!(class_name(0x01, 0x06, 0x01), Some("SATA (AHCI)"));
+        assert_eq!(class_name(0x01, 0x06, 0x00), Some("SATA Controller"));
+        assert_eq!(class_name(0x0C, 0x03, 0x30), Some("USB xHCI"));
+        assert_eq!(class_name(0x0C, 0x03, 0x40), Some("USB Controller"));
+        // Subclase desconocida: nombre genérico de la clase
+        assert_eq!(class_name(0x01, 0x80, 0x00), Some("Mass Storage"));
+        assert_eq!(class_name(0x13, 0x00, 0x00), None);
+        assert_eq!(class_name(0xFF, 0x00, 0x00), None);
+    }
+
+    #[test]
+    fn generic_class_entries_come_last() {
+        // Una entrada comodín no puede tapar otra más específica de su clase
+        for (i, e) in CLASS_NAMES.iter().enumerate() {
+            for later in &CLASS_NAMES[i + 1..] {
+                if later.class != e.class { continue; }
+                let covers = e.subclass.is_none_or(|s| Some(s) == later.subclass)
+                    && e.prog_if.is_none_or(|p| Some(p) == later.prog_if);
+                assert!(!covers, "{} tapa a {}", e.name, later.name);
+            }
+        }
+    }
+}
+
@@ -1,7 +1,22 @@
 // kernel/src/keyboard.rs - PORTIX PS/2 Keyboard Driver
 // v1.1: añadido feed_byte() para el drenado unificado del buffer PS/2
+// v1.2: mapa scancode→carácter en tabla (layout US embebido o `loadkeys`)
+// v1.3: layout ES embebido (`keymap es`) con AltGr y teclas muertas
+// v1.4: repetición de teclas (typematic) por software con pit::ticks();
+//       `decode` recibe el tick para poder probarla sin el PIT
+// v1.5: Bloq Mayús / Bloq Num / Bloq Despl con LEDs (comando 0xED) y
+//       teclado numérico según Bloq Num
+// v1.6: detección de teclado ausente (comando sin respuesta) y sondeo
+//       periódico con ECHO hasta que vuelve a responder
+// v1.7: shift() para que el ratón sepa si Shift está pulsado (Shift+clic)
 #![allow(dead_code)]
 
+use core::ptr::{addr_of, addr_of_mut};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "1.6";
+
 const PS2_DATA:   u16 = 0x60;
 const PS2_STATUS: u16 = 0x64;
 
@@ -12,6 +27,23 @@ unsafe fn inb(port: u16) -> u8 {
     v
 }
 
+#[inline(always)]
+unsafe fn outb(port: u16, v: u8) {
+    core::arch::asm!("out dx, al", in("dx") port, in("al") v, options(nostack, nomem));
+}
+
+/// Escribe un byte al teclado (puerto 0x60) cuando el buffer de entrada del
+/// controlador está libre. false si no se libera a tiempo.
+unsafe fn kbd_write(b: u8) -> bool {
+    let mut lim = 100_000u32;
+    while inb(PS2_STATUS) & 0x02 != 0 {
+        if lim == 0 { return false; }
+        lim -= 1;
+    }
+    outb(PS2_DATA, b);
+    true
+}
+
 // ── Key enum ──────────────────────────────────────────────────────────────────
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Key {
@@ -21,31 +53,418 @@ pub enum Key {
     Tab,
     Escape,
     Up, Down, Left, Right,
-    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11,
     Delete, Home, End, PageUp, PageDown, Insert,
 }
 
+// ── Mapa de teclado ───────────────────────────────────────────────────────────
+//
+// Carácter de cada scancode de make (set 1, < 0x80) sin y con Shift; 0 = la
+// tecla no escribe nada. Las teclas que `decode` trata antes (Esc, Enter,
+// modificadores, F1-F11, flechas) no pasan por el mapa.
+//
+// Formato de archivo para `loadkeys`, una tecla por línea:
+//
+//     # comentario
+//     <scancode> <normal> [<shift>]        p. ej.  0x27 n N
+//
+// El scancode va en hexadecimal (0x..) o decimal. Cada carácter es uno ASCII
+// imprimible, `0xNN`, `space` o `none` (sin carácter). Sin columna shift,
+// una letra usa su mayúscula y el resto repite el normal. Las teclas que el
+// archivo no menciona conservan el carácter del layout US.
+//
+// Layout ES: la fuente solo tiene ASCII, así que ñ/ç/º/ª/¡/¿/· se escriben
+// como su carácter ASCII más cercano (n, c, o, a, !, ?, .). AltGr (Alt
+// derecho) usa la tabla `altgr` (| @ # ~ [ ] { } \); si la tecla no tiene
+// carácter AltGr escribe el normal. Las teclas marcadas en `dead` (` ^ ´ ¨)
+// no escriben al pulsarlas: la siguiente tecla decide. Espacio (o la misma
+// muerta otra vez) da el acento suelto; una vocal da la vocal sin acento
+// (la versión ASCII de á, è, ü...); cualquier otra tecla se escribe sola.
+
+pub const KEYMAP_CODES: usize = 0x80;
+/// Bits de `Keymap::dead`: la tecla es muerta sin / con Shift.
+pub const DEAD_NORMAL: u8 = 0x01;
+pub const DEAD_SHIFT:  u8 = 0x02;
+const KEYMAP_NAME_MAX:  usize = 32;
+
+#[derive(Clone, Copy)]
+pub struct Keymap {
+    pub normal:   [u8; KEYMAP_CODES],
+    pub shift:    [u8; KEYMAP_CODES],
+    pub altgr:    [u8; KEYMAP_CODES],
+    pub dead:     [u8; KEYMAP_CODES],
+    name:         [u8; KEYMAP_NAME_MAX],
+    name_len:     usize,
+}
+
+impl Keymap {
+    /// Layout US embebido (el de por defecto).
+    pub const fn us() -> Self {
+        const NUMS_N: &[u8] = b"1234567890-=";
+        const NUMS_S: &[u8] = b"!@#$%^&*()_+";
+        const MAP: &[(u8, u8, u8)] = &[
+            (0x10,b'q',b'Q'),(0x11,b'w',b'W'),(0x12,b'e',b'E'),(0x13,b'r',b'R'),
+            (0x14,b't',b'T'),(0x15,b'y',b'Y'),(0x16,b'u',b'U'),(0x17,b'i',b'I'),
+            (0x18,b'o',b'O'),(0x19,b'p',b'P'),(0x1A,b'[',b'{'),(0x1B,b']',b'}'),
+            (0x1E,b'a',b'A'),(0x1F,b's',b'S'),(0x20,b'd',b'D'),(0x21,b'f',b'F'),
+            (0x22,b'g',b'G'),(0x23,b'h',b'H'),(0x24,b'j',b'J'),(0x25,b'k',b'K'),
+            (0x26,b'l',b'L'),(0x27,b';',b':'),(0x28,b'\'',b'"'),(0x29,b'`',b'~'),
+            (0x2B,b'\\',b'|'),
+            (0x2C,b'z',b'Z'),(0x2D,b'x',b'X'),(0x2E,b'c',b'C'),(0x2F,b'v',b'V'),
+            (0x30,b'b',b'B'),(0x31,b'n',b'N'),(0x32,b'm',b'M'),
+            (0x33,b',',b'<'),(0x34,b'.',b'>'),(0x35,b'/',b'?'),
+            (0x39,b' ',b' '),
+        ];
+        let mut km = Keymap {
+            normal: [0; KEYMAP_CODES], shift: [0; KEYMAP_CODES],
+            altgr:  [0; KEYMAP_CODES], dead:  [0; KEYMAP_CODES],
+            name: [0; KEYMAP_NAME_MAX], name_len: 2,
+        };
+        km.name[0] = b'u'; km.name[1] = b's';
+        let mut i = 0;
+        while i < NUMS_N.len() {
+            km.normal[0x02 + i] = NUMS_N[i];
+            km.shift[0x02 + i]  = NUMS_S[i];
+            i += 1;
+        }
+        let mut i = 0;
+        while i < MAP.len() {
+            let (sc, lo, hi) = MAP[i];
+            km.normal[sc as usize] = lo;
+            km.shift[sc as usize]  = hi;
+            i += 1;
+        }
+        km
+    }
+
+    /// Layout ES (QWERTY España) embebido, sobre las letras del US.
+    pub const fn es() -> Self {
+        const NUMS_N: &[u8] = b"1234567890'!";   // ¡ → !
+        const NUMS_S: &[u8] = b"!\".$%&/()=??";  // · → .  ¿ → ?
+        // (scancode, normal, shift, altgr, muerta)
+        const MAP: &[(u8, u8, u8, u8, u8)] = &[
+            (0x02, b'1',  b'!',  b'|',  0),
+            (0x03, b'2',  b'"',  b'@',  0),
+            (0x04, b'3',  b'.',  b'#',  0),
+            (0x05, b'4',  b'$',  b'~',  0),
+            (0x1A, b'`',  b'^',  b'[',  DEAD_NORMAL | DEAD_SHIFT),
+            (0x1B, b'+',  b'*',  b']',  0),
+            (0x27, b'n',  b'N',  0,     0),                        // ñ Ñ
+            (0x28, b'\'', b'"',  b'{',  DEAD_NORMAL | DEAD_SHIFT), // ´ ¨
+            (0x29, b'o',  b'a',  b'\\', 0),                        // º ª
+            (0x2B, b'c',  b'C',  b'}',  0),                        // ç Ç
+            (0x33, b',',  b';',  0,     0),
+            (0x34, b'.',  b':',  0,     0),
+            (0x35, b'-',  b'_',  0,     0),
+            (0x56, b'<',  b'>',  0,     0),                        // tecla ISO
+        ];
+        let mut km = Keymap::us();
+        km.name[0] = b'e'; km.name[1] = b's';
+        let mut i = 0;
+        while i < NUMS_N.len() {
+            km.normal[0x02 + i] = NUMS_N[i];
+            km.shift[0x02 + i]  = NUMS_S[i];
+            i += 1;
+        }
+        let mut i = 0;
+        while i < MAP.len() {
+            let (sc, lo, hi, ag, dead) = MAP[i];
+            km.normal[sc as usize] = lo;
+            km.shift[sc as usize]  = hi;
+            km.altgr[sc as usize]  = ag;
+            km.dead[sc as usize]   = dead;
+            i += 1;
+        }
+        km
+    }
+
+    pub fn name(&self) -> &[u8] { &self.name[..self.name_len] }
+
+    pub fn set_name(&mut self, name: &[u8]) {
+        let n = name.len().min(KEYMAP_NAME_MAX);
+        self.name[..n].copy_from_slice(&name[..n]);
+        self.name_len = n;
+    }
+
+    pub fn is_default(&self) -> bool { self.name() == b"us" }
+}
+
+static mut KEYMAP: Keymap = Keymap::us();
+
+/// Mapa activo (copia).
+pub fn keymap() -> Keymap { unsafe { *addr_of!(KEYMAP) } }
+
+pub fn set_keymap(km: &Keymap) { unsafe { *addr_of_mut!(KEYMAP) = *km; } }
+
+pub fn reset_keymap() { set_keymap(&Keymap::us()); }
+
+/// Scancodes que `decode` resuelve sin consultar el mapa.
+pub fn is_reserved_scancode(sc: u8) -> bool {
+    matches!(sc, 0x01 | 0x0E | 0x0F | 0x1C | 0x1D | 0x2A | 0x36 | 0x38 | 0x3A
+               | 0x37 | 0x3B..=0x53 | 0x57)
+}
+
+fn parse_num(tok: &[u8]) -> Option<u32> {
+    let (digits, radix) = match tok {
+        [b'0', b'x' | b'X', rest @ ..] if !rest.is_empty() => (rest, 16),
+        _ => (tok, 10),
+    };
+    if digits.is_empty() || digits.len() > 4 { return None; }
+    digits.iter().try_fold(0u32, |acc, &b| Some(acc * radix + (b as char).to_digit(radix)?))
+}
+
+fn parse_key_char(tok: &[u8]) -> Option<u8> {
+    match tok {
+        b"none"  => Some(0),
+        b"space" => Some(b' '),
+        [c] if c.is_ascii_graphic() => Some(*c),
+        [b'0', b'x' | b'X', ..] => match parse_num(tok)? {
+            0 => Some(0),
+            n @ 0x20..=0x7E => Some(n as u8),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Resultado de `parse_keymap`.
+pub struct KeymapParse {
+    pub map:     Keymap,
+    /// Líneas de tecla aceptadas.
+    pub entries: u32,
+    pub errors:  u32,
+}
+
+/// Interpreta un archivo de mapa (ver formato arriba) partiendo del layout
+/// US. Cada línea inválida se notifica con `on_error(nº de línea, motivo)`;
+/// con algún error el mapa no debe instalarse.
+pub fn parse_keymap(data: &[u8], mut on_error: impl FnMut(usize, &'static str)) -> KeymapParse {
+    let mut out = KeymapParse { map: Keymap::us(), entries: 0, errors: 0 };
+    for (idx, raw) in data.split(|&b| b == b'\n').enumerate() {
+        let line = raw.strip_suffix(b"\r").unwrap_or(raw);
+        let mut fields = line.split(|&b| b == b' ' || b == b'\t').filter(|f| !f.is_empty());
+        let Some(first) = fields.next() else { continue };
+        if first[0] == b'#' { continue; }
+        let mut fail = |msg| { on_error(idx + 1, msg); out.errors += 1; };
+
+        let (Some(normal), shift, None) = (fields.next(), fields.next(), fields.next()) else {
+            fail("se esperan <scancode> <normal> [<shift>]");
+            continue;
+        };
+        let sc = match parse_num(first) {
+            Some(n @ 0x01..=0x7F) => n as u8,
+            _ => { fail("scancode no valido (0x01-0x7F)"); continue; }
+        };
+        if is_reserved_scancode(sc) { fail("scancode reservado (Esc, Enter, Shift, F1-F11, bloqueos, numerico...)"); continue; }
+        let Some(lo) = parse_key_char(normal) else { fail("caracter normal no valido"); continue };
+        let hi = match shift {
+            Some(tok) => match parse_key_char(tok) {
+                Some(c) => c,
+                None    => { fail("caracter shift no valido"); continue; }
+            },
+            None => lo.to_ascii_uppercase(),
+        };
+        out.map.normal[sc as usize] = lo;
+        out.map.shift[sc as usize]  = hi;
+        out.entries += 1;
+    }
+    out
+}
+
+// ── Repetición (typematic) ────────────────────────────────────────────────────
+//
+// La tecla mantenida se repite por software: REPEAT_DELAY ticks tras la
+// pulsación y luego cada REPEAT_RATE, hasta su break code. Los make codes que
+// repite el propio teclado para la tecla mantenida se descartan para no
+// duplicar la cadencia. Solo se repiten caracteres y teclas de edición o
+// navegación; modificadores, Esc y F1-F11 nunca. Pulsar otra tecla que
+// produzca algo detiene la repetición de la anterior.
+
+/// Retardo inicial (ticks de 10 ms → 500 ms).
+pub const REPEAT_DELAY: u64 = 50;
+/// Intervalo entre repeticiones (ticks de 10 ms → 30 ms).
+pub const REPEAT_RATE:  u64 = 3;
+
+fn is_repeatable(key: Key) -> bool {
+    matches!(key, Key::Char(_) | Key::Backspace | Key::Delete | Key::Enter | Key::Tab
+                | Key::Up | Key::Down | Key::Left | Key::Right | Key::PageUp | Key::PageDown)
+}
+
+// ── Bloqueos y LEDs ───────────────────────────────────────────────────────────
+//
+// Bits del byte de datos del comando 0xED (y de `locks()`).
+pub const LED_SCROLL: u8 = 0x01;
+pub const LED_NUM:    u8 = 0x02;
+pub const LED_CAPS:   u8 = 0x04;
+
+// Secuencia: 0xED → ACK (0xFA) → máscara → ACK. Las respuestas llegan por el
+// drenado unificado de main como cualquier otro byte de teclado, así que no
+// se espera activamente: `feed_byte` las consume mientras hay un comando en
+// curso. 0xFE (RESEND) repite el último byte. Si no llega respuesta en
+// LED_TIMEOUT ticks el comando se da por perdido y la siguiente pulsación de
+// un bloqueo lo reintenta. Con una máscara nueva pedida a mitad de comando
+// se termina el actual y se envía otro al recibir el último ACK.
+
+const KBD_ACK:    u8 = 0xFA;
+const KBD_RESEND: u8 = 0xFE;
+const LED_TIMEOUT: u64 = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum LedCmd {
+    Idle,
+    /// 0xED enviado, esperando ACK.
+    Cmd,
+    /// Máscara enviada, esperando ACK.
+    Data,
+}
+
+// ── Detección ─────────────────────────────────────────────────────────────────
+//
+// El teclado se da por ausente si no hay controlador o si un comando (LEDs o
+// ECHO) se queda sin respuesta LED_TIMEOUT ticks. Mientras falta, `watch`
+// envía un ECHO (0xEE) cada PROBE_INTERVAL ticks sin esperar la respuesta:
+// cualquier byte que llegue después (el eco, un scancode o el 0xAA del
+// autotest de un teclado recién conectado) lo vuelve a dar por presente.
+
+/// Intervalo entre sondeos con el teclado ausente (ticks de 10 ms → 3 s).
+pub const PROBE_INTERVAL: u64 = 300;
+const KBD_ECHO: u8 = 0xEE;
+const KBD_BAT_OK: u8 = 0xAA;
+
+/// Estado de los bloqueos para la barra de estado (`draw_chrome`).
+static LOCKS: AtomicU8 = AtomicU8::new(0);
+
+/// Bloqueos activos (LED_CAPS | LED_NUM | LED_SCROLL).
+pub fn locks() -> u8 { LOCKS.load(Ordering::Relaxed) }
+
 // ── Keyboard state ────────────────────────────────────────────────────────────
 pub struct KeyboardState {
     shift_l:  bool,
     shift_r:  bool,
-    caps:     bool,
+    caps_lock:   bool,
+    num_lock:    bool,
+    scroll_lock: bool,
+    /// Teclas de bloqueo pulsadas (bits LED_*): la repetición del teclado
+    /// no debe volver a alternarlas.
+    locks_down:  u8,
+    /// Comando de LEDs en curso, máscara que lleva y tick del último envío.
+    led_cmd:     LedCmd,
+    led_mask:    u8,
+    led_tick:    u64,
     ctrl:     bool,
     alt:      bool,
+    /// Alt derecho (E0 38): elige la columna AltGr, no cuenta como Alt.
+    altgr:    bool,
     e0_seen:  bool,
+    /// Acento de una tecla muerta a la espera de la siguiente tecla (0 = no).
+    dead:     u8,
+    /// Tecla mantenida: scancode (bit 8 = prefijo E0) y la Key que produjo.
+    held:        Option<(u16, Key)>,
+    /// Tick de la pulsación de `held`.
+    held_tick:   u64,
+    /// Tick en el que toca la siguiente repetición.
+    next_repeat: u64,
+    /// false si el teclado no responde (ver `watch`).
+    present:     bool,
+    /// Tick del ECHO de sondeo en curso.
+    echo_tick:   Option<u64>,
+    /// Tick del siguiente sondeo.
+    next_probe:  u64,
 }
 
 impl KeyboardState {
     pub const fn new() -> Self {
         KeyboardState {
             shift_l: false, shift_r: false,
-            caps: false, ctrl: false, alt: false,
-            e0_seen: false,
+            caps_lock: false, num_lock: false, scroll_lock: false, locks_down: 0,
+            led_cmd: LedCmd::Idle, led_mask: 0, led_tick: 0,
+            ctrl: false, alt: false, altgr: false,
+            e0_seen: false, dead: 0,
+            held: None, held_tick: 0, next_repeat: 0,
+            present: true, echo_tick: None, next_probe: 0,
         }
     }
 
     #[inline(always)] pub fn ctrl(&self) -> bool { self.ctrl }
     #[inline(always)] pub fn alt(&self)  -> bool { self.alt  }
+    #[inline(always)] pub fn shift(&self) -> bool { self.shift_l || self.shift_r }
+
+    /// Bloqueos activos como máscara LED_*.
+    pub fn leds(&self) -> u8 {
+        (if self.caps_lock { LED_CAPS } else { 0 })
+            | (if self.num_lock { LED_NUM } else { 0 })
+            | (if self.scroll_lock { LED_SCROLL } else { 0 })
+    }
+
+    /// Envía al teclado el estado actual de los LEDs. Main lo llama una vez
+    /// al arrancar para apagar los que dejó encendidos la BIOS.
+    pub fn sync_leds(&mut self) {
+        let mask = self.leds();
+        LOCKS.store(mask, Ordering::Relaxed);
+        // Sin teclado no hay a quién enviarlos: `feed_byte` los sincroniza
+        // cuando vuelve
+        if !self.present { return; }
+        let now = crate::time::pit::ticks();
+        // Con un comando en curso lo reenvía el último ACK (ver `led_reply`)
+        if self.led_cmd != LedCmd::Idle && now < self.led_tick + LED_TIMEOUT { return; }
+        self.led_mask = mask;
+        self.led_tick = now;
+        self.led_cmd  = if unsafe { kbd_write(0xED) } { LedCmd::Cmd } else { LedCmd::Idle };
+    }
+
+    /// false si el teclado no respondió al último comando.
+    pub fn present(&self) -> bool { self.present }
+
+    /// Fija la presencia inicial (main: sin controlador PS/2 no hay teclado).
+    pub fn set_present(&mut self, on: bool) { self.present = on; }
+
+    /// Una vez por vuelta del bucle principal: marca el teclado como ausente
+    /// si un comando caducó sin respuesta y, mientras lo esté, lanza un ECHO
+    /// cada PROBE_INTERVAL ticks. Nunca espera la respuesta.
+    pub fn watch(&mut self, now: u64) {
+        let led_lost  = self.led_cmd != LedCmd::Idle && now >= self.led_tick + LED_TIMEOUT;
+        let echo_lost = self.echo_tick.is_some_and(|t| now >= t + LED_TIMEOUT);
+        if led_lost || echo_lost {
+            self.present   = false;
+            self.led_cmd   = LedCmd::Idle;
+            self.echo_tick = None;
+        }
+        if self.present || self.echo_tick.is_some() || now < self.next_probe { return; }
+        self.next_probe = now + PROBE_INTERVAL;
+        // Sin controlador (estado flotando a 0xFF) no hay a quién preguntar
+        if unsafe { inb(PS2_STATUS) } == 0xFF { return; }
+        if unsafe { kbd_write(KBD_ECHO) } { self.echo_tick = Some(now); }
+    }
+
+    /// Respuesta del teclado a un comando de LEDs. false si el byte no era
+    /// para nosotros (no hay comando en curso o no es ACK/RESEND).
+    fn led_reply(&mut self, b: u8) -> bool {
+        if self.led_cmd == LedCmd::Idle || (b != KBD_ACK && b != KBD_RESEND) { return false; }
+        self.led_tick = crate::time::pit::ticks();
+        let resend = b == KBD_RESEND;
+        self.led_cmd = match (self.led_cmd, resend) {
+            (LedCmd::Cmd, true)  => if unsafe { kbd_write(0xED) } { LedCmd::Cmd } else { LedCmd::Idle },
+            (LedCmd::Data, true) |
+            (LedCmd::Cmd, false) => if unsafe { kbd_write(self.led_mask) } { LedCmd::Data } else { LedCmd::Idle },
+            _ => LedCmd::Idle,
+        };
+        // Cambió algún bloqueo mientras se enviaba: otra vuelta
+        if self.led_cmd == LedCmd::Idle && self.led_mask != self.leds() { self.sync_leds(); }
+        true
+    }
+
+    /// Alterna un bloqueo (bit LED_*) en su primer make code; los repetidos
+    /// mientras sigue pulsada se ignoran.
+    fn toggle_lock(&mut self, bit: u8) {
+        if self.locks_down & bit != 0 { return; }
+        self.locks_down |= bit;
+        match bit {
+            LED_CAPS => self.caps_lock   = !self.caps_lock,
+            LED_NUM  => self.num_lock    = !self.num_lock,
+            _        => self.scroll_lock = !self.scroll_lock,
+        }
+        self.sync_leds();
+    }
 
     // ── poll() — solo para uso cuando NO se usa el drenado unificado ─────────
     //
@@ -53,26 +472,80 @@ impl KeyboardState {
     // mientras ms.poll() también está activo, los dos compiten por el mismo
     // registro 0x60 y se pierden bytes mutuamente.
     // Usar feed_byte() + drenado unificado en main en su lugar.
+    //
+    // Sin byte de teclado pendiente devuelve la repetición de la tecla
+    // mantenida, si toca.
     pub fn poll(&mut self) -> Option<Key> {
         unsafe {
             let st = inb(PS2_STATUS);
-            if st & 0x01 == 0  { return None; }
             // Si el byte es del ratón (AUXB=1), NO lo consumimos —
             // lo dejamos para que ms.poll() lo lea.
-            if st & 0x20 != 0  { return None; }
-            let sc = inb(PS2_DATA);
-            self.decode(sc)
+            if st & 0x01 != 0 && st & 0x20 == 0 {
+                let sc = inb(PS2_DATA);
+                if self.led_reply(sc) { return None; }
+                return self.decode(sc, crate::time::pit::ticks());
+            }
         }
+        self.repeat(crate::time::pit::ticks())
     }
 
     /// Procesa un byte de scancode ya leído del buffer.
     /// Usar en el drenado unificado de main: el caller ya leyó el byte,
     /// ya verificó AUXB=0, y se lo pasa aquí para decodificar.
     pub fn feed_byte(&mut self, sc: u8) -> Option<Key> {
-        self.decode(sc)
+        // Cualquier byte demuestra que hay teclado: recuperar sus LEDs
+        if !self.present {
+            self.present = true;
+            self.sync_leds();
+            if sc == KBD_ECHO || sc == KBD_BAT_OK { self.echo_tick = None; return None; }
+        }
+        if self.echo_tick.is_some() && sc == KBD_ECHO { self.echo_tick = None; return None; }
+        if self.led_reply(sc) { return None; }
+        self.decode(sc, crate::time::pit::ticks())
+    }
+
+    /// Repetición pendiente de la tecla mantenida en el tick `now`. Main la
+    /// consulta una vez por vuelta, después de los bytes recibidos. Si el
+    /// bucle se retrasa no se acumulan repeticiones atrasadas.
+    pub fn repeat(&mut self, now: u64) -> Option<Key> {
+        let (_, key) = self.held?;
+        if now < self.next_repeat { return None; }
+        self.next_repeat = now + REPEAT_RATE;
+        Some(key)
+    }
+
+    /// Tecla que se está repitiendo y tick en que se pulsó.
+    pub fn held(&self) -> Option<(Key, u64)> { self.held.map(|(_, k)| (k, self.held_tick)) }
+
+    /// Decodifica `sc` recibido en el tick `now` (arranca la repetición de
+    /// la tecla pulsada desde ese tick).
+    fn decode(&mut self, sc: u8, now: u64) -> Option<Key> {
+        if sc == 0xE0 { return self.decode_raw(sc); }
+        let code = (sc & 0x7F) as u16 | if self.e0_seen { 0x100 } else { 0 };
+        let make = sc & 0x80 == 0;
+        let held = self.held.map(|(c, _)| c);
+
+        // Repetición del propio teclado: la cadencia la lleva `repeat`
+        if make && held == Some(code) { self.e0_seen = false; return None; }
+
+        let key = self.decode_raw(sc);
+        // Enter, Esc, flechas... anulan una tecla muerta pendiente
+        if key.is_some_and(|k| !matches!(k, Key::Char(_))) { self.dead = 0; }
+
+        if !make {
+            if held == Some(code) { self.held = None; }
+        } else if let Some(k) = key {
+            self.held = None;
+            if is_repeatable(k) {
+                self.held        = Some((code, k));
+                self.held_tick   = now;
+                self.next_repeat = now + REPEAT_DELAY;
+            }
+        }
+        key
     }
 
-    fn decode(&mut self, sc: u8) -> Option<Key> {
+    fn decode_raw(&mut self, sc: u8) -> Option<Key> {
         if sc == 0xE0 { self.e0_seen = true; return None; }
 
         let e0  = self.e0_seen;
@@ -84,7 +557,11 @@ impl KeyboardState {
                 (false, 0x2A) => self.shift_l = false,
                 (false, 0x36) => self.shift_r = false,
                 (false, 0x1D) | (true, 0x1D) => self.ctrl = false,
-                (false, 0x38) | (true, 0x38) => self.alt  = false,
+                (false, 0x38) => self.alt   = false,
+                (true,  0x38) => self.altgr = false,
+                (false, 0x3A) => self.locks_down &= !LED_CAPS,
+                (false, 0x45) => self.locks_down &= !LED_NUM,
+                (false, 0x46) => self.locks_down &= !LED_SCROLL,
                 _ => {}
             }
             return None;
@@ -99,7 +576,7 @@ impl KeyboardState {
                 0x49 => Some(Key::PageUp),0x51 => Some(Key::PageDown),
                 0x52 => Some(Key::Insert),0x53 => Some(Key::Delete),
                 0x1D => { self.ctrl = true; None }
-                0x38 => { self.alt  = true; None }
+                0x38 => { self.altgr = true; None }
                 _ => None,
             };
         }
@@ -110,21 +587,40 @@ impl KeyboardState {
             0x36 => { self.shift_r = true;      None }
             0x1D => { self.ctrl    = true;      None }
             0x38 => { self.alt     = true;      None }
-            0x3A => { self.caps = !self.caps;   None }
+            0x3A => { self.toggle_lock(LED_CAPS);   None }
+            0x45 => { self.toggle_lock(LED_NUM);    None }
+            0x46 => { self.toggle_lock(LED_SCROLL); None }
 
             0x01 => Some(Key::Escape),
             0x0E => Some(Key::Backspace),
             0x0F => Some(Key::Tab),
             0x1C => Some(Key::Enter),
 
-            0x48 => Some(Key::Up),    0x50 => Some(Key::Down),
-            0x4B => Some(Key::Left),  0x4D => Some(Key::Right),
+            // Teclado numérico: dígitos con Bloq Num (Shift lo invierte),
+            // navegación sin él
+            0x47..=0x53 if sc != 0x4A && sc != 0x4E => {
+                if self.num_lock != (self.shift_l || self.shift_r) {
+                    return Some(Key::Char(b"789-456+1230."[(sc - 0x47) as usize]));
+                }
+                match sc {
+                    0x47 => Some(Key::Home),   0x48 => Some(Key::Up),
+                    0x49 => Some(Key::PageUp), 0x4B => Some(Key::Left),
+                    0x4D => Some(Key::Right),  0x4F => Some(Key::End),
+                    0x50 => Some(Key::Down),   0x51 => Some(Key::PageDown),
+                    0x52 => Some(Key::Insert), 0x53 => Some(Key::Delete),
+                    _ => None,
+                }
+            }
+            0x37 => Some(Key::Char(b'*')),
+            0x4A => Some(Key::Char(b'-')),
+            0x4E => Some(Key::Char(b'+')),
 
             0x3B => Some(Key::F1),  0x3C => Some(Key::F2),
             0x3D => Some(Key::F3),  0x3E => Some(Key::F4),
             0x3F => Some(Key::F5),  0x40 => Some(Key::F6),
             0x41 => Some(Key::F7),  0x42 => Some(Key::F8),
             0x43 => Some(Key::F9),  0x44 => Some(Key::F10),
+            0x57 => Some(Key::F11),
 
             _ => {
                 let ch = self.sc_to_char(sc);
@@ -133,40 +629,235 @@ impl KeyboardState {
         }
     }
 
-    fn sc_to_char(&self, sc: u8) -> u8 {
-        let sh  = self.shift_l || self.shift_r;
-        let up  = sh ^ self.caps;
+    fn sc_to_char(&mut self, sc: u8) -> u8 {
+        self.map_char(unsafe { &*addr_of!(KEYMAP) }, sc)
+    }
 
-        const NUMS_N: &[u8] = b"1234567890-=";
-        const NUMS_S: &[u8] = b"!@#$%^&*()_+";
-        if sc >= 0x02 && sc <= 0x0D {
-            let i = (sc - 0x02) as usize;
-            return if sh { NUMS_S[i] } else { NUMS_N[i] };
+    /// Carácter de `sc` en `km` con los modificadores y la tecla muerta
+    /// pendiente del estado actual (0 = ninguno).
+    fn map_char(&mut self, km: &Keymap, sc: u8) -> u8 {
+        let i  = sc as usize;
+        if i >= KEYMAP_CODES { return 0; }
+        let lo = km.normal[i];
+        // Bloq Mayús solo afecta a las letras y Shift lo invierte (Bloq Mayús
+        // + Shift = minúscula); el resto sigue solo a Shift
+        let sh = self.shift_l || self.shift_r;
+        let sh = if lo.is_ascii_alphabetic() { sh ^ self.caps_lock } else { sh };
+        let (ch, dead) = if self.altgr && km.altgr[i] != 0 { (km.altgr[i], false) }
+                         else if sh { (km.shift[i], km.dead[i] & DEAD_SHIFT  != 0) }
+                         else       { (lo,          km.dead[i] & DEAD_NORMAL != 0) };
+        if ch == 0 { return 0; }
+
+        // Teclas muertas (ver formato arriba)
+        let pending = core::mem::take(&mut self.dead);
+        if dead {
+            if pending == 0 { self.dead = ch; return 0; }
+            return ch;
         }
+        if pending != 0 && ch == b' ' { return pending; }
+        ch
+    }
+}
 
-        const MAP: &[(u8, u8, u8)] = &[
-            (0x10,b'q',b'Q'),(0x11,b'w',b'W'),(0x12,b'e',b'E'),(0x13,b'r',b'R'),
-            (0x14,b't',b'T'),(0x15,b'y',b'Y'),(0x16,b'u',b'U'),(0x17,b'i',b'I'),
-            (0x18,b'o',b'O'),(0x19,b'p',b'P'),(0x1A,b'[',b'{'),(0x1B,b']',b'}'),
-            (0x1E,b'a',b'A'),(0x1F,b's',b'S'),(0x20,b'd',b'D'),(0x21,b'f',b'F'),
-            (0x22,b'g',b'G'),(0x23,b'h',b'H'),(0x24,b'j',b'J'),(0x25,b'k',b'K'),
-            (0x26,b'l',b'L'),(0x27,b';',b':'),(0x28,b'\'',b'"'),(0x29,b'`',b'~'),
-            (0x2B,b'\\',b'|'),
-            (0x2C,b'z',b'Z'),(0x2D,b'x',b'X'),(0x2E,b'c',b'C'),(0x2F,b'v',b'V'),
-            (0x30,b'b',b'B'),(0x31,b'n',b'N'),(0x32,b'm',b'M'),
-            (0x33,b',',b'<'),(0x34,b'.',b'>'),(0x35,b'/',b'?'),
-            (0x39,b' ',b' '),
-        ];
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        for &(code, lo, hi) in MAP {
-            if sc == code {
-                return if lo.is_ascii_alphabetic() {
-                    if up { hi } else { lo }
-                } else {
-                    if sh { hi } else { lo }
-                };
-            }
-        }
-        0
+    const A: u8 = 0x1E;
+    const B: u8 = 0x30;
+    const LSHIFT: u8 = 0x2A;
+    const ESC: u8 = 0x01;
+    const BREAK: u8 = 0x80;
+
+    /// Teclea `codes` (make codes sin modificadores) con el layout `km`.
+    fn type_with(km: &Keymap, kb: &mut KeyboardState, codes: &[u8]) -> Vec<u8> {
+        codes.iter().map(|&sc| kb.map_char(km, sc)).filter(|&c| c != 0).collect()
+    }
+
+    #[test]
+    fn es_layout_digits_and_symbols() {
+        let es = Keymap::es();
+        assert_eq!(es.name(), b"es");
+        assert_eq!(es.normal[0x02..0x0E], *b"1234567890'!");
+        assert_eq!(es.shift[0x02..0x0E], *b"!\".$%&/()=??");
+        assert_eq!((es.normal[0x27], es.shift[0x27]), (b'n', b'N'));
+        assert_eq!((es.normal[0x56], es.shift[0x56]), (b'<', b'>'));
+        // Las letras siguen siendo las del US
+        assert_eq!(es.normal[A as usize], b'a');
+        assert_eq!(es.dead[0x1A], DEAD_NORMAL | DEAD_SHIFT);
+        assert_eq!(Keymap::us().dead, [0; KEYMAP_CODES]);
+    }
+
+    #[test]
+    fn es_altgr_column() {
+        let es = Keymap::es();
+        let mut kb = KeyboardState::new();
+        kb.altgr = true;
+        assert_eq!(type_with(&es, &mut kb, &[0x02, 0x03, 0x04, 0x1A, 0x1B, 0x28, 0x2B, 0x29]),
+                   b"|@#[]{}\\");
+        // Sin carácter AltGr escribe el normal
+        assert_eq!(kb.map_char(&es, A), b'a');
+        assert_eq!(kb.map_char(&es, 0x33), b',');
+    }
+
+    #[test]
+    fn dead_key_then_vowel_space_or_other() {
+        let es = Keymap::es();
+        let mut kb = KeyboardState::new();
+        // ´ + a → a (sin acento), ´ + espacio → ´ suelto (ASCII ')
+        assert_eq!(type_with(&es, &mut kb, &[0x28, A]), b"a");
+        assert_eq!(type_with(&es, &mut kb, &[0x28, 0x39]), b"'");
+        // Dos veces la muerta: el acento suelto
+        assert_eq!(type_with(&es, &mut kb, &[0x1A, 0x1A]), b"`");
+        // Otra tecla cualquiera se escribe sola y no queda nada pendiente
+        assert_eq!(type_with(&es, &mut kb, &[0x28, 0x02, 0x39]), b"1 ");
+        // Con Shift la misma tecla es ^ (también muerta)
+        kb.shift_l = true;
+        assert_eq!(type_with(&es, &mut kb, &[0x1A, 0x39]), b"^");
+    }
+
+    #[test]
+    fn us_layout_has_no_dead_keys() {
+        let us = Keymap::us();
+        let mut kb = KeyboardState::new();
+        assert_eq!(type_with(&us, &mut kb, &[0x28, 0x1A, A]), b"'[a");
+    }
+
+    #[test]
+    fn repeat_after_delay_then_at_rate() {
+        let mut kb = KeyboardState::new();
+        let k = kb.decode(A, 100).unwrap();
+        assert!(matches!(k, Key::Char(_)));
+        assert_eq!(kb.held(), Some((k, 100)));
+        assert_eq!(kb.repeat(100), None);
+        assert_eq!(kb.repeat(100 + REPEAT_DELAY - 1), None);
+        assert_eq!(kb.repeat(100 + REPEAT_DELAY), Some(k));
+        assert_eq!(kb.repeat(100 + REPEAT_DELAY + 1), None);
+        assert_eq!(kb.repeat(100 + REPEAT_DELAY + REPEAT_RATE), Some(k));
+    }
+
+    #[test]
+    fn late_loop_does_not_queue_repeats() {
+        let mut kb = KeyboardState::new();
+        let k = kb.decode(A, 0).unwrap();
+        // Un bucle que llega muy tarde recibe una sola repetición
+        assert_eq!(kb.repeat(10_000), Some(k));
+        assert_eq!(kb.repeat(10_000), None);
+        assert_eq!(kb.repeat(10_000 + REPEAT_RATE), Some(k));
+    }
+
+    #[test]
+    fn hardware_repeats_are_dropped_and_break_stops() {
+        let mut kb = KeyboardState::new();
+        kb.decode(A, 0).unwrap();
+        // El make repetido por el teclado no produce tecla ni reinicia el retardo
+        assert_eq!(kb.decode(A, 20), None);
+        assert!(kb.repeat(REPEAT_DELAY).is_some());
+        assert_eq!(kb.decode(A | BREAK, REPEAT_DELAY + 1), None);
+        assert_eq!(kb.held(), None);
+        assert_eq!(kb.repeat(1_000), None);
+    }
+
+    #[test]
+    fn new_key_takes_over_repeat() {
+        let mut kb = KeyboardState::new();
+        kb.decode(A, 0).unwrap();
+        let b = kb.decode(B, 30).unwrap();
+        assert_eq!(kb.held(), Some((b, 30)));
+        assert_eq!(kb.repeat(30 + REPEAT_DELAY), Some(b));
+        // Soltar la A (que ya no se repite) no detiene la B
+        kb.decode(A | BREAK, 31);
+        assert_eq!(kb.held().map(|(k, _)| k), Some(b));
+    }
+
+    #[test]
+    fn modifiers_and_escape_do_not_repeat() {
+        let mut kb = KeyboardState::new();
+        kb.decode(LSHIFT, 0);
+        assert_eq!(kb.held(), None);
+        assert_eq!(kb.decode(ESC, 0), Some(Key::Escape));
+        assert_eq!(kb.held(), None);
+        assert_eq!(kb.repeat(1_000), None);
+        // Esc también corta la repetición de la tecla anterior
+        kb.decode(A, 0).unwrap();
+        kb.decode(ESC, 1);
+        assert_eq!(kb.repeat(1_000), None);
+    }
+
+    #[test]
+    fn extended_keys_repeat() {
+        let mut kb = KeyboardState::new();
+        assert_eq!(kb.decode(0xE0, 0), None);
+        assert_eq!(kb.decode(0x48, 0), Some(Key::Up));
+        assert_eq!(kb.repeat(REPEAT_DELAY), Some(Key::Up));
+        // El break de la flecha lleva su propio prefijo E0
+        kb.decode(0xE0, 60);
+        kb.decode(0x48 | BREAK, 60);
+        assert_eq!(kb.held(), None);
+    }
+
+    const CAPS: u8 = 0x3A;
+    const NUM: u8 = 0x45;
+    const SCROLL: u8 = 0x46;
+
+    /// Estado sin teclado presente: los bloqueos no escriben en el puerto.
+    fn offline() -> KeyboardState {
+        let mut kb = KeyboardState::new();
+        kb.set_present(false);
+        kb
     }
-}
\ No newline at end of file
+
+    fn ch(kb: &mut KeyboardState, sc: u8) -> u8 {
+        kb.map_char(&Keymap::us(), sc)
+    }
+
+    #[test]
+    fn caps_lock_only_affects_letters_and_shift_inverts_it() {
+        let mut kb = KeyboardState::new();
+        kb.caps_lock = true;
+        assert_eq!(ch(&mut kb, A), b'A');
+        assert_eq!(ch(&mut kb, 0x02), b'1');
+        kb.shift_l = true;
+        assert_eq!(ch(&mut kb, A), b'a');
+        assert_eq!(ch(&mut kb, 0x02), b'!');
+        kb.caps_lock = false;
+        assert_eq!(ch(&mut kb, A), b'A');
+        kb.shift_l = false;
+        kb.shift_r = true;
+        assert_eq!(ch(&mut kb, 0x1A), b'{');
+    }
+
+    #[test]
+    fn locks_toggle_once_per_press() {
+        let mut kb = offline();
+        assert_eq!(kb.decode(CAPS, 0), None);
+        assert_eq!(kb.leds(), LED_CAPS);
+        // Make repetido mientras sigue pulsada: no vuelve a alternar
+        kb.decode(CAPS, 1);
+        assert_eq!(kb.leds(), LED_CAPS);
+        kb.decode(CAPS | BREAK, 2);
+        kb.decode(NUM, 3);
+        kb.decode(SCROLL, 3);
+        assert_eq!(kb.leds(), LED_CAPS | LED_NUM | LED_SCROLL);
+        kb.decode(CAPS, 4);
+        assert_eq!(kb.leds(), LED_NUM | LED_SCROLL);
+        assert_eq!(LOCKS.load(Ordering::Relaxed) & LED_CAPS, 0);
+    }
+
+    #[test]
+    fn keypad_follows_num_lock_and_shift() {
+        let mut kb = offline();
+        assert_eq!(kb.decode(0x47, 0), Some(Key::Home));
+        assert_eq!(kb.decode(0x53, 0), Some(Key::Delete));
+        kb.decode(NUM, 0);
+        assert_eq!(kb.decode(0x47, 0), Some(Key::Char(b'7')));
+        assert_eq!(kb.decode(0x52, 0), Some(Key::Char(b'0')));
+        assert_eq!(kb.decode(0x53, 0), Some(Key::Char(b'.')));
+        // Shift con Bloq Num activo vuelve a la navegación
+        kb.decode(LSHIFT, 0);
+        assert_eq!(kb.decode(0x48, 0), Some(Key::Up));
+        // - y + del teclado numérico no dependen de Bloq Num
+        assert_eq!(kb.decode(0x4A, 0), Some(Key::Char(b'-')));
+        assert_eq!(kb.decode(0x4E, 0), Some(Key::Char(b'+')));
+    }
+}
@@ -1,4 +1,4 @@
-// kernel/src/mouse.rs — PORTIX PS/2 Mouse Driver v6.3
+// kernel/src/mouse.rs — PORTIX PS/2 Mouse Driver
 //
 // CAMBIOS vs v6.2:
 //   - feed() ahora es pub — el drenado unificado de main lo llama directamente.
@@ -9,15 +9,70 @@
 //   - poll() eliminado: ya no tiene sentido con el drenado unificado.
 //     Si se necesita compatibilidad temporal, se puede mantener pero NO debe
 //     coexistir con el drenado unificado o habrá doble lectura del buffer.
+//   - Sensibilidad configurable (set_sensitivity), antes fija a 2.
+//   - Curva de aceleración opcional (`mouse accel on`, ver scale_delta).
+//   - Rueda: se activa el modo IntelliMouse (ID 3, paquetes de 4 bytes) y
+//     su movimiento llega en scroll_delta (> 0 = hacia arriba).
+//   - Ratón ausente: `probe` pide un reset cada PROBE_INTERVAL sin esperar y
+//     `feed_absent` busca el 0xAA 0x00 de fin de autotest (también lo envía
+//     un ratón al conectarse) para inicializarlo entonces.
 
 #![allow(dead_code)]
 use crate::time::pit;
+use core::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "6.6";
 
 const PS2_DATA:   u16 = 0x60;
 const PS2_STATUS: u16 = 0x64;
 const PS2_CMD:    u16 = 0x64;
 
 const TELEPORT_THRESHOLD: i32 = 120;
+
+/// Intervalo entre sondeos con el ratón ausente (ticks de 10 ms → 3 s).
+pub const PROBE_INTERVAL: u64 = 300;
+const MOUSE_BAT_OK: u8 = 0xAA;
+const MOUSE_ID:     u8 = 0x00;
+
+/// Multiplicador de movimiento; ajustable en caliente (`config set
+/// mouse.sensitivity N`).
+pub const SENSITIVITY_MIN: i32 = 1;
+pub const SENSITIVITY_MAX: i32 = 8;
+static SENSITIVITY: AtomicI32 = AtomicI32::new(2);
+
+pub fn sensitivity() -> i32 { SENSITIVITY.load(Ordering::Relaxed) }
+pub fn set_sensitivity(v: i32) {
+    SENSITIVITY.store(v.clamp(SENSITIVITY_MIN, SENSITIVITY_MAX), Ordering::Relaxed);
+}
+
+/// Aceleración (`mouse accel on|off`, `config set mouse.accel`). Como la
+/// sensibilidad, es global: los comandos no tienen acceso al MouseState de main.
+static ACCEL: AtomicBool = AtomicBool::new(false);
+
+pub fn accel() -> bool { ACCEL.load(Ordering::Relaxed) }
+pub fn set_accel(on: bool) { ACCEL.store(on, Ordering::Relaxed); }
+
+/// Multiplicador de la curva de aceleración, en cuartos, según el delta de
+/// un paquete: los movimientos lentos quedan 1:1 para apuntar con precisión
+/// y los rápidos se amplían hasta ×3.
+///
+///   |d| 0..=2 → ×1   3..=6 → ×1.5   7..=12 → ×2   13.. → ×3
+fn accel_quarters(d: i32) -> i32 {
+    match d.abs() {
+        0..=2  => 4,
+        3..=6  => 6,
+        7..=12 => 8,
+        _      => 12,
+    }
+}
+
+/// Delta de un eje en píxeles: sensibilidad y, si `accel`, la curva. El
+/// redondeo es hacia cero, así que nunca invierte el signo ni anula un
+/// movimiento de 1 (×1 en el primer tramo).
+pub fn scale_delta(d: i32, sens: i32, accel: bool) -> i32 {
+    if accel { d * sens * accel_quarters(d) / 4 } else { d * sens }
+}
 const ERROR_LIMIT: u32 = 25;
 
 #[inline(always)] unsafe fn inb(p: u16) -> u8 {
@@ -65,13 +120,23 @@ unsafe fn mouse_cmd_arg(cmd: u8, arg: u8) -> bool {
     inb(PS2_DATA) == 0xFA
 }
 
+/// Secuencia IntelliMouse: sample rate 200, 100, 80 y GET ID. Un ratón con
+/// rueda responde ID 3 y desde entonces envía paquetes de 4 bytes.
+unsafe fn enable_wheel() -> bool {
+    for rate in [200, 100, 80] {
+        if !mouse_cmd_arg(0xF3, rate) { return false; }
+    }
+    if !mouse_cmd(0xF2) || !wait_read() { return false; }
+    inb(PS2_DATA) == 3
+}
+
 pub struct MouseState {
     pub x: i32,
     pub y: i32,
     pub buttons: u8,
     pub prev_buttons: u8,
 
-    pkt: [u8; 3],
+    pkt: [u8; 4],
     pkt_idx: u8,
     last_tick: u64,
 
@@ -84,6 +149,10 @@ pub struct MouseState {
     pub error_count: u32,
     pub resets: u32,
     last_reset_tick: u64,
+
+    /// Sondeo con el ratón ausente: siguiente tick y 0xAA recién visto.
+    next_probe: u64,
+    bat_seen: bool,
 }
 
 impl MouseState {
@@ -91,7 +160,7 @@ impl MouseState {
         Self {
             x: 400, y: 300,
             buttons: 0, prev_buttons: 0,
-            pkt: [0; 3], pkt_idx: 0,
+            pkt: [0; 4], pkt_idx: 0,
             last_tick: 0,
             max_x: 1024, max_y: 768,
             present: false,
@@ -100,6 +169,8 @@ impl MouseState {
             error_count: 0,
             resets: 0,
             last_reset_tick: 0,
+            next_probe: 0,
+            bat_seen: false,
         }
     }
 
@@ -136,8 +207,13 @@ impl MouseState {
                 self.pkt_idx = 2;
                 false
             }
-            2 => {
+            2 if self.has_wheel => {
                 self.pkt[2] = byte;
+                self.pkt_idx = 3;
+                false
+            }
+            2 | 3 => {
+                self.pkt[self.pkt_idx as usize] = byte;
                 self.pkt_idx = 0;
                 self.process()
             }
@@ -163,6 +239,7 @@ impl MouseState {
             wait_write(); outb(PS2_DATA, (cfg | 0x02) & !0x20);
 
             mouse_cmd(0xF6);
+            self.has_wheel = enable_wheel();
             mouse_cmd_arg(0xF3, 100);
             if !mouse_cmd(0xF4) { return false; }
 
@@ -172,6 +249,32 @@ impl MouseState {
         }
     }
 
+    /// Con el ratón ausente, cada PROBE_INTERVAL ticks: habilita el puerto
+    /// auxiliar y le envía RESET (0xFF) sin esperar la respuesta, que llega
+    /// por el drenado de main a `feed_absent`.
+    pub fn probe(&mut self, now: u64) {
+        if self.present || now < self.next_probe { return; }
+        self.next_probe = now + PROBE_INTERVAL;
+        unsafe {
+            // Sin controlador (estado flotando a 0xFF) no hay a quién preguntar
+            if inb(PS2_STATUS) == 0xFF { return; }
+            wait_write(); outb(PS2_CMD, 0xA8);
+            wait_write(); outb(PS2_CMD, 0xD4);
+            wait_write(); outb(PS2_DATA, 0xFF);
+        }
+    }
+
+    /// Byte del puerto auxiliar con el ratón ausente. Al ver 0xAA 0x00 (fin
+    /// del autotest tras un reset o al conectarse) lo inicializa; devuelve
+    /// true si el ratón queda presente.
+    pub fn feed_absent(&mut self, byte: u8) -> bool {
+        let bat_done = self.bat_seen && byte == MOUSE_ID;
+        self.bat_seen = byte == MOUSE_BAT_OK;
+        if !bat_done { return false; }
+        self.bat_seen = false;
+        self.init(self.max_x as usize + 1, self.max_y as usize + 1)
+    }
+
     pub fn intelligent_reset(&mut self) {
         let now = pit::ticks();
         if now.saturating_sub(self.last_reset_tick) < 100 { return; }
@@ -179,6 +282,12 @@ impl MouseState {
         unsafe {
             drain_kbc();
             mouse_cmd(0xF6);
+            // SET DEFAULTS no cambia el ID, pero si el ratón se reinició
+            // solo habría vuelto a 3 bytes: repetir la secuencia
+            if self.has_wheel {
+                self.has_wheel = enable_wheel();
+                mouse_cmd_arg(0xF3, 100);
+            }
             mouse_cmd(0xF4);
             drain_kbc();
         }
@@ -221,16 +330,22 @@ impl MouseState {
 
         self.buttons = flags & 0x07;
 
-        let sensitivity: i32 = 2;
+        // Rueda: 4 bits con signo en el 4º byte, negativo = hacia arriba
+        let wheel = if self.has_wheel { ((self.pkt[3] << 4) as i8 >> 4) as i32 } else { 0 };
+        self.scroll_delta -= wheel;
+
+        let (sens, accel) = (sensitivity(), accel());
         let old_x = self.x;
         let old_y = self.y;
 
-        self.x = (self.x + dx * sensitivity).clamp(0, self.max_x);
-        self.y = (self.y - dy * sensitivity).clamp(0, self.max_y);
+        // Clamp después de escalar: ×3 con la sensibilidad máxima lleva un
+        // paquete de 120 a casi 3000 px. La rueda no pasa por aquí.
+        self.x = (self.x + scale_delta(dx, sens, accel)).clamp(0, self.max_x);
+        self.y = (self.y - scale_delta(dy, sens, accel)).clamp(0, self.max_y);
 
         if self.error_count > 0 { self.error_count -= 1; }
 
-        self.x != old_x || self.y != old_y || self.buttons != self.prev_buttons
+        self.x != old_x || self.y != old_y || self.buttons != self.prev_buttons || wheel != 0
     }
 
     #[inline] pub fn left_btn(&self)    -> bool { self.buttons & 0x01 != 0 }
@@ -246,4 +361,97 @@ impl MouseState {
     #[inline] pub fn left_released(&self) -> bool {
         self.buttons & 0x01 == 0 && self.prev_buttons & 0x01 != 0
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accel_curve_steps() {
+        let q: Vec<i32> = [0, 2, 3, 6, 7, 12, 13, 200].iter().map(|&d| accel_quarters(d)).collect();
+        assert_eq!(q, [4, 4, 6, 6, 8, 8, 12, 12]);
+        // Simétrica: el signo del delta no cuenta
+        assert_eq!(accel_quarters(-5), accel_quarters(5));
+    }
+
+    #[test]
+    fn scale_without_accel_is_linear() {
+        assert_eq!(scale_delta(5, 2, false), 10);
+        assert_eq!(scale_delta(-7, 3, false), -21);
+        assert_eq!(scale_delta(0, 8, false), 0);
+    }
+
+    #[test]
+    fn scale_with_accel() {
+        // Movimientos lentos 1:1 con la sensibilidad
+        assert_eq!(scale_delta(1, 1, true), 1);
+        assert_eq!(scale_delta(-1, 1, true), -1);
+        assert_eq!(scale_delta(2, 2, true), 4);
+        // ×1.5, ×2 y ×3; el redondeo va hacia cero en ambos sentidos
+        assert_eq!(scale_delta(3, 1, true), 4);
+        assert_eq!(scale_delta(-3, 1, true), -4);
+        assert_eq!(scale_delta(10, 1, true), 20);
+        assert_eq!(scale_delta(-20, 2, true), -120);
+    }
+
+    #[test]
+    fn accel_never_flips_sign_or_drops_motion() {
+        for sens in SENSITIVITY_MIN..=SENSITIVITY_MAX {
+            for d in -255..=255 {
+                let s = scale_delta(d, sens, true);
+                assert_eq!(s.signum(), d.signum());
+                assert!(s.abs() >= scale_delta(d, sens, false).abs());
+            }
+        }
+    }
+
+    #[test]
+    fn sensitivity_is_clamped() {
+        set_sensitivity(0);
+        assert_eq!(sensitivity(), SENSITIVITY_MIN);
+        set_sensitivity(99);
+        assert_eq!(sensitivity(), SENSITIVITY_MAX);
+        set_sensitivity(2);
+        assert_eq!(sensitivity(), 2);
+    }
+
+    fn feed_all(ms: &mut MouseState, bytes: &[u8]) -> bool {
+        bytes.iter().fold(false, |_, &b| ms.feed(b))
+    }
+
+    #[test]
+    fn wheel_packets_are_four_bytes() {
+        let mut ms = MouseState::new();
+        ms.has_wheel = true;
+        // Los tres primeros bytes no completan el paquete
+        assert!(!feed_all(&mut ms, &[0x08, 0, 0]));
+        assert!(ms.feed(0x01));
+        assert_eq!(ms.scroll_delta, -1);   // 4º byte positivo = hacia abajo
+        assert!(feed_all(&mut ms, &[0x08, 0, 0, 0x0F]));
+        assert!(feed_all(&mut ms, &[0x08, 0, 0, 0x0E]));
+        assert_eq!(ms.scroll_delta, 2);
+        ms.begin_frame();
+        assert_eq!(ms.scroll_delta, 0);
+    }
+
+    #[test]
+    fn wheel_uses_only_the_low_nibble() {
+        let mut ms = MouseState::new();
+        ms.has_wheel = true;
+        // Bits 4-5 = botones 4 y 5 en ratones de 5 botones: no son rueda
+        assert!(!feed_all(&mut ms, &[0x08, 0, 0, 0x30]));
+        assert_eq!(ms.scroll_delta, 0);
+        assert!(feed_all(&mut ms, &[0x08, 0, 0, 0x38]));
+        assert_eq!(ms.scroll_delta, 8);
+    }
+
+    #[test]
+    fn without_wheel_packets_stay_three_bytes() {
+        let mut ms = MouseState::new();
+        assert!(feed_all(&mut ms, &[0x09, 0, 0]));
+        assert!(ms.left_btn());
+        // El siguiente byte empieza paquete nuevo (no se toma como rueda)
+        assert!(!ms.feed(0x08));
+        assert_eq!(ms.scroll_delta, 0);
+    }
+}
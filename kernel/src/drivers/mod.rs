@@ -1,4 +1,5 @@
 pub mod input;
 pub mod bus;
 pub mod serial;
-pub mod storage;
\ No newline at end of file
+pub mod storage;
+pub mod registry;
@@ -0,0 +1,100 @@
+// kernel/src/drivers/registry.rs — PORTIX registro central de subsistemas
+// Cada driver/subsistema publica aquí su estado al inicializarse; `lsmod`
+// lo lee para la vista de diagnóstico. El estado se guarda en atómicos para
+// poder actualizarlo desde cualquier punto (p. ej. al volver a montar FAT32).
+#![allow(dead_code)]
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum State {
+    /// Aún no se ha intentado inicializar
+    Unknown  = 0,
+    /// Inicializado y en uso
+    Active   = 1,
+    /// Hardware presente pero el driver no lo usa
+    Inactive = 2,
+    /// Hardware ausente o inicialización fallida
+    Missing  = 3,
+}
+
+impl State {
+    pub fn label(self) -> &'static str {
+        match self {
+            State::Unknown  => "desconocido",
+            State::Active   => "activo",
+            State::Inactive => "inactivo",
+            State::Missing  => "no disponible",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem { Pit, Keyboard, Mouse, Serial, Pci, Acpi, Ata, Ahci, Fat32 }
+
+pub const ALL: [Subsystem; 9] = [
+    Subsystem::Pit, Subsystem::Keyboard, Subsystem::Mouse, Subsystem::Serial,
+    Subsystem::Pci, Subsystem::Acpi, Subsystem::Ata, Subsystem::Ahci, Subsystem::Fat32,
+];
+
+impl Subsystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Pit      => "pit",
+            Subsystem::Keyboard => "ps2kbd",
+            Subsystem::Mouse    => "ps2mouse",
+            Subsystem::Serial   => "serial",
+            Subsystem::Pci      => "pci",
+            Subsystem::Acpi     => "acpi",
+            Subsystem::Ata      => "ata_pio",
+            Subsystem::Ahci     => "ahci",
+            Subsystem::Fat32    => "fat32",
+        }
+    }
+
+    pub fn version(self) -> &'static str {
+        match self {
+            Subsystem::Keyboard => "1.1",
+            Subsystem::Mouse    => "6.3",
+            Subsystem::Ata      => "0.8.0",
+            Subsystem::Fat32    => "0.7.5",
+            Subsystem::Ahci     => "0.1",
+            _                   => "1.0",
+        }
+    }
+
+    pub fn desc(self) -> &'static str {
+        match self {
+            Subsystem::Pit      => "Temporizador PIT 8253 (IRQ0, 100 Hz)",
+            Subsystem::Keyboard => "Teclado PS/2 (scancode set 1)",
+            Subsystem::Mouse    => "Raton PS/2",
+            Subsystem::Serial   => "Puerto serie COM1 (8N1)",
+            Subsystem::Pci      => "Enumeracion del bus PCI",
+            Subsystem::Acpi     => "ACPI (RSDT/FADT, apagado S5/reinicio)",
+            Subsystem::Ata      => "Disco ATA/ATAPI en modo PIO",
+            Subsystem::Ahci     => "Controlador SATA AHCI (solo IDENTIFY)",
+            Subsystem::Fat32    => "Sistema de archivos FAT32",
+        }
+    }
+}
+
+static STATES: [AtomicU8; ALL.len()] = [const { AtomicU8::new(0) }; ALL.len()];
+
+pub fn set(sub: Subsystem, state: State) {
+    STATES[sub as usize].store(state as u8, Ordering::Relaxed);
+}
+
+pub fn state(sub: Subsystem) -> State {
+    match STATES[sub as usize].load(Ordering::Relaxed) {
+        1 => State::Active,
+        2 => State::Inactive,
+        3 => State::Missing,
+        _ => State::Unknown,
+    }
+}
+
+/// Atajo para drivers con resultado binario de inicialización.
+pub fn set_ok(sub: Subsystem, ok: bool) {
+    set(sub, if ok { State::Active } else { State::Missing });
+}
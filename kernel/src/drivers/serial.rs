@@ -1,12 +1,25 @@
 // kernel/src/drivers/serial.rs — PORTIX COM1 Serial Debug Port
 // Nivel kernel-grade: log levels, hex dump, loopback self-test.
+// Cada log() / log_level() queda además en un ring en RAM (`dmesg`), aunque
+// COM1 no exista. El prefijo de nivel puede ir coloreado con ANSI
+// (`config set serial.color on`); el ring guarda siempre el texto plano.
 #![allow(dead_code)]
 
-use core::sync::atomic::{AtomicBool, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 
 const COM1: u16 = 0x3F8;
+/// Reloj del UART 16550 ya dividido por 16: baud = UART_BASE / divisor.
+const UART_BASE: u32 = 115_200;
+/// Error máximo admitido entre el baud pedido y el real (en ‰). Los UART
+/// toleran ~3 %; con 2 % queda margen para el reloj del otro extremo.
+const BAUD_TOLERANCE_PM: u32 = 20;
+pub const DEFAULT_BAUD: u32 = 38_400;
+
+static BAUD: AtomicU32 = AtomicU32::new(DEFAULT_BAUD);
 
 static SERIAL_OK: AtomicBool = AtomicBool::new(false);
+/// Prefijos de nivel con color ANSI en COM1 (apagado: consolas sin VT100).
+static COLOR: AtomicBool = AtomicBool::new(false);
 
 // ── I/O primitivos ────────────────────────────────────────────────────────────
 
@@ -32,16 +45,44 @@ unsafe fn inb(port: u16) -> u8 {
 
 // ── Inicialización + loopback test ────────────────────────────────────────────
 
-/// Inicializa COM1 a 38400 8N1.
+/// Divisor del UART para `baud` y el baud real que resulta. El divisor se
+/// redondea al más cercano; None si el error supera BAUD_TOLERANCE_PM o el
+/// baud está fuera de rango (p. ej. 115200 → 1, 38400 → 3, 9600 → 12,
+/// 57600 → 2; 100000 → 1 = 115200 real, demasiado lejos).
+pub fn divisor_for(baud: u32) -> Option<(u16, u32)> {
+    if baud == 0 || baud > UART_BASE { return None; }
+    let div = ((UART_BASE + baud / 2) / baud).clamp(1, u16::MAX as u32);
+    let real = UART_BASE / div;
+    // Error con la frecuencia exacta (UART_BASE / div), no con `real`
+    // truncado: con el divisor topado a 65535, 1 baud daría 1 "exacto"
+    let ideal = div as u64 * baud as u64;
+    let err_pm = (UART_BASE as u64).abs_diff(ideal) * 1000 / ideal;
+    if err_pm > BAUD_TOLERANCE_PM as u64 { return None; }
+    Some((div as u16, real))
+}
+
+/// Baud rate configurado actualmente.
+pub fn baud() -> u32 { BAUD.load(Ordering::Relaxed) }
+
+/// Programa divisor y formato de línea 8N1 (DLAB se abre y se cierra aquí).
+unsafe fn program_line(div: u16) {
+    outb(COM1 + 3, 0x80);                // Habilitar DLAB
+    outb(COM1, (div & 0xFF) as u8);      // Divisor lo
+    outb(COM1 + 1, (div >> 8) as u8);    // Divisor hi
+    outb(COM1 + 3, 0x03);                // 8 bits, sin paridad, 1 stop (8N1), DLAB off
+}
+
+/// Inicializa COM1 a `baud` 8N1 (DEFAULT_BAUD si no es válido).
 /// Hace un loopback test; si falla, el puerto queda marcado como no-disponible
 /// y write_byte() se convierte en no-op para no colgar el kernel.
-pub fn init() {
+pub fn init(baud: u32) {
+    let (div, real) = divisor_for(baud)
+        .or_else(|| divisor_for(DEFAULT_BAUD))
+        .unwrap_or((3, DEFAULT_BAUD));
+    BAUD.store(real, Ordering::Relaxed);
     unsafe {
         outb(COM1 + 1, 0x00); // Deshabilitar interrupciones
-        outb(COM1 + 3, 0x80); // Habilitar DLAB
-        outb(COM1 + 0, 0x03); // Divisor lo → 38400 baud
-        outb(COM1 + 1, 0x00); // Divisor hi
-        outb(COM1 + 3, 0x03); // 8 bits, sin paridad, 1 stop (8N1)
+        program_line(div);
         outb(COM1 + 2, 0xC7); // Habilitar FIFO, limpiar, umbral 14 bytes
         outb(COM1 + 4, 0x1E); // Modo loopback para autotest
 
@@ -60,12 +101,45 @@ pub fn init() {
         SERIAL_OK.store(true, Ordering::Release);
     }
 
-log_level(Level::Ok, "SERIAL", "COM1 listo @ 38400 8N1");
+    let mut buf = [0u8; 32]; let mut n = 0;
+    for &b in b"COM1 listo @ " { buf[n] = b; n += 1; }
+    n += fmt_u32(real, &mut buf[n..]);
+    for &b in b" 8N1" { buf[n] = b; n += 1; }
+    log_level(Level::Ok, "SERIAL", core::str::from_utf8(&buf[..n]).unwrap_or("COM1 listo"));
+}
+
+/// Reconfigura el baud rate en caliente. Espera a que el transmisor se vacíe
+/// para no cortar un byte a medias y reescribe el formato de línea; FIFO y
+/// control de módem no se tocan. Devuelve el baud real aplicado.
+pub fn set_baud(baud: u32) -> Result<u32, &'static str> {
+    let Some((div, real)) = divisor_for(baud) else {
+        return Err("baud no alcanzable (divisor de 115200 con error > 2%)");
+    };
+    if !is_ready() { return Err("COM1 no disponible"); }
+    unsafe {
+        // LSR bit 6 = THR y registro de desplazamiento vacíos
+        for _ in 0..100_000 {
+            if inb(COM1 + 5) & 0x40 != 0 { break; }
+            core::hint::spin_loop();
+        }
+        program_line(div);
+    }
+    BAUD.store(real, Ordering::Relaxed);
+    Ok(real)
+}
+
+/// Decimal de `n` en `out`; devuelve los bytes escritos.
+fn fmt_u32(mut n: u32, out: &mut [u8]) -> usize {
+    let mut tmp = [0u8; 10]; let mut i = 0;
+    loop { tmp[i] = b'0' + (n % 10) as u8; i += 1; n /= 10; if n == 0 { break; } }
+    for k in 0..i { out[k] = tmp[i - 1 - k]; }
+    i
 }
 
 // ── Niveles de log ────────────────────────────────────────────────────────────
 
-#[derive(Clone, Copy)]
+/// Ordenados por gravedad: `dmesg -l warn` muestra Warn y Error.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Level {
     Debug,
     Info,
@@ -75,7 +149,7 @@ pub enum Level {
 }
 
 impl Level {
-    fn prefix(self) -> &'static str {
+    pub fn prefix(self) -> &'static str {
         match self {
             Level::Debug => "[ DBG ]",
             Level::Info  => "[ INF ]",
@@ -84,6 +158,61 @@ impl Level {
             Level::Error => "[ ERR ]",
         }
     }
+
+    /// Secuencia SGR del prefijo cuando `color()` está activo.
+    pub fn ansi(self) -> &'static str {
+        match self {
+            Level::Debug => "\x1B[90m",
+            Level::Info  => "\x1B[36m",
+            Level::Ok    => "\x1B[32m",
+            Level::Warn  => "\x1B[33m",
+            Level::Error => "\x1B[1;31m",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info  => "info",
+            Level::Ok    => "ok",
+            Level::Warn  => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    pub fn parse(s: &[u8]) -> Option<Level> {
+        match s {
+            b"debug" | b"dbg"  => Some(Level::Debug),
+            b"info"  | b"inf"  => Some(Level::Info),
+            b"ok"              => Some(Level::Ok),
+            b"warn"  | b"wrn"  => Some(Level::Warn),
+            b"error" | b"err"  => Some(Level::Error),
+            _ => None,
+        }
+    }
+}
+
+pub fn color() -> bool { COLOR.load(Ordering::Relaxed) }
+pub fn set_color(on: bool) { COLOR.store(on, Ordering::Relaxed); }
+
+// ── Lectura ───────────────────────────────────────────────────────────────────
+
+/// true si COM1 pasó el loopback test de `init()`.
+pub fn is_ready() -> bool {
+    SERIAL_OK.load(Ordering::Relaxed)
+}
+
+/// Lee un byte recibido sin bloquear (LSR bit 0 = dato disponible).
+pub fn read_byte() -> Option<u8> {
+    if !is_ready() {
+        return None;
+    }
+    unsafe {
+        if inb(COM1 + 5) & 0x01 == 0 {
+            return None;
+        }
+        Some(inb(COM1))
+    }
 }
 
 // ── Escritura ─────────────────────────────────────────────────────────────────
@@ -150,19 +279,75 @@ pub fn write_hex(n: usize) {
     }
 }
 
+// ── Ring de log (dmesg) ───────────────────────────────────────────────────────
+//
+// Las últimas LOG_LINES entradas, numeradas con una secuencia creciente: la
+// entrada `seq` vive en RING[seq % LOG_LINES] mientras seq >= log_seq() -
+// LOG_LINES. Los lectores guardan números de secuencia, no índices, para no
+// perder la posición cuando el ring da la vuelta.
+
+pub const LOG_LINES: usize = 128;
+pub const LOG_LEN:   usize = 80;
+
+#[derive(Clone, Copy)]
+pub struct LogEntry {
+    pub level: Level,
+    /// Tick del PIT al registrarla (0 antes de arrancar el PIT).
+    pub tick:  u64,
+    pub len:   usize,
+    pub text:  [u8; LOG_LEN],
+}
+
+impl LogEntry {
+    pub fn text(&self) -> &[u8] { &self.text[..self.len] }
+}
+
+static mut RING: [LogEntry; LOG_LINES] =
+    [LogEntry { level: Level::Info, tick: 0, len: 0, text: [0; LOG_LEN] }; LOG_LINES];
+static LOG_SEQ: AtomicUsize = AtomicUsize::new(0);
+
+fn record(level: Level, tag: &str, msg: &str) {
+    let seq = LOG_SEQ.load(Ordering::Relaxed);
+    let e = unsafe { &mut (*core::ptr::addr_of_mut!(RING))[seq % LOG_LINES] };
+    e.level = level;
+    e.tick  = crate::time::pit::ticks();
+    e.len   = 0;
+    for &b in tag.as_bytes().iter().chain(b"  ").chain(msg.as_bytes()) {
+        if e.len == LOG_LEN { break; }
+        e.text[e.len] = b;
+        e.len += 1;
+    }
+    LOG_SEQ.store(seq + 1, Ordering::Release);
+}
+
+/// Número de entradas registradas desde el arranque (la siguiente secuencia).
+pub fn log_seq() -> usize { LOG_SEQ.load(Ordering::Acquire) }
+
+/// Secuencia más antigua que sigue en el ring.
+pub fn log_oldest() -> usize { log_seq().saturating_sub(LOG_LINES) }
+
+/// Entrada `seq`, o None si ya se sobrescribió o aún no existe.
+pub fn log_entry(seq: usize) -> Option<LogEntry> {
+    if seq < log_oldest() || seq >= log_seq() { return None; }
+    Some(unsafe { (*core::ptr::addr_of!(RING))[seq % LOG_LINES] })
+}
+
 /// Mensaje simple — compatibilidad con el código existente: log("TAG", "msg")
 pub fn log(tag: &str, msg: &str) {
-    write_str("[ INF ] ");
-    write_str(tag);
-    write_str("  ");
-    write_str(msg);
-    write_byte(b'\n');
+    log_level(Level::Info, tag, msg);
 }
 
 /// Mensaje con nivel explícito — uso nuevo: log_level(Level::Ok, "TAG", "msg")
 pub fn log_level(level: Level, tag: &str, msg: &str) {
-    write_str(level.prefix());
-    write_byte(b' ');
+    record(level, tag, msg);
+    if color() {
+        write_str(level.ansi());
+        write_str(level.prefix());
+        write_str("\x1B[0m ");
+    } else {
+        write_str(level.prefix());
+        write_byte(b' ');
+    }
     write_str(tag);
     write_str("  ");
     write_str(msg);
@@ -178,4 +363,107 @@ macro_rules! serial_log {
             $msg,
         )
     };
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Los tests que escriben en el ring no pueden correr a la vez: uno lo
+    /// llena y descartaría las entradas que el otro busca.
+    static RING_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Busca en el ring la entrada más reciente con ese texto. Otros tests
+    /// pueden registrar a la vez, así que no se fija su secuencia exacta.
+    fn find(text: &[u8]) -> Option<(usize, LogEntry)> {
+        (log_oldest()..log_seq()).rev()
+            .filter_map(|s| log_entry(s).map(|e| (s, e)))
+            .find(|(_, e)| e.text() == text)
+    }
+
+    #[test]
+    fn level_parse_accepts_names_and_short_forms() {
+        for l in [Level::Debug, Level::Info, Level::Ok, Level::Warn, Level::Error] {
+            assert!(Level::parse(l.name().as_bytes()) == Some(l));
+        }
+        assert!(Level::parse(b"dbg") == Some(Level::Debug));
+        assert!(Level::parse(b"inf") == Some(Level::Info));
+        assert!(Level::parse(b"wrn") == Some(Level::Warn));
+        assert!(Level::parse(b"err") == Some(Level::Error));
+        for bad in [&b""[..], b"WARN", b"warning", b" warn", b"e"] {
+            assert!(Level::parse(bad).is_none());
+        }
+    }
+
+    #[test]
+    fn levels_are_ordered_by_severity() {
+        assert!(Level::Debug < Level::Info && Level::Info < Level::Ok);
+        assert!(Level::Ok < Level::Warn && Level::Warn < Level::Error);
+        // `dmesg -l warn`: pasan Warn y Error
+        let shown = [Level::Debug, Level::Info, Level::Ok, Level::Warn, Level::Error]
+            .iter().filter(|&&l| l >= Level::Warn).count();
+        assert_eq!(shown, 2);
+    }
+
+    #[test]
+    fn ring_keeps_tag_level_and_truncates() {
+        let _g = RING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        log_level(Level::Warn, "TEST", "ring-entry");
+        let (_, e) = find(b"TEST  ring-entry").expect("entrada no registrada");
+        assert!(e.level == Level::Warn);
+
+        let long = "x".repeat(LOG_LEN * 2);
+        log_level(Level::Error, "LONGTAG", &long);
+        let mut want = b"LONGTAG  ".to_vec();
+        want.resize(LOG_LEN, b'x');
+        assert!(find(&want).is_some_and(|(_, e)| e.len == LOG_LEN));
+    }
+
+    #[test]
+    fn ring_drops_oldest_after_wrapping() {
+        let _g = RING_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        log_level(Level::Info, "WRAP", "first");
+        let (first, _) = find(b"WRAP  first").unwrap();
+        assert!(log_entry(first).is_some());
+        for _ in 0..LOG_LINES { log_level(Level::Debug, "WRAP", "filler"); }
+        assert!(log_oldest() > first);
+        assert!(log_entry(first).is_none());
+        assert!(log_seq() - log_oldest() == LOG_LINES);
+        // Secuencias futuras tampoco existen
+        assert!(log_entry(log_seq()).is_none());
+    }
+
+    #[test]
+    fn divisor_for_standard_rates() {
+        assert_eq!(divisor_for(115_200), Some((1, 115_200)));
+        assert_eq!(divisor_for(38_400), Some((3, 38_400)));
+        assert_eq!(divisor_for(9_600), Some((12, 9_600)));
+        assert_eq!(divisor_for(300), Some((384, 300)));
+        assert_eq!(divisor_for(DEFAULT_BAUD).map(|(_, real)| real), Some(DEFAULT_BAUD));
+    }
+
+    #[test]
+    fn divisor_for_rounds_to_nearest_within_tolerance() {
+        // 57000 → divisor 2 (57600, 1% de error)
+        assert_eq!(divisor_for(57_000), Some((2, 57_600)));
+        // 56000 → 57600 supera el 2%; 100000 → 115200 también
+        assert_eq!(divisor_for(56_000), None);
+        assert_eq!(divisor_for(100_000), None);
+    }
+
+    #[test]
+    fn divisor_for_limits() {
+        assert_eq!(divisor_for(0), None);
+        assert_eq!(divisor_for(UART_BASE + 1), None);
+        // El divisor es de 16 bits: con 1 baud se queda en 65535 (≈1.76 baud)
+        assert_eq!(divisor_for(2), Some((57_600, 2)));
+        assert_eq!(divisor_for(1), None);
+    }
+
+    #[test]
+    fn set_baud_rejects_before_touching_the_port() {
+        // Sin COM1 (tests): primero se valida el baud, luego el puerto
+        assert_eq!(set_baud(56_000), Err("baud no alcanzable (divisor de 115200 con error > 2%)"));
+        assert_eq!(set_baud(9_600), Err("COM1 no disponible"));
+    }
+}
+
@@ -0,0 +1,377 @@
+// drivers/storage/ahci.rs — PORTIX Kernel
+//
+// Driver AHCI mínimo: solo detección. Para cada controlador PCI de clase
+// 01h/06h mapea ABAR (BAR5) con paging::map_mmio, recorre los puertos
+// implementados (PI) con enlace establecido y emite IDENTIFY (IDENTIFY
+// PACKET si es ATAPI) por DMA. Las unidades se añaden a `hw.disks` con
+// `ahci = true`; la lectura y escritura de sectores sigue siendo ATA PIO.
+//
+// Estructuras DMA: una lista de comandos, una zona de FIS recibidos, una
+// tabla de comando y el buffer de IDENTIFY, estáticos en .bss (RAM en
+// identidad, virtual = física) y reutilizados por cada puerto. Antes de
+// apuntar el puerto a ellos se para (ST/FRE); al terminar se vuelve a parar
+// y se restauran CLB/FB originales para no dejar punteros a nuestro .bss.
+//
+// Puertos que se saltan:
+//   - SSTS.DET != 3 o SSTS.IPM != 1 → sin dispositivo o enlace dormido
+//   - firma distinta de SATA (0000_0101) o ATAPI (EB14_0101): multiplicador
+//     de puertos (9669_0101) o puente SEMB (C33C_0101), no soportados
+
+#![allow(dead_code)]
+
+use core::ptr::{addr_of, addr_of_mut, read_volatile, write_volatile};
+use core::sync::atomic::{fence, Ordering};
+
+use crate::arch::hardware::{DiskInfo, Disks};
+use crate::arch::paging;
+use crate::drivers::bus::pci::{BarKind, PciBus, PciDevice};
+use crate::drivers::serial;
+
+/// ABAR: AHCI Base Address Register (BAR5).
+const ABAR_BAR: usize = 5;
+
+// ── Registros globales del HBA ────────────────────────────────────────────────
+const HBA_CAP: usize = 0x00;
+const HBA_GHC: usize = 0x04;
+const HBA_PI:  usize = 0x0C;
+
+const CAP_S64A: u32 = 1 << 31;   // direcciones DMA de 64 bits
+const GHC_AE:   u32 = 1 << 31;   // modo AHCI (no IDE legacy)
+
+// ── Registros de puerto (0x100 + puerto × 0x80) ───────────────────────────────
+const PORT_BASE: usize = 0x100;
+const PORT_SIZE: usize = 0x80;
+const MAX_PORTS: u8    = 32;
+
+const PX_CLB:  usize = 0x00;
+const PX_CLBU: usize = 0x04;
+const PX_FB:   usize = 0x08;
+const PX_FBU:  usize = 0x0C;
+const PX_IS:   usize = 0x10;
+const PX_IE:   usize = 0x14;
+const PX_CMD:  usize = 0x18;
+const PX_TFD:  usize = 0x20;
+const PX_SIG:  usize = 0x24;
+const PX_SSTS: usize = 0x28;
+const PX_SERR: usize = 0x30;
+const PX_CI:   usize = 0x38;
+
+const CMD_ST:  u32 = 1 << 0;
+const CMD_FRE: u32 = 1 << 4;
+const CMD_FR:  u32 = 1 << 14;
+const CMD_CR:  u32 = 1 << 15;
+
+const IS_TFES: u32 = 1 << 30;    // Task File Error Status
+
+const TFD_ERR: u32 = 0x01;
+const TFD_DRQ: u32 = 0x08;
+const TFD_BSY: u32 = 0x80;
+
+const SSTS_DET_PRESENT: u32 = 3; // dispositivo y enlace PHY establecidos
+const SSTS_IPM_ACTIVE:  u32 = 1;
+
+const SIG_ATA:   u32 = 0x0000_0101;
+const SIG_ATAPI: u32 = 0xEB14_0101;
+
+const FIS_REG_H2D:  u8 = 0x27;
+const FIS_CMD_BIT:  u8 = 0x80;   // el FIS lleva un comando (no control)
+const CFL_REG_H2D:  u16 = 5;     // longitud del FIS en dwords
+
+const ATA_CMD_IDENTIFY:        u8 = 0xEC;
+const ATA_CMD_IDENTIFY_PACKET: u8 = 0xA1;
+
+/// Iteraciones de espera (cada una lee un registro MMIO, ~1 µs en QEMU).
+const SPIN_TIMEOUT: u32 = 1_000_000;
+
+// ── Estructuras DMA ───────────────────────────────────────────────────────────
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CmdHeader {
+    flags: u16,      // CFL (bits 0-4), A (5), W (6)
+    prdtl: u16,      // entradas de la PRDT
+    prdbc: u32,      // bytes transferidos (lo escribe el HBA)
+    ctba:  u32,
+    ctbau: u32,
+    rsv:   [u32; 4],
+}
+
+#[repr(C, align(1024))]
+struct CmdList([CmdHeader; 32]);
+
+#[repr(C, align(256))]
+struct RecvFis([u8; 256]);
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct Prd {
+    dba:  u32,
+    dbau: u32,
+    rsv:  u32,
+    dbc:  u32,       // bytes - 1 (bits 0-21)
+}
+
+#[repr(C, align(128))]
+struct CmdTable {
+    cfis: [u8; 64],
+    acmd: [u8; 16],
+    rsv:  [u8; 48],
+    prdt: [Prd; 1],
+}
+
+#[repr(C, align(512))]
+struct IdentBuf([u16; 256]);
+
+const EMPTY_HEADER: CmdHeader = CmdHeader { flags: 0, prdtl: 0, prdbc: 0, ctba: 0, ctbau: 0, rsv: [0; 4] };
+const EMPTY_PRD: Prd = Prd { dba: 0, dbau: 0, rsv: 0, dbc: 0 };
+
+static mut CMD_LIST:  CmdList  = CmdList([EMPTY_HEADER; 32]);
+static mut RECV_FIS:  RecvFis  = RecvFis([0; 256]);
+static mut CMD_TABLE: CmdTable = CmdTable { cfis: [0; 64], acmd: [0; 16], rsv: [0; 48], prdt: [EMPTY_PRD; 1] };
+static mut IDENT_BUF: IdentBuf = IdentBuf([0; 256]);
+
+/// Dirección física de una estructura estática (identidad salvo que las
+/// tablas digan otra cosa).
+fn phys<T>(p: *const T) -> u64 {
+    paging::translate(p as u64).unwrap_or(p as u64)
+}
+
+// ── Acceso a registros ────────────────────────────────────────────────────────
+
+struct Hba {
+    base: u64,
+    size: usize,
+}
+
+impl Hba {
+    fn read(&self, off: usize) -> u32 {
+        unsafe { read_volatile((self.base as usize + off) as *const u32) }
+    }
+    fn write(&self, off: usize, v: u32) {
+        unsafe { write_volatile((self.base as usize + off) as *mut u32, v) }
+    }
+    fn port_off(port: u8, off: usize) -> usize { PORT_BASE + port as usize * PORT_SIZE + off }
+    fn pread(&self, port: u8, off: usize) -> u32 { self.read(Self::port_off(port, off)) }
+    fn pwrite(&self, port: u8, off: usize, v: u32) { self.write(Self::port_off(port, off), v) }
+
+    /// ¿Caben los registros de `port` en la región mapeada?
+    fn has_port(&self, port: u8) -> bool {
+        Self::port_off(port, PORT_SIZE) <= self.size
+    }
+
+    /// Espera a que los bits `mask` de un registro de puerto queden a 0.
+    fn wait_clear(&self, port: u8, off: usize, mask: u32) -> bool {
+        for _ in 0..SPIN_TIMEOUT {
+            if self.pread(port, off) & mask == 0 { return true; }
+            core::hint::spin_loop();
+        }
+        false
+    }
+
+    /// Para el motor de comandos y la recepción de FIS del puerto.
+    fn stop_port(&self, port: u8) -> bool {
+        let cmd = self.pread(port, PX_CMD);
+        self.pwrite(port, PX_CMD, cmd & !CMD_ST);
+        if !self.wait_clear(port, PX_CMD, CMD_CR) { return false; }
+        let cmd = self.pread(port, PX_CMD);
+        self.pwrite(port, PX_CMD, cmd & !CMD_FRE);
+        self.wait_clear(port, PX_CMD, CMD_FR)
+    }
+}
+
+/// Mapea ABAR del controlador. None si el BAR no es de memoria o no se
+/// puede mapear.
+fn map_abar(dev: &PciDevice) -> Option<Hba> {
+    let bar = dev.bars[ABAR_BAR];
+    if !matches!(bar.kind, BarKind::Mem32 | BarKind::Mem64) || bar.base == 0 {
+        return None;
+    }
+    let size = bar.size as usize;
+    match paging::map_mmio(bar.base, bar.size) {
+        Ok(virt) => Some(Hba { base: virt, size }),
+        Err(e) => {
+            serial::log_level(serial::Level::Warn, "AHCI", e);
+            None
+        }
+    }
+}
+
+// ── IDENTIFY por DMA ──────────────────────────────────────────────────────────
+
+/// Emite IDENTIFY (o IDENTIFY PACKET) en el slot 0 de `port` y devuelve los
+/// 256 words de respuesta.
+fn identify_port(hba: &Hba, port: u8, atapi: bool) -> Option<[u16; 256]> {
+    let s64 = hba.read(HBA_CAP) & CAP_S64A != 0;
+    let (clb, fb) = (phys(addr_of!(CMD_LIST)), phys(addr_of!(RECV_FIS)));
+    let (ctba, buf) = (phys(addr_of!(CMD_TABLE)), phys(addr_of!(IDENT_BUF)));
+    if !s64 && (clb | fb | ctba | buf) >> 32 != 0 { return None; }
+
+    let saved = [
+        hba.pread(port, PX_CLB), hba.pread(port, PX_CLBU),
+        hba.pread(port, PX_FB),  hba.pread(port, PX_FBU),
+    ];
+    if !hba.stop_port(port) { return None; }
+
+    // SAFETY: kernel monohilo; el puerto está parado y no lee estas
+    // estructuras hasta que se activa ST más abajo.
+    unsafe {
+        let list  = &mut *addr_of_mut!(CMD_LIST);
+        let table = &mut *addr_of_mut!(CMD_TABLE);
+        (*addr_of_mut!(RECV_FIS)).0 = [0; 256];
+        (*addr_of_mut!(IDENT_BUF)).0 = [0; 256];
+
+        list.0[0] = CmdHeader {
+            flags: CFL_REG_H2D, prdtl: 1, prdbc: 0,
+            ctba: ctba as u32, ctbau: (ctba >> 32) as u32, rsv: [0; 4],
+        };
+        table.cfis = [0; 64];
+        table.cfis[0] = FIS_REG_H2D;
+        table.cfis[1] = FIS_CMD_BIT;
+        table.cfis[2] = if atapi { ATA_CMD_IDENTIFY_PACKET } else { ATA_CMD_IDENTIFY };
+        table.prdt[0] = Prd { dba: buf as u32, dbau: (buf >> 32) as u32, rsv: 0, dbc: 512 - 1 };
+    }
+
+    hba.pwrite(port, PX_CLB,  clb as u32);
+    hba.pwrite(port, PX_CLBU, (clb >> 32) as u32);
+    hba.pwrite(port, PX_FB,   fb as u32);
+    hba.pwrite(port, PX_FBU,  (fb >> 32) as u32);
+    hba.pwrite(port, PX_SERR, 0xFFFF_FFFF);
+    hba.pwrite(port, PX_IS,   0xFFFF_FFFF);
+    hba.pwrite(port, PX_IE,   0);   // sin interrupciones: se sondea CI
+
+    let cmd = hba.pread(port, PX_CMD);
+    hba.pwrite(port, PX_CMD, cmd | CMD_FRE);
+    let mut ok = hba.wait_clear(port, PX_TFD, TFD_BSY | TFD_DRQ);
+    if ok {
+        let cmd = hba.pread(port, PX_CMD);
+        hba.pwrite(port, PX_CMD, cmd | CMD_ST);
+        // Las estructuras deben estar en memoria antes de dar el comando
+        fence(Ordering::SeqCst);
+        hba.pwrite(port, PX_CI, 1);
+
+        ok = false;
+        for _ in 0..SPIN_TIMEOUT {
+            if hba.pread(port, PX_IS) & IS_TFES != 0 { break; }
+            if hba.pread(port, PX_CI) & 1 == 0 {
+                ok = hba.pread(port, PX_TFD) & TFD_ERR == 0;
+                break;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    hba.stop_port(port);
+    hba.pwrite(port, PX_CLB,  saved[0]);
+    hba.pwrite(port, PX_CLBU, saved[1]);
+    hba.pwrite(port, PX_FB,   saved[2]);
+    hba.pwrite(port, PX_FBU,  saved[3]);
+
+    if !ok { return None; }
+    fence(Ordering::SeqCst);
+    Some(unsafe { read_volatile(addr_of!(IDENT_BUF.0)) })
+}
+
+// ── Sondeo ────────────────────────────────────────────────────────────────────
+
+/// Qué hay en un puerto según PxSSTS y PxSIG.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PortDevice {
+    /// Sin dispositivo o con el enlace en bajo consumo.
+    Empty,
+    Ata,
+    Atapi,
+    /// Multiplicador de puertos, puente SEMB u otra firma.
+    Unsupported,
+}
+
+fn port_device(ssts: u32, sig: u32) -> PortDevice {
+    if ssts & 0xF != SSTS_DET_PRESENT || (ssts >> 8) & 0xF != SSTS_IPM_ACTIVE {
+        return PortDevice::Empty;
+    }
+    match sig {
+        SIG_ATA   => PortDevice::Ata,
+        SIG_ATAPI => PortDevice::Atapi,
+        _         => PortDevice::Unsupported,
+    }
+}
+
+/// Resultado de `probe`, para el registro de drivers.
+#[derive(Clone, Copy, Default)]
+pub struct AhciScan {
+    /// Controladores de clase 01h/06h en el bus PCI.
+    pub controllers: usize,
+    /// Controladores cuyo ABAR se pudo mapear.
+    pub mapped:      usize,
+    /// Unidades identificadas y añadidas a `Disks`.
+    pub drives:      usize,
+}
+
+/// Busca controladores AHCI, identifica los dispositivos de sus puertos y
+/// los añade a `disks` (hasta llenar MAX_DISKS). `on_port(puerto)` se llama
+/// antes de cada IDENTIFY (progreso del arranque).
+pub fn probe<F: FnMut(u8)>(pci: &PciBus, disks: &mut Disks, mut on_port: F) -> AhciScan {
+    let mut scan = AhciScan::default();
+    let ctrls = pci.devices[..pci.count]
+        .iter()
+        .filter(|d| d.class_code == 0x01 && d.subclass == 0x06);
+
+    for dev in ctrls {
+        scan.controllers += 1;
+        let Some(hba) = map_abar(dev) else { continue };
+        scan.mapped += 1;
+        dev.enable_mem_and_dma();
+        hba.write(HBA_GHC, hba.read(HBA_GHC) | GHC_AE);
+
+        let pi = hba.read(HBA_PI);
+        for port in 0..MAX_PORTS {
+            if pi & (1 << port) == 0 || !hba.has_port(port) { continue; }
+            let atapi = match port_device(hba.pread(port, PX_SSTS), hba.pread(port, PX_SIG)) {
+                PortDevice::Empty => continue,
+                PortDevice::Ata   => false,
+                PortDevice::Atapi => true,
+                PortDevice::Unsupported => {
+                    serial::log_level(serial::Level::Warn, "AHCI", "firma no soportada (PM/SEMB)");
+                    continue;
+                }
+            };
+            on_port(port);
+            let Some(words) = identify_port(&hba, port, atapi) else {
+                serial::log_level(serial::Level::Warn, "AHCI", "IDENTIFY sin respuesta");
+                continue;
+            };
+
+            let mut d = DiskInfo::from_identify(&words, atapi);
+            d.ahci  = true;
+            d.bus   = port;
+            d.drive = 0;
+            if !disks.push(d) { return scan; }
+            scan.drives += 1;
+            serial::log("AHCI", d.model_str());
+        }
+    }
+    scan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_needs_link_and_active_power_state() {
+        assert_eq!(port_device(0x123, SIG_ATA), PortDevice::Ata);
+        assert_eq!(port_device(0x113, SIG_ATAPI), PortDevice::Atapi);
+        // DET = 1: dispositivo sin enlace PHY
+        assert_eq!(port_device(0x121, SIG_ATA), PortDevice::Empty);
+        assert_eq!(port_device(0, SIG_ATA), PortDevice::Empty);
+        // IPM = 2 (partial) o 6 (slumber): enlace dormido
+        assert_eq!(port_device(0x223, SIG_ATA), PortDevice::Empty);
+        assert_eq!(port_device(0x623, SIG_ATA), PortDevice::Empty);
+    }
+
+    #[test]
+    fn unsupported_signatures() {
+        assert_eq!(port_device(0x123, 0x9669_0101), PortDevice::Unsupported);
+        assert_eq!(port_device(0x123, 0xC33C_0101), PortDevice::Unsupported);
+        assert_eq!(port_device(0x123, 0xFFFF_FFFF), PortDevice::Unsupported);
+    }
+}
@@ -1,4 +1,4 @@
-// drivers/src/storage/ata.rs — PORTIX Kernel v0.8.0
+// drivers/src/storage/ata.rs — PORTIX Kernel
 //
 // CAMBIOS v0.8.0 — Fix "no se detecta drive ATA" en comandos del terminal:
 //
@@ -62,11 +62,19 @@
 //   - Escritura: poll DRQ después de enviar el comando
 //   - flush() comprueba ERR/DF en el status final
 //   - Todos los timeout devuelven AtaError::Timeout
+//
+// CAMBIOS v0.8.1:
+//   - reset_channel(): software reset completo de un canal con los tiempos
+//     de la especificación y nuevo IDENTIFY (comando `atareset`). Refresca
+//     el caché de Primary0.
 
 #![allow(dead_code)]
 
 use core::fmt;
 
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.8.1";
+
 // ── Puertos ATA ────────────────────────────────────────────────────────────────
 
 /// Offsets desde la base del canal
@@ -308,7 +316,11 @@ impl Channel {
 
     unsafe fn identify(&self, is_slave: bool) -> Option<[u16; 256]> {
         self.reset_and_init();
+        self.identify_selected(is_slave)
+    }
 
+    /// IDENTIFY (o IDENTIFY PACKET) sin resetear antes el canal.
+    unsafe fn identify_selected(&self, is_slave: bool) -> Option<[u16; 256]> {
         let head = if is_slave { 0xB0u8 } else { 0xA0u8 };
         self.outb(reg::DRIVE_HEAD, head);
         self.delay400ns();
@@ -389,7 +401,9 @@ impl AtaDrive {
             c.outb(reg::FEATURES, 0);
             c.outb(reg::COMMAND,
                 if self.info.lba48 { cmd::CACHE_FLUSH_EXT } else { cmd::CACHE_FLUSH });
-            let _ = c.wait_not_busy();
+            let st = c.wait_not_busy()?;
+            if st & status::DF  != 0 { return Err(AtaError::DriveFault); }
+            if st & status::ERR != 0 { return Err(AtaError::DeviceError(c.inb(reg::ERROR))); }
             Ok(())
         }
     }
@@ -525,6 +539,11 @@ impl AtaBus {
     ///     Debe invocarse UNA SOLA VEZ en main.rs durante el arranque.
     ///     Llamarlo de nuevo mata el canal en QEMU/VirtualBox.
     pub fn scan() -> Self {
+        Self::scan_with(|_| {})
+    }
+
+    /// Como `scan`, llamando a `on_probe(id)` antes de cada IDENTIFY.
+    pub fn scan_with<F: FnMut(DriveId)>(mut on_probe: F) -> Self {
         let slots: [(DriveId, &'static Channel, bool); 4] = [
             (DriveId::Primary0,   &PRIMARY,   false),
             (DriveId::Primary1,   &PRIMARY,   true),
@@ -536,6 +555,7 @@ impl AtaBus {
         let mut count  = 0;
 
         for (id, chan, is_slave) in slots {
+            on_probe(id);
             if let Some(words) = unsafe { chan.identify(is_slave) } {
                 drives[id as usize] = Some(parse_identify(words, id));
                 count += 1;
@@ -561,6 +581,68 @@ impl AtaBus {
     }
 }
 
+// ── Reset de canal (`atareset`) ───────────────────────────────────────────────
+//
+// Secuencia del software reset (ATA/ATAPI-6 §9.2): SRST=1 durante al menos
+// 5 µs, SRST=0, no leer el status antes de 2 ms y esperar hasta 31 s a que
+// BSY baje. `reset_and_init` (el del arranque) acorta estos tiempos; éste es
+// el completo, para recuperar un canal que quedó colgado tras un error.
+// Después se repite IDENTIFY en master y slave.
+
+/// Espera máxima a BSY=0 tras el reset (31 s según la especificación).
+const RESET_BSY_TICKS: u64 = 31 * crate::time::pit::PIT_HZ as u64;
+
+#[inline(always)]
+unsafe fn io_wait() {
+    core::arch::asm!("out 0x80, al", in("al") 0u8, options(nostack, nomem));
+}
+
+/// Resetea el canal `bus` (0 = primario, 1 = secundario) y devuelve los
+/// words de IDENTIFY de master y slave (None = sin unidad). Si es el
+/// primario, actualiza el caché de Primary0 (o lo invalida si desapareció).
+/// Necesita las interrupciones activas: los tiempos se miden con el PIT.
+pub fn reset_channel(bus: u8) -> AtaResult<[Option<[u16; 256]>; 2]> {
+    use crate::time::pit::ticks;
+    let chan = if bus == 0 { &PRIMARY } else { &SECONDARY };
+    let mut found = [None, None];
+    unsafe {
+        // Bus flotante: no hay controlador ni unidades en este canal
+        if chan.ctrl_inb() == 0xFF {
+            if bus == 0 { invalidate_cached_drive_info(); }
+            return Err(AtaError::NoDrive);
+        }
+
+        chan.ctrl_outb(dctl::NIEN | dctl::SRST);
+        for _ in 0..10 { io_wait(); }            // ≥ 5 µs (≈1 µs por acceso)
+        chan.ctrl_outb(dctl::NIEN);
+
+        // ≥ 2 ms: dos flancos del PIT (10 ms cada tick) garantizan al menos uno entero
+        let t0 = ticks();
+        while ticks() < t0 + 2 { core::hint::spin_loop(); }
+
+        let deadline = ticks() + RESET_BSY_TICKS;
+        while chan.ctrl_inb() & status::BSY != 0 {
+            if ticks() >= deadline {
+                if bus == 0 { invalidate_cached_drive_info(); }
+                return Err(AtaError::Timeout);
+            }
+            core::hint::spin_loop();
+        }
+
+        for (slave, slot) in found.iter_mut().enumerate() {
+            *slot = chan.identify_selected(slave == 1);
+        }
+    }
+
+    if bus == 0 {
+        match found[0] {
+            Some(words) => store_primary_drive_info(parse_identify(words, DriveId::Primary0)),
+            None        => invalidate_cached_drive_info(),
+        }
+    }
+    Ok(found)
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 //  CACHÉ GLOBAL DE DriveInfo — NUEVO en v0.8.0
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -574,8 +656,8 @@ impl AtaBus {
 //
 // Seguridad:
 //   El kernel PORTIX es single-threaded. No existen preemption ni ISRs
-//   que modifiquen este dato. La escritura ocurre una sola vez en boot
-//   y todas las lecturas son posteriores → no hay carreras de datos.
+//   que modifiquen este dato. La escritura ocurre en boot (y de nuevo tras
+//   `atareset`, desde el loop principal) → no hay carreras de datos.
 //   El compilador no puede reordenar más allá de la barrera de boot
 //   porque la escritura y las lecturas están en funciones distintas
 //   compiladas por separado.
@@ -625,6 +707,12 @@ pub fn store_primary_drive_info(info: DriveInfo) {
     }
 }
 
+/// Olvida el Primary0 cacheado (el reset del canal no lo encontró).
+pub fn invalidate_cached_drive_info() {
+    // SAFETY: ver store_primary_drive_info.
+    unsafe { CACHED_DRIVE.valid = false; }
+}
+
 /// Devuelve el DriveInfo cacheado del Primary0.
 ///
 /// Returns `None` si `store_primary_drive_info` no fue llamado todavía
@@ -655,6 +743,34 @@ pub fn get_cached_drive_info() -> Option<DriveInfo> {
 
 // ── Parseo de IDENTIFY ────────────────────────────────────────────────────────
 
+/// Capacidad en sectores de 512 B a partir del bloque IDENTIFY DEVICE.
+/// Devuelve (sectores, usa_lba48).
+///
+/// Los words llegan ya en orden de host (`inw` es little-endian, como el
+/// bus ATA); a diferencia de las cadenas de modelo/serie, los contadores no
+/// llevan los bytes intercambiados: basta con componer los words de menor a
+/// mayor peso.
+///
+///   - LBA28: words 60-61 (máx. 0x0FFF_FFFF sectores ≈ 128 GiB).
+///   - LBA48: words 100-103, solo si el word 83 es válido (bit 14 = 1,
+///     bit 15 = 0) y anuncia el feature set (bit 10). Algunos discos activan
+///     el bit pero dejan 100-103 a cero: en ese caso se usa LBA28. Si ambos
+///     están presentes se toma el mayor.
+pub fn identify_capacity(words: &[u16; 256]) -> (u64, bool) {
+    let lba28 = (words[60] as u64) | ((words[61] as u64) << 16);
+
+    let w83_valid = words[83] & 0xC000 == 0x4000;
+    let has_lba48 = w83_valid && words[83] & (1 << 10) != 0;
+    let lba48 = if has_lba48 {
+        (words[100] as u64)
+        | ((words[101] as u64) << 16)
+        | ((words[102] as u64) << 32)
+        | ((words[103] as u64) << 48)
+    } else { 0 };
+
+    if lba48 > lba28 { (lba48, true) } else { (lba28, has_lba48 && lba48 != 0) }
+}
+
 fn parse_identify(words: [u16; 256], id: DriveId) -> DriveInfo {
     let kind = if words[0] & 0x8000 != 0 { DriveType::Atapi } else { DriveType::Ata };
 
@@ -678,16 +794,7 @@ fn parse_identify(words: [u16; 256], id: DriveId) -> DriveInfo {
         serial[i * 2 + 1] = (w & 0xFF) as u8;
     }
 
-    let lba48 = words[83] & (1 << 10) != 0;
-
-    let total_sectors = if lba48 {
-        (words[100] as u64)
-        | ((words[101] as u64) << 16)
-        | ((words[102] as u64) << 32)
-        | ((words[103] as u64) << 48)
-    } else {
-        (words[60] as u64) | ((words[61] as u64) << 16)
-    };
+    let (total_sectors, lba48) = identify_capacity(&words);
 
     DriveInfo {
         id, kind, total_sectors,
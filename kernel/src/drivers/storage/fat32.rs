@@ -1,14 +1,29 @@
-// drivers/storage/fat32.rs — PORTIX Kernel v0.7.5
+// drivers/storage/fat32.rs — PORTIX Kernel
 // Driver FAT32 sobre ATA PIO.
 //
 // CAMBIOS v0.7.5:
 //   - accumulate_lfn(): eliminado el bloque de código duplicado que
 //     añadía los caracteres LFN dos veces (una vía macro push! y otra
 //     vía los bucles manuales posteriores), corrompiendo los nombres.
+//
+// CAMBIOS v0.8.1:
+//   - Modo de montaje RO/RW. Toda operación de escritura pública
+//     (write_file, create_*, delete_entry) devuelve FatError::ReadOnly si el
+//     volumen es de solo lectura. Las unidades ATAPI (CD-ROM/ISO) siempre RO.
+//   - El modo por defecto es global (`set_default_read_only`) porque el
+//     volumen se re-monta en cada comando; lo cambia `remount ro|rw`.
+//   - sync_fsinfo(): recuenta los clusters libres y actualiza el sector
+//     FSInfo (free count + next free) antes de apagar/reiniciar; después
+//     vacía la caché de escritura del disco.
+//   - resolve_path(): ruta absoluta → DirEntryInfo (la usan `cat` y el
+//     Explorador).
 
 #![allow(dead_code)]
 
-use crate::drivers::storage::ata::{AtaDrive, AtaError};
+use crate::drivers::storage::ata::{get_cached_drive_info, AtaDrive, AtaError, DriveType};
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.7.5";
 
 // ── Errores ───────────────────────────────────────────────────────────────────
 
@@ -23,6 +38,7 @@ pub enum FatError {
     NameTooLong,
     InvalidPath,
     Corrupt,
+    ReadOnly,
 }
 
 impl From<AtaError> for FatError {
@@ -31,6 +47,27 @@ impl From<AtaError> for FatError {
 
 pub type FatResult<T> = Result<T, FatError>;
 
+// ── Modo de montaje ───────────────────────────────────────────────────────────
+
+// SAFETY: kernel single-thread; solo se escribe desde comandos del terminal.
+static mut DEFAULT_READ_ONLY: bool = false;
+
+/// Modo con el que se montarán los próximos volúmenes (true = solo lectura).
+pub fn set_default_read_only(ro: bool) { unsafe { DEFAULT_READ_ONLY = ro; } }
+pub fn default_read_only() -> bool      { unsafe { DEFAULT_READ_ONLY } }
+
+/// Modo efectivo de un volumen en una unidad `kind`: el global, salvo ATAPI
+/// (siempre RO). Es lo que `mount` aplica al montar.
+pub fn read_only_for(kind: DriveType) -> bool {
+    default_read_only() || kind == DriveType::Atapi
+}
+
+/// Modo efectivo del volumen primario (el de la caché de arranque) sin
+/// montarlo: lo usan Explorer, IDE y los comandos de estado.
+pub fn effective_read_only() -> bool {
+    get_cached_drive_info().map_or(default_read_only(), |info| read_only_for(info.kind))
+}
+
 // ── Constantes FAT ────────────────────────────────────────────────────────────
 
 const FAT_EOC:        u32  = 0x0FFF_FFF8;
@@ -40,6 +77,11 @@ const ATTR_ARCH:      u8   = 0x20;
 const ATTR_LFN:       u8   = 0x0F;
 const DIR_ENTRY_SIZE: usize = 32;
 
+// Firmas del sector FSInfo
+const FSI_LEAD_SIG:   u32  = 0x4161_5252;
+const FSI_STRUC_SIG:  u32  = 0x6141_7272;
+const FSI_TRAIL_SIG:  u32  = 0xAA55_0000;
+
 // ── BPB ───────────────────────────────────────────────────────────────────────
 
 #[repr(C, packed)]
@@ -104,6 +146,11 @@ impl DirEntry83 {
     fn file_size(&self) -> u32 {
         unsafe { core::ptr::read_unaligned(core::ptr::addr_of!(self.file_size)) }
     }
+    fn mtime(&self) -> u32 {
+        let d: u16 = unsafe { core::ptr::read_unaligned(core::ptr::addr_of!(self._wrt_date)) };
+        let t: u16 = unsafe { core::ptr::read_unaligned(core::ptr::addr_of!(self._wrt_time)) };
+        ((d as u32) << 16) | t as u32
+    }
     fn is_free(&self) -> bool { self.name[0] == 0xE5 || self.name[0] == 0x00 }
     fn is_end(&self)  -> bool { self.name[0] == 0x00 }
     fn is_lfn(&self)  -> bool { self.attr == ATTR_LFN }
@@ -136,12 +183,43 @@ pub struct DirEntryInfo {
     pub cluster:    u32,
     pub dir_sector: u64,
     pub dir_offset: usize,
+    /// Fecha/hora de última escritura: (wrt_date << 16) | wrt_time.
+    /// Comparable directamente como entero (0 = sin fecha).
+    pub mtime:      u32,
 }
 
 impl DirEntryInfo {
     pub fn name_str(&self) -> &str {
         core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("?")
     }
+
+    /// Entrada sin posición en disco, para los tests de quien lista directorios.
+    #[cfg(test)]
+    pub(crate) fn for_test(name: &str, is_dir: bool, size: u32, cluster: u32, mtime: u32) -> Self {
+        let mut e = DirEntryInfo {
+            name: [0; 256], name_len: name.len(), is_dir, size,
+            cluster, dir_sector: 0, dir_offset: 0, mtime,
+        };
+        e.name[..name.len()].copy_from_slice(name.as_bytes());
+        e
+    }
+}
+
+/// Recorrido de `Fat32Volume::resolve_path` desde `root`, con `find(clúster,
+/// nombre)` buscando una entrada en un directorio. Separado del volumen para
+/// poder probarlo sin disco.
+fn resolve_with(
+    root: DirEntryInfo, path: &str,
+    mut find: impl FnMut(u32, &str) -> Option<DirEntryInfo>,
+) -> Option<DirEntryInfo> {
+    let mut cur = root.clone();
+    for comp in path.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        if !cur.is_dir { return None; }
+        if comp == ".." && cur.cluster == root.cluster { continue; }
+        let e = find(cur.cluster, comp)?;
+        cur = if e.is_dir && e.cluster == 0 { root.clone() } else { e };
+    }
+    Some(cur)
 }
 
 // ── Volumen ───────────────────────────────────────────────────────────────────
@@ -157,6 +235,8 @@ pub struct Fat32Volume {
     root_clus:     u32,
     data_start:    u64,
     clus_count:    u32,
+    fs_info_sec:   u16,
+    read_only:     bool,
 }
 
 impl Fat32Volume {
@@ -176,6 +256,7 @@ impl Fat32Volume {
         let num_fats      = vbr[16] as u32;
         let fat_size      = u32::from_le_bytes([vbr[36], vbr[37], vbr[38], vbr[39]]);
         let root_clus     = u32::from_le_bytes([vbr[44], vbr[45], vbr[46], vbr[47]]);
+        let fs_info_sec   = u16::from_le_bytes([vbr[48], vbr[49]]);
         let fs_type       = &vbr[82..90];
 
         if fs_type != b"FAT32   " { return Err(FatError::NotFat32); }
@@ -189,10 +270,12 @@ impl Fat32Volume {
             .saturating_sub(reserved_secs + num_fats * fat_size)
             / sec_per_clus.max(1);
 
+        let read_only = read_only_for(drive.info().kind);
+
         Ok(Fat32Volume {
             drive, part_lba, bytes_per_sec, sec_per_clus,
             reserved_secs, num_fats, fat_size, root_clus,
-            data_start, clus_count,
+            data_start, clus_count, fs_info_sec, read_only,
         })
     }
 
@@ -281,6 +364,68 @@ impl Fat32Volume {
     // ── API pública ────────────────────────────────────────────────────────────
 
     pub fn root_cluster(&self) -> u32 { self.root_clus }
+    pub fn is_read_only(&self) -> bool { self.read_only }
+
+    #[inline]
+    fn check_rw(&self) -> FatResult<()> {
+        if self.read_only { Err(FatError::ReadOnly) } else { Ok(()) }
+    }
+
+    /// Recorre la FAT sector a sector y devuelve (clusters libres, primer
+    /// cluster libre). El primero es 0xFFFF_FFFF si el volumen está lleno.
+    pub fn count_free_clusters(&self) -> FatResult<(u32, u32)> {
+        let per_sec   = self.bytes_per_sec as u32 / 4;
+        let mut free  = 0u32;
+        let mut first = 0xFFFF_FFFFu32;
+        let mut sec   = [0u8; 512];
+        let mut loaded = u64::MAX;
+        for c in 2..self.clus_count + 2 {
+            let lba = self.fat_lba(c);
+            if lba != loaded {
+                self.drive.read_sectors(lba, 1, &mut sec)?;
+                loaded = lba;
+            }
+            let off = ((c % per_sec) * 4) as usize;
+            let v = u32::from_le_bytes([sec[off], sec[off+1], sec[off+2], sec[off+3]]) & 0x0FFF_FFFF;
+            if v == FAT_FREE {
+                free += 1;
+                if first == 0xFFFF_FFFF { first = c; }
+            }
+        }
+        Ok((free, first))
+    }
+
+    /// Actualiza el FSInfo con el recuento real de clusters libres y vacía la
+    /// caché de escritura del disco. Devuelve los clusters libres.
+    pub fn sync_fsinfo(&self) -> FatResult<u32> {
+        self.check_rw()?;
+        if self.fs_info_sec == 0 || self.fs_info_sec == 0xFFFF {
+            return Err(FatError::Corrupt);
+        }
+        let lba = self.part_lba + self.fs_info_sec as u64;
+        let mut sec = [0u8; 512];
+        self.drive.read_sectors(lba, 1, &mut sec)?;
+        let rd = |o: usize| u32::from_le_bytes([sec[o], sec[o+1], sec[o+2], sec[o+3]]);
+        if rd(0) != FSI_LEAD_SIG || rd(484) != FSI_STRUC_SIG || rd(508) != FSI_TRAIL_SIG {
+            // Sector reservado por el BPB pero sin FSInfo válido: reconstruirlo
+            sec = [0u8; 512];
+            sec[0..4].copy_from_slice(&FSI_LEAD_SIG.to_le_bytes());
+            sec[484..488].copy_from_slice(&FSI_STRUC_SIG.to_le_bytes());
+            sec[508..512].copy_from_slice(&FSI_TRAIL_SIG.to_le_bytes());
+        }
+        let (free, first) = self.count_free_clusters()?;
+        sec[488..492].copy_from_slice(&free.to_le_bytes());
+        sec[492..496].copy_from_slice(&first.to_le_bytes());
+        self.drive.write_sectors(lba, 1, &sec)?;
+        self.drive.flush()?;
+        Ok(free)
+    }
+
+    /// Vacía la caché de escritura del disco subyacente.
+    pub fn flush(&self) -> FatResult<()> {
+        self.drive.flush()?;
+        Ok(())
+    }
 
     pub fn list_dir<F>(&self, dir_cluster: u32, mut cb: F) -> FatResult<()>
     where F: FnMut(&DirEntryInfo)
@@ -332,6 +477,24 @@ impl Fat32Volume {
         found.ok_or(FatError::NotFound)
     }
 
+    /// Resuelve una ruta absoluta ("/a/b/c.txt") desde la raíz. "." y ".."
+    /// siguen las entradas reales del directorio (un ".." con clúster 0
+    /// apunta a la raíz). La raíz se devuelve como una entrada sintética de
+    /// directorio. None si falta algún componente o un intermedio no es un
+    /// directorio.
+    pub fn resolve_path(&self, path: &str) -> Option<DirEntryInfo> {
+        resolve_with(self.root_entry(), path, |clus, name| self.find_entry(clus, name).ok())
+    }
+
+    fn root_entry(&self) -> DirEntryInfo {
+        let mut name = [0u8; 256];
+        name[0] = b'/';
+        DirEntryInfo {
+            name, name_len: 1, is_dir: true, size: 0, cluster: self.root_clus,
+            dir_sector: 0, dir_offset: 0, mtime: 0,
+        }
+    }
+
     pub fn read_file(&self, entry: &DirEntryInfo, buf: &mut [u8]) -> FatResult<usize> {
         if entry.is_dir { return Err(FatError::IsDir); }
         let to_read = buf.len().min(entry.size as usize);
@@ -349,8 +512,52 @@ impl Fat32Volume {
         Ok(done)
     }
 
+    /// Lee desde el byte `offset` del archivo, saltando clusters completos
+    /// por la cadena FAT sin leer sus datos (paginación para archivos grandes).
+    pub fn read_file_at(&self, entry: &DirEntryInfo, offset: usize, buf: &mut [u8]) -> FatResult<usize> {
+        if entry.is_dir { return Err(FatError::IsDir); }
+        let size = entry.size as usize;
+        if offset >= size { return Ok(0); }
+        let to_read = buf.len().min(size - offset);
+        let bpc = self.bpc();
+        let mut clus = entry.cluster;
+        for _ in 0..offset / bpc {
+            if self.is_eoc(clus) || clus < 2 { return Err(FatError::Corrupt); }
+            clus = self.read_fat(clus)?;
+        }
+        let mut skip = offset % bpc;
+        let mut done = 0usize;
+        while done < to_read && !self.is_eoc(clus) && clus >= 2 {
+            let mut cb = ClusterBuf::new(bpc);
+            self.read_cluster(clus, &mut cb)?;
+            let chunk = (to_read - done).min(bpc - skip);
+            buf[done..done + chunk].copy_from_slice(&cb.data[skip..skip + chunk]);
+            done += chunk;
+            skip = 0;
+            clus = self.read_fat(clus)?;
+        }
+        Ok(done)
+    }
+
     pub fn write_file(&self, entry: &mut DirEntryInfo, data: &[u8]) -> FatResult<()> {
+        self.write_file_with(entry, data.len(), |off, out| {
+            out.copy_from_slice(&data[off..off + out.len()]);
+        })
+    }
+
+    /// Como `write_file`, pero el contenido (`len` bytes) lo genera `fill`
+    /// clúster a clúster: `fill(offset, out)` rellena `out` con los bytes
+    /// `offset..offset + out.len()`. Permite escribir archivos mayores que
+    /// cualquier buffer disponible (p. ej. capturas de pantalla).
+    pub fn write_file_with(
+        &self,
+        entry: &mut DirEntryInfo,
+        len: usize,
+        mut fill: impl FnMut(usize, &mut [u8]),
+    ) -> FatResult<()> {
+        self.check_rw()?;
         if entry.is_dir { return Err(FatError::IsDir); }
+        if len > u32::MAX as usize { return Err(FatError::NoSpace); }
         let bpc = self.bpc();
         if entry.cluster != 0 { self.free_chain(entry.cluster)?; }
         let first = self.alloc_cluster()?;
@@ -359,21 +566,21 @@ impl Fat32Volume {
 
         let mut written = 0usize;
         let mut prev = first;
-        while written < data.len() {
-            let end = (written + bpc).min(data.len());
+        while written < len {
+            let end = (written + bpc).min(len);
             let mut cb = ClusterBuf::new(bpc);
             let chunk = end - written;
-            cb.data[..chunk].copy_from_slice(&data[written..end]);
+            fill(written, &mut cb.data[..chunk]);
             self.write_cluster(prev, &cb)?;
             written = end;
-            if written < data.len() {
+            if written < len {
                 let next = self.alloc_cluster()?;
                 self.write_fat(prev, next)?;
                 prev = next;
             }
         }
-        entry.size = data.len() as u32;
-        self.update_size_field(entry, data.len() as u32)?;
+        entry.size = len as u32;
+        self.update_size_field(entry, len as u32)?;
         Ok(())
     }
 
@@ -386,6 +593,7 @@ impl Fat32Volume {
     }
 
     fn create_entry(&self, dir_cluster: u32, name: &str, is_dir: bool) -> FatResult<DirEntryInfo> {
+        self.check_rw()?;
         if name.len() > 255 { return Err(FatError::NameTooLong); }
         let clus = if is_dir {
             let c = self.alloc_cluster()?;
@@ -406,10 +614,49 @@ impl Fat32Volume {
         let mut nb = [0u8; 256];
         let nl = name.len().min(255);
         nb[..nl].copy_from_slice(name.as_bytes());
-        Ok(DirEntryInfo { name: nb, name_len: nl, is_dir, size: 0, cluster: clus, dir_sector, dir_offset })
+        Ok(DirEntryInfo { name: nb, name_len: nl, is_dir, size: 0, cluster: clus, dir_sector, dir_offset, mtime: 0 })
+    }
+
+    /// Devuelve el subdirectorio `name` de `dir_cluster`, creándolo si no existe.
+    pub fn find_or_create_dir(&self, dir_cluster: u32, name: &str) -> FatResult<DirEntryInfo> {
+        match self.find_entry(dir_cluster, name) {
+            Ok(e) if e.is_dir => Ok(e),
+            Ok(_)             => Err(FatError::IsFile),
+            Err(FatError::NotFound) => self.create_dir(dir_cluster, name),
+            Err(e)            => Err(e),
+        }
+    }
+
+    /// Crea (o trunca) el archivo `name` en `dir_cluster` y escribe `data`.
+    pub fn write_file_in(&self, dir_cluster: u32, name: &str, data: &[u8]) -> FatResult<()> {
+        let mut entry = match self.find_entry(dir_cluster, name) {
+            Ok(e) if e.is_dir => return Err(FatError::IsDir),
+            Ok(e)             => e,
+            Err(FatError::NotFound) => self.create_file(dir_cluster, name)?,
+            Err(e)            => return Err(e),
+        };
+        self.write_file(&mut entry, data)
+    }
+
+    /// `write_file_with` sobre `dir_cluster/name`, creando el archivo si no existe.
+    pub fn write_file_in_with(
+        &self,
+        dir_cluster: u32,
+        name: &str,
+        len: usize,
+        fill: impl FnMut(usize, &mut [u8]),
+    ) -> FatResult<()> {
+        let mut entry = match self.find_entry(dir_cluster, name) {
+            Ok(e) if e.is_dir => return Err(FatError::IsDir),
+            Ok(e)             => e,
+            Err(FatError::NotFound) => self.create_file(dir_cluster, name)?,
+            Err(e)            => return Err(e),
+        };
+        self.write_file_with(&mut entry, len, fill)
     }
 
     pub fn delete_entry(&self, entry: &DirEntryInfo) -> FatResult<()> {
+        self.check_rw()?;
         if entry.cluster != 0 { self.free_chain(entry.cluster)?; }
         let mut sec = [0u8; 512];
         self.drive.read_sectors(entry.dir_sector, 1, &mut sec)?;
@@ -544,6 +791,7 @@ fn build_entry(raw: &DirEntry83, lfn: &[u16; 256], lfn_len: usize, dir_sector: u
         cluster:    raw.cluster(),
         dir_sector,
         dir_offset,
+        mtime:      raw.mtime(),
     }
 }
 
@@ -574,4 +822,81 @@ impl ClusterBuf {
     fn new(len: usize) -> Self {
         ClusterBuf { data: [0u8; MAX_BPC], len: len.min(MAX_BPC) }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: u32 = 2;
+    const HOME: u32 = 5;
+    const USER: u32 = 7;
+
+    /// /home/user/notas.txt, con "." y ".." como en disco (".." de un
+    /// directorio colgado de la raíz apunta al clúster 0).
+    fn find(clus: u32, name: &str) -> Option<DirEntryInfo> {
+        let dir: &[(&str, bool, u32)] = match clus {
+            ROOT => &[("home", true, HOME), ("boot.cfg", false, 30)],
+            HOME => &[(".", true, HOME), ("..", true, 0), ("user", true, USER)],
+            USER => &[(".", true, USER), ("..", true, HOME), ("notas.txt", false, 40)],
+            _    => &[],
+        };
+        dir.iter().find(|(n, _, _)| n.eq_ignore_ascii_case(name)).map(|&(n, d, c)| DirEntryInfo::for_test(n, d, 0, c, 0))
+    }
+
+    fn resolve(path: &str) -> Option<(alloc::string::String, u32)> {
+        resolve_with(DirEntryInfo::for_test("/", true, 0, ROOT, 0), path, find)
+            .map(|e| (alloc::string::String::from(e.name_str()), e.cluster))
+    }
+
+    fn at(name: &str, cluster: u32) -> Option<(alloc::string::String, u32)> {
+        Some((name.into(), cluster))
+    }
+
+    #[test]
+    fn resolve_plain_paths() {
+        assert_eq!(resolve("/"), at("/", ROOT));
+        assert_eq!(resolve(""), at("/", ROOT));
+        assert_eq!(resolve("/home/user"), at("user", USER));
+        assert_eq!(resolve("/home/user/notas.txt"), at("notas.txt", 40));
+        assert_eq!(resolve("/HOME/User/NOTAS.TXT"), at("notas.txt", 40));
+    }
+
+    #[test]
+    fn resolve_dot_and_trailing_slash() {
+        assert_eq!(resolve("/home/./user/."), at("user", USER));
+        assert_eq!(resolve("/home/user/"), at("user", USER));
+        assert_eq!(resolve("//home///user//"), at("user", USER));
+    }
+
+    #[test]
+    fn resolve_dotdot() {
+        assert_eq!(resolve("/home/user/.."), at("..", HOME));
+        // ".." con clúster 0 es la raíz
+        assert_eq!(resolve("/home/.."), at("/", ROOT));
+        assert_eq!(resolve("/home/user/../../boot.cfg"), at("boot.cfg", 30));
+        // Por encima de la raíz se queda en la raíz
+        assert_eq!(resolve("/../.."), at("/", ROOT));
+        assert_eq!(resolve("/../home"), at("home", HOME));
+    }
+
+    #[test]
+    fn resolve_missing_or_through_a_file() {
+        assert_eq!(resolve("/nada"), None);
+        assert_eq!(resolve("/home/nadie/notas.txt"), None);
+        assert_eq!(resolve("/boot.cfg/x"), None);
+        assert_eq!(resolve("/boot.cfg/.."), None);
+    }
+
+    #[test]
+    fn atapi_volumes_are_always_read_only() {
+        // DEFAULT_READ_ONLY es global: se restaura al terminar
+        let prev = default_read_only();
+        set_default_read_only(false);
+        assert!(!read_only_for(DriveType::Ata));
+        assert!(read_only_for(DriveType::Atapi));
+        set_default_read_only(true);
+        assert!(read_only_for(DriveType::Ata));
+        assert!(read_only_for(DriveType::Atapi));
+        set_default_read_only(prev);
+    }
+}
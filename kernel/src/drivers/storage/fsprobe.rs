@@ -0,0 +1,141 @@
+// drivers/storage/fsprobe.rs — PORTIX Kernel
+//
+// Tabla de particiones MBR y detección del sistema de archivos por firmas.
+// Solo lee; lo usan `parts` y `lsblk -f`.
+//
+// Firmas:
+//   NTFS   OEM "NTFS    " en el byte 3 del boot sector
+//   exFAT  OEM "EXFAT   " en el byte 3
+//   FAT    BPB coherente (bytes/sector potencia de 2 en 512..4096, sectores
+//          por clúster potencia de 2, reservados > 0, 1-2 FATs). FAT32 si
+//          no hay entradas de raíz ni FAT de 16 bits; si no, FAT12/FAT16
+//          según el nº de clústeres (< 4085 → FAT12), como manda la spec.
+//   ext    superbloque en el byte 1024 del volumen (sector 2) con magic
+//          0xEF53 en +0x38. ext4 si usa extents (incompat 0x40), ext3 si
+//          tiene journal (compat 0x04), si no ext2.
+//
+// Un disco sin MBR puede ser un "superfloppy": el sistema de archivos
+// empieza en el sector 0. Como un boot sector FAT también lleva 0x55AA, el
+// sector 0 se prueba primero como boot sector y solo después como MBR.
+
+#![allow(dead_code)]
+
+use crate::drivers::storage::ata::AtaDrive;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FsKind { Fat12, Fat16, Fat32, ExFat, Ntfs, Ext2, Ext3, Ext4, Unknown }
+
+impl FsKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FsKind::Fat12   => "FAT12",
+            FsKind::Fat16   => "FAT16",
+            FsKind::Fat32   => "FAT32",
+            FsKind::ExFat   => "exFAT",
+            FsKind::Ntfs    => "NTFS",
+            FsKind::Ext2    => "ext2",
+            FsKind::Ext3    => "ext3",
+            FsKind::Ext4    => "ext4",
+            FsKind::Unknown => "desconocido",
+        }
+    }
+}
+
+// ── MBR ───────────────────────────────────────────────────────────────────────
+
+/// Entrada primaria de la tabla (16 B desde 0x1BE):
+/// +0 estado (0x80 = arranque) · +4 tipo · +8 LBA inicio · +12 nº sectores.
+#[derive(Clone, Copy)]
+pub struct MbrEntry {
+    pub boot:    u8,
+    pub ptype:   u8,
+    pub lba:     u32,
+    pub sectors: u32,
+}
+
+impl MbrEntry {
+    pub fn is_empty(&self) -> bool { self.ptype == 0x00 }
+    pub fn is_extended(&self) -> bool { matches!(self.ptype, 0x05 | 0x0F) }
+}
+
+pub fn has_boot_signature(sec: &[u8; 512]) -> bool {
+    sec[510] == 0x55 && sec[511] == 0xAA
+}
+
+/// Las 4 entradas primarias; None si el sector no lleva la firma 0x55AA.
+pub fn parse_mbr(sec: &[u8; 512]) -> Option<[MbrEntry; 4]> {
+    if !has_boot_signature(sec) { return None; }
+    Some(core::array::from_fn(|i| {
+        let e = &sec[0x1BE + i * 16..0x1BE + i * 16 + 16];
+        MbrEntry {
+            boot:    e[0],
+            ptype:   e[4],
+            lba:     u32::from_le_bytes([e[8],  e[9],  e[10], e[11]]),
+            sectors: u32::from_le_bytes([e[12], e[13], e[14], e[15]]),
+        }
+    }))
+}
+
+// ── Firmas ────────────────────────────────────────────────────────────────────
+
+fn le16(b: &[u8], off: usize) -> u32 { u16::from_le_bytes([b[off], b[off + 1]]) as u32 }
+fn le32(b: &[u8], off: usize) -> u32 { u32::from_le_bytes([b[off], b[off + 1], b[off + 2], b[off + 3]]) }
+
+/// FAT12/16/32, exFAT o NTFS a partir del primer sector del volumen.
+pub fn detect_boot_sector(sec: &[u8; 512]) -> FsKind {
+    match &sec[3..11] {
+        b"NTFS    " => return FsKind::Ntfs,
+        b"EXFAT   " => return FsKind::ExFat,
+        _ => {}
+    }
+    if !has_boot_signature(sec) { return FsKind::Unknown; }
+
+    let bps      = le16(sec, 0x0B);
+    let spc      = sec[0x0D] as u32;
+    let reserved = le16(sec, 0x0E);
+    let fats     = sec[0x10] as u32;
+    let root_ent = le16(sec, 0x11);
+    let tot16    = le16(sec, 0x13);
+    let fatsz16  = le16(sec, 0x16);
+    let tot32    = le32(sec, 0x20);
+    let fatsz32  = le32(sec, 0x24);
+
+    let bpb_ok = (512..=4096).contains(&bps) && bps.is_power_of_two()
+        && spc != 0 && spc.is_power_of_two()
+        && reserved != 0 && (1..=2).contains(&fats);
+    if !bpb_ok { return FsKind::Unknown; }
+
+    if root_ent == 0 && fatsz16 == 0 && fatsz32 != 0 { return FsKind::Fat32; }
+
+    let total    = if tot16 != 0 { tot16 } else { tot32 };
+    let root_sec = (root_ent * 32).div_ceil(bps);
+    let meta     = reserved + fats * fatsz16 + root_sec;
+    if fatsz16 == 0 || total <= meta { return FsKind::Unknown; }
+    let clusters = (total - meta) / spc;
+    if clusters < 4085 { FsKind::Fat12 } else { FsKind::Fat16 }
+}
+
+/// ext2/3/4 a partir del sector 2 del volumen (bytes 1024..1536, el inicio
+/// del superbloque).
+pub fn detect_ext_superblock(sec: &[u8; 512]) -> FsKind {
+    if le16(sec, 0x38) != 0xEF53 { return FsKind::Unknown; }
+    let compat   = le32(sec, 0x5C);
+    let incompat = le32(sec, 0x60);
+    if incompat & 0x40 != 0 { FsKind::Ext4 }
+    else if compat & 0x04 != 0 { FsKind::Ext3 }
+    else { FsKind::Ext2 }
+}
+
+/// Prueba las firmas del volumen que empieza en `lba`. Los errores de
+/// lectura cuentan como "desconocido".
+pub fn probe_volume(drive: &AtaDrive, lba: u64) -> FsKind {
+    let mut sec = [0u8; 512];
+    if drive.read_sectors(lba, 1, &mut sec).is_ok() {
+        let fs = detect_boot_sector(&sec);
+        if fs != FsKind::Unknown { return fs; }
+    }
+    if drive.read_sectors(lba + 2, 1, &mut sec).is_ok() {
+        return detect_ext_superblock(&sec);
+    }
+    FsKind::Unknown
+}
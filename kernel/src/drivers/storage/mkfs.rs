@@ -167,7 +167,16 @@ fn write_vbr(drive: &AtaDrive, part_secs: u32) -> Result<(), ()> {
     vbr[510] = 0x55;
     vbr[511] = 0xAA;
 
-    drive.write_sectors(PART_LBA_START as u64, 1, &vbr).map_err(|_| ())
+    drive.write_sectors(PART_LBA_START as u64, 1, &vbr).map_err(|_| ())?;
+
+    // FSInfo (sector 1): recuento de libres desconocido hasta el primer sync
+    let mut fsi = [0u8; 512];
+    fsi[0..4].copy_from_slice(&0x4161_5252u32.to_le_bytes());
+    fsi[484..488].copy_from_slice(&0x6141_7272u32.to_le_bytes());
+    fsi[488..492].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes()); // free count
+    fsi[492..496].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes()); // next free
+    fsi[508..512].copy_from_slice(&0xAA55_0000u32.to_le_bytes());
+    drive.write_sectors(PART_LBA_START as u64 + 1, 1, &fsi).map_err(|_| ())
 }
 
 fn init_fat(drive: &AtaDrive) -> Result<(), ()> {
@@ -1,4 +1,6 @@
 pub mod ata;
+pub mod ahci;
 pub mod fat32;
+pub mod fsprobe;
 pub mod vfs;
 pub mod mkfs;
\ No newline at end of file
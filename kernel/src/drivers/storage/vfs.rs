@@ -1,4 +1,4 @@
-// drivers/storage/vfs.rs — PORTIX Virtual Filesystem v1.1
+// drivers/storage/vfs.rs — PORTIX Virtual Filesystem
 //
 // CAPA: drivers/storage  (no ui/)
 //
@@ -17,6 +17,9 @@
 
 #![allow(dead_code)]
 
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "1.1";
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Árbol VFS predefinido
 // ─────────────────────────────────────────────────────────────────────────────
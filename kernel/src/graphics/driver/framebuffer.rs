@@ -1,4 +1,4 @@
-// kernel/src/framebuffer.rs — PORTIX v0.9.0
+// kernel/src/framebuffer.rs — PORTIX
 //
 // CAMBIOS RESPECTO A v0.8:
 //   [+] Restauradas TODAS las constantes de Color del v0.8 (RED, GREEN, GRAY, CYAN, etc.)
@@ -10,11 +10,28 @@
 //   [+] fill_gradient_dither — mejora #4: degradado sin banding
 //   [+] Bresenham, fill_circle, scroll_region_up, blit_sprite — nuevas primitivas
 //   [+] Layout::new() 100% responsivo — sin constantes de resolución hardcodeadas
+//   [+] fill_rect/clear con rep stosq — 2 píxeles por escritura, bordes
+//       no alineados a 8 bytes con escrituras de 32 bits
+//   [+] blit_rgb / blit_rgb_scaled — copia de imágenes 0x00RRGGBB con
+//       recorte por los cuatro bordes (clip_rect) y escalado entero
+//   [+] CursorShape — cursor del ratón con forma por contexto (flecha, mano,
+//       cruz, I-beam, redimensionar vertical) vía Console::set_cursor_shape()
+//   [+] Alfa uniforme: blend_pixel, draw_rect_alpha y write_at_alpha (texto
+//       mezclado con el back-buffer, sin pintar el fondo del glifo). La
+//       mezcla de un píxel es blend_alpha(), pura y probada en el host
+//   [+] Segunda fuente compacta 6×8: Console::write_at_small(). Layout expone
+//       el avance por carácter (char_w / small_w) para no asumir 9 px fijos
+//   [+] BackBufferView — lectura del último frame fuera de Console (screenshot)
+//   [+] write_at_scaled + Layout::scaled(): fuente 8×8 ampliada por un factor
+//       entero (modo presentación del terminal)
 //   [-] NO se eliminó ningún método ni constante existente en v0.8
 //
 #![allow(dead_code)]
 #![allow(clippy::missing_safety_doc)]
 
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.9.0";
+
 const LFB_PTR_ADDR: *const u32 = 0x9004 as *const u32;
 const WIDTH_ADDR:   *const u16 = 0x9008 as *const u16;
 const HEIGHT_ADDR:  *const u16 = 0x900A as *const u16;
@@ -120,6 +137,17 @@ impl Color {
     }
 }
 
+/// Color resultante de pintar `fg` con opacidad `alpha` sobre `bg`: 0 deja
+/// `bg`, 255 es `fg` exacto y el resto usa la LUT (ver `blend_pixel`).
+#[inline(always)]
+pub fn blend_alpha(fg: Color, bg: Color, alpha: u8) -> Color {
+    match alpha {
+        0   => bg,
+        255 => fg,
+        a   => fg.blend_fast(bg, a),
+    }
+}
+
 // ── DirtyRegion (mejora #2) ───────────────────────────────────────────────────
 #[derive(Clone, Copy)]
 pub struct DirtyRegion {
@@ -166,6 +194,12 @@ pub struct Layout {
     pub line_h:    usize,
     pub font_w:    usize,
     pub font_h:    usize,
+    /// Avance por carácter de la fuente normal (glifo + separación)
+    pub char_w:    usize,
+    /// Avance por carácter de la fuente compacta 6×8
+    pub small_w:   usize,
+    /// Factor entero de la fuente normal (1 salvo en Layout::scaled)
+    pub text_scale: usize,
 }
 
 impl Layout {
@@ -195,9 +229,32 @@ impl Layout {
             right_x: col_div + pad + 4,
             line_h:  font_h + font_h / 2,
             font_w, font_h,
+            char_w:  crate::graphics::render::font::FONT_8X8_ADVANCE,
+            small_w: crate::graphics::render::font::FONT_6X8_ADVANCE,
+            text_scale: 1,
+        }
+    }
+
+    /// Misma pantalla con la fuente normal ampliada `k` veces: las medidas
+    /// de texto (font_w/h, char_w, line_h) escalan; el resto no cambia.
+    /// El texto se dibuja con Console::write_at_scaled(.., text_scale).
+    pub fn scaled(&self, k: usize) -> Layout {
+        let k = k.max(1);
+        Layout {
+            font_w: self.font_w * k,
+            font_h: self.font_h * k,
+            char_w: self.char_w * k,
+            line_h: self.line_h * k,
+            text_scale: self.text_scale * k,
+            ..*self
         }
     }
 
+    /// Ancho en píxeles de `n` caracteres con la fuente normal.
+    pub fn text_w(&self, n: usize)       -> usize { n * self.char_w }
+    /// Ancho en píxeles de `n` caracteres con la fuente compacta.
+    pub fn small_text_w(&self, n: usize) -> usize { n * self.small_w }
+
     pub fn left_w(&self)        -> usize { self.col_div.saturating_sub(self.pad * 2) }
     pub fn right_w(&self)       -> usize { self.fw.saturating_sub(self.right_x + self.pad) }
     pub fn content_h(&self)     -> usize { self.bottom_y.saturating_sub(self.content_y) }
@@ -223,25 +280,64 @@ pub struct Framebuffer {
     bpp:        u8,
     back_pitch: usize,
     pub dirty:  DirtyRegion,
+    cursor_shape: CursorShape,
+    last_cursor:  (usize, usize, usize, usize),
+}
+
+/// Dirección física y tamaño en bytes del framebuffer que dejó stage2
+/// (0 si no hay modo VESA). Lo usa `arch::paging` antes de crear la consola.
+pub fn boot_lfb() -> (u64, usize) {
+    let lfb = unsafe { core::ptr::read_volatile(LFB_PTR_ADDR) } as u64;
+    let (_, h, pitch, _) = boot_mode();
+    (lfb, h * pitch)
+}
+
+/// Modo de vídeo que dejó el bootloader: (ancho, alto, pitch del LFB, bpp).
+/// Sin información válida se asume 1024×768×24.
+pub(crate) fn boot_mode() -> (usize, usize, usize, u8) {
+    unsafe {
+        let bpp_raw = core::ptr::read_volatile(BPP_ADDR);
+        let w_raw   = core::ptr::read_volatile(WIDTH_ADDR)  as usize;
+        let h_raw   = core::ptr::read_volatile(HEIGHT_ADDR) as usize;
+        let p_raw   = core::ptr::read_volatile(PITCH_ADDR)  as usize;
+
+        if w_raw == 0 || h_raw == 0 {
+            (1024, 768, 3072, 24u8)
+        } else {
+            let bpp = if bpp_raw < 15 { 24 } else { bpp_raw };
+            let bpp_b = (bpp as usize).div_ceil(8);
+            let p = if p_raw == 0 { w_raw * bpp_b } else { p_raw };
+            (w_raw, h_raw, p, bpp)
+        }
+    }
+}
+
+/// Lectura del back-buffer sin pasar por Console, para código que no lo
+/// tiene a mano (el comando `screenshot` corre dentro del dispatcher).
+pub struct BackBufferView {
+    pub width:  usize,
+    pub height: usize,
+}
+
+impl BackBufferView {
+    pub fn current() -> Self {
+        let (width, height, _, _) = boot_mode();
+        BackBufferView { width, height }
+    }
+
+    /// Píxel 0x00RRGGBB del último frame dibujado; negro fuera de rango.
+    pub fn pixel(&self, x: usize, y: usize) -> u32 {
+        if x >= self.width || y >= self.height { return 0; }
+        let off = ((y * self.width + x) * 4) as u64;
+        unsafe { core::ptr::read_volatile((BACKBUF_ADDR + off) as *const u32) }
+    }
 }
 
 impl Framebuffer {
     pub fn new() -> Self {
         unsafe {
-            let bpp_raw = core::ptr::read_volatile(BPP_ADDR);
-            let w_raw   = core::ptr::read_volatile(WIDTH_ADDR)  as usize;
-            let h_raw   = core::ptr::read_volatile(HEIGHT_ADDR) as usize;
-            let p_raw   = core::ptr::read_volatile(PITCH_ADDR)  as usize;
-            let lfb     = core::ptr::read_volatile(LFB_PTR_ADDR) as u64;
-
-            let (w, h, lfb_pitch, bpp) = if w_raw == 0 || h_raw == 0 {
-                (1024, 768, 3072, 24u8)
-            } else {
-                let bpp = if bpp_raw < 15 { 24 } else { bpp_raw };
-                let bpp_b = (bpp as usize + 7) / 8;
-                let p = if p_raw == 0 { w_raw * bpp_b } else { p_raw };
-                (w_raw, h_raw, p, bpp)
-            };
+            let lfb = core::ptr::read_volatile(LFB_PTR_ADDR) as u64;
+            let (w, h, lfb_pitch, bpp) = boot_mode();
 
             let back_pitch = w * 4;
 
@@ -255,6 +351,8 @@ impl Framebuffer {
                 width: w, height: h,
                 lfb_pitch, bpp, back_pitch,
                 dirty: DirtyRegion::clean(),
+                cursor_shape: CursorShape::Arrow,
+                last_cursor:  (0, 0, 0, 0),
             }
         }
     }
@@ -277,6 +375,43 @@ impl Framebuffer {
         for i in 0..count { core::ptr::write_volatile(dst.add(i), val); }
     }
 
+    // ── rep stosq — dos píxeles por escritura ─────────────────────────────────
+    #[cfg(target_arch = "x86_64")]
+    #[inline]
+    unsafe fn fast_fill_u64(dst: *mut u64, val: u64, count: usize) {
+        core::arch::asm!(
+            "cld", "rep stosq",
+            inout("rdi") dst   => _,
+            inout("rcx") count => _,
+            in("rax")    val,
+            options(nostack)
+        );
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    #[inline]
+    unsafe fn fast_fill_u64(dst: *mut u64, val: u64, count: usize) {
+        for i in 0..count { core::ptr::write_volatile(dst.add(i), val); }
+    }
+
+    /// Rellena `count` píxeles de una fila: el píxel inicial no alineado a
+    /// 8 bytes y el píxel final impar van con escrituras de 32 bits; el resto
+    /// de a pares con `rep stosq`.
+    #[inline]
+    unsafe fn fill_row_u32(dst: *mut u32, val: u32, count: usize) {
+        if count < 4 { Self::fast_fill_u32(dst, val, count); return; }
+        let mut p = dst;
+        let mut n = count;
+        if (p as usize) & 7 != 0 {
+            core::ptr::write_volatile(p, val);
+            p = p.add(1); n -= 1;
+        }
+        let pair = ((val as u64) << 32) | val as u64;
+        Self::fast_fill_u64(p as *mut u64, pair, n / 2);
+        if n & 1 != 0 {
+            core::ptr::write_volatile(p.add(n - 1), val);
+        }
+    }
+
     // ── rep movsd ─────────────────────────────────────────────────────────────
     #[cfg(target_arch = "x86_64")]
     #[inline]
@@ -391,7 +526,7 @@ impl Framebuffer {
     pub fn clear(&self, color: Color) {
         let val   = color.0;
         let total = self.width * self.height;
-        unsafe { Self::fast_fill_u32(self.backbuf as *mut u32, val, total); }
+        unsafe { Self::fill_row_u32(self.backbuf as *mut u32, val, total); }
     }
 
     pub fn fill_rect(&mut self, sx: usize, sy: usize, w: usize, h: usize, c: Color) {
@@ -403,8 +538,10 @@ impl Framebuffer {
         let val = c.0;
         unsafe {
             for y in sy..ey {
+                // Cada fila se recalcula con back_pitch: la alineación de
+                // su primer píxel puede variar entre filas.
                 let row = (self.backbuf + (y * self.back_pitch + sx * 4) as u64) as *mut u32;
-                Self::fast_fill_u32(row, val, rw);
+                Self::fill_row_u32(row, val, rw);
             }
         }
         self.dirty.mark(sx, sy, ex - sx, ey - sy);
@@ -479,6 +616,7 @@ impl Framebuffer {
         if alpha == 255 { self.fill_rect(sx, sy, w, h, color); return; }
         let ex = (sx + w).min(self.width);
         let ey = (sy + h).min(self.height);
+        if sx >= ex || sy >= ey { return; }
         for y in sy..ey {
             for x in sx..ex {
                 unsafe {
@@ -491,6 +629,32 @@ impl Framebuffer {
         self.dirty.mark(sx, sy, ex - sx, ey - sy);
     }
 
+    /// Mezcla `color` con el píxel actual del back-buffer. Fuera de pantalla
+    /// no hace nada; no marca dirty (lo hace quien llama, por bloque).
+    #[inline(always)]
+    pub fn blend_pixel(&mut self, x: usize, y: usize, color: Color, alpha: u8) {
+        if x >= self.width || y >= self.height || alpha == 0 { return; }
+        unsafe {
+            let bg = self.read_back_pixel(x, y);
+            self.draw_pixel(x, y, blend_alpha(color, bg, alpha));
+        }
+    }
+
+    /// Borde translúcido de 1 px. Los cuatro lados no se solapan, así que
+    /// cada píxel (esquinas incluidas) se mezcla una sola vez. Para más
+    /// grosor se anidan anillos: tampoco se solapan entre sí.
+    pub fn draw_rect_alpha(&mut self, sx: usize, sy: usize, w: usize, h: usize,
+                           c: Color, alpha: u8) {
+        if w == 0 || h == 0 { return; }
+        self.fill_rect_alpha_fast(sx, sy, w, 1, c, alpha);
+        if h == 1 { return; }
+        self.fill_rect_alpha_fast(sx, sy + h - 1, w, 1, c, alpha);
+        self.fill_rect_alpha_fast(sx, sy + 1, 1, h - 2, c, alpha);
+        if w > 1 {
+            self.fill_rect_alpha_fast(sx + w - 1, sy + 1, 1, h - 2, c, alpha);
+        }
+    }
+
     /// Bresenham (mejora #10)
     pub fn draw_line(&mut self, mut x0: i32, mut y0: i32, x1: i32, y1: i32, c: Color) {
         let dx  =  (x1 - x0).abs();
@@ -567,50 +731,229 @@ impl Framebuffer {
         self.dirty.mark(dx, dy, sw, sh);
     }
 
+    /// Copia un rectángulo de píxeles 0x00RRGGBB (mismo formato que `Color.0`)
+    /// al back-buffer. `x`/`y` pueden ser negativos: se recorta por los cuatro
+    /// bordes. Si `pixels` tiene menos de `w*h` elementos no se dibuja nada.
+    pub fn blit_rgb(&mut self, x: i32, y: i32, w: usize, h: usize, pixels: &[u32]) {
+        if self.backbuf == 0 || pixels.len() < w * h { return; }
+        let Some(r) = clip_rect(x, y, w, h, self.width, self.height) else { return };
+        for row in 0..r.h {
+            let src = &pixels[(r.src_y + row) * w + r.src_x..][..r.w];
+            let dst = (self.backbuf + ((r.dst_y + row) * self.back_pitch + r.dst_x * 4) as u64) as *mut u32;
+            unsafe { Self::fast_copy_u32(dst, src.as_ptr(), r.w); }
+        }
+        self.dirty.mark(r.dst_x, r.dst_y, r.w, r.h);
+    }
+
+    /// Como `blit_rgb` pero cada píxel de origen ocupa `scale`×`scale` en
+    /// pantalla (escalado entero, vecino más próximo). `scale` 0 se trata como 1.
+    pub fn blit_rgb_scaled(&mut self, x: i32, y: i32, w: usize, h: usize,
+                           pixels: &[u32], scale: usize) {
+        let scale = scale.max(1);
+        if scale == 1 { self.blit_rgb(x, y, w, h, pixels); return; }
+        if self.backbuf == 0 || pixels.len() < w * h { return; }
+        let Some(r) = clip_rect(x, y, w * scale, h * scale, self.width, self.height) else { return };
+        for row in 0..r.h {
+            let src_row = &pixels[((r.src_y + row) / scale) * w..][..w];
+            let dst = (self.backbuf + ((r.dst_y + row) * self.back_pitch + r.dst_x * 4) as u64) as *mut u32;
+            // Tramos de `scale` píxeles iguales; el primero y el último pueden
+            // quedar cortados por el recorte.
+            let mut col = 0usize;
+            while col < r.w {
+                let sx  = r.src_x + col;
+                let run = (scale - sx % scale).min(r.w - col);
+                unsafe { Self::fill_row_u32(dst.add(col), src_row[sx / scale], run); }
+                col += run;
+            }
+        }
+        self.dirty.mark(r.dst_x, r.dst_y, r.w, r.h);
+    }
+
     // ── Cursor del mouse ──────────────────────────────────────────────────────
-    const CURSOR_W: usize = 12;
-    const CURSOR_H: usize = 12;
-    const ARROW: &'static [u16] = &[
-        0b1000_0000_0000_0000,
-        0b1100_0000_0000_0000,
-        0b1110_0000_0000_0000,
-        0b1111_0000_0000_0000,
-        0b1111_1000_0000_0000,
-        0b1111_1100_0000_0000,
-        0b1111_1110_0000_0000,
-        0b1111_0000_0000_0000,
-        0b1101_1000_0000_0000,
-        0b1000_1100_0000_0000,
-        0b0000_0110_0000_0000,
-        0b0000_0000_0000_0000,
-    ];
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) { self.cursor_shape = shape; }
+    pub fn cursor_shape(&self) -> CursorShape { self.cursor_shape }
 
+    /// Dibuja el cursor con la forma actual: contorno negro de 1 px y relleno
+    /// blanco. Marca sucio tanto el rect nuevo como el del frame anterior para
+    /// que un cambio de forma/tamaño no deje restos en pantalla.
     pub fn draw_cursor(&mut self, mx: i32, my: i32) {
-        let cx = mx.max(0) as usize;
-        let cy = my.max(0) as usize;
-        for (row, &mask) in Self::ARROW.iter().enumerate() {
-            for col in 0..Self::CURSOR_W {
-                if (mask >> (15 - col)) & 1 != 0 {
-                    unsafe {
-                        self.draw_pixel(
-                            (cx + col + 1).min(self.width.saturating_sub(1)),
-                            (cy + row + 1).min(self.height.saturating_sub(1)),
-                            Color::new(0, 0, 0),
-                        );
-                    }
-                }
+        let bmp = self.cursor_shape.bitmap();
+        let x0  = mx - bmp.hot_x as i32;
+        let y0  = my - bmp.hot_y as i32;
+        let set = |col: i32, row: i32| -> bool {
+            col >= 0 && row >= 0 && (col as usize) < bmp.w && (row as usize) < bmp.h
+                && (bmp.rows[row as usize] >> (15 - col)) & 1 != 0
+        };
+
+        // Contorno: píxeles vacíos con algún vecino del bitmap
+        for row in -1..=bmp.h as i32 {
+            for col in -1..=bmp.w as i32 {
+                if set(col, row) { continue; }
+                let edge = set(col - 1, row) || set(col + 1, row)
+                        || set(col, row - 1) || set(col, row + 1);
+                if edge { self.cursor_px(x0 + col, y0 + row, Color::new(10, 10, 10)); }
             }
         }
-        for (row, &mask) in Self::ARROW.iter().enumerate() {
-            for col in 0..Self::CURSOR_W {
-                if (mask >> (15 - col)) & 1 != 0 {
-                    let c = if col == 0 || row == 0 { Color::new(20, 20, 20) } else { Color::WHITE };
-                    unsafe { self.draw_pixel(cx + col, cy + row, c); }
-                }
+        for row in 0..bmp.h as i32 {
+            for col in 0..bmp.w as i32 {
+                if set(col, row) { self.cursor_px(x0 + col, y0 + row, Color::WHITE); }
             }
         }
-        unsafe { self.draw_pixel(cx, cy, Color::new(10, 10, 10)); }
-        self.dirty.mark(cx, cy, Self::CURSOR_W + 1, Self::CURSOR_H + 1);
+
+        let (px, py, pw, ph) = self.last_cursor;
+        if pw != 0 { self.dirty.mark(px, py, pw, ph); }
+        let rx = (x0 - 1).max(0) as usize;
+        let ry = (y0 - 1).max(0) as usize;
+        let rw = ((x0 + bmp.w as i32 + 1).max(0) as usize).saturating_sub(rx);
+        let rh = ((y0 + bmp.h as i32 + 1).max(0) as usize).saturating_sub(ry);
+        self.dirty.mark(rx, ry, rw, rh);
+        self.last_cursor = (rx, ry, rw, rh);
+    }
+
+    #[inline(always)]
+    fn cursor_px(&mut self, x: i32, y: i32, c: Color) {
+        if x < 0 || y < 0 { return; }
+        unsafe { self.draw_pixel(x as usize, y as usize, c); }
+    }
+}
+
+// ── Recorte de rectángulos (blit) ─────────────────────────────────────────────
+
+/// Parte visible de un rectángulo `w`×`h` colocado en (`x`, `y`) sobre una
+/// pantalla `scr_w`×`scr_h`: posición en pantalla, desplazamiento dentro del
+/// origen y tamaño recortado.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClipRect {
+    pub dst_x: usize,
+    pub dst_y: usize,
+    pub src_x: usize,
+    pub src_y: usize,
+    pub w:     usize,
+    pub h:     usize,
+}
+
+/// Recorta contra los cuatro bordes. None si no queda nada visible.
+pub fn clip_rect(x: i32, y: i32, w: usize, h: usize, scr_w: usize, scr_h: usize) -> Option<ClipRect> {
+    let src_x = if x < 0 { x.unsigned_abs() as usize } else { 0 };
+    let src_y = if y < 0 { y.unsigned_abs() as usize } else { 0 };
+    let dst_x = x.max(0) as usize;
+    let dst_y = y.max(0) as usize;
+    if src_x >= w || src_y >= h || dst_x >= scr_w || dst_y >= scr_h { return None; }
+    let cw = (w - src_x).min(scr_w - dst_x);
+    let ch = (h - src_y).min(scr_h - dst_y);
+    Some(ClipRect { dst_x, dst_y, src_x, src_y, w: cw, h: ch })
+}
+
+// ── Formas del cursor del mouse ───────────────────────────────────────────────
+//
+// Bitmaps de 1 bpp, una fila por u16 (bit 15 = columna 0). `hot_*` es el píxel
+// que coincide con la posición real del ratón.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorShape { Arrow, Hand, Cross, IBeam, ResizeV }
+
+pub struct CursorBitmap {
+    pub w:     usize,
+    pub h:     usize,
+    pub hot_x: usize,
+    pub hot_y: usize,
+    pub rows:  &'static [u16],
+}
+
+const CURSOR_ARROW: CursorBitmap = CursorBitmap { w: 12, h: 12, hot_x: 0, hot_y: 0, rows: &[
+    0b1000_0000_0000_0000,
+    0b1100_0000_0000_0000,
+    0b1110_0000_0000_0000,
+    0b1111_0000_0000_0000,
+    0b1111_1000_0000_0000,
+    0b1111_1100_0000_0000,
+    0b1111_1110_0000_0000,
+    0b1111_0000_0000_0000,
+    0b1101_1000_0000_0000,
+    0b1000_1100_0000_0000,
+    0b0000_0110_0000_0000,
+    0b0000_0000_0000_0000,
+]};
+
+const CURSOR_HAND: CursorBitmap = CursorBitmap { w: 13, h: 14, hot_x: 4, hot_y: 0, rows: &[
+    0b0000_1100_0000_0000,
+    0b0000_1100_0000_0000,
+    0b0000_1100_0000_0000,
+    0b0000_1100_0000_0000,
+    0b0000_1111_1000_0000,
+    0b0000_1111_1110_0000,
+    0b0110_1111_1111_0000,
+    0b0111_1111_1111_0000,
+    0b0011_1111_1111_0000,
+    0b0011_1111_1111_0000,
+    0b0001_1111_1111_0000,
+    0b0001_1111_1110_0000,
+    0b0000_1111_1110_0000,
+    0b0000_1111_1110_0000,
+]};
+
+const CURSOR_CROSS: CursorBitmap = CursorBitmap { w: 15, h: 15, hot_x: 7, hot_y: 7, rows: &[
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0000_0000_0000,
+    0b0000_0000_0000_0000,
+    0b1111_1000_0011_1110,
+    0b0000_0000_0000_0000,
+    0b0000_0000_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+    0b0000_0001_0000_0000,
+]};
+
+const CURSOR_IBEAM: CursorBitmap = CursorBitmap { w: 7, h: 14, hot_x: 3, hot_y: 7, rows: &[
+    0b1110_1110_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b1110_1110_0000_0000,
+]};
+
+// Doble flecha ↕ (scrollbar arrastrable); hotspot en el centro del tallo
+const CURSOR_RESIZE_V: CursorBitmap = CursorBitmap { w: 7, h: 15, hot_x: 3, hot_y: 7, rows: &[
+    0b0001_0000_0000_0000,
+    0b0011_1000_0000_0000,
+    0b0111_1100_0000_0000,
+    0b1111_1110_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b0001_0000_0000_0000,
+    0b1111_1110_0000_0000,
+    0b0111_1100_0000_0000,
+    0b0011_1000_0000_0000,
+    0b0001_0000_0000_0000,
+]};
+
+impl CursorShape {
+    pub fn bitmap(self) -> &'static CursorBitmap {
+        match self {
+            CursorShape::Arrow => &CURSOR_ARROW,
+            CursorShape::Hand  => &CURSOR_HAND,
+            CursorShape::Cross => &CURSOR_CROSS,
+            CursorShape::IBeam => &CURSOR_IBEAM,
+            CursorShape::ResizeV => &CURSOR_RESIZE_V,
+        }
     }
 }
 
@@ -645,6 +988,7 @@ impl Console {
     pub fn present(&mut self)                        { self.fb.present(); }
     pub fn present_full(&mut self)                   { self.fb.present_full(); }
     pub fn draw_cursor(&mut self, mx: i32, my: i32) { self.fb.draw_cursor(mx, my); }
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) { self.fb.set_cursor_shape(shape); }
 
     pub fn clear(&mut self, color: Color) {
         self.bg_color = color;
@@ -698,6 +1042,10 @@ impl Console {
         self.fb.dirty.mark(x, y, ex - x, ey - y);
     }
 
+    pub fn draw_rect_alpha(&mut self, x: usize, y: usize, w: usize, h: usize,
+                           c: Color, alpha: u8)
+        { self.fb.draw_rect_alpha(x,y,w,h,c,alpha); }
+
     pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, c: Color)
         { self.fb.draw_line(x0,y0,x1,y1,c); }
     pub fn fill_circle(&mut self, cx: i32, cy: i32, r: i32, c: Color)
@@ -708,6 +1056,11 @@ impl Console {
     pub fn blit_sprite(&mut self, dx: usize, dy: usize, sw: usize, sh: usize,
                         data: &[Color], key: Color)
         { self.fb.blit_sprite(dx,dy,sw,sh,data,key); }
+    pub fn blit_rgb(&mut self, x: i32, y: i32, w: usize, h: usize, pixels: &[u32])
+        { self.fb.blit_rgb(x,y,w,h,pixels); }
+    pub fn blit_rgb_scaled(&mut self, x: i32, y: i32, w: usize, h: usize,
+                           pixels: &[u32], scale: usize)
+        { self.fb.blit_rgb_scaled(x,y,w,h,pixels,scale); }
 
     fn draw_char(&mut self, x: usize, y: usize, ch: char, fg: Color, bg: Color) {
         let a = ch as usize;
@@ -724,6 +1077,21 @@ impl Console {
         }
     }
 
+    /// Glifo translúcido: solo los píxeles encendidos, mezclados con el fondo.
+    fn draw_char_alpha(&mut self, x: usize, y: usize, ch: char, fg: Color, alpha: u8) {
+        let a = ch as usize;
+        if !(32..=127).contains(&a) { return; }
+        let glyph = crate::graphics::render::font::FONT_8X8[a - 32];
+        for (row, &byte) in glyph.iter().enumerate() {
+            if byte == 0 { continue; }
+            for col in 0..8usize {
+                if byte & (1u8 << col) != 0 {
+                    self.fb.blend_pixel(x + col, y + row, fg, alpha);
+                }
+            }
+        }
+    }
+
     pub fn draw_char_tall(&mut self, x: usize, y: usize, ch: char, fg: Color, bg: Color) {
         let a = ch as usize;
         if a < 32 || a > 127 { return; }
@@ -774,6 +1142,71 @@ impl Console {
         self.cursor_x = ox; self.cursor_y = oy; self.margin_x = om;
     }
 
+    /// Como write_at, pero el texto se mezcla con lo que ya hay en el
+    /// back-buffer y el fondo del glifo no se pinta. Una sola línea: lo que
+    /// pase del borde derecho se recorta.
+    pub fn write_at_alpha(&mut self, s: &str, x: usize, y: usize, fg: Color, alpha: u8) {
+        if alpha == 0 || x >= self.fb.width || y >= self.fb.height { return; }
+        let fw = self.font_w + 1;
+        let mut cx = x;
+        for ch in s.chars() {
+            if cx >= self.fb.width { break; }
+            self.draw_char_alpha(cx, y, ch, fg, alpha);
+            cx += fw;
+        }
+        let w = (cx - x).min(self.fb.width - x);
+        let h = 8usize.min(self.fb.height - y);
+        self.fb.dirty.mark(x, y, w, h);
+    }
+
+    /// Texto con la fuente compacta 6×8 (fondo transparente). Una sola
+    /// línea, recortada al borde derecho.
+    pub fn write_at_small(&mut self, s: &str, x: usize, y: usize, color: Color) {
+        use crate::graphics::render::font::{glyph_6x8, FONT_6X8_ADVANCE};
+        if x >= self.fb.width || y >= self.fb.height { return; }
+        let mut cx = x;
+        for ch in s.chars() {
+            if cx >= self.fb.width { break; }
+            if let Some(glyph) = glyph_6x8(ch) {
+                for (row, &byte) in glyph.iter().enumerate() {
+                    for col in 0..5usize {
+                        if byte & (1u8 << col) != 0 {
+                            unsafe { self.fb.draw_pixel(cx + col, y + row, color); }
+                        }
+                    }
+                }
+            }
+            cx += FONT_6X8_ADVANCE;
+        }
+        let w = (cx - x).min(self.fb.width - x);
+        let h = 8usize.min(self.fb.height - y);
+        self.fb.dirty.mark(x, y, w, h);
+    }
+
+    /// Fuente 8×8 ampliada `scale` veces: cada píxel del glifo es un bloque
+    /// scale×scale y el fondo no se pinta. Una sola línea, recortada al borde
+    /// derecho. Con scale <= 1 es write_at.
+    pub fn write_at_scaled(&mut self, s: &str, x: usize, y: usize, color: Color, scale: usize) {
+        use crate::graphics::render::font::{FONT_8X8, FONT_8X8_ADVANCE};
+        if scale <= 1 { self.write_at(s, x, y, color); return; }
+        if x >= self.fb.width || y >= self.fb.height { return; }
+        let mut cx = x;
+        for ch in s.chars() {
+            if cx >= self.fb.width { break; }
+            let a = ch as usize;
+            if (32..=127).contains(&a) {
+                for (row, &byte) in FONT_8X8[a - 32].iter().enumerate() {
+                    for col in 0..8usize {
+                        if byte & (1u8 << col) != 0 {
+                            self.fb.fill_rect(cx + col * scale, y + row * scale, scale, scale, color);
+                        }
+                    }
+                }
+            }
+            cx += FONT_8X8_ADVANCE * scale;
+        }
+    }
+
     pub fn write_at_tall(&mut self, s: &str, x: usize, y: usize, color: Color) {
         let bg  = self.bg_color;
         let mut cx = x;
@@ -789,4 +1222,113 @@ impl Console {
         self.write_at(s, x, y, fg);
         self.bg_color = old;
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(dst_x: usize, dst_y: usize, src_x: usize, src_y: usize, w: usize, h: usize) -> Option<ClipRect> {
+        Some(ClipRect { dst_x, dst_y, src_x, src_y, w, h })
+    }
+
+    #[test]
+    fn clip_rect_inside_is_untouched() {
+        assert_eq!(clip_rect(10, 20, 30, 40, 640, 480), clip(10, 20, 0, 0, 30, 40));
+        assert_eq!(clip_rect(0, 0, 640, 480, 640, 480), clip(0, 0, 0, 0, 640, 480));
+    }
+
+    #[test]
+    fn clip_rect_negative_origin() {
+        // Lo que queda fuera por arriba/izquierda se salta en el origen
+        assert_eq!(clip_rect(-5, 0, 20, 10, 640, 480), clip(0, 0, 5, 0, 15, 10));
+        assert_eq!(clip_rect(0, -7, 20, 10, 640, 480), clip(0, 0, 0, 7, 20, 3));
+        assert_eq!(clip_rect(-5, -7, 20, 10, 640, 480), clip(0, 0, 5, 7, 15, 3));
+        // Totalmente fuera
+        assert_eq!(clip_rect(-20, 0, 20, 10, 640, 480), None);
+        assert_eq!(clip_rect(0, -10, 20, 10, 640, 480), None);
+        assert_eq!(clip_rect(i32::MIN, i32::MIN, 20, 10, 640, 480), None);
+    }
+
+    #[test]
+    fn clip_rect_past_right_and_bottom() {
+        assert_eq!(clip_rect(630, 475, 20, 10, 640, 480), clip(630, 475, 0, 0, 10, 5));
+        assert_eq!(clip_rect(640, 0, 20, 10, 640, 480), None);
+        assert_eq!(clip_rect(0, 480, 20, 10, 640, 480), None);
+        assert_eq!(clip_rect(i32::MAX, 0, 20, 10, 640, 480), None);
+    }
+
+    #[test]
+    fn clip_rect_larger_than_screen() {
+        assert_eq!(clip_rect(0, 0, 2000, 1000, 640, 480), clip(0, 0, 0, 0, 640, 480));
+        assert_eq!(clip_rect(-100, -50, 2000, 1000, 640, 480), clip(0, 0, 100, 50, 640, 480));
+        assert_eq!(clip_rect(100, 50, 2000, 1000, 640, 480), clip(100, 50, 0, 0, 540, 430));
+    }
+
+    #[test]
+    fn clip_rect_empty_inputs() {
+        assert_eq!(clip_rect(0, 0, 0, 10, 640, 480), None);
+        assert_eq!(clip_rect(0, 0, 10, 0, 640, 480), None);
+        assert_eq!(clip_rect(0, 0, 10, 10, 0, 0), None);
+    }
+
+    #[test]
+    fn blend_alpha_extremes_and_half() {
+        init_alpha_lut();
+        let fg = Color(0xFF0000);
+        let bg = Color(0x0000FF);
+        assert_eq!(blend_alpha(fg, bg, 0), bg);
+        assert_eq!(blend_alpha(fg, bg, 255), fg);
+        // 128/255 del frente y 127/255 del fondo, redondeando hacia abajo
+        assert_eq!(blend_alpha(fg, bg, 128), Color(0x80007F));
+        assert_eq!(blend_alpha(Color::WHITE, Color::BLACK, 128), Color(0x808080));
+    }
+
+    #[test]
+    fn blend_alpha_matches_exact_blend() {
+        init_alpha_lut();
+        let pairs = [(Color(0xFFFFFF), Color(0x000000)), (Color(0x10E080), Color(0xC03020))];
+        for (fg, bg) in pairs {
+            for a in [1u8, 64, 128, 200, 254] {
+                let lut   = blend_alpha(fg, bg, a);
+                let exact = fg.blend(bg, a);
+                // Dos divisiones truncadas frente a una: como mucho 1 de diferencia
+                for (l, e) in [(lut.r(), exact.r()), (lut.g(), exact.g()), (lut.b(), exact.b())] {
+                    assert!(l.abs_diff(e) <= 1, "alpha {}: {:#x} vs {:#x}", a, lut.0, exact.0);
+                }
+            }
+        }
+    }
+
+    const SHAPES: [CursorShape; 5] =
+        [CursorShape::Arrow, CursorShape::Hand, CursorShape::Cross, CursorShape::IBeam, CursorShape::ResizeV];
+
+    #[test]
+    fn every_cursor_shape_has_a_consistent_bitmap() {
+        for shape in SHAPES {
+            let b = shape.bitmap();
+            assert_eq!(b.rows.len(), b.h, "{:?}", shape);
+            assert!(b.w <= 16, "{:?}", shape);
+            assert!(b.hot_x < b.w && b.hot_y < b.h, "{:?}", shape);
+            // Nada a la derecha de la columna w-1 (bit 15 = columna 0)
+            let outside = if b.w == 16 { 0 } else { 0xFFFFu16 >> b.w };
+            for (i, &row) in b.rows.iter().enumerate() {
+                assert_eq!(row & outside, 0, "{:?} fila {}", shape, i);
+            }
+            assert!(b.rows.iter().any(|&r| r != 0), "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn cross_cursor_is_symmetric_around_the_hotspot() {
+        let b = CursorShape::Cross.bitmap();
+        let px = |x: usize, y: usize| (b.rows[y] >> (15 - x)) & 1 != 0;
+        for y in 0..b.h {
+            for x in 0..b.w {
+                // Espejo horizontal, vertical y transpuesta (brazos iguales)
+                assert_eq!(px(x, y), px(2 * b.hot_x - x, y), "({}, {})", x, y);
+                assert_eq!(px(x, y), px(x, 2 * b.hot_y - y), "({}, {})", x, y);
+                assert_eq!(px(x, y), px(y, x), "({}, {})", x, y);
+            }
+        }
+    }
+}
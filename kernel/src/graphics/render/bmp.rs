@@ -0,0 +1,315 @@
+// graphics/render/bmp.rs — PORTIX Kernel
+// Codificador BMP de 24 bpp sin compresión (BITMAPINFOHEADER). El archivo
+// se genera por rangos de bytes para poder volcarlo clúster a clúster sin
+// tener la imagen entera en memoria (ver Fat32Volume::write_file_with).
+//
+// Formato: cabecera de 54 bytes y filas de abajo arriba, cada una en orden
+// B, G, R y rellenada con ceros hasta múltiplo de 4 bytes.
+//
+// Decodificador (comando `img`): `parse_header` lee las cabeceras de
+// cualquier BMP con BITMAPINFOHEADER o posterior; solo se pueden leer
+// píxeles (`pixel_at`) de imágenes sin compresión de 24 o 32 bpp. `scale_src`
+// da el píxel de origen de cada píxel de destino al reescalar.
+
+/// BITMAPFILEHEADER (14) + BITMAPINFOHEADER (40).
+pub const HEADER_LEN: usize = 54;
+
+/// Bytes por fila, incluido el relleno a múltiplo de 4.
+pub const fn row_size(w: usize) -> usize { (w * 3 + 3) & !3 }
+
+pub const fn file_size(w: usize, h: usize) -> usize { HEADER_LEN + row_size(w) * h }
+
+/// Cabecera completa para una imagen `w`×`h` de 24 bpp.
+pub fn header(w: usize, h: usize) -> [u8; HEADER_LEN] {
+    let mut hd = [0u8; HEADER_LEN];
+    let put = |hd: &mut [u8; HEADER_LEN], off: usize, v: u32| {
+        hd[off..off + 4].copy_from_slice(&v.to_le_bytes());
+    };
+    hd[0] = b'B'; hd[1] = b'M';
+    put(&mut hd, 2,  file_size(w, h) as u32);
+    put(&mut hd, 10, HEADER_LEN as u32);          // offset de los píxeles
+    put(&mut hd, 14, 40);                         // tamaño de BITMAPINFOHEADER
+    put(&mut hd, 18, w as u32);
+    put(&mut hd, 22, h as u32);                   // positivo = filas bottom-up
+    hd[26] = 1;                                   // planos
+    hd[28] = 24;                                  // bpp
+    put(&mut hd, 34, (row_size(w) * h) as u32);   // tamaño de los datos
+    put(&mut hd, 38, 2835);                       // 72 ppp en píxeles/metro
+    put(&mut hd, 42, 2835);
+    hd
+}
+
+/// Rellena `out` con los bytes `off..off + out.len()` del archivo BMP de una
+/// imagen `w`×`h` cuyos píxeles (0x00RRGGBB, y = 0 arriba) da `pixel`.
+pub fn encode_range(
+    w: usize, h: usize, off: usize, out: &mut [u8],
+    pixel: impl Fn(usize, usize) -> u32,
+) {
+    let hd   = header(w, h);
+    let rs   = row_size(w);
+    let mut i = 0;
+
+    while i < out.len() {
+        let pos = off + i;
+        if pos < HEADER_LEN {
+            out[i] = hd[pos];
+            i += 1;
+            continue;
+        }
+        let data = pos - HEADER_LEN;
+        let (row, col) = (data / rs, data % rs);
+        if row >= h { out[i] = 0; i += 1; continue; }
+        let y = h - 1 - row;
+
+        // Resto de la fila de una vez: píxeles en BGR y luego el relleno
+        let mut c = col;
+        while c < rs && i < out.len() {
+            out[i] = if c < w * 3 {
+                let px = pixel(c / 3, y);
+                (px >> (8 * (c % 3))) as u8
+            } else { 0 };
+            c += 1;
+            i += 1;
+        }
+    }
+}
+
+// ── Decodificador ─────────────────────────────────────────────────────────────
+
+/// Lado máximo aceptado al decodificar (evita productos que desborden).
+pub const MAX_DIM: usize = 16384;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BmpError {
+    /// Menos bytes que las cabeceras o que los datos que declaran.
+    Short,
+    /// No empieza por "BM".
+    BadMagic,
+    /// Cabecera DIB desconocida (OS/2 o anterior) o valores imposibles.
+    BadHeader,
+    /// RLE, bitfields, JPEG/PNG embebido...: campo biCompression.
+    Compressed(u32),
+    /// Profundidad distinta de 24/32 bpp.
+    UnsupportedBpp(u16),
+}
+
+impl BmpError {
+    pub fn msg(self) -> &'static [u8] {
+        match self {
+            BmpError::Short             => b"archivo truncado",
+            BmpError::BadMagic          => b"no es un BMP (falta la firma 'BM')",
+            BmpError::BadHeader         => b"cabecera BMP no valida o no soportada",
+            BmpError::Compressed(_)     => b"BMP comprimido (RLE/bitfields): no soportado",
+            BmpError::UnsupportedBpp(_) => b"profundidad no soportada (solo 24 o 32 bpp)",
+        }
+    }
+}
+
+/// Datos de las cabeceras de un BMP.
+#[derive(Clone, Copy, Debug)]
+pub struct BmpInfo {
+    pub width:       usize,
+    pub height:      usize,
+    pub bpp:         u16,
+    pub compression: u32,
+    /// Filas de arriba abajo (altura negativa en la cabecera).
+    pub top_down:    bool,
+    /// Offset de los píxeles desde el inicio del archivo.
+    pub data_off:    usize,
+    /// Tamaño total declarado en BITMAPFILEHEADER.
+    pub file_size:   u32,
+}
+
+impl BmpInfo {
+    /// Bytes por fila de la imagen de origen (múltiplo de 4).
+    pub fn stride(&self) -> usize { (self.width * self.bpp as usize).div_ceil(32) * 4 }
+
+    /// Nombre del método de compresión para `img info`.
+    pub fn compression_name(&self) -> &'static str {
+        match self.compression {
+            0 => "ninguna",
+            1 => "RLE8",
+            2 => "RLE4",
+            3 => "bitfields",
+            4 => "JPEG",
+            5 => "PNG",
+            6 => "alpha bitfields",
+            _ => "desconocida",
+        }
+    }
+
+    /// Ok si `pixel_at` puede leer esta imagen desde un archivo de `len` bytes.
+    pub fn check_decodable(&self, len: usize) -> Result<(), BmpError> {
+        if self.compression != 0 { return Err(BmpError::Compressed(self.compression)); }
+        if self.bpp != 24 && self.bpp != 32 { return Err(BmpError::UnsupportedBpp(self.bpp)); }
+        if self.data_off + self.stride() * self.height > len { return Err(BmpError::Short); }
+        Ok(())
+    }
+}
+
+fn le16(b: &[u8], off: usize) -> u16 { u16::from_le_bytes([b[off], b[off + 1]]) }
+fn le32(b: &[u8], off: usize) -> u32 { u32::from_le_bytes([b[off], b[off + 1], b[off + 2], b[off + 3]]) }
+
+/// Lee BITMAPFILEHEADER + cabecera DIB de los primeros bytes del archivo
+/// (basta con HEADER_LEN). No comprueba que los datos estén completos.
+pub fn parse_header(b: &[u8]) -> Result<BmpInfo, BmpError> {
+    if b.len() < 2 || &b[..2] != b"BM" { return Err(BmpError::BadMagic); }
+    if b.len() < HEADER_LEN { return Err(BmpError::Short); }
+    // 12 = BITMAPCOREHEADER (OS/2), con campos de 16 bits: no soportado
+    if le32(b, 14) < 40 { return Err(BmpError::BadHeader); }
+
+    let w = le32(b, 18) as i32;
+    let h = le32(b, 22) as i32;
+    let planes = le16(b, 26);
+    let width  = w.unsigned_abs() as usize;
+    let height = h.unsigned_abs() as usize;
+    if w <= 0 || h == 0 || planes != 1 || width > MAX_DIM || height > MAX_DIM {
+        return Err(BmpError::BadHeader);
+    }
+    let data_off = le32(b, 10) as usize;
+    if data_off < HEADER_LEN { return Err(BmpError::BadHeader); }
+
+    Ok(BmpInfo {
+        width, height,
+        bpp:         le16(b, 28),
+        compression: le32(b, 30),
+        top_down:    h < 0,
+        data_off,
+        file_size:   le32(b, 2),
+    })
+}
+
+/// Píxel (x, y) como 0x00RRGGBB, con y = 0 arriba. `data` es el archivo
+/// completo y la imagen debe haber pasado `check_decodable`.
+pub fn pixel_at(info: &BmpInfo, data: &[u8], x: usize, y: usize) -> u32 {
+    let row = if info.top_down { y } else { info.height - 1 - y };
+    let off = info.data_off + row * info.stride() + x * (info.bpp as usize / 8);
+    (data[off + 2] as u32) << 16 | (data[off + 1] as u32) << 8 | data[off] as u32
+}
+
+/// Cómo adaptar la imagen al tamaño de destino.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Conserva la relación de aspecto; franjas negras donde sobra.
+    Fit,
+    /// Conserva la relación de aspecto cubriendo todo; recorta lo que sobra.
+    Fill,
+    /// Deforma la imagen para ocupar exactamente el destino.
+    Stretch,
+}
+
+impl ScaleMode {
+    pub fn parse(s: &[u8]) -> Option<Self> {
+        match s {
+            b"fit" | b"ajustar"     => Some(ScaleMode::Fit),
+            b"fill" | b"rellenar"   => Some(ScaleMode::Fill),
+            b"stretch" | b"estirar" => Some(ScaleMode::Stretch),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ScaleMode::Fit     => "fit",
+            ScaleMode::Fill    => "fill",
+            ScaleMode::Stretch => "stretch",
+        }
+    }
+}
+
+/// Píxel de origen (vecino más próximo) que corresponde al píxel (dx, dy) de
+/// un destino `dw`×`dh` al escalar una imagen `sw`×`sh` con `mode`. None =
+/// franja de relleno (solo en Fit).
+pub fn scale_src(
+    mode: ScaleMode, sw: usize, sh: usize, dw: usize, dh: usize, dx: usize, dy: usize,
+) -> Option<(usize, usize)> {
+    // Tamaño de la imagen escalada (sc_w × sc_h) centrada en el destino
+    let (sc_w, sc_h) = match mode {
+        ScaleMode::Stretch => (dw, dh),
+        // sw/sh frente a dw/dh, en enteros: ¿manda el ancho o el alto?
+        ScaleMode::Fit  if sw * dh >= sh * dw => (dw, (sh * dw / sw).max(1)),
+        ScaleMode::Fit                        => ((sw * dh / sh).max(1), dh),
+        ScaleMode::Fill if sw * dh >= sh * dw => ((sw * dh / sh).max(1), dh),
+        ScaleMode::Fill                       => (dw, (sh * dw / sw).max(1)),
+    };
+    // Desplazamiento del destino dentro de la imagen escalada (negativo en
+    // Fit = franja; positivo en Fill = recorte)
+    let ox = sc_w as isize - dw as isize;
+    let oy = sc_h as isize - dh as isize;
+    let sx = dx as isize + ox / 2;
+    let sy = dy as isize + oy / 2;
+    if sx < 0 || sy < 0 || sx >= sc_w as isize || sy >= sc_h as isize { return None; }
+    Some((sx as usize * sw / sc_w, sy as usize * sh / sc_h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn row_padding() {
+        assert_eq!(row_size(1), 4);
+        assert_eq!(row_size(3), 12);
+        assert_eq!(row_size(4), 12);
+        assert_eq!(row_size(5), 16);
+        assert_eq!(row_size(1024), 3072);
+    }
+
+    #[test]
+    fn header_fields() {
+        let hd = header(3, 2);
+        assert_eq!(&hd[..2], b"BM");
+        // 3 px × 3 bytes = 9 → 12 con relleno; 2 filas
+        assert_eq!(le32(&hd, 2), (HEADER_LEN + 24) as u32);
+        assert_eq!(le32(&hd, 10), HEADER_LEN as u32);
+        assert_eq!(le32(&hd, 14), 40);
+        assert_eq!(le32(&hd, 18), 3);
+        assert_eq!(le32(&hd, 22), 2);
+        assert_eq!(le16(&hd, 26), 1);
+        assert_eq!(le16(&hd, 28), 24);
+        assert_eq!(le32(&hd, 30), 0);
+        assert_eq!(le32(&hd, 34), 24);
+
+        let info = parse_header(&header(1920, 1080)).unwrap();
+        assert_eq!((info.width, info.height, info.bpp, info.top_down), (1920, 1080, 24, false));
+        assert_eq!(info.file_size as usize, file_size(1920, 1080));
+        assert_eq!(info.stride(), row_size(1920));
+    }
+
+    #[test]
+    fn encoded_rows_are_bottom_up_bgr_and_padded() {
+        let px = |x: usize, y: usize| 0x10_20_30 + (y * 3 + x) as u32;
+        let mut out = vec![0xAAu8; file_size(3, 2)];
+        encode_range(3, 2, 0, &mut out, px);
+        // Primera fila del archivo = fila de abajo (y = 1)
+        assert_eq!(&out[HEADER_LEN..HEADER_LEN + 12],
+                   &[0x33, 0x20, 0x10, 0x34, 0x20, 0x10, 0x35, 0x20, 0x10, 0, 0, 0]);
+        assert_eq!(&out[HEADER_LEN + 12..],
+                   &[0x30, 0x20, 0x10, 0x31, 0x20, 0x10, 0x32, 0x20, 0x10, 0, 0, 0]);
+
+        let info = parse_header(&out).unwrap();
+        info.check_decodable(out.len()).unwrap();
+        for y in 0..2 { for x in 0..3 { assert_eq!(pixel_at(&info, &out, x, y), px(x, y)); } }
+    }
+
+    #[test]
+    fn encode_by_chunks_matches_whole_file() {
+        let px = |x: usize, y: usize| ((x * 37 + y * 11) as u32) * 0x010305;
+        let (w, h) = (5, 4);
+        let mut whole = vec![0u8; file_size(w, h)];
+        encode_range(w, h, 0, &mut whole, px);
+        // Trozos que cortan la cabecera y las filas por cualquier sitio
+        for chunk in [1, 7, 13, 50, 64] {
+            let mut parts = vec![0u8; whole.len()];
+            for (i, part) in parts.chunks_mut(chunk).enumerate() {
+                encode_range(w, h, i * chunk, part, px);
+            }
+            assert_eq!(parts, whole, "trozos de {}", chunk);
+        }
+        // Más allá del final solo hay ceros
+        let mut tail = [0xFFu8; 8];
+        encode_range(w, h, whole.len(), &mut tail, px);
+        assert_eq!(tail, [0; 8]);
+    }
+}
@@ -1,8 +1,25 @@
 // kernel/src/font.rs - Fuente 8×8 píxeles, ASCII 32..=127
 // Cada entrada = 8 bytes (una fila de 8 bits por byte, MSB = pixel izquierdo)
 // Índice 0 = carácter 32 (espacio), índice 95 = carácter 127 (DEL/unused)
+// (el renderer lee el bit 0 como píxel izquierdo)
+//
+// FONT_6X8: segunda fuente compacta para barras de estado densas. Glifos de
+// 5×7 en una celda de 6×8 (misma codificación por filas que FONT_8X8).
+// Los cálculos de layout deben usar FONT_8X8_ADVANCE / FONT_6X8_ADVANCE
+// (o Layout::char_w / small_w) en lugar de asumir 9 px por carácter.
 #![allow(dead_code)]
 
+/// Avance horizontal por carácter de FONT_8X8 (glifo de 8 px + 1 de separación)
+pub const FONT_8X8_ADVANCE: usize = 9;
+/// Avance horizontal por carácter de FONT_6X8 (glifo de 5 px + 1 de separación)
+pub const FONT_6X8_ADVANCE: usize = 6;
+
+/// Filas del glifo de `ch` en FONT_6X8, o None si no es ASCII imprimible.
+pub fn glyph_6x8(ch: char) -> Option<&'static [u8; 8]> {
+    let a = ch as usize;
+    if (32..=127).contains(&a) { Some(&FONT_6X8[a - 32]) } else { None }
+}
+
 pub const FONT_8X8: [[u8; 8]; 96] = [
     // 32 ' '
     [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
@@ -196,4 +213,231 @@ pub const FONT_8X8: [[u8; 8]; 96] = [
     [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
     // 127 DEL (unused, mostrar como bloque)
     [0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
-];
\ No newline at end of file
+];
+
+pub const FONT_6X8: [[u8; 8]; 96] = [
+    // 32 ' '
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 33 '!'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04, 0x00],
+    // 34 '"'
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 35 '#'
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A, 0x00],
+    // 36 '$'
+    [0x04, 0x1E, 0x05, 0x0E, 0x14, 0x0F, 0x04, 0x00],
+    // 37 '%'
+    [0x03, 0x13, 0x08, 0x04, 0x02, 0x19, 0x18, 0x00],
+    // 38 '&'
+    [0x06, 0x09, 0x05, 0x02, 0x15, 0x09, 0x16, 0x00],
+    // 39 '\''
+    [0x06, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 40 '('
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08, 0x00],
+    // 41 ')'
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02, 0x00],
+    // 42 '*'
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00, 0x00],
+    // 43 '+'
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00, 0x00],
+    // 44 ','
+    [0x00, 0x00, 0x00, 0x00, 0x06, 0x04, 0x02, 0x00],
+    // 45 '-'
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00, 0x00],
+    // 46 '.'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x06, 0x06, 0x00],
+    // 47 '/'
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00, 0x00],
+    // 48 '0'
+    [0x0E, 0x11, 0x19, 0x15, 0x13, 0x11, 0x0E, 0x00],
+    // 49 '1'
+    [0x04, 0x06, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00],
+    // 50 '2'
+    [0x0E, 0x11, 0x10, 0x08, 0x04, 0x02, 0x1F, 0x00],
+    // 51 '3'
+    [0x1F, 0x08, 0x04, 0x08, 0x10, 0x11, 0x0E, 0x00],
+    // 52 '4'
+    [0x08, 0x0C, 0x0A, 0x09, 0x1F, 0x08, 0x08, 0x00],
+    // 53 '5'
+    [0x1F, 0x01, 0x0F, 0x10, 0x10, 0x11, 0x0E, 0x00],
+    // 54 '6'
+    [0x0C, 0x02, 0x01, 0x0F, 0x11, 0x11, 0x0E, 0x00],
+    // 55 '7'
+    [0x1F, 0x10, 0x08, 0x04, 0x02, 0x02, 0x02, 0x00],
+    // 56 '8'
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E, 0x00],
+    // 57 '9'
+    [0x0E, 0x11, 0x11, 0x1E, 0x10, 0x08, 0x06, 0x00],
+    // 58 ':'
+    [0x00, 0x06, 0x06, 0x00, 0x06, 0x06, 0x00, 0x00],
+    // 59 ';'
+    [0x00, 0x06, 0x06, 0x00, 0x06, 0x04, 0x02, 0x00],
+    // 60 '<'
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08, 0x00],
+    // 61 '='
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00, 0x00],
+    // 62 '>'
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02, 0x00],
+    // 63 '?'
+    [0x0E, 0x11, 0x10, 0x08, 0x04, 0x00, 0x04, 0x00],
+    // 64 '@'
+    [0x0E, 0x11, 0x10, 0x16, 0x15, 0x15, 0x0E, 0x00],
+    // 65 'A'
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x00],
+    // 66 'B'
+    [0x0F, 0x11, 0x11, 0x0F, 0x11, 0x11, 0x0F, 0x00],
+    // 67 'C'
+    [0x0E, 0x11, 0x01, 0x01, 0x01, 0x11, 0x0E, 0x00],
+    // 68 'D'
+    [0x07, 0x09, 0x11, 0x11, 0x11, 0x09, 0x07, 0x00],
+    // 69 'E'
+    [0x1F, 0x01, 0x01, 0x0F, 0x01, 0x01, 0x1F, 0x00],
+    // 70 'F'
+    [0x1F, 0x01, 0x01, 0x0F, 0x01, 0x01, 0x01, 0x00],
+    // 71 'G'
+    [0x0E, 0x11, 0x01, 0x1D, 0x11, 0x11, 0x1E, 0x00],
+    // 72 'H'
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11, 0x00],
+    // 73 'I'
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00],
+    // 74 'J'
+    [0x1C, 0x08, 0x08, 0x08, 0x08, 0x09, 0x06, 0x00],
+    // 75 'K'
+    [0x11, 0x09, 0x05, 0x03, 0x05, 0x09, 0x11, 0x00],
+    // 76 'L'
+    [0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x1F, 0x00],
+    // 77 'M'
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11, 0x00],
+    // 78 'N'
+    [0x11, 0x11, 0x13, 0x15, 0x19, 0x11, 0x11, 0x00],
+    // 79 'O'
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00],
+    // 80 'P'
+    [0x0F, 0x11, 0x11, 0x0F, 0x01, 0x01, 0x01, 0x00],
+    // 81 'Q'
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x09, 0x16, 0x00],
+    // 82 'R'
+    [0x0F, 0x11, 0x11, 0x0F, 0x05, 0x09, 0x11, 0x00],
+    // 83 'S'
+    [0x1E, 0x01, 0x01, 0x0E, 0x10, 0x10, 0x0F, 0x00],
+    // 84 'T'
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00],
+    // 85 'U'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E, 0x00],
+    // 86 'V'
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00],
+    // 87 'W'
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A, 0x00],
+    // 88 'X'
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11, 0x00],
+    // 89 'Y'
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x00],
+    // 90 'Z'
+    [0x1F, 0x10, 0x08, 0x04, 0x02, 0x01, 0x1F, 0x00],
+    // 91 '['
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E, 0x00],
+    // 92 '\\'
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00, 0x00],
+    // 93 ']'
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E, 0x00],
+    // 94 '^'
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 95 '_'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F, 0x00],
+    // 96 '`'
+    [0x02, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 97 'a'
+    [0x00, 0x00, 0x0E, 0x10, 0x1E, 0x11, 0x1E, 0x00],
+    // 98 'b'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x0F, 0x00],
+    // 99 'c'
+    [0x00, 0x00, 0x0E, 0x01, 0x01, 0x11, 0x0E, 0x00],
+    // 100 'd'
+    [0x10, 0x10, 0x16, 0x19, 0x11, 0x11, 0x1E, 0x00],
+    // 101 'e'
+    [0x00, 0x00, 0x0E, 0x11, 0x1F, 0x01, 0x0E, 0x00],
+    // 102 'f'
+    [0x0C, 0x12, 0x02, 0x07, 0x02, 0x02, 0x02, 0x00],
+    // 103 'g'
+    [0x00, 0x1E, 0x11, 0x11, 0x1E, 0x10, 0x0E, 0x00],
+    // 104 'h'
+    [0x01, 0x01, 0x0D, 0x13, 0x11, 0x11, 0x11, 0x00],
+    // 105 'i'
+    [0x04, 0x00, 0x06, 0x04, 0x04, 0x04, 0x0E, 0x00],
+    // 106 'j'
+    [0x08, 0x00, 0x0C, 0x08, 0x08, 0x09, 0x06, 0x00],
+    // 107 'k'
+    [0x01, 0x01, 0x09, 0x05, 0x03, 0x05, 0x09, 0x00],
+    // 108 'l'
+    [0x06, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E, 0x00],
+    // 109 'm'
+    [0x00, 0x00, 0x0B, 0x15, 0x15, 0x11, 0x11, 0x00],
+    // 110 'n'
+    [0x00, 0x00, 0x0D, 0x13, 0x11, 0x11, 0x11, 0x00],
+    // 111 'o'
+    [0x00, 0x00, 0x0E, 0x11, 0x11, 0x11, 0x0E, 0x00],
+    // 112 'p'
+    [0x00, 0x00, 0x0F, 0x11, 0x0F, 0x01, 0x01, 0x00],
+    // 113 'q'
+    [0x00, 0x00, 0x16, 0x19, 0x1E, 0x10, 0x10, 0x00],
+    // 114 'r'
+    [0x00, 0x00, 0x0D, 0x13, 0x01, 0x01, 0x01, 0x00],
+    // 115 's'
+    [0x00, 0x00, 0x0E, 0x01, 0x0E, 0x10, 0x0F, 0x00],
+    // 116 't'
+    [0x02, 0x02, 0x07, 0x02, 0x02, 0x12, 0x0C, 0x00],
+    // 117 'u'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x19, 0x16, 0x00],
+    // 118 'v'
+    [0x00, 0x00, 0x11, 0x11, 0x11, 0x0A, 0x04, 0x00],
+    // 119 'w'
+    [0x00, 0x00, 0x11, 0x11, 0x15, 0x15, 0x0A, 0x00],
+    // 120 'x'
+    [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x00],
+    // 121 'y'
+    [0x00, 0x00, 0x11, 0x11, 0x1E, 0x10, 0x0E, 0x00],
+    // 122 'z'
+    [0x00, 0x00, 0x1F, 0x08, 0x04, 0x02, 0x1F, 0x00],
+    // 123 '{'
+    [0x08, 0x04, 0x04, 0x02, 0x04, 0x04, 0x08, 0x00],
+    // 124 '|'
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04, 0x00],
+    // 125 '}'
+    [0x02, 0x04, 0x04, 0x08, 0x04, 0x04, 0x02, 0x00],
+    // 126 '~'
+    [0x00, 0x00, 0x02, 0x15, 0x08, 0x00, 0x00, 0x00],
+    // 127 DEL (unused, mostrar como bloque)
+    [0x1F, 0x1F, 0x1F, 0x1F, 0x1F, 0x00, 0x00, 0x00],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_printable_ascii_has_a_glyph() {
+        for ch in '!'..='~' {
+            let g = glyph_6x8(ch).unwrap_or_else(|| panic!("sin glifo para {:?}", ch));
+            assert!(g.iter().any(|&r| r != 0), "glifo vacío para {:?}", ch);
+            // Solo se dibujan las 5 columnas de la izquierda
+            assert!(g.iter().all(|&r| r & !0x1F == 0), "glifo de {:?} más ancho de 5 px", ch);
+        }
+        assert_eq!(glyph_6x8(' '), Some(&[0u8; 8]));
+    }
+
+    #[test]
+    fn printable_glyphs_are_distinct() {
+        for a in '!'..='~' {
+            for b in (a as u8 + 1) as char..='~' {
+                assert_ne!(glyph_6x8(a), glyph_6x8(b), "{:?} y {:?} tienen el mismo glifo", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn non_ascii_has_no_glyph() {
+        for ch in ['\0', '\n', '\x1F', '\u{80}', 'ñ', '€'] {
+            assert_eq!(glyph_6x8(ch), None);
+        }
+    }
+}
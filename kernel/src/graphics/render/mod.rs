@@ -1 +1,2 @@
-pub mod font;
\ No newline at end of file
+pub mod font;
+pub mod bmp;
@@ -1,4 +1,4 @@
-// kernel/src/main.rs — PORTIX Kernel v0.7.4
+// kernel/src/main.rs — PORTIX Kernel
 //
 // FIXES:
 //   - ctrl leído desde kbd.ctrl() en lugar de hardcoded false
@@ -6,12 +6,14 @@
 //   - explorer: preview se carga tras mover selección
 //   - variables ide/explorer: binding único sin doble-shadow
 //   - unused_unsafe: init_page_pool ya es safe (el unsafe está dentro)
+//   - `cargo test` en el host: sin no_std/no_main ni el punto de entrada,
+//     los handlers de panic/OOM ni el allocator global (ver mem, isr_handlers)
 
-#![no_std]
-#![no_main]
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
 #![allow(dead_code)]
 #![allow(static_mut_refs)] // kernel bare-metal single-thread — safe
-#![feature(alloc_error_handler)]
+#![cfg_attr(not(test), feature(alloc_error_handler))]
 #![feature(allocator_api)]
 
 extern crate alloc;
@@ -26,20 +28,25 @@ pub mod ui;
 pub mod util;
 
 use console::terminal::editor::draw_editor_tab;
+use console::terminal::viewer::{draw_viewer_tab, viewer_visible_rows};
+use console::terminal::logview::{draw_logview_tab, logview_visible_rows};
 use console::terminal::LineColor;
+#[cfg(not(test))]
 use core::arch::global_asm;
 use drivers::input::keyboard::Key;
 use drivers::storage::{ata, fat32, mkfs};
-use graphics::driver::framebuffer::{Color, Console, Layout};
-use mem::allocator::BuddyAllocator;
-use ui::tabs::explorer::ExplorerState;
-use ui::tabs::ide::{init_page_pool, IdeState, MenuState, MENUS};
-use ui::tabs::ide::{MENU_H as IDE_MENU_H, STATUS_H as IDE_STATUS_H, TABS_H as IDE_TABS_H};
+use graphics::driver::framebuffer::{Color, Console, CursorShape, Layout};
+use ui::tabs::explorer::{explorer_visible_rows, ExplorerState};
+use ui::tabs::ide::{dropdown_contains, dropdown_item_at, ide_edit_area, ide_gutter_line_at, ide_visible_rows, init_page_pool, IdeState, MenuState, MENUS};
+use ui::tabs::ide::MENU_H as IDE_MENU_H;
+use ui::chrome::{draw_power_menu, power_menu_hit, PowerAction, PowerHit, PowerMenu};
 use ui::{
     draw_chrome, draw_devices_tab, draw_explorer_tab, draw_ide_tab, draw_system_tab,
-    draw_terminal_tab, terminal_hist_geometry, Tab, SCROLLBAR_W,
+    draw_terminal_tab, terminal_hist_geometry, terminal_layout, terminal_link_hit, terminal_split_pane, Tab,
+    SCROLLBAR_W,
 };
 
+#[cfg(not(test))]
 #[alloc_error_handler]
 fn alloc_error(layout: core::alloc::Layout) -> ! {
     panic!("OOM: size={} align={}", layout.size(), layout.align());
@@ -51,6 +58,7 @@ extern "C" {
     static __stack_top: u8;
 }
 
+#[cfg(not(test))]
 global_asm!(
     ".section .text._start, \"ax\"",
     ".global _start",
@@ -78,11 +86,10 @@ global_asm!(
     RUST_MAIN = sym rust_main,
 );
 
-#[global_allocator]
-static ALLOCATOR: BuddyAllocator = BuddyAllocator::new();
-
 // ── Constantes ────────────────────────────────────────────────────────────────
 
+/// Versión del kernel; cada subsistema declara la suya (comando `versions`).
+pub const VERSION: &str = "0.9.0";
 const RENDER_HZ: u64 = 30;
 const RENDER_INTERVAL: u64 = 100 / RENDER_HZ;
 const PS2_STATUS: u16 = 0x64;
@@ -131,6 +138,81 @@ fn ide_help_btn_hit(mx: i32, my: i32, content_y: usize, fw: usize, font_w: usize
     x >= help_x && x < fw
 }
 
+// ── Menú de energía de la cabecera ───────────────────────────────────────────
+//
+// Devuelve true si el clic lo consume el menú. Un clic fuera lo cierra pero no
+// se consume, así que sigue llegando a las pestañas o al contenido.
+fn handle_power_click(
+    power: &mut PowerMenu, term: &mut console::terminal::Terminal, lay: &Layout, mx: i32, my: i32,
+) -> bool {
+    match power_menu_hit(lay, *power, mx, my) {
+        PowerHit::Button => {
+            *power = if *power == PowerMenu::Closed { PowerMenu::Open } else { PowerMenu::Closed };
+        }
+        PowerHit::Item(a) => *power = PowerMenu::Confirm(a),
+        PowerHit::Yes => match *power {
+            PowerMenu::Confirm(PowerAction::Shutdown) => console::terminal::commands::system::shutdown_now(term),
+            PowerMenu::Confirm(PowerAction::Reboot) => console::terminal::commands::system::reboot_now(term),
+            _ => *power = PowerMenu::Closed,
+        },
+        PowerHit::No => *power = PowerMenu::Closed,
+        PowerHit::Inside => {}
+        PowerHit::Outside => {
+            *power = PowerMenu::Closed;
+            return false;
+        }
+    }
+    true
+}
+
+// ── Minibuffer del IDE ───────────────────────────────────────────────────────
+//
+// Ejecuta en el terminal la línea confirmada con Ctrl+: y resume la salida en
+// la status bar del IDE; el resto queda en el historial del terminal.
+// Devuelve true si el comando abrió el editor o el visor del terminal.
+fn run_ide_command(
+    ide: &mut IdeState, term: &mut console::terminal::Terminal,
+    hw: &arch::hardware::HardwareInfo, pci: &drivers::bus::pci::PciBus,
+) -> bool {
+    let mut line = [0u8; ui::input::INPUT_MAX];
+    let n = ide.take_command(&mut line);
+    if n == 0 {
+        return false;
+    }
+    if n > console::terminal::INPUT_MAX {
+        ide.set_status("Comando demasiado largo para el terminal", true);
+        return false;
+    }
+    let before = term.line_count;
+    let ok = term.submit(&line[..n], hw, pci);
+    term.remote_prompt();
+
+    // La primera línea nueva es el eco del prompt
+    let out = term.line_count.saturating_sub(before + 1);
+    let first: &[u8] = if out > 0 && out < console::terminal::TERM_ROWS {
+        let l = term.line_at(before + 1);
+        &l.buf[..l.len]
+    } else {
+        b""
+    };
+    ide.show_command_result(first, out.saturating_sub(1), ok);
+    term.editor.is_some() || term.viewer.is_some() || term.logview.is_some()
+}
+
+// ── Recargar del IDE ─────────────────────────────────────────────────────────
+//
+// Lee del disco el archivo que el IDE pidió recargar (Ctrl+R o `:reload`, ya
+// confirmado si había cambios) y se lo devuelve para sustituir el buffer.
+fn run_ide_reload(ide: &mut IdeState, term: &mut console::terminal::Terminal) {
+    let mut path = [0u8; 256];
+    let n = ide.take_reload(&mut path);
+    if n == 0 {
+        return;
+    }
+    let res = term.reread_file(&path[..n]);
+    ide.finish_reload(res);
+}
+
 fn exp_help_btn_hit(mx: i32, my: i32, content_y: usize, fw: usize, font_w: usize) -> bool {
     use ui::tabs::explorer::TOOLBAR_H;
     let y = my as usize;
@@ -142,56 +224,137 @@ fn exp_help_btn_hit(mx: i32, my: i32, content_y: usize, fw: usize, font_w: usize
     x >= hx && x < fw
 }
 
-// Hit-test dentro del dropdown abierto. Devuelve el índice del item (-1 si no).
-fn ide_dropdown_hit(mx: i32, my: i32, menu_idx: usize, content_y: usize, font_w: usize) -> i32 {
-    if menu_idx >= MENUS.len() {
-        return -1;
+// ── Forma del cursor del ratón según el contexto ─────────────────────────────
+//
+// Mano sobre elementos clickeables (pestañas, menús, botones [?]), I-beam
+// sobre zonas de texto (terminal, editor del IDE), doble flecha vertical
+// sobre la scrollbar arrastrable del terminal y flecha en el resto.
+fn cursor_shape_at(
+    mx: i32, my: i32, tab: Tab, lay: &Layout,
+    term: &console::terminal::Terminal, ide: &IdeState, explorer: &ExplorerState,
+) -> CursorShape {
+    if mx < 0 || my < 0 {
+        return CursorShape::Arrow;
     }
-    let menu = &MENUS[menu_idx];
-
-    let mut x_pos = 6usize;
-    for i in 0..menu_idx {
-        x_pos += MENUS[i].title.len() * font_w + 18;
+    let (x, y) = (mx as usize, my as usize);
+    if lay.tab_hit(mx, my) >= 0 {
+        return CursorShape::Hand;
     }
+    if y < lay.content_y || y >= lay.bottom_y {
+        return CursorShape::Arrow;
+    }
+    match tab {
+        Tab::Terminal => {
+            if term.viewer.is_some() || term.logview.is_some() {
+                CursorShape::Arrow
+            } else if term.editor.is_some() {
+                CursorShape::IBeam
+            } else if x >= lay.fw.saturating_sub(SCROLLBAR_W) {
+                CursorShape::ResizeV
+            } else {
+                let tl = terminal_layout(lay, term);
+                let (hist_top, _, _, _) = terminal_hist_geometry(&tl);
+                if terminal_link_hit(&tl, term, mx, my).is_some() {
+                    CursorShape::Hand
+                } else if y >= hist_top && y < lay.bottom_y.saturating_sub(4) {
+                    CursorShape::IBeam
+                } else {
+                    CursorShape::Arrow
+                }
+            }
+        }
+        Tab::Ide => {
+            if !ide.zen && (ide_menubar_hit(mx, my, lay.content_y, lay.font_w) >= 0
+                || ide_help_btn_hit(mx, my, lay.content_y, lay.fw, lay.font_w))
+            {
+                return CursorShape::Hand;
+            }
+            if let MenuState::Open(idx) = ide.menu {
+                if dropdown_item_at(idx, lay.font_w, lay.content_y, x, y).is_some() {
+                    return CursorShape::Hand;
+                }
+            }
+            if ide.menu == MenuState::Closed && ide_gutter_line_at(lay, ide, x, y).is_some() {
+                return CursorShape::Hand;
+            }
+            let (edit_start, edit_h) = ide_edit_area(lay, ide.zen);
+            let edit_end = edit_start + edit_h;
+            if y >= edit_start && y < edit_end { CursorShape::IBeam } else { CursorShape::Arrow }
+        }
+        Tab::Explorer => {
+            let on_menu = explorer.context.item_at(x, y, lay.fw, lay.bottom_y, lay.font_w).is_some();
+            if on_menu || exp_help_btn_hit(mx, my, lay.content_y, lay.fw, lay.font_w)
+                || (!explorer.context.visible && explorer.crumb_at(x, y, lay).is_some())
+            {
+                CursorShape::Hand
+            } else {
+                CursorShape::Arrow
+            }
+        }
+        _ => CursorShape::Arrow,
+    }
+}
 
-    let max_label = menu
-        .items
-        .iter()
-        .map(|it| it.label.len())
-        .max()
-        .unwrap_or(10);
-    let max_short = menu
-        .items
-        .iter()
-        .map(|it| it.shortcut.len())
-        .max()
-        .unwrap_or(0);
-    let dd_w = (max_label + max_short + 6) * font_w + 16;
-    let dd_x = x_pos;
-    let dd_y = content_y + IDE_MENU_H;
-
-    let x = mx as usize;
-    let y = my as usize;
-    if x < dd_x || x >= dd_x + dd_w {
-        return -1;
+// ── Rutas clickables del terminal ────────────────────────────────────────────
+//
+// Abre el enlace (línea lógica `li`, columnas `start..start+len`): un archivo
+// va a un buffer nuevo del IDE y una carpeta al Explorador. Devuelve la
+// pestaña a la que cambiar; los errores quedan escritos en el terminal.
+fn open_terminal_link(
+    term: &mut console::terminal::Terminal, ide: &mut IdeState, explorer: &mut ExplorerState,
+    li: usize, start: usize, len: usize,
+) -> Option<Tab> {
+    let target = term.link_target(li, start, len)?;
+    if target.is_dir {
+        return explorer.open_path(target.path()).then_some(Tab::Explorer);
+    }
+    let data = term.read_link_file(&target)?;
+    if !ide.open_with_data(target.name(), target.path(), data) {
+        term.write_line("  IDE: no quedan buffers libres (cierra alguno con Ctrl+W)", LineColor::Warning);
+        return None;
     }
+    Some(Tab::Ide)
+}
 
-    const ITEM_H: usize = 16;
-    let rel_y = y.wrapping_sub(dd_y + 3);
-    let item = rel_y / ITEM_H;
-    if item < menu.items.len() && y >= dd_y + 3 {
-        item as i32
-    } else {
-        -1
+/// Atiende `open_request` del Explorador (Enter, doble clic o Recientes):
+/// lee el archivo de disco por su ruta y lo abre en el IDE con Recargar
+/// habilitado. Devuelve la pestaña a mostrar; los errores van a la status
+/// bar del Explorador.
+fn open_explorer_request(
+    explorer: &mut ExplorerState, ide: &mut IdeState, term: &mut console::terminal::Terminal,
+) -> Option<Tab> {
+    if !explorer.open_request {
+        return None;
+    }
+    explorer.open_request = false;
+    let (name, path) = (explorer.open_name, explorer.open_path);
+    let name = core::str::from_utf8(&name[..explorer.open_name_len]).unwrap_or("archivo");
+    let path = &path[..explorer.open_path_len];
+    let data = match term.reread_file(path) {
+        Ok(data) => data,
+        Err(drivers::storage::fat32::FatError::NotFat32) => {
+            explorer.set_status("No se pudo montar el volumen", false);
+            return None;
+        }
+        Err(_) => {
+            explorer.set_status("No se pudo leer el archivo (max 64 KiB)", false);
+            return None;
+        }
+    };
+    if !ide.open_with_data(name, path, data) {
+        explorer.set_status("IDE: no quedan buffers libres (cierra alguno con Ctrl+W)", false);
+        return None;
     }
+    Some(Tab::Ide)
 }
 
 // ── Punto de entrada ──────────────────────────────────────────────────────────
 
 #[no_mangle]
 extern "C" fn rust_main() -> ! {
+    let (heap_start, heap_size) = mem::heap_region();
     unsafe {
-        ALLOCATOR.init();
+        mem::ALLOCATOR.init(heap_start, heap_size);
     }
 
     init_page_pool();
@@ -199,17 +362,53 @@ extern "C" fn rust_main() -> ! {
     unsafe {
         arch::idt::init_idt();
     }
-    drivers::serial::init();
+    drivers::serial::init(drivers::serial::DEFAULT_BAUD);
     time::pit::init();
+    match arch::paging::init() {
+        Ok(())  => drivers::serial::log("MMU", "tablas de paginas propias activas"),
+        Err(e)  => drivers::serial::log_level(drivers::serial::Level::Warn, "MMU", e),
+    }
+    // Tras la MMU: las tablas ACPI altas se leen con map_mmio
+    drivers::bus::acpi::init();
+    {
+        use drivers::registry::{self, State, Subsystem};
+        registry::set_ok(Subsystem::Serial, drivers::serial::is_ready());
+        registry::set(Subsystem::Pit, State::Active);
+        // Apagado/reinicio: por ACPI o, sin tablas, por puertos fijos
+        registry::set(Subsystem::Acpi, State::Active);
+    }
     unsafe {
         core::arch::asm!("sti", options(nostack, preserves_flags));
     }
+    // Con IRQ0 ya en marcha: frecuencia del TSC (la copia hw.cpu.tsc_mhz)
+    time::tsc::calibrate();
     drivers::serial::log("PIT", "temporizador 100 Hz");
 
-    let hw = arch::hardware::HardwareInfo::detect_all();
+    // Consola antes de los escaneos: el panel de progreso muestra qué
+    // bus/unidad se está probando mientras duran los timeouts
+    let mut c = Console::new();
+    let mut boot = ui::boot_progress::BootProgress::new(&mut c);
+
+    boot.stage("Escaneando discos ATA");
+    let mut hw = arch::hardware::HardwareInfo::detect_all_with(|bus, drive| boot.ata(bus, drive));
     drivers::serial::log("HW", hw.cpu.brand_str());
+    drivers::serial::log("HV", hw.cpu.hypervisor.name());
 
-    let pci = drivers::bus::pci::PciBus::scan();
+    boot.stage("Escaneando bus PCI");
+    let pci = drivers::bus::pci::PciBus::scan_with(|bus, dev| boot.pci(bus, dev));
+    {
+        use drivers::registry::{self, State, Subsystem};
+        registry::set_ok(Subsystem::Pci, pci.count > 0);
+        // Controlador SATA AHCI (clase 01h, subclase 06h): sus discos se
+        // añaden a hw.disks (solo detección, la E/S sigue siendo ATA PIO)
+        boot.stage("Controladores SATA AHCI");
+        let ahci = drivers::storage::ahci::probe(&pci, &mut hw.disks, |port| boot.ahci(port));
+        registry::set(Subsystem::Ahci, match ahci {
+            a if a.controllers == 0 => State::Missing,
+            a if a.mapped == 0      => State::Inactive,
+            _                       => State::Active,
+        });
+    }
     {
         let mut t = [0u8; 16];
         let s = util::fmt::fmt_u32(pci.count as u32, &mut t);
@@ -218,14 +417,32 @@ extern "C" fn rust_main() -> ! {
         drivers::serial::write_str(" dispositivos\n");
     }
 
+    boot.stage("Canal ATA PIO");
+    let ata_bus = ata::AtaBus::scan_with(|id| boot.ata(id as u8 / 2, id as u8 % 2));
+    boot.finish();
+
     let mut kbd = drivers::input::keyboard::KeyboardState::new();
     let mut ms = drivers::input::mouse::MouseState::new();
-    let mut c = Console::new();
     let lay = Layout::new(c.width(), c.height());
-    ms.init(lay.fw.max(1), lay.fh.max(1));
+    let mouse_ok = ms.init(lay.fw.max(1), lay.fh.max(1));
+    drivers::registry::set_ok(drivers::registry::Subsystem::Mouse, mouse_ok);
+    // Sin controlador PS/2 el puerto de estado flota a 0xFF
+    let kbc_ok = unsafe { ps2_inb(PS2_STATUS) } != 0xFF;
+    drivers::registry::set_ok(drivers::registry::Subsystem::Keyboard, kbc_ok);
+    // LEDs en el estado inicial (todos apagados); el ACK llega por el drenado.
+    // Si no llega, `kbd.watch` da el teclado por ausente y empieza a sondear.
+    kbd.set_present(kbc_ok);
+    if kbc_ok { kbd.sync_leds(); }
 
     let mut term = console::terminal::Terminal::new();
-    term.write_line("PORTIX v0.7.4  Kernel Bare-Metal", LineColor::Header);
+    {
+        let mut buf = [0u8; 48]; let mut pos = 0;
+        for part in [&b"PORTIX v"[..], VERSION.as_bytes(), b"  Kernel Bare-Metal"] {
+            buf[pos..pos + part.len()].copy_from_slice(part);
+            pos += part.len();
+        }
+        term.write_bytes(&buf[..pos], LineColor::Header);
+    }
     term.write_line("Escribe 'ayuda' para comandos.", LineColor::Info);
     term.write_empty();
 
@@ -237,7 +454,7 @@ extern "C" fn rust_main() -> ! {
     }
 
   {
-        let ata = ata::AtaBus::scan();
+        let ata = ata_bus;
         ata::log_drives(&ata);
 
         // v0.8.0: guardar DriveInfo ANTES de lanzar el loop principal.
@@ -246,6 +463,8 @@ extern "C" fn rust_main() -> ! {
         if let Some(info) = ata.info(ata::DriveId::Primary0) {
             ata::store_primary_drive_info(*info);
         }
+        let ata_ok = ata::get_cached_drive_info().is_some();
+        drivers::registry::set_ok(drivers::registry::Subsystem::Ata, ata_ok);
 
         // Montar FAT32 o formatear si no existe
         let vol_result = if let Some(drive_info) = ata::get_cached_drive_info() {
@@ -268,24 +487,32 @@ extern "C" fn rust_main() -> ! {
             None
         };
 
+        drivers::registry::set_ok(drivers::registry::Subsystem::Fat32, vol_result.is_some());
+
         // Inicializar ExplorerState con el cluster raíz real del volumen
         if let Some(vol) = vol_result {
             let root = vol.root_cluster();
             unsafe {
                 core::ptr::addr_of_mut!(EXPLORER_STORAGE)
                     .write(core::mem::MaybeUninit::new(ExplorerState::new(root)));
+                // Marcadores persistidos en /portix/bookmark.txt
+                (*core::ptr::addr_of_mut!(EXPLORER_STORAGE)).assume_init_mut().load_bookmarks(&vol);
             }
         }
         // Si vol_result == None, EXPLORER_STORAGE ya fue inicializado con
         // ExplorerState::new(2) arriba — usa cluster 2 como fallback.
     }
 
+    // Opciones guardadas con `config save` (/portix/config)
+    console::terminal::commands::config::load_at_boot(&mut term, &hw, &pci);
+
     // Referencias limpias para el loop principal
     let ide: &mut IdeState = unsafe { (*core::ptr::addr_of_mut!(IDE_STORAGE)).assume_init_mut() };
     let explorer: &mut ExplorerState =
         unsafe { (*core::ptr::addr_of_mut!(EXPLORER_STORAGE)).assume_init_mut() };
 
     let mut tab = Tab::System;
+    let mut power = PowerMenu::Closed;
     let mut sb_dragging = false;
     let mut sb_drag_y: i32 = 0;
     let mut sb_drag_offset: usize = 0;
@@ -322,7 +549,8 @@ extern "C" fn rust_main() -> ! {
         unsafe {
             loop {
                 let st = ps2_inb(PS2_STATUS);
-                if st & 0x01 == 0 {
+                // 0xFF = no hay controlador: el bit "lleno" nunca se vacía
+                if st & 0x01 == 0 || st == 0xFF {
                     break;
                 }
                 let byte = ps2_inb(PS2_DATA);
@@ -340,16 +568,80 @@ extern "C" fn rust_main() -> ! {
             }
         }
 
+        // ── Teclado/ratón no detectados: sondeo periódico sin bloquear ────
+        // Las respuestas llegan en vueltas posteriores por el drenado.
+        let input_before = (kbd.present(), ms.present);
+        kbd.watch(now);
+        if !ms.present {
+            for &b in &ms_buf[..ms_n] {
+                if ms.feed_absent(b) { break; }
+            }
+            // Lo que quede son restos del reset, no paquetes de movimiento
+            ms_n = 0;
+            ms.probe(now);
+        }
+
+        // ── Sesión remota: entrada desde COM1 ─────────────────────────────
+        if term.poll_remote(&hw, &pci) {
+            needs_draw = true;
+            if term.editor.is_some() {
+                tab = Tab::Terminal;
+            }
+        }
+
+        // ── `atareset`: el reset necesita hw mutable (actualiza hw.disks) ──
+        if let Some(bus) = term.ata_reset.take() {
+            console::terminal::commands::disk::run_atareset(&mut term, &mut hw, bus);
+            needs_draw = true;
+        }
+
+        // ── `regwatch`: muestreo del registro MMIO ────────────────────────
+        if console::terminal::commands::debug::regwatch_poll(&mut term, now) && tab == Tab::Terminal {
+            needs_draw = true;
+        }
+
+        // ── `watch`: re-ejecución periódica de un comando ─────────────────
+        if console::terminal::commands::watch::watch_poll(&mut term, now, &hw, &pci) && tab == Tab::Terminal {
+            needs_draw = true;
+        }
+
+        // ── Temporizadores (`timer`) ──────────────────────────────────────
+        match time::timer::poll(now) {
+            time::timer::TimerEvent::Expired(tm) => {
+                console::terminal::commands::system::timer_expired(&mut term, &tm);
+                needs_draw = true;
+            }
+            time::timer::TimerEvent::Shutdown => {
+                console::terminal::commands::system::shutdown_now(&mut term);
+            }
+            time::timer::TimerEvent::Changed => needs_draw = true,
+            time::timer::TimerEvent::None => {}
+        }
+
         // ── Cola de teclado ───────────────────────────────────────────────
-        for i in 0..kbd_n {
-            if let Some(key) = kbd.feed_byte(kbd_buf[i]) {
+        // Tras los bytes recibidos, la repetición por software (typematic)
+        // y al final las teclas de la sesión remota para el editor/visores.
+        let leds_before = kbd.leds();
+        let (remote_keys, remote_n) = term.take_remote_keys();
+        for i in 0..=kbd_n + remote_n {
+            let from_remote = i > kbd_n;
+            let (key, remote_ctrl) = match i {
+                _ if i < kbd_n => (kbd.feed_byte(kbd_buf[i]), false),
+                _ if i == kbd_n => (kbd.repeat(now), false),
+                _ => { let (k, c) = remote_keys[i - kbd_n - 1]; (Some(k), c) }
+            };
+            if let Some(key) = key {
                 needs_draw = true;
+                // Si el editor/visor se cerró en este frame sobran las remotas
+                if from_remote && term.editor.is_none() && term.viewer.is_none() && term.logview.is_none() {
+                    continue;
+                }
 
                 // Editor de texto del terminal (modo especial)
                if term.editor.is_some() {
     let should_exit = {
         let ed = term.editor.as_mut().unwrap();
-        let ctrl = kbd.ctrl();
+        let ctrl = kbd.ctrl() || remote_ctrl;
         ed.handle_key(key, ctrl);  // ← ctrl ahora se pasa
         ed.exit
     };
@@ -361,15 +653,76 @@ extern "C" fn rust_main() -> ! {
                     continue;
                 }
 
+                // Visor hex de archivos (`view`) — consume todas las teclas
+                if let Some(v) = term.viewer.as_mut() {
+                    v.handle_key(key, viewer_visible_rows(&lay));
+                    if v.exit {
+                        term.viewer = None;
+                        term.write_line("  Visor cerrado.", LineColor::Info);
+                    }
+                    continue;
+                }
+
+                // Visor del log (`dmesg -w`) — consume todas las teclas
+                if let Some(lv) = term.logview.as_mut() {
+                    lv.handle_key(key, logview_visible_rows(&lay));
+                    if lv.exit {
+                        term.logview = None;
+                        term.write_line("  Visor de log cerrado.", LineColor::Info);
+                    }
+                    continue;
+                }
+
+                // `watch`: Esc o q lo detienen; no se escribe en el prompt,
+                // pero el scroll y el cambio de pestaña siguen funcionando
+                if tab == Tab::Terminal && term.watch_active() {
+                    match key {
+                        Key::Escape | Key::Char(b'q') | Key::Char(b'Q') => {
+                            console::terminal::commands::watch::watch_stop(&mut term);
+                            continue;
+                        }
+                        Key::Char(_) | Key::Enter | Key::Backspace | Key::Tab => continue,
+                        _ => {}
+                    }
+                }
+
+                // `regwatch`: cualquier tecla lo detiene
+                if tab == Tab::Terminal && term.watching() {
+                    console::terminal::commands::debug::regwatch_stop(&mut term);
+                    continue;
+                }
+
+                // `waitkey`: la tecla reanuda el script en pausa (Esc lo aborta)
+                if tab == Tab::Terminal && term.waiting_key() {
+                    console::terminal::commands::script::resume_after_key(
+                        &mut term, key == Key::Escape, &hw, &pci,
+                    );
+                    continue;
+                }
+
                 // ► LECTURA REAL DEL ESTADO CTRL ◄
                 let ctrl = kbd.ctrl();
 
                 // Escape cierra menú IDE o limpia terminal
                 if key == Key::Escape {
+                    // …salvo que haya un apagado programado: lo cancela
+                    if time::timer::cancel_shutdown() {
+                        term.write_line("  Apagado programado cancelado (Esc).", LineColor::Success);
+                        continue;
+                    }
+                    if power != PowerMenu::Closed {
+                        power = PowerMenu::Closed;
+                        continue;
+                    }
                     if ide.menu != MenuState::Closed {
                         ide.menu = MenuState::Closed;
                         continue;
                     }
+                    // Prompt, reemplazo o selección del IDE
+                    if tab == Tab::Ide && ide.wants_escape() {
+                        ide.handle_key(key, ctrl, ide_visible_rows(&lay, ide.zen));
+                        continue;
+                    }
                     if tab == Tab::Terminal {
                         term.clear_history();
                         term.clear_input();
@@ -404,23 +757,43 @@ extern "C" fn rust_main() -> ! {
                     }
 
                     // ── Terminal ──────────────────────────────────────────
+                    // Ctrl+RePag/AvPag: panel superior congelado de `split`
+                    Key::PageUp if tab == Tab::Terminal && ctrl && term.split_active() => {
+                        let (_, _, _, ml) = terminal_hist_geometry(&terminal_layout(&lay, &term));
+                        term.split_scroll_up(10, ml);
+                    }
+                    Key::PageDown if tab == Tab::Terminal && ctrl && term.split_active() => {
+                        term.split_scroll_down(10);
+                    }
                     Key::PageUp if tab == Tab::Terminal => {
-                        let (_, _, _, ml) = terminal_hist_geometry(&lay);
+                        let (_, _, _, ml) = terminal_hist_geometry(&terminal_layout(&lay, &term));
                         term.scroll_up(10, ml);
                     }
                     Key::PageDown if tab == Tab::Terminal => term.scroll_down(10),
                     Key::Home if tab == Tab::Terminal => {
-                        let (_, _, _, ml) = terminal_hist_geometry(&lay);
+                        let (_, _, _, ml) = terminal_hist_geometry(&terminal_layout(&lay, &term));
                         term.scroll_up(usize::MAX / 2, ml);
                     }
                     Key::End if tab == Tab::Terminal => term.scroll_to_bottom(),
+                    // Ctrl+↑/↓: scroll fino de una línea. Las flechas sin
+                    // modificador quedan libres para el historial de comandos.
+                    Key::Up if tab == Tab::Terminal && ctrl => {
+                        let (_, _, _, ml) = terminal_hist_geometry(&terminal_layout(&lay, &term));
+                        term.scroll_up(1, ml);
+                    }
+                    Key::Down if tab == Tab::Terminal && ctrl => term.scroll_down(1),
                     Key::Char(ch) if tab == Tab::Terminal => {
                         term.type_char(ch);
                         drivers::serial::write_byte(ch);
                     }
-                    Key::Backspace if tab == Tab::Terminal => term.backspace(),
+                    Key::Backspace if tab == Tab::Terminal => {
+                        if term.remote_active() && term.input_len > 0 {
+                            drivers::serial::write_str("\x08 \x08");
+                        }
+                        term.backspace();
+                    }
                     Key::Enter if tab == Tab::Terminal => {
-                        drivers::serial::write_byte(b'\n');
+                        drivers::serial::write_str("\n");
                         term.enter(&hw, &pci);
                         if term.editor.is_some() {
                             tab = Tab::Terminal;
@@ -429,13 +802,12 @@ extern "C" fn rust_main() -> ! {
 
                     // ── IDE — Ctrl+S/N/W y teclas de edición ──────────────
                     _ if tab == Tab::Ide => {
-                        let edit_start = lay.content_y + IDE_MENU_H + IDE_TABS_H;
-                        let edit_h = lay.fh.saturating_sub(edit_start + IDE_STATUS_H);
-                        let lh = lay.font_h + 3;
-                        let vis_r = (edit_h / lh).max(1);
-
                         // Ctrl+S/N/W/Tab manejados dentro de ide.handle_key
-                        ide.handle_key(key, ctrl, vis_r);
+                        ide.handle_key(key, ctrl, ide_visible_rows(&lay, ide.zen));
+                        run_ide_reload(ide, &mut term);
+                        if run_ide_command(ide, &mut term, &hw, &pci) {
+                            tab = Tab::Terminal;
+                        }
                     }
 
                     // ── Explorer ──────────────────────────────────────────
@@ -444,13 +816,8 @@ extern "C" fn rust_main() -> ! {
                         explorer.handle_key(key);
                         // Cargar preview si cambió la selección y hay FAT32
                         let _ = prev_sel; // preview se carga en el render o con FAT32
-                        if explorer.open_request {
-                            explorer.open_request = false;
-                            let name =
-                                core::str::from_utf8(&explorer.open_name[..explorer.open_name_len])
-                                    .unwrap_or("archivo");
-                            ide.open_new(name);
-                            tab = Tab::Ide;
+                        if let Some(next) = open_explorer_request(explorer, ide, &mut term) {
+                            tab = next;
                         }
                     }
 
@@ -458,6 +825,10 @@ extern "C" fn rust_main() -> ! {
                 }
             }
         }
+        // Bloq Mayús/Num/Despl no producen Key: refrescar sus indicadores
+        if kbd.leds() != leds_before {
+            needs_draw = true;
+        }
 
         // ── Cola de ratón ─────────────────────────────────────────────────
         let mouse_changed = if ms.present && ms_n > 0 {
@@ -480,8 +851,24 @@ extern "C" fn rust_main() -> ! {
             needs_draw = true;
         }
 
+        // Cambio de presencia: registro (`lsmod`), log e indicador de la barra
+        if (kbd.present(), ms.present) != input_before {
+            use drivers::registry::{set_ok, Subsystem};
+            use drivers::serial::{log_level, Level};
+            if kbd.present() != input_before.0 {
+                set_ok(Subsystem::Keyboard, kbd.present());
+                if kbd.present() { log_level(Level::Ok, "PS2", "Teclado detectado"); }
+                else { log_level(Level::Warn, "PS2", "Teclado sin respuesta; reintentando"); }
+            }
+            if ms.present != input_before.1 {
+                set_ok(Subsystem::Mouse, ms.present);
+                log_level(Level::Ok, "PS2", "Raton detectado");
+            }
+            needs_draw = true;
+        }
+
         // ── Interacción con ratón ─────────────────────────────────────────
-        if term.editor.is_none() {
+        if term.editor.is_none() && term.viewer.is_none() && term.logview.is_none() {
             let fw = lay.fw;
             let sb_x = fw.saturating_sub(SCROLLBAR_W) as i32;
 
@@ -493,7 +880,7 @@ extern "C" fn rust_main() -> ! {
 
             // Arrastrar scrollbar del terminal
             if sb_dragging && ms.left_btn() && tab == Tab::Terminal {
-                let (_, hist_h, _, max_lines) = terminal_hist_geometry(&lay);
+                let (_, hist_h, _, max_lines) = terminal_hist_geometry(&terminal_layout(&lay, &term));
                 let max_scroll = term.max_scroll(max_lines);
                 if max_scroll > 0 {
                     let available = term.line_count.saturating_sub(
@@ -506,13 +893,13 @@ extern "C" fn rust_main() -> ! {
                     let thumb_h = if available == 0 {
                         hist_h
                     } else {
-                        (hist_h * max_lines / available).max(10).min(hist_h)
+                        (hist_h * term.scroll_rows(max_lines) / available).max(10).min(hist_h)
                     };
                     let travel = hist_h.saturating_sub(thumb_h) as i32;
                     if travel > 0 {
                         let dy = ms.y - sb_drag_y;
                         let new_offset = sb_drag_offset as i32 - (dy * max_scroll as i32) / travel;
-                        term.scroll_offset = new_offset.max(0).min(max_scroll as i32) as usize;
+                        term.set_scroll(new_offset.max(0) as usize, max_lines);
                     }
                 }
                 needs_draw = true;
@@ -522,7 +909,20 @@ extern "C" fn rust_main() -> ! {
                 explorer.handle_right_click(ms.x as usize, ms.y as usize, lay.content_y, lay.fw);
                 needs_draw = true;
             }
-            if mouse_changed && ms.left_clicked() {
+            let power_click = mouse_changed
+                && ms.left_clicked()
+                && handle_power_click(&mut power, &mut term, &lay, ms.x, ms.y);
+            if mouse_changed && ms.left_clicked() && !power_click {
+                let link_hit = if tab == Tab::Terminal {
+                    terminal_link_hit(&terminal_layout(&lay, &term), &term, ms.x, ms.y)
+                } else {
+                    None
+                };
+                let gutter_hit = if tab == Tab::Ide && ide.menu == MenuState::Closed {
+                    ide_gutter_line_at(&lay, ide, ms.x.max(0) as usize, ms.y.max(0) as usize)
+                } else {
+                    None
+                };
                 // ── Click en scrollbar del terminal ───────────────────────
                 if tab == Tab::Terminal && ms.x >= sb_x {
                     sb_dragging = true;
@@ -556,32 +956,24 @@ extern "C" fn rust_main() -> ! {
                         _ => {}
                     }
 
+                // ── Click en una ruta de la salida del terminal ───────────
+                } else if let Some((li, start, len)) = link_hit {
+                    if let Some(next) = open_terminal_link(&mut term, ide, explorer, li, start, len) {
+                        tab = next;
+                    }
+                    needs_draw = true;
+
+                // ── Click en un número de línea del IDE (Shift = línea entera)
+                } else if let Some(li) = gutter_hit {
+                    needs_draw |= ide.click_gutter(li, kbd.shift());
+
                 // ── Click dentro del área de contenido del IDE ────────────
-                } else if tab == Tab::Ide {
+                // (en modo zen no hay menubar ni botón [?] que pulsar)
+                } else if tab == Tab::Ide && !ide.zen {
                     if ide_help_btn_hit(ms.x, ms.y, lay.content_y, lay.fw, lay.font_w) {
                         ide.show_help = !ide.show_help;
                         needs_draw = true;
                     }
-                    if explorer.context.visible {
-                        // ¿Hit en algún item del menú?
-                        let cx = explorer.context.x;
-                        let cy = explorer.context.y;
-                        let mw = explorer.context.width(lay.font_w);
-                        let x = ms.x as usize;
-                        let y = ms.y as usize;
-                        if x >= cx && x < cx + mw && y >= cy {
-                            let item_idx = (y.saturating_sub(cy + 2)) / 18;
-                            explorer.execute_context(item_idx);
-                        } else {
-                            explorer.context.close();
-                        }
-                        needs_draw = true;
-                    }
-                    // Botón [?] de help en toolbar del explorer
-                    else if exp_help_btn_hit(ms.x, ms.y, lay.content_y, lay.fw, lay.font_w) {
-                        explorer.show_help = !explorer.show_help;
-                        needs_draw = true;
-                    }
                     let hit_menu = ide_menubar_hit(ms.x, ms.y, lay.content_y, lay.font_w);
                     if hit_menu >= 0 {
                         // Abrir/cerrar menú
@@ -593,18 +985,17 @@ extern "C" fn rust_main() -> ! {
                         };
                         needs_draw = true;
                     } else if let MenuState::Open(open_idx) = ide.menu {
-                        // Click dentro del dropdown
-                        let item_hit =
-                            ide_dropdown_hit(ms.x, ms.y, open_idx, lay.content_y, lay.font_w);
-                        if item_hit >= 0 {
-                            let action = MENUS[open_idx].items[item_hit as usize].action;
+                        // Click dentro del dropdown (separadores y márgenes no hacen nada)
+                        let (x, y) = (ms.x.max(0) as usize, ms.y.max(0) as usize);
+                        if let Some(item) = dropdown_item_at(open_idx, lay.font_w, lay.content_y, x, y) {
+                            let action = MENUS[open_idx].items[item].action;
                             ide.execute_menu(action);
-                            needs_draw = true;
-                        } else {
+                            run_ide_reload(ide, &mut term);
+                        } else if !dropdown_contains(open_idx, lay.font_w, lay.content_y, x, y) {
                             // Click fuera del dropdown → cerrar
                             ide.menu = MenuState::Closed;
-                            needs_draw = true;
                         }
+                        needs_draw = true;
                     } else {
                         // Click en editor — cerrar cualquier menú abierto
                         if ide.menu != MenuState::Closed {
@@ -613,6 +1004,44 @@ extern "C" fn rust_main() -> ! {
                         }
                     }
 
+                // ── Click en el Explorador: menú contextual y botón [?] ───
+                } else if tab == Tab::Explorer {
+                    if explorer.context.visible {
+                        // ¿Hit en algún item del menú? (separadores y márgenes no hacen nada)
+                        let (x, y) = (ms.x.max(0) as usize, ms.y.max(0) as usize);
+                        let ctx = &explorer.context;
+                        if let Some(item_idx) = ctx.item_at(x, y, lay.fw, lay.bottom_y, lay.font_w) {
+                            explorer.execute_context(item_idx);
+                        } else if !ctx.contains(x, y, lay.fw, lay.bottom_y, lay.font_w) {
+                            explorer.context.close();
+                        }
+                        needs_draw = true;
+                    }
+                    // Botón [?] de help en toolbar del explorer
+                    else if exp_help_btn_hit(ms.x, ms.y, lay.content_y, lay.fw, lay.font_w) {
+                        explorer.show_help = !explorer.show_help;
+                        needs_draw = true;
+                    }
+                    // Clic en el breadcrumb: subir hasta ese nivel
+                    else if let Some(i) = explorer.crumb_at(ms.x.max(0) as usize, ms.y.max(0) as usize, &lay) {
+                        explorer.go_to_crumb(i);
+                        needs_draw = true;
+                    }
+                    // Clic en la lista: seleccionar fila (doble clic = abrir)
+                    else if explorer.handle_left_click(ms.x.max(0) as usize, ms.y.max(0) as usize, &lay, time::pit::ticks()) {
+                        needs_draw = true;
+                    }
+                    if let Some(next) = open_explorer_request(explorer, ide, &mut term) {
+                        tab = next;
+                    }
+                    // "Abrir en terminal": cwd del terminal = carpeta actual
+                    if explorer.terminal_request {
+                        explorer.terminal_request = false;
+                        let path = explorer.terminal_path;
+                        term.open_dir(&path[..explorer.terminal_path_len], &hw, &pci);
+                        tab = Tab::Terminal;
+                    }
+
                 // ── Click fuera del IDE con menú abierto → cerrarlo ───────
                 } else if ide.menu != MenuState::Closed {
                     ide.menu = MenuState::Closed;
@@ -620,16 +1049,60 @@ extern "C" fn rust_main() -> ! {
                 }
             }
 
+            // Item resaltado bajo el ratón: dropdown del IDE y menú contextual
+            if mouse_changed {
+                let (x, y) = (ms.x.max(0) as usize, ms.y.max(0) as usize);
+                ide.menu_hover = match ide.menu {
+                    MenuState::Open(mi) if tab == Tab::Ide =>
+                        dropdown_item_at(mi, lay.font_w, lay.content_y, x, y).unwrap_or(usize::MAX),
+                    _ => usize::MAX,
+                };
+                let ctx = &mut explorer.context;
+                ctx.hovered = ctx.item_at(x, y, lay.fw, lay.bottom_y, lay.font_w).unwrap_or(usize::MAX);
+            }
+
             // Scroll del ratón en terminal
             if mouse_changed && ms.scroll_delta != 0 && tab == Tab::Terminal && !sb_dragging {
-                let (_, _, _, ml) = terminal_hist_geometry(&lay);
-                if ms.scroll_delta > 0 {
+                let (_, _, _, ml) = terminal_hist_geometry(&terminal_layout(&lay, &term));
+                let tl = terminal_layout(&lay, &term);
+                let over_split = terminal_split_pane(&tl, &term).is_some_and(|(top, rows)| {
+                    let y = ms.y.max(0) as usize;
+                    y >= top && y < top + (rows + 1) * tl.line_h
+                });
+                if over_split {
+                    if ms.scroll_delta > 0 {
+                        term.split_scroll_up(console::terminal::SCROLL_STEP, ml);
+                    } else {
+                        term.split_scroll_down(console::terminal::SCROLL_STEP);
+                    }
+                } else if ms.scroll_delta > 0 {
                     term.scroll_up(console::terminal::SCROLL_STEP, ml);
                 } else {
                     term.scroll_down(console::terminal::SCROLL_STEP);
                 }
                 needs_draw = true;
             }
+
+            // Rueda en el Explorador (lista de archivos) y en el IDE (buffer activo)
+            if mouse_changed && ms.scroll_delta != 0 {
+                match tab {
+                    Tab::Explorer => needs_draw |= explorer.wheel(ms.scroll_delta, explorer_visible_rows(&lay)),
+                    Tab::Ide      => needs_draw |= ide.wheel(ms.scroll_delta, ide_visible_rows(&lay, ide.zen)),
+                    _ => {}
+                }
+            }
+        }
+
+        // ── Visor del log: rueda y entradas nuevas ────────────────────────
+        if let Some(lv) = term.logview.as_mut() {
+            let vis = logview_visible_rows(&lay);
+            if mouse_changed && ms.scroll_delta != 0 && tab == Tab::Terminal {
+                lv.scroll(ms.scroll_delta > 0, console::terminal::SCROLL_STEP, vis);
+                needs_draw = true;
+            }
+            if lv.refresh(vis) && tab == Tab::Terminal {
+                needs_draw = true;
+            }
         }
 
         // ── Cursor parpadeante ────────────────────────────────────────────
@@ -645,23 +1118,37 @@ extern "C" fn rust_main() -> ! {
 
         // ── Render ────────────────────────────────────────────────────────
         if needs_draw {
-            draw_chrome(&mut c, &lay, &hw, tab, ms.x, ms.y);
+            draw_chrome(&mut c, &lay, &hw, tab, power, ms.x, ms.y);
 
             match tab {
                 Tab::System => draw_system_tab(&mut c, &lay, &hw, boot_lines),
                 Tab::Terminal => {
                     if let Some(ref ed) = term.editor {
                         draw_editor_tab(&mut c, &lay, ed);
+                    } else if let Some(ref v) = term.viewer {
+                        draw_viewer_tab(&mut c, &lay, v);
+                    } else if let Some(ref lv) = term.logview {
+                        draw_logview_tab(&mut c, &lay, lv);
                     } else {
-                        draw_terminal_tab(&mut c, &lay, &term, sb_dragging);
+                        let mouse = if ms.present { (ms.x, ms.y) } else { (-1, -1) };
+                        draw_terminal_tab(&mut c, &lay, &term, sb_dragging, mouse);
                     }
                 }
                 Tab::Devices => draw_devices_tab(&mut c, &lay, &hw, &pci),
                 Tab::Ide => draw_ide_tab(&mut c, &lay, ide),
                 Tab::Explorer => draw_explorer_tab(&mut c, &lay, explorer),
             }
+            draw_power_menu(&mut c, &lay, power, ms.x, ms.y);
 
             if ms.present {
+                let shape = match power_menu_hit(&lay, power, ms.x, ms.y) {
+                    // Arrastrando la scrollbar la forma no cambia al salir de ella
+                    PowerHit::Outside if sb_dragging => CursorShape::ResizeV,
+                    PowerHit::Outside => cursor_shape_at(ms.x, ms.y, tab, &lay, &term, ide, explorer),
+                    PowerHit::Inside => CursorShape::Arrow,
+                    _ => CursorShape::Hand,
+                };
+                c.set_cursor_shape(shape);
                 c.draw_cursor(ms.x, ms.y);
             }
             needs_draw = false;
@@ -6,8 +6,17 @@
 //   • AllocStats: contadores atómicos visibles desde la UI (sin lock).
 //   • Serial debug en init() — imprime mapa de bloques iniciales.
 //   • Trazas alloc/free bajo cfg(debug_assertions).
-
-use super::{HEAP_SIZE, HEAP_START, MAX_ORDER, MIN_ORDER, ORDER_COUNT};
+//   • Región del heap elegida en el arranque a partir del mapa E820
+//     (mem::heap_region); sin región válida se usa HEAP_SIZE desde
+//     HEAP_START. Agotado el heap, toda asignación devuelve null.
+//   • Alineación: la base está alineada a 2^MAX_ORDER, así que un bloque de
+//     orden o queda alineado a 2^o en direcciones absolutas; basta con pedir
+//     un orden que cubra max(size, align).
+//   • Contadores free_blocks exactos también al partir y fusionar bloques.
+//   • free_block_counts(): los mismos contadores recorriendo las listas de
+//     una instancia concreta, sin pasar por ALLOC_STATS.
+
+use super::{MAX_ORDER, MIN_ORDER, ORDER_COUNT};
 use crate::drivers::serial::{self, Level};
 use core::alloc::{GlobalAlloc, Layout};
 use core::ptr;
@@ -76,6 +85,8 @@ impl FreeNode {
 
 struct BuddyInner {
     free_lists: [*mut FreeNode; ORDER_COUNT],
+    start:      usize,
+    end:        usize,
 }
 
 unsafe impl Send for BuddyInner {}
@@ -85,6 +96,8 @@ impl BuddyInner {
     const fn new() -> Self {
         Self {
             free_lists: [ptr::null_mut(); ORDER_COUNT],
+            start:      0,
+            end:        0,
         }
     }
 }
@@ -107,25 +120,29 @@ impl BuddyAllocator {
         }
     }
 
-    /// Inicializa el heap y emite el mapa de bloques por serial.
+    /// Inicializa el heap sobre `[start, start + size)` y emite el mapa de
+    /// bloques por serial. `start` debe estar alineado a 2^MAX_ORDER.
     ///
     /// # Safety
-    /// Llamar exactamente una vez, antes de cualquier alloc/free.
-    pub unsafe fn init(&self) {
-        if self.inited.swap(true, Ordering::AcqRel) {
+    /// Llamar exactamente una vez, antes de cualquier alloc/free, con una
+    /// región de RAM libre e identity-mapped.
+    pub unsafe fn init(&self, start: usize, size: usize) {
+        if size < (1 << MIN_ORDER) || self.inited.swap(true, Ordering::AcqRel) {
             return;
         }
 
         serial::log_level(Level::Info, "HEAP", "Inicializando buddy allocator...");
         serial::write_str("[  INF ] HEAP  rango: ");
-        serial::write_hex(HEAP_START);
+        serial::write_hex(start);
         serial::write_str(" - ");
-        serial::write_hex(HEAP_START + HEAP_SIZE);
+        serial::write_hex(start + size);
         serial::write_byte(b'\n');
 
         let inner = &mut *self.inner.get();
-        let mut addr  = HEAP_START;
-        let end       = HEAP_START + HEAP_SIZE;
+        inner.start   = start;
+        inner.end     = start + size;
+        let mut addr  = start;
+        let end       = start + size;
         let mut count = 0usize;
 
         while addr < end {
@@ -159,10 +176,35 @@ impl BuddyAllocator {
         serial::write_str("[   OK ] HEAP  listo — ");
         serial::write_usize(count);
         serial::write_str(" bloques libres, ");
-        serial::write_usize(HEAP_SIZE >> 10);
+        serial::write_usize(size >> 10);
         serial::write_str(" KiB totales\n");
     }
 
+    /// Región gestionada (inicio, tamaño); (0, 0) si no se inicializó.
+    pub fn region(&self) -> (usize, usize) {
+        if !self.inited.load(Ordering::Acquire) { return (0, 0); }
+        self.lock();
+        let inner = unsafe { &*self.inner.get() };
+        let r = (inner.start, inner.end - inner.start);
+        self.unlock();
+        r
+    }
+
+    /// Bloques libres por orden contados recorriendo las listas de esta
+    /// instancia (ALLOC_STATS es global y lo comparten todas).
+    pub fn free_block_counts(&self) -> [usize; ORDER_COUNT] {
+        let mut counts = [0usize; ORDER_COUNT];
+        if !self.inited.load(Ordering::Acquire) { return counts; }
+        self.lock();
+        let inner = unsafe { &*self.inner.get() };
+        for (n, &head) in counts.iter_mut().zip(inner.free_lists.iter()) {
+            let mut cur = head;
+            while !cur.is_null() { *n += 1; cur = unsafe { (*cur).next }; }
+        }
+        self.unlock();
+        counts
+    }
+
     #[inline(always)]
     fn lock(&self) {
         while self.locked
@@ -243,10 +285,10 @@ unsafe fn find_buddy(inner: &mut BuddyInner, order: usize, buddy_addr: usize) ->
 }
 
 #[inline(always)]
-fn buddy_of(addr: usize, order: usize) -> usize {
+fn buddy_of(start: usize, addr: usize, order: usize) -> usize {
     let size   = 1usize << order;
-    let offset = addr - HEAP_START;
-    HEAP_START + (offset ^ size)
+    let offset = addr - start;
+    start + (offset ^ size)
 }
 
 // ── GlobalAlloc ───────────────────────────────────────────────────────────────
@@ -273,7 +315,6 @@ unsafe impl GlobalAlloc for BuddyAllocator {
             serial::log_level(Level::Warn, "HEAP", "OOM — alloc fallida");
         } else {
             ALLOC_STATS.total_allocs.fetch_add(1, Ordering::Relaxed);
-            ALLOC_STATS.free_blocks[ord_idx(order)].fetch_sub(1, Ordering::Relaxed);
             #[cfg(debug_assertions)]
             {
                 serial::write_str("[ DBG ] HEAP  alloc ord=");
@@ -289,13 +330,13 @@ unsafe impl GlobalAlloc for BuddyAllocator {
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
         let need  = layout.size().max(layout.align()).max(1 << MIN_ORDER);
         let order = order_for(need);
+        if ptr.is_null() || order > MAX_ORDER { return; }
 
         self.lock();
         buddy_free(&mut *self.inner.get(), ptr, order);
         self.unlock();
 
         ALLOC_STATS.total_frees.fetch_add(1, Ordering::Relaxed);
-        ALLOC_STATS.free_blocks[ord_idx(order)].fetch_add(1, Ordering::Relaxed);
 
         #[cfg(debug_assertions)]
         {
@@ -332,11 +373,14 @@ unsafe impl GlobalAlloc for BuddyAllocator {
 
 // ── Lógica core alloc/free ────────────────────────────────────────────────────
 
+/// Orden del bloque más pequeño que cubre `size`; MAX_ORDER + 1 si ni el
+/// mayor alcanza (la asignación debe fallar, no recibir un bloque corto).
 #[inline]
 fn order_for(size: usize) -> usize {
     let mut ord = MIN_ORDER;
     let mut blk = 1usize << MIN_ORDER;
-    while blk < size && ord < MAX_ORDER {
+    while blk < size {
+        if ord == MAX_ORDER { return MAX_ORDER + 1; }
         ord += 1;
         blk <<= 1;
     }
@@ -356,6 +400,7 @@ unsafe fn buddy_alloc(inner: &mut BuddyInner, order: usize) -> *mut u8 {
     }
 
     let ptr = inner_pop(inner, found_ord).unwrap();
+    ALLOC_STATS.free_blocks[ord_idx(found_ord)].fetch_sub(1, Ordering::Relaxed);
     let mut cur_ord = found_ord;
 
     while cur_ord > order {
@@ -381,8 +426,8 @@ unsafe fn buddy_free(inner: &mut BuddyInner, ptr: *mut u8, order: usize) {
 
     loop {
         if ord >= MAX_ORDER { break; }
-        let buddy_addr = buddy_of(addr, ord);
-        if buddy_addr < HEAP_START || buddy_addr >= HEAP_START + HEAP_SIZE { break; }
+        let buddy_addr = buddy_of(inner.start, addr, ord);
+        if buddy_addr < inner.start || buddy_addr + (1 << ord) > inner.end { break; }
         if !find_buddy(inner, ord, buddy_addr) { break; }
 
         inner_remove(inner, ord, buddy_addr as *mut u8);
@@ -395,6 +440,7 @@ unsafe fn buddy_free(inner: &mut BuddyInner, ptr: *mut u8, order: usize) {
     }
 
     inner_push(inner, ord, addr as *mut u8);
+    ALLOC_STATS.free_blocks[ord_idx(ord)].fetch_add(1, Ordering::Relaxed);
 
     #[cfg(debug_assertions)]
     if merges > 0 {
@@ -404,4 +450,135 @@ unsafe fn buddy_free(inner: &mut BuddyInner, ptr: *mut u8, order: usize) {
         serial::write_u32(ord as u32);
         serial::write_byte(b'\n');
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Heap simulado de dos bloques de orden MAX_ORDER sobre memoria del host.
+    struct TestHeap { base: *mut u8, layout: Layout, heap: BuddyAllocator }
+
+    impl TestHeap {
+        fn new() -> Self {
+            let layout = Layout::from_size_align(2 << MAX_ORDER, 1 << MAX_ORDER).unwrap();
+            let base = unsafe { std::alloc::alloc(layout) };
+            assert!(!base.is_null());
+            let heap = BuddyAllocator::new();
+            unsafe { heap.init(base as usize, layout.size()); }
+            TestHeap { base, layout, heap }
+        }
+        fn alloc(&self, size: usize, align: usize) -> *mut u8 {
+            unsafe { self.heap.alloc(Layout::from_size_align(size, align).unwrap()) }
+        }
+        fn free(&self, p: *mut u8, size: usize, align: usize) {
+            unsafe { self.heap.dealloc(p, Layout::from_size_align(size, align).unwrap()) }
+        }
+        fn contains(&self, p: *mut u8, size: usize) -> bool {
+            p >= self.base && p as usize + size <= self.base as usize + self.layout.size()
+        }
+    }
+
+    impl Drop for TestHeap {
+        fn drop(&mut self) { unsafe { std::alloc::dealloc(self.base, self.layout) } }
+    }
+
+    #[test]
+    fn uninitialized_heap_returns_null() {
+        let heap = BuddyAllocator::new();
+        assert!(unsafe { heap.alloc(Layout::from_size_align(16, 8).unwrap()) }.is_null());
+        assert_eq!(heap.region(), (0, 0));
+    }
+
+    #[test]
+    fn allocations_are_aligned_and_disjoint() {
+        let h = TestHeap::new();
+        assert_eq!(h.heap.region(), (h.base as usize, 2 << MAX_ORDER));
+        let mut prev: [(usize, usize); 4] = [(0, 0); 4];
+        for (i, &(size, align)) in [(1, 1), (24, 8), (100, 64), (3000, 4096)].iter().enumerate() {
+            let p = h.alloc(size, align);
+            assert!(!p.is_null());
+            assert_eq!(p as usize % align, 0);
+            assert!(h.contains(p, size));
+            for &(a, n) in &prev[..i] {
+                assert!(p as usize >= a + n || p as usize + size <= a);
+            }
+            prev[i] = (p as usize, size);
+        }
+    }
+
+    #[test]
+    fn exhaustion_returns_null_and_free_recovers() {
+        let h = TestHeap::new();
+        let big = 1 << MAX_ORDER;
+        let a = h.alloc(big, 8);
+        let b = h.alloc(big, 8);
+        assert!(!a.is_null() && !b.is_null());
+        assert!(h.alloc(16, 8).is_null());
+        // Más grande que el mayor bloque: nunca cabe
+        assert!(h.alloc(big + 1, 8).is_null());
+        h.free(a, big, 8);
+        let c = h.alloc(big, 8);
+        assert_eq!(c, a);
+        h.free(b, big, 8);
+        h.free(c, big, 8);
+    }
+
+    #[test]
+    fn freed_buddies_coalesce() {
+        let h = TestHeap::new();
+        let small: [*mut u8; 3] = [h.alloc(16, 16), h.alloc(200, 8), h.alloc(5000, 8)];
+        assert!(small.iter().all(|p| !p.is_null()));
+        h.free(small[0], 16, 16);
+        h.free(small[1], 200, 8);
+        h.free(small[2], 5000, 8);
+        // Si los bloques partidos no se fusionaran no quedarían dos de MAX_ORDER
+        let big = 1 << MAX_ORDER;
+        let a = h.alloc(big, 8);
+        let b = h.alloc(big, 8);
+        assert!(!a.is_null() && !b.is_null());
+    }
+
+    #[test]
+    fn summary_follows_alloc_and_free() {
+        use crate::mem::{summarize, HeapUsage};
+        let h = TestHeap::new();
+        let size = 2usize << MAX_ORDER;
+        let usage = |h: &TestHeap| {
+            let (start, size) = h.heap.region();
+            HeapUsage::from_free_blocks(start, size, h.heap.free_block_counts())
+        };
+
+        let u = usage(&h);
+        assert_eq!((u.free, u.largest_free, u.used()), (size, 1 << MAX_ORDER, 0));
+        assert_eq!(u.free_blocks[MAX_ORDER - MIN_ORDER], 2);
+
+        // 3 páginas, 1 MiB y una asignación mínima (redondeada a 2^MIN_ORDER)
+        let pages = [h.alloc(4096, 4096), h.alloc(4096, 4096), h.alloc(4096, 4096)];
+        let mib = h.alloc(1 << 20, 8);
+        let tiny = h.alloc(1, 1);
+        let used = 3 * 4096 + (1 << 20) + (1 << MIN_ORDER);
+        let u = usage(&h);
+        assert_eq!(u.used(), used);
+        assert_eq!(u.largest_free, 1 << MAX_ORDER);
+
+        let s = summarize(4 * size as u64, &u);
+        assert_eq!(s.heap_size, size as u64);
+        assert_eq!(s.heap_used, used as u64);
+        assert_eq!(s.heap_free, (size - used) as u64);
+        assert_eq!(s.unmanaged, 3 * size as u64);
+        assert_eq!(s.heap_used_pct(), (used * 100 / size) as u32);
+
+        // Liberar una página devuelve exactamente 4 KiB
+        h.free(pages[1], 4096, 4096);
+        assert_eq!(usage(&h).used(), used - 4096);
+
+        h.free(pages[0], 4096, 4096);
+        h.free(pages[2], 4096, 4096);
+        h.free(mib, 1 << 20, 8);
+        h.free(tiny, 1, 1);
+        let u = usage(&h);
+        assert_eq!(u.free, size);
+        assert_eq!(u.free_blocks[MAX_ORDER - MIN_ORDER], 2);
+        assert_eq!(summarize(size as u64, &u).heap_used, 0);
+    }
+}
@@ -4,16 +4,210 @@ pub mod allocator;  // ← pub, no solo mod
 
 use core::sync::atomic::Ordering;
 
+/// Allocator global: habilita `alloc::vec::Vec`, `Box`… en el kernel.
+#[cfg_attr(not(test), global_allocator)]
+pub static ALLOCATOR: allocator::BuddyAllocator = allocator::BuddyAllocator::new();
+
+/// Dirección mínima del heap (nunca por debajo de 16 MiB).
 pub const HEAP_START:   usize = 0x0100_0000;
+/// Tamaño máximo del heap.
 pub const HEAP_SIZE:    usize = 64 * 1024 * 1024;
+/// RAM mapeada en identidad: el primer GiB (stage2 y luego `arch::paging`).
+pub const IDENTITY_LIMIT: usize = 0x4000_0000;
+/// Región más pequeña que merece la pena gestionar.
+const HEAP_MIN:         usize = 1024 * 1024;
 pub const MIN_ORDER:    usize = 4;
 pub const MAX_ORDER:    usize = 22;
 pub const ORDER_COUNT:  usize = MAX_ORDER - MIN_ORDER + 1;
 
+extern "C" {
+    static __stack_top: u8;
+}
+
+/// Mapa E820 de stage2 (boot/stage2.asm): número de entradas (u16) en
+/// BINFO_E820CNT y entradas de 24 bytes (base u64, longitud u64, tipo u32,
+/// atributos ACPI u32) desde BINFO_E820.
+pub const E820_COUNT_ADDR: usize = 0x9000;
+pub const E820_ADDR:       usize = 0x9100;
+pub const E820_STRIDE:     usize = 24;
+/// Entradas que caben antes del bloque EDID (0x9C00); stage2 no escribe más.
+pub const E820_MAX:        usize = (0x9C00 - E820_ADDR) / E820_STRIDE;
+
+/// Número de entradas que stage2 dejó en el mapa E820 (sin recortar).
+pub fn e820_count() -> u16 {
+    unsafe { core::ptr::read_volatile(E820_COUNT_ADDR as *const u16) }
+}
+
+/// Entradas (base, longitud, tipo) del mapa E820, como mucho E820_MAX.
+pub fn e820_entries() -> impl Iterator<Item = (u64, u64, u32)> {
+    (0..(e820_count() as usize).min(E820_MAX)).map(|i| {
+        let p = (E820_ADDR + i * E820_STRIDE) as *const u8;
+        unsafe {(
+            core::ptr::read_unaligned(p as *const u64),
+            core::ptr::read_unaligned(p.add(8) as *const u64),
+            core::ptr::read_unaligned(p.add(16) as *const u32),
+        )}
+    })
+}
+
+/// Región del heap (inicio, tamaño). Sale del mapa E820 (`pick_heap_region`);
+/// si ninguna entrada vale se usa HEAP_SIZE desde HEAP_START, como antes de
+/// leer el mapa.
+pub fn heap_region() -> (usize, usize) {
+    let floor = (core::ptr::addr_of!(__stack_top) as usize).max(HEAP_START);
+    pick_heap_region(e820_entries(), floor).unwrap_or((align_heap(floor), HEAP_SIZE))
+}
+
+fn align_heap(addr: usize) -> usize {
+    let align = 1usize << MAX_ORDER;
+    (addr + align - 1) & !(align - 1)
+}
+
+/// Primer tramo utilizable (tipo 1) que, empezando en `floor` o después y
+/// con la base alineada a 2^MAX_ORDER, deje al menos HEAP_MIN por debajo de
+/// IDENTITY_LIMIT. El tamaño se limita a HEAP_SIZE y se redondea a
+/// 2^MIN_ORDER.
+fn pick_heap_region(entries: impl Iterator<Item = (u64, u64, u32)>, floor: usize) -> Option<(usize, usize)> {
+    for (base, len, kind) in entries {
+        if kind != 1 || len == 0 { continue; }
+        let end   = base.saturating_add(len).min(IDENTITY_LIMIT as u64) as usize;
+        let start = align_heap((base.min(IDENTITY_LIMIT as u64) as usize).max(floor));
+        if start >= end || end - start < HEAP_MIN { continue; }
+        let size  = (end - start).min(HEAP_SIZE) & !((1usize << MIN_ORDER) - 1);
+        return Some((start, size));
+    }
+    None
+}
+
+/// Resumen del heap para el comando `heap`.
+pub struct HeapUsage {
+    pub start:        usize,
+    pub size:         usize,
+    pub free:         usize,
+    /// Bloque libre más grande (lo máximo que cabe en una sola asignación).
+    pub largest_free: usize,
+    pub free_blocks:  [usize; ORDER_COUNT],
+}
+
+impl HeapUsage {
+    /// Uso de la región `[start, start + size)` a partir de los bloques
+    /// libres por orden (índice 0 = MIN_ORDER).
+    pub fn from_free_blocks(start: usize, size: usize, free_blocks: [usize; ORDER_COUNT]) -> Self {
+        let mut u = HeapUsage { start, size, free: 0, largest_free: 0, free_blocks };
+        for (i, &n) in free_blocks.iter().enumerate() {
+            u.free += n << (MIN_ORDER + i);
+            if n > 0 { u.largest_free = 1 << (MIN_ORDER + i); }
+        }
+        u
+    }
+
+    pub fn used(&self) -> usize { self.size.saturating_sub(self.free) }
+
+    /// Fragmentación externa en %: parte de la memoria libre que no está en
+    /// el bloque libre más grande (0 = todo libre contiguo).
+    pub fn fragmentation_pct(&self) -> u32 {
+        if self.free == 0 { return 0; }
+        (100 - (self.largest_free as u64 * 100 / self.free as u64)) as u32
+    }
+}
+
+pub fn heap_usage() -> HeapUsage {
+    let (start, size) = ALLOCATOR.region();
+    let counts = core::array::from_fn(|i| allocator::ALLOC_STATS.free_blocks[i].load(Ordering::Relaxed));
+    HeapUsage::from_free_blocks(start, size, counts)
+}
+
 pub fn alloc_stats_free_total() -> usize {
     let mut total = 0usize;
     for cell in &allocator::ALLOC_STATS.free_blocks {
         total += cell.load(Ordering::Relaxed);
     }
     total
-}
\ No newline at end of file
+}
+
+/// RAM detectada frente a lo que gestiona el heap (comando `free`).
+pub struct MemSummary {
+    /// RAM utilizable según E820, en bytes.
+    pub total:     u64,
+    /// Tamaño del heap (0 = sin inicializar).
+    pub heap_size: u64,
+    pub heap_used: u64,
+    pub heap_free: u64,
+    /// RAM que no está en el heap: kernel, pila, tablas y lo que supera
+    /// HEAP_SIZE o IDENTITY_LIMIT. Nadie la asigna dinámicamente.
+    pub unmanaged: u64,
+}
+
+impl MemSummary {
+    pub fn heap_ready(&self) -> bool { self.heap_size != 0 }
+
+    /// % del heap en uso (0 sin heap).
+    pub fn heap_used_pct(&self) -> u32 {
+        if self.heap_size == 0 { return 0; }
+        (self.heap_used * 100 / self.heap_size) as u32
+    }
+}
+
+/// Resumen a partir de la RAM total y del estado del heap. Separado de
+/// `mem_summary` para no depender del allocator global.
+pub fn summarize(total: u64, heap: &HeapUsage) -> MemSummary {
+    let heap_size = heap.size as u64;
+    let heap_free = (heap.free as u64).min(heap_size);
+    MemSummary {
+        total,
+        heap_size,
+        heap_used: heap_size - heap_free,
+        heap_free,
+        unmanaged: total.saturating_sub(heap_size),
+    }
+}
+
+/// `total` = RAM utilizable detectada, en bytes.
+pub fn mem_summary(total: u64) -> MemSummary {
+    summarize(total, &heap_usage())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIB: u64 = 1024 * 1024;
+
+    #[test]
+    fn heap_region_skips_reserved_and_low_ram() {
+        let map = [
+            (0,            0x9F000,   1),   // memoria baja: por debajo del suelo
+            (0xF0000,      0x10000,   2),   // reservada
+            (MIB,          511 * MIB, 1),
+        ];
+        let r = pick_heap_region(map.into_iter(), HEAP_START);
+        assert_eq!(r, Some((HEAP_START, HEAP_SIZE)));
+    }
+
+    #[test]
+    fn heap_region_aligns_base_and_caps_size() {
+        // Base sin alinear por encima del suelo: sube al siguiente 2^MAX_ORDER
+        let map = [(HEAP_START as u64 + 0x1234, 20 * MIB, 1)];
+        let (start, size) = pick_heap_region(map.into_iter(), HEAP_START).unwrap();
+        assert_eq!(start, HEAP_START + (1 << MAX_ORDER));
+        assert_eq!(start % (1 << MAX_ORDER), 0);
+        assert_eq!(size as u64, 16 * MIB + 0x1230);
+    }
+
+    #[test]
+    fn heap_region_stops_at_identity_limit() {
+        let map = [(IDENTITY_LIMIT as u64 - 8 * MIB, 64 * MIB, 1)];
+        let (start, size) = pick_heap_region(map.into_iter(), HEAP_START).unwrap();
+        assert_eq!(start + size, IDENTITY_LIMIT);
+        // Todo por encima del primer GiB no sirve
+        let high = [(IDENTITY_LIMIT as u64, 64 * MIB, 1)];
+        assert_eq!(pick_heap_region(high.into_iter(), HEAP_START), None);
+    }
+
+    #[test]
+    fn heap_region_rejects_small_or_empty_maps() {
+        let small = [(HEAP_START as u64, HEAP_MIN as u64 / 2, 1)];
+        assert_eq!(pick_heap_region(small.into_iter(), HEAP_START), None);
+        assert_eq!(pick_heap_region(core::iter::empty(), HEAP_START), None);
+    }
+}
@@ -1 +1,5 @@
-pub mod pit;
\ No newline at end of file
+pub mod pit;
+pub mod profile;
+pub mod rtc;
+pub mod timer;
+pub mod tsc;
@@ -2,7 +2,10 @@
 #![allow(dead_code)]
 
 const PIT_CHANNEL0: u16 = 0x40;
+const PIT_CHANNEL2: u16 = 0x42;
 const PIT_CMD:      u16 = 0x43;
+/// Puerto B del 8042: bit 0 = puerta del canal 2, bit 1 = altavoz.
+const SPEAKER_PORT: u16 = 0x61;
 
 // 1_193_182 Hz / 100 = 11931 → ~100 Hz
 pub const PIT_HZ: u32 = 100;
@@ -12,13 +15,20 @@ const PIT_DIVISOR: u16 = 11931;
 unsafe fn outb(port: u16, val: u8) {
     core::arch::asm!("out dx, al", in("dx") port, in("al") val, options(nostack, nomem));
 }
+#[inline(always)]
+unsafe fn inb(port: u16) -> u8 {
+    let v: u8;
+    core::arch::asm!("in al, dx", out("al") v, in("dx") port, options(nostack, nomem));
+    v
+}
 
 /// Global tick counter, incremented each IRQ0.  100 ticks = 1 second.
 pub static mut TICKS: u64 = 0;
 
-/// Called from the IRQ0 stub in isr.asm.
+/// Called from the IRQ0 stub in isr.asm with the interrupted RIP.
 #[no_mangle]
-pub extern "C" fn pit_tick() {
+pub extern "C" fn pit_tick(rip: u64) {
+    super::profile::sample(rip);
     unsafe {
         let v = core::ptr::read_volatile(&raw const TICKS);
         core::ptr::write_volatile(&raw mut TICKS, v.wrapping_add(1));
@@ -49,4 +59,21 @@ pub fn init() {
         outb(PIT_CHANNEL0, (PIT_DIVISOR & 0xFF) as u8);
         outb(PIT_CHANNEL0, (PIT_DIVISOR >> 8) as u8);
     }
-}
\ No newline at end of file
+}
+
+/// Altavoz del PC: canal 2 en onda cuadrada a `freq` Hz y puerta abierta.
+/// Queda sonando hasta `speaker_off()`.
+pub fn speaker_on(freq: u32) {
+    let div = 1_193_182u32 / freq.clamp(20, 20_000);
+    unsafe {
+        // Channel 2 | access lo/hi | mode 3 | binary
+        outb(PIT_CMD, 0xB6);
+        outb(PIT_CHANNEL2, (div & 0xFF) as u8);
+        outb(PIT_CHANNEL2, ((div >> 8) & 0xFF) as u8);
+        outb(SPEAKER_PORT, inb(SPEAKER_PORT) | 0x03);
+    }
+}
+
+pub fn speaker_off() {
+    unsafe { outb(SPEAKER_PORT, inb(SPEAKER_PORT) & !0x03); }
+}
@@ -0,0 +1,58 @@
+// kernel/src/time/profile.rs — PORTIX muestreo de RIP (comando `profile`)
+//
+// El stub de IRQ0 pasa a `pit_tick` el RIP interrumpido; mientras haya una
+// sesión activa se guarda tal cual en un buffer fijo. Todo el análisis
+// (ordenar, histograma, atribuir a funciones) se hace después desde el
+// comando, fuera de la interrupción.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Capacidad del buffer: ~10 s a 100 Hz.
+pub const MAX_SAMPLES: usize = 1024;
+
+static mut SAMPLES: [u64; MAX_SAMPLES] = [0; MAX_SAMPLES];
+/// Muestras escritas en la sesión actual.
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Muestras a tomar; 0 = muestreo inactivo.
+static LIMIT: AtomicUsize = AtomicUsize::new(0);
+
+/// Llamado desde IRQ0: solo guarda el RIP si hay sitio.
+#[inline(always)]
+pub fn sample(rip: u64) {
+    let n = COUNT.load(Ordering::Relaxed);
+    if n < LIMIT.load(Ordering::Relaxed) {
+        unsafe { core::ptr::write_volatile((&raw mut SAMPLES).cast::<u64>().add(n), rip); }
+        COUNT.store(n + 1, Ordering::Release);
+    }
+}
+
+/// Empieza una sesión nueva de `n` muestras (se descarta la anterior).
+pub fn start(n: usize) {
+    LIMIT.store(0, Ordering::SeqCst);
+    COUNT.store(0, Ordering::SeqCst);
+    LIMIT.store(n.min(MAX_SAMPLES), Ordering::SeqCst);
+}
+
+/// Detiene el muestreo conservando lo ya capturado.
+pub fn stop() {
+    LIMIT.store(COUNT.load(Ordering::SeqCst), Ordering::SeqCst);
+}
+
+/// true mientras falten muestras por tomar.
+pub fn active() -> bool {
+    COUNT.load(Ordering::Acquire) < LIMIT.load(Ordering::Acquire)
+}
+
+/// (tomadas, objetivo) de la sesión actual.
+pub fn progress() -> (usize, usize) {
+    (COUNT.load(Ordering::Acquire), LIMIT.load(Ordering::Acquire))
+}
+
+/// Copia las muestras tomadas en `out`; devuelve cuántas.
+pub fn copy_samples(out: &mut [u64]) -> usize {
+    let n = COUNT.load(Ordering::Acquire).min(out.len());
+    for (i, slot) in out.iter_mut().take(n).enumerate() {
+        *slot = unsafe { core::ptr::read_volatile((&raw const SAMPLES).cast::<u64>().add(i)) };
+    }
+    n
+}
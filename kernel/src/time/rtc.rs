@@ -0,0 +1,165 @@
+// kernel/src/time/rtc.rs — PORTIX reloj de tiempo real (CMOS MC146818)
+//
+// Lectura y escritura de fecha/hora por los puertos 0x70/0x71. El formato
+// de los registros depende del registro B: bit 2 = binario (si no, BCD) y
+// bit 1 = 24 h (si no, 12 h con el bit 7 de la hora como PM). Ambas
+// direcciones respetan el formato que ya tenga el chip.
+//
+// Lectura: se espera a que no haya actualización en curso (registro A,
+// bit 7) y se repite hasta obtener dos lecturas iguales seguidas.
+// Escritura: con el bit SET del registro B activo el chip detiene sus
+// actualizaciones, así ningún campo avanza a medias mientras se escriben.
+#![allow(dead_code)]
+
+const CMOS_ADDR: u16 = 0x70;
+const CMOS_DATA: u16 = 0x71;
+
+const REG_SEC:     u8 = 0x00;
+const REG_MIN:     u8 = 0x02;
+const REG_HOUR:    u8 = 0x04;
+const REG_WDAY:    u8 = 0x06;
+const REG_DAY:     u8 = 0x07;
+const REG_MONTH:   u8 = 0x08;
+const REG_YEAR:    u8 = 0x09;
+const REG_A:       u8 = 0x0A;
+const REG_B:       u8 = 0x0B;
+/// Siglo: no es estándar, pero QEMU/Bochs y la mayoría de BIOS lo usan.
+const REG_CENTURY: u8 = 0x32;
+
+const A_UIP:    u8 = 0x80;
+const B_SET:    u8 = 0x80;
+const B_24H:    u8 = 0x02;
+const B_BINARY: u8 = 0x04;
+
+#[inline(always)]
+unsafe fn outb(port: u16, val: u8) {
+    core::arch::asm!("out dx, al", in("dx") port, in("al") val, options(nostack, nomem));
+}
+#[inline(always)]
+unsafe fn inb(port: u16) -> u8 {
+    let v: u8;
+    core::arch::asm!("in al, dx", out("al") v, in("dx") port, options(nostack, nomem));
+    v
+}
+
+fn read_reg(reg: u8) -> u8 {
+    unsafe { outb(CMOS_ADDR, reg); inb(CMOS_DATA) }
+}
+fn write_reg(reg: u8, val: u8) {
+    unsafe { outb(CMOS_ADDR, reg); outb(CMOS_DATA, val); }
+}
+
+fn from_bcd(v: u8) -> u8 { (v & 0x0F) + (v >> 4) * 10 }
+fn to_bcd(v: u8)   -> u8 { ((v / 10) << 4) | (v % 10) }
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year:   u16,
+    pub month:  u8,
+    pub day:    u8,
+    pub hour:   u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+fn is_leap(y: u16) -> bool { (y.is_multiple_of(4) && !y.is_multiple_of(100)) || y.is_multiple_of(400) }
+
+pub fn days_in_month(y: u16, m: u8) -> u8 {
+    match m {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 => if is_leap(y) { 29 } else { 28 },
+        _ => 0,
+    }
+}
+
+impl DateTime {
+    /// Rangos que el CMOS puede representar (siglo incluido: 1900-2099).
+    pub fn is_valid(&self) -> bool {
+        (1900..=2099).contains(&self.year)
+            && (1..=12).contains(&self.month)
+            && self.day >= 1 && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24 && self.minute < 60 && self.second < 60
+    }
+
+    /// Día de la semana 1 = domingo .. 7 = sábado (formato del registro 6).
+    pub fn weekday(&self) -> u8 {
+        // Método de Sakamoto
+        const T: [u16; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let y = if self.month < 3 { self.year - 1 } else { self.year };
+        let d = (y + y / 4 - y / 100 + y / 400 + T[self.month as usize - 1] + self.day as u16) % 7;
+        d as u8 + 1
+    }
+}
+
+fn update_in_progress() -> bool { read_reg(REG_A) & A_UIP != 0 }
+
+fn read_raw() -> [u8; 7] {
+    let mut spins = 100_000u32;
+    while update_in_progress() && spins > 0 { spins -= 1; }
+    [
+        read_reg(REG_SEC), read_reg(REG_MIN), read_reg(REG_HOUR),
+        read_reg(REG_DAY), read_reg(REG_MONTH), read_reg(REG_YEAR),
+        read_reg(REG_CENTURY),
+    ]
+}
+
+/// Fecha y hora actuales del RTC.
+pub fn read() -> DateTime {
+    let mut raw = read_raw();
+    for _ in 0..8 {
+        let again = read_raw();
+        if again == raw { break; }
+        raw = again;
+    }
+    let b = read_reg(REG_B);
+    let bin = b & B_BINARY != 0;
+    let conv = |v: u8| if bin { v } else { from_bcd(v) };
+
+    let pm = b & B_24H == 0 && raw[2] & 0x80 != 0;
+    let mut hour = conv(raw[2] & 0x7F);
+    if b & B_24H == 0 {
+        hour %= 12;
+        if pm { hour += 12; }
+    }
+    let century = match conv(raw[6]) { c @ 19..=20 => c as u16, _ => 20 };
+
+    DateTime {
+        year:   century * 100 + conv(raw[5]) as u16,
+        month:  conv(raw[4]),
+        day:    conv(raw[3]),
+        hour,
+        minute: conv(raw[1]),
+        second: conv(raw[0]),
+    }
+}
+
+/// Escribe fecha y hora. Falla sin tocar el chip si algún campo está fuera
+/// de rango.
+pub fn write(dt: &DateTime) -> Result<(), &'static str> {
+    if !dt.is_valid() { return Err("fecha/hora fuera de rango"); }
+
+    let b   = read_reg(REG_B);
+    let bin = b & B_BINARY != 0;
+    let enc = |v: u8| if bin { v } else { to_bcd(v) };
+
+    let hour = if b & B_24H != 0 {
+        enc(dt.hour)
+    } else {
+        let h12 = match dt.hour % 12 { 0 => 12, h => h };
+        enc(h12) | if dt.hour >= 12 { 0x80 } else { 0 }
+    };
+
+    // Congelar las actualizaciones mientras se escriben todos los campos
+    write_reg(REG_B, b | B_SET);
+    write_reg(REG_SEC,     enc(dt.second));
+    write_reg(REG_MIN,     enc(dt.minute));
+    write_reg(REG_HOUR,    hour);
+    write_reg(REG_WDAY,    enc(dt.weekday()));
+    write_reg(REG_DAY,     enc(dt.day));
+    write_reg(REG_MONTH,   enc(dt.month));
+    write_reg(REG_YEAR,    enc((dt.year % 100) as u8));
+    write_reg(REG_CENTURY, enc((dt.year / 100) as u8));
+    write_reg(REG_B, b & !B_SET);
+    Ok(())
+}
@@ -0,0 +1,160 @@
+// kernel/src/time/timer.rs — PORTIX temporizadores de cuenta atrás (`timer`)
+//
+// Hasta TIMER_MAX temporizadores simultáneos sobre los ticks del PIT. No hay
+// IRQ propia: el loop principal llama a `poll()` en cada vuelta, que avisa
+// de los vencidos y de cuándo cambia el segundo mostrado en la barra de
+// estado. La alarma usa el altavoz (PIT canal 2) sin bloquear: `poll()` lo
+// apaga al pasar ALARM_TICKS.
+//
+// El apagado programado (`poweroff <seg>`) es una cuenta atrás aparte: no
+// ocupa hueco de temporizador ni lo cancela `timer cancel`.
+#![allow(dead_code)]
+
+use super::pit::{self, PIT_HZ};
+
+pub const TIMER_MAX: usize = 4;
+/// Duración máxima de un temporizador: 24 h.
+pub const TIMER_MAX_SECS: u32 = 24 * 3600;
+
+const ALARM_HZ:    u32 = 880;
+const ALARM_TICKS: u64 = 40;
+
+#[derive(Clone, Copy)]
+pub struct Timer {
+    pub id:       u32,
+    pub secs:     u32,
+    pub deadline: u64,
+}
+
+impl Timer {
+    /// Segundos que faltan, redondeando hacia arriba (0 = vencido).
+    pub fn remaining_secs(&self, now: u64) -> u32 {
+        let left = self.deadline.saturating_sub(now);
+        left.div_ceil(PIT_HZ as u64) as u32
+    }
+}
+
+pub enum TimerEvent {
+    None,
+    /// Cambió el segundo del temporizador más próximo: redibujar.
+    Changed,
+    Expired(Timer),
+    /// Venció el apagado programado.
+    Shutdown,
+}
+
+struct TimerSet {
+    slots:      [Option<Timer>; TIMER_MAX],
+    next_id:    u32,
+    shown_secs: u32,
+    alarm_off:  u64,
+    shutdown:   Option<u64>,
+    shown_off:  u32,
+}
+
+static mut TIMERS: TimerSet = TimerSet {
+    slots: [None; TIMER_MAX], next_id: 1, shown_secs: 0, alarm_off: 0,
+    shutdown: None, shown_off: 0,
+};
+
+fn set() -> &'static mut TimerSet {
+    // SAFETY: kernel monohilo; solo el loop principal y los comandos del
+    // terminal (llamados desde él) tocan los temporizadores.
+    unsafe { &mut *core::ptr::addr_of_mut!(TIMERS) }
+}
+
+/// Arranca un temporizador de `secs` segundos. None si no queda hueco.
+pub fn start(secs: u32) -> Option<Timer> {
+    let s    = set();
+    let slot = s.slots.iter().position(|t| t.is_none())?;
+    let t = Timer {
+        id: s.next_id,
+        secs,
+        deadline: pit::ticks() + secs as u64 * PIT_HZ as u64,
+    };
+    s.next_id += 1;
+    s.slots[slot] = Some(t);
+    Some(t)
+}
+
+/// Cancela el temporizador `id`. false si no existe.
+pub fn cancel(id: u32) -> bool {
+    match set().slots.iter_mut().find(|t| t.is_some_and(|t| t.id == id)) {
+        Some(slot) => { *slot = None; true }
+        None       => false,
+    }
+}
+
+/// Cancela todos; devuelve cuántos había.
+pub fn cancel_all() -> usize {
+    let s = set();
+    let n = s.slots.iter().filter(|t| t.is_some()).count();
+    s.slots = [None; TIMER_MAX];
+    n
+}
+
+/// Temporizadores activos, ordenados por vencimiento.
+pub fn active() -> ([Timer; TIMER_MAX], usize) {
+    let mut out = [Timer { id: 0, secs: 0, deadline: 0 }; TIMER_MAX];
+    let mut n = 0;
+    for t in set().slots.iter().flatten() {
+        out[n] = *t;
+        n += 1;
+    }
+    out[..n].sort_unstable_by_key(|t| t.deadline);
+    (out, n)
+}
+
+/// El más próximo a vencer y cuántos hay activos.
+pub fn soonest() -> Option<(Timer, usize)> {
+    let (list, n) = active();
+    if n == 0 { None } else { Some((list[0], n)) }
+}
+
+/// Programa el apagado dentro de `secs` segundos (sustituye al anterior).
+/// Devuelve true si ya había uno programado.
+pub fn schedule_shutdown(secs: u32) -> bool {
+    let s = set();
+    let had = s.shutdown.is_some();
+    s.shutdown = Some(pit::ticks() + secs as u64 * PIT_HZ as u64);
+    had
+}
+
+/// Cancela el apagado programado. false si no había ninguno.
+pub fn cancel_shutdown() -> bool {
+    set().shutdown.take().is_some()
+}
+
+/// Segundos que faltan para el apagado programado, redondeando hacia arriba.
+pub fn shutdown_remaining(now: u64) -> Option<u32> {
+    set().shutdown.map(|d| d.saturating_sub(now).div_ceil(PIT_HZ as u64) as u32)
+}
+
+/// Llamar en cada vuelta del loop principal. Devuelve un vencido por
+/// llamada (los demás salen en las siguientes).
+pub fn poll(now: u64) -> TimerEvent {
+    let s = set();
+    if s.alarm_off != 0 && now >= s.alarm_off {
+        pit::speaker_off();
+        s.alarm_off = 0;
+    }
+    if s.shutdown.is_some_and(|d| now >= d) {
+        s.shutdown = None;
+        return TimerEvent::Shutdown;
+    }
+    if let Some(slot) = s.slots.iter_mut().find(|t| t.is_some_and(|t| now >= t.deadline)) {
+        let t = slot.take().unwrap();
+        pit::speaker_on(ALARM_HZ);
+        s.alarm_off = now + ALARM_TICKS;
+        return TimerEvent::Expired(t);
+    }
+    let shown = soonest().map_or(0, |(t, _)| t.remaining_secs(now));
+    let off   = shutdown_remaining(now).unwrap_or(0);
+    let s = set();
+    if shown != s.shown_secs || off != s.shown_off {
+        s.shown_secs = shown;
+        s.shown_off  = off;
+        return TimerEvent::Changed;
+    }
+    TimerEvent::None
+}
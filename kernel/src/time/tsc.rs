@@ -0,0 +1,112 @@
+// kernel/src/time/tsc.rs — PORTIX Time Stamp Counter (benchmarks)
+//
+// RDTSC cuenta ciclos con resolución de nanosegundos; el PIT (100 Hz) solo
+// sirve para calibrarlo. `calibrate` mide los ciclos entre flancos de IRQ0,
+// así que necesita las interrupciones ya habilitadas. En CPUs sin TSC
+// invariante (CPUID 8000_0007h EDX.8) la frecuencia cambia con los estados
+// de energía y las conversiones a tiempo son solo orientativas.
+
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use super::pit;
+
+/// Ticks del PIT usados para calibrar (50 ms).
+const CALIBRATE_TICKS: u64 = 5;
+/// Ciclos máximos esperando un tick: sin IRQ0 no se calibra (~5 s a 4 GHz).
+const TICK_TIMEOUT: u64 = 20_000_000_000;
+
+/// Frecuencia medida en Hz; 0 = sin TSC o sin calibrar.
+static HZ: AtomicU64 = AtomicU64::new(0);
+static INVARIANT: AtomicBool = AtomicBool::new(false);
+
+#[inline(always)]
+pub fn read() -> u64 {
+    let (lo, hi): (u32, u32);
+    unsafe { core::arch::asm!("rdtsc", out("eax") lo, out("edx") hi, options(nostack, nomem)); }
+    (hi as u64) << 32 | lo as u64
+}
+
+fn cpuid_edx(leaf: u32) -> u32 {
+    core::arch::x86_64::__cpuid(leaf).edx
+}
+
+/// CPUID.1:EDX.4 — la CPU tiene RDTSC.
+pub fn present() -> bool { cpuid_edx(1) & (1 << 4) != 0 }
+
+/// TSC de frecuencia constante (se comprueba en `calibrate`).
+pub fn invariant() -> bool { INVARIANT.load(Ordering::Relaxed) }
+
+pub fn hz() -> u64 { HZ.load(Ordering::Relaxed) }
+pub fn mhz() -> u32 { (hz() / 1_000_000) as u32 }
+
+/// Microsegundos que dura `cycles` a `hz` ciclos por segundo (0 si hz = 0).
+pub fn cycles_to_us(cycles: u64, hz: u64) -> u64 {
+    if hz == 0 { return 0; }
+    (cycles as u128 * 1_000_000 / hz as u128) as u64
+}
+
+/// Espera al siguiente flanco de IRQ0; devuelve el TSC en ese instante.
+fn next_tick_edge() -> Option<u64> {
+    let (t0, start) = (pit::ticks(), read());
+    loop {
+        let now = read();
+        if pit::ticks() != t0 { return Some(now); }
+        if now.wrapping_sub(start) > TICK_TIMEOUT { return None; }
+        core::hint::spin_loop();
+    }
+}
+
+/// Mide la frecuencia del TSC contra CALIBRATE_TICKS ticks del PIT.
+/// Devuelve los Hz medidos (0 si no hay TSC o el PIT no avanza).
+pub fn calibrate() -> u64 {
+    use crate::drivers::serial::{self, Level};
+    if !present() {
+        serial::log_level(Level::Warn, "TSC", "CPU sin RDTSC: bench sin tiempos");
+        return 0;
+    }
+    let max_ext = core::arch::x86_64::__cpuid(0x8000_0000).eax;
+    INVARIANT.store(max_ext >= 0x8000_0007 && cpuid_edx(0x8000_0007) & (1 << 8) != 0, Ordering::Relaxed);
+
+    let Some(c0) = next_tick_edge() else {
+        serial::log_level(Level::Warn, "TSC", "IRQ0 no avanza: TSC sin calibrar");
+        return 0;
+    };
+    let t0 = pit::ticks();
+    while pit::ticks().wrapping_sub(t0) < CALIBRATE_TICKS - 1 { core::hint::spin_loop(); }
+    let Some(c1) = next_tick_edge() else { return 0 };
+    let hz = c1.wrapping_sub(c0) * pit::PIT_HZ as u64 / CALIBRATE_TICKS;
+    HZ.store(hz, Ordering::Relaxed);
+    serial::log_level(
+        if invariant() { Level::Ok } else { Level::Warn }, "TSC",
+        if invariant() { "calibrado contra el PIT (invariante)" }
+        else           { "calibrado contra el PIT (NO invariante: tiempos aproximados)" },
+    );
+    hz
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_to_us_at_common_rates() {
+        assert_eq!(cycles_to_us(3_000_000_000, 3_000_000_000), 1_000_000);
+        assert_eq!(cycles_to_us(2_400, 2_400_000_000), 1);
+        // Se trunca: 2399 ciclos a 2.4 GHz no llegan a 1 µs
+        assert_eq!(cycles_to_us(2_399, 2_400_000_000), 0);
+        assert_eq!(cycles_to_us(0, 1_000_000), 0);
+    }
+
+    #[test]
+    fn cycles_to_us_does_not_overflow() {
+        // cycles × 10^6 no cabe en u64: el cálculo va en u128
+        assert_eq!(cycles_to_us(u64::MAX, 1_000_000), u64::MAX);
+        assert_eq!(cycles_to_us(1 << 60, 4_000_000_000), (1u64 << 60) / 4_000);
+    }
+
+    #[test]
+    fn uncalibrated_tsc_gives_zero() {
+        assert_eq!(cycles_to_us(123_456, 0), 0);
+    }
+}
+
@@ -0,0 +1,115 @@
+// ui/boot_progress.rs — Pantalla de progreso del escaneo de hardware
+//
+// Antes del loop principal los escaneos de discos (ATA, AHCI) y del bus PCI
+// pueden tardar segundos en hardware real por los timeouts de IDENTIFY. Este
+// panel muestra la etapa y el bus/dispositivo que se está probando para que
+// el arranque no parezca colgado. Los drivers solo reciben un closure.
+//
+// Coste: PCI prueba 256 × 32 ranuras, así que su detalle se redibuja como
+// mucho una vez por tick del PIT (10 ms). Las pruebas de discos son pocas y
+// cada una puede bloquear mucho, así que se dibujan siempre. En ambos casos
+// solo se repinta la franja de detalle y present() copia ese rectángulo.
+
+use crate::graphics::driver::framebuffer::{Color, Console};
+use crate::time::pit;
+
+const PANEL_W: usize = 440;
+const PANEL_H: usize = 92;
+const BAR_H:   usize = 6;
+
+const PANEL_BG:  Color = Color::new(4, 14, 30);
+const PANEL_BOR: Color = Color::new(20, 60, 100);
+
+/// Etapas del escaneo: discos ATA, bus PCI, puertos AHCI, canal ATA PIO.
+pub const STAGES: u32 = 4;
+
+pub struct BootProgress<'a> {
+    c:         &'a mut Console,
+    px:        usize,
+    py:        usize,
+    stage:     u32,
+    last_tick: u64,
+}
+
+impl<'a> BootProgress<'a> {
+    pub fn new(c: &'a mut Console) -> Self {
+        c.clear(Color::PORTIX_BG);
+        let px = c.width().saturating_sub(PANEL_W) / 2;
+        let py = c.height().saturating_sub(PANEL_H) / 2;
+        c.fill_rounded(px, py, PANEL_W, PANEL_H, 6, PANEL_BG);
+        c.draw_rect(px, py, PANEL_W, PANEL_H, 1, PANEL_BOR);
+        c.write_at("PORTIX  -  detectando hardware", px + 14, py + 12, Color::PORTIX_GOLD);
+        c.hline(px + 10, py + 28, PANEL_W - 20, Color::SEP_BRIGHT);
+        c.present_full();
+        BootProgress { c, px, py, stage: 0, last_tick: 0 }
+    }
+
+    /// Empieza una etapa: título, detalle en blanco y barra avanzada.
+    pub fn stage(&mut self, name: &str) {
+        let (px, py) = (self.px, self.py);
+        self.c.fill_rect(px + 14, py + 36, PANEL_W - 28, 12, PANEL_BG);
+        self.c.write_at(name, px + 14, py + 36, Color::WHITE);
+        self.c.fill_rect(px + 14, py + 54, PANEL_W - 28, 12, PANEL_BG);
+        let pct = self.stage * 100 / STAGES;
+        self.c.progress_bar(px + 14, py + 74, PANEL_W - 28, BAR_H, pct,
+                            Color::TEAL, Color::PORTIX_BG, PANEL_BOR);
+        self.stage += 1;
+        self.c.present();
+    }
+
+    /// Marca el final del escaneo (barra completa).
+    pub fn finish(&mut self) {
+        self.stage = STAGES;
+        self.stage("Hardware detectado");
+    }
+
+    /// Ranura PCI que se va a probar (limitado a un redibujo por tick).
+    pub fn pci(&mut self, bus: u8, dev: u8) {
+        let now = pit::ticks();
+        if now == self.last_tick { return; }
+        self.last_tick = now;
+        let mut b = [0u8; 32]; let mut p = 0;
+        push(&mut b, &mut p, b"bus ");
+        push_hex2(&mut b, &mut p, bus);
+        push(&mut b, &mut p, b"  dispositivo ");
+        push_hex2(&mut b, &mut p, dev);
+        self.detail(&b[..p]);
+    }
+
+    /// Unidad ATA legacy a punto de recibir IDENTIFY.
+    pub fn ata(&mut self, bus: u8, drive: u8) {
+        let mut b = [0u8; 32]; let mut p = 0;
+        push(&mut b, &mut p, if bus == 0 { b"ATA0 " } else { b"ATA1 " });
+        push(&mut b, &mut p, if drive == 0 { b"maestro" } else { b"esclavo" });
+        push(&mut b, &mut p, b"  (IDENTIFY)");
+        self.detail(&b[..p]);
+    }
+
+    /// Puerto AHCI a punto de recibir IDENTIFY.
+    pub fn ahci(&mut self, port: u8) {
+        let mut b = [0u8; 32]; let mut p = 0;
+        push(&mut b, &mut p, b"SATA puerto ");
+        if port >= 10 { push(&mut b, &mut p, &[b'0' + port / 10]); }
+        push(&mut b, &mut p, &[b'0' + port % 10]);
+        push(&mut b, &mut p, b"  (IDENTIFY)");
+        self.detail(&b[..p]);
+    }
+
+    fn detail(&mut self, text: &[u8]) {
+        let (px, py) = (self.px, self.py);
+        self.c.fill_rect(px + 14, py + 54, PANEL_W - 28, 12, PANEL_BG);
+        self.c.write_at(core::str::from_utf8(text).unwrap_or(""), px + 14, py + 54, Color::LIGHT_GRAY);
+        self.c.present();
+    }
+}
+
+fn push(b: &mut [u8], p: &mut usize, s: &[u8]) {
+    let n = s.len().min(b.len() - *p);
+    b[*p..*p + n].copy_from_slice(&s[..n]);
+    *p += n;
+}
+
+fn push_hex2(b: &mut [u8], p: &mut usize, v: u8) {
+    const H: &[u8] = b"0123456789ABCDEF";
+    push(b, p, &[H[(v >> 4) as usize], H[(v & 0xF) as usize]]);
+}
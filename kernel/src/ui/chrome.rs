@@ -1,10 +1,14 @@
-// ui/chrome.rs — PORTIX Kernel v0.9.0
+// ui/chrome.rs — PORTIX Kernel
 //
 // CORRECCIONES:
 //   - Bug línea ~200: usize::<MAX> era inválido. Ahora usa usize::MAX correctamente.
 //   - Footer: 3 zonas fijas (L/C/R), sin colisión posible.
 //
 // DISEÑO: Cyberpunk neon — amarillo eléctrico + cian + verde neon.
+//
+// Menú de energía: botón "ENERGIA" en la esquina derecha de la cabecera con
+// un desplegable Apagar/Reiniciar que pide confirmación. El estado vive en
+// main (PowerMenu); aquí solo geometría, hit-test y dibujo.
 
 use crate::graphics::driver::framebuffer::{Color, Console, Layout};
 use crate::arch::hardware::HardwareInfo;
@@ -98,6 +102,131 @@ pub fn section_label(c: &mut Console, x: usize, y: usize, title: &str, w: usize)
     c.write_at(title, x + 7, y + 3, Pal::YELLOW);
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Menú de energía
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PowerAction { Shutdown, Reboot }
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PowerMenu { Closed, Open, Confirm(PowerAction) }
+
+/// Resultado de un clic respecto al menú de energía.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PowerHit {
+    Button,
+    Item(PowerAction),
+    Yes,
+    No,
+    /// Dentro del desplegable pero en ningún elemento activo.
+    Inside,
+    Outside,
+}
+
+const POWER_LABEL:  &str  = "ENERGIA";
+const POWER_ITEMS:  [(&str, PowerAction); 2] = [
+    ("Apagar",    PowerAction::Shutdown),
+    ("Reiniciar", PowerAction::Reboot),
+];
+const POWER_ITEM_H: usize = 18;
+/// Ancho del desplegable en caracteres (cabe "Reiniciar el sistema?").
+const POWER_MENU_COLS: usize = 24;
+
+fn power_question(a: PowerAction) -> &'static str {
+    match a {
+        PowerAction::Shutdown => "Apagar el sistema?",
+        PowerAction::Reboot   => "Reiniciar el sistema?",
+    }
+}
+
+/// (x, y, w, h) del botón de la cabecera.
+pub fn power_button_rect(lay: &Layout) -> (usize, usize, usize, usize) {
+    let h = lay.font_h + 8;
+    let w = POWER_LABEL.len() * lay.font_w + 16;
+    (lay.fw.saturating_sub(10 + w), lay.header_h.saturating_sub(h) / 2, w, h)
+}
+
+/// (x, y, w, h) del desplegable, alineado al borde derecho del botón.
+fn power_menu_rect(lay: &Layout, menu: PowerMenu) -> (usize, usize, usize, usize) {
+    let (bx, by, bw, bh) = power_button_rect(lay);
+    let w = POWER_MENU_COLS * lay.font_w + 16;
+    let h = match menu {
+        PowerMenu::Confirm(_) => POWER_ITEM_H * 2 + 12,
+        _                     => POWER_ITEM_H * POWER_ITEMS.len() + 6,
+    };
+    ((bx + bw).saturating_sub(w), by + bh + 4, w, h)
+}
+
+/// Botones Sí / No de la confirmación: (x, y, w, h) de cada uno.
+fn power_confirm_buttons(lay: &Layout, menu: PowerMenu) -> [(usize, usize, usize, usize); 2] {
+    let (x, y, w, _) = power_menu_rect(lay, menu);
+    let bw = 6 * lay.font_w;
+    let by = y + POWER_ITEM_H + 6;
+    let no_x = x + w - 8 - bw;
+    [(no_x.saturating_sub(bw + 8), by, bw, POWER_ITEM_H - 2), (no_x, by, bw, POWER_ITEM_H - 2)]
+}
+
+fn in_rect(mx: i32, my: i32, (x, y, w, h): (usize, usize, usize, usize)) -> bool {
+    if mx < 0 || my < 0 { return false; }
+    let (px, py) = (mx as usize, my as usize);
+    px >= x && px < x + w && py >= y && py < y + h
+}
+
+pub fn power_menu_hit(lay: &Layout, menu: PowerMenu, mx: i32, my: i32) -> PowerHit {
+    if in_rect(mx, my, power_button_rect(lay)) { return PowerHit::Button; }
+    let rect = power_menu_rect(lay, menu);
+    match menu {
+        PowerMenu::Closed => PowerHit::Outside,
+        _ if !in_rect(mx, my, rect) => PowerHit::Outside,
+        PowerMenu::Open => {
+            let i = (my as usize).saturating_sub(rect.1 + 3) / POWER_ITEM_H;
+            match POWER_ITEMS.get(i) {
+                Some(&(_, a)) => PowerHit::Item(a),
+                None          => PowerHit::Inside,
+            }
+        }
+        PowerMenu::Confirm(_) => {
+            let [yes, no] = power_confirm_buttons(lay, menu);
+            if in_rect(mx, my, yes)     { PowerHit::Yes }
+            else if in_rect(mx, my, no) { PowerHit::No }
+            else                        { PowerHit::Inside }
+        }
+    }
+}
+
+/// Desplegable por encima de la pestaña activa (llamar tras dibujarla).
+pub fn draw_power_menu(c: &mut Console, lay: &Layout, menu: PowerMenu, mx: i32, my: i32) {
+    if menu == PowerMenu::Closed { return; }
+    let (x, y, w, h) = power_menu_rect(lay, menu);
+    let cw = lay.font_w;
+    let ty = (POWER_ITEM_H - lay.font_h) / 2;
+    c.fill_rounded(x, y, w, h, 3, Pal::PANEL);
+    c.draw_rect(x, y, w, h, 1, Pal::BOR_WARM);
+
+    match menu {
+        PowerMenu::Confirm(a) => {
+            c.write_at(power_question(a), x + 8, y + 4 + ty, Pal::TXT_BRIGHT);
+            let [yes, no] = power_confirm_buttons(lay, menu);
+            for (r, label, fg) in [(yes, "Si", Color::RED), (no, "No", Pal::TXT_MID)] {
+                let hov = in_rect(mx, my, r);
+                c.fill_rounded(r.0, r.1, r.2, r.3, 2, if hov { Pal::TAB_HOV } else { Pal::RAISED });
+                c.draw_rect(r.0, r.1, r.2, r.3, 1, if hov { fg } else { Pal::BOR_COLD });
+                c.write_at(label, r.0 + (r.2 - label.len() * cw) / 2, r.1 + (r.3 - lay.font_h) / 2, fg);
+            }
+        }
+        _ => {
+            for (i, &(label, a)) in POWER_ITEMS.iter().enumerate() {
+                let iy = y + 3 + i * POWER_ITEM_H;
+                let hov = power_menu_hit(lay, menu, mx, my) == PowerHit::Item(a);
+                if hov { c.fill_rect(x + 2, iy, w - 4, POWER_ITEM_H, Pal::TAB_HOV); }
+                let fg = if a == PowerAction::Shutdown { Color::RED } else { Pal::GOLD };
+                c.write_at(label, x + 10, iy + ty, if hov { fg } else { Pal::TXT_MID });
+            }
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // draw_chrome
 // ─────────────────────────────────────────────────────────────────────────────
@@ -107,6 +236,7 @@ pub fn draw_chrome(
     lay:    &Layout,
     hw:     &HardwareInfo,
     active: Tab,
+    power:  PowerMenu,
     mx:     i32,
     my:     i32,
 ) {
@@ -127,7 +257,8 @@ pub fn draw_chrome(
     // Logo PORTIX con glow
     let logo_y = (hh.saturating_sub(18)) / 2;
     write_glow(c, "PORTIX", 13, logo_y, Pal::YELLOW, Color::new(0x44, 0x28, 0x00));
-    c.write_at("v0.9.0", 13, logo_y + 13, Pal::TXT_DIM);
+    c.write_at("v", 13, logo_y + 13, Pal::TXT_DIM);
+    c.write_at(crate::VERSION, 13 + cw, logo_y + 13, Pal::TXT_DIM);
 
     // Separador vertical
     c.vline(82, 8, hh - 16, Pal::BOR_SEP);
@@ -145,10 +276,10 @@ pub fn draw_chrome(
     // Badges derecha — calculados desde la derecha para evitar overflow
     let badge_y  = (hh.saturating_sub(ch + 8)) / 2;
     let gap      = 4usize;
-    let margin_r = 10usize;
     let bw_boot  = "BOOT OK".len() * cw + 16;
     let bw_arch  = "x86_64".len()  * cw + 16;
-    let bx_boot  = fw.saturating_sub(margin_r + bw_boot);
+    let (bx_pwr, ..) = power_button_rect(lay);
+    let bx_boot  = bx_pwr.saturating_sub(gap + bw_boot);
     let bx_arch  = bx_boot.saturating_sub(gap + bw_arch);
 
     neon_badge(c, bx_arch, badge_y, "x86_64",
@@ -156,6 +287,13 @@ pub fn draw_chrome(
     neon_badge(c, bx_boot, badge_y, "BOOT OK",
                Pal::GREEN_NEO, Pal::GREEN_DIM, Color::new(0x00, 0x77, 0x44), cw, ch);
 
+    let pwr_hot = power != PowerMenu::Closed
+        || power_menu_hit(lay, power, mx, my) == PowerHit::Button;
+    neon_badge(c, bx_pwr, badge_y, POWER_LABEL,
+               if pwr_hot { Pal::YELLOW } else { Pal::GOLD },
+               if pwr_hot { Pal::YELLOW_DIM } else { Pal::RAISED },
+               if pwr_hot { Pal::YELLOW } else { Pal::BOR_WARM }, cw, ch);
+
     c.hline(0, hh - 1, fw, Pal::BOR_SEP);
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -236,21 +374,35 @@ pub fn draw_chrome(
     let mut bmy = [0u8; 16];
     let mxs = fmt_u32(mx.max(0) as u32, &mut bmx);
     let mys = fmt_u32(my.max(0) as u32, &mut bmy);
-    // Ancho texto: "XY:" + mxs + "," + mys
-    let xy_chars  = 3 + mxs.len() + 1 + mys.len();
-    let zone_r_w  = xy_chars * cw + 18; // 9px padding por lado
+    // Bloqueos del teclado: "MAY NUM DES " delante de XY, encendidos en verde
+    const LOCKS: [(&str, u8); 3] = [
+        ("MAY", crate::drivers::input::keyboard::LED_CAPS),
+        ("NUM", crate::drivers::input::keyboard::LED_NUM),
+        ("DES", crate::drivers::input::keyboard::LED_SCROLL),
+    ];
+    let locks      = crate::drivers::input::keyboard::locks();
+    let lock_chars = LOCKS.len() * 4;
+    // Ancho texto: bloqueos + "XY:" + mxs + "," + mys — fuente compacta 6×8
+    let sw        = lay.small_w;
+    let xy_chars  = lock_chars + 3 + mxs.len() + 1 + mys.len();
+    let zone_r_w  = lay.small_text_w(xy_chars) + 18; // 9px padding por lado
     let zone_r_x  = fw.saturating_sub(zone_r_w);
 
     // Cápsula zona R
     c.fill_rect(zone_r_x, bar_top, zone_r_w, bar_h, Pal::RAISED);
     c.vline(zone_r_x, bar_top, bar_h, Pal::BOR_SEP);
 
+    for (i, &(label, bit)) in LOCKS.iter().enumerate() {
+        c.write_at_small(label, zone_r_x + 9 + i * 4 * sw, ty_txt,
+                         if locks & bit != 0 { Pal::GREEN_NEO } else { Pal::BOR_SEP });
+    }
+
     // Texto XY
-    let xy_x = zone_r_x + 9;
-    c.write_at("XY:", xy_x, ty_txt, Pal::TXT_DIM);
-    c.write_at(mxs, xy_x + 3 * cw, ty_txt, Pal::TXT_MID);
-    c.write_at(",",  xy_x + (3 + mxs.len()) * cw, ty_txt, Pal::BOR_WARM);
-    c.write_at(mys, xy_x + (4 + mxs.len()) * cw, ty_txt, Pal::TXT_MID);
+    let xy_x = zone_r_x + 9 + lock_chars * sw;
+    c.write_at_small("XY:", xy_x, ty_txt, Pal::TXT_DIM);
+    c.write_at_small(mxs, xy_x + 3 * sw, ty_txt, Pal::TXT_MID);
+    c.write_at_small(",",  xy_x + (3 + mxs.len()) * sw, ty_txt, Pal::BOR_WARM);
+    c.write_at_small(mys, xy_x + (4 + mxs.len()) * sw, ty_txt, Pal::TXT_MID);
 
     // ── ZONA L (izquierda fija) ───────────────────────────────────────────
     let mut lx = 10usize;
@@ -268,6 +420,49 @@ pub fn draw_chrome(
     c.write_at("LISTO", lx, ty_txt, Pal::GREEN_NEO);
     lx += "LISTO".len() * cw + 10; // +10 holgura
 
+    // Dispositivos de entrada no detectados (main reintenta periódicamente)
+    {
+        use crate::drivers::registry::{state, State, Subsystem};
+        for (sub, label) in [(Subsystem::Keyboard, "SIN TECLADO"), (Subsystem::Mouse, "SIN RATON")] {
+            if state(sub) != State::Missing { continue; }
+            c.write_at(">", lx, ty_txt, Pal::BOR_WARM);
+            lx += cw + 6;
+            c.write_at(label, lx, ty_txt, Color::RED);
+            lx += label.len() * cw + 10;
+        }
+    }
+
+    // Apagado programado (`poweroff <seg>`)
+    if let Some(left) = crate::time::timer::shutdown_remaining(crate::time::pit::ticks()) {
+        let mut tb = [0u8; 16]; let mut tl = 0;
+        push_clock(&mut tb, &mut tl, left);
+        let ts = core::str::from_utf8(&tb[..tl]).unwrap_or("");
+        c.write_at(">", lx, ty_txt, Pal::BOR_WARM);
+        lx += cw + 6;
+        c.write_at("APAGADO", lx, ty_txt, Color::RED);
+        lx += 8 * cw;
+        c.write_at(ts, lx, ty_txt, if left <= 10 { Color::RED } else { Pal::TXT_BRIGHT });
+        lx += tl * cw + 10;
+    }
+
+    // Temporizador más próximo (`timer`), con "+N" si hay más
+    if let Some((tm, n)) = crate::time::timer::soonest() {
+        let left = tm.remaining_secs(crate::time::pit::ticks());
+        let mut tb = [0u8; 24]; let mut tl = 0;
+        push_clock(&mut tb, &mut tl, left);
+        if n > 1 {
+            tb[tl] = b' '; tb[tl + 1] = b'+'; tl += 2;
+            push_num(&mut tb, &mut tl, (n - 1) as u32, 1);
+        }
+        let ts = core::str::from_utf8(&tb[..tl]).unwrap_or("");
+        c.write_at(">", lx, ty_txt, Pal::BOR_WARM);
+        lx += cw + 6;
+        c.write_at("TIMER", lx, ty_txt, Pal::TXT_DIM);
+        lx += 6 * cw;
+        c.write_at(ts, lx, ty_txt, if left <= 10 { Pal::YELLOW } else { Pal::TXT_BRIGHT });
+        lx += tl * cw + 10;
+    }
+
     let zone_l_end = lx;
 
     // ── ZONA C (flexible — aparece solo si hay espacio) ───────────────────
@@ -301,4 +496,24 @@ pub fn draw_chrome(
         c.write_at(ram_str, cx + 4 * cw, ty_txt, Pal::GOLD);
     }
     // Si no cabe nada en C → zona vacía, sin colisión.
-}
\ No newline at end of file
+}
+
+/// `[h:]mm:ss`
+fn push_clock(buf: &mut [u8], pos: &mut usize, secs: u32) {
+    if secs >= 3600 {
+        push_num(buf, pos, secs / 3600, 1);
+        buf[*pos] = b':'; *pos += 1;
+    }
+    push_num(buf, pos, (secs % 3600) / 60, 2);
+    buf[*pos] = b':'; *pos += 1;
+    push_num(buf, pos, secs % 60, 2);
+}
+
+/// Número con al menos `width` dígitos (ceros a la izquierda).
+fn push_num(buf: &mut [u8], pos: &mut usize, v: u32, width: usize) {
+    let mut nb = [0u8; 16];
+    let s = fmt_u32(v, &mut nb).as_bytes();
+    for _ in s.len()..width { buf[*pos] = b'0'; *pos += 1; }
+    buf[*pos..*pos + s.len()].copy_from_slice(s);
+    *pos += s.len();
+}
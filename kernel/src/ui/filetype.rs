@@ -0,0 +1,31 @@
+// ui/filetype.rs — PORTIX Kernel
+// Paleta única de colores por tipo de archivo, compartida por el IDE
+// (pestañas de buffers, barra de estado) y el Explorador (iconos y nombres).
+// La clave es una extensión ("rs", "h", "bmp"...) o una etiqueta de lenguaje
+// del IDE ("Rust", "C", "ASM", "TXT"); no distingue mayúsculas.
+
+use crate::graphics::driver::framebuffer::Color;
+
+pub const RUST:   Color = Color::new(0xDE, 0x6A, 0x40);
+pub const C:      Color = Color::new(0x44, 0x99, 0xFF);
+pub const ASM:    Color = Color::new(0xCC, 0xAA, 0x00);
+pub const TEXT:   Color = Color::new(0x9C, 0xBE, 0xE8);
+pub const IMAGE:  Color = Color::new(0x66, 0xCC, 0x88);
+pub const BINARY: Color = Color::new(0x66, 0x6E, 0x7A);
+
+/// Color de icono para una extensión o etiqueta de lenguaje.
+pub fn lang_color(lang_or_ext: &str) -> Color {
+    let k = lang_or_ext.as_bytes();
+    let is = |s: &str| k.eq_ignore_ascii_case(s.as_bytes());
+    if is("rs") || is("rust") { RUST }
+    else if is("c") || is("h") { C }
+    else if is("asm") || is("s") { ASM }
+    else if is("txt") || is("md") || is("toml") { TEXT }
+    else if is("bmp") || is("png") || is("raw") { IMAGE }
+    else { BINARY }
+}
+
+/// Variante clara del mismo color, para el texto de nombres de archivo.
+pub fn lang_text_color(lang_or_ext: &str) -> Color {
+    lang_color(lang_or_ext).blend(Color::WHITE, 140)
+}
@@ -2,7 +2,8 @@
 //
 // Widget de entrada de texto inline. SOLO UI — sin lógica de paths ni FAT32.
 // Usado por:
-//   IDE      → Guardar como, Ir a línea
+//   IDE      → Guardar como, Ir a línea, Buscar, Reemplazar, minibuffer (Ctrl+:),
+//              confirmar Recargar
 //   Explorer → Nueva carpeta, Nuevo archivo, Eliminar
 //
 // El rendering se hace en draw_input_overlay() para reutilizarlo en
@@ -24,6 +25,11 @@ pub enum InputMode {
     None,
     SaveAs,    // IDE: Guardar como / renombrar
     GoToLine,  // IDE: Ir a línea (número)
+    Find,        // IDE: Buscar patrón
+    ReplaceFind, // IDE: Reemplazar — patrón a buscar
+    ReplaceWith, // IDE: Reemplazar — texto nuevo (vacío = borrar)
+    Command,     // IDE: minibuffer — línea de comando del terminal
+    Reload,      // IDE: confirmar recarga (descarta cambios)
     NewDir,    // Explorer: Nueva carpeta
     NewFile,   // Explorer: Nuevo archivo
     Delete,    // Explorer: Confirmar eliminación
@@ -35,6 +41,11 @@ impl InputMode {
         match self {
             InputMode::SaveAs   => "Nombre: ",
             InputMode::GoToLine => "Ir a línea: ",
+            InputMode::Find        => "Buscar: ",
+            InputMode::ReplaceFind => "Reemplazar: ",
+            InputMode::ReplaceWith => "Reemplazar con: ",
+            InputMode::Command     => ":",
+            InputMode::Reload      => "Descartar cambios y recargar (Enter=confirmar): ",
             InputMode::NewDir   => "Nueva carpeta: ",
             InputMode::NewFile  => "Nuevo archivo: ",
             InputMode::Delete   => "Eliminar (Enter=confirmar): ",
@@ -166,7 +177,7 @@ pub fn draw_input_overlay(
 
     let ty     = sy + (bar_h.saturating_sub(ch)) / 2;
     let prompt = input.mode.prompt();
-    let bg     = if input.mode == InputMode::Delete { INPUT_BG_DELETE } else { INPUT_BG };
+    let bg     = if matches!(input.mode, InputMode::Delete | InputMode::Reload) { INPUT_BG_DELETE } else { INPUT_BG };
 
     // Prompt
     c.write_at(prompt, x0, ty, INPUT_PROMPT_FG);
@@ -1,14 +1,19 @@
-// ui/mod.rs — PORTIX Kernel v0.7.4
+// ui/mod.rs — PORTIX Kernel
 
+pub mod boot_progress;
 pub mod chrome;
 pub mod exception;
 pub mod tabs;
 pub mod input;
+pub mod filetype;
 
 // Re-exportamos para facilitar el uso desde main.rs
 pub use chrome::{section_label, draw_chrome};
 pub use tabs::{draw_system_tab, draw_terminal_tab, draw_devices_tab, draw_ide_tab, draw_explorer_tab};
-pub use tabs::terminal::terminal_hist_geometry;
+pub use tabs::terminal::{terminal_hist_geometry, terminal_layout, terminal_link_hit, terminal_split_pane};
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.9.0";
 
 /// Ancho de la barra de scroll en píxeles
 pub const SCROLLBAR_W: usize = 12;
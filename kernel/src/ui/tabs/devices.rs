@@ -1,11 +1,27 @@
 // ui/tabs/devices.rs — Pestaña DISPOSITIVOS: CPU, pantalla, disco, E/S, bus PCI
 
 use crate::graphics::driver::framebuffer::{Color, Console, Layout};
-use crate::arch::hardware::HardwareInfo;
+use crate::arch::hardware::{EdidMode, HardwareInfo};
 use crate::drivers::bus::pci::PciBus;
 use crate::util::fmt::{fmt_u32, fmt_mhz, fmt_mib, fmt_hex};
 use crate::ui::chrome::section_label;
 
+/// Añade "1920x1080" (y "@60Hz" si `hz`) a `buf` desde `pos`.
+fn push_mode(buf: &mut [u8], pos: &mut usize, m: &EdidMode, hz: bool) {
+    let mut push = |s: &[u8], pos: &mut usize| {
+        for &b in s { if *pos < buf.len() { buf[*pos] = b; *pos += 1; } }
+    };
+    let mut n = [0u8; 16];
+    push(fmt_u32(m.width as u32, &mut n).as_bytes(), pos);
+    push(b"x", pos);
+    push(fmt_u32(m.height as u32, &mut n).as_bytes(), pos);
+    if hz && m.refresh > 0 {
+        push(b" @", pos);
+        push(fmt_u32(m.refresh as u32, &mut n).as_bytes(), pos);
+        push(b"Hz", pos);
+    }
+}
+
 pub fn draw_devices_tab(
     c: &mut Console,
     lay: &Layout,
@@ -70,6 +86,53 @@ pub fn draw_devices_tab(
         c.write_at("Pitch:", c1x + 4, ry, Color::GRAY);
         c.write_at(fmt_u32(hw.display.pitch as u32, &mut bp), c1x + 56, ry, Color::WHITE);
         c.write_at("(DblBuf@0x600000)", c1x + 4, ry + lay.line_h, Color::new(28, 40, 56));
+        ry += lay.line_h * 2;
+
+        match &hw.display.edid {
+            Ok(e) => {
+                c.write_at("Monitor:", c1x + 4, ry, Color::GRAY);
+                let name = if e.name_len > 0 { e.name_str() } else { "(sin nombre)" };
+                c.write_at(name, c1x + 76, ry, Color::WHITE); ry += lay.line_h;
+
+                let mut bm = [0u8; 18]; let mut by = [0u8; 16];
+                c.write_at("Fabricante:", c1x + 4, ry, Color::GRAY);
+                c.write_at(e.manufacturer_str(), c1x + 100, ry, Color::WHITE);
+                c.write_at(fmt_hex(e.product as u64, &mut bm), c1x + 132, ry, Color::TEAL);
+                if e.year > 0 {
+                    c.write_at(fmt_u32(e.year as u32, &mut by), c1x + 228, ry, Color::LIGHT_GRAY);
+                }
+                ry += lay.line_h;
+
+                if let Some(m) = e.native {
+                    let mut b = [0u8; 24]; let mut p = 0;
+                    push_mode(&mut b, &mut p, &m, true);
+                    c.write_at("Nativa:", c1x + 4, ry, Color::GRAY);
+                    c.write_at(core::str::from_utf8(&b[..p]).unwrap_or(""), c1x + 64, ry, Color::CYAN);
+                    ry += lay.line_h;
+                }
+
+                // Modos que quepan enteros en la columna
+                let max = c1w.saturating_sub(60) / lay.font_w.max(1);
+                let mut b = [0u8; 64]; let mut p = 0;
+                for m in &e.modes[..e.mode_count] {
+                    let mut one = [0u8; 16]; let mut ol = 0;
+                    push_mode(&mut one, &mut ol, m, false);
+                    let sep = (p > 0) as usize;
+                    if p + sep + ol > max.min(b.len()) { break; }
+                    if sep == 1 { b[p] = b' '; p += 1; }
+                    b[p..p + ol].copy_from_slice(&one[..ol]); p += ol;
+                }
+                if p > 0 {
+                    c.write_at("Modos:", c1x + 4, ry, Color::GRAY);
+                    c.write_at(core::str::from_utf8(&b[..p]).unwrap_or(""), c1x + 56, ry, Color::LIGHT_GRAY);
+                }
+            }
+            Err(err) => {
+                c.write_at("EDID:", c1x + 4, ry, Color::GRAY);
+                c.write_at("no disponible", c1x + 52, ry, Color::LIGHT_GRAY); ry += lay.line_h;
+                c.write_at(err.reason(), c1x + 4, ry, Color::new(28, 40, 56));
+            }
+        }
     }
 
     // ── Columna 2: Almacenamiento + Dispositivos de entrada ───────────────
@@ -77,12 +140,14 @@ pub fn draw_devices_tab(
     let mut c2y = ry_start;
 
     section_label(c, c2x, c2y, " ALMACENAMIENTO", col_w - 8); c2y += 20;
-    for i in 0..hw.disks.count.min(4) {
+    for d in &hw.disks.drives[..hw.disks.count] {
         if c2y + lay.line_h * 2 > lay.bottom_y { break; }
-        let d = &hw.disks.drives[i];
+        let mut lb = [0u8; 8];
         c.fill_rounded(c2x + 4, c2y - 1, 56, 13, 2, Color::new(3, 14, 30));
-        c.write_at(if d.bus == 0 { "ATA0" } else { "ATA1" },    c2x + 6,  c2y, Color::TEAL);
-        c.write_at(if d.drive == 0 { "-M" } else { "-E" },       c2x + 42, c2y, Color::GRAY);
+        c.write_at(d.bus_label(&mut lb),                          c2x + 6,  c2y, Color::TEAL);
+        if !d.ahci {
+            c.write_at(if d.drive == 0 { "-M" } else { "-E" },   c2x + 42, c2y, Color::GRAY);
+        }
         c.write_at(if d.is_atapi { "ATAPI" } else { "ATA" },      c2x + 64, c2y, Color::PORTIX_AMBER);
         c2y += lay.line_h - 2;
         let m = d.model_str(); let m = if m.len() > 26 { &m[..26] } else { m };
@@ -146,8 +211,27 @@ pub fn draw_devices_tab(
         c.write_at(core::str::from_utf8(&vhex).unwrap_or("????"), c3x + 4,  c3y, Color::TEAL);
         c.write_at(":", c3x + 40, c3y, Color::GRAY);
         c.write_at(core::str::from_utf8(&dhex).unwrap_or("????"), c3x + 50, c3y, Color::TEAL);
-        let cn = d.class_name(); let cn = if cn.len() > 18 { &cn[..18] } else { cn };
-        c.write_at(cn, c3x + 98, c3y, Color::LIGHT_GRAY);
+        // Clase (o su código en hex) y, si cabe, el fabricante detrás
+        let mut nbuf = [0u8; 48]; let mut np = 0usize;
+        let cols = c3w.saturating_sub(98) / lay.font_w;
+        {
+            let mut push = |s: &[u8], np: &mut usize| {
+                for &b in s { if *np < nbuf.len() { nbuf[*np] = b; *np += 1; } }
+            };
+            match d.class_name() {
+                Some(n) => push(n.as_bytes(), &mut np),
+                None => {
+                    push(b"Clase ", &mut np);
+                    push(&[H[(d.class_code >> 4) as usize], H[(d.class_code & 0xF) as usize], b':',
+                           H[(d.subclass >> 4) as usize], H[(d.subclass & 0xF) as usize]], &mut np);
+                }
+            }
+            if let Some(v) = d.vendor_name() {
+                if np + 1 + v.len() <= cols { push(b" ", &mut np); push(v.as_bytes(), &mut np); }
+            }
+        }
+        let np = np.min(cols);
+        c.write_at(core::str::from_utf8(&nbuf[..np]).unwrap_or(""), c3x + 98, c3y, Color::LIGHT_GRAY);
         c3y += lay.line_h - 1;
     }
 }
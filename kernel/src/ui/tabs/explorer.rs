@@ -1,4 +1,4 @@
-// ui/tabs/explorer.rs — PORTIX Kernel v0.8.0
+// ui/tabs/explorer.rs — PORTIX Kernel
 //
 // REDISEÑO COMPLETO:
 //  - Paleta desaturada (VS Code File Explorer-inspired)
@@ -8,14 +8,28 @@
 //  - Headers/footers modernos, sin "pixel art azul"
 //  - Driver de disco primario + fallback automático
 //  - Layout más limpio y menos recargado
+//  - Ordenación configurable: nombre/tamaño/tipo/fecha, asc/desc (S / I)
+//  - "Abrir en terminal" (menú contextual): fija el cwd del terminal a la
+//    carpeta actual y cambia a la pestaña Terminal
+//  - Clic izquierdo en la lista selecciona la fila; doble clic (dos clics en
+//    la misma fila en DOUBLE_CLICK_TICKS) entra al directorio o abre el archivo
+//  - Breadcrumb clickable: un clic en un componente de la ruta sube hasta ese
+//    nivel (`crumbs` da las posiciones X al dibujo y al hit-test)
+//  - Abrir un archivo (Enter, doble clic o Recientes) deja su ruta absoluta
+//    en `open_path`; main lo lee del disco y lo carga en el IDE
 
 #![allow(dead_code)]
 
 use crate::drivers::input::keyboard::Key;
-use crate::drivers::storage::fat32::{DirEntryInfo, Fat32Volume};
+use crate::drivers::storage::ata::{AtaDrive, get_cached_drive_info};
+use crate::drivers::storage::fat32::{self, DirEntryInfo, Fat32Volume, FatResult};
 use crate::drivers::storage::vfs::VFS_TREE;
 use crate::graphics::driver::framebuffer::{Color, Console, Layout};
 use crate::ui::input::{draw_input_overlay, InputBox, InputMode, INPUT_BG, INPUT_BG_DELETE, INPUT_MAX};
+use crate::ui::filetype;
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.8.2";
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Paleta — desaturada, moderna
@@ -53,10 +67,6 @@ impl ExpPal {
     pub const DIR_ICON:    Color = Color::new(0xFF, 0xBF, 0x00);
     pub const FILE_FG:     Color = Color::new(0x9C, 0xBE, 0xE8);
     pub const FILE_ICON:   Color = Color::new(0x60, 0x90, 0xC0);
-    pub const FILE_RS:     Color = Color::new(0xDE, 0x6A, 0x40);
-    pub const FILE_C:      Color = Color::new(0x44, 0x99, 0xFF);
-    pub const FILE_ASM:    Color = Color::new(0xCC, 0xAA, 0x00);
-    pub const FILE_IMG:    Color = Color::new(0x66, 0xCC, 0x88);
     pub const SIZE_FG:     Color = Color::new(0x80, 0x80, 0x80);
     pub const TYPE_FG:     Color = Color::new(0x66, 0x6E, 0x7A);
     pub const ACCENT:      Color = Color::new(0x00, 0x7A, 0xCC);
@@ -75,6 +85,7 @@ impl ExpPal {
 
 pub const TOOLBAR_H:  usize = 28;  // barra de pestañas/herramientas
 const HDR_H:      usize = 22;  // breadcrumb
+const CRUMB_X0:   usize = 6;   // + "[HDD]" = inicio del primer componente
 const COL_HDR_H:  usize = 18;  // cabecera de columnas
 const PREVIEW_H:  usize = 76;  // panel preview
 const STATUS_H:   usize = 18;  // status bar
@@ -82,6 +93,10 @@ const SIDEBAR_W:  usize = 120; // panel VFS izquierdo
 const TREE_W:     usize = 150; // árbol de rutas
 const SCR_W:      usize = 8;   // scrollbar
 const ROW_H:      usize = 16;  // altura fila
+const WHEEL_ROWS: usize = 3;   // filas por muesca de la rueda
+
+/// Separación máxima entre los dos clics de un doble clic (ticks PIT → 400 ms).
+const DOUBLE_CLICK_TICKS: u64 = 40;
 
 const MAX_ENTRIES:    usize = 256;
 const MAX_PATH_DEPTH: usize = 32;
@@ -97,6 +112,45 @@ const CONTEXT_ITEM_H: usize = 18;
 #[derive(Clone, Copy, PartialEq)]
 pub enum ExplorerView { Files, Bookmarks, Recent }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Criterio de ordenación (tecla S = criterio, I = invertir)
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey { Name, Size, Kind, Date }
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct SortMode {
+    pub key:  SortKey,
+    pub desc: bool,
+}
+
+impl SortMode {
+    pub const fn default() -> Self { SortMode { key: SortKey::Name, desc: false } }
+
+    pub fn next_key(&mut self) {
+        self.key = match self.key {
+            SortKey::Name => SortKey::Size,
+            SortKey::Size => SortKey::Kind,
+            SortKey::Kind => SortKey::Date,
+            SortKey::Date => SortKey::Name,
+        };
+    }
+
+    pub fn label(&self) -> &'static str {
+        match (self.key, self.desc) {
+            (SortKey::Name, false) => "Orden: nombre (asc)",
+            (SortKey::Name, true)  => "Orden: nombre (desc)",
+            (SortKey::Size, false) => "Orden: tamaño (asc)",
+            (SortKey::Size, true)  => "Orden: tamaño (desc)",
+            (SortKey::Kind, false) => "Orden: tipo (asc)",
+            (SortKey::Kind, true)  => "Orden: tipo (desc)",
+            (SortKey::Date, false) => "Orden: fecha (asc)",
+            (SortKey::Date, true)  => "Orden: fecha (desc)",
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Menú contextual
 // ─────────────────────────────────────────────────────────────────────────────
@@ -111,7 +165,9 @@ pub enum ContextAction {
     NewFolder, NewFile,
     Delete, Rename,
     CopyPath,
+    Copy, Paste,
     AddBookmark,
+    OpenInTerminal,
     Refresh,
     Properties,
 }
@@ -143,19 +199,20 @@ impl ContextMenu {
         }
     }
 
-    fn show_for_zone(&mut self, x: usize, y: usize, zone: ContextZone, has_file: bool) {
+    fn show_for_zone(&mut self, x: usize, y: usize, zone: ContextZone, has_file: bool, has_clip: bool) {
         self.visible = true; self.x = x; self.y = y; self.zone = zone; self.item_count = 0; self.hovered = usize::MAX;
         match zone {
             ContextZone::Sidebar => {
                 self.push(ContextItem::new("Abrir carpeta", ContextAction::Open));
                 self.push(ContextItem::sep());
-                self.push(ContextItem::new("Agregar marcador", ContextAction::AddBookmark));
+                self.push(ContextItem::new("Agregar/quitar marcador", ContextAction::AddBookmark));
                 self.push(ContextItem::new("Copiar ruta", ContextAction::CopyPath));
             }
             ContextZone::Tree => {
                 self.push(ContextItem::new("Abrir", ContextAction::Open));
                 self.push(ContextItem::sep());
                 self.push(ContextItem::new("Nueva carpeta", ContextAction::NewFolder));
+                self.push(ContextItem::new("Abrir en terminal", ContextAction::OpenInTerminal));
                 self.push(ContextItem::new("Actualizar", ContextAction::Refresh));
             }
             ContextZone::FileList if has_file => {
@@ -165,13 +222,17 @@ impl ContextMenu {
                 self.push(ContextItem::new("Renombrar", ContextAction::Rename));
                 self.push(ContextItem::new("Eliminar", ContextAction::Delete));
                 self.push(ContextItem::sep());
+                self.push(ContextItem::new("Copiar", ContextAction::Copy));
+                if has_clip { self.push(ContextItem::new("Pegar", ContextAction::Paste)); }
                 self.push(ContextItem::new("Copiar ruta", ContextAction::CopyPath));
                 self.push(ContextItem::new("Propiedades", ContextAction::Properties));
             }
             ContextZone::EmptyArea | _ => {
                 self.push(ContextItem::new("Nueva carpeta", ContextAction::NewFolder));
                 self.push(ContextItem::new("Nuevo archivo", ContextAction::NewFile));
+                if has_clip { self.push(ContextItem::new("Pegar", ContextAction::Paste)); }
                 self.push(ContextItem::sep());
+                self.push(ContextItem::new("Abrir en terminal", ContextAction::OpenInTerminal));
                 self.push(ContextItem::new("Actualizar", ContextAction::Refresh));
             }
         }
@@ -185,6 +246,32 @@ pub fn width(&self, cw: usize) -> usize { // <--- Ahora es accesible desde main.
     let max_l = self.items[..self.item_count].iter().map(|it| it.label.len()).max().unwrap_or(10);
     (max_l + 4) * cw + 16
 }
+
+/// Esquina superior izquierda tal como se dibuja: desplazada hacia dentro si
+/// el menú se saldría por la derecha (`fw`) o por abajo (`bot`).
+pub fn origin(&self, fw: usize, bot: usize, cw: usize) -> (usize, usize) {
+    let (mw, mh) = (self.width(cw), self.height());
+    let mx = if self.x + mw + 4 > fw { fw.saturating_sub(mw + 4) } else { self.x };
+    let my = if self.y + mh + 4 > bot { bot.saturating_sub(mh + 4) } else { self.y };
+    (mx, my)
+}
+
+/// true si el menú está abierto y (x, y) cae dentro de él.
+pub fn contains(&self, x: usize, y: usize, fw: usize, bot: usize, cw: usize) -> bool {
+    if !self.visible { return false; }
+    let (mx, my) = self.origin(fw, bot, cw);
+    x >= mx && x < mx + self.width(cw) && y >= my && y < my + self.height()
+}
+
+/// Item bajo (x, y). None fuera del menú, en sus márgenes o sobre un separador.
+pub fn item_at(&self, x: usize, y: usize, fw: usize, bot: usize, cw: usize) -> Option<usize> {
+    if !self.contains(x, y, fw, bot, cw) { return None; }
+    let (_, my) = self.origin(fw, bot, cw);
+    if y < my + 2 { return None; }
+    let idx = (y - my - 2) / CONTEXT_ITEM_H;
+    if idx >= self.item_count || self.items[idx].action == ContextAction::Separator { return None; }
+    Some(idx)
+}
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -223,41 +310,61 @@ fn file_kind(name: &str, is_dir: bool) -> FileKind {
     else { FileKind::Other }
 }
 
-fn kind_icon(k: FileKind) -> (&'static str, Color) {
+/// Clave de ui::filetype para cada tipo (los directorios no tienen).
+fn kind_key(k: FileKind) -> &'static str {
     match k {
-        FileKind::Dir    => ("▶", ExpPal::DIR_ICON),
-        FileKind::Rust   => ("⬡", ExpPal::FILE_RS),
-        FileKind::C      => ("◈", ExpPal::FILE_C),
-        FileKind::Asm    => ("⊞", ExpPal::FILE_ASM),
-        FileKind::Text   => ("≡", ExpPal::FILE_FG),
-        FileKind::Image  => ("⊡", ExpPal::FILE_IMG),
-        FileKind::Binary => ("⊟", ExpPal::TYPE_FG),
-        FileKind::Other  => ("◦", ExpPal::TYPE_FG),
+        FileKind::Rust   => "rs",
+        FileKind::C      => "c",
+        FileKind::Asm    => "asm",
+        FileKind::Text   => "txt",
+        FileKind::Image  => "bmp",
+        FileKind::Binary => "bin",
+        FileKind::Dir | FileKind::Other => "",
     }
 }
 
-// fallback ASCII para sistemas sin unicode en framebuffer
-fn kind_icon_ascii(k: FileKind) -> (&'static str, Color) {
+fn kind_color(k: FileKind) -> Color {
     match k {
-        FileKind::Dir    => ("[D]", ExpPal::DIR_ICON),
-        FileKind::Rust   => ("[rs]", ExpPal::FILE_RS),
-        FileKind::C      => ("[ c]", ExpPal::FILE_C),
-        FileKind::Asm    => ("[as]", ExpPal::FILE_ASM),
-        FileKind::Text   => ("[tx]", ExpPal::FILE_FG),
-        FileKind::Image  => ("[im]", ExpPal::FILE_IMG),
-        FileKind::Binary => ("[bi]", ExpPal::TYPE_FG),
-        FileKind::Other  => ("[  ]", ExpPal::TYPE_FG),
+        FileKind::Dir => ExpPal::DIR_ICON,
+        _             => filetype::lang_color(kind_key(k)),
     }
 }
 
+fn kind_icon(k: FileKind) -> (&'static str, Color) {
+    let icon = match k {
+        FileKind::Dir    => "▶",
+        FileKind::Rust   => "⬡",
+        FileKind::C      => "◈",
+        FileKind::Asm    => "⊞",
+        FileKind::Text   => "≡",
+        FileKind::Image  => "⊡",
+        FileKind::Binary => "⊟",
+        FileKind::Other  => "◦",
+    };
+    (icon, kind_color(k))
+}
+
+// fallback ASCII para sistemas sin unicode en framebuffer
+fn kind_icon_ascii(k: FileKind) -> (&'static str, Color) {
+    let icon = match k {
+        FileKind::Dir    => "[D]",
+        FileKind::Rust   => "[rs]",
+        FileKind::C      => "[ c]",
+        FileKind::Asm    => "[as]",
+        FileKind::Text   => "[tx]",
+        FileKind::Image  => "[im]",
+        FileKind::Binary => "[bi]",
+        FileKind::Other  => "[  ]",
+    };
+    (icon, kind_color(k))
+}
+
 fn kind_fg(k: FileKind, selected: bool) -> Color {
     if selected { return ExpPal::TEXT_SEL; }
     match k {
-        FileKind::Dir  => ExpPal::DIR_FG,
-        FileKind::Rust => Color::new(0xE8, 0xB0, 0x90),
-        FileKind::C    => Color::new(0x88, 0xCC, 0xFF),
-        FileKind::Asm  => Color::new(0xDD, 0xCC, 0x88),
-        _              => ExpPal::FILE_FG,
+        FileKind::Dir => ExpPal::DIR_FG,
+        FileKind::Rust | FileKind::C | FileKind::Asm => filetype::lang_text_color(kind_key(k)),
+        _             => ExpPal::FILE_FG,
     }
 }
 
@@ -278,6 +385,127 @@ impl Bookmark {
     pub fn path_str(&self) -> &str { core::str::from_utf8(&self.path[..self.path_len]).unwrap_or("?") }
 }
 
+// Persistencia: /portix/bookmark.txt (nombres 8.3 — el driver FAT32 no
+// escribe entradas LFN). Formato: una línea por marcador, "<cluster> <ruta>".
+pub const BOOKMARKS_DIR:  &str = "portix";
+pub const BOOKMARKS_FILE: &str = "bookmark.txt";
+const BOOKMARKS_BYTES:    usize = MAX_BOOKMARKS * (12 + 256);
+
+/// Serializa los marcadores en `out`. Devuelve los bytes escritos.
+pub fn serialize_bookmarks(bms: &[Bookmark], out: &mut [u8]) -> usize {
+    let mut pos = 0usize;
+    for bm in bms {
+        let mut num = [0u8; 10]; let mut ni = num.len();
+        let mut n = bm.cluster;
+        loop { ni -= 1; num[ni] = b'0' + (n % 10) as u8; n /= 10; if n == 0 { break; } }
+        let need = (num.len() - ni) + 1 + bm.path_len + 1;
+        if pos + need > out.len() { break; }
+        for &b in &num[ni..] { out[pos] = b; pos += 1; }
+        out[pos] = b' '; pos += 1;
+        out[pos..pos + bm.path_len].copy_from_slice(&bm.path[..bm.path_len]); pos += bm.path_len;
+        out[pos] = b'\n'; pos += 1;
+    }
+    pos
+}
+
+/// Parsea el formato de `serialize_bookmarks`. Las líneas mal formadas
+/// (también un cluster que no cabe en u32) se ignoran; como mucho se cargan
+/// MAX_BOOKMARKS.
+pub fn parse_bookmarks(data: &[u8], out: &mut [Bookmark; MAX_BOOKMARKS]) -> usize {
+    let mut count = 0usize;
+    for line in data.split(|&b| b == b'\n') {
+        if count >= MAX_BOOKMARKS { break; }
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let Some(sp) = line.iter().position(|&b| b == b' ') else { continue };
+        let (num, path) = (&line[..sp], &line[sp + 1..]);
+        if num.is_empty() || path.is_empty() || path[0] != b'/' || path.len() > 255 { continue; }
+        let cluster = num.iter().try_fold(0u32, |acc, &d| {
+            if !d.is_ascii_digit() { return None; }
+            acc.checked_mul(10)?.checked_add((d - b'0') as u32)
+        });
+        let Some(cluster) = cluster else { continue };
+        out[count] = Bookmark::empty();
+        out[count].path[..path.len()].copy_from_slice(path);
+        out[count].path_len = path.len();
+        out[count].cluster  = cluster;
+        count += 1;
+    }
+    count
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Recientes
+// ─────────────────────────────────────────────────────────────────────────────
+
+const MAX_RECENT: usize = 8;
+
+/// Archivo abierto recientemente. Se guarda el directorio que lo contiene para
+/// poder comprobar al reabrirlo que sigue existiendo.
+#[derive(Clone, Copy)]
+pub struct RecentFile {
+    pub name:        [u8; 256],
+    pub name_len:    usize,
+    /// Ruta absoluta del archivo (para abrirlo desde cualquier carpeta).
+    pub path:        [u8; 256],
+    pub path_len:    usize,
+    pub dir_cluster: u32,
+    pub cluster:     u32,
+    pub size:        u32,
+}
+impl RecentFile {
+    const fn empty() -> Self {
+        RecentFile { name: [0u8; 256], name_len: 0, path: [0u8; 256], path_len: 0, dir_cluster: 0, cluster: 0, size: 0 }
+    }
+    pub fn name_str(&self) -> &str { core::str::from_utf8(&self.name[..self.name_len]).unwrap_or("?") }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Copiar / Pegar
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Tamaño máximo de archivo que se puede copiar (se lee entero a RAM).
+const COPY_MAX: usize = 256 * 1024;
+static mut COPY_BUF: [u8; COPY_MAX] = [0u8; COPY_MAX];
+
+/// Elige el nombre de destino de una copia. Si `name` está libre se usa tal
+/// cual; si no, se prueba "base_N.ext" (N = 1..99). El driver FAT32 solo crea
+/// entradas 8.3, así que la base se recorta para que el sufijo quepa en los 8
+/// caracteres: si no, "informe_1.txt" y "informe_2.txt" acabarían ambos como
+/// INFORME.TXT. Devuelve la longitud escrita en `out` o None si no hay hueco.
+pub fn copy_name(name: &str, exists: impl Fn(&str) -> bool, out: &mut [u8; 256]) -> Option<usize> {
+    if !exists(name) {
+        out[..name.len()].copy_from_slice(name.as_bytes());
+        return Some(name.len());
+    }
+    let (base, ext) = match name.rfind('.') {
+        Some(d) if d > 0 => (&name[..d], &name[d + 1..]),
+        _                => (name, ""),
+    };
+    let ext = &ext[..ext.len().min(3)];
+    for n in 1u32..100 {
+        let mut suf = [b'_', 0, 0]; let mut sl = 1;
+        if n >= 10 { suf[sl] = b'0' + (n / 10) as u8; sl += 1; }
+        suf[sl] = b'0' + (n % 10) as u8; sl += 1;
+
+        let bl = base.len().min(8 - sl);
+        let mut p = 0usize;
+        for &b in base.as_bytes()[..bl].iter().chain(&suf[..sl]) { out[p] = b; p += 1; }
+        if !ext.is_empty() {
+            out[p] = b'.'; p += 1;
+            for &b in ext.as_bytes() { out[p] = b; p += 1; }
+        }
+        let cand = core::str::from_utf8(&out[..p]).unwrap_or("");
+        if !exists(cand) { return Some(p); }
+    }
+    None
+}
+
+/// Monta el volumen primario desde el DriveInfo cacheado (no re-escanea el bus).
+fn mount_cached() -> Option<Fat32Volume> {
+    let info = get_cached_drive_info()?;
+    Fat32Volume::mount(AtaDrive::from_info(info)).ok()
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // ExplorerState
 // ─────────────────────────────────────────────────────────────────────────────
@@ -307,9 +535,16 @@ pub struct ExplorerState {
     pub open_cluster:   u32,
     pub open_name:      [u8; 256],
     pub open_name_len:  usize,
+    pub open_path:      [u8; 256],
+    pub open_path_len:  usize,
     pub open_size:      u32,
     pub needs_refresh:  bool,
 
+    // Señal "abrir en terminal": main fija el cwd del terminal a esta ruta
+    pub terminal_request:  bool,
+    pub terminal_path:     [u8; 256],
+    pub terminal_path_len: usize,
+
     // Input inline
     pub input: InputBox,
 
@@ -319,6 +554,7 @@ pub struct ExplorerState {
 
     // Vista actual (toolbar tabs)
     pub view:    ExplorerView,
+    pub sort:    SortMode,
 
     // Menú contextual
     pub context: ContextMenu,
@@ -327,13 +563,23 @@ pub struct ExplorerState {
     pub bookmarks:      [Bookmark; MAX_BOOKMARKS],
     pub bookmark_count: usize,
 
-    // Recientes (últimas rutas abiertas)
-    pub recent:      [[u8; 256]; 8],
-    pub recent_lens: [usize; 8],
-    pub recent_count:usize,
+    // Recientes (últimos archivos abiertos)
+    pub recent:       [RecentFile; MAX_RECENT],
+    pub recent_count: usize,
+
+    // Selección propia de las vistas Marcadores / Recientes
+    pub bookmark_sel: usize,
+    pub recent_sel:   usize,
+
+    // Portapapeles de archivos (Copiar / Pegar)
+    pub clipboard_entry: Option<DirEntryInfo>,
+    pub clipboard_dir:   u32,
 
     // Ayuda
     pub show_help: bool,
+
+    // Último clic en la lista (índice de entrada, tick) para el doble clic
+    last_click: Option<(usize, u64)>,
 }
 
 impl ExplorerState {
@@ -358,19 +604,29 @@ impl ExplorerState {
             open_cluster:   0,
             open_name:      [0u8; 256],
             open_name_len:  0,
+            open_path:      [0u8; 256],
+            open_path_len:  0,
             open_size:      0,
             needs_refresh:  true,
+            terminal_request:  false,
+            terminal_path:     [0u8; 256],
+            terminal_path_len: 0,
             input:          InputBox::new(),
             vfs_sel:        0,
             show_vfs:       true,
             view:           ExplorerView::Files,
+            sort:           SortMode::default(),
             context:        ContextMenu::new(),
             bookmarks:      [const { Bookmark::empty() }; MAX_BOOKMARKS],
             bookmark_count: 0,
-            recent:         [[0u8; 256]; 8],
-            recent_lens:    [0usize; 8],
+            recent:         [RecentFile::empty(); MAX_RECENT],
             recent_count:   0,
+            bookmark_sel:   0,
+            recent_sel:     0,
+            clipboard_entry: None,
+            clipboard_dir:   0,
             show_help:      false,
+            last_click:     None,
         };
         s.path_stack[0] = PathNode::root(root_cluster);
         s
@@ -384,6 +640,22 @@ impl ExplorerState {
         self.status_len = n; self.status_ok = ok;
     }
 
+    /// Reordena la lista actual con `self.sort` manteniendo la selección
+    /// sobre la misma entrada (se localiza por posición en disco).
+    pub fn resort(&mut self) {
+        let sel_key = self.selected_entry().map(|e| (e.dir_sector, e.dir_offset));
+        sort_entries(&mut self.entries, self.entry_count, self.sort);
+        if let Some(k) = sel_key {
+            if let Some(i) = self.entries[..self.entry_count].iter()
+                .position(|e| e.as_ref().map(|e| (e.dir_sector, e.dir_offset)) == Some(k))
+            {
+                self.selected = i;
+            }
+        }
+        self.clamp_scroll(0);
+        self.set_status(self.sort.label(), true);
+    }
+
     pub fn refresh(&mut self, vol: &Fat32Volume) {
         self.entry_count = 0;
         const NONE_ENTRY: Option<DirEntryInfo> = None;
@@ -397,7 +669,7 @@ impl ExplorerState {
             if count < MAX_ENTRIES { entries_ref[count] = Some(e.clone()); count += 1; }
         });
         self.entry_count = count;
-        sort_entries(&mut self.entries, count);
+        sort_entries(&mut self.entries, count, self.sort);
         if self.selected >= count && count > 0 { self.selected = count - 1; }
         self.needs_refresh = false;
         self.set_status("Directorio cargado", true);
@@ -436,22 +708,220 @@ impl ExplorerState {
             let mut n = [0u8; 256]; n[..e.name_len].copy_from_slice(&e.name[..e.name_len]);
             (e.cluster, e.size, e.name_len, n)
         } else { return false; };
+        let mut path = [0u8; 256];
+        let path_len = self.file_path(&name[..name_len], &mut path);
         self.open_request = true; self.open_cluster = cluster; self.open_size = size;
         self.open_name = name; self.open_name_len = name_len;
+        self.open_path = path; self.open_path_len = path_len;
         // Agregar a recientes
-        self.push_recent(&name[..name_len]);
+        let dir_cluster = self.current_cluster();
+        self.push_recent(&name[..name_len], &path[..path_len], dir_cluster, cluster, size);
         true
     }
 
-    fn push_recent(&mut self, name: &[u8]) {
-        if self.recent_count < 8 {
-            let n = name.len().min(255);
-            self.recent[self.recent_count][..n].copy_from_slice(&name[..n]);
-            self.recent_lens[self.recent_count] = n;
+    fn push_recent(&mut self, name: &[u8], path: &[u8], dir_cluster: u32, cluster: u32, size: u32) {
+        let n = name.len().min(255);
+        // Ya estaba: solo actualizar tamaño (puede haberse guardado desde el IDE)
+        if let Some(r) = self.recent[..self.recent_count].iter_mut()
+            .find(|r| r.dir_cluster == dir_cluster && r.name[..r.name_len] == name[..n])
+        {
+            r.cluster = cluster; r.size = size;
+            return;
+        }
+        if self.recent_count < MAX_RECENT {
+            let r = &mut self.recent[self.recent_count];
+            r.name[..n].copy_from_slice(&name[..n]);
+            r.name_len    = n;
+            r.path[..path.len()].copy_from_slice(path);
+            r.path_len    = path.len();
+            r.dir_cluster = dir_cluster;
+            r.cluster     = cluster;
+            r.size        = size;
             self.recent_count += 1;
         }
     }
 
+    // ── Selección por vista ──────────────────────────────────────────────────
+
+    /// Número de filas de la vista actual.
+    pub fn view_len(&self) -> usize {
+        match self.view {
+            ExplorerView::Files     => self.entry_count,
+            ExplorerView::Bookmarks => self.bookmark_count,
+            ExplorerView::Recent    => self.recent_count,
+        }
+    }
+
+    /// Índice seleccionado en la vista actual (acotado a su longitud).
+    pub fn view_sel(&self) -> usize {
+        let sel = match self.view {
+            ExplorerView::Files     => self.selected,
+            ExplorerView::Bookmarks => self.bookmark_sel,
+            ExplorerView::Recent    => self.recent_sel,
+        };
+        sel.min(self.view_len().saturating_sub(1))
+    }
+
+    fn set_view_sel(&mut self, sel: usize) {
+        let sel = sel.min(self.view_len().saturating_sub(1));
+        match self.view {
+            ExplorerView::Files     => { self.selected = sel; self.clamp_scroll(0); }
+            ExplorerView::Bookmarks => self.bookmark_sel = sel,
+            ExplorerView::Recent    => self.recent_sel   = sel,
+        }
+    }
+
+    fn activate_selection(&mut self) {
+        match self.view {
+            ExplorerView::Files => { if !self.try_enter_dir() { self.try_open_file(); } }
+            ExplorerView::Bookmarks => {
+                let i = self.view_sel();
+                if i < self.bookmark_count { self.open_bookmark(i); }
+            }
+            ExplorerView::Recent => {
+                let i = self.view_sel();
+                if i < self.recent_count { self.open_recent(i); }
+            }
+        }
+    }
+
+    /// Recorre `path` (absoluta, sin "." ni "..") desde la raíz y construye la
+    /// pila de navegación equivalente. None si algún componente no existe o
+    /// no es un directorio. Devuelve también el clúster de la última carpeta.
+    fn walk_path(&self, vol: &Fat32Volume, path: &[u8])
+        -> Option<([PathNode; MAX_PATH_DEPTH], usize, u32)>
+    {
+        let root = self.path_stack[0].cluster;
+        let mut stack = [const { PathNode::root(0) }; MAX_PATH_DEPTH];
+        stack[0] = PathNode::root(root);
+        let mut depth = 1usize;
+        let mut cluster = root;
+
+        for comp in path.split(|&b| b == b'/').filter(|c| !c.is_empty()) {
+            let name = core::str::from_utf8(comp).unwrap_or("");
+            let found = vol.find_entry(cluster, name).ok().filter(|e| e.is_dir);
+            let (Some(e), true) = (found, depth < MAX_PATH_DEPTH) else { return None };
+            let n = comp.len().min(255);
+            stack[depth].name[..n].copy_from_slice(&comp[..n]);
+            stack[depth].name_len = n;
+            stack[depth].cluster  = e.cluster;
+            cluster = e.cluster;
+            depth += 1;
+        }
+        Some((stack, depth, cluster))
+    }
+
+    fn enter_stack(&mut self, stack: [PathNode; MAX_PATH_DEPTH], depth: usize) {
+        self.path_stack = stack;
+        self.path_depth = depth;
+        self.selected = 0; self.scroll = 0; self.preview_len = 0;
+        self.needs_refresh = true;
+        self.view = ExplorerView::Files;
+    }
+
+    /// Navega a la carpeta `path` (absoluta). Lo usan los enlaces clickables
+    /// del terminal. false si no se pudo montar o la carpeta no existe.
+    pub fn open_path(&mut self, path: &[u8]) -> bool {
+        let Some(vol) = mount_cached() else {
+            self.set_status("No se pudo montar el volumen", false);
+            return false;
+        };
+        let Some((stack, depth, _)) = self.walk_path(&vol, path) else {
+            self.set_status("Carpeta no encontrada", false);
+            return false;
+        };
+        self.enter_stack(stack, depth);
+        self.set_status(core::str::from_utf8(path).unwrap_or("/"), true);
+        true
+    }
+
+    /// Reconstruye `path_stack` recorriendo la ruta del marcador desde la raíz.
+    /// Falla si algún componente ya no existe o si el clúster final no coincide
+    /// con el guardado (la carpeta se borró y el nombre se reutilizó).
+    fn open_bookmark(&mut self, idx: usize) {
+        let bm = self.bookmarks[idx].clone();
+        let Some(vol) = mount_cached() else {
+            self.set_status("Marcador: no se pudo montar el volumen", false);
+            return;
+        };
+        let walked = self.walk_path(&vol, &bm.path[..bm.path_len]);
+        self.enter_bookmark(&bm, walked);
+    }
+
+    /// Entra en la carpeta de `bm` con el resultado de `walk_path` sobre su ruta.
+    fn enter_bookmark(&mut self, bm: &Bookmark, walked: Option<([PathNode; MAX_PATH_DEPTH], usize, u32)>) {
+        let Some((stack, depth, _)) = walked.filter(|w| w.2 == bm.cluster) else {
+            self.set_status("Marcador invalido: la carpeta ya no existe", false);
+            return;
+        };
+        self.enter_stack(stack, depth);
+        self.set_status(bm.path_str(), true);
+    }
+
+    /// Reabre un reciente como si se hubiera seleccionado en la lista. Antes
+    /// comprueba que el archivo siga en su directorio.
+    fn open_recent(&mut self, idx: usize) {
+        let r = self.recent[idx];
+        let found = mount_cached()
+            .and_then(|vol| vol.find_entry(r.dir_cluster, r.name_str()).ok());
+        self.reopen_recent(idx, found);
+    }
+
+    /// Pide abrir el reciente `idx` con su entrada actual en disco (`found`),
+    /// o lo quita de la lista si ya no existe como archivo.
+    fn reopen_recent(&mut self, idx: usize, found: Option<DirEntryInfo>) {
+        let r = self.recent[idx];
+        let Some(e) = found.filter(|e| !e.is_dir) else {
+            // Quitarlo de la lista: ya no se puede abrir
+            self.recent.copy_within(idx + 1..self.recent_count, idx);
+            self.recent_count -= 1;
+            self.recent[self.recent_count] = RecentFile::empty();
+            self.recent_sel = self.recent_sel.min(self.recent_count.saturating_sub(1));
+            self.set_status("El archivo ya no existe (eliminado de recientes)", false);
+            return;
+        };
+        self.recent[idx].cluster = e.cluster;
+        self.recent[idx].size    = e.size;
+        self.open_request  = true;
+        self.open_cluster  = e.cluster;
+        self.open_size     = e.size;
+        self.open_name     = r.name;
+        self.open_name_len = r.name_len;
+        self.open_path     = r.path;
+        self.open_path_len = r.path_len;
+    }
+
+    /// Componentes del breadcrumb: (índice en `path_stack`, x, ancho). Mismo
+    /// avance que el dibujo: nombre + separador " > ".
+    fn crumbs(&self, cw: usize) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        let mut bx = CRUMB_X0 + 6 * cw;
+        (0..self.path_depth).map(move |i| {
+            let (x, w) = (bx, self.path_stack[i].name_str().len() * cw);
+            bx += w + cw + 6;
+            (i, x, w)
+        })
+    }
+
+    /// Componente clickable del breadcrumb bajo (x, y). El último (el
+    /// directorio actual) no cuenta: hacer clic en él no haría nada.
+    pub fn crumb_at(&self, x: usize, y: usize, lay: &Layout) -> Option<usize> {
+        if self.input.is_active() || self.show_help { return None; }
+        let hdr_y = lay.content_y + TOOLBAR_H;
+        if y < hdr_y || y >= hdr_y + HDR_H { return None; }
+        self.crumbs(lay.font_w)
+            .find(|&(i, cx, w)| i + 1 < self.path_depth && x >= cx && x < cx + w)
+            .map(|(i, _, _)| i)
+    }
+
+    /// Sube hasta el componente `i` del breadcrumb (0 = raíz).
+    pub fn go_to_crumb(&mut self, i: usize) {
+        if i + 1 >= self.path_depth { return; }
+        self.path_depth = i + 1;
+        self.view = ExplorerView::Files;
+        self.selected = 0; self.scroll = 0; self.last_click = None;
+        self.needs_refresh = true; self.preview_len = 0;
+    }
+
     pub fn go_up(&mut self) {
         if self.path_depth > 1 {
             self.path_depth -= 1; self.selected = 0; self.scroll = 0;
@@ -486,7 +956,37 @@ impl ExplorerState {
             return;
         };
 
-        self.context.show_for_zone(rx, ry, zone, has_file);
+        let has_clip = self.clipboard_entry.is_some();
+        self.context.show_for_zone(rx, ry, zone, has_file, has_clip);
+    }
+
+    /// Clic izquierdo en la vista Archivos: selecciona la fila bajo el
+    /// puntero y, si es el segundo clic sobre ella en DOUBLE_CLICK_TICKS,
+    /// la activa como Enter. Clics en la cabecera de columnas, fuera de la
+    /// lista o bajo la última entrada no hacen nada. Devuelve true si hay que
+    /// redibujar.
+    pub fn handle_left_click(&mut self, x: usize, y: usize, lay: &Layout, now: u64) -> bool {
+        if self.input.is_active() || self.show_help || self.view != ExplorerView::Files { return false; }
+        let vfs_w  = if self.show_vfs { SIDEBAR_W } else { 0 };
+        let list_x = vfs_w + TREE_W + 1;
+        if x < list_x || x >= lay.fw.saturating_sub(SCR_W) { return false; }
+
+        let vis    = explorer_visible_rows(lay);
+        let scroll = compute_scroll(self.scroll, self.selected, vis);
+        let list_y = lay.content_y + TOOLBAR_H + HDR_H + COL_HDR_H;
+        let Some(idx) = row_at_y(y, list_y, scroll, vis, self.entry_count) else { return false };
+
+        let double = matches!(self.last_click, Some((i, t)) if i == idx && now.wrapping_sub(t) <= DOUBLE_CLICK_TICKS);
+        // Fijar el scroll mostrado: la selección ya está dentro de la vista
+        self.scroll   = scroll;
+        self.selected = idx;
+        if double {
+            self.last_click = None;
+            self.activate_selection();
+        } else {
+            self.last_click = Some((idx, now));
+        }
+        true
     }
 
     /// Ejecuta la acción del menú contextual en el item clickeado
@@ -510,7 +1010,16 @@ impl ExplorerState {
             }
             ContextAction::Rename         => { self.input.start(InputMode::NewFile, ""); self.set_status("Nuevo nombre (Enter=OK, Esc=cancelar):", true); true }
             ContextAction::AddBookmark    => { self.add_current_bookmark(); true }
+            ContextAction::OpenInTerminal => {
+                let mut path = [0u8; 256];
+                self.terminal_path_len = self.current_path(&mut path);
+                self.terminal_path     = path;
+                self.terminal_request  = true;
+                true
+            }
             ContextAction::CopyPath       => { self.set_status("Ruta copiada (sin portapapeles en modo kernel)", true); true }
+            ContextAction::Copy           => { self.copy_selected(); true }
+            ContextAction::Paste          => { self.paste_clipboard(); true }
             ContextAction::Refresh        => { self.needs_refresh = true; true }
             ContextAction::Properties     => { self.show_properties(); true }
             ContextAction::Separator      => true,
@@ -518,15 +1027,151 @@ impl ExplorerState {
         }
     }
 
+    // ── Copiar / Pegar ───────────────────────────────────────────────────────
+
+    fn copy_selected(&mut self) {
+        let Some(e) = self.selected_entry().cloned() else { return };
+        if e.is_dir {
+            self.set_status("No se pueden copiar carpetas (solo archivos)", false);
+            return;
+        }
+        if e.size as usize > COPY_MAX {
+            self.set_status("Archivo demasiado grande para copiar (max 256 KiB)", false);
+            return;
+        }
+        let mut msg = [0u8; 80]; let mut mp = 0;
+        for &b in b"Copiado: ".iter().chain(&e.name[..e.name_len]) { if mp < 80 { msg[mp] = b; mp += 1; } }
+        self.clipboard_dir   = self.current_cluster();
+        self.clipboard_entry = Some(e);
+        self.set_status(core::str::from_utf8(&msg[..mp]).unwrap_or("Copiado"), true);
+    }
+
+    /// Pega el archivo del portapapeles en el directorio actual. Si el nombre
+    /// ya existe (p. ej. pegando en el mismo directorio) se busca uno libre
+    /// con `copy_name`.
+    fn paste_clipboard(&mut self) {
+        let Some(src) = self.clipboard_entry.clone() else { return };
+        if fat32::effective_read_only() {
+            self.set_status("Volumen de solo lectura ('remount rw' en el terminal)", false);
+            return;
+        }
+        let Some(vol) = mount_cached() else {
+            self.set_status("Pegar: no se pudo montar el volumen", false);
+            return;
+        };
+
+        // El origen pudo borrarse o reescribirse desde que se copió
+        let cur = vol.find_entry(self.clipboard_dir, src.name_str()).ok()
+            .filter(|e| !e.is_dir && e.cluster == src.cluster);
+        let Some(cur) = cur else {
+            self.clipboard_entry = None;
+            self.set_status("El archivo copiado ya no existe", false);
+            return;
+        };
+        let size = cur.size as usize;
+        if size > COPY_MAX {
+            self.set_status("Archivo demasiado grande para copiar (max 256 KiB)", false);
+            return;
+        }
+
+        let dir = self.current_cluster();
+        let mut name = [0u8; 256];
+        let Some(nl) = copy_name(cur.name_str(), |n| vol.find_entry(dir, n).is_ok(), &mut name) else {
+            self.set_status("Pegar: no hay nombre libre para la copia", false);
+            return;
+        };
+        let name = core::str::from_utf8(&name[..nl]).unwrap_or("copia");
+
+        // SAFETY: el Explorer se ejecuta en un único hilo; el buffer no se
+        // comparte con nadie más.
+        let buf = unsafe { &mut *core::ptr::addr_of_mut!(COPY_BUF) };
+        let res = vol.read_file(&cur, &mut buf[..size])
+            .and_then(|n| if n == size { Ok(()) } else { Err(fat32::FatError::Corrupt) })
+            .and_then(|_| vol.write_file_in(dir, name, &buf[..size]));
+        match res {
+            Ok(()) => {
+                let mut msg = [0u8; 80]; let mut mp = 0;
+                for &b in b"Pegado como ".iter().chain(name.as_bytes()) { if mp < 80 { msg[mp] = b; mp += 1; } }
+                self.set_status(core::str::from_utf8(&msg[..mp]).unwrap_or("Pegado"), true);
+                self.needs_refresh = true;
+            }
+            Err(_) => self.set_status("Pegar: error de E/S al copiar el archivo", false),
+        }
+    }
+
+    /// Ruta absoluta del directorio actual a partir de `path_stack`.
+    fn current_path(&self, out: &mut [u8; 256]) -> usize {
+        out[0] = b'/';
+        let mut n = 1usize;
+        for node in &self.path_stack[1..self.path_depth] {
+            if n > 1 && n < 255 { out[n] = b'/'; n += 1; }
+            let l = node.name_len.min(255 - n);
+            out[n..n + l].copy_from_slice(&node.name[..l]); n += l;
+        }
+        n
+    }
+
+    /// Ruta absoluta de `name` dentro del directorio actual.
+    fn file_path(&self, name: &[u8], out: &mut [u8; 256]) -> usize {
+        let mut n = self.current_path(out);
+        if n > 1 && n < 255 { out[n] = b'/'; n += 1; }
+        let l = name.len().min(256 - n);
+        out[n..n + l].copy_from_slice(&name[..l]);
+        n + l
+    }
+
+    /// Agrega el directorio actual a marcadores, o lo quita si ya estaba.
     fn add_current_bookmark(&mut self) {
-        if self.bookmark_count >= MAX_BOOKMARKS { return; }
-        let node = &self.path_stack[self.path_depth.saturating_sub(1)];
-        let n = node.name_len.min(255);
-        self.bookmarks[self.bookmark_count].path[..n].copy_from_slice(&node.name[..n]);
-        self.bookmarks[self.bookmark_count].path_len = n;
-        self.bookmarks[self.bookmark_count].cluster = node.cluster;
+        let mut path = [0u8; 256];
+        let plen = self.current_path(&mut path);
+        let cluster = self.current_cluster();
+
+        if let Some(i) = self.bookmarks[..self.bookmark_count].iter()
+            .position(|b| b.path[..b.path_len] == path[..plen])
+        {
+            for j in i..self.bookmark_count - 1 { self.bookmarks[j] = self.bookmarks[j + 1].clone(); }
+            self.bookmark_count -= 1;
+            self.bookmarks[self.bookmark_count] = Bookmark::empty();
+            self.persist_bookmarks("Marcador eliminado");
+            return;
+        }
+        if self.bookmark_count >= MAX_BOOKMARKS {
+            self.set_status("Limite de marcadores alcanzado (max 8)", false);
+            return;
+        }
+        let bm = &mut self.bookmarks[self.bookmark_count];
+        bm.path[..plen].copy_from_slice(&path[..plen]);
+        bm.path_len = plen;
+        bm.cluster  = cluster;
         self.bookmark_count += 1;
-        self.set_status("Marcador agregado", true);
+        self.persist_bookmarks("Marcador agregado");
+    }
+
+    fn persist_bookmarks(&mut self, ok_msg: &str) {
+        let saved = match mount_cached() {
+            Some(vol) => self.save_bookmarks(&vol).is_ok(),
+            None      => false,
+        };
+        if saved { self.set_status(ok_msg, true); }
+        else     { self.set_status("Marcadores: no se pudieron guardar en disco", false); }
+    }
+
+    /// Carga los marcadores desde disco. Si el archivo no existe se empieza vacío.
+    pub fn load_bookmarks(&mut self, vol: &Fat32Volume) {
+        self.bookmark_count = 0;
+        let Ok(dir)  = vol.find_entry(vol.root_cluster(), BOOKMARKS_DIR) else { return };
+        let Ok(file) = vol.find_entry(dir.cluster, BOOKMARKS_FILE) else { return };
+        let mut data = [0u8; BOOKMARKS_BYTES];
+        let n = vol.read_file(&file, &mut data).unwrap_or(0);
+        self.bookmark_count = parse_bookmarks(&data[..n], &mut self.bookmarks);
+    }
+
+    /// Escribe todos los marcadores en /portix/bookmark.txt (crea lo que falte).
+    pub fn save_bookmarks(&self, vol: &Fat32Volume) -> FatResult<()> {
+        let dir = vol.find_or_create_dir(vol.root_cluster(), BOOKMARKS_DIR)?;
+        let mut data = [0u8; BOOKMARKS_BYTES];
+        let n = serialize_bookmarks(&self.bookmarks[..self.bookmark_count], &mut data);
+        vol.write_file_in(dir.cluster, BOOKMARKS_FILE, &data[..n])
     }
 
     fn show_properties(&mut self) {
@@ -552,7 +1197,11 @@ impl ExplorerState {
             if let Some(confirmed) = self.input.feed(key) {
                 let mode = self.input.mode;
                 self.input.close();
-                if confirmed {
+                if confirmed && fat32::effective_read_only()
+                    && matches!(mode, InputMode::NewDir | InputMode::NewFile | InputMode::Delete)
+                {
+                    self.set_status("Volumen de solo lectura ('remount rw' en el terminal)", false);
+                } else if confirmed {
                     match mode {
                         InputMode::NewDir  => { self.needs_refresh = true; self.set_status("Carpeta creada (pendiente FAT32)", true); }
                         InputMode::NewFile => { self.needs_refresh = true; self.set_status("Archivo creado (pendiente FAT32)", true); }
@@ -565,12 +1214,12 @@ impl ExplorerState {
         }
 
         match key {
-            Key::Up    => { if self.selected > 0 { self.selected -= 1; } self.clamp_scroll(0); true }
-            Key::Down  => { if self.selected + 1 < self.entry_count { self.selected += 1; } self.clamp_scroll(0); true }
-            Key::PageUp   => { self.selected = self.selected.saturating_sub(12); self.clamp_scroll(0); true }
-            Key::PageDown => { self.selected = (self.selected + 12).min(self.entry_count.saturating_sub(1)); self.clamp_scroll(0); true }
-            Key::Enter    => { if !self.try_enter_dir() { self.try_open_file(); } true }
-            Key::Backspace => { self.go_up(); true }
+            Key::Up       => { let s = self.view_sel(); self.set_view_sel(s.saturating_sub(1)); true }
+            Key::Down     => { let s = self.view_sel(); self.set_view_sel(s + 1); true }
+            Key::PageUp   => { let s = self.view_sel(); self.set_view_sel(s.saturating_sub(12)); true }
+            Key::PageDown => { let s = self.view_sel(); self.set_view_sel(s + 12); true }
+            Key::Enter    => { self.activate_selection(); true }
+            Key::Backspace => { if self.view == ExplorerView::Files { self.go_up(); } else { self.view = ExplorerView::Files; } true }
             Key::F1       => { self.show_help = true; true }
             Key::F5       => { self.needs_refresh = true; true }
             Key::Char(b'n') | Key::Char(b'N') => { self.input.start(InputMode::NewDir, "nueva_carpeta"); self.set_status("Nombre de carpeta (Enter=OK, Esc=Cancelar):", true); true }
@@ -584,6 +1233,8 @@ impl ExplorerState {
                 }
                 true
             }
+            Key::Char(b's') | Key::Char(b'S') => { self.sort.next_key(); self.resort(); true }
+            Key::Char(b'i') | Key::Char(b'I') => { self.sort.desc = !self.sort.desc; self.resort(); true }
             Key::Tab => {
                 // Rotar vistas: Files → Bookmarks → Recent → Files
                 self.view = match self.view { ExplorerView::Files => ExplorerView::Bookmarks, ExplorerView::Bookmarks => ExplorerView::Recent, ExplorerView::Recent => ExplorerView::Files };
@@ -593,6 +1244,32 @@ impl ExplorerState {
         }
     }
 
+    /// Rueda del ratón (`delta` > 0 = arriba). En Archivos desplaza la lista
+    /// WHEEL_ROWS filas por muesca sin pasar de la última página y arrastra
+    /// la selección para que siga dentro de la vista (el dibujo recoloca
+    /// `scroll` en torno a `selected`). Marcadores y Recientes no tienen
+    /// scroll propio: ahí mueve la selección. Con el menú contextual abierto
+    /// solo lo cierra. Devuelve true si hay que redibujar.
+    pub fn wheel(&mut self, delta: i32, vis: usize) -> bool {
+        if self.context.visible { self.context.close(); return true; }
+        if self.show_help || self.input.is_active() || delta == 0 { return false; }
+        let step = delta.unsigned_abs() as usize * WHEEL_ROWS;
+        let up   = delta > 0;
+        if self.view != ExplorerView::Files {
+            let s = self.view_sel();
+            self.set_view_sel(if up { s.saturating_sub(step) } else { s + step });
+            return true;
+        }
+        let vis = vis.max(1);
+        let max = self.entry_count.saturating_sub(vis);
+        let cur = compute_scroll(self.scroll, self.selected, vis).min(max);
+        let new = if up { cur.saturating_sub(step) } else { (cur + step).min(max) };
+        if new == cur { return false; }
+        self.scroll   = new;
+        self.selected = self.selected.clamp(new, (new + vis - 1).min(self.entry_count.saturating_sub(1)));
+        true
+    }
+
     fn clamp_scroll(&mut self, vis: usize) {
         if self.selected < self.scroll { self.scroll = self.selected; }
         if vis > 0 && self.selected >= self.scroll + vis { self.scroll = self.selected + 1 - vis; }
@@ -603,20 +1280,44 @@ impl ExplorerState {
 // Ordenación
 // ─────────────────────────────────────────────────────────────────────────────
 
-fn sort_entries(entries: &mut [Option<DirEntryInfo>; MAX_ENTRIES], count: usize) {
-    for i in 0..count {
-        for j in i + 1..count {
-            let swap = match (&entries[i], &entries[j]) {
-                (Some(a), Some(b)) => {
-                    if a.is_dir && !b.is_dir { false } else if !a.is_dir && b.is_dir { true } else { name_gt(a, b) }
-                }
+/// Ordenación por inserción (estable): a igualdad de clave se conserva el
+/// orden del directorio. Los directorios van siempre primero.
+fn sort_entries(entries: &mut [Option<DirEntryInfo>; MAX_ENTRIES], count: usize, mode: SortMode) {
+    for i in 1..count {
+        let mut j = i;
+        while j > 0 {
+            let gt = match (&entries[j - 1], &entries[j]) {
+                (Some(a), Some(b)) => entry_gt(a, b, mode),
                 _ => false,
             };
-            if swap { entries.swap(i, j); }
+            if !gt { break; }
+            entries.swap(j - 1, j);
+            j -= 1;
         }
     }
 }
 
+/// true si `a` debe ir después de `b` según `mode`.
+fn entry_gt(a: &DirEntryInfo, b: &DirEntryInfo, mode: SortMode) -> bool {
+    use core::cmp::Ordering;
+    if a.is_dir != b.is_dir { return !a.is_dir; }
+    let ord = match mode.key {
+        SortKey::Name => name_cmp(a, b),
+        SortKey::Size => a.size.cmp(&b.size),
+        SortKey::Kind => file_ext(a.name_str()).cmp(file_ext(b.name_str())),
+        SortKey::Date => a.mtime.cmp(&b.mtime),
+    };
+    // Desempate por nombre para que el resultado sea determinista
+    let ord = if ord == Ordering::Equal && mode.key != SortKey::Name { name_cmp(a, b) } else { ord };
+    if mode.desc { ord == Ordering::Less } else { ord == Ordering::Greater }
+}
+
+fn name_cmp(a: &DirEntryInfo, b: &DirEntryInfo) -> core::cmp::Ordering {
+    if name_gt(a, b) { core::cmp::Ordering::Greater }
+    else if name_gt(b, a) { core::cmp::Ordering::Less }
+    else { core::cmp::Ordering::Equal }
+}
+
 fn name_gt(a: &DirEntryInfo, b: &DirEntryInfo) -> bool {
     let la = a.name_len.min(16); let lb = b.name_len.min(16);
     for i in 0..la.min(lb) {
@@ -630,6 +1331,14 @@ fn name_gt(a: &DirEntryInfo, b: &DirEntryInfo) -> bool {
 // draw_explorer_tab
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Filas de la lista de archivos que caben en pantalla (lo usa `main` para
+/// la rueda del ratón).
+pub fn explorer_visible_rows(lay: &Layout) -> usize {
+    let content_y = lay.content_y + TOOLBAR_H + HDR_H;
+    let preview_y = lay.bottom_y.saturating_sub(STATUS_H + PREVIEW_H);
+    (preview_y.saturating_sub(content_y + COL_HDR_H) / ROW_H).max(1)
+}
+
 pub fn draw_explorer_tab(c: &mut Console, lay: &Layout, exp: &ExplorerState) {
     let fw  = lay.fw;
     let cw  = lay.font_w;
@@ -691,31 +1400,31 @@ pub fn draw_explorer_tab(c: &mut Console, lay: &Layout, exp: &ExplorerState) {
     c.hline(0, hdr_y + HDR_H - 1, fw, ExpPal::BORDER);
 
     // Icono de disco pequeño
-    c.write_at("[HDD]", 6, hdr_y + (HDR_H - ch) / 2, ExpPal::GOLD);
+    c.write_at("[HDD]", CRUMB_X0, hdr_y + (HDR_H - ch) / 2, ExpPal::GOLD);
 
     // Path breadcrumbs
-    let mut bx = 6 + 6 * cw;
-    for i in 0..exp.path_depth {
-        let node = &exp.path_stack[i];
-        let name = node.name_str();
+    for (i, bx, w) in exp.crumbs(cw) {
         let is_last = i + 1 == exp.path_depth;
         let fg = if is_last { ExpPal::TEXT } else { ExpPal::TEXT_DIM };
-        c.write_at(name, bx, hdr_y + (HDR_H - ch) / 2, fg);
-        bx += name.len() * cw;
+        c.write_at(exp.path_stack[i].name_str(), bx, hdr_y + (HDR_H - ch) / 2, fg);
         if !is_last {
-            c.write_at(">", bx + 2, hdr_y + (HDR_H - ch) / 2, ExpPal::TEXT_DIM);
-            bx += cw + 6;
+            c.write_at(">", bx + w + 2, hdr_y + (HDR_H - ch) / 2, ExpPal::TEXT_DIM);
         }
     }
 
+    // Volumen de solo lectura — badge a la derecha del breadcrumb
+    if fat32::effective_read_only() {
+        let rx = fw.saturating_sub(cw * 16 + 8);
+        c.write_at("[solo lectura]", rx, hdr_y + (HDR_H - ch) / 2, ExpPal::GOLD);
+    }
+
     // ═════════════════════════════════════════════════════════════════════════
     // CONTENIDO — según la vista activa
     // ═════════════════════════════════════════════════════════════════════════
     let content_y = hdr_y + HDR_H;
     let status_y  = bot.saturating_sub(STATUS_H);
     let preview_y = status_y.saturating_sub(PREVIEW_H);
-    let list_area_h = preview_y.saturating_sub(content_y + COL_HDR_H);
-    let visible = (list_area_h / ROW_H).max(1);
+    let visible   = explorer_visible_rows(lay);
 
     match exp.view {
         ExplorerView::Files     => draw_files_view(c, lay, exp, content_y, preview_y, visible, cw, ch, fw),
@@ -847,6 +1556,20 @@ fn draw_files_view(
     c.write_at("Nombre", list_x + 32, content_y + (COL_HDR_H - ch) / 2, ExpPal::TEXT_DIM);
     c.write_at("Tipo",   type_col_x,  content_y + (COL_HDR_H - ch) / 2, ExpPal::TEXT_DIM);
     c.write_at("Tamaño", size_col_x,  content_y + (COL_HDR_H - ch) / 2, ExpPal::TEXT_DIM);
+    // Indicador de orden junto a la columna activa (fecha no tiene columna)
+    let arrow = if exp.sort.desc { "v" } else { "^" };
+    let arrow_x = match exp.sort.key {
+        SortKey::Name => list_x + 32 + 7 * cw,
+        SortKey::Kind => type_col_x + 5 * cw,
+        SortKey::Size => size_col_x + 7 * cw,
+        SortKey::Date => list_x + 32 + 9 * cw,
+    };
+    if exp.sort.key == SortKey::Date {
+        c.write_at("(fecha)", arrow_x.saturating_sub(cw), content_y + (COL_HDR_H - ch) / 2, ExpPal::TEXT_DIM);
+        c.write_at(arrow, arrow_x + 7 * cw, content_y + (COL_HDR_H - ch) / 2, ExpPal::ACCENT2);
+    } else {
+        c.write_at(arrow, arrow_x, content_y + (COL_HDR_H - ch) / 2, ExpPal::ACCENT2);
+    }
     c.hline(list_x, content_y + COL_HDR_H - 1, list_w, ExpPal::BORDER);
 
     // Scrollbar track
@@ -907,6 +1630,17 @@ fn draw_files_view(
     }
 }
 
+/// Entrada de la lista bajo la coordenada `y`. `list_y` es el borde superior
+/// de la primera fila (debajo de la cabecera de columnas). None en la
+/// cabecera, por debajo de la última fila visible o tras la última entrada.
+fn row_at_y(y: usize, list_y: usize, scroll: usize, vis: usize, count: usize) -> Option<usize> {
+    if y < list_y { return None; }
+    let row = (y - list_y) / ROW_H;
+    if row >= vis { return None; }
+    let idx = scroll + row;
+    if idx < count { Some(idx) } else { None }
+}
+
 fn list_area_h(preview_y: usize, content_y: usize) -> usize {
     preview_y.saturating_sub(content_y + COL_HDR_H)
 }
@@ -929,10 +1663,12 @@ fn draw_bookmarks_view(c: &mut Console, _lay: &Layout, exp: &ExplorerState, cont
         return;
     }
 
+    let sel = exp.view_sel();
     for i in 0..exp.bookmark_count {
         let by = content_y + COL_HDR_H + i * ROW_H;
-        let bg = if i % 2 == 0 { ExpPal::ROW_EVEN } else { ExpPal::ROW_ODD };
+        let bg = if i == sel { ExpPal::ROW_SEL } else if i % 2 == 0 { ExpPal::ROW_EVEN } else { ExpPal::ROW_ODD };
         c.fill_rect(0, by, fw, ROW_H, bg);
+        if i == sel { c.fill_rect(0, by, 3, ROW_H, ExpPal::ACCENT); }
         c.write_at("[⭐]", 8, by + (ROW_H - ch) / 2, ExpPal::GOLD);
         let path = exp.bookmarks[i].path_str();
         c.write_at(path, 8 + 5 * cw, by + (ROW_H - ch) / 2, ExpPal::DIR_FG);
@@ -956,15 +1692,17 @@ fn draw_recent_view(c: &mut Console, _lay: &Layout, exp: &ExplorerState, content
         return;
     }
 
-    for i in 0..exp.recent_count {
+    let sel = exp.view_sel();
+    for (i, r) in exp.recent[..exp.recent_count].iter().enumerate() {
         let ry = content_y + COL_HDR_H + i * ROW_H;
-        let bg = if i % 2 == 0 { ExpPal::ROW_EVEN } else { ExpPal::ROW_ODD };
+        let bg = if i == sel { ExpPal::ROW_SEL } else if i % 2 == 0 { ExpPal::ROW_EVEN } else { ExpPal::ROW_ODD };
         c.fill_rect(0, ry, fw, ROW_H, bg);
-        let name = core::str::from_utf8(&exp.recent[i][..exp.recent_lens[i]]).unwrap_or("?");
+        if i == sel { c.fill_rect(0, ry, 3, ROW_H, ExpPal::ACCENT); }
+        let name = r.name_str();
         let kind = file_kind(name, false);
         let (icon, icol) = kind_icon_ascii(kind);
         c.write_at(icon, 8, ry + (ROW_H - ch) / 2, icol);
-        c.write_at(name, 8 + 5 * cw, ry + (ROW_H - ch) / 2, ExpPal::FILE_FG);
+        c.write_at(name, 8 + 5 * cw, ry + (ROW_H - ch) / 2, kind_fg(kind, i == sel));
     }
 }
 
@@ -1014,8 +1752,7 @@ fn draw_context_menu(c: &mut Console, ctx: &ContextMenu, fw: usize, bot: usize,
     let mh = ctx.height();
 
     // Ajustar posición para no salir de pantalla
-    let mx = if ctx.x + mw + 4 > fw { fw.saturating_sub(mw + 4) } else { ctx.x };
-    let my = if ctx.y + mh + 4 > bot { bot.saturating_sub(mh + 4) } else { ctx.y };
+    let (mx, my) = ctx.origin(fw, bot, cw);
 
     // Sombra
     c.fill_rect(mx + 3, my + 3, mw, mh, Color::new(0x00, 0x00, 0x00));
@@ -1067,18 +1804,24 @@ fn draw_help_overlay(c: &mut Console, lay: &Layout) {
 
     c.fill_rect(ox, oy, OW, OH, ExpPal::OVERLAY_BG);
     c.draw_rect(ox, oy, OW, OH, 1, ExpPal::CONTEXT_BOR);
+    // Halo translúcido de 3 px alrededor del panel (anillos concéntricos)
+    for i in 0..3 {
+        c.draw_rect_alpha(ox.saturating_sub(3 - i), oy.saturating_sub(3 - i),
+                          OW + 6 - 2 * i, OH + 6 - 2 * i, ExpPal::ACCENT, 90);
+    }
     c.fill_rect(ox, oy, OW, 24, ExpPal::ACCENT);
     c.write_at("Atajos — Explorador de Archivos", ox + 10, oy + (24 - ch) / 2, Color::WHITE);
-    c.write_at("[Cualquier tecla]", ox + OW - 18 * cw - 6, oy + (24 - ch) / 2, Color::new(0xCC, 0xFF, 0xFF));
+    c.write_at_alpha("[Cualquier tecla]", ox + OW - 18 * cw - 6, oy + (24 - ch) / 2, Color::WHITE, 170);
 
     let entries: &[(&str, &str)] = &[
         ("Flechas",  "Navegar lista"),
-        ("Enter",    "Abrir/entrar"),
+        ("Enter",    "Abrir/entrar (o marcador/reciente)"),
         ("Backspace","Subir directorio"),
         ("N",        "Nueva carpeta"),
         ("F",        "Nuevo archivo"),
         ("D / Supr", "Eliminar"),
         ("Tab",      "Cambiar vista"),
+        ("S / I",    "Criterio de orden / invertir"),
         ("──────────", ""),
         ("Clic der", "Menú contextual"),
         ("F1 / [?]", "Esta ayuda"),
@@ -1148,4 +1891,444 @@ fn fmt_usize_local(mut n: usize, buf: &mut [u8]) -> &str {
     if n == 0 { buf[i - 1] = b'0'; return core::str::from_utf8(&buf[i - 1..]).unwrap_or("0"); }
     while n > 0 && i > 0 { i -= 1; buf[i] = b'0' + (n % 10) as u8; n /= 10; }
     core::str::from_utf8(&buf[i..]).unwrap_or("?")
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIST_Y: usize = 100;
+
+    #[test]
+    fn row_at_y_maps_rows_from_list_top() {
+        assert_eq!(row_at_y(LIST_Y, LIST_Y, 0, 10, 20), Some(0));
+        assert_eq!(row_at_y(LIST_Y + ROW_H - 1, LIST_Y, 0, 10, 20), Some(0));
+        assert_eq!(row_at_y(LIST_Y + ROW_H, LIST_Y, 0, 10, 20), Some(1));
+        assert_eq!(row_at_y(LIST_Y + 9 * ROW_H + 3, LIST_Y, 0, 10, 20), Some(9));
+        // Con scroll la fila 0 de la vista es la entrada `scroll`
+        assert_eq!(row_at_y(LIST_Y + 2 * ROW_H, LIST_Y, 5, 10, 20), Some(7));
+    }
+
+    #[test]
+    fn row_at_y_ignores_header_and_rows_past_the_end() {
+        // Cabecera de columnas (justo encima de la lista)
+        assert_eq!(row_at_y(LIST_Y - 1, LIST_Y, 0, 10, 20), None);
+        assert_eq!(row_at_y(LIST_Y - COL_HDR_H, LIST_Y, 0, 10, 20), None);
+        // Por debajo de la última fila visible
+        assert_eq!(row_at_y(LIST_Y + 10 * ROW_H, LIST_Y, 0, 10, 20), None);
+        // Fila visible pero sin entrada (lista corta)
+        assert_eq!(row_at_y(LIST_Y + 3 * ROW_H, LIST_Y, 0, 10, 3), None);
+        assert_eq!(row_at_y(LIST_Y + 2 * ROW_H, LIST_Y, 0, 10, 3), Some(2));
+        assert_eq!(row_at_y(LIST_Y, LIST_Y, 0, 10, 0), None);
+    }
+
+    #[test]
+    fn compute_scroll_keeps_selection_visible() {
+        assert_eq!(compute_scroll(0, 3, 10), 0);
+        assert_eq!(compute_scroll(0, 10, 10), 1);
+        assert_eq!(compute_scroll(8, 2, 10), 2);
+        assert_eq!(compute_scroll(4, 13, 10), 4);
+    }
+
+    /// Ordena la lista de ejemplo con `key`/`desc` y devuelve los nombres.
+    fn sorted(key: SortKey, desc: bool) -> alloc::vec::Vec<alloc::string::String> {
+        let list = [
+            Some(DirEntryInfo::for_test("zeta.txt", false, 300, 0, 5)),
+            Some(DirEntryInfo::for_test("src",      true,  0,   0, 9)),
+            Some(DirEntryInfo::for_test("Alpha.rs", false, 10,  0, 7)),
+            Some(DirEntryInfo::for_test("beta.c",   false, 300, 0, 1)),
+            Some(DirEntryInfo::for_test("docs",     true,  0,   0, 2)),
+            Some(DirEntryInfo::for_test("alpha.md", false, 50,  0, 7)),
+        ];
+        let n = list.len();
+        let mut entries: [Option<DirEntryInfo>; MAX_ENTRIES] = core::array::from_fn(|_| None);
+        for (slot, e) in entries.iter_mut().zip(list) { *slot = e; }
+        sort_entries(&mut entries, n, SortMode { key, desc });
+        entries[..n].iter().map(|e| alloc::string::String::from(e.as_ref().unwrap().name_str())).collect()
+    }
+
+    #[test]
+    fn sort_by_name_ignores_case_and_keeps_dirs_first() {
+        assert_eq!(sorted(SortKey::Name, false), ["docs", "src", "alpha.md", "Alpha.rs", "beta.c", "zeta.txt"]);
+        // Descendente invierte dentro de cada grupo, no el orden dirs/archivos
+        assert_eq!(sorted(SortKey::Name, true), ["src", "docs", "zeta.txt", "beta.c", "Alpha.rs", "alpha.md"]);
+    }
+
+    #[test]
+    fn sort_by_size_breaks_ties_by_name() {
+        assert_eq!(sorted(SortKey::Size, false), ["docs", "src", "Alpha.rs", "alpha.md", "beta.c", "zeta.txt"]);
+        assert_eq!(sorted(SortKey::Size, true), ["src", "docs", "zeta.txt", "beta.c", "alpha.md", "Alpha.rs"]);
+    }
+
+    #[test]
+    fn sort_by_kind() {
+        // Extensiones: C < MD < RS < TXT
+        assert_eq!(sorted(SortKey::Kind, false), ["docs", "src", "beta.c", "alpha.md", "Alpha.rs", "zeta.txt"]);
+    }
+
+    #[test]
+    fn sort_by_date() {
+        assert_eq!(sorted(SortKey::Date, false), ["docs", "src", "beta.c", "zeta.txt", "alpha.md", "Alpha.rs"]);
+        assert_eq!(sorted(SortKey::Date, true), ["src", "docs", "Alpha.rs", "alpha.md", "zeta.txt", "beta.c"]);
+    }
+
+    #[test]
+    fn sort_is_stable_on_equal_keys() {
+        // "README" y "readme" comparan igual: conservan el orden del directorio
+        for first in ["README", "readme"] {
+            let second = if first == "README" { "readme" } else { "README" };
+            let mut entries: [Option<DirEntryInfo>; MAX_ENTRIES] = core::array::from_fn(|_| None);
+            entries[0] = Some(DirEntryInfo::for_test("z", false, 0, 0, 0));
+            entries[1] = Some(DirEntryInfo::for_test(first, false, 0, 0, 0));
+            entries[2] = Some(DirEntryInfo::for_test(second, false, 0, 0, 0));
+            sort_entries(&mut entries, 3, SortMode::default());
+            let names: alloc::vec::Vec<&str> = entries[..3].iter().map(|e| e.as_ref().unwrap().name_str()).collect();
+            assert_eq!(names, [first, second, "z"]);
+        }
+    }
+
+    fn bookmark(cluster: u32, path: &str) -> Bookmark {
+        let mut b = Bookmark::empty();
+        b.path[..path.len()].copy_from_slice(path.as_bytes());
+        b.path_len = path.len();
+        b.cluster  = cluster;
+        b
+    }
+
+    fn parse(data: &[u8]) -> alloc::vec::Vec<(u32, alloc::string::String)> {
+        let mut out: [Bookmark; MAX_BOOKMARKS] = core::array::from_fn(|_| Bookmark::empty());
+        let n = parse_bookmarks(data, &mut out);
+        out[..n].iter().map(|b| (b.cluster, alloc::string::String::from(b.path_str()))).collect()
+    }
+
+    #[test]
+    fn bookmarks_round_trip() {
+        let bms = [bookmark(2, "/"), bookmark(4_000_000_000, "/home/user"), bookmark(17, "/portix/docs")];
+        let mut buf = [0u8; BOOKMARKS_BYTES];
+        let n = serialize_bookmarks(&bms, &mut buf);
+        assert_eq!(&buf[..n], b"2 /\n4000000000 /home/user\n17 /portix/docs\n");
+        let back = parse(&buf[..n]);
+        assert_eq!(back.len(), bms.len());
+        for (b, (cluster, path)) in bms.iter().zip(&back) {
+            assert_eq!((b.cluster, b.path_str()), (*cluster, path.as_str()));
+        }
+        // Caben siempre MAX_BOOKMARKS de ruta máxima
+        let long = "/".repeat(255);
+        let full: [Bookmark; MAX_BOOKMARKS] = core::array::from_fn(|_| bookmark(u32::MAX, &long));
+        let n = serialize_bookmarks(&full, &mut buf);
+        assert_eq!(parse(&buf[..n]).len(), MAX_BOOKMARKS);
+    }
+
+    #[test]
+    fn serialize_stops_when_out_is_full() {
+        let bms = [bookmark(2, "/a"), bookmark(3, "/b")];
+        let mut buf = [0u8; 8];
+        let n = serialize_bookmarks(&bms, &mut buf);
+        assert_eq!(&buf[..n], b"2 /a\n");
+    }
+
+    #[test]
+    fn malformed_bookmark_lines_are_skipped() {
+        let data = b"\n\
+            sinespacio\n\
+            12 relativa\n\
+            x3 /nonum\n\
+            -3 /neg\n\
+             /sincluster\n\
+            5 \n\
+            99999999999 /desborda\n\
+            7 /ok\r\n\
+            8 /con espacios";
+        assert_eq!(parse(data), [(7, "/ok".into()), (8, "/con espacios".into())]);
+        let too_long = alloc::format!("9 /{}", "a".repeat(255));
+        assert!(parse(too_long.as_bytes()).is_empty());
+    }
+
+    #[test]
+    fn parse_caps_at_max_bookmarks() {
+        let mut data = alloc::string::String::new();
+        for i in 0..MAX_BOOKMARKS + 3 { data += &alloc::format!("{} /d{}\n", i, i); }
+        let got = parse(data.as_bytes());
+        assert_eq!(got.len(), MAX_BOOKMARKS);
+        assert_eq!(got[MAX_BOOKMARKS - 1].0, MAX_BOOKMARKS as u32 - 1);
+    }
+
+    /// `copy_name` contra un directorio con `taken` (sin distinguir
+    /// mayúsculas, como FAT32).
+    fn copy_as(name: &str, taken: &[&str]) -> Option<alloc::string::String> {
+        let mut out = [0u8; 256];
+        let n = copy_name(name, |c| taken.iter().any(|t| t.eq_ignore_ascii_case(c)), &mut out)?;
+        Some(alloc::string::String::from(core::str::from_utf8(&out[..n]).unwrap()))
+    }
+
+    #[test]
+    fn copy_name_free_name_is_kept() {
+        assert_eq!(copy_as("informe_largo.txt", &["otro.txt"]).as_deref(), Some("informe_largo.txt"));
+    }
+
+    #[test]
+    fn copy_name_collision_fits_8_3() {
+        assert_eq!(copy_as("a.txt", &["a.txt"]).as_deref(), Some("a_1.txt"));
+        // Base recortada para que el sufijo quepa en 8 caracteres
+        assert_eq!(copy_as("informe.txt", &["INFORME.TXT"]).as_deref(), Some("inform_1.txt"));
+        assert_eq!(copy_as("informe.txt", &["informe.txt", "inform_1.txt", "INFORM_2.TXT"]).as_deref(),
+                   Some("inform_3.txt"));
+        // Extensión de más de 3 caracteres y archivo sin extensión
+        assert_eq!(copy_as("index.html", &["index.html"]).as_deref(), Some("index_1.htm"));
+        assert_eq!(copy_as("Makefile", &["makefile"]).as_deref(), Some("Makefi_1"));
+    }
+
+    #[test]
+    fn copy_name_two_digit_suffix_and_exhaustion() {
+        let mut taken = alloc::vec![alloc::string::String::from("datos.bin")];
+        for n in 1..10 { taken.push(alloc::format!("datos_{}.bin", n)); }
+        let refs: alloc::vec::Vec<&str> = taken.iter().map(|s| s.as_str()).collect();
+        assert_eq!(copy_as("datos.bin", &refs).as_deref(), Some("datos_10.bin"));
+
+        for n in 10..100 { taken.push(alloc::format!("datos_{}.bin", n)); }
+        let refs: alloc::vec::Vec<&str> = taken.iter().map(|s| s.as_str()).collect();
+        assert_eq!(copy_as("datos.bin", &refs), None);
+    }
+
+    #[test]
+    fn wheel_scrolls_file_list_and_drags_selection() {
+        let mut ex = ExplorerState::new(2);
+        ex.entry_count = 100;
+        // Arriba del todo: nada que subir
+        assert!(!ex.wheel(1, 10));
+        assert!(ex.wheel(-1, 10));
+        assert_eq!((ex.scroll, ex.selected), (WHEEL_ROWS, WHEEL_ROWS));
+        // Sin pasar de la última página
+        assert!(ex.wheel(-100, 10));
+        assert_eq!((ex.scroll, ex.selected), (90, 90));
+        assert!(!ex.wheel(-1, 10));
+        // Subiendo, la selección sigue visible y no se mueve
+        assert!(ex.wheel(1, 10));
+        assert_eq!((ex.scroll, ex.selected), (90 - WHEEL_ROWS, 90));
+    }
+
+    #[test]
+    fn wheel_short_list_does_not_scroll() {
+        let mut ex = ExplorerState::new(2);
+        ex.entry_count = 5;
+        assert!(!ex.wheel(-1, 10));
+        assert_eq!(ex.scroll, 0);
+    }
+
+    #[test]
+    fn wheel_moves_selection_in_bookmarks_and_closes_menu() {
+        let mut ex = ExplorerState::new(2);
+        ex.view = ExplorerView::Bookmarks;
+        ex.bookmark_count = 5;
+        assert!(ex.wheel(-1, 10));
+        assert_eq!(ex.bookmark_sel, 3);
+        assert!(ex.wheel(-1, 10));
+        assert_eq!(ex.bookmark_sel, 4);
+        assert!(ex.wheel(1, 10));
+        assert_eq!(ex.bookmark_sel, 1);
+
+        ex.context.visible = true;
+        assert!(ex.wheel(-1, 10));
+        assert!(!ex.context.visible);
+        assert_eq!(ex.bookmark_sel, 1);
+    }
+
+    const FW: usize = 1024;
+    const BOT: usize = 700;
+    const CW: usize = 8;
+
+    /// Menú de la lista de archivos con un archivo seleccionado, en (x, y).
+    fn file_menu(x: usize, y: usize) -> ContextMenu {
+        let mut m = ContextMenu::new();
+        m.show_for_zone(x, y, ContextZone::FileList, true, false);
+        m
+    }
+
+    #[test]
+    fn context_item_at_rows_and_separators() {
+        let m = file_menu(100, 100);
+        let row = |i: usize| 100 + 2 + i * CONTEXT_ITEM_H;
+        assert_eq!(m.item_at(110, row(0), FW, BOT, CW), Some(0));
+        assert_eq!(m.item_at(110, row(1) + CONTEXT_ITEM_H - 1, FW, BOT, CW), Some(1));
+        // Índice 2 es separador
+        assert!(m.items[2].action == ContextAction::Separator);
+        assert_eq!(m.item_at(110, row(2), FW, BOT, CW), None);
+        assert!(m.contains(110, row(2), FW, BOT, CW));
+        assert_eq!(m.item_at(110, row(3), FW, BOT, CW), Some(3));
+        // Márgenes y fuera
+        assert_eq!(m.item_at(110, 101, FW, BOT, CW), None);
+        assert_eq!(m.item_at(99, row(0), FW, BOT, CW), None);
+        assert_eq!(m.item_at(100 + m.width(CW), row(0), FW, BOT, CW), None);
+        assert_eq!(m.item_at(110, row(m.item_count), FW, BOT, CW), None);
+    }
+
+    #[test]
+    fn context_menu_is_pushed_inside_the_screen() {
+        let m = file_menu(FW - 10, BOT - 10);
+        let (mx, my) = m.origin(FW, BOT, CW);
+        assert_eq!(mx + m.width(CW) + 4, FW);
+        assert_eq!(my + m.height() + 4, BOT);
+        // El hit-test usa la posición desplazada, no el punto del clic
+        assert_eq!(m.item_at(mx + 1, my + 2, FW, BOT, CW), Some(0));
+        assert_eq!(m.item_at(FW - 5, BOT - 8, FW, BOT, CW), None);
+        assert_eq!(file_menu(10, 10).origin(FW, BOT, CW), (10, 10));
+    }
+
+    #[test]
+    fn closed_context_menu_hits_nothing() {
+        let mut m = file_menu(100, 100);
+        m.close();
+        assert!(!m.contains(110, 110, FW, BOT, CW));
+        assert_eq!(m.item_at(110, 110, FW, BOT, CW), None);
+    }
+
+    /// Explorer en la vista `view` con `n` filas en ella.
+    fn in_view(view: ExplorerView, n: usize) -> ExplorerState {
+        let mut ex = ExplorerState::new(2);
+        ex.view = view;
+        match view {
+            ExplorerView::Files     => ex.entry_count    = n,
+            ExplorerView::Bookmarks => ex.bookmark_count = n,
+            ExplorerView::Recent    => ex.recent_count   = n,
+        }
+        ex
+    }
+
+    #[test]
+    fn up_down_clamp_in_every_view() {
+        for view in [ExplorerView::Files, ExplorerView::Bookmarks, ExplorerView::Recent] {
+            let mut ex = in_view(view, 3);
+            ex.handle_key(Key::Up);
+            assert_eq!(ex.view_sel(), 0);
+            for _ in 0..5 { ex.handle_key(Key::Down); }
+            assert_eq!(ex.view_sel(), 2);
+            ex.handle_key(Key::Up);
+            assert_eq!(ex.view_sel(), 1);
+            ex.handle_key(Key::PageDown);
+            assert_eq!(ex.view_sel(), 2);
+            ex.handle_key(Key::PageUp);
+            assert_eq!(ex.view_sel(), 0);
+        }
+    }
+
+    #[test]
+    fn selection_is_kept_per_view() {
+        let mut ex = in_view(ExplorerView::Files, 10);
+        ex.bookmark_count = 4;
+        ex.handle_key(Key::Down);
+        ex.view = ExplorerView::Bookmarks;
+        for _ in 0..3 { ex.handle_key(Key::Down); }
+        assert_eq!((ex.selected, ex.bookmark_sel, ex.recent_sel), (1, 3, 0));
+        ex.view = ExplorerView::Files;
+        assert_eq!(ex.view_sel(), 1);
+    }
+
+    #[test]
+    fn empty_view_selects_row_zero_and_enter_does_nothing() {
+        for view in [ExplorerView::Bookmarks, ExplorerView::Recent] {
+            let mut ex = in_view(view, 0);
+            ex.handle_key(Key::Down);
+            ex.handle_key(Key::Up);
+            assert_eq!(ex.view_sel(), 0);
+            ex.handle_key(Key::Enter);
+            assert!(ex.view == view);
+            assert!(!ex.open_request);
+            assert_eq!(ex.status_len, 0);
+        }
+        let mut ex = in_view(ExplorerView::Files, 0);
+        ex.handle_key(Key::Down);
+        assert_eq!(ex.view_sel(), 0);
+    }
+
+    #[test]
+    fn enter_on_bookmark_without_volume_keeps_the_view() {
+        // En el host no hay drive cacheado: mount_cached() falla
+        let mut ex = in_view(ExplorerView::Bookmarks, 1);
+        ex.bookmarks[0] = bookmark(7, "/home");
+        ex.handle_key(Key::Enter);
+        assert!(ex.view == ExplorerView::Bookmarks);
+        assert!(!ex.status_ok);
+        assert_eq!(&ex.status[..ex.status_len], b"Marcador: no se pudo montar el volumen");
+    }
+
+    /// Pila como la que devuelve `walk_path` para "/home" (clúster `cluster`).
+    fn home_stack(cluster: u32) -> Option<([PathNode; MAX_PATH_DEPTH], usize, u32)> {
+        let mut stack = [const { PathNode::root(0) }; MAX_PATH_DEPTH];
+        stack[0] = PathNode::root(2);
+        stack[1].name[..4].copy_from_slice(b"home");
+        stack[1].name_len = 4;
+        stack[1].cluster  = cluster;
+        Some((stack, 2, cluster))
+    }
+
+    #[test]
+    fn bookmark_enters_its_folder_in_the_file_view() {
+        let mut ex = in_view(ExplorerView::Bookmarks, 1);
+        ex.selected = 5;
+        let bm = bookmark(7, "/home");
+        ex.enter_bookmark(&bm, home_stack(7));
+        assert!(ex.view == ExplorerView::Files);
+        assert_eq!(ex.path_depth, 2);
+        assert_eq!((ex.path_stack[1].name_str(), ex.path_stack[1].cluster), ("home", 7));
+        assert_eq!(ex.selected, 0);
+        assert!(ex.needs_refresh && ex.status_ok);
+        assert_eq!(&ex.status[..ex.status_len], b"/home");
+    }
+
+    #[test]
+    fn bookmark_to_a_reused_or_missing_folder_is_rejected() {
+        // La ruta existe pero con otro clúster: la carpeta se recreó
+        for walked in [home_stack(9), None] {
+            let mut ex = in_view(ExplorerView::Bookmarks, 1);
+            ex.enter_bookmark(&bookmark(7, "/home"), walked);
+            assert!(ex.view == ExplorerView::Bookmarks);
+            assert_eq!(ex.path_depth, 1);
+            assert_eq!(&ex.status[..ex.status_len], b"Marcador invalido: la carpeta ya no existe");
+        }
+    }
+
+    /// Explorer en Recientes con los archivos `names` (en el directorio 5).
+    fn with_recent(names: &[&str]) -> ExplorerState {
+        let mut ex = in_view(ExplorerView::Recent, names.len());
+        for (r, name) in ex.recent.iter_mut().zip(names) {
+            r.name[..name.len()].copy_from_slice(name.as_bytes());
+            r.name_len    = name.len();
+            r.path[0]     = b'/';
+            r.path[1..=name.len()].copy_from_slice(name.as_bytes());
+            r.path_len    = name.len() + 1;
+            r.dir_cluster = 5;
+        }
+        ex
+    }
+
+    #[test]
+    fn recent_entry_requests_opening_the_file() {
+        let mut ex = with_recent(&["a.txt", "b.txt"]);
+        ex.handle_key(Key::Down);
+        ex.reopen_recent(ex.view_sel(), Some(DirEntryInfo::for_test("b.txt", false, 1234, 40, 0)));
+        assert!(ex.open_request);
+        assert_eq!((ex.open_cluster, ex.open_size), (40, 1234));
+        assert_eq!(&ex.open_name[..ex.open_name_len], b"b.txt");
+        assert_eq!(&ex.open_path[..ex.open_path_len], b"/b.txt");
+        // Se actualiza con lo que hay ahora en disco
+        assert_eq!((ex.recent[1].cluster, ex.recent[1].size), (40, 1234));
+        assert_eq!(ex.recent_count, 2);
+    }
+
+    #[test]
+    fn enter_on_missing_recent_removes_it() {
+        // Sin volumen en el host: el archivo no se encuentra
+        let mut ex = with_recent(&["a.txt", "b.txt", "c.txt"]);
+        for _ in 0..2 { ex.handle_key(Key::Down); }
+        ex.handle_key(Key::Enter);
+        assert!(!ex.open_request);
+        assert_eq!(ex.recent_count, 2);
+        assert_eq!(ex.recent_sel, 1);
+        assert_eq!(ex.recent[1].name_str(), "b.txt");
+        assert!(!ex.status_ok);
+        // Una carpeta con ese nombre tampoco vale
+        let mut ex = with_recent(&["a.txt", "b.txt"]);
+        ex.reopen_recent(0, Some(DirEntryInfo::for_test("a.txt", true, 0, 0, 0)));
+        assert!(!ex.open_request);
+        assert_eq!(ex.recent[0].name_str(), "b.txt");
+    }
+}
@@ -1,4 +1,4 @@
-// ui/tabs/ide.rs — PORTIX Kernel v0.8.0
+// ui/tabs/ide.rs — PORTIX Kernel
 //
 // REDISEÑO COMPLETO:
 //  - Paleta desaturada, profesional (estilo VS Code Dark+)
@@ -7,19 +7,49 @@
 //  - Barra de menú más limpia y compacta
 //  - Status bar más informativa y menos recargada
 //  - Sin texto de ayuda visible en la barra (solo nombre + posición)
+//  - Buscar (Ctrl+F) y reemplazar (Ctrl+H) en el buffer o en la selección
+//    marcada con Ctrl+Espacio; Ctrl+Z deshace reemplazos
+//  - Status bar: función/struct/impl que contiene al cursor (Rust/C)
+//  - Minibuffer (Ctrl+:) para ejecutar comandos del terminal sin salir del
+//    editor: el IDE solo guarda la línea (take_command) y main la ejecuta
+//    en el terminal; la salida corta vuelve a la status bar
+//  - Recargar (Ctrl+R o `:reload`): descarta los cambios y relee el archivo
+//    de origen; pide confirmación si el buffer está sucio. La lectura la hace
+//    main (take_reload → finish_reload) igual que el minibuffer
+//  - Con el cursor sobre un identificador se marcan sus otras apariciones
+//    (palabra completa) en las líneas visibles, bajo el syntax highlighting
+//  - Modo zen (F11): oculta menubar, pestañas y status bar; el área de
+//    edición ocupa todo content_y..bottom_y. Se sale con F11 o Esc
+//  - Resaltado de ensamblador (NASM y AT&T): mnemónicos x86 como keywords,
+//    registros como tipos, directivas/tamaños como macros y etiquetas al
+//    principio de línea en su propio color (tablas ASM_*). `$`/`$$` de NASM
+//    son símbolos, no inmediatos AT&T
+//  - Comentarios de bloque `/* */` multilínea en Rust/C (anidados en Rust):
+//    `highlight_line` recibe y devuelve la profundidad abierta y el render
+//    la calcula para la primera línea visible (block_comment_depth, con una
+//    caché que solo invalidan las ediciones por encima de esa línea)
+//  - Clic en un número de línea del gutter: cursor al inicio de esa línea;
+//    con Shift selecciona la línea entera (o extiende la marca que ya hay).
+//    La fila sale de la Y del clic (ide_gutter_line_at → scroll + fila)
 //
 // LAYOUT INTERNO (dentro de content_y..bottom_y):
 //   [MENU_H  = 22px]  → Archivo | Editar | Ver | Ayuda | [?]
 //   [TABS_H  = 20px]  → pestañas de buffers
 //   [EDIT_H  = …   ]  → área de edición con gutter
 //   [STATUS_H= 18px]  → Ln/Col · Lang · nombre · estado
+//   (en modo zen solo queda EDIT_H; el minibuffer se superpone abajo)
 
 #![allow(dead_code)]
 
+use core::cell::Cell;
 use core::mem::MaybeUninit;
 use crate::drivers::input::keyboard::Key;
+use crate::drivers::storage::fat32::FatError;
 use crate::graphics::driver::framebuffer::{Color, Console, Layout};
-use crate::ui::input::{InputBox, InputMode, draw_input_overlay, INPUT_BG};
+use crate::ui::input::{InputBox, InputMode, draw_input_overlay, INPUT_BG, INPUT_BG_DELETE, INPUT_MAX};
+
+/// Versión de este subsistema (comando `versions`).
+pub const VERSION: &str = "0.8.4";
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Paleta IDE  —  desaturada, VS Code-inspired
@@ -58,6 +88,9 @@ impl IdePal {
     pub const CURSOR_LINE:  Color = Color::new(0x28, 0x28, 0x28); // línea del cursor
     pub const CURSOR_BG:    Color = Color::new(0xAE, 0xAF, 0xAD); // cursor block
     pub const CURSOR_FG:    Color = Color::new(0x1E, 0x1E, 0x1E); // char en cursor
+    pub const SELECTION:    Color = Color::new(0x26, 0x4F, 0x78); // selección (marca)
+    pub const MATCH:        Color = Color::new(0x61, 0x3A, 0x14); // coincidencia actual
+    pub const WORD_HL:      Color = Color::new(0x3A, 0x3D, 0x41); // misma palabra que el cursor
     // Syntax
     pub const SYN_KW:       Color = Color::new(0x56, 0x9C, 0xD6); // keyword
     pub const SYN_STR:      Color = Color::new(0xCE, 0x91, 0x78); // string
@@ -66,6 +99,7 @@ impl IdePal {
     pub const SYN_TYP:      Color = Color::new(0x4E, 0xC9, 0xB0); // type
     pub const SYN_MAC:      Color = Color::new(0xBD, 0x63, 0xC5); // macro
     pub const SYN_PUN:      Color = Color::new(0xD4, 0xD4, 0xD4); // punctuation (neutral)
+    pub const SYN_LBL:      Color = Color::new(0xDC, 0xDC, 0xAA); // etiqueta (asm)
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -75,9 +109,10 @@ impl IdePal {
 #[derive(Clone, Copy, PartialEq)]
 pub enum MenuAction {
     None, Separator,
-    FileNew, FileOpen, FileSave, FileSaveAs, FileClose,
+    FileNew, FileOpen, FileSave, FileSaveAs, FileReload, FileClose,
     EditUndo, EditSelectAll, EditGoToLine,
-    ViewLineNumbers, ViewWordWrap,
+    EditFind, EditReplace, EditMark, EditCommand,
+    ViewLineNumbers, ViewWordWrap, ViewZen,
     HelpAbout, HelpKeys,
 }
 
@@ -98,6 +133,7 @@ const MENU_ARCH: &[MenuItem] = &[
     MenuItem::sep(),
     MenuItem::new("Guardar",         "Ctrl+S", MenuAction::FileSave),
     MenuItem::new("Guardar como...", "",       MenuAction::FileSaveAs),
+    MenuItem::new("Recargar",        "Ctrl+R", MenuAction::FileReload),
     MenuItem::sep(),
     MenuItem::new("Cerrar",          "Ctrl+W", MenuAction::FileClose),
 ];
@@ -106,10 +142,17 @@ const MENU_EDIT: &[MenuItem] = &[
     MenuItem::sep(),
     MenuItem::new("Selec. todo",     "Ctrl+A", MenuAction::EditSelectAll),
     MenuItem::new("Ir a línea...",   "Ctrl+G", MenuAction::EditGoToLine),
+    MenuItem::sep(),
+    MenuItem::new("Buscar...",       "Ctrl+F", MenuAction::EditFind),
+    MenuItem::new("Reemplazar...",   "Ctrl+H", MenuAction::EditReplace),
+    MenuItem::new("Marcar selec.",   "Ctrl+Esp", MenuAction::EditMark),
+    MenuItem::sep(),
+    MenuItem::new("Comando...",      "Ctrl+:", MenuAction::EditCommand),
 ];
 const MENU_VIEW: &[MenuItem] = &[
     MenuItem::new("Núm. de línea",   "",       MenuAction::ViewLineNumbers),
     MenuItem::new("Ajuste línea",    "",       MenuAction::ViewWordWrap),
+    MenuItem::new("Modo zen",        "F11",    MenuAction::ViewZen),
 ];
 const MENU_HELP: &[MenuItem] = &[
     MenuItem::new("Atajos (F1)",     "F1",     MenuAction::HelpKeys),
@@ -144,12 +187,8 @@ impl Lang {
     }
     pub fn label(self) -> &'static str { match self { Lang::Rust=>"Rust", Lang::C=>"C", Lang::Asm=>"ASM", Lang::Plain=>"TXT" } }
     pub fn icon(self) -> &'static str  { match self { Lang::Rust=>"rs", Lang::C=>"c", Lang::Asm=>"asm", Lang::Plain=>"txt" } }
-    pub fn icon_color(self) -> Color   { match self {
-        Lang::Rust  => Color::new(0xDE, 0x6A, 0x40),
-        Lang::C     => Color::new(0x44, 0x99, 0xFF),
-        Lang::Asm   => Color::new(0xCC, 0xAA, 0x00),
-        Lang::Plain => Color::new(0x88, 0x99, 0xAA),
-    }}
+    /// Color compartido con el Explorador (ui::filetype).
+    pub fn icon_color(self) -> Color   { crate::ui::filetype::lang_color(self.icon()) }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -161,6 +200,8 @@ const MAX_LINE_LEN:    usize = 512;
 const MAX_BUFFERS:     usize = 8;
 const PAGE_LINES:      usize = 64;
 const MAX_PAGES_TOTAL: usize = 64;
+/// Bytes guardados del nombre de la sección actual (status bar).
+const SECTION_MAX:     usize = 48;
 
 #[derive(Clone, Copy)]
 pub struct Line { pub data: [u8; MAX_LINE_LEN], pub len: usize }
@@ -225,10 +266,22 @@ pub struct TextBuffer {
     pub head_page: i32, pub tail_page: i32,
     pub page_cnt:  usize, pub line_cnt: usize,
     pub name:      [u8; 256], pub name_len: usize,
+    /// Ruta absoluta de origen en disco (vacía = buffer sin archivo).
+    pub path:      [u8; 256], pub path_len: usize,
     pub lang:      Lang,
     pub dirty:     bool,
     pub cursor_l:  usize, pub cursor_c: usize,
     pub scroll:    usize,
+    /// Ancla de la selección (línea, col): la región va de la marca al cursor.
+    pub mark:      Option<(usize, usize)>,
+    /// Sección (fn/struct/impl) que contiene al cursor; se recalcula solo
+    /// cuando cambia `cursor_l` (ver refresh_section).
+    pub section:     [u8; SECTION_MAX],
+    pub section_len: usize,
+    section_line:    usize,
+    /// Última profundidad de `/* */` calculada: (línea, profundidad). La
+    /// invalida cualquier edición en una línea anterior.
+    block_cache:     Cell<Option<(usize, u8)>>,
 }
 
 impl TextBuffer {
@@ -239,8 +292,10 @@ impl TextBuffer {
         let mut tb = TextBuffer {
             head_page: head, tail_page: head,
             page_cnt: if head >= 0 { 1 } else { 0 }, line_cnt: 1,
-            name: [0u8; 256], name_len: 0, lang, dirty: false,
-            cursor_l: 0, cursor_c: 0, scroll: 0,
+            name: [0u8; 256], name_len: 0, path: [0u8; 256], path_len: 0, lang, dirty: false,
+            cursor_l: 0, cursor_c: 0, scroll: 0, mark: None,
+            section: [0u8; SECTION_MAX], section_len: 0, section_line: usize::MAX,
+            block_cache: Cell::new(None),
         };
         let n = name.len().min(255);
         tb.name[..n].copy_from_slice(name.as_bytes());
@@ -261,7 +316,7 @@ impl TextBuffer {
             }
         }
         let mut cur_line_idx: usize = 0;
-        self.line_cnt = 1; self.cursor_l = 0; self.cursor_c = 0; self.scroll = 0;
+        self.line_cnt = 1; self.cursor_l = 0; self.cursor_c = 0; self.scroll = 0; self.mark = None;
         unsafe { let p = page_mut(self.head_page as usize); p.count = 1; p.lines[0] = Line::empty(); }
         for &b in data {
             if b == b'\n' {
@@ -278,6 +333,8 @@ impl TextBuffer {
             }
         }
         self.cursor_l = 0; self.cursor_c = 0; self.dirty = false;
+        self.section_line = usize::MAX;
+        self.block_cache.set(None);
     }
 
     pub fn serialize(&self, out: &mut [u8]) -> usize {
@@ -378,6 +435,143 @@ impl TextBuffer {
         }
     }
 
+    // ── Buscar / reemplazar ───────────────────────────────────────────────────
+
+    /// Región seleccionada (inicio, fin) ordenada, o None si no hay marca o
+    /// está vacía.
+    pub fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let m = self.mark?;
+        let c = (self.cursor_l, self.cursor_c);
+        if m == c { return None; }
+        Some(if m < c { (m, c) } else { (c, m) })
+    }
+
+    /// Primera aparición de `pat` en o después de `from` que termine antes
+    /// de `end` (exclusivo). Las coincidencias no cruzan líneas.
+    fn find_from(&self, pat: &[u8], from: (usize, usize), end: (usize, usize)) -> Option<(usize, usize)> {
+        if pat.is_empty() { return None; }
+        let last = end.0.min(self.line_cnt.saturating_sub(1));
+        for li in from.0..=last {
+            let Some(line) = self.get_line(li) else { break };
+            let data  = &line.data[..line.len];
+            let start = if li == from.0 { from.1 } else { 0 };
+            let limit = if li == end.0 { end.1.min(data.len()) } else { data.len() };
+            if start + pat.len() > limit { continue; }
+            if let Some(off) = data[start..limit].windows(pat.len()).position(|w| w == pat) {
+                return Some((li, start + off));
+            }
+        }
+        None
+    }
+
+    /// Sustituye `old_len` bytes en (l, c) por `new`. Falla sin tocar nada si
+    /// la línea resultante no cabe en MAX_LINE_LEN.
+    fn replace_at(&mut self, l: usize, c: usize, old_len: usize, new: &[u8]) -> bool {
+        let Some(line) = self.get_line_mut(l) else { return false };
+        if c + old_len > line.len { return false; }
+        let new_total = line.len - old_len + new.len();
+        if new_total > MAX_LINE_LEN { return false; }
+        line.data.copy_within(c + old_len..line.len, c + new.len());
+        line.data[c..c + new.len()].copy_from_slice(new);
+        line.len = new_total;
+        self.dirty = true;
+        true
+    }
+
+    // ── Palabra bajo el cursor ────────────────────────────────────────────────
+
+    /// Identificador que contiene al cursor (o que termina justo antes de
+    /// él) como (inicio, fin) en la línea actual. None sobre espacios,
+    /// puntuación o números.
+    pub fn word_at_cursor(&self) -> Option<(usize, usize)> {
+        let line = self.get_line(self.cursor_l)?;
+        let data = &line.data[..line.len];
+        let c = self.cursor_c.min(data.len());
+        let at = if c < data.len() && is_ident(data[c]) { c }
+                 else if c > 0 && is_ident(data[c - 1]) { c - 1 }
+                 else { return None };
+        let start = data[..at].iter().rposition(|&b| !is_ident(b)).map_or(0, |i| i + 1);
+        let end   = data[at..].iter().position(|&b| !is_ident(b)).map_or(data.len(), |i| at + i);
+        if !is_ident_start(data[start]) { return None; }
+        Some((start, end))
+    }
+
+    // ── Comentarios de bloque ─────────────────────────────────────────────────
+
+    /// Profundidad de `/* */` abierta al empezar la línea `li`. Recorre las
+    /// líneas anteriores con el mismo `highlight_line` que colorea, así estado
+    /// y colores nunca discrepan; parte de `block_cache` si está por encima,
+    /// de modo que redibujar con el mismo scroll (o bajando) no repite el
+    /// recorrido desde la línea 0.
+    pub fn block_comment_depth(&self, li: usize) -> u8 {
+        if !matches!(self.lang, Lang::Rust | Lang::C) { return 0; }
+        let li = li.min(self.line_cnt);
+        let (from, mut depth) = match self.block_cache.get() {
+            Some((l, d)) if l <= li => (l, d),
+            _ => (0, 0),
+        };
+        for l in from..li {
+            if let Some(line) = self.get_line(l) {
+                depth = highlight_line(&line.data[..line.len], self.lang, depth, |_, _, _| {});
+            }
+        }
+        self.block_cache.set(Some((li, depth)));
+        depth
+    }
+
+    /// La línea `li` cambió: la profundidad guardada para líneas posteriores
+    /// ya no vale.
+    fn invalidate_block_cache(&self, li: usize) {
+        if matches!(self.block_cache.get(), Some((l, _)) if l > li) { self.block_cache.set(None); }
+    }
+
+    // ── Sección actual ────────────────────────────────────────────────────────
+
+    pub fn section_str(&self) -> &str { core::str::from_utf8(&self.section[..self.section_len]).unwrap_or("") }
+
+    /// Busca hacia atrás desde el cursor la cabecera (fn/struct/impl…) cuyo
+    /// bloque lo contiene. Es textual: un '}' al principio de una línea
+    /// cierra la cabecera de arriba con su misma sangría, así que entre dos
+    /// funciones, o fuera de todas, el resultado queda vacío.
+    pub fn refresh_section(&mut self) {
+        if self.section_line == self.cursor_l { return; }
+        self.section_line = self.cursor_l;
+        self.section_len  = 0;
+        if !matches!(self.lang, Lang::Rust | Lang::C) { return; }
+
+        // Cierres pendientes por nivel de sangría
+        let mut closed = [0u16; 64];
+        let mut li = self.cursor_l.min(self.line_cnt.saturating_sub(1));
+        loop {
+            if let Some(line) = self.get_line(li) {
+                let data   = &line.data[..line.len];
+                let indent = data.iter().take_while(|&&b| b == b' ' || b == b'\t').count().min(63);
+                let body   = &data[indent..];
+                if let Some((kw, name)) = section_header(body, indent, self.lang) {
+                    // Cabecera de una línea (`fn f() {}`): cerrada salvo que sea la del cursor
+                    // y las que no abren bloque (`struct U;`, `fn f();` de un trait)
+                    let one_line = li != self.cursor_l
+                        && (body.iter().position(|&b| b == b'{').is_some_and(|o| body[o..].contains(&b'}'))
+                            || body.trim_ascii_end().ends_with(b";"));
+                    if closed[indent] > 0 {
+                        closed[indent] -= 1;
+                    } else if !one_line {
+                        let mut sec = [0u8; SECTION_MAX]; let mut p = 0;
+                        push_bytes(&mut sec, &mut p, kw);
+                        push_bytes(&mut sec, &mut p, b" ");
+                        push_bytes(&mut sec, &mut p, name);
+                        self.section = sec; self.section_len = p;
+                        return;
+                    }
+                } else if body.first() == Some(&b'}') && li != self.cursor_l {
+                    closed[indent] = closed[indent].saturating_add(1);
+                }
+            }
+            if li == 0 { break; }
+            li -= 1;
+        }
+    }
+
     // ── Paginación ────────────────────────────────────────────────────────────
 
     fn find_page_for_line(&self, li: usize) -> Option<(usize, usize)> {
@@ -433,6 +627,7 @@ impl TextBuffer {
         self.find_page_for_line(li).map(|(pidx, off)| unsafe { &page_ref(pidx).lines[off] })
     }
     fn get_line_mut(&mut self, li: usize) -> Option<&mut Line> {
+        self.invalidate_block_cache(li);
         if let Some((pidx, off)) = self.find_page_for_line(li) { unsafe { Some(&mut page_mut(pidx).lines[off]) } } else { None }
     }
 
@@ -463,6 +658,7 @@ impl TextBuffer {
     fn remove_last_line_slot(&mut self) {
         if self.line_cnt == 0 { return; }
         let last = self.line_cnt - 1;
+        self.invalidate_block_cache(last);
         if let Some((pidx, off)) = self.find_page_for_line(last) {
             unsafe {
                 let p = page_mut(pidx);
@@ -487,9 +683,109 @@ impl TextBuffer {
         let mut cur = self.head_page as i32;
         while cur >= 0 { let next = unsafe { page_ref(cur as usize).next }; unsafe { free_page(cur as usize); } cur = next; }
         self.head_page = -1; self.tail_page = -1; self.page_cnt = 0; self.line_cnt = 0;
+        self.block_cache.set(None);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Deshacer — historial de reemplazos
+// ─────────────────────────────────────────────────────────────────────────────
+//
+// Solo se registran reemplazos: cada grupo guarda el texto original, la
+// longitud del nuevo y las posiciones (línea, col) en el orden en que se
+// aplicaron, así que deshacer los recorre al revés y cada columna vuelve a
+// ser válida. "Reemplazar todo" es un único grupo. Cualquier otra edición
+// invalida el historial: las posiciones guardadas ya no serían fiables.
+
+const UNDO_GROUPS: usize = 16;
+const UNDO_POS:    usize = 2048;
+
+#[derive(Clone, Copy)]
+struct UndoGroup {
+    buf:     usize,
+    start:   usize,
+    count:   usize,
+    old:     [u8; INPUT_MAX],
+    old_len: usize,
+    new_len: usize,
+}
+impl UndoGroup {
+    const EMPTY: Self = UndoGroup { buf: 0, start: 0, count: 0, old: [0u8; INPUT_MAX], old_len: 0, new_len: 0 };
+}
+
+struct UndoLog {
+    groups: [UndoGroup; UNDO_GROUPS],
+    n:      usize,
+    pos:    [(u32, u32); UNDO_POS],
+    pos_n:  usize,
+}
+
+static mut UNDO: UndoLog = UndoLog {
+    groups: [UndoGroup::EMPTY; UNDO_GROUPS], n: 0,
+    pos: [(0, 0); UNDO_POS], pos_n: 0,
+};
+
+#[inline(always)] fn undo_log() -> &'static mut UndoLog { unsafe { &mut *core::ptr::addr_of_mut!(UNDO) } }
+
+impl UndoLog {
+    fn clear(&mut self) { self.n = 0; self.pos_n = 0; }
+
+    fn drop_oldest(&mut self) {
+        if self.n == 0 { return; }
+        let shift = self.groups[0].count;
+        self.pos.copy_within(shift..self.pos_n, 0);
+        self.pos_n -= shift;
+        self.groups.copy_within(1..self.n, 0);
+        self.n -= 1;
+        for g in self.groups[..self.n].iter_mut() { g.start -= shift; }
+    }
+
+    fn begin(&mut self, buf: usize, old: &[u8], new_len: usize) {
+        if self.n == UNDO_GROUPS { self.drop_oldest(); }
+        let mut g = UndoGroup::EMPTY;
+        g.buf = buf; g.start = self.pos_n; g.new_len = new_len;
+        g.old_len = old.len().min(INPUT_MAX);
+        g.old[..g.old_len].copy_from_slice(&old[..g.old_len]);
+        self.groups[self.n] = g;
+        self.n += 1;
+    }
+
+    /// Anota un reemplazo en el grupo abierto. Si no cabe ni descartando los
+    /// grupos antiguos se vacía todo el historial (y se devuelve false): el
+    /// grupo quedaría incompleto y deshacerlo corrompería el texto.
+    fn record(&mut self, l: usize, c: usize) -> bool {
+        if self.n == 0 { return false; }
+        while self.pos_n == UNDO_POS && self.n > 1 { self.drop_oldest(); }
+        if self.pos_n == UNDO_POS { self.clear(); return false; }
+        self.pos[self.pos_n] = (l as u32, c as u32);
+        self.pos_n += 1;
+        self.groups[self.n - 1].count += 1;
+        true
+    }
+
+    /// Cierra el grupo abierto; si no llegó a registrar nada se descarta.
+    fn end(&mut self) {
+        if self.n > 0 && self.groups[self.n - 1].count == 0 { self.n -= 1; }
     }
 }
 
+/// Sesión interactiva de "reemplazar": el cursor está sobre `cur` y el
+/// usuario decide (Enter/S = reemplazar, N = saltar, T = todo, Esc = fin).
+#[derive(Clone, Copy)]
+pub struct ReplaceSession {
+    cur:      (usize, usize),
+    /// Fin (exclusivo) del ámbito; su columna se corrige cuando un reemplazo
+    /// cambia la longitud de esa misma línea.
+    end:      (usize, usize),
+    in_sel:   bool,
+    done:     u32,
+    /// Coincidencias que no se reemplazaron porque la línea excedería
+    /// MAX_LINE_LEN.
+    too_long: u32,
+    /// El historial de deshacer se vació por falta de espacio.
+    no_undo:  bool,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // IdeState
 // ─────────────────────────────────────────────────────────────────────────────
@@ -502,11 +798,25 @@ pub struct IdeState {
     pub status_len: usize,
     pub status_err: bool,
     pub menu:       MenuState,
+    pub menu_hover: usize,  // item del dropdown bajo el ratón (usize::MAX = ninguno)
     pub show_ln:    bool,
     pub show_help:  bool,   // F1 = overlay de atajos
+    pub zen:        bool,   // F11 = solo el área de edición
     pub input:      InputBox,
     pub save_path:  [u8; 256],
     pub save_plen:  usize,
+    pub find_pat:   [u8; INPUT_MAX],
+    pub find_len:   usize,
+    pub repl_txt:   [u8; INPUT_MAX],
+    pub repl_len:   usize,
+    pub replace:    Option<ReplaceSession>,
+    /// Línea confirmada en el minibuffer, pendiente de ejecutar (0 = nada).
+    pending_cmd:    [u8; INPUT_MAX],
+    pending_len:    usize,
+    /// Recarga del buffer activo pedida (y confirmada), pendiente de leer.
+    reload_req:     bool,
+    /// Filas visibles en el último handle_key (para acciones desde el menú).
+    last_vis:       usize,
 }
 
 impl IdeState {
@@ -516,10 +826,18 @@ impl IdeState {
             active:     0, buf_count: 0,
             status_msg: [0u8; 80], status_len: 0, status_err: false,
             menu:       MenuState::Closed,
+            menu_hover: usize::MAX,
             show_ln:    true,
             show_help:  false,
+            zen:        false,
             input:      InputBox::new(),
             save_path:  [0u8; 256], save_plen: 0,
+            find_pat:   [0u8; INPUT_MAX], find_len: 0,
+            repl_txt:   [0u8; INPUT_MAX], repl_len: 0,
+            replace:    None,
+            pending_cmd: [0u8; INPUT_MAX], pending_len: 0,
+            reload_req: false,
+            last_vis:   1,
         };
         ide.open_new("untitled.txt");
         ide
@@ -537,14 +855,20 @@ impl IdeState {
         false
     }
 
-    pub fn open_with_data(&mut self, name: &str, data: &[u8]) -> bool {
+    /// Abre `data` en un buffer nuevo; `path` es su ruta absoluta en disco
+    /// (vacía si no viene de un archivo) y habilita Recargar.
+    pub fn open_with_data(&mut self, name: &str, path: &[u8], data: &[u8]) -> bool {
         if self.buf_count >= MAX_BUFFERS { return false; }
         for i in 0..MAX_BUFFERS {
             if self.buffers[i].is_none() {
                 let mut buf = TextBuffer::new_empty(name);
                 buf.load_text(data);
+                let pn = path.len().min(256);
+                buf.path[..pn].copy_from_slice(&path[..pn]);
+                buf.path_len = pn;
                 self.buffers[i] = Some(buf);
                 self.active = i; self.buf_count += 1;
+                self.refresh_section();
                 self.set_status("Archivo abierto", false); return true;
             }
         }
@@ -553,6 +877,8 @@ impl IdeState {
 
     pub fn close_active(&mut self) {
         if let Some(mut buf) = self.buffers[self.active].take() { buf.clear_pages(); }
+        undo_log().clear();
+        self.replace = None;
         if self.buf_count > 0 { self.buf_count -= 1; }
         for i in 0..MAX_BUFFERS { if self.buffers[i].is_some() { self.active = i; return; } }
         self.active = 0; self.open_new("untitled.txt");
@@ -578,6 +904,9 @@ impl IdeState {
         match action {
             MenuAction::FileNew     => { self.open_new("untitled.txt"); }
             MenuAction::FileOpen    => { self.input.start(InputMode::SaveAs, ""); self.set_status("Ruta del archivo a abrir:", false); }
+            MenuAction::FileSave | MenuAction::FileSaveAs if crate::drivers::storage::fat32::effective_read_only() => {
+                self.set_status("Volumen de solo lectura — 'remount rw' en el terminal", true);
+            }
             MenuAction::FileSave    => {
                 if self.save_plen == 0 {
                     let name = if let Some(b) = &self.buffers[self.active] { b.name_str() } else { "untitled.txt" };
@@ -593,43 +922,392 @@ impl IdeState {
                 self.input.start(InputMode::SaveAs, name);
                 self.set_status("Guardar como:", false);
             }
+            MenuAction::FileReload  => {
+                match self.buffers[self.active].as_ref().map(|b| (b.path_len > 0, b.dirty)) {
+                    Some((false, _)) => self.set_status("Este buffer no viene de un archivo en disco", true),
+                    Some((true, true)) => {
+                        self.input.start(InputMode::Reload, "");
+                        self.set_status("Hay cambios sin guardar: se perderan", true);
+                    }
+                    Some((true, false)) => self.reload_req = true,
+                    None => {}
+                }
+            }
             MenuAction::FileClose   => { self.close_active(); }
-            MenuAction::EditUndo    => { self.set_status("Deshacer: no implementado", true); }
+            MenuAction::EditUndo    => { self.undo(); }
             MenuAction::EditSelectAll => { self.set_status("Selec. todo: no implementado", true); }
             MenuAction::EditGoToLine => { self.input.start(InputMode::SaveAs, ""); self.set_status("Ir a línea:", false); }
+            MenuAction::EditFind    => {
+                let pat = core::str::from_utf8(&self.find_pat[..self.find_len]).unwrap_or("");
+                self.input.start(InputMode::Find, pat);
+                self.set_status("Enter = siguiente coincidencia", false);
+            }
+            MenuAction::EditReplace => {
+                let pat = core::str::from_utf8(&self.find_pat[..self.find_len]).unwrap_or("");
+                self.input.start(InputMode::ReplaceFind, pat);
+                let in_sel = self.buffers[self.active].as_ref().is_some_and(|b| b.selection().is_some());
+                self.set_status(if in_sel { "Ambito: seleccion" } else { "Ambito: todo el archivo" }, false);
+            }
+            MenuAction::EditMark    => {
+                if let Some(buf) = self.buffers[self.active].as_mut() {
+                    if buf.mark.take().is_some() {
+                        self.set_status("Seleccion cancelada", false);
+                    } else {
+                        buf.mark = Some((buf.cursor_l, buf.cursor_c));
+                        self.set_status("Marca puesta: mueve el cursor para seleccionar", false);
+                    }
+                }
+            }
+            MenuAction::EditCommand => {
+                self.input.start(InputMode::Command, "");
+                self.set_status("Comando del terminal (salida larga: F2)", false);
+            }
             MenuAction::ViewLineNumbers => {
                 self.show_ln = !self.show_ln;
                 self.set_status(if self.show_ln { "Núm. de línea: ON" } else { "Núm. de línea: OFF" }, false);
             }
             MenuAction::ViewWordWrap => { self.set_status("Ajuste de línea: no implementado", true); }
+            MenuAction::ViewZen      => { self.set_zen(true); }
             MenuAction::HelpKeys    => { self.show_help = true; }
-            MenuAction::HelpAbout   => { self.set_status("PORTIX IDE v0.8.0 — Kernel x86_64 Bare-Metal", false); }
+            MenuAction::HelpAbout   => {
+                let mut msg = [0u8; 80]; let mut p = 0;
+                push_bytes(&mut msg, &mut p, b"PORTIX IDE v");
+                push_bytes(&mut msg, &mut p, VERSION.as_bytes());
+                push_bytes(&mut msg, &mut p, "  Kernel x86_64 Bare-Metal v".as_bytes());
+                push_bytes(&mut msg, &mut p, crate::VERSION.as_bytes());
+                self.set_status(core::str::from_utf8(&msg[..p]).unwrap_or(""), false);
+            }
             MenuAction::Separator   => {}
             MenuAction::None        => {}
         }
+        self.refresh_section();
         true
     }
 
     pub fn confirm_input(&mut self) -> bool {
         let mode = self.input.mode;
-        let tb = &self.input.buf[..self.input.len];
-        if let InputMode::SaveAs = mode {
-            if self.input.len > 0 {
+        let mut text = [0u8; INPUT_MAX];
+        let len = self.input.len;
+        text[..len].copy_from_slice(&self.input.buf[..len]);
+        let tb = &text[..len];
+        // Cerrar antes: ReplaceFind encadena otro prompt
+        self.input.close();
+        match mode {
+            InputMode::SaveAs if len > 0 => {
                 if let Some(buf) = self.buffers[self.active].as_mut() {
-                    let n = self.input.len.min(256);
+                    let n = len.min(256);
                     buf.name[..n].copy_from_slice(&tb[..n]);
                     buf.name_len = n; buf.dirty = false;
-                    let pn = self.input.len.min(256);
+                    let pn = len.min(256);
                     self.save_path[..pn].copy_from_slice(&tb[..pn]);
                     self.save_plen = pn;
                 }
                 self.set_status("Nombre actualizado", false);
             }
+            InputMode::Find | InputMode::ReplaceFind if len == 0 => {
+                self.set_status("Patron vacio", true);
+            }
+            InputMode::Find => {
+                self.find_pat[..len].copy_from_slice(tb); self.find_len = len;
+                self.find_next();
+            }
+            InputMode::ReplaceFind => {
+                self.find_pat[..len].copy_from_slice(tb); self.find_len = len;
+                let repl = core::str::from_utf8(&self.repl_txt[..self.repl_len]).unwrap_or("");
+                self.input.start(InputMode::ReplaceWith, repl);
+                self.set_status("Vacio = borrar las coincidencias", false);
+            }
+            InputMode::ReplaceWith => {
+                self.repl_txt[..len].copy_from_slice(tb); self.repl_len = len;
+                self.start_replace();
+            }
+            InputMode::Reload => self.reload_req = true,
+            InputMode::Command if tb.trim_ascii() == b"reload" => {
+                self.execute_menu(MenuAction::FileReload);
+            }
+            InputMode::Command if len > 0 => {
+                self.pending_cmd[..len].copy_from_slice(tb); self.pending_len = len;
+            }
+            InputMode::Command => self.set_status("", false),
+            _ => {}
+        }
+        true
+    }
+
+    /// Saca la línea pendiente del minibuffer (la ejecuta main, que tiene
+    /// el terminal). Devuelve su longitud; 0 si no hay nada.
+    pub fn take_command(&mut self, out: &mut [u8; INPUT_MAX]) -> usize {
+        let n = core::mem::take(&mut self.pending_len);
+        out[..n].copy_from_slice(&self.pending_cmd[..n]);
+        n
+    }
+
+    /// Saca la recarga pendiente: copia en `out` la ruta del buffer activo
+    /// (la lee main, que tiene el volumen). Devuelve su longitud; 0 si no hay.
+    pub fn take_reload(&mut self, out: &mut [u8; 256]) -> usize {
+        if !core::mem::take(&mut self.reload_req) { return 0; }
+        let Some(buf) = self.buffers[self.active].as_ref() else { return 0 };
+        out[..buf.path_len].copy_from_slice(&buf.path[..buf.path_len]);
+        buf.path_len
+    }
+
+    /// Sustituye el buffer activo por lo leído del disco. El cursor se
+    /// conserva si su línea sigue existiendo; si el archivo ya no está, el
+    /// buffer se mantiene y queda marcado como modificado.
+    pub fn finish_reload(&mut self, res: Result<&[u8], FatError>) {
+        let vis = self.last_vis;
+        let Some(buf) = self.buffers[self.active].as_mut() else { return };
+        match res {
+            Ok(data) => {
+                let (l, c, scroll) = (buf.cursor_l, buf.cursor_c, buf.scroll);
+                buf.load_text(data);
+                if l < buf.line_cnt {
+                    buf.cursor_l = l; buf.cursor_c = c; buf.scroll = scroll;
+                    buf.clamp_col();
+                    buf.ensure_scroll(vis);
+                }
+                undo_log().clear();
+                self.replace = None;
+                self.set_status("Recargado desde disco", false);
+            }
+            Err(FatError::NotFound) | Err(FatError::IsDir) => {
+                buf.dirty = true;
+                self.set_status("El archivo ya no existe en disco: se conserva el buffer", true);
+            }
+            Err(FatError::NotFat32) => self.set_status("Recargar: no hay volumen FAT32 montado", true),
+            Err(_) => self.set_status("Recargar: error al leer el disco", true),
+        }
+    }
+
+    /// Resultado de un comando del minibuffer en la status bar: la primera
+    /// línea de salida y, si hubo más, cuántas quedaron en el terminal.
+    pub fn show_command_result(&mut self, first: &[u8], more: usize, ok: bool) {
+        const SHOWN: usize = 48;
+        let first = first.trim_ascii();
+        let mut msg = [0u8; 80]; let mut p = 0; let mut tmp = [0u8; 12];
+        if first.is_empty() {
+            push_bytes(&mut msg, &mut p, if ok { b"Comando ejecutado" } else { b"El comando fallo" });
+        } else if first.len() > SHOWN {
+            push_bytes(&mut msg, &mut p, &first[..SHOWN - 2]);
+            push_bytes(&mut msg, &mut p, b"..");
+        } else {
+            push_bytes(&mut msg, &mut p, first);
+        }
+        if more > 0 {
+            push_bytes(&mut msg, &mut p, b" (+");
+            push_bytes(&mut msg, &mut p, fmt_usize(more, &mut tmp).as_bytes());
+            push_bytes(&mut msg, &mut p, b" en F2)");
+        }
+        self.set_status(core::str::from_utf8(&msg[..p]).unwrap_or(""), !ok);
+    }
+
+    /// ¿Debe llegar Esc al IDE? (cancelar un prompt, la sesión de reemplazo
+    /// o la selección).
+    pub fn wants_escape(&self) -> bool {
+        self.input.is_active() || self.replace.is_some() || self.zen
+            || self.buffers[self.active].as_ref().is_some_and(|b| b.mark.is_some())
+    }
+
+    // ── Buscar / reemplazar ───────────────────────────────────────────────────
+
+    /// Siguiente aparición del patrón tras el cursor; al llegar al final
+    /// vuelve a empezar desde arriba.
+    fn find_next(&mut self) {
+        let pat = &self.find_pat[..self.find_len];
+        let Some(buf) = self.buffers[self.active].as_mut() else { return };
+        let end = (buf.line_cnt, 0);
+        let mut wrapped = false;
+        let hit = buf.find_from(pat, (buf.cursor_l, buf.cursor_c + 1), end)
+            .or_else(|| { wrapped = true; buf.find_from(pat, (0, 0), end) });
+        match hit {
+            Some((l, c)) => {
+                buf.cursor_l = l; buf.cursor_c = c;
+                buf.ensure_scroll(self.last_vis);
+                self.set_status(if wrapped { "Encontrado (desde el principio)" } else { "Encontrado" }, false);
+            }
+            None => self.set_status("No encontrado", true),
+        }
+    }
+
+    fn start_replace(&mut self) {
+        let pat = &self.find_pat[..self.find_len];
+        let Some(buf) = self.buffers[self.active].as_mut() else { return };
+        let (start, end, in_sel) = match buf.selection() {
+            Some((a, b)) => (a, b, true),
+            None         => ((0, 0), (buf.line_cnt, 0), false),
+        };
+        buf.mark = None;
+        let Some(cur) = buf.find_from(pat, start, end) else {
+            self.set_status(if in_sel { "Sin coincidencias en la seleccion" } else { "Sin coincidencias" }, true);
+            return;
+        };
+        buf.cursor_l = cur.0; buf.cursor_c = cur.1;
+        buf.ensure_scroll(self.last_vis);
+        self.replace = Some(ReplaceSession { cur, end, in_sel, done: 0, too_long: 0, no_undo: false });
+        self.set_status("Reemplazar? Enter/S=si  N=saltar  T=todos  Esc=fin", false);
+    }
+
+    /// Reemplaza la coincidencia actual de `s` y devuelve desde dónde seguir
+    /// buscando (tras el texto nuevo, para no volver a encontrarlo).
+    fn replace_one(buf: &mut TextBuffer, s: &mut ReplaceSession, pat: &[u8], new: &[u8]) -> (usize, usize) {
+        let (l, c) = s.cur;
+        if !buf.replace_at(l, c, pat.len(), new) {
+            s.too_long += 1;
+            return (l, c + pat.len());
+        }
+        if !undo_log().record(l, c) { s.no_undo = true; }
+        if l == s.end.0 { s.end.1 = (s.end.1 + new.len()).saturating_sub(pat.len()); }
+        s.done += 1;
+        (l, c + new.len())
+    }
+
+    fn handle_replace_key(&mut self, key: Key, vis: usize) {
+        let Some(mut s) = self.replace else { return };
+        let pat = &self.find_pat[..self.find_len];
+        let new = &self.repl_txt[..self.repl_len];
+        let Some(buf) = self.buffers[self.active].as_mut() else { self.replace = None; return };
+
+        let next = match key {
+            Key::Enter | Key::Char(b's') | Key::Char(b'S') => {
+                let log = undo_log();
+                log.begin(self.active, pat, new.len());
+                let from = Self::replace_one(buf, &mut s, pat, new);
+                log.end();
+                buf.find_from(pat, from, s.end)
+            }
+            Key::Char(b'n') | Key::Char(b'N') => buf.find_from(pat, (s.cur.0, s.cur.1 + pat.len()), s.end),
+            Key::Char(b't') | Key::Char(b'T') => {
+                let log = undo_log();
+                log.begin(self.active, pat, new.len());
+                loop {
+                    let from = Self::replace_one(buf, &mut s, pat, new);
+                    match buf.find_from(pat, from, s.end) {
+                        Some(p) => s.cur = p,
+                        None    => break,
+                    }
+                }
+                log.end();
+                None
+            }
+            Key::Escape => None,
+            _ => return,
+        };
+
+        match next {
+            Some(p) => {
+                s.cur = p;
+                buf.cursor_l = p.0; buf.cursor_c = p.1;
+                buf.ensure_scroll(vis);
+                self.replace = Some(s);
+            }
+            None => {
+                buf.clamp_col();
+                buf.ensure_scroll(vis);
+                self.replace = None;
+                let mut msg = [0u8; 80]; let mut p = 0; let mut tmp = [0u8; 12];
+                push_bytes(&mut msg, &mut p, fmt_usize(s.done as usize, &mut tmp).as_bytes());
+                push_bytes(&mut msg, &mut p, if s.done == 1 { b" reemplazo" } else { b" reemplazos" });
+                if s.in_sel { push_bytes(&mut msg, &mut p, b" en la seleccion"); }
+                if s.too_long > 0 {
+                    push_bytes(&mut msg, &mut p, b", ");
+                    push_bytes(&mut msg, &mut p, fmt_usize(s.too_long as usize, &mut tmp).as_bytes());
+                    push_bytes(&mut msg, &mut p, b" no caben");
+                }
+                if s.no_undo { push_bytes(&mut msg, &mut p, b" (sin deshacer)"); }
+                self.set_status(core::str::from_utf8(&msg[..p]).unwrap_or(""), s.too_long > 0 || s.no_undo);
+            }
         }
-        self.input.close(); true
+    }
+
+    /// Deshace el último grupo de reemplazos del historial.
+    fn undo(&mut self) {
+        let log = undo_log();
+        if log.n == 0 { self.set_status("Nada que deshacer (solo reemplazos)", false); return; }
+        let g = log.groups[log.n - 1];
+        if g.buf != self.active { self.set_status("Deshacer: el ultimo reemplazo es de otro archivo", true); return; }
+        let Some(buf) = self.buffers[self.active].as_mut() else { return };
+        for i in (g.start..g.start + g.count).rev() {
+            let (l, c) = log.pos[i];
+            let _ = buf.replace_at(l as usize, c as usize, g.new_len, &g.old[..g.old_len]);
+        }
+        let (l, c) = log.pos[g.start];
+        buf.cursor_l = l as usize; buf.cursor_c = c as usize;
+        buf.mark = None;
+        buf.ensure_scroll(self.last_vis);
+        log.n -= 1; log.pos_n = g.start;
+
+        let mut msg = [0u8; 80]; let mut p = 0; let mut tmp = [0u8; 12];
+        push_bytes(&mut msg, &mut p, b"Deshecho: ");
+        push_bytes(&mut msg, &mut p, fmt_usize(g.count, &mut tmp).as_bytes());
+        push_bytes(&mut msg, &mut p, if g.count == 1 { b" reemplazo" } else { b" reemplazos" });
+        self.set_status(core::str::from_utf8(&msg[..p]).unwrap_or(""), false);
+    }
+
+    fn refresh_section(&mut self) {
+        if let Some(buf) = self.buffers[self.active].as_mut() { buf.refresh_section(); }
+    }
+
+    /// Entra o sale del modo zen. Al entrar se cierra el menú (su barra deja
+    /// de estar visible).
+    pub fn set_zen(&mut self, on: bool) {
+        self.zen = on;
+        if on { self.menu = MenuState::Closed; }
+        else  { self.set_status("Modo zen desactivado", false); }
+    }
+
+    /// Rueda del ratón (`delta` > 0 = arriba): desplaza la vista del buffer
+    /// activo WHEEL_LINES líneas por muesca sin mover el cursor ni pasar de
+    /// la última página. Con un menú abierto solo lo cierra. Devuelve true
+    /// si hay que redibujar.
+    pub fn wheel(&mut self, delta: i32, vis: usize) -> bool {
+        if self.menu != MenuState::Closed { self.menu = MenuState::Closed; return true; }
+        if self.show_help || delta == 0 { return false; }
+        let Some(buf) = self.buffers[self.active].as_mut() else { return false };
+        let step = delta.unsigned_abs() as usize * WHEEL_LINES;
+        let max  = buf.line_cnt.saturating_sub(vis).max(buf.scroll);
+        let new  = if delta > 0 { buf.scroll.saturating_sub(step) } else { (buf.scroll + step).min(max) };
+        if new == buf.scroll { return false; }
+        buf.scroll = new;
+        true
+    }
+
+    /// Clic en el número de la línea `line`. Sin Shift lleva el cursor al
+    /// inicio de la línea y quita la marca; con Shift marca la línea entera
+    /// o, si ya había marca, la extiende hasta esa línea. Devuelve true si
+    /// hay que redibujar.
+    pub fn click_gutter(&mut self, line: usize, shift: bool) -> bool {
+        if self.show_help { return false; }
+        let vis = self.last_vis;
+        let Some(buf) = self.buffers[self.active].as_mut() else { return false };
+        if line >= buf.line_cnt { return false; }
+        let len = buf.get_line(line).map(|l| l.len).unwrap_or(0);
+        match (shift, buf.mark) {
+            (false, _) => {
+                buf.mark = None;
+                buf.cursor_l = line; buf.cursor_c = 0;
+            }
+            (true, None) => {
+                buf.mark = Some((line, 0));
+                buf.cursor_l = line; buf.cursor_c = len;
+            }
+            // Hacia abajo hasta el final de la línea, hacia arriba hasta su inicio
+            (true, Some((ml, _))) => {
+                buf.cursor_l = line;
+                buf.cursor_c = if line >= ml { len } else { 0 };
+            }
+        }
+        buf.ensure_scroll(vis);
+        self.refresh_section();
+        true
     }
 
     pub fn handle_key(&mut self, key: Key, ctrl: bool, vis: usize) -> bool {
+        let used = self.handle_key_inner(key, ctrl, vis);
+        self.refresh_section();
+        used
+    }
+
+    fn handle_key_inner(&mut self, key: Key, ctrl: bool, vis: usize) -> bool {
         use crate::ui::input::InputMode;
 
         // Cerrar help overlay primero
@@ -638,6 +1316,7 @@ impl IdeState {
         }
 
         // Input activo consume todo
+        self.last_vis = vis;
         if self.input.mode != InputMode::None {
             if let Some(confirmed) = self.input.feed(key) {
                 if confirmed { self.confirm_input(); }
@@ -646,24 +1325,50 @@ impl IdeState {
             return true;
         }
 
+        // Sesión de reemplazo activa consume todo
+        if self.replace.is_some() { self.handle_replace_key(key, vis); return true; }
+
         // Escape cierra menú
         if key == Key::Escape && self.menu != MenuState::Closed { self.menu = MenuState::Closed; return true; }
         // F1 = help
         if key == Key::F1 { self.show_help = true; return true; }
+        // F11 = modo zen
+        if key == Key::F11 { self.set_zen(!self.zen); return true; }
 
         if ctrl {
             match key {
                 Key::Char(b's') | Key::Char(b'S') => return self.execute_menu(MenuAction::FileSave),
                 Key::Char(b'n') | Key::Char(b'N') => return self.execute_menu(MenuAction::FileNew),
                 Key::Char(b'w') | Key::Char(b'W') => return self.execute_menu(MenuAction::FileClose),
+                Key::Char(b'r') | Key::Char(b'R') => return self.execute_menu(MenuAction::FileReload),
+                Key::Char(b'f') | Key::Char(b'F') => return self.execute_menu(MenuAction::EditFind),
+                Key::Char(b'h') | Key::Char(b'H') => return self.execute_menu(MenuAction::EditReplace),
+                Key::Char(b'z') | Key::Char(b'Z') => return self.execute_menu(MenuAction::EditUndo),
+                Key::Char(b' ')                   => return self.execute_menu(MenuAction::EditMark),
+                Key::Char(b':') | Key::Char(b';') => return self.execute_menu(MenuAction::EditCommand),
                 Key::Tab | Key::Right => { self.switch_next(); return true; }
                 Key::Left => { self.switch_prev(); return true; }
                 _ => {}
             }
         }
 
+        // Esc: primero cancela la selección; si no hay, sale del modo zen
+        if key == Key::Escape {
+            let marked = self.buffers[self.active].as_mut().is_some_and(|b| b.mark.take().is_some());
+            if marked { self.set_status("Seleccion cancelada", false); }
+            else if self.zen { self.set_zen(false); }
+            return true;
+        }
+
         let Some(buf) = self.buffers[self.active].as_mut() else { return false };
 
+        // Cualquier edición mueve columnas: la marca y el historial de
+        // reemplazos dejan de ser válidos.
+        if matches!(key, Key::Enter | Key::Tab | Key::Backspace | Key::Delete | Key::Char(0x20..=0x7E)) {
+            buf.mark = None;
+            undo_log().clear();
+        }
+
         match key {
             Key::Up    => { if buf.cursor_l > 0 { buf.cursor_l -= 1; buf.clamp_col(); } buf.ensure_scroll(vis); }
             Key::Down  => { if buf.cursor_l + 1 < buf.line_cnt { buf.cursor_l += 1; buf.clamp_col(); } buf.ensure_scroll(vis); }
@@ -723,14 +1428,178 @@ const C_KEYWORDS: &[&[u8]] = &[
     b"const",b"volatile",b"sizeof",b"NULL",b"true",b"false",
 ];
 
-pub fn highlight_line<F>(line: &[u8], lang: Lang, mut emit: F) where F: FnMut(usize, usize, Color) {
-    if lang == Lang::Plain { emit(0, line.len(), IdePal::TEXT); return; }
+/// Mnemónicos x86 habituales (NASM y GAS sin sufijo de tamaño). Los saltos
+/// y `set`/`cmov` condicionales van con sus alias más comunes.
+const ASM_MNEMONICS: &[&[u8]] = &[
+    b"mov", b"movzx", b"movsx", b"movsxd", b"lea", b"xchg", b"push", b"pop",
+    b"pusha", b"popa", b"pushad", b"popad", b"pushf", b"popf", b"pushfd", b"popfd",
+    b"pushfq", b"popfq", b"add", b"adc", b"sub", b"sbb", b"inc", b"dec", b"neg",
+    b"mul", b"imul", b"div", b"idiv", b"and", b"or", b"xor", b"not", b"test",
+    b"cmp", b"shl", b"shr", b"sal", b"sar", b"rol", b"ror", b"rcl", b"rcr",
+    b"bt", b"bts", b"btr", b"btc", b"bsf", b"bsr", b"bswap", b"cbw", b"cwde",
+    b"cdqe", b"cwd", b"cdq", b"cqo",
+    b"jmp", b"call", b"ret", b"retf", b"retfq", b"iret", b"iretd", b"iretq",
+    b"je", b"jne", b"jz", b"jnz", b"ja", b"jae", b"jb", b"jbe", b"jg", b"jge",
+    b"jl", b"jle", b"jc", b"jnc", b"jo", b"jno", b"js", b"jns", b"jp", b"jnp",
+    b"jcxz", b"jecxz", b"jrcxz", b"loop", b"loope", b"loopne",
+    b"sete", b"setne", b"setz", b"setnz", b"seta", b"setb", b"setg", b"setl",
+    b"cmove", b"cmovne", b"cmovz", b"cmovnz", b"cmova", b"cmovb", b"cmovg", b"cmovl",
+    b"int", b"into", b"syscall", b"sysret", b"sysretq", b"sysenter", b"sysexit",
+    b"cli", b"sti", b"clc", b"stc", b"cmc", b"cld", b"std", b"hlt", b"nop", b"pause",
+    b"in", b"out", b"insb", b"insw", b"insd", b"outsb", b"outsw", b"outsd",
+    b"rep", b"repe", b"repz", b"repne", b"repnz", b"lock",
+    b"movsb", b"movsw", b"movsd", b"movsq", b"stosb", b"stosw", b"stosd", b"stosq",
+    b"lodsb", b"lodsw", b"lodsd", b"lodsq", b"scasb", b"cmpsb",
+    b"lgdt", b"lidt", b"sgdt", b"sidt", b"ltr", b"lldt", b"invlpg", b"wbinvd",
+    b"cpuid", b"rdtsc", b"rdtscp", b"rdmsr", b"wrmsr", b"xgetbv", b"xsetbv",
+    b"fxsave", b"fxrstor", b"xsave", b"xrstor", b"swapgs", b"ud2", b"enter", b"leave",
+    b"movaps", b"movups", b"movdqa", b"movdqu", b"movq", b"movd", b"pxor", b"xorps",
+];
+
+/// Registros con nombre propio. r8..r15 (con b/w/d) y xmm/ymm/zmm N los
+/// reconoce `is_asm_register` por patrón.
+const ASM_REGISTERS: &[&[u8]] = &[
+    b"rax", b"rbx", b"rcx", b"rdx", b"rsi", b"rdi", b"rbp", b"rsp",
+    b"eax", b"ebx", b"ecx", b"edx", b"esi", b"edi", b"ebp", b"esp",
+    b"ax", b"bx", b"cx", b"dx", b"si", b"di", b"bp", b"sp",
+    b"al", b"ah", b"bl", b"bh", b"cl", b"ch", b"dl", b"dh",
+    b"sil", b"dil", b"bpl", b"spl",
+    b"cs", b"ds", b"es", b"fs", b"gs", b"ss",
+    b"rip", b"eip", b"ip", b"rflags", b"eflags",
+    b"cr0", b"cr2", b"cr3", b"cr4", b"cr8",
+    b"dr0", b"dr1", b"dr2", b"dr3", b"dr6", b"dr7",
+];
+
+/// Directivas NASM sin punto y modificadores de tamaño de operando.
+const ASM_DIRECTIVES: &[&[u8]] = &[
+    b"bits", b"org", b"section", b"segment", b"global", b"extern", b"align",
+    b"times", b"equ", b"db", b"dw", b"dd", b"dq", b"resb", b"resw", b"resd", b"resq",
+    b"incbin", b"default", b"rel", b"abs",
+    b"byte", b"word", b"dword", b"qword", b"tword", b"oword", b"ptr",
+    b"short", b"near", b"far", b"o16", b"o32", b"o64", b"a16", b"a32",
+];
+
+fn is_kw_ci(w: &[u8], list: &[&[u8]]) -> bool { list.iter().any(|k| k.eq_ignore_ascii_case(w)) }
+
+fn is_asm_register(w: &[u8]) -> bool {
+    if is_kw_ci(w, ASM_REGISTERS) { return true; }
+    let lw = |i: usize| w[i].to_ascii_lowercase();
+    // r8..r15 con sufijo opcional b/w/d
+    if w.len() >= 2 && lw(0) == b'r' {
+        let d = &w[1..];
+        let d = match d.last().map(u8::to_ascii_lowercase) {
+            Some(b'b' | b'w' | b'd') => &d[..d.len() - 1],
+            _ => d,
+        };
+        return matches!(d, b"8" | b"9" | b"10" | b"11" | b"12" | b"13" | b"14" | b"15");
+    }
+    // xmm0..31, ymm0..31, zmm0..31 (sin ceros a la izquierda)
+    let d = w.get(3..).unwrap_or(b"");
+    w.len() >= 4 && matches!(lw(0), b'x' | b'y' | b'z') && lw(1) == b'm' && lw(2) == b'm'
+        && d.len() <= 2 && d.iter().all(u8::is_ascii_digit) && (d.len() == 1 || d[0] != b'0')
+        && d.iter().fold(0u32, |n, &b| n * 10 + (b - b'0') as u32) <= 31
+}
+
+/// Mnemónico, también con sufijo de tamaño AT&T (`movq`, `pushl`...).
+fn is_asm_mnemonic(w: &[u8]) -> bool {
+    if is_kw_ci(w, ASM_MNEMONICS) { return true; }
+    w.len() > 2 && matches!(w[w.len() - 1].to_ascii_lowercase(), b'b' | b'w' | b'l' | b'q')
+        && is_kw_ci(&w[..w.len() - 1], ASM_MNEMONICS)
+}
+
+/// Token de ensamblador en `i`: etiquetas (`x:`, `.l:`), directivas,
+/// mnemónicos, registros (`rax`, `%rax` en AT&T), preprocesador NASM
+/// (`%define`), inmediatos AT&T (`$0x10`) y los símbolos `$`/`$$` de NASM
+/// (`times 510-($-$$) db 0`). None si en `i` no empieza
+/// ninguno (números, cadenas y puntuación siguen el camino común).
+fn asm_token<F: FnMut(usize, usize, Color)>(line: &[u8], i: usize, emit: &mut F) -> Option<usize> {
+    let word_end = |mut j: usize| { while j < line.len() && is_ident(line[j]) { j += 1; } j };
+    let next = line.get(i + 1).copied().unwrap_or(0);
+    // Solo es etiqueta al principio de la línea: `[es:di]` es un segmento
+    let label = |e: usize| line.get(e) == Some(&b':') && line[..i].iter().all(|&b| b == b' ' || b == b'\t');
+    match line[i] {
+        b'%' if is_ident_start(next) => {
+            let e = word_end(i + 1);
+            let color = if is_asm_register(&line[i + 1..e]) { IdePal::SYN_TYP } else { IdePal::SYN_MAC };
+            emit(i, e, color);
+            Some(e)
+        }
+        b'$' if next.is_ascii_digit() || (next == b'-' && line.get(i + 2).is_some_and(u8::is_ascii_digit)) => {
+            let e = word_end(i + 2);
+            emit(i, e, IdePal::SYN_NUM);
+            Some(e)
+        }
+        b'$' => {
+            // NASM: `$` es la dirección actual y `$$` el inicio de la sección
+            let e = if next == b'$' { i + 2 } else { i + 1 };
+            emit(i, e, IdePal::SYN_MAC);
+            Some(e)
+        }
+        b'.' if is_ident_start(next) || next.is_ascii_digit() => {
+            let e = word_end(i + 1);
+            if label(e) { emit(i, e + 1, IdePal::SYN_LBL); return Some(e + 1); }
+            emit(i, e, IdePal::SYN_MAC);
+            Some(e)
+        }
+        b if is_ident_start(b) => {
+            let e = word_end(i);
+            if label(e) { emit(i, e + 1, IdePal::SYN_LBL); return Some(e + 1); }
+            let w = &line[i..e];
+            let color = if is_asm_mnemonic(w)             { IdePal::SYN_KW }
+                        else if is_asm_register(w)        { IdePal::SYN_TYP }
+                        else if is_kw_ci(w, ASM_DIRECTIVES) { IdePal::SYN_MAC }
+                        else                              { IdePal::TEXT };
+            emit(i, e, color);
+            Some(e)
+        }
+        _ => None,
+    }
+}
+
+/// Avanza por un comentario de bloque abierto (`depth` > 0) desde `i`.
+/// Devuelve dónde termina y la profundidad que queda (0 = cerrado en esta
+/// línea). En Rust los `/* */` anidan; en C el primer `*/` cierra.
+fn block_comment_end(line: &[u8], mut i: usize, mut depth: u8, nests: bool) -> (usize, u8) {
+    while i < line.len() {
+        let rem = &line[i..];
+        if rem.starts_with(b"*/") {
+            depth -= 1; i += 2;
+            if depth == 0 { return (i, 0); }
+        } else if nests && rem.starts_with(b"/*") {
+            depth = depth.saturating_add(1); i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    (i, depth)
+}
+
+/// Colorea `line` llamando a `emit(inicio, fin, color)` por tramos. `block`
+/// es la profundidad de comentario de bloque con la que empieza la línea
+/// (0 fuera de comentario) y se devuelve la que pasa a la siguiente, así el
+/// llamador la propaga línea a línea (ver TextBuffer::block_comment_depth).
+pub fn highlight_line<F>(line: &[u8], lang: Lang, block: u8, mut emit: F) -> u8 where F: FnMut(usize, usize, Color) {
+    if lang == Lang::Plain { emit(0, line.len(), IdePal::TEXT); return 0; }
+    let c_like = lang == Lang::Rust || lang == Lang::C;
+    let nests  = lang == Lang::Rust;
     let mut i = 0usize; let mut in_str: u8 = 0;
+    if c_like && block > 0 {
+        let (end, depth) = block_comment_end(line, 0, block, nests);
+        emit(0, end, IdePal::SYN_CMT);
+        if depth > 0 { return depth; }
+        i = end;
+    }
     while i < line.len() {
         if in_str == 0 {
             let rem = &line[i..];
-            if (lang == Lang::Rust || lang == Lang::C) && rem.starts_with(b"//") { emit(i, line.len(), IdePal::SYN_CMT); return; }
-            if lang == Lang::Asm && (line[i] == b';' || line[i] == b'#') { emit(i, line.len(), IdePal::SYN_CMT); return; }
+            if c_like && rem.starts_with(b"//") { emit(i, line.len(), IdePal::SYN_CMT); return 0; }
+            if c_like && rem.starts_with(b"/*") {
+                let (end, depth) = block_comment_end(line, i + 2, 1, nests);
+                emit(i, end, IdePal::SYN_CMT);
+                if depth > 0 { return depth; }
+                i = end; continue;
+            }
+            if lang == Lang::Asm && (line[i] == b';' || line[i] == b'#') { emit(i, line.len(), IdePal::SYN_CMT); return 0; }
         }
         if in_str == 0 && (line[i] == b'"' || line[i] == b'\'') {
             let delim = line[i]; in_str = delim; let s = i; i += 1;
@@ -750,13 +1619,7 @@ pub fn highlight_line<F>(line: &[u8], lang: Lang, mut emit: F) where F: FnMut(us
             continue;
         }
         if lang == Lang::Asm {
-            if line[i] == b'.' { let s = i; i += 1; while i < line.len() && is_ident(line[i]) { i += 1; } emit(s, i, IdePal::SYN_MAC); continue; }
-            if is_ident_start(line[i]) {
-                let s = i;
-                while i < line.len() && is_ident(line[i]) { i += 1; }
-                if i < line.len() && line[i] == b':' { i += 1; emit(s, i, IdePal::SYN_TYP); } else { emit(s, i, IdePal::SYN_KW); }
-                continue;
-            }
+            if let Some(end) = asm_token(line, i, &mut emit) { i = end; continue; }
         }
         if line[i].is_ascii_digit() {
             let s = i;
@@ -766,12 +1629,108 @@ pub fn highlight_line<F>(line: &[u8], lang: Lang, mut emit: F) where F: FnMut(us
         if b"{}[]();,.<>!&|^~%+-*/=@#".contains(&line[i]) { emit(i, i+1, IdePal::SYN_PUN); i += 1; continue; }
         emit(i, i+1, IdePal::TEXT); i += 1;
     }
+    0
+}
+
+/// Palabra clave y nombre si `body` (la línea sin sangría) abre una sección.
+/// Rust: fn/struct/enum/trait/impl con calificadores delante. C: struct, o
+/// una definición de función a columna 0 (`tipo nombre(...)` sin ';').
+fn section_header(body: &[u8], indent: usize, lang: Lang) -> Option<(&'static [u8], &[u8])> {
+    let word_end = |s: &[u8]| s.iter().position(|&b| !is_ident(b)).unwrap_or(s.len());
+    let mut rest = body;
+    if lang == Lang::Rust {
+        loop {
+            let w = &rest[..word_end(rest)];
+            if w == b"pub" {
+                rest = &rest[w.len()..];
+                if rest.first() == Some(&b'(') {
+                    rest = rest.iter().position(|&b| b == b')').map_or(&[][..], |e| &rest[e + 1..]);
+                }
+            } else if matches!(w, b"unsafe" | b"async" | b"const" | b"extern" | b"default") {
+                rest = &rest[w.len()..];
+                if rest.trim_ascii_start().first() == Some(&b'"') {
+                    // extern "C"
+                    let r = rest.trim_ascii_start();
+                    rest = r[1..].iter().position(|&b| b == b'"').map_or(&[][..], |e| &r[e + 2..]);
+                }
+            } else { break; }
+            rest = rest.trim_ascii_start();
+        }
+        let w = &rest[..word_end(rest)];
+        let kw: &'static [u8] = match w {
+            b"fn" => b"fn", b"struct" => b"struct", b"enum" => b"enum",
+            b"trait" => b"trait", b"impl" => b"impl",
+            _ => return None,
+        };
+        let after = &rest[w.len()..];
+        if !after.first().is_some_and(|&b| b == b' ' || b == b'<') { return None; }
+        if kw == b"impl" {
+            // `impl<T> Trait for Tipo where …` hasta '{' o `where`
+            let mut a = after;
+            if a.first() == Some(&b'<') {
+                let mut depth = 0usize; let mut i = 0;
+                while i < a.len() {
+                    match a[i] {
+                        b'<' => depth += 1,
+                        b'>' if a[i - 1] != b'-' => { depth -= 1; if depth == 0 { i += 1; break; } }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                a = &a[i..];
+            }
+            let a = a.trim_ascii_start();
+            let end = a.windows(6).position(|w| w == b" where").or_else(|| a.iter().position(|&b| b == b'{')).unwrap_or(a.len());
+            let name = a[..end].trim_ascii_end();
+            return if name.is_empty() { None } else { Some((kw, name)) };
+        }
+        let after = after.trim_ascii_start();
+        let name = &after[..word_end(after)];
+        return if name.is_empty() { None } else { Some((kw, name)) };
+    }
+
+    // C
+    let w = &rest[..word_end(rest)];
+    if w == b"struct" || w == b"union" || w == b"enum" {
+        let after = rest[w.len()..].trim_ascii_start();
+        let name = &after[..word_end(after)];
+        // `struct x *p;` es un uso, no una definición
+        if name.is_empty() || body.contains(&b';') { return None; }
+        return Some((if w == b"struct" { b"struct" } else if w == b"union" { b"union" } else { b"enum" }, name));
+    }
+    if indent > 0 || w.is_empty() || !is_ident_start(rest[0]) || body.contains(&b';') { return None; }
+    if matches!(w, b"if" | b"else" | b"for" | b"while" | b"do" | b"switch" | b"return" | b"case" | b"default" | b"typedef") {
+        return None;
+    }
+    let paren = body.iter().position(|&b| b == b'(')?;
+    let head = body[..paren].trim_ascii_end();
+    let start = head.iter().rposition(|&b| !is_ident(b)).map_or(0, |i| i + 1);
+    let name = &head[start..];
+    if name.is_empty() || start == 0 { None } else { Some((b"fn", name)) }
 }
 
 fn is_ident_start(b: u8) -> bool { b.is_ascii_alphabetic() || b == b'_' }
 fn is_ident(b: u8) -> bool       { b.is_ascii_alphanumeric() || b == b'_' }
 fn is_kw(w: &[u8], list: &[&[u8]]) -> bool { list.iter().any(|&k| k == w) }
 
+/// Llama a `f` con la columna de cada aparición de `word` como palabra
+/// completa (sin identificador pegado a ninguno de los dos lados).
+fn for_each_word<F: FnMut(usize)>(line: &[u8], word: &[u8], mut f: F) {
+    let n = word.len();
+    let mut i = 0;
+    while i + n <= line.len() {
+        if &line[i..i + n] == word
+            && (i == 0 || !is_ident(line[i - 1]))
+            && (i + n == line.len() || !is_ident(line[i + n]))
+        {
+            f(i);
+            i += n;
+        } else {
+            i += 1;
+        }
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Constantes de renderizado
 // ─────────────────────────────────────────────────────────────────────────────
@@ -784,6 +1743,38 @@ const DROPDOWN_ITEM_H:    usize = 16;
 const HELP_OVERLAY_W:     usize = 380;
 const HELP_OVERLAY_H:     usize = 280;
 
+/// Líneas por muesca de la rueda del ratón.
+const WHEEL_LINES: usize = 3;
+
+/// Área de edición (y, alto): sin menubar, pestañas ni status bar en modo zen.
+pub fn ide_edit_area(lay: &Layout, zen: bool) -> (usize, usize) {
+    if zen {
+        return (lay.content_y, lay.bottom_y.saturating_sub(lay.content_y));
+    }
+    let edit_y = lay.content_y + MENU_H + TABS_H;
+    (edit_y, lay.bottom_y.saturating_sub(edit_y + STATUS_H))
+}
+
+/// Filas de texto visibles en el área de edición.
+pub fn ide_visible_rows(lay: &Layout, zen: bool) -> usize {
+    let (_, edit_h) = ide_edit_area(lay, zen);
+    (edit_h / (lay.font_h + 3)).max(1)
+}
+
+/// Línea del buffer activo cuyo número está bajo (x, y) en el gutter, o
+/// None si el clic cae fuera del gutter, en una fila sin línea (tras la
+/// última) o con los números ocultos.
+pub fn ide_gutter_line_at(lay: &Layout, ide: &IdeState, x: usize, y: usize) -> Option<usize> {
+    if !ide.show_ln || ide.show_help { return None; }
+    let buf = ide.buffers[ide.active].as_ref()?;
+    let (edit_y, edit_h) = ide_edit_area(lay, ide.zen);
+    if x >= GUTTER_W * lay.font_w + 10 || y < edit_y || y >= edit_y + edit_h { return None; }
+    let row = (y - edit_y) / (lay.font_h + 3);
+    if row >= ide_visible_rows(lay, ide.zen) { return None; }
+    let line = buf.scroll + row;
+    (line < buf.line_cnt).then_some(line)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // draw_ide_tab
 // ─────────────────────────────────────────────────────────────────────────────
@@ -798,86 +1789,86 @@ pub fn draw_ide_tab(c: &mut Console, lay: &Layout, ide: &IdeState) {
     // ── Fondo completo ────────────────────────────────────────────────────────
     c.fill_rect(0, y0, fw, lay.bottom_y.saturating_sub(y0), IdePal::BG);
 
-    // ═════════════════════════════════════════════════════════════════════════
-    // BARRA DE MENÚ
-    // ═════════════════════════════════════════════════════════════════════════
-    let my = y0;
-    c.fill_rect(0, my, fw, MENU_H, IdePal::MENU_BG);
-    c.hline(0, my + MENU_H - 1, fw, IdePal::MENU_BORDER);
-
-    let mut mx_pos = 8usize;
-    for (mi, menu) in MENUS.iter().enumerate() {
-        let is_open = ide.menu == MenuState::Open(mi);
-        let lw      = menu.title.len() * cw + 14;
-        if is_open {
-            c.fill_rect(mx_pos, my, lw, MENU_H - 1, IdePal::DROP_HOV);
-            // Línea de acento superior
-            c.fill_rect(mx_pos, my, lw, 2, IdePal::ACCENT);
-        }
-        let fg = if is_open { IdePal::MENU_FG_ACT } else { IdePal::MENU_FG };
-        c.write_at(menu.title, mx_pos + 7, my + (MENU_H - ch) / 2, fg);
-        mx_pos += lw + 2;
-    }
-
-    // Botón [?] de ayuda — extremo derecho de la menubar
-    let help_x = fw.saturating_sub(cw * 3 + 12);
-    let help_active = ide.show_help;
-    let help_bg = if help_active { IdePal::DROP_HOV } else { IdePal::MENU_BG };
-    c.fill_rect(help_x, my + 3, cw * 2 + 8, MENU_H - 6, help_bg);
-    c.draw_rect(help_x, my + 3, cw * 2 + 8, MENU_H - 6, 1, IdePal::DROP_BOR);
-    c.write_at("?", help_x + 4 + cw / 2, my + (MENU_H - ch) / 2, Color::new(0xCC, 0xCC, 0xCC));
-
-    // ═════════════════════════════════════════════════════════════════════════
-    // PESTAÑAS DE BUFFERS
-    // ═════════════════════════════════════════════════════════════════════════
-    let ty = y0 + MENU_H;
-    c.fill_rect(0, ty, fw, TABS_H, Color::new(0x25, 0x25, 0x26));
-    c.hline(0, ty + TABS_H - 1, fw, IdePal::BORDER);
-
-    let mut tx = 0usize;
-    for i in 0..MAX_BUFFERS {
-        if let Some(buf) = &ide.buffers[i] {
-            let is_act = i == ide.active;
-            let name   = buf.name_str();
-            let nmax   = 18usize;
-            let ndisp  = if name.len() > nmax { &name[..nmax] } else { name };
-            // width: icono(2ch) + espacio + nombre + sucio(2ch) + padding
-            let tab_w  = (3 + 1 + ndisp.len() + if buf.dirty { 2 } else { 1 }) * cw + 12;
-
-            let bg = if is_act { IdePal::TAB_ACT } else { Color::new(0x2D, 0x2D, 0x2D) };
-            c.fill_rect(tx, ty, tab_w, TABS_H, bg);
-
-            if is_act {
-                // Línea de acento inferior = indica tab activa
-                c.fill_rect(tx, ty + TABS_H - 2, tab_w, 2, IdePal::ACCENT);
+    if !ide.zen {
+        // ═════════════════════════════════════════════════════════════════════
+        // BARRA DE MENÚ
+        // ═════════════════════════════════════════════════════════════════════
+        let my = y0;
+        c.fill_rect(0, my, fw, MENU_H, IdePal::MENU_BG);
+        c.hline(0, my + MENU_H - 1, fw, IdePal::MENU_BORDER);
+
+        let mut mx_pos = 8usize;
+        for (mi, menu) in MENUS.iter().enumerate() {
+            let is_open = ide.menu == MenuState::Open(mi);
+            let lw      = menu.title.len() * cw + 14;
+            if is_open {
+                c.fill_rect(mx_pos, my, lw, MENU_H - 1, IdePal::DROP_HOV);
+                // Línea de acento superior
+                c.fill_rect(mx_pos, my, lw, 2, IdePal::ACCENT);
             }
+            let fg = if is_open { IdePal::MENU_FG_ACT } else { IdePal::MENU_FG };
+            c.write_at(menu.title, mx_pos + 7, my + (MENU_H - ch) / 2, fg);
+            mx_pos += lw + 2;
+        }
 
-            // Separador derecho
-            c.vline(tx + tab_w - 1, ty, TABS_H, IdePal::BORDER);
+        // Botón [?] de ayuda — extremo derecho de la menubar
+        let help_x = fw.saturating_sub(cw * 3 + 12);
+        let help_active = ide.show_help;
+        let help_bg = if help_active { IdePal::DROP_HOV } else { IdePal::MENU_BG };
+        c.fill_rect(help_x, my + 3, cw * 2 + 8, MENU_H - 6, help_bg);
+        c.draw_rect(help_x, my + 3, cw * 2 + 8, MENU_H - 6, 1, IdePal::DROP_BOR);
+        c.write_at("?", help_x + 4 + cw / 2, my + (MENU_H - ch) / 2, Color::new(0xCC, 0xCC, 0xCC));
+
+        // ═════════════════════════════════════════════════════════════════════
+        // PESTAÑAS DE BUFFERS
+        // ═════════════════════════════════════════════════════════════════════
+        let ty = y0 + MENU_H;
+        c.fill_rect(0, ty, fw, TABS_H, Color::new(0x25, 0x25, 0x26));
+        c.hline(0, ty + TABS_H - 1, fw, IdePal::BORDER);
+
+        let mut tx = 0usize;
+        for i in 0..MAX_BUFFERS {
+            if let Some(buf) = &ide.buffers[i] {
+                let is_act = i == ide.active;
+                let name   = buf.name_str();
+                let nmax   = 18usize;
+                let ndisp  = if name.len() > nmax { &name[..nmax] } else { name };
+                // width: icono(2ch) + espacio + nombre + sucio(2ch) + padding
+                let tab_w  = (3 + 1 + ndisp.len() + if buf.dirty { 2 } else { 1 }) * cw + 12;
+
+                let bg = if is_act { IdePal::TAB_ACT } else { Color::new(0x2D, 0x2D, 0x2D) };
+                c.fill_rect(tx, ty, tab_w, TABS_H, bg);
+
+                if is_act {
+                    // Línea de acento inferior = indica tab activa
+                    c.fill_rect(tx, ty + TABS_H - 2, tab_w, 2, IdePal::ACCENT);
+                }
 
-            let tty  = ty + (TABS_H - ch) / 2;
-            let icon_col = buf.lang.icon_color();
-            let name_col = if is_act { IdePal::TAB_FG_ACT } else { IdePal::TAB_FG };
+                // Separador derecho
+                c.vline(tx + tab_w - 1, ty, TABS_H, IdePal::BORDER);
 
-            // Punto de color del lenguaje
-            c.fill_rounded(tx + 5, tty + 2, 4, 4, 2, icon_col);
-            c.write_at(ndisp, tx + 14, tty, name_col);
+                let tty  = ty + (TABS_H - ch) / 2;
+                let icon_col = buf.lang.icon_color();
+                let name_col = if is_act { IdePal::TAB_FG_ACT } else { IdePal::TAB_FG };
 
-            if buf.dirty {
-                // Punto naranja de "sin guardar"
-                let dot_x = tx + 14 + ndisp.len() * cw + 3;
-                c.fill_rounded(dot_x, tty + 2, 4, 4, 2, IdePal::DIRTY);
+                // Punto de color del lenguaje
+                c.fill_rounded(tx + 5, tty + 2, 4, 4, 2, icon_col);
+                c.write_at(ndisp, tx + 14, tty, name_col);
+
+                if buf.dirty {
+                    // Punto naranja de "sin guardar"
+                    let dot_x = tx + 14 + ndisp.len() * cw + 3;
+                    c.fill_rounded(dot_x, tty + 2, 4, 4, 2, IdePal::DIRTY);
+                }
+                tx += tab_w;
             }
-            tx += tab_w;
         }
     }
 
     // ═════════════════════════════════════════════════════════════════════════
     // ÁREA DE EDICIÓN
     // ═════════════════════════════════════════════════════════════════════════
-    let edit_y     = ty + TABS_H;
-    let avail_h    = lay.bottom_y.saturating_sub(edit_y);
-    let edit_h     = avail_h.saturating_sub(STATUS_H);
+    let (edit_y, edit_h) = ide_edit_area(lay, ide.zen);
     let vis_rows   = edit_h / lh;
     let gutter_px  = if ide.show_ln { GUTTER_W * cw + 10 } else { 4 };
 
@@ -892,7 +1883,21 @@ pub fn draw_ide_tab(c: &mut Console, lay: &Layout, ide: &IdeState) {
         c.vline(gutter_px, edit_y, edit_h, IdePal::BORDER);
     }
 
+    // Palabra bajo el cursor: se copia una vez y se busca solo en las filas
+    // que se dibujan.
+    let mut word_buf = [0u8; MAX_LINE_LEN];
+    let mut word_len = 0usize;
+    let mut word_at  = (0usize, 0usize);
+    if let (Some((ws, we)), Some(line)) = (buf.word_at_cursor(), buf.get_line(buf.cursor_l)) {
+        word_len = we - ws;
+        word_buf[..word_len].copy_from_slice(&line.data[ws..we]);
+        word_at = (buf.cursor_l, ws);
+    }
+    let word = &word_buf[..word_len];
+
     let mut lnbuf = [0u8; 8];
+    // Comentario de bloque abierto antes de la primera línea visible
+    let mut block = buf.block_comment_depth(buf.scroll);
     for vis in 0..vis_rows {
         let lnum = buf.scroll + vis;
         if lnum >= buf.line_cnt { break; }
@@ -923,7 +1928,35 @@ pub fn draw_ide_tab(c: &mut Console, lay: &Layout, ide: &IdeState) {
         }
         let text_x  = gutter_px + 6;
         let max_col = fw.saturating_sub(text_x + 8) / cw;
-        draw_hl_line(c, &line_buf[..line_len], buf.lang, text_x, py + 2, cw, max_col);
+
+        // ── Otras apariciones de la palabra del cursor (fondo) ───────────────
+        if !word.is_empty() {
+            for_each_word(&line_buf[..line_len], word, |a| {
+                if (lnum, a) == word_at { return; }
+                let b = (a + word.len()).min(max_col);
+                if b > a { c.fill_rect(text_x + a * cw, py, (b - a) * cw, lh, IdePal::WORD_HL); }
+            });
+        }
+
+        // ── Selección y coincidencia actual (fondo, antes del texto) ─────────
+        if let Some(((sl, sc), (el, ec))) = buf.selection() {
+            if lnum >= sl && lnum <= el {
+                let a = if lnum == sl { sc } else { 0 };
+                // +1 = el salto de línea también queda seleccionado
+                let b = if lnum == el { ec } else { line_len + 1 };
+                let (a, b) = (a.min(max_col), b.min(max_col));
+                if b > a { c.fill_rect(text_x + a * cw, py, (b - a) * cw, lh, IdePal::SELECTION); }
+            }
+        }
+        if let Some(rs) = &ide.replace {
+            if rs.cur.0 == lnum {
+                let a = rs.cur.1.min(max_col);
+                let b = (rs.cur.1 + ide.find_len).min(max_col);
+                if b > a { c.fill_rect(text_x + a * cw, py, (b - a) * cw, lh, IdePal::MATCH); }
+            }
+        }
+
+        block = draw_hl_line(c, &line_buf[..line_len], buf.lang, block, (text_x, py + 2), cw, max_col);
 
         // ── CARET (cursor de edición) ─────────────────────────────────────────
         // FIX: limpia exactamente cw × lh píxeles, luego dibuja el carácter
@@ -954,51 +1987,75 @@ pub fn draw_ide_tab(c: &mut Console, lay: &Layout, ide: &IdeState) {
     // ═════════════════════════════════════════════════════════════════════════
     let sy      = lay.bottom_y.saturating_sub(STATUS_H);
     let in_inp  = ide.input.is_active();
-    let st_bg   = if in_inp { INPUT_BG } else if ide.status_err { IdePal::STATUS_ERR } else { IdePal::STATUS_BG };
-    c.fill_rect(0, sy, fw, STATUS_H, st_bg);
-
-    if in_inp {
-        draw_input_overlay(c, &ide.input, 8, sy, fw, STATUS_H, cw, ch);
-    } else {
-        let sty = sy + (STATUS_H - ch) / 2;
-        // Izquierda: Ln/Col
-        let mut pb = [0u8; 32]; let mut pp = 0;
-        let mut tmp = [0u8; 8];
-        for b in b"Ln " { pb[pp] = *b; pp += 1; }
-        for b in fmt_usize(buf.cursor_l + 1, &mut tmp).bytes() { pb[pp] = b; pp += 1; }
-        for b in b"  Col " { pb[pp] = *b; pp += 1; }
-        for b in fmt_usize(buf.cursor_c + 1, &mut tmp).bytes() { pb[pp] = b; pp += 1; }
-        c.write_at(core::str::from_utf8(&pb[..pp]).unwrap_or(""), 8, sty, Color::WHITE);
-
-        // Separador
-        c.write_at("|", 120, sty, Color::new(0x00, 0x55, 0xAA));
+    let st_bg   = if in_inp && ide.input.mode == InputMode::Reload { INPUT_BG_DELETE }
+                  else if in_inp { INPUT_BG } else if ide.status_err { IdePal::STATUS_ERR } else { IdePal::STATUS_BG };
+    // En modo zen la barra solo aparece mientras el minibuffer está activo
+    if !ide.zen || in_inp {
+        c.fill_rect(0, sy, fw, STATUS_H, st_bg);
+
+        if in_inp {
+            draw_input_overlay(c, &ide.input, 8, sy, fw, STATUS_H, cw, ch);
+        } else {
+            let sty = sy + (STATUS_H - ch) / 2;
+            // Izquierda: Ln/Col
+            let mut pb = [0u8; 32]; let mut pp = 0;
+            let mut tmp = [0u8; 8];
+            for b in b"Ln " { pb[pp] = *b; pp += 1; }
+            for b in fmt_usize(buf.cursor_l + 1, &mut tmp).bytes() { pb[pp] = b; pp += 1; }
+            for b in b"  Col " { pb[pp] = *b; pp += 1; }
+            for b in fmt_usize(buf.cursor_c + 1, &mut tmp).bytes() { pb[pp] = b; pp += 1; }
+            c.write_at(core::str::from_utf8(&pb[..pp]).unwrap_or(""), 8, sty, Color::WHITE);
+
+            // Separador
+            c.write_at("|", 120, sty, Color::new(0x00, 0x55, 0xAA));
+
+            // Lenguaje
+            c.write_at(buf.lang.label(), 132, sty, Color::WHITE);
+
+            // Volumen de solo lectura: guardar deshabilitado
+            if crate::drivers::storage::fat32::effective_read_only() {
+                c.write_at("[RO]", 132 + 8 * cw, sty, IdePal::DIRTY);
+            }
 
-        // Lenguaje
-        c.write_at(buf.lang.label(), 132, sty, Color::WHITE);
+            // Nombre centrado
+            let ndisp = buf.name_str();
+            let nx    = fw / 2 - ndisp.len() * cw / 2;
+
+            // Sección actual (fn/struct/impl), recortada al hueco hasta el nombre
+            let sec = buf.section_str();
+            if !sec.is_empty() {
+                let sx   = 132 + 13 * cw;
+                let room = nx.saturating_sub(sx + 2 * cw) / cw;
+                if room >= 4 {
+                    if sec.len() <= room {
+                        c.write_at(sec, sx, sty, Color::new(0xCC, 0xE4, 0xFF));
+                    } else {
+                        c.write_at(core::str::from_utf8(&sec.as_bytes()[..room - 2]).unwrap_or(""), sx, sty, Color::new(0xCC, 0xE4, 0xFF));
+                        c.write_at("..", sx + (room - 2) * cw, sty, Color::new(0xCC, 0xE4, 0xFF));
+                    }
+                }
+            }
+            c.write_at(ndisp, nx, sty, Color::WHITE);
+            if buf.dirty {
+                c.write_at("●", nx + ndisp.len() * cw + 4, sty, IdePal::DIRTY);
+            }
 
-        // Nombre centrado
-        let ndisp = buf.name_str();
-        let nx    = fw / 2 - ndisp.len() * cw / 2;
-        c.write_at(ndisp, nx, sty, Color::WHITE);
-        if buf.dirty {
-            c.write_at("●", nx + ndisp.len() * cw + 4, sty, IdePal::DIRTY);
-        }
+            // Mensaje de status (derecha) — solo si hay algo que decir
+            let msg = core::str::from_utf8(&ide.status_msg[..ide.status_len]).unwrap_or("");
+            if !msg.is_empty() {
+                c.write_at(msg, fw.saturating_sub(msg.len() * cw + 8), sty, Color::WHITE);
+            }
 
-        // Mensaje de status (derecha) — solo si hay algo que decir
-        let msg = core::str::from_utf8(&ide.status_msg[..ide.status_len]).unwrap_or("");
-        if !msg.is_empty() {
-            c.write_at(msg, fw.saturating_sub(msg.len() * cw + 8), sty, Color::WHITE);
+            // Hint de ayuda muy sutil (extremo derecho inferior de toda la UI)
+            // No en la barra — solo el botón [?] en menubar
         }
-
-        // Hint de ayuda muy sutil (extremo derecho inferior de toda la UI)
-        // No en la barra — solo el botón [?] en menubar
     }
 
     // ═════════════════════════════════════════════════════════════════════════
     // DROPDOWN DE MENÚ
     // ═════════════════════════════════════════════════════════════════════════
     if let MenuState::Open(oi) = ide.menu {
-        draw_dropdown(c, lay, oi, y0);
+        draw_dropdown(c, lay, oi, y0, ide.menu_hover);
     }
 
     // ═════════════════════════════════════════════════════════════════════════
@@ -1013,21 +2070,44 @@ pub fn draw_ide_tab(c: &mut Console, lay: &Layout, ide: &IdeState) {
 // draw_dropdown
 // ─────────────────────────────────────────────────────────────────────────────
 
-fn draw_dropdown(c: &mut Console, lay: &Layout, mi: usize, y0: usize) {
-    if mi >= MENUS.len() { return; }
-    let cw  = lay.font_w;
-    let ch  = lay.font_h;
+/// Rectángulo (x, y, ancho, alto) del dropdown del menú `mi`, bajo la
+/// menubar que empieza en `y0`. Lo comparten el dibujo y el hit-test.
+pub fn dropdown_rect(mi: usize, cw: usize, y0: usize) -> (usize, usize, usize, usize) {
     let menu = &MENUS[mi];
-
-    let mut mx_pos = 8usize;
-    for i in 0..mi { mx_pos += MENUS[i].title.len() * cw + 16; }
-
+    let mut dd_x = 8usize;
+    for m in &MENUS[..mi] { dd_x += m.title.len() * cw + 16; }
     let max_l = menu.items.iter().map(|it| it.label.len()).max().unwrap_or(10);
     let max_s = menu.items.iter().map(|it| it.shortcut.len()).max().unwrap_or(0);
     let dd_w = (max_l + max_s + 5) * cw + 20;
     let dd_h = menu.items.len() * DROPDOWN_ITEM_H + 8;
-    let dd_x = mx_pos;
-    let dd_y = y0 + MENU_H;
+    (dd_x, y0 + MENU_H, dd_w, dd_h)
+}
+
+/// true si (x, y) cae dentro del dropdown del menú `mi`.
+pub fn dropdown_contains(mi: usize, cw: usize, y0: usize, x: usize, y: usize) -> bool {
+    if mi >= MENUS.len() { return false; }
+    let (dx, dy, dw, dh) = dropdown_rect(mi, cw, y0);
+    x >= dx && x < dx + dw && y >= dy && y < dy + dh
+}
+
+/// Item del dropdown del menú `mi` bajo (x, y). None fuera del menú, en sus
+/// márgenes o sobre un separador.
+pub fn dropdown_item_at(mi: usize, cw: usize, y0: usize, x: usize, y: usize) -> Option<usize> {
+    if !dropdown_contains(mi, cw, y0, x, y) { return None; }
+    let (_, dy, _, _) = dropdown_rect(mi, cw, y0);
+    let top = dy + 4;
+    if y < top { return None; }
+    let idx = (y - top) / DROPDOWN_ITEM_H;
+    let item = MENUS[mi].items.get(idx)?;
+    if item.action == MenuAction::Separator { None } else { Some(idx) }
+}
+
+fn draw_dropdown(c: &mut Console, lay: &Layout, mi: usize, y0: usize, hover: usize) {
+    if mi >= MENUS.len() { return; }
+    let cw  = lay.font_w;
+    let ch  = lay.font_h;
+    let menu = &MENUS[mi];
+    let (dd_x, dd_y, dd_w, dd_h) = dropdown_rect(mi, cw, y0);
 
     // Sombra sutil
     c.fill_rect(dd_x + 2, dd_y + 2, dd_w, dd_h, Color::new(0x00, 0x00, 0x00));
@@ -1042,7 +2122,11 @@ fn draw_dropdown(c: &mut Console, lay: &Layout, mi: usize, y0: usize) {
         if item.action == MenuAction::Separator {
             c.hline(dd_x + 6, iy + DROPDOWN_ITEM_H / 2, dd_w - 12, IdePal::DROP_SEP);
         } else {
-            c.write_at(item.label, dd_x + 12, tty, IdePal::MENU_FG);
+            let disabled = crate::drivers::storage::fat32::effective_read_only()
+                && matches!(item.action, MenuAction::FileSave | MenuAction::FileSaveAs);
+            if ii == hover { c.fill_rect(dd_x + 1, iy, dd_w - 2, DROPDOWN_ITEM_H, IdePal::DROP_HOV); }
+            let fg = if disabled { IdePal::TEXT_DIM } else if ii == hover { IdePal::MENU_FG_ACT } else { IdePal::MENU_FG };
+            c.write_at(item.label, dd_x + 12, tty, fg);
             if !item.shortcut.is_empty() {
                 let sx = dd_x + dd_w - item.shortcut.len() * cw - 10;
                 c.write_at(item.shortcut, sx, tty, IdePal::MENU_SHORT);
@@ -1080,6 +2164,7 @@ fn draw_help_overlay(c: &mut Console, lay: &Layout) {
     let entries: &[(&str, &str)] = &[
         ("Ctrl+N",     "Nuevo archivo"),
         ("Ctrl+S",     "Guardar"),
+        ("Ctrl+R",     "Recargar de disco"),
         ("Ctrl+W",     "Cerrar archivo"),
         ("Ctrl+Tab",   "Siguiente buffer"),
         ("Ctrl+Left",  "Buffer anterior"),
@@ -1093,9 +2178,16 @@ fn draw_help_overlay(c: &mut Console, lay: &Layout) {
         ("Backspace",  "Borrar izq"),
         ("Delete",     "Borrar der"),
         ("─────────────", ""),
+        ("Ctrl+F",     "Buscar"),
+        ("Ctrl+H",     "Reemplazar"),
+        ("Ctrl+Esp",   "Marcar selección"),
+        ("Ctrl+Z",     "Deshacer reempl."),
+        ("Ctrl+:",     "Comando terminal"),
+        ("─────────────", ""),
         ("Clic menú",  "Abrir menús"),
         ("F1 / [?]",   "Mostrar esta ayuda"),
-        ("Esc",        "Cerrar menús"),
+        ("F11",        "Modo zen"),
+        ("Esc",        "Cerrar menús/zen"),
     ];
 
     let col_w = HELP_OVERLAY_W / 2;
@@ -1124,16 +2216,24 @@ fn draw_help_overlay(c: &mut Console, lay: &Layout) {
 // draw_hl_line — syntax highlighting por columna
 // ─────────────────────────────────────────────────────────────────────────────
 
-fn draw_hl_line(c: &mut Console, line: &[u8], lang: Lang, x0: usize, y: usize, cw: usize, max_cols: usize) {
+/// Dibuja `line` con resaltado; `block` es el estado de comentario de
+/// bloque de entrada y se devuelve el de salida para la línea siguiente.
+fn draw_hl_line(c: &mut Console, line: &[u8], lang: Lang, block: u8, (x0, y): (usize, usize), cw: usize, max_cols: usize) -> u8 {
     let mut col = 0usize;
-    highlight_line(line, lang, |start, end, color| {
+    highlight_line(line, lang, block, |start, end, color| {
         for i in start..end {
             if col >= max_cols || i >= line.len() { break; }
             let s = [line[i]];
             c.write_at(core::str::from_utf8(&s).unwrap_or("."), x0 + col * cw, y, color);
             col += 1;
         }
-    });
+    })
+}
+
+fn push_bytes(out: &mut [u8], pos: &mut usize, s: &[u8]) {
+    let n = s.len().min(out.len().saturating_sub(*pos));
+    out[*pos..*pos + n].copy_from_slice(&s[..n]);
+    *pos += n;
 }
 
 fn fmt_usize(mut n: usize, buf: &mut [u8]) -> &str {
@@ -1142,4 +2242,289 @@ fn fmt_usize(mut n: usize, buf: &mut [u8]) -> &str {
     if n == 0 { buf[i - 1] = b'0'; return core::str::from_utf8(&buf[i - 1..]).unwrap_or("0"); }
     while n > 0 && i > 0 { i -= 1; buf[i] = b'0' + (n % 10) as u8; n /= 10; }
     core::str::from_utf8(&buf[i..]).unwrap_or("?")
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    /// El pool de páginas es global y sin lock: los tests que crean
+    /// TextBuffer se ejecutan de uno en uno.
+    static POOL: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// Tramos (texto, color) de `line` y la profundidad que pasa a la
+    /// siguiente línea.
+    fn spans(line: &str, lang: Lang, block: u8) -> (Vec<(String, Color)>, u8) {
+        let mut out = Vec::new();
+        let depth = highlight_line(line.as_bytes(), lang, block, |s, e, c| {
+            out.push((String::from(&line[s..e]), c));
+        });
+        (out, depth)
+    }
+
+    /// `line` con los bytes de comentario como 'c' y el resto como '.'.
+    fn comment_mask(line: &str, lang: Lang, block: u8) -> (String, u8) {
+        let mut mask: Vec<u8> = line.bytes().map(|_| b'.').collect();
+        let depth = highlight_line(line.as_bytes(), lang, block, |s, e, c| {
+            if c == IdePal::SYN_CMT { mask[s..e].fill(b'c'); }
+        });
+        (String::from_utf8(mask).unwrap(), depth)
+    }
+
+    /// Tramos sin los de solo espacios, para comparar tokens.
+    fn tokens(line: &str, lang: Lang) -> Vec<(String, Color)> {
+        spans(line, lang, 0).0.into_iter().filter(|(t, _)| !t.trim().is_empty()).collect()
+    }
+
+    fn tok(t: &str, c: Color) -> (String, Color) { (String::from(t), c) }
+
+    #[test]
+    fn asm_intel_instruction() {
+        assert_eq!(tokens("mov rax, 0x10", Lang::Asm), [
+            tok("mov", IdePal::SYN_KW), tok("rax", IdePal::SYN_TYP),
+            tok(",", IdePal::SYN_PUN), tok("0x10", IdePal::SYN_NUM),
+        ]);
+    }
+
+    #[test]
+    fn asm_att_instruction() {
+        assert_eq!(tokens("movl $1, %eax", Lang::Asm), [
+            tok("movl", IdePal::SYN_KW), tok("$1", IdePal::SYN_NUM),
+            tok(",", IdePal::SYN_PUN), tok("%eax", IdePal::SYN_TYP),
+        ]);
+        assert_eq!(tokens("addq $-8, %rsp", Lang::Asm)[1], tok("$-8", IdePal::SYN_NUM));
+    }
+
+    #[test]
+    fn asm_labels_only_at_line_start() {
+        assert_eq!(tokens(".loop:", Lang::Asm), [tok(".loop:", IdePal::SYN_LBL)]);
+        assert_eq!(tokens("  start: nop", Lang::Asm), [
+            tok("start:", IdePal::SYN_LBL), tok("nop", IdePal::SYN_KW),
+        ]);
+        // `es:` dentro de corchetes es un prefijo de segmento
+        let t = tokens("mov al, [es:di]", Lang::Asm);
+        assert!(t.contains(&tok("es", IdePal::SYN_TYP)));
+        assert!(t.contains(&tok("di", IdePal::SYN_TYP)));
+        assert!(!t.iter().any(|(_, c)| *c == IdePal::SYN_LBL));
+    }
+
+    #[test]
+    fn asm_nasm_location_symbols() {
+        let t = tokens("times 510-($-$$) db 0", Lang::Asm);
+        assert!(t.contains(&tok("$", IdePal::SYN_MAC)));
+        assert!(t.contains(&tok("$$", IdePal::SYN_MAC)));
+        assert!(t.contains(&tok("510", IdePal::SYN_NUM)));
+        assert!(t.contains(&tok("0", IdePal::SYN_NUM)));
+        assert_eq!(t.iter().filter(|(s, _)| s == "-").count(), 2);
+        assert_eq!(tokens("jmp $", Lang::Asm)[1], tok("$", IdePal::SYN_MAC));
+    }
+
+    #[test]
+    fn rust_block_comments_nest() {
+        assert_eq!(comment_mask("a /* x /* y */ z */ b", Lang::Rust, 0),
+                   (String::from("..ccccccccccccccccc.."), 0));
+        assert_eq!(comment_mask("/* uno /* dos", Lang::Rust, 0).1, 2);
+        // Cada línea arrastra la profundidad de la anterior
+        assert_eq!(comment_mask("*/ sigue", Lang::Rust, 2), (String::from("cccccccc"), 1));
+        assert_eq!(comment_mask("*/ fin", Lang::Rust, 1), (String::from("cc...."), 0));
+    }
+
+    #[test]
+    fn c_block_comment_closes_at_first_end() {
+        assert_eq!(comment_mask("/* a /* b */ c", Lang::C, 0), (String::from("cccccccccccc.."), 0));
+        assert_eq!(comment_mask("/* a /* b", Lang::C, 0).1, 1);
+        assert_eq!(comment_mask("*/ x", Lang::C, 1), (String::from("cc.."), 0));
+        // En C `*/ */` en una línea abierta cierra con el primero
+        assert_eq!(comment_mask("*/ */", Lang::C, 1), (String::from("cc..."), 0));
+    }
+
+    #[test]
+    fn block_comment_markers_inside_strings_are_text() {
+        let (m, depth) = comment_mask("let s = \"/*\"; x", Lang::Rust, 0);
+        assert_eq!(depth, 0);
+        assert!(!m.contains('c'));
+        let (sp, _) = spans("char *p = \"*/\";", Lang::C, 0);
+        assert!(sp.iter().any(|(t, c)| t == "\"*/\"" && *c == IdePal::SYN_STR));
+    }
+
+    #[test]
+    fn block_comment_on_one_line() {
+        assert_eq!(comment_mask("x /* c */ y", Lang::Rust, 0), (String::from("..ccccccc.."), 0));
+        // `//` después de cerrar el bloque sigue siendo comentario de línea
+        assert_eq!(comment_mask("/* a */ b // c", Lang::C, 0), (String::from("ccccccc...cccc"), 0));
+        // En ASM y texto plano no hay comentarios de bloque
+        assert_eq!(comment_mask("/* x", Lang::Plain, 0).1, 0);
+        assert_eq!(comment_mask("mov rax, 1", Lang::Asm, 3).1, 0);
+    }
+
+    /// IDE con `n` líneas en el buffer activo.
+    fn ide_with_lines(n: usize) -> IdeState {
+        let mut text = String::new();
+        for i in 0..n { text.push_str(if i == 0 { "x" } else { "\nx" }); }
+        let mut ide = IdeState::new();
+        ide.open_with_data("t.txt", b"", text.as_bytes());
+        ide
+    }
+
+    fn free_ide(ide: &mut IdeState) {
+        for b in ide.buffers.iter_mut().flatten() { b.clear_pages(); }
+    }
+
+    #[test]
+    fn gutter_row_from_y() {
+        let _g = POOL.lock().unwrap_or_else(|e| e.into_inner());
+        init_page_pool();
+        let lay = Layout::new(1024, 768);
+        let lh = lay.font_h + 3;
+        let (edit_y, edit_h) = ide_edit_area(&lay, false);
+        let vis = ide_visible_rows(&lay, false);
+        let mut ide = ide_with_lines(500);
+        let at = |ide: &IdeState, y: usize| ide_gutter_line_at(&lay, ide, 4, y);
+
+        assert_eq!(at(&ide, edit_y), Some(0));
+        assert_eq!(at(&ide, edit_y + lh - 1), Some(0));
+        assert_eq!(at(&ide, edit_y + lh), Some(1));
+        assert_eq!(at(&ide, edit_y + vis * lh - 1), Some(vis - 1));
+        // Borde del área: la fila parcial de abajo y edit_y + edit_h no cuentan
+        if vis * lh < edit_h { assert_eq!(at(&ide, edit_y + vis * lh), None); }
+        assert_eq!(at(&ide, edit_y + edit_h - 1), if vis * lh < edit_h { None } else { Some(vis - 1) });
+        assert_eq!(at(&ide, edit_y + edit_h), None);
+        assert_eq!(at(&ide, edit_y - 1), None);
+
+        // Con scroll la fila se suma a la primera línea visible
+        ide.buffers[ide.active].as_mut().unwrap().scroll = 10;
+        assert_eq!(at(&ide, edit_y + 2 * lh), Some(12));
+        // Fuera del gutter o con los números ocultos
+        assert_eq!(ide_gutter_line_at(&lay, &ide, GUTTER_W * lay.font_w + 10, edit_y), None);
+        ide.show_ln = false;
+        assert_eq!(at(&ide, edit_y), None);
+        free_ide(&mut ide);
+    }
+
+    #[test]
+    fn gutter_click_past_last_line() {
+        let _g = POOL.lock().unwrap_or_else(|e| e.into_inner());
+        init_page_pool();
+        let lay = Layout::new(1024, 768);
+        let lh = lay.font_h + 3;
+        let (edit_y, _) = ide_edit_area(&lay, false);
+        let mut ide = ide_with_lines(3);
+        assert_eq!(ide_gutter_line_at(&lay, &ide, 4, edit_y + 2 * lh), Some(2));
+        assert_eq!(ide_gutter_line_at(&lay, &ide, 4, edit_y + 3 * lh), None);
+        assert_eq!(ide_gutter_line_at(&lay, &ide, 4, edit_y + 5 * lh), None);
+        free_ide(&mut ide);
+    }
+
+    fn buffer(lines: &[&str], lang_name: &str) -> TextBuffer {
+        let mut text = String::new();
+        for (i, l) in lines.iter().enumerate() {
+            if i > 0 { text.push('\n'); }
+            text.push_str(l);
+        }
+        let mut b = TextBuffer::new_empty(lang_name);
+        b.load_text(text.as_bytes());
+        b
+    }
+
+    #[test]
+    fn block_depth_cache_follows_edits() {
+        let _g = POOL.lock().unwrap_or_else(|e| e.into_inner());
+        init_page_pool();
+        let mut b = buffer(&["fn a() {}", "/* abre", "dentro", "*/", "fn b() {}", "x"], "t.rs");
+        assert_eq!(b.block_comment_depth(2), 1);
+        assert_eq!(b.block_cache.get(), Some((2, 1)));
+        // Bajando se parte de la caché; subiendo se recalcula
+        assert_eq!(b.block_comment_depth(4), 0);
+        assert_eq!(b.block_comment_depth(3), 1);
+        // Editar por debajo de la línea guardada no la invalida
+        b.cursor_l = 5; b.cursor_c = 0;
+        b.insert_char(b'y');
+        assert_eq!(b.block_cache.get(), Some((3, 1)));
+        // Editar por encima sí: cerrar el comentario en la línea 1
+        b.cursor_l = 1; b.cursor_c = 7;
+        for &c in b" */" { b.insert_char(c); }
+        assert_eq!(b.block_cache.get(), None);
+        assert_eq!(b.block_comment_depth(3), 0);
+        // Más allá del final se limita a line_cnt
+        assert_eq!(b.block_comment_depth(usize::MAX), b.block_comment_depth(b.line_cnt));
+        b.clear_pages();
+    }
+
+    #[test]
+    fn wheel_scrolls_without_moving_cursor() {
+        let _g = POOL.lock().unwrap_or_else(|e| e.into_inner());
+        init_page_pool();
+        let mut ide = ide_with_lines(100);
+        let scroll = |ide: &IdeState| ide.buffers[ide.active].as_ref().unwrap().scroll;
+
+        assert!(!ide.wheel(1, 10));
+        assert!(ide.wheel(-1, 10));
+        assert_eq!(scroll(&ide), WHEEL_LINES);
+        assert!(ide.wheel(-50, 10));
+        assert_eq!(scroll(&ide), 90);
+        assert!(!ide.wheel(-1, 10));
+        assert!(ide.wheel(2, 10));
+        assert_eq!(scroll(&ide), 90 - 2 * WHEEL_LINES);
+        let b = ide.buffers[ide.active].as_ref().unwrap();
+        assert_eq!((b.cursor_l, b.cursor_c), (0, 0));
+
+        // Con un menú abierto la rueda solo lo cierra
+        ide.menu = MenuState::Open(0);
+        assert!(ide.wheel(-1, 10));
+        assert!(ide.menu == MenuState::Closed);
+        assert_eq!(scroll(&ide), 90 - 2 * WHEEL_LINES);
+        free_ide(&mut ide);
+    }
+
+    #[test]
+    fn dropdown_hits_items_but_not_separators() {
+        const CW: usize = 8;
+        const Y0: usize = 40;
+        let mut seps = 0;
+        for (mi, menu) in MENUS.iter().enumerate() {
+            let (dx, dy, dw, dh) = dropdown_rect(mi, CW, Y0);
+            assert_eq!(dh, menu.items.len() * DROPDOWN_ITEM_H + 8);
+            for (i, item) in menu.items.iter().enumerate() {
+                let y = dy + 4 + i * DROPDOWN_ITEM_H;
+                for (x, y) in [(dx, y), (dx + dw - 1, y + DROPDOWN_ITEM_H - 1)] {
+                    let hit = dropdown_item_at(mi, CW, Y0, x, y);
+                    if item.action == MenuAction::Separator {
+                        assert_eq!(hit, None);
+                        seps += 1;
+                    } else {
+                        assert_eq!(hit, Some(i));
+                    }
+                }
+            }
+        }
+        assert!(seps > 0, "ningun menu tiene separadores");
+    }
+
+    #[test]
+    fn dropdown_margins_and_outside() {
+        const CW: usize = 8;
+        let (dx, dy, dw, dh) = dropdown_rect(0, CW, 0);
+        // Margen superior: dentro del menú pero sin item
+        assert!(dropdown_contains(0, CW, 0, dx, dy));
+        assert_eq!(dropdown_item_at(0, CW, 0, dx, dy + 3), None);
+        // Margen inferior
+        assert!(dropdown_contains(0, CW, 0, dx, dy + dh - 1));
+        assert_eq!(dropdown_item_at(0, CW, 0, dx, dy + dh - 1), None);
+        // Fuera por cada lado
+        for (x, y) in [(dx - 1, dy + 10), (dx + dw, dy + 10), (dx, dy - 1), (dx, dy + dh)] {
+            assert!(!dropdown_contains(0, CW, 0, x, y));
+            assert_eq!(dropdown_item_at(0, CW, 0, x, y), None);
+        }
+        // Índice de menú inexistente
+        assert!(!dropdown_contains(MENUS.len(), CW, 0, dx, dy + 10));
+        assert_eq!(dropdown_item_at(MENUS.len(), CW, 0, dx, dy + 10), None);
+    }
+
+    #[test]
+    fn dropdowns_follow_menubar_titles() {
+        let (x0, ..) = dropdown_rect(0, 8, 0);
+        let (x1, ..) = dropdown_rect(1, 8, 0);
+        assert_eq!(x1, x0 + MENUS[0].title.len() * 8 + 16);
+    }
+}
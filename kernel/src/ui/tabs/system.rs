@@ -53,12 +53,12 @@ pub fn draw_system_tab(
         let pc  = fmt_u32(hw.cpu.physical_cores as u32, &mut bc);
         let lc  = fmt_u32(hw.cpu.logical_cores  as u32, &mut bl);
         c.write_at(pc,  rx + 6,                       ry, Color::PORTIX_GOLD);
-        c.write_at("C /", rx + 6 + pc.len() * 9,      ry, Color::GRAY);
-        c.write_at(lc,  rx + 6 + pc.len() * 9 + 28,   ry, Color::PORTIX_GOLD);
-        c.write_at("T",  rx + 6 + pc.len() * 9 + 28 + lc.len() * 9, ry, Color::GRAY);
+        c.write_at("C /", rx + 6 + lay.text_w(pc.len()),      ry, Color::GRAY);
+        c.write_at(lc,  rx + 6 + lay.text_w(pc.len()) + 28,   ry, Color::PORTIX_GOLD);
+        c.write_at("T",  rx + 6 + lay.text_w(pc.len()) + 28 + lay.text_w(lc.len()), ry, Color::GRAY);
         let freq = fmt_mhz(hw.cpu.max_mhz, &mut bf);
-        c.fill_rounded(rx + rw - freq.len() * 9 - 18, ry - 2, freq.len() * 9 + 14, 14, 3, Color::new(0, 25, 50));
-        c.write_at(freq, rx + rw - freq.len() * 9 - 11, ry, Color::CYAN);
+        c.fill_rounded(rx + rw - lay.text_w(freq.len()) - 18, ry - 2, lay.text_w(freq.len()) + 14, 14, 3, Color::new(0, 25, 50));
+        c.write_at(freq, rx + rw - lay.text_w(freq.len()) - 11, ry, Color::CYAN);
         ry += lay.line_h + 4;
     }
 
@@ -96,7 +96,7 @@ pub fn draw_system_tab(
         let mut be = [0u8; 16];
         c.write_at("E820:",    rx + 6,  ry, Color::GRAY);
         c.write_at(fmt_u32(hw.ram.entry_count as u32, &mut be), rx + 50, ry, Color::LIGHT_GRAY);
-        c.write_at("entradas", rx + 50 + 5 * 9, ry, Color::GRAY);
+        c.write_at("entradas", rx + 50 + lay.text_w(5), ry, Color::GRAY);
         ry += lay.line_h + 4;
     }
 
@@ -106,9 +106,12 @@ pub fn draw_system_tab(
         if ry + lay.line_h > lay.bottom_y.saturating_sub(50) { break; }
         let d = &hw.disks.drives[i];
         c.fill_rounded(rx + 6, ry - 1, 50, 13, 2, Color::new(4, 16, 36));
-        c.write_at(if d.bus == 0 { "ATA0" } else { "ATA1" }, rx + 8,  ry + 1, Color::TEAL);
-        c.write_at("-",                                        rx + 40, ry + 1, Color::GRAY);
-        c.write_at(if d.drive == 0 { "M" } else { "S" },      rx + 48, ry + 1, Color::TEAL);
+        let mut lb = [0u8; 8];
+        c.write_at(d.bus_label(&mut lb),                       rx + 8,  ry + 1, Color::TEAL);
+        if !d.ahci {
+            c.write_at("-",                                    rx + 40, ry + 1, Color::GRAY);
+            c.write_at(if d.drive == 0 { "M" } else { "S" },  rx + 48, ry + 1, Color::TEAL);
+        }
         c.write_at(if d.is_atapi { "OPT" } else { "HDD" },    rx + 64, ry,     Color::PORTIX_AMBER);
         let m = d.model_str(); let m = if m.len() > 22 { &m[..22] } else { m };
         c.write_at(m, rx + 94, ry, Color::WHITE);
@@ -135,7 +138,7 @@ pub fn draw_system_tab(
         let hs = fmt_u32(hw.display.height as u32, &mut bh);
         let bs = fmt_u32(hw.display.bpp    as u32, &mut bb);
         c.write_at(ws,    rx + 6,              ry, Color::WHITE);
-        c.write_at("x",   rx + 6 + ws.len() * 9, ry, Color::GRAY);
+        c.write_at("x",   rx + 6 + lay.text_w(ws.len()), ry, Color::GRAY);
         c.write_at(hs,    rx + 60,             ry, Color::WHITE);
         c.write_at("@",   rx + 108,            ry, Color::GRAY);
         c.write_at(bs,    rx + 122,            ry, Color::WHITE);
@@ -161,18 +164,18 @@ pub fn draw_system_tab(
         let se = fmt_u32(failed as u32, &mut be);
 
         c.write_at(sa,      rx + 6,                        ry, Color::NEON_GREEN);
-        c.write_at("alloc", rx + 6 + sa.len() * 9 + 4,    ry, Color::GRAY);
+        c.write_at("alloc", rx + 6 + lay.text_w(sa.len()) + 4,    ry, Color::GRAY);
         c.write_at(sf,      rx + 90,                       ry, Color::TEAL);
-        c.write_at("free",  rx + 90 + sf.len() * 9 + 4,   ry, Color::GRAY);
+        c.write_at("free",  rx + 90 + lay.text_w(sf.len()) + 4,   ry, Color::GRAY);
 
         if failed > 0 {
             c.fill_rounded(
-                rx + rw - se.len() * 9 - 46, ry - 2,
-                se.len() * 9 + 42, 14, 3,
+                rx + rw - lay.text_w(se.len()) - 46, ry - 2,
+                lay.text_w(se.len()) + 42, 14, 3,
                 Color::new(40, 0, 0),
             );
-            c.write_at("OOM:", rx + rw - se.len() * 9 - 42, ry, Color::new(200, 60, 60));
-            c.write_at(se,     rx + rw - se.len() * 9 - 14, ry, Color::new(255, 80, 80));
+            c.write_at("OOM:", rx + rw - lay.text_w(se.len()) - 42, ry, Color::new(200, 60, 60));
+            c.write_at(se,     rx + rw - lay.text_w(se.len()) - 14, ry, Color::new(255, 80, 80));
         }
         ry += lay.line_h + 2;
 
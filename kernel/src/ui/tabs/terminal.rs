@@ -1,14 +1,32 @@
 // ui/tabs/terminal.rs — Pestaña TERMINAL: historial, input, barra de scroll
+//
+// Modo presentación (`present-mode on`): historial e input con la fuente al
+// doble de tamaño. Todas las medidas de texto salen de terminal_layout(); la
+// barra de título, la scrollbar y sus avisos siguen a tamaño normal. Las
+// líneas más anchas que las columnas efectivas se parten en varias filas.
 
 use crate::graphics::driver::framebuffer::{Color, Console, Layout};
-use crate::console::terminal::{Terminal, LineColor, TERM_ROWS, SCROLL_STEP};
+use crate::console::terminal::{Terminal, TermLine, LineColor, TERM_ROWS, SCROLL_STEP};
 use crate::ui::SCROLLBAR_W;
+use crate::util::fmt::fmt_u32;
+
+/// Factor de la fuente en modo presentación.
+pub const PRESENT_SCALE: usize = 2;
+/// Filas visuales como máximo en el área de scroll (1080 px / 12 px = 90).
+const MAX_VIEW_ROWS: usize = 160;
+
+/// Layout con las medidas de texto del terminal: el normal o, en modo
+/// presentación, el escalado. Es el que esperan las funciones de geometría
+/// de este módulo.
+pub fn terminal_layout(lay: &Layout, term: &Terminal) -> Layout {
+    if term.present { lay.scaled(PRESENT_SCALE) } else { *lay }
+}
 
 /// Devuelve (hist_top, hist_h, input_y, max_lines) para la geometría del
 /// área de historial y la caja de input. Usado tanto aquí como en main para
 /// el manejo de eventos de ratón/teclado.
 pub fn terminal_hist_geometry(lay: &Layout) -> (usize, usize, usize, usize) {
-    let input_h  = 24usize;
+    let input_h  = lay.font_h + 16;
     let input_y  = lay.bottom_y.saturating_sub(input_h + 4);
     let hist_top = lay.content_y + 22;
     let hist_h   = input_y.saturating_sub(hist_top + 2);
@@ -16,11 +34,134 @@ pub fn terminal_hist_geometry(lay: &Layout) -> (usize, usize, usize, usize) {
     (hist_top, hist_h, input_y, max_lines)
 }
 
+/// Caracteres de historial que caben antes de la scrollbar.
+pub fn hist_max_chars(lay: &Layout) -> usize {
+    lay.fw.saturating_sub(SCROLLBAR_W).saturating_sub(lay.pad + 4) / lay.char_w + 1
+}
+
+/// Filas visuales del historial con scroll como (línea lógica, columna
+/// inicial). A tamaño normal es una fila por línea, como siempre; en modo
+/// presentación las líneas largas ocupan varias filas. Abajo del todo se
+/// ancla la última línea (la salida nueva siempre se ve); con scroll, la
+/// primera del rango.
+fn scroll_view(lay: &Layout, term: &Terminal, out: &mut [(usize, usize); MAX_VIEW_ROWS]) -> usize {
+    let (_, _, input_y, max_lines) = terminal_hist_geometry(lay);
+    let rows = (input_y.saturating_sub(scroll_rows_top(lay, term)) / lay.line_h).min(MAX_VIEW_ROWS);
+    let (start, count) = term.visible_range(max_lines);
+    let width = hist_max_chars(lay).max(1);
+    let wrap  = lay.text_scale > 1;
+    let segs  = |li: usize| if wrap { term.line_at(li).len.div_ceil(width).max(1) } else { 1 };
+
+    let mut n = 0;
+    if !wrap || !term.at_bottom() {
+        'fill: for li in start..start + count {
+            for k in 0..segs(li) {
+                if n == rows { break 'fill; }
+                out[n] = (li, k * width); n += 1;
+            }
+        }
+        return n;
+    }
+    // Desde abajo: se rellena al revés y se da la vuelta al final
+    'back: for li in (start..start + count).rev() {
+        for k in (0..segs(li)).rev() {
+            if n == rows { break 'back; }
+            out[n] = (li, k * width); n += 1;
+        }
+    }
+    out[..n].reverse();
+    n
+}
+
+/// Panel superior de `split` como (y inicial, filas de historial); el
+/// separador ocupa la fila siguiente. None si la vista no está dividida.
+pub fn terminal_split_pane(lay: &Layout, term: &Terminal) -> Option<(usize, usize)> {
+    let (hist_top, _, _, max_lines) = terminal_hist_geometry(lay);
+    let rows = term.split_rows(max_lines);
+    if rows == 0 { return None; }
+    Some((hist_top + term.fixed_rows() * lay.line_h, rows - 1))
+}
+
+/// Y de la primera fila del historial con scroll (bajo la cabecera fija y
+/// el panel superior de `split`).
+fn scroll_rows_top(lay: &Layout, term: &Terminal) -> usize {
+    let (hist_top, _, _, max_lines) = terminal_hist_geometry(lay);
+    hist_top + (term.fixed_rows() + term.split_rows(max_lines)) * lay.line_h
+}
+
+/// Ruta clickable del historial bajo el ratón, como (línea lógica, inicio,
+/// longitud). La comparten el render (hover), el clic y la forma del cursor.
+/// Cubre los dos paneles de `split`.
+pub fn terminal_link_hit(
+    lay: &Layout, term: &Terminal, mx: i32, my: i32,
+) -> Option<(usize, usize, usize)> {
+    if mx < 0 || my < 0 { return None; }
+    let (x, y) = (mx as usize, my as usize);
+    let (_, _, input_y, _) = terminal_hist_geometry(lay);
+    let text_x = lay.pad + 4;
+    if y >= input_y || x < text_x || x >= lay.fw.saturating_sub(SCROLLBAR_W) {
+        return None;
+    }
+    let (li, from) = match terminal_split_pane(lay, term) {
+        Some((up_top, up_rows)) if y >= up_top && y < up_top + up_rows * lay.line_h => {
+            let (start, count) = term.split_range(up_rows);
+            let row = (y - up_top) / lay.line_h;
+            if row >= count { return None; }
+            (start + row, 0)
+        }
+        _ => {
+            let rows_top = scroll_rows_top(lay, term);
+            if y < rows_top { return None; }
+            let row = (y - rows_top) / lay.line_h;
+            if rows_top + (row + 1) * lay.line_h > input_y { return None; }
+            let mut view = [(0usize, 0usize); MAX_VIEW_ROWS];
+            if row >= scroll_view(lay, term, &mut view) { return None; }
+            view[row]
+        }
+    };
+
+    let line = term.line_at(li);
+    let col  = from + (x - text_x) / lay.char_w;
+    if col >= line.len.min(from + hist_max_chars(lay)) { return None; }
+    line.link_at(col).map(|(s, l)| (li, s, l))
+}
+
+/// Una fila del historial en `ly`: la línea desde la columna `from` (más
+/// de 0 solo en las filas de continuación del modo presentación). `hover`
+/// es el enlace bajo el ratón si cae en esta línea.
+fn draw_hist_line(
+    c: &mut Console, lay: &Layout, line: &TermLine, from: usize, ly: usize,
+    hover: Option<(usize, usize)>,
+) {
+    let end = line.len.min(from + hist_max_chars(lay));
+    let from = from.min(end);
+    let s = core::str::from_utf8(&line.buf[from..end]).unwrap_or("");
+
+    let col = if line.color == LineColor::Prompt {
+        c.fill_rect(0, ly - 1, lay.fw, lay.line_h + 1, Color::new(5, 12, 22));
+        status_color(line.ok)
+    } else { line_color(line.color) };
+    c.write_at_scaled(s, lay.pad + 4, ly, col, lay.text_scale);
+
+    // Ruta bajo el ratón: resaltada y subrayada (solo el trozo de esta fila)
+    if let Some((ls, ll)) = hover {
+        let hs = ls.max(from);
+        let he = (ls + ll).min(end);
+        if hs < he {
+            let lx = lay.pad + 4 + lay.text_w(hs - from);
+            let t  = core::str::from_utf8(&line.buf[hs..he]).unwrap_or("");
+            c.write_at_scaled(t, lx, ly, Color::WHITE, lay.text_scale);
+            c.hline(lx, ly + lay.font_h, lay.text_w(he - hs), Color::TEAL);
+        }
+    }
+}
+
 pub fn draw_terminal_tab(
     c: &mut Console,
     lay: &Layout,
     term: &Terminal,
     sb_dragging: bool,
+    mouse: (i32, i32),
 ) {
     let cy  = lay.content_y;
     let ch  = lay.bottom_y.saturating_sub(cy);
@@ -35,11 +176,26 @@ pub fn draw_terminal_tab(
     c.fill_rect(pad,      cy + 4, 8, 8, Color::GREEN);
     c.fill_rect(pad + 14, cy + 4, 8, 8, Color::PORTIX_AMBER);
     c.fill_rect(pad + 28, cy + 4, 8, 8, Color::RED);
-    c.write_at("PORTIX TERMINAL v0.7", pad + 46, cy + 5, Color::PORTIX_AMBER);
+    let title_len = "PORTIX TERMINAL v".len() + crate::console::terminal::VERSION.len();
+    c.write_at("PORTIX TERMINAL v", pad + 46, cy + 5, Color::PORTIX_AMBER);
+    c.write_at(crate::console::terminal::VERSION, pad + 46 + lay.text_w(17), cy + 5, Color::PORTIX_AMBER);
+    if term.remote_active() {
+        let rx = pad + 46 + lay.text_w(title_len + 1);
+        c.fill_rounded(rx, cy + 2, 104, 14, 3, Color::new(0, 40, 20));
+        c.write_at("REMOTO COM1", rx + 4, cy + 5, Color::NEON_GREEN);
+    }
     c.write_at("Rueda/RePag=scroll  ESC=limpiar",
                fw.saturating_sub(280), cy + 5, Color::new(32, 48, 68));
 
-    let (hist_top, hist_h, input_y, max_lines) = terminal_hist_geometry(lay);
+    // Medidas de texto del historial/input (escaladas en modo presentación);
+    // `lay` queda para lo que siempre va a tamaño normal
+    let tl = &terminal_layout(lay, term);
+    let (hist_top, hist_h, input_y, max_lines) = terminal_hist_geometry(tl);
+    // Banner + líneas sticky (y el panel superior de `split`) ocupan el
+    // tope; el scroll solo recorre lo de debajo
+    let fixed_h    = term.fixed_rows() * tl.line_h;
+    let rows_top   = scroll_rows_top(tl, term);
+    let scroll_rows = term.scroll_rows(max_lines);
 
     // Borde izquierdo decorativo
     for y in (hist_top..input_y).step_by(2) {
@@ -49,7 +205,7 @@ pub fn draw_terminal_tab(
     // ── Scrollbar ─────────────────────────────────────────────────────────
     let sb_x = fw.saturating_sub(SCROLLBAR_W);
 
-    if term.line_count > max_lines {
+    if term.line_count > scroll_rows {
         c.fill_rect(sb_x, hist_top, SCROLLBAR_W, hist_h, Color::new(4, 10, 20));
 
         let max_scroll = term.max_scroll(max_lines);
@@ -57,7 +213,7 @@ pub fn draw_terminal_tab(
             if term.line_count > TERM_ROWS { term.line_count - TERM_ROWS } else { 0 }
         );
         let thumb_h  = if available == 0 { hist_h }
-                       else { (hist_h * max_lines / available).max(10).min(hist_h) };
+                       else { (hist_h * scroll_rows / available).max(10).min(hist_h) };
         let travel   = hist_h.saturating_sub(thumb_h);
         let thumb_top = if max_scroll == 0 {
             hist_top + travel
@@ -76,58 +232,139 @@ pub fn draw_terminal_tab(
 
         if !term.at_bottom() {
             let bx = sb_x.saturating_sub(82);
-            c.fill_rounded(bx, hist_top + 4, 78, 14, 3, Color::new(20, 40, 0));
-            c.write_at("arrib SCROLL", bx + 4, hist_top + 6, Color::PORTIX_GOLD);
+            c.fill_rounded(bx, rows_top + 4, 78, 14, 3, Color::new(20, 40, 0));
+            c.write_at("arrib SCROLL", bx + 4, rows_top + 6, Color::PORTIX_GOLD);
+            if term.unseen > 0 {
+                let mut nb = [0u8; 16];
+                let n = fmt_u32(term.unseen.min(99_999) as u32, &mut nb);
+                let nw = 8 + lay.text_w(n.len() + 8);
+                let nx = sb_x.saturating_sub(nw + 4);
+                c.fill_rounded(nx, rows_top + 20, nw, 14, 3, Color::new(0, 30, 40));
+                c.write_at("+", nx + 4, rows_top + 22, Color::TEAL);
+                c.write_at(n, nx + 13, rows_top + 22, Color::TEAL);
+                c.write_at("nuevas", nx + 22 + lay.text_w(n.len()), rows_top + 22, Color::TEAL);
+            }
         }
     } else {
         c.fill_rect(sb_x, hist_top, SCROLLBAR_W, hist_h, Color::new(2, 6, 12));
     }
 
-    // ── Historial visible ─────────────────────────────────────────────────
-    let (start, count)  = term.visible_range(max_lines);
-    let text_area_w     = sb_x.saturating_sub(pad + 4);
+    // ── Cabecera fija: banner + líneas sticky (fuera del scroll) ─────────
+    let max_chars = hist_max_chars(tl);
 
-    for i in 0..count {
-        let line = term.line_at(start + i);
-        if line.len == 0 { continue; }
-        let ly = hist_top + i * lay.line_h;
-        if ly + lay.line_h > input_y { break; }
-
-        let col = match line.color {
-            LineColor::Success => Color::NEON_GREEN,
-            LineColor::Warning => Color::PORTIX_AMBER,
-            LineColor::Error   => Color::RED,
-            LineColor::Info    => Color::CYAN,
-            LineColor::Prompt  => Color::PORTIX_GOLD,
-            LineColor::Header  => Color::WHITE,
-            LineColor::Normal  => Color::LIGHT_GRAY,
-        };
+    if term.banner_count > 0 {
+        let bh = term.banner_count * tl.line_h;
+        c.fill_rect(0, hist_top - 1, sb_x, bh + 1, Color::new(3, 10, 20));
+        for (i, line) in term.banner[..term.banner_count].iter().enumerate() {
+            let ly = hist_top + i * tl.line_h;
+            let s  = core::str::from_utf8(&line.buf[..line.len.min(max_chars)]).unwrap_or("");
+            c.write_at_scaled(s, pad + 4, ly, line_color(line.color), tl.text_scale);
+        }
+    }
+    if term.sticky_count > 0 {
+        let sy = hist_top + term.banner_count * tl.line_h;
+        c.fill_rect(0, sy - 1, sb_x, term.sticky_count * tl.line_h + 1, Color::new(6, 16, 30));
+        for (i, line) in term.sticky[..term.sticky_count].iter().enumerate() {
+            let ly = sy + i * tl.line_h;
+            let s  = core::str::from_utf8(&line.buf[..line.len.min(max_chars)]).unwrap_or("");
+            c.fill_rect(pad - 4, ly, 2, tl.line_h - 2, Color::PORTIX_GOLD);
+            c.write_at_scaled(s, pad + 4, ly, line_color(line.color), tl.text_scale);
+        }
+    }
+    if fixed_h > 0 {
+        c.hline(0, hist_top + fixed_h - 1, sb_x, Color::new(16, 32, 60));
+    }
+
+    let hover = terminal_link_hit(tl, term, mouse.0, mouse.1);
+    let hover_on = |li: usize| hover.filter(|h| h.0 == li).map(|h| (h.1, h.2));
 
-        let s = core::str::from_utf8(&line.buf[..line.len.min(text_area_w / 9 + 1)])
-            .unwrap_or("");
+    // ── Panel superior congelado (`split`) ───────────────────────────────
+    if let Some((up_top, up_rows)) = terminal_split_pane(tl, term) {
+        let (start, count) = term.split_range(up_rows);
+        c.fill_rect(0, up_top - 1, sb_x, up_rows * tl.line_h + 1, Color::new(2, 7, 14));
+        for i in 0..count {
+            let line = term.line_at(start + i);
+            if line.len == 0 { continue; }
+            draw_hist_line(c, tl, line, 0, up_top + i * tl.line_h, hover_on(start + i));
+        }
 
-        if line.color == LineColor::Prompt {
-            c.fill_rect(0, ly - 1, fw, lay.line_h + 1, Color::new(5, 12, 22));
+        // Separador: posición del panel y cómo moverlo/cerrarlo
+        let dy = up_top + up_rows * tl.line_h;
+        c.fill_rect(0, dy - 1, sb_x, tl.line_h, Color::new(10, 24, 44));
+        c.hline(0, dy - 1, sb_x, Color::PORTIX_AMBER);
+        let mut x = pad + 4;
+        c.write_at("CONGELADO", x, dy + 1, Color::PORTIX_AMBER);
+        x += lay.text_w(11);
+        let mut nb = [0u8; 16];
+        for (val, sep) in [(start + 1, "-"), (start + count, "/"), (term.line_count, "")] {
+            let n = fmt_u32(val.min(999_999) as u32, &mut nb);
+            c.write_at(n, x, dy + 1, Color::LIGHT_GRAY);
+            x += lay.text_w(n.len());
+            c.write_at(sep, x, dy + 1, Color::LIGHT_GRAY);
+            x += lay.text_w(sep.len());
         }
-        c.write_at(s, pad + 4, ly, col);
+        c.write_at("Rueda/Ctrl+RePag=scroll  split off=cerrar",
+                   sb_x.saturating_sub(lay.text_w(43)), dy + 1, Color::new(60, 80, 110));
+    }
+
+    // ── Historial visible ─────────────────────────────────────────────────
+    let mut view = [(0usize, 0usize); MAX_VIEW_ROWS];
+    let rows = scroll_view(tl, term, &mut view);
+
+    for (i, &(li, from)) in view[..rows].iter().enumerate() {
+        let line = term.line_at(li);
+        let ly = rows_top + i * tl.line_h;
+        if ly + tl.line_h > input_y { break; }
+        // Separador de `clear --keep` sobre la primera línea tras la marca
+        if i > 0 && from == 0 && li == term.clear_mark {
+            c.hline(pad, ly, sb_x.saturating_sub(pad * 2), Color::new(40, 70, 110));
+        }
+        if line.len == 0 { continue; }
+        draw_hist_line(c, tl, line, from, ly, hover_on(li));
     }
 
     // ── Línea de input ────────────────────────────────────────────────────
+    let input_h = tl.font_h + 16;
     c.fill_rect(0, input_y - 2, fw, 2,  Color::new(12, 28, 52));
-    c.fill_rect(0, input_y,     fw, 24, Color::new(2, 10, 22));
+    c.fill_rect(0, input_y,     fw, input_h, Color::new(2, 10, 22));
 
     let prompt = "PORTIX> ";
-    c.write_at(prompt, pad, input_y + 8, Color::PORTIX_GOLD);
+    c.write_at_scaled(prompt, pad, input_y + 8, status_color(term.last_ok), tl.text_scale);
 
-    let ix         = pad + prompt.len() * 9;
-    let input_str  = core::str::from_utf8(&term.input[..term.input_len]).unwrap_or("");
-    c.write_at(input_str, ix, input_y + 8, Color::WHITE);
+    // Con la fuente grande la línea puede no caber: se ve el final
+    let ix         = pad + tl.text_w(prompt.len());
+    let room       = sb_x.saturating_sub(ix + tl.char_w) / tl.char_w;
+    let skip       = term.input_len.saturating_sub(room);
+    let input_str  = core::str::from_utf8(&term.input[skip..term.input_len]).unwrap_or("");
+    c.write_at_scaled(input_str, ix, input_y + 8, Color::WHITE, tl.text_scale);
 
-    let cur_x = ix + term.input_len * 9;
-    if term.cursor_vis && cur_x + 7 < sb_x {
-        c.fill_rect(cur_x, input_y + 6, 7, 13, Color::PORTIX_GOLD);
+    let cur_x = ix + tl.text_w(term.input_len - skip);
+    if term.cursor_vis && cur_x + tl.font_w - 1 < sb_x {
+        c.fill_rect(cur_x, input_y + 6, tl.font_w - 1, tl.font_h + 5, Color::PORTIX_GOLD);
     }
 
     // Evitar warnings de importaciones no usadas en algunas configuraciones
     let _ = SCROLL_STEP;
 }
+
+fn line_color(lc: LineColor) -> Color {
+    match lc {
+        LineColor::Success => Color::NEON_GREEN,
+        LineColor::Warning => Color::PORTIX_AMBER,
+        LineColor::Error   => Color::RED,
+        LineColor::Info    => Color::CYAN,
+        LineColor::Prompt  => Color::PORTIX_GOLD,
+        LineColor::Header  => Color::WHITE,
+        LineColor::Normal  => Color::LIGHT_GRAY,
+    }
+}
+
+/// Color del prompt/eco según el resultado del comando: verde si terminó
+/// bien, rojo si falló y dorado (neutro) si aún no hay resultado.
+fn status_color(ok: Option<bool>) -> Color {
+    match ok {
+        Some(true)  => Color::NEON_GREEN,
+        Some(false) => Color::RED,
+        None        => Color::PORTIX_GOLD,
+    }
+}
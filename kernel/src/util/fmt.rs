@@ -53,7 +53,14 @@ pub fn fmt_mhz<'a>(mhz: u32, buf: &'a mut [u8; 24]) -> &'a str {
 pub fn fmt_mib<'a>(mb: u64, buf: &'a mut [u8; 24]) -> &'a str {
     if mb == 0 { buf[0] = b'0'; buf[1] = b'B'; return core::str::from_utf8(&buf[..2]).unwrap_or("0"); }
     let mut pos = 0usize;
-    if mb >= 1024 {
+    if mb >= 1024 * 1024 {
+        let ti = mb / (1024 * 1024); let tf = (mb % (1024 * 1024)) * 10 / (1024 * 1024);
+        let mut t = [0u8; 20]; let s = fmt_u64(ti, &mut t);
+        for b in s.bytes() { if pos < 24 { buf[pos] = b; pos += 1; } }
+        if pos < 24 { buf[pos] = b'.'; pos += 1; }
+        if pos < 24 { buf[pos] = b'0' + tf as u8; pos += 1; }
+        for b in b" TB" { if pos < 24 { buf[pos] = *b; pos += 1; } }
+    } else if mb >= 1024 {
         let gi = mb / 1024; let gf = (mb % 1024) * 10 / 1024;
         let mut t = [0u8; 20]; let s = fmt_u64(gi, &mut t);
         for b in s.bytes() { if pos < 24 { buf[pos] = b; pos += 1; } }
@@ -67,6 +67,7 @@ BOOTBIN    = BUILD / "boot.bin"
 STAGE2BIN  = BUILD / "stage2.bin"
 KERNELBIN  = BUILD / "kernel.bin"
 ISROBJ     = BUILD / "isr.o"
+KSYMSBIN   = BUILD / "ksyms.bin"
 
 ISO_IMG    = DIST / "portix.iso"
 VDI_IMG    = DIST / "portix.vdi"
@@ -107,6 +108,7 @@ TARGET_JSON_CONTENT = """{
   "linker": "rust-lld",
   "panic-strategy": "abort",
   "disable-redzone": true,
+  "frame-pointer": "always",
   "features": "-mmx,-sse,+soft-float",
   "pre-link-args": {
     "ld.lld": ["-Tlinker.ld", "-n", "--gc-sections"]
@@ -257,6 +259,7 @@ def build_kernel() -> int:
             log("[ERROR] ELF no encontrado"); sys.exit(1)
         elf = cands[0]
         log(f"[WARN]  ELF alternativo: {elf}")
+    embed_symbols(elf)
     run([_OBJCOPY, "-I", "elf64-x86-64", "-O", "binary",
          "--strip-all", str(elf), str(KERNELBIN)])
     sects = sectors_of(KERNELBIN)
@@ -264,6 +267,77 @@ def build_kernel() -> int:
     return sects
 
 
+# ── Tabla de símbolos (.ksyms, ver kernel/src/arch/symbols.rs) ──────────────
+KSYMS_NAME_MAX = 60
+
+
+def _ksym_name(raw: str) -> str:
+    """Nombre demangled sin el hash `::h0123…` ni genéricos largos."""
+    if len(raw) > 19 and raw[-19:-16] == "::h":
+        raw = raw[:-19]
+    return raw[-KSYMS_NAME_MAX:]
+
+
+def build_ksyms(syms: list, capacity: int) -> tuple[bytes, int]:
+    """Serializa [(addr, size, name)] al formato KSYM. Si no cabe, descarta
+    primero símbolos de core/alloc/compiler_builtins y luego los más pequeños;
+    el tamaño guardado evita que sus direcciones se atribuyan al vecino."""
+    syms = sorted(syms)
+    def size_of(n_syms, names):
+        return 16 + 16 * n_syms + sum(len(n) for n in names)
+    def ext(s):
+        return s[2].startswith(("core::", "alloc::", "compiler_builtins::", "<core::", "<alloc::"))
+    while syms and size_of(len(syms), {s[2] for s in syms}) > capacity:
+        drop = [s for s in syms if ext(s)] or syms
+        victim = min(drop, key=lambda s: s[1])
+        syms.remove(victim)
+    names, strtab = {}, bytearray()
+    for _, _, n in syms:
+        if n not in names:
+            names[n] = len(strtab)
+            strtab += n.encode()
+    out = bytearray(b"KSYM")
+    out += struct.pack("<III", len(syms), 16 + 16 * len(syms), 0)
+    for addr, size, n in syms:
+        out += struct.pack("<QIHBB", addr, min(size, 0xFFFFFFFF), names[n], len(n.encode()), 0)
+    out += strtab
+    out += bytes(capacity - len(out))
+    return bytes(out), len(syms)
+
+
+def embed_symbols(elf: Path):
+    """Rellena .ksyms del ELF con las funciones de `nm` (sin reenlazar)."""
+    nm = find_tool("nm", "llvm-nm")
+    if not nm:
+        log("[WARN]  nm no encontrado — panic sin nombres de símbolo")
+        return
+    r = subprocess.run([nm, "-C", "-n", "-S", "--defined-only", str(elf)],
+                       capture_output=True, text=True)
+    if r.returncode != 0:
+        log("[WARN]  nm falló — panic sin nombres de símbolo")
+        return
+    syms, bounds = [], {}
+    for line in r.stdout.splitlines():
+        parts = line.split(maxsplit=3)
+        if len(parts) == 3:
+            parts = [parts[0], "0", parts[1], parts[2]]
+        if len(parts) != 4:
+            continue
+        addr, size, kind, name = parts
+        if name in ("__ksyms_start", "__ksyms_end"):
+            bounds[name] = int(addr, 16)
+        elif kind in "Tt":
+            syms.append((int(addr, 16), int(size, 16), _ksym_name(name)))
+    if len(bounds) != 2:
+        log("[WARN]  .ksyms no encontrada en el ELF — panic sin nombres de símbolo")
+        return
+    cap = bounds["__ksyms_end"] - bounds["__ksyms_start"]
+    blob, n = build_ksyms(syms, cap)
+    KSYMSBIN.write_bytes(blob)
+    run([_OBJCOPY, "--update-section", f".ksyms={KSYMSBIN}", str(elf)])
+    log(f"[OK]    .ksyms — {n}/{len(syms)} símbolos en {cap // 1024} KiB")
+
+
 def assemble_stage2(kernel_sectors: int):
     step(f"ENSAMBLANDO STAGE2 (KERNEL_SECTORS={kernel_sectors} KERNEL_LBA={KERNEL_LBA_START})")
     run(["nasm", "-f", "bin",